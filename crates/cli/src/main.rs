@@ -1,15 +1,46 @@
 //! Glottisdale CLI — syllable-level audio collage, speak, and sing.
 
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result, bail};
-use clap::{Parser, Subcommand};
-
-use glottisdale_core::audio::io::{extract_audio, read_wav};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
+use rayon::prelude::*;
+
+use glottisdale_core::audio::analysis::retain_quality_syllables;
+use glottisdale_core::audio::capture::{record_for, save_recording};
+use glottisdale_core::audio::device::BufferSize;
+use glottisdale_core::audio::effects::{DenoiseLevel, ReverbIr, RmsNormalization, RmsTarget, VoiceFx};
+use glottisdale_core::audio::encode::{convert_wav, OutputFormat};
+use glottisdale_core::audio::loudness::{Mastering, DEFAULT_TARGET_LUFS};
+use glottisdale_core::audio::io::{
+    denoise_wav, extract_audio, load_stereo_sources, read_wav, trim_wav, SourceRange,
+};
+use glottisdale_core::collage::process::SourceWeight;
 use glottisdale_core::collage::stretch::{StretchConfig, parse_stretch_factor};
-use glottisdale_core::language::align::get_aligner;
-use glottisdale_core::names::create_run_dir;
+use glottisdale_core::collage::tempo::bpm_from_taps;
+use glottisdale_core::editor::render::{export_arrangement, RenderSettings};
+use glottisdale_core::editor::timeline_image::{self, export_timeline_image};
+use glottisdale_core::editor::{Arrangement, MissingSources};
+use glottisdale_core::language::align::{
+    aligners_for_inputs, get_aligner, Aligner, AlignerOverride, DEFAULT_VAD_CHUNK_S,
+};
+use glottisdale_core::language::g2p::Language;
+use glottisdale_core::names::{create_run_dir_with, seed_from_name, write_seed_record, NameTheme};
+use glottisdale_core::profile;
+use glottisdale_core::progress::{estimate_remaining, ProgressReporter, Stage};
+use glottisdale_core::config::Config;
+use glottisdale_core::hooks::{check_placeholders, run_hook, RunOutputs};
+use glottisdale_core::webhook::{length_line, post_run, RunPost, WebhookConfig};
+use glottisdale_core::run_diff::{diff_runs, write_config_record, RunRecord};
+use glottisdale_core::source::Relink;
+use glottisdale_core::temp::clean_temp_dirs;
+use glottisdale_core::speak::matcher::DEFAULT_BEAM_WIDTH;
+use glottisdale_core::types::AlignmentResult;
 
 // ─── Top-level CLI ───────────────────────────────────────────────
 
@@ -29,9 +60,65 @@ enum Command {
     /// Syllable-level audio collage
     Collage(Box<CollageArgs>),
     /// Map syllables to MIDI melody ("drunk choir")
-    Sing(SingArgs),
+    Sing(Box<SingArgs>),
     /// Reconstruct text using source audio syllables
-    Speak(SpeakArgs),
+    Speak(Box<SpeakArgs>),
+    /// Render an editor project to audio without the GUI
+    Render(RenderArgs),
+    /// Draw an editor project's timeline to a PNG or SVG image
+    TimelineImage(TimelineImageArgs),
+    /// Record from the microphone, optionally running a pipeline on it
+    Record(RecordArgs),
+    /// Compare two runs' seeds, options, and clip choices
+    Diff(DiffArgs),
+    /// Remove scratch files left by earlier runs
+    Clean(CleanArgs),
+}
+
+impl Cli {
+    /// Parse `argv`, keeping each pipeline's options (defaults included)
+    /// for its run's config record.
+    fn parse_recording_options<I, T>(argv: I) -> std::result::Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<std::ffi::OsString> + Clone,
+    {
+        let mut command = Cli::command();
+        let matches = command.try_get_matches_from_mut(argv)?;
+        let mut cli = Cli::from_arg_matches(&matches)?;
+        let shared = match &mut cli.command {
+            Command::Collage(a) => &mut a.shared,
+            Command::Sing(a) => &mut a.shared,
+            Command::Speak(a) => &mut a.shared,
+            _ => return Ok(cli),
+        };
+        if let Some((name, sub_matches)) = matches.subcommand() {
+            if let Some(sub) = command.find_subcommand(name) {
+                shared.command = name.to_string();
+                shared.options = recorded_options(sub, sub_matches);
+            }
+        }
+        Ok(cli)
+    }
+}
+
+/// Every option of `command` that has a value in `matches`, keyed by
+/// flag (or by name for positional arguments).
+fn recorded_options(command: &clap::Command, matches: &clap::ArgMatches) -> BTreeMap<String, String> {
+    command
+        .get_arguments()
+        .filter_map(|arg| {
+            let id = arg.get_id().as_str();
+            let values: Vec<String> = matches
+                .try_get_raw(id)
+                .ok()
+                .flatten()?
+                .map(|v| v.to_string_lossy().to_string())
+                .collect();
+            let key = arg.get_long().map_or_else(|| id.to_string(), |long| format!("--{}", long));
+            Some((key, values.join(", ")))
+        })
+        .collect()
 }
 
 // ─── Shared arguments (embedded in each subcommand) ──────────────
@@ -63,6 +150,10 @@ struct SharedArgs {
     #[arg(long)]
     seed: Option<u64>,
 
+    /// Derive the RNG seed from a memorable name like "breathy-bassoon"
+    #[arg(long, value_name = "NAME", conflicts_with = "seed")]
+    seed_from_name: Option<String>,
+
     /// Show verbose output
     #[arg(short, long, default_value_t = true)]
     verbose: bool,
@@ -74,6 +165,229 @@ struct SharedArgs {
     /// Custom run name (default: auto-generated)
     #[arg(long)]
     run_name: Option<String>,
+
+    /// Word lists for generated run names: voice, birds, weather, a user
+    /// theme in ~/.config/glottisdale/names/, or a directory holding
+    /// adjectives.txt and nouns.txt
+    #[arg(long, default_value = "voice")]
+    name_theme: String,
+
+    /// Language spoken in the sources: en, es, or de (sets Whisper's
+    /// language and the G2P rules used for syllables)
+    #[arg(long, default_value = "en", value_parser = ["en", "es", "de"])]
+    language: String,
+
+    /// Alignment inference device ("auto" uses the GPU when available)
+    #[arg(long, visible_alias = "bfa-device", default_value = "auto", value_parser = ["auto", "cpu", "cuda"])]
+    device: String,
+
+    /// Only use part of a source, in seconds: "talk.wav=120-300" (repeatable)
+    #[arg(long = "source-range", value_name = "FILE=START-END", value_parser = parse_source_range)]
+    source_ranges: Vec<SourceRange>,
+
+    /// Reduce background hiss in each source before alignment, using a
+    /// noise profile learned from its room tone: light (the default with no
+    /// level), or aggressive, which also filters out hum and rumble and
+    /// gates harder
+    #[arg(long, value_name = "LEVEL", num_args = 0..=1, default_missing_value = "light", value_parser = parse_denoise)]
+    denoise: Option<DenoiseLevel>,
+
+    /// Align only the detected speech in each source, skipping silence
+    #[arg(long)]
+    vad: bool,
+
+    /// With --vad, the longest stretch of speech aligned in one pass (seconds)
+    #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_VAD_CHUNK_S)]
+    vad_max_chunk: f64,
+
+    /// Drop aligned syllables whose audio quality score (0-1) is below
+    /// this: silent, clipped, pitch-jumping, or implausibly long or short
+    #[arg(long, value_name = "SCORE", default_value_t = 0.0, value_parser = parse_min_quality)]
+    min_quality: f64,
+
+    /// Aligner for one input: "studio.wav=bfa" or "phone.m4a=default:small" (repeatable)
+    #[arg(long = "input-aligner", value_name = "FILE=ALIGNER[:MODEL]", value_parser = parse_aligner_override)]
+    input_aligners: Vec<AlignerOverride>,
+
+    /// Format for the final audio: "wav", "flac", or, in builds with the
+    /// `ogg` and `mp3` features, "ogg" or "mp3"
+    #[arg(long, default_value = "wav", value_parser = parse_output_format)]
+    output_format: OutputFormat,
+
+    /// Integrated loudness to normalize the final audio to (LUFS)
+    #[arg(long, default_value_t = DEFAULT_TARGET_LUFS, allow_negative_numbers = true)]
+    target_lufs: f64,
+
+    /// Measure the final audio's loudness but leave it as rendered
+    #[arg(long)]
+    no_loudness_normalize: bool,
+
+    /// Write the final mix without the peak limiter (may hard-clip)
+    #[arg(long)]
+    no_limiter: bool,
+
+    /// Convolution reverb on the final audio: "room", "hall", or "spring"
+    #[arg(long, value_name = "IR", value_parser = parse_reverb)]
+    reverb: Option<ReverbIr>,
+
+    /// Wet/dry balance of --reverb (0 = dry, 1 = reverb only)
+    #[arg(long, default_value_t = 0.2)]
+    reverb_mix: f64,
+
+    /// Worker threads for alignment and clip cutting (0 = one per core)
+    #[arg(long, short = 'j')]
+    jobs: Option<usize>,
+
+    /// Shell command to run after a successful run, e.g. to upload the
+    /// result. Placeholders: {output}, {outputs}, {run_dir}, {run_name}.
+    /// Overrides on_complete in the config file; "" runs nothing
+    #[arg(long, value_name = "CMD", value_parser = parse_hook)]
+    on_complete: Option<String>,
+
+    /// Discord or Slack incoming webhook URL to post the finished run to.
+    /// Overrides url under [webhook] in the config file; "" posts nothing
+    #[arg(long, value_name = "URL")]
+    webhook: Option<String>,
+
+    /// Time each pipeline stage and DSP call, print a summary, and save it
+    /// as profile.txt in the run directory
+    #[arg(long)]
+    profile: bool,
+
+    /// With --profile, also save a Chrome trace (profile.json) of every
+    /// timed call, for chrome://tracing or Perfetto
+    #[arg(long, requires = "profile")]
+    profile_trace: bool,
+
+    /// Subcommand that parsed these arguments, for the run's config record.
+    #[arg(skip)]
+    command: String,
+
+    /// Options the run was started with, for its config record.
+    #[arg(skip)]
+    options: BTreeMap<String, String>,
+}
+
+impl SharedArgs {
+    /// RNG seed from `--seed` or `--seed-from-name`.
+    fn rng_seed(&self) -> Option<u64> {
+        self.seed.or_else(|| self.seed_from_name.as_deref().map(seed_from_name))
+    }
+
+    /// Print the seed and save it (with its name, if any) in the run
+    /// directory, along with the options the run started with.
+    fn record_seed(&self, run_dir: &Path) -> Result<()> {
+        if !self.command.is_empty() {
+            write_config_record(run_dir, &self.command, &self.options)?;
+        }
+        let Some(seed) = self.rng_seed() else {
+            return Ok(());
+        };
+        match &self.seed_from_name {
+            Some(name) => println!("Seed: {} (from name \"{}\")", seed, name),
+            None => println!("Seed: {}", seed),
+        }
+        write_seed_record(run_dir, seed, self.seed_from_name.as_deref())
+    }
+
+    /// What to do after the run: `--on-complete` and `--webhook`, or else
+    /// the config file's. Resolved before the run so a bad config fails early.
+    fn after_run(&self) -> Result<AfterRun> {
+        if self.profile {
+            profile::enable();
+        }
+        let config = Config::load()?;
+        let on_complete = self.on_complete.clone().or(config.hooks.on_complete);
+        let webhook = WebhookConfig { url: self.webhook.clone().or(config.webhook.url), ..config.webhook };
+        Ok(AfterRun {
+            on_complete: on_complete.filter(|c| !c.trim().is_empty()),
+            webhook: webhook.url.as_deref().is_some_and(|u| !u.trim().is_empty()).then_some(webhook),
+            profile_trace: self.profile_trace,
+        })
+    }
+
+    /// `--reverb` and loudness normalization, for the pipeline to give
+    /// its final audio.
+    fn mastering(&self) -> Mastering {
+        Mastering {
+            reverb: self.reverb.map(|ir| (ir, self.reverb_mix)),
+            target_lufs: (!self.no_loudness_normalize).then_some(self.target_lufs),
+        }
+    }
+
+    /// One aligner per input, applying any `--input-aligner` overrides
+    /// and, with `--vad`, skipping silence.
+    fn aligners(&self, default_name: &str) -> Result<Vec<Box<dyn Aligner>>> {
+        aligners_for_inputs(
+            &self.input_files,
+            &self.input_aligners,
+            default_name,
+            &self.whisper_model,
+            &self.language,
+            &self.device,
+            self.vad.then_some(self.vad_max_chunk),
+        )
+    }
+
+    /// Encode a finished WAV in `--output-format`.
+    fn finish_output(&self, wav: &Path) -> Result<PathBuf> {
+        convert_wav(wav, self.output_format)
+    }
+}
+
+fn parse_source_range(s: &str) -> std::result::Result<SourceRange, String> {
+    SourceRange::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_aligner_override(s: &str) -> std::result::Result<AlignerOverride, String> {
+    AlignerOverride::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_source_weight(s: &str) -> std::result::Result<SourceWeight, String> {
+    SourceWeight::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_output_format(s: &str) -> std::result::Result<OutputFormat, String> {
+    OutputFormat::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_voice_fx(s: &str) -> std::result::Result<VoiceFx, String> {
+    VoiceFx::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_rms_target(s: &str) -> std::result::Result<RmsTarget, String> {
+    RmsTarget::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_denoise(s: &str) -> std::result::Result<DenoiseLevel, String> {
+    DenoiseLevel::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_reverb(s: &str) -> std::result::Result<ReverbIr, String> {
+    ReverbIr::parse(s).map_err(|e| e.to_string())
+}
+
+fn parse_hook(s: &str) -> std::result::Result<String, String> {
+    check_placeholders(s).map(|()| s.to_string()).map_err(|e| e.to_string())
+}
+
+fn parse_relink(s: &str) -> std::result::Result<Relink, String> {
+    Relink::parse(s).map_err(|e| e.to_string())
+}
+
+/// Load an editor project, applying `--relink` to sources that moved.
+fn load_project(path: &Path, relinks: &[Relink]) -> Result<Arrangement> {
+    Arrangement::load_project_relinked(path, relinks).map_err(|e| match e.downcast_ref::<MissingSources>() {
+        Some(missing) => anyhow::anyhow!("{}\nPoint moved sources at their new location with --relink OLD=NEW", missing),
+        None => e,
+    })
+}
+
+fn parse_min_quality(s: &str) -> std::result::Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(score) if (0.0..=1.0).contains(&score) => Ok(score),
+        _ => Err(format!("expected a score from 0 to 1, got '{}'", s)),
+    }
 }
 
 // ─── Collage ─────────────────────────────────────────────────────
@@ -85,10 +399,20 @@ struct CollageArgs {
     #[command(flatten)]
     shared: SharedArgs,
 
-    /// Collage mode: "random" (default) or "shuffle" (template-based)
-    #[arg(long, default_value = "random", value_parser = ["random", "shuffle"])]
+    /// Collage mode: "random" (default), "shuffle" (template-based), or
+    /// "phrase" (whole source phrases, cut-up style)
+    #[arg(long, default_value = "random", value_parser = ["random", "shuffle", "phrase"])]
     mode: String,
 
+    /// Phrase mode: minimum silence between words that ends a phrase (ms)
+    #[arg(long, default_value_t = 300.0)]
+    phrase_min_pause: f64,
+
+    /// Random mode: keep a rough preview.wav of the first N seconds in
+    /// the run directory while assembling, to listen before it finishes
+    #[arg(long, value_name = "SECONDS", default_value_t = 0.0)]
+    preview: f64,
+
     // -- Prosodic grouping --
     /// Syllables per word: "3" or "1-4"
     #[arg(long, default_value = "1-4")]
@@ -122,14 +446,27 @@ struct CollageArgs {
     #[arg(long, default_value_t = 50.0)]
     word_crossfade: f64,
 
+    // -- Tempo --
+    /// Place words on a beat grid at this tempo instead of random pauses
+    #[arg(long, conflicts_with = "tap_tempo")]
+    bpm: Option<f64>,
+
+    /// Set the beat grid tempo by tapping Enter on the beat
+    #[arg(long)]
+    tap_tempo: bool,
+
+    /// Beat grid steps per beat (1 = quarters, 2 = eighths, 4 = sixteenths)
+    #[arg(long, default_value_t = 2)]
+    subdivision: usize,
+
+    /// Delay of off-beat grid steps as a fraction of a step (0-0.5)
+    #[arg(long, default_value_t = 0.0)]
+    swing: f64,
+
     /// Alignment backend
     #[arg(long, default_value = "auto", value_parser = ["auto", "default", "bfa"])]
     aligner: String,
 
-    /// BFA inference device
-    #[arg(long, default_value = "cpu", value_parser = ["cpu", "cuda"])]
-    bfa_device: String,
-
     // -- Audio polish --
     /// Pink noise bed level in dB (0 to disable)
     #[arg(long, default_value_t = -40.0, allow_hyphen_values = true)]
@@ -175,6 +512,20 @@ struct CollageArgs {
     #[arg(long, overrides_with = "volume_normalize")]
     no_volume_normalize: bool,
 
+    /// Level volume normalization aims for: median or mean of the clips'
+    /// RMS, or a fixed RMS level in dBFS such as -20
+    #[arg(long, value_name = "TARGET", default_value = "median", value_parser = parse_rms_target, allow_hyphen_values = true)]
+    volume_target: RmsTarget,
+
+    /// Most volume normalization turns a clip up (dB); lower it to keep
+    /// whispered sources from being boosted into noise
+    #[arg(long, value_name = "DB", default_value_t = 20.0)]
+    volume_max_boost: f64,
+
+    /// Most volume normalization turns a clip down (dB)
+    #[arg(long, value_name = "DB", default_value_t = 20.0)]
+    volume_max_cut: f64,
+
     /// Apply phrase-level volume envelope [use --no-prosodic-dynamics to disable]
     #[arg(long, default_value_t = true)]
     prosodic_dynamics: bool,
@@ -183,6 +534,22 @@ struct CollageArgs {
     #[arg(long, overrides_with = "prosodic_dynamics")]
     no_prosodic_dynamics: bool,
 
+    /// Let pitch fall across each sentence [use --no-intonation to disable]
+    #[arg(long, default_value_t = true)]
+    intonation: bool,
+
+    /// Disable sentence intonation
+    #[arg(long, overrides_with = "intonation")]
+    no_intonation: bool,
+
+    /// Fraction of sentences that rise at the end like questions
+    #[arg(long, default_value_t = 0.0)]
+    question_probability: f64,
+
+    /// Lo-fi voice on each whole phrase: telephone, radio, or megaphone
+    #[arg(long, value_name = "PRESET", value_parser = parse_voice_fx)]
+    voice_fx: Option<VoiceFx>,
+
     // -- Time stretch --
     /// Global speed factor (0.5=half, 2.0=double)
     #[arg(long)]
@@ -236,6 +603,53 @@ struct CollageArgs {
     /// Set to 0 to disable.
     #[arg(long, default_value_t = 1.0)]
     dispersal_gap: f64,
+
+    // -- Stereo --
+    /// Keep the stereo image of stereo sources (default: downmix to mono)
+    #[arg(long)]
+    stereo: bool,
+
+    /// Spread word clips across the stereo field (0.0 = off, 1.0 = full width)
+    #[arg(long, default_value_t = 0.0)]
+    pan_spread: f64,
+
+    // -- Anchors --
+    /// Comma-separated words that must appear in the collage: "hello,world"
+    #[arg(long)]
+    anchor_words: Option<String>,
+
+    /// Start sentences with the anchor words instead of placing them at random
+    #[arg(long)]
+    anchor_sentence_start: bool,
+
+    // -- Sampling bias --
+    /// Bias syllable sampling toward longer or shorter syllables
+    #[arg(long, value_parser = ["long", "short"])]
+    prefer_duration: Option<String>,
+
+    /// Bias syllable sampling toward louder or quieter syllables
+    #[arg(long, value_parser = ["high", "low"])]
+    prefer_energy: Option<String>,
+
+    /// Relative share of syllables drawn from one input: "host.wav=0.7"
+    /// (repeatable; unlisted inputs get 1.0, 0 leaves an input out)
+    #[arg(long = "source-weight", value_name = "FILE=WEIGHT", value_parser = parse_source_weight)]
+    source_weights: Vec<SourceWeight>,
+
+    // -- Granularity --
+    /// Cut units: "syllable" (default) or "word" (whole aligned words;
+    /// ignores --syllables-per-word)
+    #[arg(long, default_value = "syllable", value_parser = ["syllable", "word"])]
+    unit: String,
+
+    /// How syllables are ordered inside nonsense words: "phonotactic"
+    /// (sonority scoring) or "markov" (phoneme bigrams from the sources)
+    #[arg(long, default_value = "phonotactic", value_parser = ["phonotactic", "markov"])]
+    word_model: String,
+
+    /// Fewest other syllables between two uses of the same source syllable (0 = no limit)
+    #[arg(long, default_value_t = 8)]
+    min_reuse_distance: usize,
 }
 
 // ─── Sing ────────────────────────────────────────────────────────
@@ -246,10 +660,30 @@ struct SingArgs {
     #[command(flatten)]
     shared: SharedArgs,
 
-    /// Directory containing MIDI files
+    /// MIDI file, or a directory with melody.mid plus backing .mid files
     #[arg(long)]
     midi: PathBuf,
 
+    /// Part of a single MIDI file to sing, numbered as logged (default: pick automatically)
+    #[arg(long, value_name = "N")]
+    melody_track: Option<usize>,
+
+    /// Lyrics to sing: source syllables are matched to their phonemes and sung in order
+    #[arg(long, value_name = "TEXT")]
+    lyrics: Option<String>,
+
+    /// TOML file pinning melody notes to words of the sources
+    #[arg(long, value_name = "FILE")]
+    pins: Option<PathBuf>,
+
+    /// TOML file mapping GM drum notes to synth settings or samples
+    #[arg(long, value_name = "FILE")]
+    drum_map: Option<PathBuf>,
+
+    /// SF2 soundfont to play the backing tracks with (default: sine tones)
+    #[arg(long, value_name = "FILE")]
+    soundfont: Option<PathBuf>,
+
     /// Enable vibrato [use --no-vibrato to disable]
     #[arg(long, default_value_t = true)]
     vibrato: bool,
@@ -258,6 +692,26 @@ struct SingArgs {
     #[arg(long, overrides_with = "vibrato")]
     no_vibrato: bool,
 
+    /// Vibrato pitch swing in cents
+    #[arg(long, default_value_t = 50.0)]
+    vibrato_depth: f64,
+
+    /// Vibrato wobbles per second
+    #[arg(long, default_value_t = 5.5)]
+    vibrato_rate: f64,
+
+    /// Time into a held syllable before vibrato fades in, in milliseconds
+    #[arg(long, default_value_t = 300.0)]
+    vibrato_onset: f64,
+
+    /// Vibrato wet/dry mix, 0-1 (below 1 blends in the steady syllable)
+    #[arg(long, default_value_t = 1.0)]
+    vibrato_mix: f64,
+
+    /// Gain on syllables with vibrato, in dB
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    vibrato_gain: f64,
+
     /// Enable chorus [use --no-chorus to disable]
     #[arg(long, default_value_t = true)]
     chorus: bool,
@@ -266,10 +720,82 @@ struct SingArgs {
     #[arg(long, overrides_with = "chorus")]
     no_chorus: bool,
 
+    /// Detuned copies layered over chorused notes
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(1..=8))]
+    chorus_voices: u8,
+
+    /// Chorus detune in cents: "10-15" or "12"
+    #[arg(long, default_value = "10-15")]
+    chorus_detune: String,
+
+    /// Chorus copy delay in milliseconds: "15-30" or "20"
+    #[arg(long, default_value = "15-30")]
+    chorus_delay: String,
+
+    /// Chorus wet/dry mix, 0-1 (below 1 blends in the dry note)
+    #[arg(long, default_value_t = 1.0)]
+    chorus_mix: f64,
+
+    /// Gain on chorused notes, in dB
+    #[arg(long, default_value_t = 0.0, allow_negative_numbers = true)]
+    chorus_gain: f64,
+
+    /// Fade-out tail sung past a note's end into a rest, in milliseconds (0 = cut off)
+    #[arg(long, default_value_t = 0.0)]
+    release: f64,
+
+    /// Hold a note into the next one across rests up to this long, in milliseconds
+    #[arg(long, default_value_t = 0.0)]
+    legato_gap: f64,
+
     /// Max semitone drift from melody
     #[arg(long, default_value_t = 2.0)]
     drift_range: f64,
 
+    /// Notes shorter than this many seconds are short
+    #[arg(long, default_value_t = 0.2)]
+    short_note: f64,
+
+    /// Notes at least this many seconds long are long
+    #[arg(long, default_value_t = 1.0)]
+    long_note: f64,
+
+    /// Syllables per short note, picked at random: "1" or "1,1,2"
+    #[arg(long, default_value = "1")]
+    short_syllables: String,
+
+    /// Syllables per medium note, picked at random
+    #[arg(long, default_value = "1,1,1,2,2,3")]
+    medium_syllables: String,
+
+    /// Syllables per long note, picked at random
+    #[arg(long, default_value = "1,2,2,3,3,4")]
+    long_syllables: String,
+
+    /// Fit the melody to --target-duration: loop it, truncate it, or play it once
+    #[arg(long, default_value = "loop", value_parser = ["loop", "truncate", "once"])]
+    loop_mode: String,
+
+    /// Sing harmony voices under the melody, pitched to a chord track
+    #[arg(long)]
+    harmony: bool,
+
+    /// Number of harmony voices
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u8).range(1..=3))]
+    harmony_voices: u8,
+
+    /// MIDI file with the chords to harmonize (default: the most chordal backing part)
+    #[arg(long, value_name = "FILE")]
+    chords: Option<PathBuf>,
+
+    /// Max late entry of each harmony note, in milliseconds
+    #[arg(long, default_value_t = 30.0)]
+    harmony_jitter: f64,
+
+    /// Keep the stereo image of stereo sources (default: downmix to mono)
+    #[arg(long)]
+    stereo: bool,
+
     /// Max source videos (Slack mode)
     #[arg(long, default_value_t = 5)]
     max_videos: usize,
@@ -283,7 +809,7 @@ struct SpeakArgs {
     #[command(flatten)]
     shared: SharedArgs,
 
-    /// Target text to reconstruct
+    /// Target text to reconstruct; may mark up <pause ms="400"/>, <emph>...</emph>, <rate value="0.8">...</rate>
     #[arg(long)]
     text: Option<String>,
 
@@ -295,6 +821,14 @@ struct SpeakArgs {
     #[arg(long, default_value = "syllable", value_parser = ["syllable", "phoneme"])]
     match_unit: String,
 
+    /// How syllables are matched to the bank
+    #[arg(long, default_value = "viterbi", value_parser = ["greedy", "viterbi", "beam"])]
+    match_strategy: String,
+
+    /// Partial paths kept by --match-strategy beam
+    #[arg(long, default_value_t = DEFAULT_BEAM_WIDTH)]
+    beam_width: usize,
+
     /// Adjust pitch to target intonation [use --no-pitch-correct to disable]
     #[arg(long, default_value_t = true)]
     pitch_correct: bool,
@@ -319,15 +853,183 @@ struct SpeakArgs {
     #[arg(long, overrides_with = "normalize_volume")]
     no_normalize_volume: bool,
 
+    /// Level volume normalization aims for: median or mean of the clips'
+    /// RMS, or a fixed RMS level in dBFS such as -20
+    #[arg(long, value_name = "TARGET", default_value = "median", value_parser = parse_rms_target, allow_hyphen_values = true)]
+    volume_target: RmsTarget,
+
+    /// Most volume normalization turns a clip up (dB); lower it to keep
+    /// whispered sources from being boosted into noise
+    #[arg(long, value_name = "DB", default_value_t = 20.0)]
+    volume_max_boost: f64,
+
+    /// Most volume normalization turns a clip down (dB)
+    #[arg(long, value_name = "DB", default_value_t = 20.0)]
+    volume_max_cut: f64,
+
     /// Alignment backend
     #[arg(long, default_value = "auto", value_parser = ["auto", "default", "bfa"])]
     aligner: String,
 }
 
+// ─── Timeline image ──────────────────────────────────────────────
+
+#[derive(Parser, Debug)]
+#[command(about = "Render an editor project (.glott) to audio without the GUI")]
+struct RenderArgs {
+    /// Editor project file (.glott)
+    project: PathBuf,
+
+    /// Output audio; the extension picks the format (.wav, .flac, .ogg or .mp3)
+    #[arg(long, short = 'o')]
+    output: PathBuf,
+
+    /// Point a moved source file or folder at its new location (repeatable)
+    #[arg(long, value_name = "OLD=NEW", value_parser = parse_relink)]
+    relink: Vec<Relink>,
+
+    /// Crossfade between clips in ms (default: the project's crossfade)
+    #[arg(long)]
+    crossfade: Option<f64>,
+
+    /// Pink noise bed level in dB (0 to disable)
+    #[arg(long, default_value_t = -40.0, allow_hyphen_values = true)]
+    noise_level: f64,
+
+    /// Fill gaps with room tone [use --no-room-tone to disable]
+    #[arg(long, default_value_t = true)]
+    room_tone: bool,
+
+    /// Disable room tone
+    #[arg(long, overrides_with = "room_tone")]
+    no_room_tone: bool,
+
+    /// Normalize pitch across clips [use --no-pitch-normalize to disable]
+    #[arg(long, default_value_t = true)]
+    pitch_normalize: bool,
+
+    /// Disable pitch normalization
+    #[arg(long, overrides_with = "pitch_normalize")]
+    no_pitch_normalize: bool,
+
+    /// Max pitch shift in semitones
+    #[arg(long, default_value_t = 5.0)]
+    pitch_range: f64,
+
+    /// Insert breaths between clips [use --no-breaths to disable]
+    #[arg(long, default_value_t = true)]
+    breaths: bool,
+
+    /// Disable breath insertion
+    #[arg(long, overrides_with = "breaths")]
+    no_breaths: bool,
+
+    /// Probability of a breath at each gap
+    #[arg(long, default_value_t = 0.6)]
+    breath_probability: f64,
+
+    /// Peak-normalize the mix to -1 dB [use --no-volume-normalize to disable]
+    #[arg(long, default_value_t = true)]
+    volume_normalize: bool,
+
+    /// Disable volume normalization
+    #[arg(long, overrides_with = "volume_normalize")]
+    no_volume_normalize: bool,
+
+    /// Apply the phrase-level volume envelope [use --no-prosodic-dynamics to disable]
+    #[arg(long, default_value_t = true)]
+    prosodic_dynamics: bool,
+
+    /// Disable prosodic dynamics
+    #[arg(long, overrides_with = "prosodic_dynamics")]
+    no_prosodic_dynamics: bool,
+
+    /// Global speed factor (0.5=half, 2.0=double)
+    #[arg(long)]
+    speed: Option<f64>,
+
+    /// RNG seed for noise and breath placement
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Write the mix without the peak limiter (may hard-clip)
+    #[arg(long)]
+    no_limiter: bool,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Draw an editor project's timeline to a PNG or SVG image")]
+struct TimelineImageArgs {
+    /// Editor project file (.glott)
+    project: PathBuf,
+
+    /// Output image; the extension picks the format (.png or .svg)
+    #[arg(long, short = 'o')]
+    output: PathBuf,
+
+    /// Point a moved source file or folder at its new location (repeatable)
+    #[arg(long, value_name = "OLD=NEW", value_parser = parse_relink)]
+    relink: Vec<Relink>,
+
+    /// Image width in pixels
+    #[arg(long, default_value_t = timeline_image::DEFAULT_WIDTH)]
+    width: u32,
+}
+
+// ─── Record ──────────────────────────────────────────────────────
+
+#[derive(Parser, Debug)]
+#[command(about = "Record from the default microphone to a WAV file")]
+struct RecordArgs {
+    /// Recording length in seconds
+    #[arg(long, default_value_t = 30.0)]
+    duration: f64,
+
+    /// WAV file to write (default: <output dir>/recordings/recording-<time>.wav)
+    #[arg(long, short = 'o')]
+    output: Option<PathBuf>,
+
+    /// Input buffer size in frames (default: let the audio backend choose)
+    #[arg(long, value_name = "FRAMES", value_parser = clap::value_parser!(u32).range(16..=8192))]
+    buffer_size: Option<u32>,
+
+    /// Pipeline to run on the recording, with its options, e.g. `-- collage --bpm 120`
+    #[arg(last = true, value_name = "PIPELINE")]
+    pipeline: Vec<String>,
+}
+
+// ─── Diff ────────────────────────────────────────────────────────
+
+#[derive(Parser, Debug)]
+#[command(about = "Compare two runs' seeds, options, and clip choices")]
+struct DiffArgs {
+    /// First run directory, or a run name in --output-dir
+    run_a: PathBuf,
+
+    /// Second run directory, or a run name in --output-dir
+    run_b: PathBuf,
+
+    /// Where to look up runs given by name
+    #[arg(long, default_value_os_t = default_output_dir())]
+    output_dir: PathBuf,
+}
+
+#[derive(Parser, Debug)]
+#[command(about = "Remove scratch files left by earlier runs")]
+struct CleanArgs {
+    /// Remove the temp folders the GUI builds syllable banks in
+    #[arg(long)]
+    temp: bool,
+
+    /// List what would be removed without removing it
+    #[arg(long)]
+    dry_run: bool,
+}
+
 // ─── Main ────────────────────────────────────────────────────────
 
 fn main() {
-    let cli = Cli::parse();
+    let cli = Cli::parse_recording_options(std::env::args_os()).unwrap_or_else(|e| e.exit());
 
     // Init logging
     let log_level = match &cli.command {
@@ -340,18 +1042,37 @@ fn main() {
         .format_timestamp(None)
         .init();
 
-    let result = match cli.command {
-        Command::Collage(args) => run_collage(*args),
-        Command::Sing(args) => run_sing(args),
-        Command::Speak(args) => run_speak(args),
-    };
-
-    if let Err(e) = result {
+    if let Err(e) = run_command(cli.command) {
         log::error!("{:#}", e);
         std::process::exit(1);
     }
 }
 
+fn run_command(command: Command) -> Result<()> {
+    let jobs = match &command {
+        Command::Collage(a) => a.shared.jobs,
+        Command::Sing(a) => a.shared.jobs,
+        Command::Speak(a) => a.shared.jobs,
+        Command::Render(_) | Command::TimelineImage(_) | Command::Record(_) | Command::Diff(_) | Command::Clean(_) => None,
+    };
+    if let Some(jobs) = jobs {
+        if let Err(e) = rayon::ThreadPoolBuilder::new().num_threads(jobs).build_global() {
+            log::warn!("Could not set --jobs {}: {}", jobs, e);
+        }
+    }
+
+    match command {
+        Command::Collage(args) => run_collage(*args),
+        Command::Sing(args) => run_sing(*args),
+        Command::Speak(args) => run_speak(*args),
+        Command::Render(args) => run_render(args),
+        Command::TimelineImage(args) => run_timeline_image(args),
+        Command::Record(args) => run_record(args),
+        Command::Diff(args) => run_diff(args),
+        Command::Clean(args) => run_clean(args),
+    }
+}
+
 // ─── Helpers ─────────────────────────────────────────────────────
 
 /// Validate input files exist.
@@ -368,7 +1089,21 @@ fn validate_inputs(paths: &[PathBuf]) -> Result<()> {
 }
 
 /// Extract audio from each input file to 16kHz mono WAV in the work dir.
-fn prepare_audio(inputs: &[PathBuf], work_dir: &std::path::Path) -> Result<Vec<PathBuf>> {
+///
+/// Inputs with a `--source-range` are trimmed to that window before
+/// alignment, and with `denoise` set, run through the spectral gate.
+fn prepare_audio(
+    inputs: &[PathBuf],
+    ranges: &[SourceRange],
+    work_dir: &std::path::Path,
+    denoise: Option<DenoiseLevel>,
+) -> Result<Vec<PathBuf>> {
+    for range in ranges {
+        if !inputs.iter().any(|input| range.matches(input)) {
+            bail!("--source-range refers to unknown input: {}", range.file.display());
+        }
+    }
+
     std::fs::create_dir_all(work_dir)?;
     let mut audio_paths = Vec::new();
     for input in inputs {
@@ -379,41 +1114,282 @@ fn prepare_audio(inputs: &[PathBuf], work_dir: &std::path::Path) -> Result<Vec<P
         let wav_path = work_dir.join(format!("{}_16k.wav", stem));
         log::info!("Extracting audio: {} -> {}", input.display(), wav_path.display());
         extract_audio(input, &wav_path)?;
+        trim_to_source_range(input, ranges, &wav_path)?;
+        if let Some(level) = denoise {
+            denoise_source(&wav_path, level)?;
+        }
         audio_paths.push(wav_path);
     }
     Ok(audio_paths)
 }
 
+/// Trim an extracted WAV to the `--source-range` matching its input, if any.
+fn trim_to_source_range(
+    input: &std::path::Path,
+    ranges: &[SourceRange],
+    wav_path: &std::path::Path,
+) -> Result<()> {
+    if let Some(range) = ranges.iter().find(|r| r.matches(input)) {
+        log::info!(
+            "Using {:.1}-{:.1}s of {}",
+            range.start_s,
+            range.end_s,
+            input.display()
+        );
+        trim_wav(wav_path, range.start_s, range.end_s)?;
+    }
+    Ok(())
+}
+
+/// De-noise an extracted WAV in place, warning when it has no room tone.
+fn denoise_source(wav_path: &std::path::Path, level: DenoiseLevel) -> Result<()> {
+    log::info!("De-noising {} ({})", wav_path.display(), level.name());
+    if !denoise_wav(wav_path, level)? {
+        log::warn!("No room tone found in {}; left as is", wav_path.display());
+    }
+    Ok(())
+}
+
+/// Align every source in parallel (bounded by `--jobs`) and load its audio.
+///
+/// Results come back in input order.
+fn align_sources(
+    audio_paths: &[PathBuf],
+    aligners: &[Box<dyn Aligner>],
+    progress: &ProgressLine,
+    min_quality: f64,
+) -> Result<Vec<(AlignmentResult, Vec<f64>, u32)>> {
+    let done = AtomicUsize::new(0);
+    progress.stage(Stage::Alignment, 0.0);
+    audio_paths
+        .par_iter()
+        .zip(aligners.par_iter())
+        .map(|(audio_path, aligner)| {
+            let alignment = {
+                let _span = profile::span(profile::Category::Align, "align");
+                aligner
+                    .process(audio_path, None)
+                    .with_context(|| format!("Alignment failed for {}", audio_path.display()))?
+            };
+            let (samples, sr) = read_wav(audio_path)?;
+            let mut alignment = alignment;
+            let dropped = retain_quality_syllables(&mut alignment.syllables, &samples, sr, min_quality);
+            if dropped > 0 {
+                log::info!("Dropped {} low-quality syllables from {}", dropped, audio_path.display());
+            }
+            let finished = done.fetch_add(1, Ordering::Relaxed) + 1;
+            progress.stage(Stage::Alignment, finished as f64 / audio_paths.len() as f64);
+            Ok((alignment, samples, sr))
+        })
+        .collect()
+}
+
+/// Steps after a successful run, from [`SharedArgs::after_run`].
+struct AfterRun {
+    on_complete: Option<String>,
+    webhook: Option<WebhookConfig>,
+    /// Write profile.json as well as profile.txt.
+    profile_trace: bool,
+}
+
+impl AfterRun {
+    /// Write the `--profile` report, post the run to the webhook, if any,
+    /// then run the on-complete hook. `stats` are extra lines for the post.
+    /// A failed post only warns: the run itself succeeded.
+    fn finish(
+        self,
+        pipeline: &str,
+        shared: &SharedArgs,
+        run_dir: &Path,
+        outputs: Vec<PathBuf>,
+        stats: Vec<String>,
+    ) -> Result<()> {
+        if profile::is_enabled() {
+            self.write_profile(run_dir)?;
+        }
+        if let Some(webhook) = &self.webhook {
+            let run_name = run_dir.file_name().unwrap_or_default().to_string_lossy();
+            let lines = shared
+                .rng_seed()
+                .map(|seed| format!("Seed: {}", seed))
+                .into_iter()
+                .chain(outputs.first().and_then(|o| length_line(o)))
+                .chain(stats)
+                .collect();
+            let post = RunPost { title: format!("{} run {} finished", pipeline, run_name), lines, files: outputs.clone() };
+            match post_run(webhook.url.as_deref().unwrap_or_default(), &post, webhook.upload) {
+                Ok(()) => log::info!("Posted {} to the webhook", run_name),
+                Err(e) => log::warn!("Webhook post failed: {:#}", e),
+            }
+        }
+        let Some(command) = self.on_complete else {
+            return Ok(());
+        };
+        run_hook(&command, &RunOutputs { run_dir: run_dir.to_path_buf(), outputs }).context("--on-complete")
+    }
+
+    /// Print the profile summary and save it, with the trace if asked for,
+    /// in the run directory.
+    fn write_profile(&self, run_dir: &Path) -> Result<()> {
+        let (events, elapsed) = profile::finish();
+        let report = profile::report(&events, elapsed);
+        eprintln!("\n{}", report);
+        std::fs::write(run_dir.join("profile.txt"), &report)?;
+        if self.profile_trace {
+            std::fs::write(run_dir.join("profile.json"), profile::chrome_trace(&events).to_string())?;
+        }
+        Ok(())
+    }
+}
+
+/// Split a comma-separated word list, dropping empty entries.
+fn parse_word_list(s: &str) -> Vec<String> {
+    s.split(',')
+        .map(|w| w.trim().to_string())
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+// ─── Progress ────────────────────────────────────────────────────
+
+/// Progress line with an ETA, redrawn in place on stderr.
+///
+/// Stays silent when stderr is not a terminal so piped logs stay clean.
+struct ProgressLine {
+    started: Instant,
+    enabled: bool,
+    /// Last percentage drawn; also serializes redraws from worker threads.
+    last_percent: Mutex<Option<u32>>,
+}
+
+impl ProgressLine {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            enabled: std::io::stderr().is_terminal(),
+            last_percent: Mutex::new(None),
+        }
+    }
+}
+
+impl ProgressReporter for ProgressLine {
+    fn report(&self, stage: &str, fraction: f64) {
+        profile::stage(stage);
+        let percent = (fraction.clamp(0.0, 1.0) * 100.0).round() as u32;
+        if !self.enabled {
+            return;
+        }
+        let mut last = self.last_percent.lock().unwrap();
+        if *last == Some(percent) {
+            return;
+        }
+        *last = Some(percent);
+        let eta = estimate_remaining(self.started.elapsed(), fraction)
+            .map(|d| format!("  ETA {}", format_eta(d)))
+            .unwrap_or_default();
+        let mut stderr = std::io::stderr();
+        let _ = write!(stderr, "\r\x1b[K{:<10} {:>3}%{}", stage, percent, eta);
+        let _ = stderr.flush();
+    }
+}
+
+impl Drop for ProgressLine {
+    fn drop(&mut self) {
+        // End the progress line so later output starts on a fresh line
+        if self.enabled && self.last_percent.lock().unwrap().is_some() {
+            eprintln!();
+        }
+    }
+}
+
+/// Format a duration as `m:ss`.
+fn format_eta(d: Duration) -> String {
+    let secs = d.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Taps needed before the tap-tempo estimate is used.
+const TAP_COUNT: usize = 8;
+
+/// Read a tempo from Enter presses on stdin.
+fn tap_tempo() -> Result<f64> {
+    eprintln!("Tap Enter on the beat ({} taps)...", TAP_COUNT);
+    let started = Instant::now();
+    let mut taps = Vec::with_capacity(TAP_COUNT);
+    let mut line = String::new();
+    while taps.len() < TAP_COUNT {
+        line.clear();
+        if std::io::stdin().read_line(&mut line)? == 0 {
+            break;
+        }
+        taps.push(started.elapsed().as_secs_f64());
+    }
+    let bpm = bpm_from_taps(&taps).ok_or_else(|| anyhow::anyhow!("Tap tempo needs at least two taps"))?;
+    eprintln!("Tempo: {:.1} BPM", bpm);
+    Ok(bpm)
+}
+
 // ─── Collage runner ──────────────────────────────────────────────
 
 fn run_collage(args: CollageArgs) -> Result<()> {
     validate_inputs(&args.shared.input_files)?;
+    let after_run = args.shared.after_run()?;
+
+    // Ask for the tempo up front so alignment runs unattended
+    let bpm = if args.tap_tempo { Some(tap_tempo()?) } else { args.bpm };
+    if let Some(bpm) = bpm {
+        if !(20.0..=400.0).contains(&bpm) {
+            bail!("--bpm must be between 20 and 400, got {}", bpm);
+        }
+    }
+    for w in &args.source_weights {
+        if !args.shared.input_files.iter().any(|input| w.matches(input)) {
+            bail!("--source-weight refers to unknown input: {}", w.file.display());
+        }
+    }
 
-    let run_dir = create_run_dir(
+    let run_dir = create_run_dir_with(
         &args.shared.output_dir,
-        args.shared.seed,
+        args.shared.rng_seed(),
         args.shared.run_name.as_deref(),
+        &NameTheme::load(&args.shared.name_theme)?,
     )?;
     println!("Run: {}", run_dir.file_name().unwrap().to_string_lossy());
+    args.shared.record_seed(&run_dir)?;
 
     let work_dir = run_dir.join("work");
-    let audio_paths = prepare_audio(&args.shared.input_files, &work_dir)?;
+    let audio_paths = prepare_audio(
+        &args.shared.input_files,
+        &args.shared.source_ranges,
+        &work_dir,
+        args.shared.denoise,
+    )?;
 
     // Align each source and collect samples + syllables keyed by source
-    let aligner = get_aligner(&args.aligner, &args.shared.whisper_model, "en", &args.bfa_device)?;
+    let aligners = args.shared.aligners(&args.aligner)?;
     let mut source_audio: HashMap<String, (Vec<f64>, u32)> = HashMap::new();
     let mut source_syllables: HashMap<String, Vec<glottisdale_core::types::Syllable>> = HashMap::new();
+    let progress = ProgressLine::new();
 
-    for audio_path in &audio_paths {
+    let aligned = align_sources(&audio_paths, &aligners, &progress, args.shared.min_quality)?;
+    for (audio_path, (alignment, samples, sr)) in audio_paths.iter().zip(aligned) {
         let key = audio_path.to_string_lossy().to_string();
-        let alignment = aligner.process(audio_path, None)
-            .with_context(|| format!("Alignment failed for {}", audio_path.display()))?;
-
-        let (samples, sr) = read_wav(audio_path)?;
         source_audio.insert(key.clone(), (samples, sr));
         source_syllables.insert(key, alignment.syllables);
     }
 
+    // Source maps are keyed by the prepared audio path, parallel to the inputs
+    let source_weights: HashMap<String, f64> = args
+        .shared
+        .input_files
+        .iter()
+        .zip(&audio_paths)
+        .filter_map(|(input, audio_path)| {
+            let w = args.source_weights.iter().rfind(|w| w.matches(input))?;
+            Some((audio_path.to_string_lossy().to_string(), w.weight))
+        })
+        .collect();
+
     let total_syls: usize = source_syllables.values().map(|v| v.len()).sum();
     log::info!(
         "Aligned {} source(s): {} syllables",
@@ -427,6 +1403,7 @@ fn run_collage(args: CollageArgs) -> Result<()> {
     let breaths = args.breaths && !args.no_breaths;
     let volume_normalize = args.volume_normalize && !args.no_volume_normalize;
     let prosodic_dynamics = args.prosodic_dynamics && !args.no_prosodic_dynamics;
+    let intonation = args.intonation && !args.no_intonation;
 
     // Build collage config from CLI args
     let config = glottisdale_core::collage::process::CollageConfig {
@@ -439,7 +1416,7 @@ fn run_collage(args: CollageArgs) -> Result<()> {
         phrase_pause: args.phrase_pause,
         sentence_pause: args.sentence_pause,
         word_crossfade_ms: args.word_crossfade,
-        seed: args.shared.seed,
+        seed: args.shared.rng_seed(),
         noise_level_db: args.noise_level,
         room_tone,
         pitch_normalize,
@@ -447,7 +1424,17 @@ fn run_collage(args: CollageArgs) -> Result<()> {
         breaths,
         breath_probability: args.breath_probability,
         volume_normalize,
+        volume_normalization: RmsNormalization {
+            target: args.volume_target,
+            max_boost_db: args.volume_max_boost,
+            max_cut_db: args.volume_max_cut,
+        },
         prosodic_dynamics,
+        limiter: !args.shared.no_limiter,
+        mastering: args.shared.mastering(),
+        intonation,
+        question_probability: args.question_probability.clamp(0.0, 1.0),
+        voice_fx: args.voice_fx,
         speed: args.speed,
         stretch_config: StretchConfig {
             random_stretch: args.random_stretch,
@@ -462,15 +1449,59 @@ fn run_collage(args: CollageArgs) -> Result<()> {
         stutter: args.stutter,
         stutter_count: args.stutter_count,
         dispersal_gap: args.dispersal_gap,
+        anchor_words: args
+            .anchor_words
+            .as_deref()
+            .map(parse_word_list)
+            .unwrap_or_default(),
+        anchor_sentence_start: args.anchor_sentence_start,
+        pan_spread: args.pan_spread,
+        prefer_duration: args.prefer_duration,
+        prefer_energy: args.prefer_energy,
+        unit: args.unit,
+        word_model: args.word_model,
+        min_reuse_distance: args.min_reuse_distance,
+        phrase_min_pause_ms: args.phrase_min_pause,
+        bpm,
+        beat_subdivision: args.subdivision,
+        swing: args.swing,
+        source_weights,
+        preview_s: args.preview,
     };
 
     let result = if args.mode == "shuffle" {
-        glottisdale_core::collage::shuffle::process_shuffle(
+        let result = glottisdale_core::collage::shuffle::process_shuffle(
             &source_audio,
             &source_syllables,
             &run_dir,
             args.shared.target_duration,
             args.crossfade,
+            !args.shared.no_limiter,
+            &args.shared.mastering(),
+        )?;
+        progress.stage(Stage::Assembly, 1.0);
+        result
+    } else if args.mode == "phrase" {
+        glottisdale_core::collage::phrases::process_phrases(
+            &source_audio,
+            &source_syllables,
+            &run_dir,
+            &config,
+            &progress,
+        )?
+    } else if args.stereo {
+        let source_channels = load_stereo_sources(
+            &args.shared.input_files,
+            &args.shared.source_ranges,
+            &audio_paths,
+            args.shared.denoise,
+        )?;
+        glottisdale_core::collage::process::process_channels(
+            &source_channels,
+            &source_syllables,
+            &run_dir,
+            &config,
+            &progress,
         )?
     } else {
         glottisdale_core::collage::process::process(
@@ -478,8 +1509,10 @@ fn run_collage(args: CollageArgs) -> Result<()> {
             &source_syllables,
             &run_dir,
             &config,
+            &progress,
         )?
     };
+    drop(progress);
 
     // Create clips zip from the clips directory
     let clips_dir = run_dir.join("clips");
@@ -510,64 +1543,136 @@ fn run_collage(args: CollageArgs) -> Result<()> {
 
     println!("Processed {} source file(s)", args.shared.input_files.len());
     println!("Selected {} clips", result.clips.len());
-    println!("Output: {}", result.concatenated.display());
-
-    Ok(())
+    let output = args.shared.finish_output(&result.concatenated)?;
+    println!("Output: {}", output.display());
+
+    let outputs = std::iter::once(output).chain(zip_path.is_file().then_some(zip_path)).collect();
+    let stats = vec![
+        format!("Sources: {}", args.shared.input_files.len()),
+        format!("Clips: {}", result.clips.len()),
+    ];
+    after_run.finish("Collage", &args.shared, &run_dir, outputs, stats)
 }
 
 // ─── Sing runner ─────────────────────────────────────────────────
 
 fn run_sing(args: SingArgs) -> Result<()> {
-    use glottisdale_core::sing::midi_parser::parse_midi;
-    use glottisdale_core::sing::syllable_prep::{prepare_syllables, median_f0};
-    use glottisdale_core::sing::vocal_mapper::{plan_note_mapping, render_vocal_track};
-    use glottisdale_core::sing::mixer::mix_tracks;
+    use glottisdale_core::sing::harmony::{pick_chords, plan_harmony};
+    use glottisdale_core::sing::lyrics::{apply_lyrics, match_lyrics};
+    use glottisdale_core::sing::midi_parser::{load_song, parse_midi};
+    use glottisdale_core::audio::io::{to_layout, Channels};
+    use glottisdale_core::sing::syllable_prep::{prepare_syllables_channels, median_f0};
+    use glottisdale_core::sing::vocal_mapper::{
+        arrange_track, plan_note_mapping_with, render_vocal_track_with, ChorusConfig, LoopMode,
+        NotePins, ReleaseConfig, SingConfig, VibratoConfig,
+    };
+    use glottisdale_core::sing::mixer::{
+        bar_seconds, mix_harmony_channels, mix_tracks_channels, truncate_with_fade, write_stem_metadata,
+    };
+    use glottisdale_core::sing::soundfont::Soundfont;
+    use glottisdale_core::sing::synthesize::{DrumMap, Instruments};
+    use glottisdale_core::speak::matcher::MatchStrategy;
 
     validate_inputs(&args.shared.input_files)?;
-
-    let melody_path = args.midi.join("melody.mid");
-    if !melody_path.exists() {
-        bail!("MIDI melody not found: {}", melody_path.display());
+    let after_run = args.shared.after_run()?;
+    let loop_mode = LoopMode::parse(&args.loop_mode)?;
+    let chorus = args.chorus && !args.no_chorus;
+    let sing_config = SingConfig {
+        drift_range: args.drift_range,
+        chorus_probability: if chorus { 0.3 } else { 0.0 },
+        short_note_s: args.short_note,
+        long_note_s: args.long_note,
+        short_syllables: SingConfig::parse_counts(&args.short_syllables).context("--short-syllables")?,
+        medium_syllables: SingConfig::parse_counts(&args.medium_syllables).context("--medium-syllables")?,
+        long_syllables: SingConfig::parse_counts(&args.long_syllables).context("--long-syllables")?,
+    };
+    sing_config.validate()?;
+    let language = Language::from_code(&args.shared.language)?;
+    let pins = args.pins.as_deref().map(NotePins::load).transpose()?.unwrap_or_default();
+    let instruments = Instruments {
+        soundfont: args.soundfont.as_deref().map(Soundfont::load).transpose()?,
+        drum_map: args.drum_map.as_deref().map(DrumMap::load).transpose()?,
+    };
+    if !args.midi.exists() {
+        bail!("MIDI not found: {}", args.midi.display());
     }
 
-    let run_dir = create_run_dir(
+    let run_dir = create_run_dir_with(
         &args.shared.output_dir,
-        args.shared.seed,
+        args.shared.rng_seed(),
         args.shared.run_name.as_deref(),
+        &NameTheme::load(&args.shared.name_theme)?,
     )?;
     println!("Run: {}", run_dir.file_name().unwrap().to_string_lossy());
+    args.shared.record_seed(&run_dir)?;
 
     let work_dir = run_dir.join("work");
-    let audio_paths = prepare_audio(&args.shared.input_files, &work_dir)?;
+    let audio_paths = prepare_audio(
+        &args.shared.input_files,
+        &args.shared.source_ranges,
+        &work_dir,
+        args.shared.denoise,
+    )?;
 
-    // Parse MIDI melody
-    log::info!("Parsing MIDI: {}", melody_path.display());
-    let track = parse_midi(&melody_path)?;
+    // Parse MIDI melody and backing
+    log::info!("Parsing MIDI: {}", args.midi.display());
+    let song = load_song(&args.midi, args.melody_track)?;
+    let track = song.melody;
     log::info!(
         "Melody: {} notes, {} BPM, {:.1}s",
         track.notes.len(),
         track.tempo,
         track.total_duration
     );
+    let target_duration = args.shared.target_duration;
+    let period = track.total_duration;
+    let melody = arrange_track(&track, period, target_duration, loop_mode);
+    if loop_mode != LoopMode::Once {
+        log::info!(
+            "Arranged melody to {:.1}s ({}): {} notes",
+            melody.total_duration,
+            args.loop_mode,
+            melody.notes.len()
+        );
+    }
 
     // Align and prepare syllables from source audio
-    let aligner = get_aligner("auto", &args.shared.whisper_model, "en", "cpu")?;
-    let mut all_syllable_clips = Vec::new();
+    let aligners = args.shared.aligners("auto")?;
+    let stereo_sources = if args.stereo {
+        Some(load_stereo_sources(
+            &args.shared.input_files,
+            &args.shared.source_ranges,
+            &audio_paths,
+            args.shared.denoise,
+        )?)
+    } else {
+        None
+    };
+    // One clip list per output channel, parallel to each other
+    let mut channel_clips = vec![Vec::new(); if args.stereo { 2 } else { 1 }];
     let mut sample_rate = 16000u32;
+    let progress = ProgressLine::new();
 
-    for audio_path in &audio_paths {
-        let alignment = aligner.process(audio_path, None)?;
-        let (samples, sr) = read_wav(audio_path)?;
+    let aligned = align_sources(&audio_paths, &aligners, &progress, args.shared.min_quality)?;
+    for (audio_path, (alignment, samples, sr)) in audio_paths.iter().zip(aligned) {
         sample_rate = sr;
 
-        let prepared = prepare_syllables(
+        let source = match &stereo_sources {
+            Some(sources) => to_layout(&sources[&audio_path.to_string_lossy().to_string()].0, Channels::Stereo),
+            None => vec![samples],
+        };
+        let prepared = prepare_syllables_channels(
             &alignment.syllables,
-            &samples,
+            &source,
             sr,
             12.0, // max_semitone_shift
         );
-        all_syllable_clips.extend(prepared);
+        for (clips, prepared) in channel_clips.iter_mut().zip(prepared) {
+            clips.extend(prepared);
+        }
     }
+    progress.stage(Stage::Cutting, 1.0);
+    let all_syllable_clips = &channel_clips[0];
 
     log::info!("Prepared {} syllable clips", all_syllable_clips.len());
 
@@ -576,116 +1681,211 @@ fn run_sing(args: SingArgs) -> Result<()> {
     }
 
     // Compute median F0
-    let med_f0 = median_f0(&all_syllable_clips).unwrap_or(220.0);
+    let med_f0 = median_f0(all_syllable_clips).unwrap_or(220.0);
     log::info!("Median F0: {:.1} Hz", med_f0);
 
     // Apply --no-* overrides
-    let _vibrato = args.vibrato && !args.no_vibrato;
-    let chorus = args.chorus && !args.no_chorus;
+    let vibrato = args.vibrato && !args.no_vibrato;
+    let vibrato_config = VibratoConfig {
+        depth_cents: if vibrato { args.vibrato_depth.max(0.0) } else { 0.0 },
+        rate_hz: args.vibrato_rate.max(0.0),
+        onset_ms: args.vibrato_onset.max(0.0),
+        mix: args.vibrato_mix.clamp(0.0, 1.0),
+        gain_db: args.vibrato_gain,
+    };
+    let release_config = ReleaseConfig {
+        release_ms: args.release.max(0.0),
+        legato_gap_ms: args.legato_gap.max(0.0),
+    };
+    let chorus_config = ChorusConfig {
+        voices: args.chorus_voices as usize,
+        detune_cents: ChorusConfig::parse_range(&args.chorus_detune).context("--chorus-detune")?,
+        delay_ms: ChorusConfig::parse_range(&args.chorus_delay).context("--chorus-delay")?,
+        seed: args.shared.rng_seed().unwrap_or(ChorusConfig::default().seed),
+        mix: args.chorus_mix.clamp(0.0, 1.0),
+        gain_db: args.chorus_gain,
+    };
 
     // Plan note mapping
-    let chorus_prob = if chorus { 0.3 } else { 0.0 };
-    let mappings = plan_note_mapping(
-        &track.notes,
+    let mut mappings = plan_note_mapping_with(
+        &melody.notes,
         all_syllable_clips.len(),
-        args.shared.seed,
-        args.drift_range,
-        chorus_prob,
+        args.shared.rng_seed(),
+        &sing_config,
     );
+    let lyric = match &args.lyrics {
+        Some(text) => {
+            let lyric = match_lyrics(text, all_syllable_clips, language, MatchStrategy::Viterbi)?;
+            log::info!("Lyrics: {} syllables over {} notes", lyric.len(), melody.notes.len());
+            if lyric.len() > melody.notes.len() {
+                log::warn!(
+                    "Lyrics have more syllables than the melody has notes; the last {} are not sung",
+                    lyric.len() - melody.notes.len()
+                );
+            }
+            apply_lyrics(&mut mappings, &lyric);
+            lyric
+        }
+        None => Vec::new(),
+    };
+    pins.apply(&mut mappings, track.notes.len(), all_syllable_clips)?;
     log::info!("Planned {} note mappings", mappings.len());
 
-    // Render vocal track
+    // Render vocal track, one channel at a time
+    progress.stage(Stage::Assembly, 0.0);
     log::info!("Rendering vocal track");
-    let vocal_samples = render_vocal_track(
-        &mappings,
-        &all_syllable_clips,
-        med_f0,
-        sample_rate,
-    );
+    let render = |mappings: &[_], clips: &[_]| {
+        render_vocal_track_with(
+            mappings,
+            clips,
+            med_f0,
+            sample_rate,
+            &chorus_config,
+            &vibrato_config,
+            &release_config,
+        )
+    };
+    let mut vocal_channels: Vec<Vec<f64>> = channel_clips.iter().map(|clips| render(&mappings, clips)).collect();
+
+    let backing_tracks: Vec<_> = song
+        .backing
+        .iter()
+        .map(|t| arrange_track(t, period, target_duration, loop_mode))
+        .collect();
+
+    if args.harmony {
+        let chords = match &args.chords {
+            Some(path) => Some(arrange_track(&parse_midi(path)?, period, target_duration, loop_mode)),
+            None => pick_chords(&backing_tracks).map(|i| backing_tracks[i].clone()),
+        };
+        let chord_notes = match &chords {
+            Some(track) => track.notes.as_slice(),
+            None => {
+                log::warn!("No chord track found; harmony voices sing fixed intervals below the melody");
+                &[]
+            }
+        };
+        let mut voices = plan_harmony(
+            &melody.notes,
+            chord_notes,
+            args.harmony_voices as usize,
+            all_syllable_clips.len(),
+            args.shared.rng_seed(),
+            &sing_config,
+            args.harmony_jitter / 1000.0,
+        );
+        // Harmony sings the lyrics along with the lead
+        for voice in &mut voices {
+            apply_lyrics(voice, &lyric);
+        }
+        log::info!("Rendering {} harmony voices", voices.len());
+        let rendered: Vec<Vec<Vec<f64>>> = voices
+            .iter()
+            .map(|voice| channel_clips.iter().map(|clips| render(voice, clips)).collect())
+            .collect();
+        vocal_channels = mix_harmony_channels(&vocal_channels, &rendered, -6.0);
+    }
+    if loop_mode.cuts_melody(period, target_duration) {
+        for channel in &mut vocal_channels {
+            truncate_with_fade(channel, sample_rate, target_duration, bar_seconds(track.tempo));
+        }
+    }
 
-    if vocal_samples.is_empty() {
+    let vocal_len = vocal_channels[0].len();
+    if vocal_len == 0 {
         bail!("Vocal rendering produced no output");
     }
     log::info!(
         "Vocal track: {} samples ({:.1}s)",
-        vocal_samples.len(),
-        vocal_samples.len() as f64 / sample_rate as f64
+        vocal_len,
+        vocal_len as f64 / sample_rate as f64
     );
 
-    // Parse backing MIDI tracks (all .mid files except melody)
-    let mut backing_tracks = Vec::new();
-    if let Ok(entries) = std::fs::read_dir(&args.midi) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.extension().map(|e| e == "mid" || e == "midi").unwrap_or(false)
-                && path != melody_path
-            {
-                if let Ok(t) = parse_midi(&path) {
-                    backing_tracks.push(t);
-                }
-            }
-        }
-    }
-
     // Mix
+    progress.stage(Stage::Assembly, 0.7);
     log::info!("Mixing tracks");
-    let (full_mix, acappella) = mix_tracks(
-        &vocal_samples,
+    let (full_mix, acappella) = mix_tracks_channels(
+        &vocal_channels,
         sample_rate,
         &backing_tracks,
+        &instruments,
         &run_dir,
         0.0,   // vocal_db
         -12.0, // midi_db
+        !args.shared.no_limiter,
+        &args.shared.mastering(),
     )?;
+    progress.stage(Stage::Assembly, 1.0);
+    drop(progress);
 
+    let full_mix = args.shared.finish_output(&full_mix)?;
+    let acappella = args.shared.finish_output(&acappella)?;
+    let vocal_duration = vocal_len as f64 / sample_rate as f64;
+    let stems = write_stem_metadata(&run_dir, &acappella, vocal_duration, track.tempo, &melody.notes)?;
     println!("Output: {}", full_mix.display());
     println!("A cappella: {}", acappella.display());
+    println!("Stem timing: {}", stems.display());
 
-    Ok(())
+    let stats = vec![format!("Tempo: {:.0} BPM", track.tempo), format!("Notes: {}", melody.notes.len())];
+    after_run.finish("Sing", &args.shared, &run_dir, vec![full_mix, acappella], stats)
 }
 
 // ─── Speak runner ────────────────────────────────────────────────
 
 fn run_speak(args: SpeakArgs) -> Result<()> {
-    use glottisdale_core::speak::syllable_bank::build_bank;
-    use glottisdale_core::speak::target_text::{text_to_syllables, word_boundaries_from_syllables};
-    use glottisdale_core::speak::matcher::{match_syllables, match_phonemes};
-    use glottisdale_core::speak::assembler::{plan_timing, assemble};
+    use glottisdale_core::speak::syllable_bank::{build_bank, score_bank};
+    use glottisdale_core::speak::target_text::{
+        markup_to_syllables, phoneme_prosody, text_to_syllables_in, word_boundaries_from_syllables,
+    };
+    use glottisdale_core::speak::matcher::{
+        alternates, match_phonemes_in, match_syllables_with, write_matches_report, MatchStrategy,
+        DEFAULT_ALTERNATES,
+    };
+    use glottisdale_core::speak::assembler::{plan_timing_with, assemble};
 
     validate_inputs(&args.shared.input_files)?;
+    let after_run = args.shared.after_run()?;
+    let language = Language::from_code(&args.shared.language)?;
 
     if args.text.is_none() && args.reference.is_none() {
         bail!("Either --text or --reference is required");
     }
 
-    let run_dir = create_run_dir(
+    let run_dir = create_run_dir_with(
         &args.shared.output_dir,
-        args.shared.seed,
+        args.shared.rng_seed(),
         args.shared.run_name.as_deref(),
+        &NameTheme::load(&args.shared.name_theme)?,
     )?;
     println!("Run: {}", run_dir.file_name().unwrap().to_string_lossy());
+    args.shared.record_seed(&run_dir)?;
 
     let work_dir = run_dir.join("work");
-    let audio_paths = prepare_audio(&args.shared.input_files, &work_dir)?;
+    let audio_paths = prepare_audio(
+        &args.shared.input_files,
+        &args.shared.source_ranges,
+        &work_dir,
+        args.shared.denoise,
+    )?;
 
     // Build syllable bank from source audio
     log::info!("Building source syllable bank");
-    let aligner = get_aligner(&args.aligner, &args.shared.whisper_model, "en", "cpu")?;
+    let aligners = args.shared.aligners(&args.aligner)?;
     let mut all_bank_entries = Vec::new();
     let mut source_audio: HashMap<String, (Vec<f64>, u32)> = HashMap::new();
+    let progress = ProgressLine::new();
 
-    for audio_path in &audio_paths {
+    let aligned = align_sources(&audio_paths, &aligners, &progress, args.shared.min_quality)?;
+    for (audio_path, (alignment, samples, sr)) in audio_paths.iter().zip(aligned) {
         let key = audio_path.to_string_lossy().to_string();
-        let alignment = aligner.process(audio_path, None)?;
-        let entries = build_bank(&alignment.syllables, &key);
+        let mut entries = build_bank(&alignment.syllables, &key);
+        score_bank(&mut entries, &samples, sr);
         log::info!(
             "  {}: {} syllables",
             audio_path.file_name().unwrap().to_string_lossy(),
             entries.len()
         );
         all_bank_entries.extend(entries);
-
-        let (samples, sr) = read_wav(audio_path)?;
         source_audio.insert(key, (samples, sr));
     }
 
@@ -699,7 +1899,11 @@ fn run_speak(args: SpeakArgs) -> Result<()> {
         log::info!("Transcribing reference audio: {}", ref_path.display());
         let ref_wav = work_dir.join("reference_16k.wav");
         extract_audio(ref_path, &ref_wav)?;
-        let ref_alignment = aligner.process(&ref_wav, None)?;
+        let ref_aligner = get_aligner(&args.aligner, &args.shared.whisper_model, &args.shared.language, &args.shared.device)?;
+        let ref_alignment = {
+            let _span = profile::span(profile::Category::Align, "align reference");
+            ref_aligner.process(&ref_wav, None)?
+        };
         target_text = Some(ref_alignment.text);
         reference_timings = Some(
             ref_alignment
@@ -714,7 +1918,12 @@ fn run_speak(args: SpeakArgs) -> Result<()> {
     log::info!("Target text: {}", target_text);
 
     // Convert target text to syllables
-    let target_syls = text_to_syllables(&target_text);
+    // Typed text may carry markup; transcripts are plain
+    let target_syls = if reference_timings.is_some() {
+        text_to_syllables_in(&target_text, language)
+    } else {
+        markup_to_syllables(&target_text, language)?
+    };
     let word_bounds = word_boundaries_from_syllables(&target_syls);
     log::info!(
         "Target: {} syllables, {} words",
@@ -723,24 +1932,38 @@ fn run_speak(args: SpeakArgs) -> Result<()> {
     );
 
     // Match
-    log::info!("Matching ({} mode)", args.match_unit);
+    progress.stage(Stage::Matching, 0.0);
+    log::info!("Matching ({} mode, {})", args.match_unit, args.match_strategy);
     let matches = if args.match_unit == "phoneme" {
         let all_phonemes: Vec<String> = target_syls
             .iter()
             .flat_map(|ts| ts.phonemes.clone())
             .collect();
-        match_phonemes(&all_phonemes, &all_bank_entries)
+        match_phonemes_in(&all_phonemes, &all_bank_entries, language)
     } else {
         let target_phoneme_lists: Vec<Vec<String>> =
             target_syls.iter().map(|ts| ts.phonemes.clone()).collect();
         let target_stresses: Vec<Option<u8>> =
             target_syls.iter().map(|ts| ts.stress).collect();
-        match_syllables(
+        let strategy = MatchStrategy::from_name(&args.match_strategy, args.beam_width)
+            .ok_or_else(|| anyhow::anyhow!("Unknown match strategy: {}", args.match_strategy))?;
+        let matches = match_syllables_with(
             &target_phoneme_lists,
             &all_bank_entries,
             Some(&target_stresses),
             None, // use default continuity bonus
-        )
+            strategy,
+            language,
+        );
+        let alts = alternates(
+            &target_phoneme_lists,
+            &all_bank_entries,
+            Some(&target_stresses),
+            DEFAULT_ALTERNATES,
+            language,
+        );
+        write_matches_report(&run_dir.join("matches.json"), &target_syls, &matches, &alts)?;
+        matches
     };
 
     // Plan timing
@@ -750,19 +1973,30 @@ fn run_speak(args: SpeakArgs) -> Result<()> {
         all_bank_entries.iter().map(|e| e.duration()).sum::<f64>() / all_bank_entries.len() as f64
     };
 
-    let timing = plan_timing(
+    let prosody = if args.match_unit == "phoneme" {
+        phoneme_prosody(&target_syls)
+    } else {
+        target_syls.iter().map(|ts| ts.prosody).collect()
+    };
+    let timing = plan_timing_with(
         &matches,
         &word_bounds,
         avg_dur,
         reference_timings.as_deref(),
         args.timing_strictness,
+        &prosody,
     );
 
     // Apply --no-* overrides
-    let normalize_volume = args.normalize_volume && !args.no_normalize_volume;
+    let normalize_volume = (args.normalize_volume && !args.no_normalize_volume).then_some(RmsNormalization {
+        target: args.volume_target,
+        max_boost_db: args.volume_max_boost,
+        max_cut_db: args.volume_max_cut,
+    });
     let pitch_correct = args.pitch_correct && !args.no_pitch_correct;
 
     // Assemble
+    progress.stage(Stage::Assembly, 0.0);
     log::info!("Assembling output audio");
     let output_path = assemble(
         &matches,
@@ -771,12 +2005,163 @@ fn run_speak(args: SpeakArgs) -> Result<()> {
         &run_dir,
         args.crossfade,
         None, // pitch_shifts - use default
-        normalize_volume,
+        normalize_volume.as_ref(),
         pitch_correct,
+        !args.shared.no_limiter,
+        &args.shared.mastering(),
     )?;
+    progress.stage(Stage::Assembly, 1.0);
+    drop(progress);
 
+    let output_path = args.shared.finish_output(&output_path)?;
     println!("Target text: {}", target_text);
     println!("Output: {}", output_path.display());
 
+    let stats = vec![format!("Text: {}", target_text)];
+    after_run.finish("Speak", &args.shared, &run_dir, vec![output_path], stats)
+}
+
+// ─── Render runner ───────────────────────────────────────────────
+
+fn run_render(args: RenderArgs) -> Result<()> {
+    let extension = args
+        .output
+        .extension()
+        .map(|e| e.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let format = OutputFormat::parse(&extension)
+        .with_context(|| format!("Cannot pick a format for {}", args.output.display()))?;
+
+    let arrangement = load_project(&args.project, &args.relink)?;
+    if arrangement.timeline.is_empty() {
+        bail!("Project has no clips on the timeline: {}", args.project.display());
+    }
+    let settings = RenderSettings {
+        crossfade_ms: args.crossfade.unwrap_or(arrangement.crossfade_ms),
+        volume_normalize: args.volume_normalize && !args.no_volume_normalize,
+        pitch_normalize: args.pitch_normalize && !args.no_pitch_normalize,
+        pitch_range: args.pitch_range,
+        prosodic_dynamics: args.prosodic_dynamics && !args.no_prosodic_dynamics,
+        noise_level_db: args.noise_level,
+        room_tone: args.room_tone && !args.no_room_tone,
+        breaths: args.breaths && !args.no_breaths,
+        breath_probability: args.breath_probability,
+        speed: args.speed,
+        seed: args.seed,
+        limiter: !args.no_limiter,
+    };
+    log::info!(
+        "Rendering {} clips ({:.1}s) from {}",
+        arrangement.timeline.len(),
+        arrangement.total_duration_s(),
+        args.project.display()
+    );
+    export_arrangement(&arrangement, &settings, &args.output, format, &ProgressLine::new())?;
+    println!("Output: {}", args.output.display());
+    Ok(())
+}
+
+// ─── Timeline image runner ───────────────────────────────────────
+
+fn run_timeline_image(args: TimelineImageArgs) -> Result<()> {
+    let arrangement = load_project(&args.project, &args.relink)?;
+    export_timeline_image(&arrangement, &args.output, args.width.max(1))?;
+    println!("Output: {}", args.output.display());
+    Ok(())
+}
+
+// ─── Record runner ───────────────────────────────────────────────
+
+/// Width of the terminal level meter in characters.
+const METER_WIDTH: usize = 20;
+
+fn run_record(args: RecordArgs) -> Result<()> {
+    // Validate the pipeline before recording so a typo doesn't waste a take
+    if let Some(name) = args.pipeline.first() {
+        if !["collage", "sing", "speak"].contains(&name.as_str()) {
+            bail!("Unknown pipeline after --: {} (expected collage, sing, or speak)", name);
+        }
+    }
+
+    let output = args.output.clone().unwrap_or_else(|| {
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        default_output_dir()
+            .join("recordings")
+            .join(format!("recording-{}.wav", secs))
+    });
+
+    let show_meter = std::io::stderr().is_terminal();
+    eprintln!("Recording {:.0}s from the default input device...", args.duration);
+    let buffer_size = BufferSize::from_frames(args.buffer_size);
+    let (samples, sr) = record_for(args.duration, buffer_size, |elapsed, level| {
+        if show_meter {
+            let filled = (level * METER_WIDTH as f32).round() as usize;
+            let mut stderr = std::io::stderr();
+            let _ = write!(
+                stderr,
+                "\r\x1b[K{:>5.1}s / {:.1}s  [{:<width$}]",
+                elapsed,
+                args.duration,
+                "#".repeat(filled),
+                width = METER_WIDTH
+            );
+            let _ = stderr.flush();
+        }
+    })?;
+    if show_meter {
+        eprintln!();
+    }
+    save_recording(&samples, sr, &output)?;
+    println!("Recorded: {}", output.display());
+
+    let Some((name, rest)) = args.pipeline.split_first() else {
+        return Ok(());
+    };
+    let argv = ["glottisdale".to_string(), name.clone(), output.to_string_lossy().to_string()]
+        .into_iter()
+        .chain(rest.iter().cloned());
+    let cli = Cli::parse_recording_options(argv)?;
+    run_command(cli.command)
+}
+
+// ─── Diff runner ─────────────────────────────────────────────────
+
+fn run_diff(args: DiffArgs) -> Result<()> {
+    let find = |run: &Path| -> PathBuf {
+        let named = args.output_dir.join(run);
+        if !run.exists() && named.is_dir() {
+            named
+        } else {
+            run.to_path_buf()
+        }
+    };
+    let a = RunRecord::load(&find(&args.run_a))?;
+    let b = RunRecord::load(&find(&args.run_b))?;
+    if a.options.is_empty() || b.options.is_empty() {
+        log::info!("A run without config.json (from an older version or the GUI) is compared by seed and manifest only");
+    }
+    println!("{}", diff_runs(&a, &b));
+    Ok(())
+}
+
+// ─── Clean runner ────────────────────────────────────────────────
+
+fn run_clean(args: CleanArgs) -> Result<()> {
+    if !args.temp {
+        bail!("Nothing to clean: pass --temp");
+    }
+    let report = clean_temp_dirs(args.dry_run)?;
+    for path in &report.removed {
+        println!("{}{}", if args.dry_run { "Would remove " } else { "Removed " }, path.display());
+    }
+    println!(
+        "{} {} temp folder(s), {:.1} MB",
+        if args.dry_run { "Would free" } else { "Freed" },
+        report.removed.len(),
+        report.freed_bytes as f64 / 1e6
+    );
     Ok(())
 }
@@ -1,22 +1,29 @@
 //! Audio analysis: RMS energy, F0 pitch estimation, room tone detection,
+//! noise profiling, voice activity detection, syllable quality scoring,
 //! breath detection, pink noise generation.
 
+use std::ops::Range;
+
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
+use realfft::RealFftPlanner;
+
+use super::sample::Sample;
+use crate::types::Syllable;
 
 /// Compute RMS energy of the entire signal.
-pub fn compute_rms(samples: &[f64]) -> f64 {
+pub fn compute_rms<S: Sample>(samples: &[S]) -> f64 {
     if samples.is_empty() {
         return 0.0;
     }
-    let sum_sq: f64 = samples.iter().map(|s| s * s).sum();
+    let sum_sq: f64 = samples.iter().map(|s| s.to_f64() * s.to_f64()).sum();
     (sum_sq / samples.len() as f64).sqrt()
 }
 
 /// Compute RMS energy in sliding windows.
 ///
 /// Returns a Vec of RMS values, one per hop step.
-pub fn compute_rms_windowed(samples: &[f64], sr: u32, window_ms: u32, hop_ms: u32) -> Vec<f64> {
+pub fn compute_rms_windowed<S: Sample>(samples: &[S], sr: u32, window_ms: u32, hop_ms: u32) -> Vec<f64> {
     let window_samples = (sr as usize * window_ms as usize) / 1000;
     let hop_samples = (sr as usize * hop_ms as usize) / 1000;
 
@@ -30,7 +37,7 @@ pub fn compute_rms_windowed(samples: &[f64], sr: u32, window_ms: u32, hop_ms: u3
     for i in 0..n_frames {
         let start = i * hop_samples;
         let frame = &samples[start..start + window_samples];
-        let sum_sq: f64 = frame.iter().map(|s| s * s).sum();
+        let sum_sq: f64 = frame.iter().map(|s| s.to_f64() * s.to_f64()).sum();
         rms.push((sum_sq / window_samples as f64).sqrt());
     }
 
@@ -43,7 +50,7 @@ pub fn compute_rms_windowed(samples: &[f64], sr: u32, window_ms: u32, hop_ms: u3
 /// then finds the longest contiguous run of quiet frames.
 ///
 /// Returns `Some((start_s, end_s))` or `None` if no suitable region is found.
-pub fn find_room_tone(samples: &[f64], sr: u32, min_duration_ms: u32) -> Option<(f64, f64)> {
+pub fn find_room_tone<S: Sample>(samples: &[S], sr: u32, min_duration_ms: u32) -> Option<(f64, f64)> {
     let min_samples = (sr as usize * min_duration_ms as usize) / 1000;
     if samples.len() < min_samples {
         return None;
@@ -101,13 +108,278 @@ pub fn find_room_tone(samples: &[f64], sr: u32, min_duration_ms: u32) -> Option<
     Some((start_s, end_s))
 }
 
+/// Frame length and hop for voice activity detection.
+const VAD_FRAME_MS: u32 = 20;
+const VAD_HOP_MS: u32 = 10;
+
+/// Frames this far above the noise floor (dB) are speech on energy alone.
+const VAD_ENERGY_DB: f64 = 10.0;
+
+/// Quieter frames still count as speech (fricatives like "s") when this
+/// far above the floor and crossing zero on at least this share of samples.
+const VAD_FRICATIVE_DB: f64 = 4.0;
+const VAD_FRICATIVE_ZCR: f64 = 0.25;
+
+/// Pauses shorter than this stay inside one speech region.
+const VAD_HANGOVER_MS: f64 = 300.0;
+
+/// Silence kept on each side of a speech region.
+const VAD_PAD_MS: f64 = 150.0;
+
+/// Regions shorter than this are dropped as clicks.
+const VAD_MIN_SPEECH_MS: f64 = 100.0;
+
+/// Pauses at least this long always end a speech chunk.
+const CHUNK_BREAK_MS: f64 = 2000.0;
+
+/// Find the stretches of `samples` that contain speech.
+///
+/// A frame is speech when its energy is well above the recording's noise
+/// floor (its quietest 10% of frames), or moderately above it with a high
+/// zero-crossing rate. Short pauses are bridged, each region is padded,
+/// and blips are dropped. Returns sorted, non-overlapping sample ranges.
+pub fn detect_speech<S: Sample>(samples: &[S], sr: u32) -> Vec<Range<usize>> {
+    let rms = compute_rms_windowed(samples, sr, VAD_FRAME_MS, VAD_HOP_MS);
+    if rms.is_empty() {
+        return Vec::new();
+    }
+    let frame_len = (sr as usize * VAD_FRAME_MS as usize) / 1000;
+    let hop = (sr as usize * VAD_HOP_MS as usize) / 1000;
+
+    let mut sorted = rms.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let floor = sorted[sorted.len() / 10].max(1e-5);
+    let loudest = sorted[sorted.len() - 1];
+    // Recordings without pauses have no true floor; never demand more
+    // than 20 dB under the loudest frame
+    let energy_threshold = (floor * 10f64.powf(VAD_ENERGY_DB / 20.0)).min(loudest * 0.1).max(1e-5);
+    let fricative_threshold = (floor * 10f64.powf(VAD_FRICATIVE_DB / 20.0)).min(energy_threshold);
+
+    let is_speech = |i: usize, level: f64| {
+        if level >= energy_threshold {
+            return true;
+        }
+        if level < fricative_threshold {
+            return false;
+        }
+        let frame = &samples[i * hop..i * hop + frame_len];
+        let crossings = frame.windows(2).filter(|w| (w[0].to_f64() >= 0.0) != (w[1].to_f64() >= 0.0)).count();
+        crossings as f64 / frame_len as f64 >= VAD_FRICATIVE_ZCR
+    };
+
+    let ms = |ms: f64| (ms * sr as f64 / 1000.0) as usize;
+    let mut regions: Vec<Range<usize>> = Vec::new();
+    for (i, &level) in rms.iter().enumerate() {
+        if !is_speech(i, level) {
+            continue;
+        }
+        let (start, end) = (i * hop, i * hop + frame_len);
+        match regions.last_mut() {
+            Some(last) if start <= last.end + ms(VAD_HANGOVER_MS) => last.end = end,
+            _ => regions.push(start..end),
+        }
+    }
+
+    let mut padded: Vec<Range<usize>> = Vec::new();
+    for region in regions.into_iter().filter(|r| r.len() >= ms(VAD_MIN_SPEECH_MS)) {
+        let start = region.start.saturating_sub(ms(VAD_PAD_MS));
+        let end = (region.end + ms(VAD_PAD_MS)).min(samples.len());
+        match padded.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => padded.push(start..end),
+        }
+    }
+    padded
+}
+
+/// Group speech regions into chunks of at most `max_chunk_s` for
+/// alignment.
+///
+/// Neighbouring regions share a chunk unless a pause of two seconds or
+/// more separates them; regions longer than a chunk are cut into pieces.
+pub fn speech_chunks(regions: &[Range<usize>], sr: u32, max_chunk_s: f64) -> Vec<Range<usize>> {
+    let max_len = ((max_chunk_s * sr as f64) as usize).max(1);
+    let break_gap = (CHUNK_BREAK_MS * sr as f64 / 1000.0) as usize;
+    let mut chunks: Vec<Range<usize>> = Vec::new();
+    for region in regions {
+        let mut start = region.start;
+        while start < region.end {
+            let end = (start + max_len).min(region.end);
+            match chunks.last_mut() {
+                Some(last) if start - last.end < break_gap && end - last.start <= max_len => last.end = end,
+                _ => chunks.push(start..end),
+            }
+            start = end;
+        }
+    }
+    chunks
+}
+
+/// Shortest room tone a noise profile is learned from.
+const NOISE_PROFILE_MIN_MS: u32 = 250;
+
+/// A bin counts as noise while its magnitude stays within this many
+/// standard deviations above its mean in the room tone.
+const NOISE_THRESHOLD_STDS: f64 = 1.5;
+
+/// FFT size for spectral de-noising: about 32 ms at `sr`.
+pub(crate) fn denoise_fft_size(sr: u32) -> usize {
+    ((sr as f64 * 0.032) as usize).next_power_of_two().max(64)
+}
+
+/// Periodic Hann window of length `n`.
+pub(crate) fn hann_window(n: usize) -> Vec<f64> {
+    (0..n)
+        .map(|i| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / n as f64).cos())
+        .collect()
+}
+
+/// Per-frequency noise floor of a recording, learned from its room tone
+/// and used by [`spectral_gate`](super::effects::spectral_gate).
+#[derive(Debug, Clone)]
+pub struct NoiseProfile {
+    /// FFT size the profile was measured with.
+    pub fft_size: usize,
+    /// Magnitude per FFT bin below which the bin is treated as noise.
+    pub thresholds: Vec<f64>,
+}
+
+/// Sample range of the room tone a noise profile should be learned from
+/// (see [`find_room_tone`]).
+pub fn noise_region<S: Sample>(samples: &[S], sr: u32) -> Option<Range<usize>> {
+    let (start_s, end_s) = find_room_tone(samples, sr, NOISE_PROFILE_MIN_MS)?;
+    let start = (start_s * sr as f64) as usize;
+    let end = ((end_s * sr as f64) as usize).min(samples.len());
+    (start < end).then_some(start..end)
+}
+
+/// Measure a noise profile from noise-only audio.
+///
+/// Returns `None` when `noise` is shorter than one FFT frame.
+pub fn noise_profile(noise: &[f64], sr: u32) -> Option<NoiseProfile> {
+    let n = denoise_fft_size(sr);
+    if noise.len() < n {
+        return None;
+    }
+    let window = hann_window(n);
+    let fft = RealFftPlanner::<f64>::new().plan_fft_forward(n);
+    let mut input = fft.make_input_vec();
+    let mut spectrum = fft.make_output_vec();
+    let mut sum = vec![0.0; spectrum.len()];
+    let mut sum_sq = vec![0.0; spectrum.len()];
+    let mut frames = 0usize;
+    for start in (0..=noise.len() - n).step_by(n / 4) {
+        for ((x, &s), &w) in input.iter_mut().zip(&noise[start..start + n]).zip(&window) {
+            *x = s * w;
+        }
+        fft.process(&mut input, &mut spectrum).ok()?;
+        for ((c, total), total_sq) in spectrum.iter().zip(&mut sum).zip(&mut sum_sq) {
+            let magnitude = c.norm();
+            *total += magnitude;
+            *total_sq += magnitude * magnitude;
+        }
+        frames += 1;
+    }
+    let thresholds = sum
+        .iter()
+        .zip(&sum_sq)
+        .map(|(&total, &total_sq)| {
+            let mean = total / frames as f64;
+            let std = (total_sq / frames as f64 - mean * mean).max(0.0).sqrt();
+            mean + NOISE_THRESHOLD_STDS * std
+        })
+        .collect();
+    Some(NoiseProfile { fft_size: n, thresholds })
+}
+
+/// Learn a noise profile from the room tone in `samples`.
+///
+/// Returns `None` when the recording has no usable stretch of room tone.
+pub fn learn_noise_profile(samples: &[f64], sr: u32) -> Option<NoiseProfile> {
+    noise_profile(&samples[noise_region(samples, sr)?], sr)
+}
+
+/// YIN dips below this aperiodicity are clearly voiced.
+const YIN_THRESHOLD: f64 = 0.15;
+
+/// Frames whose best YIN dip is above this are treated as unvoiced.
+const YIN_MAX_APERIODICITY: f64 = 0.35;
+
+/// Estimate fundamental frequency, in Hz.
+///
+/// Uses YIN (`estimate_f0_yin`), falling back to autocorrelation
+/// (`estimate_f0_autocorr`) for frames YIN finds too aperiodic.
+/// Returns `None` for silence, noise, or weak periodicity.
+pub fn estimate_f0<S: Sample>(samples: &[S], sr: u32, f0_min: u32, f0_max: u32) -> Option<f64> {
+    estimate_f0_yin(samples, sr, f0_min, f0_max).or_else(|| estimate_f0_autocorr(samples, sr, f0_min, f0_max))
+}
+
+/// Estimate fundamental frequency with YIN (de Cheveigné & Kawahara, 2002).
+///
+/// Computes the cumulative mean normalized difference function, takes the
+/// first dip below an aperiodicity threshold (or, for breathy frames, the
+/// first dip close to the deepest one), and refines its lag with parabolic
+/// interpolation. Taking the earliest good dip avoids the octave-down
+/// errors autocorrelation makes on breathy voices.
+///
+/// Returns F0 in Hz, or `None` when no lag is periodic enough.
+pub fn estimate_f0_yin<S: Sample>(samples: &[S], sr: u32, f0_min: u32, f0_max: u32) -> Option<f64> {
+    if samples.is_empty() || f0_min == 0 || f0_max == 0 || compute_rms(samples) < 1e-6 {
+        return None;
+    }
+    let lag_min = (sr / f0_max).max(2) as usize;
+    let lag_max = (sr / f0_min) as usize;
+    // Every lag is compared over the same window, so at most half the frame
+    let lag_max = lag_max.min(samples.len() / 2);
+    if lag_min + 1 >= lag_max {
+        return None;
+    }
+
+    let mean = samples.iter().map(|s| s.to_f64()).sum::<f64>() / samples.len() as f64;
+    let x: Vec<f64> = samples.iter().map(|s| s.to_f64() - mean).collect();
+    let window = x.len() - lag_max;
+
+    // Cumulative mean normalized difference, d'(0) = 1
+    let mut cmnd = vec![1.0; lag_max + 1];
+    let mut running = 0.0;
+    for lag in 1..=lag_max {
+        let d: f64 = x[..window].iter().zip(&x[lag..lag + window]).map(|(a, b)| (a - b) * (a - b)).sum();
+        running += d;
+        cmnd[lag] = if running > 0.0 { d * lag as f64 / running } else { 1.0 };
+    }
+
+    let deepest = cmnd[lag_min..=lag_max].iter().copied().fold(f64::INFINITY, f64::min);
+    if deepest > YIN_MAX_APERIODICITY {
+        return None;
+    }
+    let threshold = YIN_THRESHOLD.max(deepest + 0.05);
+    let mut lag = (lag_min..=lag_max).find(|&lag| cmnd[lag] < threshold)?;
+    while lag < lag_max && cmnd[lag + 1] < cmnd[lag] {
+        lag += 1;
+    }
+
+    let refined = if lag > 1 && lag < lag_max {
+        let (a, b, c) = (cmnd[lag - 1], cmnd[lag], cmnd[lag + 1]);
+        let curvature = a - 2.0 * b + c;
+        if curvature > 0.0 {
+            lag as f64 + 0.5 * (a - c) / curvature
+        } else {
+            lag as f64
+        }
+    } else {
+        lag as f64
+    };
+    Some(sr as f64 / refined)
+}
+
 /// Estimate fundamental frequency using autocorrelation.
 ///
 /// Finds the first autocorrelation peak above a periodicity threshold,
 /// searching from the shortest lag (highest frequency) to avoid octave errors.
+/// Prefer `estimate_f0`, which only falls back to this when YIN fails.
 ///
 /// Returns F0 in Hz, or `None` for silence, noise, or weak periodicity.
-pub fn estimate_f0(samples: &[f64], sr: u32, f0_min: u32, f0_max: u32) -> Option<f64> {
+pub fn estimate_f0_autocorr<S: Sample>(samples: &[S], sr: u32, f0_min: u32, f0_max: u32) -> Option<f64> {
     if samples.is_empty() {
         return None;
     }
@@ -125,8 +397,8 @@ pub fn estimate_f0(samples: &[f64], sr: u32, f0_min: u32, f0_max: u32) -> Option
     }
 
     // Remove DC offset
-    let mean: f64 = samples.iter().sum::<f64>() / samples.len() as f64;
-    let x: Vec<f64> = samples.iter().map(|s| s - mean).collect();
+    let mean: f64 = samples.iter().map(|s| s.to_f64()).sum::<f64>() / samples.len() as f64;
+    let x: Vec<f64> = samples.iter().map(|s| s.to_f64() - mean).collect();
 
     let autocorr_0: f64 = x.iter().map(|v| v * v).sum();
     if autocorr_0 < 1e-12 {
@@ -166,12 +438,103 @@ pub fn estimate_f0(samples: &[f64], sr: u32, f0_min: u32, f0_max: u32) -> Option
     None
 }
 
+/// Syllables at or below this RMS level (dBFS) score zero for level,
+/// and at or above the upper one score full marks.
+const QUALITY_SILENT_DB: f64 = -50.0;
+const QUALITY_LOUD_DB: f64 = -35.0;
+
+/// Samples this close to full scale count as clipped; a syllable with
+/// this share of clipped samples scores zero for clipping.
+const QUALITY_CLIP_LEVEL: f64 = 0.99;
+const QUALITY_CLIP_MAX_RATIO: f64 = 0.02;
+
+/// Pitch jumps between thirds of a syllable (semitones) that are fine,
+/// and those that score zero (crosstalk, another speaker, misalignment).
+const QUALITY_PITCH_OK_ST: f64 = 2.0;
+const QUALITY_PITCH_BAD_ST: f64 = 7.0;
+
+/// Plausible syllable lengths in seconds: full marks between the inner
+/// pair, ramping to zero at the outer pair.
+const QUALITY_DURATION_S: [f64; 4] = [0.03, 0.06, 0.6, 1.5];
+
+/// How usable a syllable's audio is.
+///
+/// Each check scores from 0 (unusable) to 1 (fine); `score` combines them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SyllableQuality {
+    /// Loud enough to be speech rather than silence or a misalignment.
+    pub level: f64,
+    /// Free of samples pinned at full scale.
+    pub clipping: f64,
+    /// Pitch holds steady across the syllable rather than jumping.
+    pub pitch_stability: f64,
+    /// Long enough, and short enough, to be one syllable.
+    pub duration: f64,
+}
+
+impl SyllableQuality {
+    /// Overall quality from 0 to 1: the product of the checks, so any
+    /// one failing sinks the syllable.
+    pub fn score(&self) -> f64 {
+        self.level * self.clipping * self.pitch_stability * self.duration
+    }
+}
+
+/// Linear ramp from 0 at `zero` to 1 at `one` (either order), clamped.
+fn ramp(x: f64, zero: f64, one: f64) -> f64 {
+    ((x - zero) / (one - zero)).clamp(0.0, 1.0)
+}
+
+/// Score the audio of one syllable, cut exactly to its boundaries.
+pub fn assess_quality<S: Sample>(samples: &[S], sr: u32) -> SyllableQuality {
+    let duration_s = samples.len() as f64 / sr as f64;
+    let [too_short, short, long, too_long] = QUALITY_DURATION_S;
+    let duration = ramp(duration_s, too_short, short).min(ramp(duration_s, too_long, long));
+    if samples.is_empty() {
+        return SyllableQuality { level: 0.0, clipping: 1.0, pitch_stability: 1.0, duration };
+    }
+
+    let level_db = 20.0 * compute_rms(samples).max(1e-10).log10();
+    let level = ramp(level_db, QUALITY_SILENT_DB, QUALITY_LOUD_DB);
+
+    let clipped = samples.iter().filter(|s| s.to_f64().abs() >= QUALITY_CLIP_LEVEL).count();
+    let clipping = 1.0 - (clipped as f64 / samples.len() as f64 / QUALITY_CLIP_MAX_RATIO).min(1.0);
+
+    // Unvoiced syllables have too little pitch to judge and are not penalized
+    let third = samples.len() / 3;
+    let f0s: Vec<f64> = if third == 0 {
+        Vec::new()
+    } else {
+        samples.chunks_exact(third).take(3).filter_map(|part| estimate_f0(part, sr, 80, 600)).collect()
+    };
+    let max_jump = f0s.windows(2).map(|w| (12.0 * (w[1] / w[0]).log2()).abs()).fold(0.0, f64::max);
+    let pitch_stability = ramp(max_jump, QUALITY_PITCH_BAD_ST, QUALITY_PITCH_OK_ST);
+
+    SyllableQuality { level, clipping, pitch_stability, duration }
+}
+
+/// Score a syllable from its source recording.
+pub fn syllable_quality<S: Sample>(source: &[S], sr: u32, syllable: &Syllable) -> SyllableQuality {
+    let to_sample = |t: f64| ((t.max(0.0) * sr as f64) as usize).min(source.len());
+    let (start, end) = (to_sample(syllable.start), to_sample(syllable.end));
+    assess_quality(&source[start..end.max(start)], sr)
+}
+
+/// Drop syllables scoring below `min_quality`. Returns how many went.
+pub fn retain_quality_syllables(syllables: &mut Vec<Syllable>, source: &[f64], sr: u32, min_quality: f64) -> usize {
+    let before = syllables.len();
+    if min_quality > 0.0 {
+        syllables.retain(|s| syllable_quality(source, sr, s).score() >= min_quality);
+    }
+    before - syllables.len()
+}
+
 /// Find breath-like sounds in inter-word gaps.
 ///
 /// A breath is an inter-word gap in [min_gap_ms, max_gap_ms] whose RMS
 /// is between 1% and 30% of the speech RMS level.
-pub fn find_breaths(
-    samples: &[f64],
+pub fn find_breaths<S: Sample>(
+    samples: &[S],
     sr: u32,
     word_boundaries: &[(f64, f64)],
     min_gap_ms: u32,
@@ -242,60 +605,76 @@ pub fn generate_pink_noise(duration_s: f64, sr: u32, seed: Option<u64>) -> Vec<f
         return vec![];
     }
 
-    let mut rng = match seed {
-        Some(s) => StdRng::seed_from_u64(s),
-        None => StdRng::from_entropy(),
-    };
+    let mut noise = PinkNoise::new(seed);
+    let mut output: Vec<f64> = (0..n_samples).map(|_| noise.next_sample()).collect();
+
+    // Normalize to [-1, 1]
+    let peak = output.iter().map(|v| v.abs()).fold(0.0f64, f64::max);
+    if peak > 0.0 {
+        for v in output.iter_mut() {
+            *v /= peak;
+        }
+    }
 
-    voss_mccartney_pink_noise(n_samples, &mut rng)
+    output
 }
 
-/// Voss-McCartney algorithm for pink noise generation.
+/// Unnormalized pink noise, one sample at a time (Voss-McCartney).
 ///
 /// Uses multiple random number generators at different update rates
-/// to approximate 1/f spectrum without FFT.
-fn voss_mccartney_pink_noise(n_samples: usize, rng: &mut StdRng) -> Vec<f64> {
-    const NUM_ROWS: usize = 16;
-    let mut rows = [0.0f64; NUM_ROWS];
-    let mut running_sum = 0.0f64;
+/// to approximate 1/f spectrum without FFT. [`generate_pink_noise`] is this
+/// generator's first `n` samples divided by their peak, so a streaming
+/// caller can reproduce it with two passes over the same seed.
+pub struct PinkNoise {
+    rng: StdRng,
+    rows: [f64; PINK_NOISE_ROWS],
+    running_sum: f64,
+    index: usize,
+}
 
-    // Initialize
-    for row in rows.iter_mut() {
-        *row = rng.gen_range(-1.0..1.0);
-        running_sum += *row;
+const PINK_NOISE_ROWS: usize = 16;
+
+impl PinkNoise {
+    pub fn new(seed: Option<u64>) -> Self {
+        let mut rng = match seed {
+            Some(s) => StdRng::seed_from_u64(s),
+            None => StdRng::from_entropy(),
+        };
+        let mut rows = [0.0f64; PINK_NOISE_ROWS];
+        let mut running_sum = 0.0f64;
+        for row in rows.iter_mut() {
+            *row = rng.gen_range(-1.0..1.0);
+            running_sum += *row;
+        }
+        Self { rng, rows, running_sum, index: 0 }
     }
 
-    let mut output = Vec::with_capacity(n_samples);
-
-    for i in 0..n_samples {
-        // Determine which rows to update based on trailing zeros of index
-        let mut changed = i;
+    pub fn next_sample(&mut self) -> f64 {
+        // Determine which row to update based on trailing zeros of the index
+        let mut changed = self.index;
         let mut num_zeros = 0;
         while changed != 0 && (changed & 1) == 0 {
             num_zeros += 1;
             changed >>= 1;
         }
+        self.index += 1;
 
-        if num_zeros < NUM_ROWS {
-            running_sum -= rows[num_zeros];
-            rows[num_zeros] = rng.gen_range(-1.0..1.0);
-            running_sum += rows[num_zeros];
+        if num_zeros < PINK_NOISE_ROWS {
+            self.running_sum -= self.rows[num_zeros];
+            self.rows[num_zeros] = self.rng.gen_range(-1.0..1.0);
+            self.running_sum += self.rows[num_zeros];
         }
 
         // Add white noise component for high-frequency content
-        let white = rng.gen_range(-1.0..1.0);
-        output.push(running_sum + white);
+        let white: f64 = self.rng.gen_range(-1.0..1.0);
+        self.running_sum + white
     }
 
-    // Normalize to [-1, 1]
-    let peak = output.iter().map(|v| v.abs()).fold(0.0f64, f64::max);
-    if peak > 0.0 {
-        for v in output.iter_mut() {
-            *v /= peak;
-        }
+    /// Peak absolute value of the first `n_samples` samples for `seed`.
+    pub fn peak(seed: u64, n_samples: usize) -> f64 {
+        let mut noise = Self::new(Some(seed));
+        (0..n_samples).map(|_| noise.next_sample().abs()).fold(0.0f64, f64::max)
     }
-
-    output
 }
 
 #[cfg(test)]
@@ -304,7 +683,7 @@ mod tests {
 
     #[test]
     fn test_compute_rms_silence() {
-        assert_eq!(compute_rms(&[]), 0.0);
+        assert_eq!(compute_rms::<f64>(&[]), 0.0);
         assert_eq!(compute_rms(&[0.0; 100]), 0.0);
     }
 
@@ -360,11 +739,86 @@ mod tests {
     fn test_estimate_f0_silence() {
         let samples = vec![0.0; 16000];
         assert!(estimate_f0(&samples, 16000, 50, 400).is_none());
+        assert!(estimate_f0_yin(&samples, 16000, 50, 400).is_none());
     }
 
     #[test]
     fn test_estimate_f0_empty() {
-        assert!(estimate_f0(&[], 16000, 50, 400).is_none());
+        assert!(estimate_f0::<f64>(&[], 16000, 50, 400).is_none());
+        assert!(estimate_f0_yin::<f64>(&[], 16000, 50, 400).is_none());
+    }
+
+    #[test]
+    fn test_estimate_f0_yin_rejects_noise() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let noise: Vec<f64> = (0..1024).map(|_| rng.gen_range(-0.5..0.5)).collect();
+        assert!(estimate_f0_yin(&noise, 16000, 80, 600).is_none());
+    }
+
+    #[test]
+    fn test_estimate_f0_yin_breathy_voice_no_octave_error() {
+        // Weak fundamental under a strong second harmonic, plus breath noise
+        let sr = 16000;
+        let mut rng = StdRng::seed_from_u64(4);
+        let samples: Vec<f64> = (0..640)
+            .map(|i| {
+                let t = i as f64 / sr as f64 * std::f64::consts::TAU;
+                0.3 * (120.0 * t).sin() + 0.6 * (240.0 * t).sin() + 0.2 * (360.0 * t).sin()
+                    + rng.gen_range(-0.15..0.15)
+            })
+            .collect();
+        let f0 = estimate_f0_yin(&samples, sr, 80, 600).unwrap();
+        assert!((f0 - 120.0).abs() < 3.0, "got {} Hz", f0);
+    }
+
+    mod props {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// A tone with harmonics whose pitch wobbles by `depth_st` semitones
+        /// at `rate_hz`, and its mean frequency over the frame.
+        fn vibrato_tone(f0: f64, depth_st: f64, rate_hz: f64, phase: f64, sr: u32, n: usize) -> (Vec<f64>, f64) {
+            let mut angle = 0.0;
+            let mut freqs = 0.0;
+            let samples = (0..n)
+                .map(|i| {
+                    let t = i as f64 / sr as f64;
+                    let freq = f0 * 2f64.powf(depth_st / 12.0 * (std::f64::consts::TAU * rate_hz * t + phase).sin());
+                    freqs += freq;
+                    angle += std::f64::consts::TAU * freq / sr as f64;
+                    0.5 * angle.sin() + 0.25 * (2.0 * angle).sin() + 0.1 * (3.0 * angle).sin()
+                })
+                .collect();
+            (samples, freqs / n as f64)
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(48))]
+
+            #[test]
+            fn yin_tracks_vibrato_tones(
+                f0 in 85.0f64..500.0,
+                depth_st in 0.0f64..0.5,
+                rate_hz in 4.0f64..7.0,
+                phase in 0.0f64..std::f64::consts::TAU,
+                sr in prop::sample::select(vec![16000u32, 22050, 44100]),
+            ) {
+                // 40ms frames, as used for pitch tracking
+                let n = sr as usize / 25;
+                let (samples, mean_hz) = vibrato_tone(f0, depth_st, rate_hz, phase, sr, n);
+                let estimate = estimate_f0_yin(&samples, sr, 80, 600);
+                prop_assert!(estimate.is_some(), "no pitch for {:.1} Hz", mean_hz);
+                let error_st = 12.0 * (estimate.unwrap() / mean_hz).log2().abs();
+                prop_assert!(error_st < 0.5, "{:.1} Hz tone read as {:?} Hz", mean_hz, estimate);
+            }
+
+            #[test]
+            fn yin_is_accurate_on_steady_tones(f0 in 85.0f64..500.0) {
+                let (samples, _) = vibrato_tone(f0, 0.0, 5.0, 0.0, 16000, 1024);
+                let estimate = estimate_f0_yin(&samples, 16000, 80, 600).unwrap();
+                prop_assert!((estimate - f0).abs() / f0 < 0.01, "{:.2} Hz read as {:.2} Hz", f0, estimate);
+            }
+        }
     }
 
     #[test]
@@ -402,7 +856,7 @@ mod tests {
 
     #[test]
     fn test_find_breaths_empty() {
-        assert!(find_breaths(&[], 16000, &[], 200, 600).is_empty());
+        assert!(find_breaths::<f64>(&[], 16000, &[], 200, 600).is_empty());
         assert!(find_breaths(&[0.0; 100], 16000, &[(0.0, 0.5)], 200, 600).is_empty());
     }
 
@@ -426,9 +880,107 @@ mod tests {
         assert_eq!(a, b);
     }
 
+    #[test]
+    fn test_pink_noise_stream_matches_generate() {
+        let expected = generate_pink_noise(0.25, 16000, Some(9));
+        let peak = PinkNoise::peak(9, expected.len());
+        let mut noise = PinkNoise::new(Some(9));
+        for e in &expected {
+            assert_eq!((noise.next_sample() / peak).to_bits(), e.to_bits());
+        }
+    }
+
     #[test]
     fn test_generate_pink_noise_empty() {
         let noise = generate_pink_noise(0.0, 16000, None);
         assert!(noise.is_empty());
     }
+    fn tone(hz: impl Fn(f64) -> f64, amplitude: f64, seconds: f64) -> Vec<f64> {
+        let sr = 16000.0;
+        let mut phase = 0.0;
+        (0..(seconds * sr) as usize)
+            .map(|i| {
+                phase += hz(i as f64 / sr) / sr * std::f64::consts::TAU;
+                amplitude * phase.sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_assess_quality_clean_syllable_scores_high() {
+        let quality = assess_quality(&tone(|_| 150.0, 0.3, 0.25), 16000);
+        assert!(quality.score() > 0.95, "{:?}", quality);
+    }
+
+    #[test]
+    fn test_assess_quality_flags_each_problem() {
+        let sr = 16000;
+        let quiet = assess_quality(&tone(|_| 150.0, 0.001, 0.25), sr);
+        assert_eq!(quiet.level, 0.0);
+        assert_eq!(quiet.score(), 0.0);
+
+        let clipped: Vec<f64> = tone(|_| 150.0, 3.0, 0.25).iter().map(|s| s.clamp(-1.0, 1.0)).collect();
+        assert_eq!(assess_quality(&clipped, sr).clipping, 0.0);
+
+        // An octave jump partway through, like a second speaker
+        let jumpy = assess_quality(&tone(|t| if t < 0.125 { 120.0 } else { 240.0 }, 0.3, 0.25), sr);
+        assert!(jumpy.pitch_stability < 0.1, "{:?}", jumpy);
+        assert!(jumpy.level > 0.99);
+
+        assert_eq!(assess_quality(&tone(|_| 150.0, 0.3, 0.01), sr).duration, 0.0);
+        assert_eq!(assess_quality(&tone(|_| 150.0, 0.3, 2.0), sr).duration, 0.0);
+        assert_eq!(assess_quality::<f64>(&[], sr).score(), 0.0);
+    }
+
+    #[test]
+    fn test_retain_quality_syllables() {
+        let sr = 16000;
+        let mut source = tone(|_| 150.0, 0.3, 0.5);
+        source.extend(vec![0.0; 8000]);
+        let syllable = |start: f64, end: f64| Syllable {
+            phonemes: vec![],
+            start,
+            end,
+            word: "la".into(),
+            word_index: 0,
+        };
+        let mut syllables = vec![syllable(0.0, 0.25), syllable(0.6, 0.9), syllable(0.25, 0.5)];
+        assert_eq!(retain_quality_syllables(&mut syllables.clone(), &source, sr, 0.0), 0);
+        assert_eq!(retain_quality_syllables(&mut syllables, &source, sr, 0.5), 1);
+        assert_eq!(syllables.len(), 2);
+        assert!(syllables.iter().all(|s| s.start < 0.5));
+    }
+
+    #[test]
+    fn test_detect_speech_trims_silence() {
+        let sr = 16000;
+        let mut rng = StdRng::seed_from_u64(3);
+        // 1s hiss, 1s tone, 3s hiss, 0.5s tone, 1s hiss
+        let mut samples: Vec<f64> = (0..sr as usize * 13 / 2).map(|_| rng.gen_range(-0.002..0.002)).collect();
+        for (from, to) in [(16000, 32000), (80000, 88000)] {
+            for (i, s) in samples[from..to].iter_mut().enumerate() {
+                *s += 0.3 * (i as f64 / sr as f64 * 220.0 * std::f64::consts::TAU).sin();
+            }
+        }
+        let regions = detect_speech(&samples, sr);
+        assert_eq!(regions.len(), 2, "{:?}", regions);
+        assert!(regions[0].start < 16000 && regions[0].start > 12000, "{:?}", regions);
+        assert!(regions[0].end > 32000 && regions[0].end < 36000, "{:?}", regions);
+        assert!(regions[1].start < 80000 && regions[1].end > 88000, "{:?}", regions);
+
+        assert!(detect_speech(&vec![0.0; 16000], sr).is_empty());
+        assert!(detect_speech::<f64>(&[], sr).is_empty());
+    }
+
+    #[test]
+    fn test_speech_chunks_split_on_long_pauses_and_length() {
+        let sr = 1000;
+        // Short pause merges, long pause splits
+        let regions = vec![0..1000, 1500..2000, 5000..6000];
+        assert_eq!(speech_chunks(&regions, sr, 60.0), vec![0..2000, 5000..6000]);
+        // Chunks never exceed the maximum length
+        assert_eq!(speech_chunks(&regions, sr, 1.5), vec![0..1000, 1500..2000, 5000..6000]);
+        assert_eq!(speech_chunks(std::slice::from_ref(&(0..2500)), sr, 1.0), vec![0..1000, 1000..2000, 2000..2500]);
+        assert!(speech_chunks(&[], sr, 1.0).is_empty());
+    }
 }
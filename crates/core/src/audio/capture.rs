@@ -0,0 +1,196 @@
+//! Audio capture from the default input device via cpal.
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+
+use super::device::{build_with_fallback, BufferSize};
+use super::io::write_wav;
+
+/// Longest recording accepted (seconds), to bound memory use.
+pub const MAX_RECORDING_S: f64 = 30.0 * 60.0;
+
+/// State shared with the input stream callback.
+#[derive(Default)]
+struct Shared {
+    samples: Mutex<Vec<f64>>,
+    /// Peak of the most recent callback buffer (0–1).
+    level: Mutex<f32>,
+    error: Mutex<Option<String>>,
+}
+
+/// A recording in progress on the default input device.
+///
+/// Input is mixed to mono as it arrives. Dropping the recorder stops the
+/// stream and discards the audio; call [`Recorder::finish`] to keep it.
+pub struct Recorder {
+    _stream: cpal::Stream,
+    shared: Arc<Shared>,
+    sample_rate: u32,
+}
+
+impl Recorder {
+    /// Open the default input device and start recording.
+    pub fn start() -> Result<Self> {
+        Self::start_with_buffer(BufferSize::Default)
+    }
+
+    /// Start recording with a specific device buffer size.
+    pub fn start_with_buffer(buffer_size: BufferSize) -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_input_device()
+            .ok_or_else(|| anyhow!("No audio input device found"))?;
+        let supported = device
+            .default_input_config()
+            .context("Failed to query input device configuration")?;
+        let sample_format = supported.sample_format();
+        let mut config: cpal::StreamConfig = supported.config();
+        config.buffer_size = buffer_size.to_cpal(supported.buffer_size());
+        let shared = Arc::new(Shared::default());
+
+        let stream = build_with_fallback(&config, |config| match sample_format {
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, config, &shared),
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, config, &shared),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, config, &shared),
+            cpal::SampleFormat::I32 => build_stream::<i32>(&device, config, &shared),
+            other => bail!("Unsupported input sample format: {:?}", other),
+        })?;
+        stream.play().context("Failed to start recording")?;
+
+        Ok(Self {
+            _stream: stream,
+            shared,
+            sample_rate: config.sample_rate.0,
+        })
+    }
+
+    /// Sample rate of the recorded audio.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Seconds recorded so far.
+    pub fn elapsed_s(&self) -> f64 {
+        self.shared.samples.lock().unwrap().len() as f64 / self.sample_rate as f64
+    }
+
+    /// Peak level of the latest input buffer (0–1), for a level meter.
+    pub fn level(&self) -> f32 {
+        *self.shared.level.lock().unwrap()
+    }
+
+    /// Stream error reported by the device, if any.
+    pub fn error(&self) -> Option<String> {
+        self.shared.error.lock().unwrap().clone()
+    }
+
+    /// Stop recording and return the mono samples and sample rate.
+    pub fn finish(self) -> Result<(Vec<f64>, u32)> {
+        let Self { _stream: stream, shared, sample_rate } = self;
+        drop(stream);
+        if let Some(err) = shared.error.lock().unwrap().take() {
+            bail!("Recording failed: {}", err);
+        }
+        let samples = std::mem::take(&mut *shared.samples.lock().unwrap());
+        Ok((samples, sample_rate))
+    }
+}
+
+fn build_stream<T>(device: &cpal::Device, config: &cpal::StreamConfig, shared: &Arc<Shared>) -> Result<cpal::Stream>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let channels = config.channels as usize;
+    let max_samples = (MAX_RECORDING_S * config.sample_rate.0 as f64) as usize;
+    let data_shared = Arc::clone(shared);
+    let error_shared = Arc::clone(shared);
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let frames = downmix_interleaved(data.iter().map(|&s| s.to_sample::<f32>()), channels);
+                *data_shared.level.lock().unwrap() = peak_level(&frames);
+                let mut samples = data_shared.samples.lock().unwrap();
+                let room = max_samples.saturating_sub(samples.len());
+                samples.extend(frames.iter().take(room).map(|&s| s as f64));
+            },
+            move |err| {
+                *error_shared.error.lock().unwrap() = Some(err.to_string());
+            },
+            None,
+        )
+        .context("Failed to open audio input stream")
+}
+
+/// Average interleaved frames down to mono.
+pub fn downmix_interleaved(data: impl Iterator<Item = f32>, channels: usize) -> Vec<f32> {
+    let channels = channels.max(1);
+    let data: Vec<f32> = data.collect();
+    data.chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// Absolute peak of a buffer, clamped to 0–1.
+pub fn peak_level(samples: &[f32]) -> f32 {
+    samples.iter().fold(0.0f32, |m, s| m.max(s.abs())).min(1.0)
+}
+
+/// Record for `duration_s` seconds, calling `on_tick(elapsed_s, level)`
+/// about ten times a second.
+pub fn record_for(
+    duration_s: f64,
+    buffer_size: BufferSize,
+    mut on_tick: impl FnMut(f64, f32),
+) -> Result<(Vec<f64>, u32)> {
+    if !(duration_s > 0.0 && duration_s <= MAX_RECORDING_S) {
+        bail!("Recording duration must be between 0 and {} seconds", MAX_RECORDING_S);
+    }
+    let recorder = Recorder::start_with_buffer(buffer_size)?;
+    while recorder.elapsed_s() < duration_s {
+        if let Some(err) = recorder.error() {
+            bail!("Recording failed: {}", err);
+        }
+        on_tick(recorder.elapsed_s(), recorder.level());
+        std::thread::sleep(Duration::from_millis(100));
+    }
+    let (mut samples, sr) = recorder.finish()?;
+    samples.truncate((duration_s * sr as f64).round() as usize);
+    Ok((samples, sr))
+}
+
+/// Write a recording to a mono WAV file.
+pub fn save_recording(samples: &[f64], sample_rate: u32, path: &Path) -> Result<()> {
+    if samples.is_empty() {
+        bail!("Nothing was recorded");
+    }
+    write_wav(path, samples, sample_rate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_downmix_and_peak() {
+        let stereo = [0.5f32, -0.5, 1.0, 0.0, -0.8, -0.4];
+        let mono = downmix_interleaved(stereo.into_iter(), 2);
+        assert_eq!(mono, vec![0.0, 0.5, -0.6]);
+        assert!((peak_level(&mono) - 0.6).abs() < 1e-6);
+        assert_eq!(peak_level(&[2.0]), 1.0);
+        assert_eq!(downmix_interleaved([0.25f32].into_iter(), 0), vec![0.25]);
+    }
+
+    #[test]
+    fn test_save_recording_rejects_empty() {
+        let path = std::env::temp_dir().join("glottisdale_empty_recording.wav");
+        assert!(save_recording(&[], 16000, &path).is_err());
+        assert!(record_for(0.0, BufferSize::Default, |_, _| {}).is_err());
+    }
+}
@@ -0,0 +1,196 @@
+//! Audio device streams with a configurable buffer size.
+//!
+//! Backends pick their own default buffer, which on some Linux setups is
+//! large enough to make scrubbing and clip triggers feel sluggish. Smaller
+//! buffers lower latency at the cost of more CPU wakeups and a higher risk
+//! of dropouts.
+
+use std::sync::{Arc, Mutex};
+
+use anyhow::{anyhow, bail, Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample, SupportedBufferSize};
+use rodio::dynamic_mixer::{self, DynamicMixer, DynamicMixerController};
+use rodio::Sink;
+
+/// Buffer sizes offered in the GUI (frames).
+pub const BUFFER_SIZE_CHOICES: &[u32] = &[128, 256, 512, 1024, 2048];
+
+/// Requested device buffer size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BufferSize {
+    /// Let the audio backend choose.
+    #[default]
+    Default,
+    /// Fixed number of frames per callback.
+    Frames(u32),
+}
+
+impl BufferSize {
+    /// `None` means the backend default.
+    pub fn from_frames(frames: Option<u32>) -> Self {
+        frames.map_or(Self::Default, Self::Frames)
+    }
+
+    /// Latency of one buffer in milliseconds, if fixed.
+    pub fn latency_ms(&self, sample_rate: u32) -> Option<f64> {
+        match *self {
+            Self::Default => None,
+            Self::Frames(n) => Some(n as f64 * 1000.0 / sample_rate.max(1) as f64),
+        }
+    }
+
+    /// Short description for settings and logs.
+    pub fn label(&self, sample_rate: u32) -> String {
+        match (self, self.latency_ms(sample_rate)) {
+            (Self::Frames(n), Some(ms)) => format!("{} frames ({:.1} ms)", n, ms),
+            _ => "Default".to_string(),
+        }
+    }
+
+    /// Convert to a cpal buffer size, clamped to what the device supports.
+    pub fn to_cpal(self, supported: &SupportedBufferSize) -> cpal::BufferSize {
+        match (self, supported) {
+            (Self::Default, _) => cpal::BufferSize::Default,
+            (Self::Frames(n), SupportedBufferSize::Range { min, max }) if min <= max => {
+                cpal::BufferSize::Fixed(n.clamp(*min, *max))
+            }
+            (Self::Frames(n), _) => cpal::BufferSize::Fixed(n.max(1)),
+        }
+    }
+}
+
+/// Build a stream, retrying with the backend default if the requested
+/// buffer size is refused.
+pub(crate) fn build_with_fallback(
+    config: &cpal::StreamConfig,
+    build: impl Fn(&cpal::StreamConfig) -> Result<cpal::Stream>,
+) -> Result<cpal::Stream> {
+    match build(config) {
+        Ok(stream) => Ok(stream),
+        Err(e) if config.buffer_size != cpal::BufferSize::Default => {
+            log::warn!("Buffer size {:?} refused ({:#}); using the device default", config.buffer_size, e);
+            let fallback = cpal::StreamConfig {
+                buffer_size: cpal::BufferSize::Default,
+                ..config.clone()
+            };
+            build(&fallback)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// An open output stream on the default device that mixes any number of
+/// rodio sinks.
+pub struct AudioOutput {
+    _stream: cpal::Stream,
+    mixer: Arc<DynamicMixerController<f32>>,
+    sample_rate: u32,
+    buffer_size: BufferSize,
+}
+
+impl AudioOutput {
+    /// Open the default output device with the given buffer size.
+    pub fn open(buffer_size: BufferSize) -> Result<Self> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .ok_or_else(|| anyhow!("No audio output device found"))?;
+        let supported = device
+            .default_output_config()
+            .context("Failed to query output device configuration")?;
+        let sample_format = supported.sample_format();
+        let mut config: cpal::StreamConfig = supported.config();
+        config.buffer_size = buffer_size.to_cpal(supported.buffer_size());
+
+        let (mixer, mixer_out) = dynamic_mixer::mixer::<f32>(config.channels, config.sample_rate.0);
+        // The mixer moves into whichever callback gets built; share it so the
+        // fallback attempt can reuse it.
+        let mixer_out = Arc::new(Mutex::new(mixer_out));
+        let stream = build_with_fallback(&config, |config| match sample_format {
+            cpal::SampleFormat::F32 => build_output::<f32>(&device, config, &mixer_out),
+            cpal::SampleFormat::I16 => build_output::<i16>(&device, config, &mixer_out),
+            cpal::SampleFormat::U16 => build_output::<u16>(&device, config, &mixer_out),
+            cpal::SampleFormat::I32 => build_output::<i32>(&device, config, &mixer_out),
+            other => bail!("Unsupported output sample format: {:?}", other),
+        })?;
+        stream.play().context("Failed to start audio output")?;
+
+        let buffer_size = match config.buffer_size {
+            cpal::BufferSize::Fixed(n) => BufferSize::Frames(n),
+            cpal::BufferSize::Default => BufferSize::Default,
+        };
+        log::info!("Audio output opened: {} Hz, buffer {}", config.sample_rate.0, buffer_size.label(config.sample_rate.0));
+        Ok(Self {
+            _stream: stream,
+            mixer,
+            sample_rate: config.sample_rate.0,
+            buffer_size,
+        })
+    }
+
+    /// Device sample rate.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Buffer size requested from the device, after clamping.
+    pub fn buffer_size(&self) -> BufferSize {
+        self.buffer_size
+    }
+
+    /// Create a sink that plays through this output.
+    pub fn new_sink(&self) -> Sink {
+        let (sink, queue) = Sink::new_idle();
+        self.mixer.add(queue);
+        sink
+    }
+}
+
+fn build_output<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mixer_out: &Arc<Mutex<DynamicMixer<f32>>>,
+) -> Result<cpal::Stream>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let mixer_out = Arc::clone(mixer_out);
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                let mut mixer = mixer_out.lock().unwrap();
+                for d in data.iter_mut() {
+                    *d = mixer.next().map(T::from_sample).unwrap_or(T::EQUILIBRIUM);
+                }
+            },
+            |err| log::error!("Audio output stream error: {}", err),
+            None,
+        )
+        .context("Failed to open audio output stream")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_size_clamps_to_device_range() {
+        let range = SupportedBufferSize::Range { min: 64, max: 1024 };
+        assert_eq!(BufferSize::Default.to_cpal(&range), cpal::BufferSize::Default);
+        assert_eq!(BufferSize::Frames(256).to_cpal(&range), cpal::BufferSize::Fixed(256));
+        assert_eq!(BufferSize::Frames(16).to_cpal(&range), cpal::BufferSize::Fixed(64));
+        assert_eq!(BufferSize::Frames(4096).to_cpal(&range), cpal::BufferSize::Fixed(1024));
+        assert_eq!(BufferSize::Frames(300).to_cpal(&SupportedBufferSize::Unknown), cpal::BufferSize::Fixed(300));
+    }
+
+    #[test]
+    fn test_buffer_size_latency() {
+        assert_eq!(BufferSize::Default.latency_ms(48000), None);
+        assert!((BufferSize::Frames(480).latency_ms(48000).unwrap() - 10.0).abs() < 1e-12);
+        assert_eq!(BufferSize::Frames(256).label(48000), "256 frames (5.3 ms)");
+        assert_eq!(BufferSize::from_frames(None), BufferSize::Default);
+        assert_eq!(BufferSize::from_frames(Some(512)), BufferSize::Frames(512));
+    }
+}
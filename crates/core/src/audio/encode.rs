@@ -0,0 +1,369 @@
+//! Output encoding: 16-bit WAV, lossless FLAC, Ogg Vorbis and MP3.
+//!
+//! FLAC goes through the pure-Rust `flacenc`. No pure-Rust Vorbis or MP3
+//! encoder exists, so Ogg Vorbis (`vorbis_rs`, bundling libvorbis) and MP3
+//! (`mp3lame-encoder`, bundling LAME) are behind the opt-in `ogg` and `mp3`
+//! cargo features; without them those formats fail to parse.
+
+#[cfg(feature = "ogg")]
+use std::num::{NonZeroU32, NonZeroU8};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use super::io::{read_wav_channels, write_wav_channels};
+
+/// Output format names accepted by `OutputFormat::parse`.
+pub const OUTPUT_FORMATS: &[&str] = &["wav", "flac", "ogg", "mp3"];
+
+/// Samples handed to the Vorbis encoder per analysis block.
+#[cfg(feature = "ogg")]
+const VORBIS_BLOCK_SIZE: usize = 1024;
+
+/// MP3 bitrate; transparent for speech and most music.
+#[cfg(feature = "mp3")]
+const MP3_BITRATE: mp3lame_encoder::Bitrate = mp3lame_encoder::Bitrate::Kbps192;
+
+/// Audio container/codec for rendered output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    #[default]
+    Wav,
+    Flac,
+    /// Ogg Vorbis.
+    Ogg,
+    Mp3,
+}
+
+impl OutputFormat {
+    /// Parse a format name (case-insensitive): "wav", "flac", "ogg" or "mp3".
+    ///
+    /// Fails for a format whose cargo feature this build leaves out.
+    pub fn parse(s: &str) -> Result<Self> {
+        let format = match s.trim().to_ascii_lowercase().as_str() {
+            "wav" => Self::Wav,
+            "flac" => Self::Flac,
+            "ogg" | "vorbis" => Self::Ogg,
+            "mp3" => Self::Mp3,
+            other => anyhow::bail!(
+                "Unknown output format '{}' (expected one of: {})",
+                other,
+                OUTPUT_FORMATS.join(", ")
+            ),
+        };
+        format.ensure_built()?;
+        Ok(format)
+    }
+
+    /// Fail if this build leaves out the cargo feature the format needs.
+    fn ensure_built(&self) -> Result<()> {
+        let feature = match self {
+            Self::Ogg if !cfg!(feature = "ogg") => "ogg",
+            Self::Mp3 if !cfg!(feature = "mp3") => "mp3",
+            _ => return Ok(()),
+        };
+        anyhow::bail!("{} output needs a build with the `{}` cargo feature", self.extension(), feature)
+    }
+
+    /// File extension without the dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Wav => "wav",
+            Self::Flac => "flac",
+            Self::Ogg => "ogg",
+            Self::Mp3 => "mp3",
+        }
+    }
+}
+
+/// Write planar channels in the given format.
+///
+/// Samples are clipped to [-1, 1]; WAV and FLAC are quantized to 16 bits.
+pub fn write_audio(path: &Path, channels: &[Vec<f64>], sample_rate: u32, format: OutputFormat) -> Result<()> {
+    let bytes = match format {
+        OutputFormat::Wav => return write_wav_channels(path, channels, sample_rate),
+        OutputFormat::Flac => encode_flac(channels, sample_rate)?,
+        #[cfg(feature = "ogg")]
+        OutputFormat::Ogg => encode_ogg(channels, sample_rate)?,
+        #[cfg(feature = "mp3")]
+        OutputFormat::Mp3 => encode_mp3(channels, sample_rate)?,
+        #[allow(unreachable_patterns)]
+        other => {
+            other.ensure_built()?;
+            unreachable!("{:?} output is built", other)
+        }
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    std::fs::write(path, bytes).with_context(|| format!("Failed to write audio file: {}", path.display()))
+}
+
+/// Re-encode a pipeline WAV in another format, replacing the WAV.
+///
+/// Returns the path of the encoded file (unchanged for `OutputFormat::Wav`).
+pub fn convert_wav(wav_path: &Path, format: OutputFormat) -> Result<PathBuf> {
+    if format == OutputFormat::Wav {
+        return Ok(wav_path.to_path_buf());
+    }
+    let (channels, sr) = read_wav_channels(wav_path)?;
+    let out_path = wav_path.with_extension(format.extension());
+    write_audio(&out_path, &channels, sr, format)?;
+    std::fs::remove_file(wav_path)
+        .with_context(|| format!("Failed to remove {}", wav_path.display()))?;
+    Ok(out_path)
+}
+
+/// Encode planar channels as a 16-bit FLAC stream.
+///
+/// Shorter channels are padded with silence.
+pub fn encode_flac(channels: &[Vec<f64>], sample_rate: u32) -> Result<Vec<u8>> {
+    use flacenc::component::BitRepr;
+    use flacenc::error::Verify;
+
+    let planar = padded(channels, 8);
+    let frames = planar.first().map_or(0, |c| c.len());
+    let interleaved: Vec<i32> = (0..frames)
+        .flat_map(|i| planar.iter().map(move |c| (c[i].clamp(-1.0, 1.0) * 32767.0) as i16 as i32))
+        .collect();
+
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| anyhow::anyhow!("Invalid FLAC encoder config: {}", e))?;
+    let source =
+        flacenc::source::MemSource::from_samples(&interleaved, planar.len(), 16, sample_rate as usize);
+    let stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| anyhow::anyhow!("FLAC encoding failed: {:?}", e))?;
+    let mut sink = flacenc::bitsink::ByteSink::new();
+    stream
+        .write(&mut sink)
+        .map_err(|e| anyhow::anyhow!("FLAC encoding failed: {}", e))?;
+    Ok(sink.as_slice().to_vec())
+}
+
+/// Encode planar channels as an Ogg Vorbis stream at the default quality.
+///
+/// Shorter channels are padded with silence.
+#[cfg(feature = "ogg")]
+pub fn encode_ogg(channels: &[Vec<f64>], sample_rate: u32) -> Result<Vec<u8>> {
+    let planar: Vec<Vec<f32>> = padded(channels, 255)
+        .into_iter()
+        .map(|c| c.into_iter().map(|s| s.clamp(-1.0, 1.0) as f32).collect())
+        .collect();
+    let frames = planar.first().map_or(0, |c| c.len());
+    let n_channels = NonZeroU8::new(planar.len() as u8).context("Vorbis needs at least one channel")?;
+    let rate = NonZeroU32::new(sample_rate).context("Sample rate must be positive")?;
+
+    // A fixed serial keeps output reproducible for a given input.
+    let mut encoder = vorbis_rs::VorbisEncoderBuilder::new_with_serial(rate, n_channels, Vec::new(), 0)
+        .build()
+        .context("Failed to start Vorbis encoder")?;
+    for start in (0..frames).step_by(VORBIS_BLOCK_SIZE) {
+        let end = (start + VORBIS_BLOCK_SIZE).min(frames);
+        let block: Vec<&[f32]> = planar.iter().map(|c| &c[start..end]).collect();
+        encoder.encode_audio_block(&block).context("Vorbis encoding failed")?;
+    }
+    encoder.finish().context("Vorbis encoding failed")
+}
+
+/// Encode mono or stereo planar channels as a 192 kbps MP3 stream.
+///
+/// MP3 carries at most two channels; extra channels are an error.
+#[cfg(feature = "mp3")]
+pub fn encode_mp3(channels: &[Vec<f64>], sample_rate: u32) -> Result<Vec<u8>> {
+    use mp3lame_encoder::{Builder, DualPcm, FlushNoGap, MonoPcm};
+
+    anyhow::ensure!(
+        channels.len() <= 2,
+        "MP3 supports mono or stereo output, not {} channels",
+        channels.len()
+    );
+    let planar: Vec<Vec<f64>> = padded(channels, 2)
+        .into_iter()
+        .map(|c| c.into_iter().map(|s| s.clamp(-1.0, 1.0)).collect())
+        .collect();
+    let frames = planar.first().map_or(0, |c| c.len());
+
+    let lame_err = |e: mp3lame_encoder::BuildError| anyhow::anyhow!("Failed to start MP3 encoder: {}", e);
+    let mut builder = Builder::new().context("Failed to start MP3 encoder")?;
+    builder.set_num_channels(planar.len() as u8).map_err(lame_err)?;
+    builder.set_sample_rate(sample_rate).map_err(lame_err)?;
+    builder.set_brate(MP3_BITRATE).map_err(lame_err)?;
+    builder.set_quality(mp3lame_encoder::Quality::Best).map_err(lame_err)?;
+    let mut encoder = builder.build().map_err(lame_err)?;
+
+    let encode_err = |e: mp3lame_encoder::EncodeError| anyhow::anyhow!("MP3 encoding failed: {}", e);
+    let mut out = Vec::with_capacity(mp3lame_encoder::max_required_buffer_size(frames));
+    match planar.as_slice() {
+        [mono] => encoder.encode_to_vec(MonoPcm(mono.as_slice()), &mut out),
+        [left, right] => encoder.encode_to_vec(DualPcm { left, right }, &mut out),
+        _ => unreachable!("channel count checked above"),
+    }
+    .map_err(encode_err)?;
+    encoder.flush_to_vec::<FlushNoGap>(&mut out).map_err(encode_err)?;
+    Ok(out)
+}
+
+/// Clamp the channel count to 1..=`max_channels` and pad every channel with
+/// silence to the longest one's length.
+fn padded(channels: &[Vec<f64>], max_channels: usize) -> Vec<Vec<f64>> {
+    let n_channels = channels.len().clamp(1, max_channels);
+    let frames = channels.iter().map(|c| c.len()).max().unwrap_or(0);
+    (0..n_channels)
+        .map(|ch| {
+            let mut c = channels.get(ch).cloned().unwrap_or_default();
+            c.resize(frames, 0.0);
+            c
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::io::{extract_audio_channels, Channels};
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join("glottisdale_test_encode");
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join(name)
+    }
+
+    #[test]
+    fn test_output_format_parse() {
+        assert_eq!(OutputFormat::parse("wav").unwrap(), OutputFormat::Wav);
+        assert_eq!(OutputFormat::parse("FLAC").unwrap(), OutputFormat::Flac);
+        assert!(OutputFormat::parse("aiff").is_err());
+        assert_eq!(OutputFormat::Flac.extension(), "flac");
+        assert_eq!(OutputFormat::Ogg.extension(), "ogg");
+        for name in OUTPUT_FORMATS {
+            if let Ok(format) = OutputFormat::parse(name) {
+                assert_eq!(format.extension(), *name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_output_format_parse_needs_feature() {
+        for (name, format, built) in [
+            ("Vorbis", OutputFormat::Ogg, cfg!(feature = "ogg")),
+            ("mp3", OutputFormat::Mp3, cfg!(feature = "mp3")),
+        ] {
+            match OutputFormat::parse(name) {
+                Ok(parsed) => assert!(built && parsed == format),
+                Err(e) => {
+                    assert!(!built);
+                    assert!(e.to_string().contains("cargo feature"), "{}", e);
+                    let path = temp_path(&format!("unbuilt.{}", format.extension()));
+                    assert!(write_audio(&path, &[vec![0.0; 100]], 16000, format).is_err());
+                    assert!(!path.exists());
+                }
+            }
+        }
+    }
+
+    fn tone(sr: u32, n: usize) -> Vec<f64> {
+        (0..n)
+            .map(|i| (i as f64 / sr as f64 * 440.0 * std::f64::consts::TAU).sin() * 0.5)
+            .collect()
+    }
+
+    /// Write `channels` as `format`, decode it back and return the decoded
+    /// channels and sample rate. Decoding resamples to 16 kHz.
+    #[cfg(any(feature = "ogg", feature = "mp3"))]
+    fn roundtrip(name: &str, channels: &[Vec<f64>], sr: u32, format: OutputFormat) -> (Vec<Vec<f64>>, u32) {
+        let path = temp_path(&format!("{}.{}", name, format.extension()));
+        write_audio(&path, channels, sr, format).unwrap();
+        let wav_path = temp_path(&format!("{}_decoded.wav", name));
+        let mode = if channels.len() > 1 { Channels::Stereo } else { Channels::Mono };
+        extract_audio_channels(&path, &wav_path, mode).unwrap();
+        let decoded = read_wav_channels(&wav_path).unwrap();
+        std::fs::remove_file(&path).ok();
+        std::fs::remove_file(&wav_path).ok();
+        decoded
+    }
+
+    #[cfg(any(feature = "ogg", feature = "mp3"))]
+    fn rms(samples: &[f64]) -> f64 {
+        (samples.iter().map(|s| s * s).sum::<f64>() / samples.len().max(1) as f64).sqrt()
+    }
+
+    #[test]
+    fn test_flac_roundtrip_is_lossless() {
+        let sr = 16000;
+        let left = tone(sr, 10000);
+        let mut right = vec![0.0; 10000];
+        right[5000..].iter_mut().enumerate().for_each(|(i, s)| *s = (i % 7) as f64 * 0.01);
+
+        let flac_path = temp_path("roundtrip.flac");
+        write_audio(&flac_path, &[left.clone(), right.clone()], sr, OutputFormat::Flac).unwrap();
+        let bytes = std::fs::read(&flac_path).unwrap();
+        assert!(bytes.len() < 10000 * 4, "FLAC should compress: {} bytes", bytes.len());
+
+        let wav_path = temp_path("roundtrip_decoded.wav");
+        extract_audio_channels(&flac_path, &wav_path, Channels::Stereo).unwrap();
+        let (decoded, decoded_sr) = read_wav_channels(&wav_path).unwrap();
+        assert_eq!(decoded_sr, sr);
+        assert_eq!(decoded.len(), 2);
+        for (original, decoded) in [left, right].iter().zip(&decoded) {
+            assert_eq!(decoded.len(), original.len());
+            for (a, b) in original.iter().zip(decoded) {
+                let expected = (a.clamp(-1.0, 1.0) * 32767.0) as i16 as f64 / 32768.0;
+                assert!((expected - b).abs() < 1e-4, "{} vs {}", expected, b);
+            }
+        }
+
+        std::fs::remove_file(&flac_path).ok();
+        std::fs::remove_file(&wav_path).ok();
+    }
+
+    #[cfg(feature = "ogg")]
+    #[test]
+    fn test_ogg_roundtrip_keeps_channels_and_level() {
+        let sr = 16000;
+        let left = tone(sr, 16000);
+        let right = vec![0.0; 16000];
+        let (decoded, decoded_sr) = roundtrip("roundtrip_ogg", &[left.clone(), right], sr, OutputFormat::Ogg);
+        assert_eq!(decoded_sr, sr);
+        assert_eq!(decoded.len(), 2);
+        assert!((decoded[0].len() as i64 - left.len() as i64).abs() < 2048, "{} frames", decoded[0].len());
+        let mid = 3000..13000;
+        assert!((rms(&decoded[0][mid.clone()]) - rms(&left[mid.clone()])).abs() < 0.02);
+        assert!(rms(&decoded[1][mid]) < 0.01, "silent channel should stay silent");
+    }
+
+    #[cfg(feature = "mp3")]
+    #[test]
+    fn test_mp3_roundtrip_keeps_level() {
+        let sr = 16000;
+        let mono = tone(sr, 16000);
+        let (decoded, decoded_sr) = roundtrip("roundtrip_mp3", std::slice::from_ref(&mono), sr, OutputFormat::Mp3);
+        assert_eq!(decoded_sr, sr);
+        assert_eq!(decoded.len(), 1);
+        // LAME adds encoder delay and padding of a few frames
+        assert!((decoded[0].len() as i64 - mono.len() as i64).abs() < 4096, "{} frames", decoded[0].len());
+        let mid = 3000..13000;
+        assert!((rms(&decoded[0][mid.clone()]) - rms(&mono[mid])).abs() < 0.02);
+    }
+
+    #[cfg(feature = "mp3")]
+    #[test]
+    fn test_mp3_rejects_more_than_two_channels() {
+        assert!(encode_mp3(&[vec![0.0; 100], vec![0.0; 100], vec![0.0; 100]], 44100).is_err());
+    }
+
+    #[test]
+    fn test_convert_wav_replaces_file() {
+        let wav_path = temp_path("convert.wav");
+        write_wav_channels(&wav_path, &[vec![0.25; 2000]], 16000).unwrap();
+        let flac_path = convert_wav(&wav_path, OutputFormat::Flac).unwrap();
+        assert_eq!(flac_path.extension().unwrap(), "flac");
+        assert!(flac_path.exists());
+        assert!(!wav_path.exists());
+        std::fs::remove_file(&flac_path).ok();
+
+        let same = convert_wav(&wav_path, OutputFormat::Wav).unwrap();
+        assert_eq!(same, wav_path);
+    }
+}
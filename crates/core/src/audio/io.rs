@@ -2,7 +2,70 @@
 
 use anyhow::{Context, Result};
 use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
-use std::path::Path;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use super::analysis::{noise_profile, noise_region};
+use super::effects::{spectral_gate, DenoiseLevel, Filter, PeakLimiter};
+use super::sample::Sample;
+use crate::profile::{self, Category};
+
+/// Channel layout of an audio buffer or file.
+///
+/// Internally, multi-channel audio is held planar: one `Vec<f64>` per
+/// channel, all the same length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Channels {
+    #[default]
+    Mono,
+    Stereo,
+}
+
+impl Channels {
+    /// Number of channels in this layout.
+    pub fn count(self) -> usize {
+        match self {
+            Self::Mono => 1,
+            Self::Stereo => 2,
+        }
+    }
+
+    /// Layout for a channel count (anything above one is treated as stereo).
+    pub fn from_count(n: usize) -> Self {
+        if n >= 2 { Self::Stereo } else { Self::Mono }
+    }
+}
+
+/// Average planar channels down to a single mono buffer.
+pub fn mix_to_mono<S: Sample>(channels: &[Vec<S>]) -> Vec<S> {
+    match channels.len() {
+        0 => Vec::new(),
+        1 => channels[0].clone(),
+        n => {
+            let len = channels.iter().map(|c| c.len()).min().unwrap_or(0);
+            (0..len)
+                .map(|i| S::from_f64(channels.iter().map(|c| c[i].to_f64()).sum::<f64>() / n as f64))
+                .collect()
+        }
+    }
+}
+
+/// Convert planar audio to the given layout.
+///
+/// Mono is duplicated to both sides when upmixing; stereo is averaged when
+/// downmixing. Buffers already in the target layout are copied as-is.
+pub fn to_layout<S: Sample>(channels: &[Vec<S>], layout: Channels) -> Vec<Vec<S>> {
+    match (Channels::from_count(channels.len()), layout) {
+        (Channels::Mono, Channels::Stereo) => {
+            let mono = channels.first().cloned().unwrap_or_default();
+            vec![mono.clone(), mono]
+        }
+        (Channels::Stereo, Channels::Mono) => vec![mix_to_mono(channels)],
+        _ => channels.iter().take(layout.count()).cloned().collect(),
+    }
+}
 
 /// Read a WAV file and return (samples_f64_normalized, sample_rate).
 ///
@@ -57,11 +120,89 @@ pub fn read_wav(path: &Path) -> Result<(Vec<f64>, u32)> {
     Ok((samples, sample_rate))
 }
 
+/// Read a WAV file keeping up to two channels, as planar buffers.
+///
+/// Returns (channels, sample_rate). Files with more than two channels keep
+/// the first two.
+pub fn read_wav_channels(path: &Path) -> Result<(Vec<Vec<f64>>, u32)> {
+    let reader = WavReader::open(path)
+        .with_context(|| format!("Failed to open WAV file: {}", path.display()))?;
+
+    let spec = reader.spec();
+    let sample_rate = spec.sample_rate;
+    let channels = spec.channels as usize;
+    let keep = channels.min(2);
+
+    let interleaved: Vec<f64> = match spec.sample_format {
+        SampleFormat::Int => {
+            let max_val = (1i64 << (spec.bits_per_sample - 1)) as f64;
+            reader
+                .into_samples::<i32>()
+                .map(|s| s.map(|v| v as f64 / max_val))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .context("Failed to read WAV samples")?
+        }
+        SampleFormat::Float => reader
+            .into_samples::<f32>()
+            .map(|s| s.map(|v| v as f64))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .context("Failed to read WAV samples")?,
+    };
+
+    let frames = interleaved.len() / channels.max(1);
+    let mut planar = vec![Vec::with_capacity(frames); keep];
+    for frame in interleaved.chunks_exact(channels.max(1)) {
+        for (ch, buf) in planar.iter_mut().enumerate() {
+            buf.push(frame[ch]);
+        }
+    }
+
+    Ok((planar, sample_rate))
+}
+
+/// Write planar channels to a 16-bit PCM WAV file.
+///
+/// One buffer writes a mono file, two write stereo. Shorter channels are
+/// padded with silence. Clips values to [-1, 1] before conversion.
+pub fn write_wav_channels<S: Sample>(path: &Path, channels: &[Vec<S>], sample_rate: u32) -> Result<()> {
+    if channels.len() <= 1 {
+        return write_wav(path, channels.first().map(|c| c.as_slice()).unwrap_or(&[]), sample_rate);
+    }
+    let _span = profile::span(Category::Io, "write wav");
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    let spec = WavSpec {
+        channels: channels.len() as u16,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    let mut writer = WavWriter::create(path, spec)
+        .with_context(|| format!("Failed to create WAV file: {}", path.display()))?;
+
+    let frames = channels.iter().map(|c| c.len()).max().unwrap_or(0);
+    for i in 0..frames {
+        for ch in channels {
+            let clipped = ch.get(i).map_or(0.0, |s| s.to_f64()).clamp(-1.0, 1.0);
+            writer.write_sample((clipped * 32767.0) as i16)?;
+        }
+    }
+
+    writer.finalize().context("Failed to finalize WAV file")?;
+    Ok(())
+}
+
 /// Write f64 samples to a 16-bit PCM WAV file.
 ///
 /// Clips values to [-1, 1] before conversion.
 /// Creates parent directories if needed.
-pub fn write_wav(path: &Path, samples: &[f64], sample_rate: u32) -> Result<()> {
+pub fn write_wav<S: Sample>(path: &Path, samples: &[S], sample_rate: u32) -> Result<()> {
+    let _span = profile::span(Category::Io, "write wav");
     if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)
             .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
@@ -78,7 +219,7 @@ pub fn write_wav(path: &Path, samples: &[f64], sample_rate: u32) -> Result<()> {
         .with_context(|| format!("Failed to create WAV file: {}", path.display()))?;
 
     for &sample in samples {
-        let clipped = sample.clamp(-1.0, 1.0);
+        let clipped = sample.to_f64().clamp(-1.0, 1.0);
         let int16 = (clipped * 32767.0) as i16;
         writer.write_sample(int16)?;
     }
@@ -87,6 +228,113 @@ pub fn write_wav(path: &Path, samples: &[f64], sample_rate: u32) -> Result<()> {
     Ok(())
 }
 
+/// De-noise a WAV in place with a spectral gate learned from its room tone.
+///
+/// Levels with a hum filter apply it first, so steady hum does not hide
+/// the room tone. Each channel's noise profile is then measured over the
+/// quietest stretch of the mono mixdown. Returns false, leaving the file
+/// as it was, when there is no usable room tone.
+pub fn denoise_wav(path: &Path, level: DenoiseLevel) -> Result<bool> {
+    let (channels, sr) = read_wav_channels(path)?;
+    let channels: Vec<Vec<f64>> = match level.hum_cutoff_hz() {
+        // Applied twice for a steeper slope between hum and voice
+        Some(cutoff_hz) => {
+            let high_pass = Filter::HighPass { cutoff_hz };
+            channels.iter().map(|c| high_pass.apply(&high_pass.apply(c, sr), sr)).collect()
+        }
+        None => channels,
+    };
+    let Some(region) = noise_region(&mix_to_mono(&channels), sr) else {
+        return Ok(false);
+    };
+    let mut denoised = Vec::with_capacity(channels.len());
+    for channel in &channels {
+        let Some(profile) = noise_profile(&channel[region.clone()], sr) else {
+            return Ok(false);
+        };
+        denoised.push(spectral_gate(channel, &profile, level)?);
+    }
+    write_wav_channels(path, &denoised, sr)?;
+    Ok(true)
+}
+
+/// Incremental 16-bit PCM WAV writer, for outputs too long to hold in memory.
+///
+/// Frames are written as they arrive and the header is patched on
+/// [`WavStreamWriter::finalize`]. Conversion matches [`write_wav_channels`]:
+/// values are clipped to [-1, 1] and short channels padded with silence.
+pub struct WavStreamWriter {
+    writer: WavWriter<BufWriter<File>>,
+    channels: usize,
+    sample_rate: u32,
+    frames: usize,
+    limiter: Option<PeakLimiter>,
+}
+
+impl WavStreamWriter {
+    /// Create `path` (and its parent directories) for `channels` channels.
+    pub fn create(path: &Path, channels: usize, sample_rate: u32) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+        }
+
+        let channels = channels.max(1);
+        let spec = WavSpec {
+            channels: channels as u16,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        };
+        let writer = WavWriter::create(path, spec)
+            .with_context(|| format!("Failed to create WAV file: {}", path.display()))?;
+        Ok(Self { writer, channels, sample_rate, frames: 0, limiter: None })
+    }
+
+    /// Run everything written through a [`PeakLimiter`] when `enabled`.
+    pub fn limited(mut self, enabled: bool) -> Self {
+        self.limiter = enabled.then(|| PeakLimiter::new(self.channels, self.sample_rate));
+        self
+    }
+
+    /// Append a block of planar audio.
+    pub fn write_frames<S: Sample, C: AsRef<[S]>>(&mut self, block: &[C]) -> Result<()> {
+        let frames = block.iter().map(|c| c.as_ref().len()).max().unwrap_or(0);
+        self.frames += frames;
+        if let Some(limiter) = &mut self.limiter {
+            let block: Vec<Vec<f64>> = block.iter().map(|c| c.as_ref().iter().map(|s| s.to_f64()).collect()).collect();
+            let limited = limiter.process(&block);
+            return self.write_planar(&limited);
+        }
+        self.write_planar(block)
+    }
+
+    fn write_planar<S: Sample, C: AsRef<[S]>>(&mut self, block: &[C]) -> Result<()> {
+        let frames = block.iter().map(|c| c.as_ref().len()).max().unwrap_or(0);
+        for i in 0..frames {
+            for ch in 0..self.channels {
+                let sample = block.get(ch).and_then(|c| c.as_ref().get(i)).map_or(0.0, |s| s.to_f64());
+                self.writer.write_sample((sample.clamp(-1.0, 1.0) * 32767.0) as i16)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Frames written so far.
+    pub fn frames_written(&self) -> usize {
+        self.frames
+    }
+
+    /// Flush and write the final header.
+    pub fn finalize(mut self) -> Result<()> {
+        if let Some(limiter) = self.limiter.take() {
+            let tail = limiter.finish();
+            self.write_planar(&tail)?;
+        }
+        self.writer.finalize().context("Failed to finalize WAV file")
+    }
+}
+
 /// Get duration of a WAV file in seconds.
 pub fn get_wav_duration(path: &Path) -> Result<f64> {
     let reader = WavReader::open(path)
@@ -97,10 +345,55 @@ pub fn get_wav_duration(path: &Path) -> Result<f64> {
     Ok(num_samples / channels / spec.sample_rate as f64)
 }
 
+/// File extensions the audio extractor accepts (audio and video containers).
+pub const MEDIA_EXTENSIONS: &[&str] = &["wav", "mp3", "mp4", "mov", "mkv", "flac", "ogg", "m4a"];
+
+/// Whether a path has one of [`MEDIA_EXTENSIONS`] (case-insensitive).
+pub fn is_media_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| MEDIA_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Duration of any supported media file in seconds, read from the container
+/// headers without decoding.
+pub fn probe_duration(path: &Path) -> Result<f64> {
+    use symphonia::core::codecs::CODEC_TYPE_NULL;
+    use symphonia::core::formats::FormatOptions;
+    use symphonia::core::io::MediaSourceStream;
+    use symphonia::core::meta::MetadataOptions;
+    use symphonia::core::probe::Hint;
+
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open: {}", path.display()))?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
+        .with_context(|| format!("Unsupported format: {}", path.display()))?;
+    let track = probed
+        .format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .context("No audio track found")?;
+    let params = &track.codec_params;
+    let frames = params.n_frames.context("Duration not stored in file header")?;
+    if let Some(tb) = params.time_base {
+        let t = tb.calc_time(frames);
+        return Ok(t.seconds as f64 + t.frac);
+    }
+    let sr = params.sample_rate.context("Sample rate not stored in file header")?;
+    Ok(frames as f64 / sr as f64)
+}
+
 /// Extract a time range from samples. Returns the slice as a new Vec.
 ///
 /// Clamps to valid bounds.
-pub fn extract_range(samples: &[f64], sample_rate: u32, start_s: f64, end_s: f64) -> Vec<f64> {
+pub fn extract_range<S: Sample>(samples: &[S], sample_rate: u32, start_s: f64, end_s: f64) -> Vec<S> {
     let start_idx = (start_s * sample_rate as f64).round() as usize;
     let end_idx = (end_s * sample_rate as f64).round() as usize;
     let start_idx = start_idx.min(samples.len());
@@ -111,9 +404,82 @@ pub fn extract_range(samples: &[f64], sample_rate: u32, start_s: f64, end_s: f64
     samples[start_idx..end_idx].to_vec()
 }
 
+/// A time window (in seconds) restricting which part of a source is used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceRange {
+    /// Input file the range applies to, as given on the command line.
+    pub file: PathBuf,
+    pub start_s: f64,
+    pub end_s: f64,
+}
+
+impl SourceRange {
+    /// Parse a `FILE=START-END` spec, e.g. `interview.wav=120-300`.
+    ///
+    /// The last `=` separates the file from the range so paths containing
+    /// `=` still work.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (file, range) = spec
+            .rsplit_once('=')
+            .with_context(|| format!("Invalid source range '{}': expected FILE=START-END", spec))?;
+        let (start, end) = range
+            .split_once('-')
+            .with_context(|| format!("Invalid source range '{}': expected START-END seconds", spec))?;
+        let start_s: f64 = start
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid range start in '{}'", spec))?;
+        let end_s: f64 = end
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid range end in '{}'", spec))?;
+        if file.is_empty() {
+            anyhow::bail!("Invalid source range '{}': missing file", spec);
+        }
+        if start_s < 0.0 || end_s <= start_s {
+            anyhow::bail!("Invalid source range '{}': end must be after start", spec);
+        }
+        Ok(Self {
+            file: PathBuf::from(file),
+            start_s,
+            end_s,
+        })
+    }
+
+    /// Whether this range targets `input`, by full path or by file name.
+    pub fn matches(&self, input: &Path) -> bool {
+        self.file == input || input.file_name() == Some(self.file.as_os_str())
+    }
+}
+
+/// Trim a WAV file in place to `[start_s, end_s)`, keeping its channels.
+///
+/// Fails if the range lies entirely past the end of the audio.
+pub fn trim_wav(path: &Path, start_s: f64, end_s: f64) -> Result<()> {
+    let (channels, sr) = read_wav_channels(path)?;
+    let trimmed: Vec<Vec<f64>> = channels
+        .iter()
+        .map(|ch| extract_range(ch, sr, start_s, end_s))
+        .collect();
+    if trimmed.iter().all(|ch| ch.is_empty()) {
+        anyhow::bail!(
+            "Range {}-{}s is outside the audio in {}",
+            start_s,
+            end_s,
+            path.display()
+        );
+    }
+    write_wav_channels(path, &trimmed, sr)
+}
+
+/// Input frames per resampler call.
+const RESAMPLE_CHUNK: usize = 1024;
+
 /// Resample audio from source sample rate to target sample rate.
 ///
-/// Uses rubato for high-quality resampling.
+/// Uses rubato for high-quality resampling. The filter is flushed at the
+/// end, so the output is `len * to_sr / from_sr` samples long, rounded,
+/// with nothing lost from the tail.
 pub fn resample(samples: &[f64], from_sr: u32, to_sr: u32) -> Result<Vec<f64>> {
     if from_sr == to_sr {
         return Ok(samples.to_vec());
@@ -122,6 +488,7 @@ pub fn resample(samples: &[f64], from_sr: u32, to_sr: u32) -> Result<Vec<f64>> {
     if samples.is_empty() {
         return Ok(vec![]);
     }
+    let _span = profile::span(Category::Dsp, "resample");
 
     use rubato::{SincFixedIn, SincInterpolationParameters, SincInterpolationType, WindowFunction, Resampler};
 
@@ -138,14 +505,29 @@ pub fn resample(samples: &[f64], from_sr: u32, to_sr: u32) -> Result<Vec<f64>> {
         ratio,
         2.0, // max relative ratio (allows some flexibility)
         params,
-        samples.len(),
+        RESAMPLE_CHUNK,
         1, // mono
     )?;
 
-    let input = vec![samples.to_vec()];
-    let output = resampler.process(&input, None)?;
-
-    Ok(output.into_iter().next().unwrap_or_default())
+    // Feed fixed chunks, then flush the filter until the tail is out
+    let expected = (samples.len() as f64 * ratio).round() as usize;
+    let mut output = Vec::with_capacity(expected + RESAMPLE_CHUNK);
+    let mut pos = 0;
+    while output.len() < expected {
+        let block = if pos + RESAMPLE_CHUNK <= samples.len() {
+            pos += RESAMPLE_CHUNK;
+            resampler.process(&[&samples[pos - RESAMPLE_CHUNK..pos]], None)?
+        } else if pos < samples.len() {
+            let rest = &samples[pos..];
+            pos = samples.len();
+            resampler.process_partial(Some(&[rest]), None)?
+        } else {
+            resampler.process_partial(None::<&[&[f64]]>, None)?
+        };
+        output.extend_from_slice(&block[0]);
+    }
+    output.truncate(expected);
+    Ok(output)
 }
 
 /// Extract/convert audio from any format to 16kHz mono WAV.
@@ -153,6 +535,101 @@ pub fn resample(samples: &[f64], from_sr: u32, to_sr: u32) -> Result<Vec<f64>> {
 /// Supports WAV, MP3, and MP4 (AAC audio track) via symphonia.
 /// No external tools required.
 pub fn extract_audio(input_path: &Path, output_path: &Path) -> Result<()> {
+    extract_audio_channels(input_path, output_path, Channels::Mono)
+}
+
+/// Extract/convert audio from any format to a 16kHz WAV with the given layout.
+///
+/// Multi-channel sources are downmixed for `Channels::Mono` and reduced to
+/// their first two channels for `Channels::Stereo`. Mono sources stay mono
+/// even when stereo is requested.
+pub fn extract_audio_channels(input_path: &Path, output_path: &Path, layout: Channels) -> Result<()> {
+    let _span = profile::span(Category::Io, "decode");
+    let file = std::fs::File::open(input_path)
+        .with_context(|| format!("Failed to open: {}", input_path.display()))?;
+    let extension = input_path.extension().and_then(|e| e.to_str());
+    let planar = decode_audio(Box::new(file), extension, layout)
+        .and_then(to_16k)
+        .with_context(|| format!("Failed to decode: {}", input_path.display()))?;
+    write_wav_channels(output_path, &planar, 16000)?;
+    Ok(())
+}
+
+/// Planar multi-channel audio per source: name -> (channels, sample_rate).
+pub type SourceChannels = HashMap<String, (Vec<Vec<f64>>, u32)>;
+
+/// Where [`load_stereo_sources`] keeps the stereo extraction of a working
+/// WAV: `<stem>_stereo.wav` beside it.
+pub fn stereo_wav_path(audio_path: &Path) -> PathBuf {
+    let stem = audio_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "input".to_string());
+    audio_path.with_file_name(format!("{}_stereo.wav", stem))
+}
+
+/// Extract each input's stereo image beside its mono working WAV, trimmed
+/// to its source range and de-noised like the mono one, and read it back.
+///
+/// `audio_paths` are the mono WAVs, parallel to `inputs`; the result is
+/// keyed by them, as the pipelines key their sources. Mono inputs stay mono.
+pub fn load_stereo_sources(
+    inputs: &[PathBuf],
+    ranges: &[SourceRange],
+    audio_paths: &[PathBuf],
+    denoise: Option<DenoiseLevel>,
+) -> Result<SourceChannels> {
+    let mut sources = SourceChannels::new();
+    for (input, audio_path) in inputs.iter().zip(audio_paths) {
+        let stereo_path = stereo_wav_path(audio_path);
+        log::info!("Extracting stereo: {} -> {}", input.display(), stereo_path.display());
+        extract_audio_channels(input, &stereo_path, Channels::Stereo)?;
+        if let Some(range) = ranges.iter().find(|r| r.matches(input)) {
+            trim_wav(&stereo_path, range.start_s, range.end_s)?;
+        }
+        if let Some(level) = denoise {
+            if !denoise_wav(&stereo_path, level)? {
+                log::warn!("No room tone found in {}; left as is", stereo_path.display());
+            }
+        }
+        sources.insert(audio_path.to_string_lossy().to_string(), read_wav_channels(&stereo_path)?);
+    }
+    Ok(sources)
+}
+
+/// Decode in-memory media to planar 16kHz audio, as `extract_audio_channels`
+/// does for files. `extension` is a format hint (e.g. "mp3").
+///
+/// Malformed input is an error, never a panic; the fuzz targets hold this.
+pub fn decode_audio_bytes(data: Vec<u8>, extension: Option<&str>, layout: Channels) -> Result<Vec<Vec<f64>>> {
+    decode_audio_bytes_native(data, extension, layout).and_then(to_16k)
+}
+
+/// Like [`decode_audio_bytes`], but keeps the media's own sample rate.
+///
+/// Returns `(channels, sample_rate)`.
+pub fn decode_audio_bytes_native(
+    data: Vec<u8>,
+    extension: Option<&str>,
+    layout: Channels,
+) -> Result<(Vec<Vec<f64>>, u32)> {
+    decode_audio(Box::new(std::io::Cursor::new(data)), extension, layout)
+}
+
+/// Resample decoded `(channels, sample_rate)` to 16kHz.
+fn to_16k((planar, sample_rate): (Vec<Vec<f64>>, u32)) -> Result<Vec<Vec<f64>>> {
+    if sample_rate == 16000 {
+        return Ok(planar);
+    }
+    planar.iter().map(|ch| resample(ch, sample_rate, 16000)).collect()
+}
+
+/// Decode media to planar audio at its own sample rate.
+fn decode_audio(
+    source: Box<dyn symphonia::core::io::MediaSource>,
+    extension: Option<&str>,
+    layout: Channels,
+) -> Result<(Vec<Vec<f64>>, u32)> {
     use symphonia::core::audio::SampleBuffer;
     use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
     use symphonia::core::errors::Error as SymphError;
@@ -161,18 +638,16 @@ pub fn extract_audio(input_path: &Path, output_path: &Path) -> Result<()> {
     use symphonia::core::meta::MetadataOptions;
     use symphonia::core::probe::Hint;
 
-    let file = std::fs::File::open(input_path)
-        .with_context(|| format!("Failed to open: {}", input_path.display()))?;
-    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+    let mss = MediaSourceStream::new(source, Default::default());
 
     let mut hint = Hint::new();
-    if let Some(ext) = input_path.extension().and_then(|e| e.to_str()) {
+    if let Some(ext) = extension {
         hint.with_extension(ext);
     }
 
     let probed = symphonia::default::get_probe()
         .format(&hint, mss, &FormatOptions::default(), &MetadataOptions::default())
-        .with_context(|| format!("Unsupported format: {}", input_path.display()))?;
+        .context("Unsupported format")?;
 
     let mut format = probed.format;
 
@@ -184,13 +659,23 @@ pub fn extract_audio(input_path: &Path, output_path: &Path) -> Result<()> {
 
     let track_id = track.id;
     let source_sr = track.codec_params.sample_rate.unwrap_or(44100);
+    if source_sr == 0 {
+        anyhow::bail!("Invalid sample rate 0");
+    }
     let channels = track.codec_params.channels.map(|c| c.count()).unwrap_or(1);
+    // FLAC encoders may zero-pad the last block; STREAMINFO has the true length
+    let flac_frames = track
+        .codec_params
+        .n_frames
+        .filter(|_| track.codec_params.codec == symphonia::core::codecs::CODEC_TYPE_FLAC);
 
     let mut decoder = symphonia::default::get_codecs()
         .make(&track.codec_params, &DecoderOptions::default())
         .context("Unsupported codec")?;
 
+    let keep_stereo = layout == Channels::Stereo && channels > 1;
     let mut all_samples: Vec<f64> = Vec::new();
+    let mut right_samples: Vec<f64> = Vec::new();
 
     loop {
         let packet = match format.next_packet() {
@@ -208,6 +693,11 @@ pub fn extract_audio(input_path: &Path, output_path: &Path) -> Result<()> {
             Ok(decoded) => {
                 let spec = *decoded.spec();
                 let num_frames = decoded.frames();
+                // Trust the packet, not the container header: they can disagree
+                let channels = spec.channels.count();
+                if channels == 0 || num_frames == 0 {
+                    continue;
+                }
                 let mut sample_buf = SampleBuffer::<f64>::new(
                     num_frames as u64,
                     spec,
@@ -215,14 +705,15 @@ pub fn extract_audio(input_path: &Path, output_path: &Path) -> Result<()> {
                 sample_buf.copy_interleaved_ref(decoded);
                 let interleaved = sample_buf.samples();
 
-                // Convert to mono by averaging channels
-                if channels > 1 {
-                    for frame in 0..num_frames {
-                        let mut sum = 0.0;
-                        for ch in 0..channels {
-                            sum += interleaved[frame * channels + ch];
-                        }
-                        all_samples.push(sum / channels as f64);
+                if keep_stereo {
+                    for frame in interleaved.chunks_exact(channels) {
+                        all_samples.push(frame[0]);
+                        right_samples.push(frame.get(1).copied().unwrap_or(frame[0]));
+                    }
+                } else if channels > 1 {
+                    // Convert to mono by averaging channels
+                    for frame in interleaved.chunks_exact(channels) {
+                        all_samples.push(frame.iter().sum::<f64>() / channels as f64);
                     }
                 } else {
                     all_samples.extend_from_slice(interleaved);
@@ -234,18 +725,17 @@ pub fn extract_audio(input_path: &Path, output_path: &Path) -> Result<()> {
     }
 
     if all_samples.is_empty() {
-        anyhow::bail!("No audio decoded from {}", input_path.display());
+        anyhow::bail!("No audio decoded");
     }
 
-    // Resample to 16kHz if needed
-    let samples_16k = if source_sr != 16000 {
-        resample(&all_samples, source_sr, 16000)?
-    } else {
-        all_samples
-    };
-
-    write_wav(output_path, &samples_16k, 16000)?;
-    Ok(())
+    let mut planar = vec![all_samples];
+    if keep_stereo {
+        planar.push(right_samples);
+    }
+    if let Some(n) = flac_frames {
+        planar.iter_mut().for_each(|ch| ch.truncate(n as usize));
+    }
+    Ok((planar, source_sr))
 }
 
 #[cfg(test)]
@@ -299,10 +789,21 @@ mod tests {
 
         let dur = get_wav_duration(&path).unwrap();
         assert!((dur - 1.0).abs() < 0.001);
+        let probed = probe_duration(&path).unwrap();
+        assert!((probed - 1.0).abs() < 0.001);
+        assert!(probe_duration(&temp_wav_path("missing.wav")).is_err());
 
         std::fs::remove_file(&path).ok();
     }
 
+    #[test]
+    fn test_is_media_file() {
+        assert!(is_media_file(Path::new("a/b/take.WAV")));
+        assert!(is_media_file(Path::new("clip.mkv")));
+        assert!(!is_media_file(Path::new("notes.txt")));
+        assert!(!is_media_file(Path::new("wav")));
+    }
+
     #[test]
     fn test_extract_range() {
         let samples: Vec<f64> = (0..16000).map(|i| i as f64).collect();
@@ -318,6 +819,71 @@ mod tests {
         assert_eq!(extracted.len(), 100);
     }
 
+    #[test]
+    fn test_source_range_parse() {
+        let range = SourceRange::parse("talk.wav=120-300").unwrap();
+        assert_eq!(range.file, PathBuf::from("talk.wav"));
+        assert_eq!(range.start_s, 120.0);
+        assert_eq!(range.end_s, 300.0);
+
+        let range = SourceRange::parse("a=b.wav=1.5-2").unwrap();
+        assert_eq!(range.file, PathBuf::from("a=b.wav"));
+
+        assert!(SourceRange::parse("talk.wav").is_err());
+        assert!(SourceRange::parse("talk.wav=300-120").is_err());
+        assert!(SourceRange::parse("=1-2").is_err());
+        assert!(SourceRange::parse("talk.wav=a-b").is_err());
+    }
+
+    #[test]
+    fn test_source_range_matches() {
+        let range = SourceRange::parse("talk.wav=0-1").unwrap();
+        assert!(range.matches(Path::new("talk.wav")));
+        assert!(range.matches(Path::new("/data/talk.wav")));
+        assert!(!range.matches(Path::new("other.wav")));
+
+        let range = SourceRange::parse("/data/talk.wav=0-1").unwrap();
+        assert!(range.matches(Path::new("/data/talk.wav")));
+        assert!(!range.matches(Path::new("/other/talk.wav")));
+    }
+
+    #[test]
+    fn test_trim_wav() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_trim_{}", std::process::id()));
+        let path = dir.join("trim.wav");
+        write_wav(&path, &vec![0.25; 16000], 16000).unwrap();
+
+        trim_wav(&path, 0.25, 0.5).unwrap();
+        let (samples, sr) = read_wav(&path).unwrap();
+        assert_eq!(sr, 16000);
+        assert_eq!(samples.len(), 4000);
+
+        assert!(trim_wav(&path, 10.0, 20.0).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_load_stereo_sources_trims_and_keys_by_working_wav() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_stereo_src_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("talk.wav");
+        write_wav_channels(&input, &[vec![0.5; 16000], vec![-0.25; 16000]], 16000).unwrap();
+        let mono_input = dir.join("mono.wav");
+        write_wav(&mono_input, &vec![0.1; 8000], 16000).unwrap();
+        let audio_paths = vec![dir.join("talk_16k.wav"), dir.join("mono_16k.wav")];
+        let ranges = vec![SourceRange::parse("talk.wav=0.25-0.75").unwrap()];
+
+        let sources = load_stereo_sources(&[input, mono_input], &ranges, &audio_paths, None).unwrap();
+        let (channels, sr) = &sources[&audio_paths[0].to_string_lossy().to_string()];
+        assert_eq!(*sr, 16000);
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].len(), 8000);
+        assert!((channels[0][100] - 0.5).abs() < 1e-3 && (channels[1][100] + 0.25).abs() < 1e-3);
+        assert!(stereo_wav_path(&audio_paths[0]).exists());
+        assert_eq!(sources[&audio_paths[1].to_string_lossy().to_string()].0.len(), 1);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn test_resample_same_rate() {
         let samples = vec![1.0, 2.0, 3.0];
@@ -330,9 +896,17 @@ mod tests {
         // 4000 samples at 8kHz → should produce ~8000 samples at 16kHz
         let samples: Vec<f64> = (0..4000).map(|i| (i as f64 / 4000.0 * std::f64::consts::TAU).sin()).collect();
         let result = resample(&samples, 8000, 16000).unwrap();
-        // Sinc resampler loses samples at edges due to filter length; allow wide tolerance
-        assert!(result.len() >= 7000 && result.len() <= 8500,
-            "Expected ~8000 samples, got {}", result.len());
+        // The filter is flushed, so no samples are lost at the edges
+        assert_eq!(result.len(), 8000);
+    }
+
+    #[test]
+    fn test_resample_keeps_timing() {
+        let mut samples = vec![0.0; 4000];
+        samples[1000] = 1.0;
+        let result = resample(&samples, 16000, 8000).unwrap();
+        let peak = (0..result.len()).max_by(|&a, &b| result[a].total_cmp(&result[b])).unwrap();
+        assert!((peak as i64 - 500).abs() <= 1, "impulse moved to {}", peak);
     }
 
     #[test]
@@ -341,6 +915,111 @@ mod tests {
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn test_channels_count() {
+        assert_eq!(Channels::Mono.count(), 1);
+        assert_eq!(Channels::Stereo.count(), 2);
+        assert_eq!(Channels::from_count(1), Channels::Mono);
+        assert_eq!(Channels::from_count(6), Channels::Stereo);
+    }
+
+    #[test]
+    fn test_mix_to_mono_and_layout() {
+        let stereo = vec![vec![1.0, 0.0], vec![0.0, 1.0]];
+        assert_eq!(mix_to_mono(&stereo), vec![0.5, 0.5]);
+
+        let up = to_layout(&[vec![0.25, 0.5]], Channels::Stereo);
+        assert_eq!(up.len(), 2);
+        assert_eq!(up[0], up[1]);
+
+        let down = to_layout(&stereo, Channels::Mono);
+        assert_eq!(down, vec![vec![0.5, 0.5]]);
+    }
+
+    #[test]
+    fn test_write_read_channels_roundtrip() {
+        let path = temp_wav_path("stereo_roundtrip.wav");
+        let left: Vec<f64> = (0..500).map(|i| i as f64 / 1000.0).collect();
+        let right: Vec<f64> = left.iter().map(|v| -v).collect();
+        write_wav_channels(&path, &[left.clone(), right.clone()], 16000).unwrap();
+
+        let (channels, sr) = read_wav_channels(&path).unwrap();
+        assert_eq!(sr, 16000);
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].len(), 500);
+        assert!((channels[0][400] - left[400]).abs() < 0.001);
+        assert!((channels[1][400] - right[400]).abs() < 0.001);
+
+        // read_wav still returns the first channel
+        let (mono, _) = read_wav(&path).unwrap();
+        assert!((mono[400] - left[400]).abs() < 0.001);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_stream_writer_matches_write_wav_channels() {
+        let left: Vec<f64> = (0..900).map(|i| (i as f64 * 0.01).sin() * 1.2).collect();
+        let right: Vec<f64> = left.iter().take(850).map(|v| -v * 0.5).collect();
+        let whole = temp_wav_path("stream_whole.wav");
+        write_wav_channels(&whole, &[left.clone(), right.clone()], 16000).unwrap();
+
+        // Uneven blocks, the last with a short right channel
+        let streamed = temp_wav_path("stream_blocks.wav");
+        let mut writer = WavStreamWriter::create(&streamed, 2, 16000).unwrap();
+        for (a, b) in [(0, 1), (1, 400), (400, 400), (400, 900)] {
+            let r = &right[a.min(right.len())..b.min(right.len())];
+            writer.write_frames(&[&left[a..b], r]).unwrap();
+        }
+        assert_eq!(writer.frames_written(), 900);
+        writer.finalize().unwrap();
+
+        assert_eq!(std::fs::read(&streamed).unwrap(), std::fs::read(&whole).unwrap());
+        std::fs::remove_file(&whole).ok();
+        std::fs::remove_file(&streamed).ok();
+    }
+
+    #[test]
+    fn test_denoise_wav_learns_from_room_tone() {
+        use crate::audio::analysis::compute_rms;
+        use rand::rngs::StdRng;
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let hiss: Vec<f64> = (0..32000).map(|_| rng.gen_range(-0.01..0.01)).collect();
+        // One second of room tone, then a tone over the same hiss
+        let samples: Vec<f64> = hiss
+            .iter()
+            .enumerate()
+            .map(|(i, n)| if i < 16000 { *n } else { n + 0.4 * (i as f64 * 0.2).sin() })
+            .collect();
+        let path = temp_wav_path("denoise.wav");
+        write_wav(&path, &samples, 16000).unwrap();
+        assert!(denoise_wav(&path, DenoiseLevel::Light).unwrap());
+        let (denoised, _) = read_wav(&path).unwrap();
+        assert_eq!(denoised.len(), samples.len());
+        assert!(compute_rms(&denoised[2000..14000]) < compute_rms(&samples[2000..14000]) * 0.3);
+
+        // Aggressive also takes out 50 Hz hum under the hiss
+        let hummy: Vec<f64> = samples
+            .iter()
+            .enumerate()
+            .map(|(i, s)| s + 0.05 * (2.0 * std::f64::consts::PI * 50.0 * i as f64 / 16000.0).sin())
+            .collect();
+        write_wav(&path, &hummy, 16000).unwrap();
+        assert!(denoise_wav(&path, DenoiseLevel::Aggressive).unwrap());
+        let (denoised, _) = read_wav(&path).unwrap();
+        assert!(compute_rms(&denoised[2000..14000]) < compute_rms(&hummy[2000..14000]) * 0.1);
+
+        // A steady tone has no room tone to learn from
+        let tone: Vec<f64> = (0..16000).map(|i| 0.4 * (i as f64 * 0.2).sin()).collect();
+        write_wav(&path, &tone, 16000).unwrap();
+        let before = std::fs::read(&path).unwrap();
+        assert!(!denoise_wav(&path, DenoiseLevel::Aggressive).unwrap());
+        assert_eq!(std::fs::read(&path).unwrap(), before);
+        std::fs::remove_file(&path).ok();
+    }
+
     #[test]
     fn test_extract_audio_native_wav() {
         // Create a WAV file, then extract it via the native path
@@ -375,4 +1054,93 @@ mod tests {
 
         std::fs::remove_dir_all(&dir).ok();
     }
+
+    #[test]
+    fn test_extract_audio_keeps_stereo() {
+        let dir = std::env::temp_dir().join("glottisdale_test_extract_stereo");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let input = dir.join("input.wav");
+        let output = dir.join("output.wav");
+
+        // Left channel carries signal, right channel is silent
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate: 16000,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        };
+        let mut writer = hound::WavWriter::create(&input, spec).unwrap();
+        for i in 0..16000 {
+            let sample = ((i as f64 / 16000.0 * 440.0 * std::f64::consts::TAU).sin() * 16000.0) as i16;
+            writer.write_sample(sample).unwrap();
+            writer.write_sample(0i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        extract_audio_channels(&input, &output, Channels::Stereo).unwrap();
+        let (channels, sr) = read_wav_channels(&output).unwrap();
+        assert_eq!(sr, 16000);
+        assert_eq!(channels.len(), 2);
+        let left_peak = channels[0].iter().fold(0.0f64, |m, v| m.max(v.abs()));
+        let right_peak = channels[1].iter().fold(0.0f64, |m, v| m.max(v.abs()));
+        assert!(left_peak > 0.3);
+        assert!(right_peak < 0.001);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_decode_audio_bytes() {
+        let path = temp_wav_path("decode_bytes.wav");
+        let samples: Vec<f64> = (0..8000).map(|i| (i as f64 * 0.05).sin() * 0.5).collect();
+        write_wav(&path, &samples, 16000).unwrap();
+        let data = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let planar = decode_audio_bytes(data.clone(), Some("wav"), Channels::Mono).unwrap();
+        assert_eq!(planar.len(), 1);
+        assert_eq!(planar[0].len(), 8000);
+
+        // The native variant keeps the media's rate instead of resampling
+        write_wav(&path, &samples, 44100).unwrap();
+        let hi_res = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+        let (planar, sr) = decode_audio_bytes_native(hi_res.clone(), Some("wav"), Channels::Mono).unwrap();
+        assert_eq!((planar[0].len(), sr), (8000, 44100));
+        let resampled = decode_audio_bytes(hi_res, Some("wav"), Channels::Mono).unwrap();
+        assert!((resampled[0].len() as i64 - 8000 * 16000 / 44100).abs() < 8, "{} frames", resampled[0].len());
+
+        // Malformed input errors (or decodes what it can) without panicking
+        assert!(decode_audio_bytes(Vec::new(), None, Channels::Mono).is_err());
+        assert!(decode_audio_bytes(b"RIFF\xff\xff\xff\xffWAVEjunk".to_vec(), Some("wav"), Channels::Mono).is_err());
+        for cut in [12, 44, 45, 1001] {
+            let _ = decode_audio_bytes(data[..cut].to_vec(), Some("wav"), Channels::Stereo);
+        }
+    }
+
+    mod props {
+        use super::*;
+        use proptest::prelude::*;
+
+        fn rates() -> impl Strategy<Value = u32> {
+            prop::sample::select(vec![8000u32, 16000, 22050, 44100, 48000])
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(24))]
+
+            #[test]
+            fn resample_length_follows_rate_ratio(n in 1usize..3000, from in rates(), to in rates()) {
+                let samples: Vec<f64> = (0..n).map(|i| (i as f64 * 0.01).sin()).collect();
+                let out = resample(&samples, from, to).unwrap();
+                let expected = n as f64 * to as f64 / from as f64;
+                prop_assert!(
+                    (out.len() as f64 - expected).abs() <= 1.0,
+                    "{} samples at {} -> {}: got {}, expected {:.1}",
+                    n, from, to, out.len(), expected
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,402 @@
+//! Loudness measurement and normalization after ITU-R BS.1770 / EBU R128.
+//!
+//! Integrated loudness is measured on K-weighted audio in 400 ms blocks
+//! overlapping by 75%, gated at -70 LUFS and then 10 LU below the
+//! ungated level. Normalization applies one gain to the whole file,
+//! reduced if needed so sample peaks stay under [`PEAK_CEILING_DBFS`].
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::effects::{apply_reverb, map_channels, ReverbIr};
+use super::io::{read_wav_channels, write_wav_channels};
+use crate::profile::{self, Category};
+
+/// Default integrated loudness target for final outputs (LUFS).
+pub const DEFAULT_TARGET_LUFS: f64 = -16.0;
+
+/// Highest sample peak normalization may raise a file to (dBFS).
+pub const PEAK_CEILING_DBFS: f64 = -1.0;
+
+/// Blocks quieter than this never count towards integrated loudness.
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+
+/// Blocks this far below the absolute-gated level are dropped too.
+const RELATIVE_GATE_LU: f64 = 10.0;
+
+/// Gating block length and hop, in 100 ms steps.
+const BLOCK_STEPS: usize = 4;
+
+/// A two-pole IIR filter in direct form I.
+#[derive(Clone)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self { b, a, x: [0.0; 2], y: [0.0; 2] }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.b[1] * self.x[0] + self.b[2] * self.x[1]
+            - self.a[0] * self.y[0]
+            - self.a[1] * self.y[1];
+        self.x = [x, self.x[0]];
+        self.y = [y, self.y[0]];
+        y
+    }
+}
+
+/// The BS.1770 K-weighting filter (high shelf, then high pass) with
+/// coefficients derived for any sample rate.
+fn k_weighting(sr: u32) -> [Biquad; 2] {
+    let sr = sr as f64;
+
+    let (f0, gain_db, q) = (1681.974450955533, 3.999843853973347, 0.7071752369554196);
+    let k = (std::f64::consts::PI * f0 / sr).tan();
+    let vh = 10f64.powf(gain_db / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad::new(
+        [(vh + vb * k / q + k * k) / a0, 2.0 * (k * k - vh) / a0, (vh - vb * k / q + k * k) / a0],
+        [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+    );
+
+    let (f0, q) = (38.13547087602444, 0.5003270373238773);
+    let k = (std::f64::consts::PI * f0 / sr).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad::new([1.0, -2.0, 1.0], [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0]);
+
+    [shelf, high_pass]
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// Integrated loudness of planar audio in LUFS.
+///
+/// Every channel is weighted 1, as for front channels in BS.1770.
+/// Returns `None` for audio shorter than one 400 ms block or silent
+/// enough to be gated out entirely.
+pub fn integrated_loudness(channels: &[Vec<f64>], sr: u32) -> Option<f64> {
+    let step = (sr as usize / 10).max(1);
+    let frames = channels.iter().map(|c| c.len()).max().unwrap_or(0);
+    let n_steps = frames / step;
+    if n_steps < BLOCK_STEPS {
+        return None;
+    }
+
+    // Sum of K-weighted squares per 100 ms step, over all channels
+    let mut step_energy = vec![0.0; n_steps];
+    for channel in channels {
+        let mut filters = k_weighting(sr);
+        for (i, &sample) in channel.iter().take(n_steps * step).enumerate() {
+            let weighted = filters.iter_mut().fold(sample, |x, f| f.process(x));
+            step_energy[i / step] += weighted * weighted;
+        }
+    }
+
+    let block_len = (BLOCK_STEPS * step) as f64;
+    let blocks: Vec<f64> = step_energy
+        .windows(BLOCK_STEPS)
+        .map(|w| w.iter().sum::<f64>() / block_len)
+        .filter(|&p| p > 0.0 && power_to_lufs(p) > ABSOLUTE_GATE_LUFS)
+        .collect();
+    if blocks.is_empty() {
+        return None;
+    }
+    let mean = |powers: &[f64]| powers.iter().sum::<f64>() / powers.len() as f64;
+    let relative_gate = power_to_lufs(mean(&blocks)) - RELATIVE_GATE_LU;
+    let gated: Vec<f64> = blocks
+        .into_iter()
+        .filter(|&p| power_to_lufs(p) > relative_gate)
+        .collect();
+    Some(power_to_lufs(mean(&gated)))
+}
+
+/// Highest absolute sample value across all channels.
+pub fn sample_peak(channels: &[Vec<f64>]) -> f64 {
+    channels
+        .iter()
+        .flat_map(|c| c.iter())
+        .fold(0.0f64, |peak, s| peak.max(s.abs()))
+}
+
+/// Measured and applied loudness of one output, as reported in the
+/// run manifest.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LoudnessReport {
+    /// Integrated loudness before normalization (`None` if silent).
+    pub measured_lufs: Option<f64>,
+    /// Loudness the output was normalized towards, if any.
+    pub target_lufs: Option<f64>,
+    /// Gain applied, in dB.
+    pub gain_db: f64,
+    /// Integrated loudness after the gain (`None` if silent).
+    pub output_lufs: Option<f64>,
+    /// Sample peak after the gain, in dBFS.
+    pub peak_dbfs: f64,
+    /// Whether the peak ceiling held the gain below the target.
+    pub peak_limited: bool,
+}
+
+/// Measure `channels` and, given a target, apply the gain that brings
+/// them to it without pushing peaks past [`PEAK_CEILING_DBFS`].
+///
+/// Silent audio is left untouched.
+pub fn normalize_loudness(channels: &mut [Vec<f64>], sr: u32, target_lufs: Option<f64>) -> LoudnessReport {
+    let measured = integrated_loudness(channels, sr);
+    let peak = sample_peak(channels);
+
+    let mut gain_db = match (measured, target_lufs) {
+        (Some(measured), Some(target)) => target - measured,
+        _ => 0.0,
+    };
+    let mut peak_limited = false;
+    if peak > 0.0 {
+        let headroom_db = PEAK_CEILING_DBFS - 20.0 * peak.log10();
+        if gain_db > headroom_db && gain_db > 0.0 {
+            gain_db = headroom_db.max(0.0);
+            peak_limited = true;
+        }
+    }
+
+    if gain_db != 0.0 {
+        let gain = 10f64.powf(gain_db / 20.0);
+        for sample in channels.iter_mut().flat_map(|c| c.iter_mut()) {
+            *sample *= gain;
+        }
+    }
+
+    LoudnessReport {
+        measured_lufs: measured,
+        target_lufs,
+        gain_db,
+        output_lufs: measured.map(|m| m + gain_db),
+        peak_dbfs: if peak > 0.0 { 20.0 * peak.log10() + gain_db } else { f64::NEG_INFINITY },
+        peak_limited,
+    }
+}
+
+/// Normalize a WAV file in place; with no target, only measure it.
+pub fn normalize_wav(path: &Path, target_lufs: Option<f64>) -> Result<LoudnessReport> {
+    let (mut channels, sr) = read_wav_channels(path)?;
+    let report = normalize_loudness(&mut channels, sr, target_lufs);
+    if report.gain_db != 0.0 {
+        write_wav_channels(path, &channels, sr)?;
+    }
+    Ok(report)
+}
+
+/// The last processing every pipeline gives its final audio: optional
+/// reverb, then loudness normalization, so the reverb tail counts
+/// towards the measured level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mastering {
+    /// Convolution reverb and its wet/dry mix (None = dry).
+    pub reverb: Option<(ReverbIr, f64)>,
+    /// Loudness to normalize to (None = measure only).
+    pub target_lufs: Option<f64>,
+}
+
+impl Default for Mastering {
+    fn default() -> Self {
+        Self { reverb: None, target_lufs: Some(DEFAULT_TARGET_LUFS) }
+    }
+}
+
+impl Mastering {
+    /// Master `channels` and write them to `path`.
+    pub fn write(&self, path: &Path, channels: &[Vec<f64>], sr: u32) -> Result<LoudnessReport> {
+        let mut channels = match self.reverb {
+            Some((ir, mix)) => {
+                log::info!("Adding {} reverb to {} (mix {:.2})", ir.name(), path.display(), mix);
+                let _span = profile::span(Category::Dsp, "reverb");
+                map_channels(channels, |ch| apply_reverb(ch, sr, ir, mix))?
+            }
+            None => channels.to_vec(),
+        };
+        let report = normalize_loudness(&mut channels, sr, self.target_lufs);
+        log_loudness(path, &report);
+        write_wav_channels(path, &channels, sr)?;
+        Ok(report)
+    }
+
+    /// Master a WAV file in place, for outputs streamed straight to disk.
+    pub fn apply_wav(&self, path: &Path) -> Result<LoudnessReport> {
+        if self.reverb.is_none() {
+            let report = normalize_wav(path, self.target_lufs)?;
+            log_loudness(path, &report);
+            return Ok(report);
+        }
+        let (channels, sr) = read_wav_channels(path)?;
+        self.write(path, &channels, sr)
+    }
+}
+
+fn log_loudness(path: &Path, report: &LoudnessReport) {
+    match report.measured_lufs {
+        Some(lufs) => log::info!(
+            "Loudness of {}: {:.1} LUFS, gain {:+.1} dB{}",
+            path.display(),
+            lufs,
+            report.gain_db,
+            if report.peak_limited { " (held back by the peak ceiling)" } else { "" }
+        ),
+        None => log::info!("{} is silent; loudness left as is", path.display()),
+    }
+}
+
+/// The manifest's "loudness" table holding `report` for `output`.
+pub fn loudness_table(output: &Path, report: &LoudnessReport) -> Result<serde_json::Value> {
+    let name = output.file_name().unwrap_or_default().to_string_lossy().to_string();
+    // A silent output's -inf peak becomes null
+    Ok(serde_json::json!({ name: serde_json::to_value(report)? }))
+}
+
+/// Add `report` for `output` under "loudness" in the run's manifest.json,
+/// creating the manifest if the pipeline did not write one.
+pub fn record_loudness(run_dir: &Path, output: &Path, report: &LoudnessReport) -> Result<()> {
+    let manifest_path = run_dir.join("manifest.json");
+    let mut manifest: serde_json::Value = match std::fs::read_to_string(&manifest_path) {
+        Ok(text) => serde_json::from_str(&text)
+            .with_context(|| format!("Failed to parse {}", manifest_path.display()))?,
+        Err(_) => serde_json::json!({}),
+    };
+    let name = output.file_name().unwrap_or_default().to_string_lossy().to_string();
+    // A silent output's -inf peak becomes null
+    manifest["loudness"][name] = serde_json::to_value(report)?;
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+        .with_context(|| format!("Failed to write {}", manifest_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(amplitude: f64, freq: f64, seconds: f64, sr: u32) -> Vec<f64> {
+        (0..(seconds * sr as f64) as usize)
+            .map(|i| amplitude * (2.0 * std::f64::consts::PI * freq * i as f64 / sr as f64).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_integrated_loudness_of_sine() {
+        // A 1 kHz sine at -20 dBFS peak reads about -23 LUFS mono, 3 LU
+        // louder on both channels of a stereo file.
+        for sr in [44100, 48000] {
+            let tone = sine(0.1, 1000.0, 3.0, sr);
+            let mono = integrated_loudness(std::slice::from_ref(&tone), sr).unwrap();
+            assert!((mono + 23.0).abs() < 0.2, "mono at {} Hz: {}", sr, mono);
+            let stereo = integrated_loudness(&[tone.clone(), tone], sr).unwrap();
+            assert!((stereo - mono - 3.01).abs() < 0.05);
+        }
+    }
+
+    #[test]
+    fn test_integrated_loudness_gates_silence() {
+        let sr = 16000;
+        assert_eq!(integrated_loudness(&[vec![0.0; sr as usize * 2]], sr), None);
+        assert_eq!(integrated_loudness(&[sine(0.5, 1000.0, 0.3, sr)], sr), None);
+        assert_eq!(integrated_loudness(&[], sr), None);
+
+        // Long silences between phrases do not drag the level down
+        let tone = sine(0.1, 1000.0, 2.0, sr);
+        let mut gappy = tone.clone();
+        gappy.extend(vec![0.0; sr as usize * 4]);
+        gappy.extend(&tone);
+        let plain = integrated_loudness(&[tone], sr).unwrap();
+        let gated = integrated_loudness(&[gappy], sr).unwrap();
+        assert!((plain - gated).abs() < 1.0, "{} vs {}", plain, gated);
+    }
+
+    #[test]
+    fn test_normalize_loudness_hits_target() {
+        let sr = 22050;
+        let mut channels = vec![sine(0.05, 440.0, 2.0, sr)];
+        let report = normalize_loudness(&mut channels, sr, Some(-16.0));
+        assert!(!report.peak_limited);
+        assert!((report.output_lufs.unwrap() + 16.0).abs() < 1e-9);
+        let after = integrated_loudness(&channels, sr).unwrap();
+        assert!((after + 16.0).abs() < 0.05);
+
+        // Measure only
+        let before = channels.clone();
+        let report = normalize_loudness(&mut channels, sr, None);
+        assert_eq!(report.gain_db, 0.0);
+        assert_eq!(channels, before);
+    }
+
+    #[test]
+    fn test_normalize_loudness_respects_peak_ceiling() {
+        let sr = 22050;
+        // A short loud click over a quiet bed: reaching the target would clip
+        let mut bed = sine(0.01, 300.0, 2.0, sr);
+        bed[1000] = 0.5;
+        let mut channels = vec![bed];
+        let report = normalize_loudness(&mut channels, sr, Some(-10.0));
+        assert!(report.peak_limited);
+        assert!((report.peak_dbfs - PEAK_CEILING_DBFS).abs() < 1e-9);
+        assert!(sample_peak(&channels) <= 10f64.powf(PEAK_CEILING_DBFS / 20.0) + 1e-12);
+        assert!(report.output_lufs.unwrap() < -10.0);
+    }
+
+    #[test]
+    fn test_mastering_reverb_before_normalization() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_mastering_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.wav");
+        let sr = 16000;
+        let tone = sine(0.05, 440.0, 2.0, sr);
+
+        let mastering = Mastering { reverb: Some((ReverbIr::Room, 0.5)), target_lufs: Some(-20.0) };
+        let report = mastering.write(&path, std::slice::from_ref(&tone), sr).unwrap();
+        let (written, _) = read_wav_channels(&path).unwrap();
+        assert!(written[0].len() > tone.len(), "reverb tail missing");
+        // The level is measured after the reverb, so the file hits the target
+        let lufs = integrated_loudness(&written, sr).unwrap();
+        assert!(!report.peak_limited);
+        assert!((lufs + 20.0).abs() < 0.5, "{}", lufs);
+        assert!((lufs - report.output_lufs.unwrap()).abs() < 0.1);
+
+        // Measure only: the file is left at its level
+        write_wav_channels(&path, std::slice::from_ref(&tone), sr).unwrap();
+        let measure = Mastering { reverb: None, target_lufs: None };
+        let report = measure.apply_wav(&path).unwrap();
+        assert_eq!(report.gain_db, 0.0);
+        assert_eq!(read_wav_channels(&path).unwrap().0[0].len(), tone.len());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_record_loudness_merges_into_manifest() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_loudness_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("manifest.json"), r#"{"mode": "phrase"}"#).unwrap();
+        let report = LoudnessReport {
+            measured_lufs: Some(-20.0),
+            target_lufs: Some(-16.0),
+            gain_db: 4.0,
+            output_lufs: Some(-16.0),
+            peak_dbfs: -3.0,
+            peak_limited: false,
+        };
+        record_loudness(&dir, &dir.join("run.flac"), &report).unwrap();
+        let silent = LoudnessReport { peak_dbfs: f64::NEG_INFINITY, ..report };
+        record_loudness(&dir, &dir.join("quiet.wav"), &silent).unwrap();
+
+        let text = std::fs::read_to_string(dir.join("manifest.json")).unwrap();
+        let manifest: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(manifest["mode"], "phrase");
+        assert_eq!(manifest["loudness"]["run.flac"]["gain_db"], 4.0);
+        assert!(manifest["loudness"]["quiet.wav"]["peak_dbfs"].is_null());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
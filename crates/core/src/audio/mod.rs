@@ -1,4 +1,9 @@
 pub mod io;
+pub mod encode;
 pub mod analysis;
 pub mod effects;
+pub mod loudness;
+pub mod sample;
 pub mod playback;
+pub mod capture;
+pub mod device;
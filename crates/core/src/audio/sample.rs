@@ -0,0 +1,59 @@
+//! Sample types for audio buffers.
+//!
+//! Pipelines keep `f64` buffers; long-lived storage such as the editor's
+//! clip bank uses `f32` to halve memory. DSP functions are generic over
+//! [`Sample`] and do their arithmetic in `f64`, so `f64` callers get
+//! exactly the results they always did and `f32` buffers only round on
+//! store.
+
+use std::fmt::Debug;
+
+/// A mono audio sample: `f32` or `f64`.
+pub trait Sample: Copy + Default + PartialEq + PartialOrd + Debug + Send + Sync + 'static {
+    fn to_f64(self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+impl Sample for f64 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl Sample for f32 {
+    #[inline]
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    #[inline]
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+/// Convert a buffer between sample types.
+pub fn convert<A: Sample, B: Sample>(samples: &[A]) -> Vec<B> {
+    samples.iter().map(|&s| B::from_f64(s.to_f64())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_roundtrip() {
+        let wide = [0.25f64, -1.0, 0.1];
+        let narrow: Vec<f32> = convert(&wide);
+        assert_eq!(narrow, vec![0.25f32, -1.0, 0.1]);
+        let back: Vec<f64> = convert(&narrow);
+        assert_eq!(back[..2], [0.25, -1.0]);
+        assert!((back[2] - 0.1).abs() < 1e-7);
+    }
+}
@@ -0,0 +1,93 @@
+//! Sentence intonation: pitch falls gradually across each collage sentence,
+//! and questions rise at the end. Applied as one pitch shift per word
+//! before the words are joined.
+
+use rand::rngs::StdRng;
+use rand::Rng;
+
+/// Total fall across a sentence, in semitones. The first word sits half
+/// of this above its source pitch and the last word half below.
+pub const DECLINATION_ST: f64 = 2.0;
+
+/// Rise over the last words of a question, in semitones.
+pub const QUESTION_RISE_ST: f64 = 4.0;
+
+/// Words at the end of a question that carry the rise.
+const QUESTION_RISE_WORDS: usize = 2;
+
+/// Overall pitch shape of a sentence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Contour {
+    /// Falls from start to end.
+    Statement,
+    /// Falls, then rises over the last words.
+    Question,
+}
+
+impl Contour {
+    /// A question with probability `question_probability`, else a
+    /// statement. Draws from `rng` only when questions are possible.
+    pub fn pick(question_probability: f64, rng: &mut StdRng) -> Self {
+        if question_probability > 0.0 && rng.gen::<f64>() < question_probability {
+            Self::Question
+        } else {
+            Self::Statement
+        }
+    }
+}
+
+/// Pitch shift in semitones for each of a sentence's `n_words` words.
+pub fn word_shifts(n_words: usize, contour: Contour) -> Vec<f64> {
+    let fall = |k: usize| {
+        if n_words <= 1 {
+            0.0
+        } else {
+            DECLINATION_ST * (0.5 - k as f64 / (n_words - 1) as f64)
+        }
+    };
+    let mut shifts: Vec<f64> = (0..n_words).map(fall).collect();
+    if contour == Contour::Question && n_words > 0 {
+        let rising = QUESTION_RISE_WORDS.min(n_words);
+        let base = fall(n_words - rising);
+        for (j, shift) in shifts[n_words - rising..].iter_mut().enumerate() {
+            *shift = base + QUESTION_RISE_ST * (j + 1) as f64 / rising as f64;
+        }
+    }
+    shifts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_word_shifts_statement_falls() {
+        let shifts = word_shifts(5, Contour::Statement);
+        assert_eq!(shifts.len(), 5);
+        assert!((shifts[0] - DECLINATION_ST / 2.0).abs() < 1e-9);
+        assert!((shifts[4] + DECLINATION_ST / 2.0).abs() < 1e-9);
+        assert!(shifts.windows(2).all(|w| w[1] < w[0]));
+        assert_eq!(word_shifts(1, Contour::Statement), vec![0.0]);
+        assert!(word_shifts(0, Contour::Question).is_empty());
+    }
+
+    #[test]
+    fn test_word_shifts_question_rises_at_end() {
+        let shifts = word_shifts(5, Contour::Question);
+        let statement = word_shifts(5, Contour::Statement);
+        assert_eq!(shifts[..3], statement[..3]);
+        assert!(shifts[3] > shifts[2] && shifts[4] > shifts[3]);
+        assert!((shifts[4] - (statement[3] + QUESTION_RISE_ST)).abs() < 1e-9);
+        assert_eq!(word_shifts(1, Contour::Question), vec![QUESTION_RISE_ST]);
+    }
+
+    #[test]
+    fn test_contour_pick() {
+        let mut rng = StdRng::seed_from_u64(1);
+        assert!((0..20).all(|_| Contour::pick(0.0, &mut rng) == Contour::Statement));
+        assert!((0..20).all(|_| Contour::pick(1.0, &mut rng) == Contour::Question));
+        let questions = (0..1000).filter(|_| Contour::pick(0.3, &mut rng) == Contour::Question).count();
+        assert!((200..400).contains(&questions));
+    }
+}
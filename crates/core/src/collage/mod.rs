@@ -1,3 +1,8 @@
 pub mod stretch;
 pub mod process;
 pub mod shuffle;
+pub mod phrases;
+pub mod tempo;
+pub mod subtitles;
+pub mod intonation;
+pub mod preview;
@@ -0,0 +1,313 @@
+//! Phrase mode: cut-up collage of whole source phrases.
+//!
+//! Splits each source at natural pauses (silence between words, or a word
+//! ending in punctuation) and reassembles the phrases in random order, for
+//! a "cut-up interview" sound instead of syllable gibberish.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{Result, bail};
+use rand::Rng;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::audio::effects::{cut_clip, generate_silence};
+use crate::audio::io::{write_wav, WavStreamWriter};
+use crate::audio::loudness::loudness_table;
+use crate::collage::process::{normalize_volume_clips, parse_gap, CollageConfig};
+use crate::progress::{ProgressReporter, Stage};
+use crate::source::{relative_to, source_table, SourceId};
+use crate::types::{Clip, PipelineResult, Syllable};
+
+/// Phrases shorter than this are dropped (seconds).
+const MIN_PHRASE_S: f64 = 0.3;
+
+/// Fade at phrase edges (ms).
+const PHRASE_FADE_MS: f64 = 10.0;
+
+/// Split aligned syllables into phrases at pauses and punctuation.
+///
+/// A phrase ends after a word whose text ends in `. , ; : ! ?` (or a dash),
+/// or when the gap before the next word is at least `min_pause_s`.
+pub fn detect_phrases(syllables: &[Syllable], min_pause_s: f64) -> Vec<Vec<Syllable>> {
+    let mut phrases: Vec<Vec<Syllable>> = Vec::new();
+    let mut current: Vec<Syllable> = Vec::new();
+
+    for (i, syl) in syllables.iter().enumerate() {
+        current.push(syl.clone());
+        let Some(next) = syllables.get(i + 1) else { break };
+        let word_ends = next.word_index != syl.word_index || next.word != syl.word;
+        if !word_ends {
+            continue;
+        }
+        let punctuated = syl
+            .word
+            .trim_end()
+            .ends_with(['.', ',', ';', ':', '!', '?', '-', '—']);
+        if punctuated || next.start - syl.end >= min_pause_s {
+            phrases.push(std::mem::take(&mut current));
+        }
+    }
+    if !current.is_empty() {
+        phrases.push(current);
+    }
+    phrases
+}
+
+/// Spoken text of a phrase, one entry per word.
+fn phrase_text(phrase: &[Syllable]) -> String {
+    let mut words: Vec<&str> = Vec::new();
+    let mut last: Option<(usize, &str)> = None;
+    for syl in phrase {
+        let key = (syl.word_index, syl.word.as_str());
+        if last != Some(key) {
+            words.push(syl.word.trim());
+            last = Some(key);
+        }
+    }
+    words.join(" ")
+}
+
+/// Run the phrase-mode collage pipeline.
+///
+/// Uses `target_duration`, `padding_ms`, `phrase_pause` (silence between
+/// phrases), `phrase_min_pause_ms`, `volume_normalize`, and `seed` from the
+/// config; syllable-level options do not apply.
+pub fn process_phrases(
+    source_audio: &HashMap<String, (Vec<f64>, u32)>,
+    source_syllables: &HashMap<String, Vec<Syllable>>,
+    output_dir: &Path,
+    config: &CollageConfig,
+    progress: &dyn ProgressReporter,
+) -> Result<PipelineResult> {
+    let mut rng = match config.seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => StdRng::from_entropy(),
+    };
+
+    std::fs::create_dir_all(output_dir)?;
+    let clips_dir = output_dir.join("clips");
+    std::fs::create_dir_all(&clips_dir)?;
+
+    let sr = source_audio
+        .values()
+        .next()
+        .map(|(_, sr)| *sr)
+        .unwrap_or(16000);
+
+    // Collect phrases from every source (sorted for seed reproducibility)
+    let mut source_names: Vec<&String> = source_syllables.keys().collect();
+    source_names.sort();
+    let mut pool: Vec<(String, Vec<Syllable>)> = Vec::new();
+    for name in source_names {
+        let phrases = detect_phrases(&source_syllables[name], config.phrase_min_pause_ms / 1000.0);
+        pool.extend(
+            phrases
+                .into_iter()
+                .filter(|p| p.last().unwrap().end - p[0].start >= MIN_PHRASE_S)
+                .map(|p| (name.clone(), p)),
+        );
+    }
+    let total_phrases = pool.len();
+    if pool.is_empty() {
+        bail!("No phrases found in the source audio");
+    }
+    log::info!("Phrase mode: {} phrases detected", total_phrases);
+
+    pool.shuffle(&mut rng);
+
+    // Select phrases until the target duration is reached
+    let mut selected: Vec<(String, Vec<Syllable>, Vec<Vec<f64>>)> = Vec::new();
+    let mut total_dur = 0.0;
+    progress.stage(Stage::Cutting, 0.0);
+    for (source, phrase) in pool {
+        if total_dur >= config.target_duration {
+            break;
+        }
+        progress.stage(Stage::Cutting, total_dur / config.target_duration);
+        let Some((samples, sample_rate)) = source_audio.get(&source) else {
+            continue;
+        };
+        let start = phrase[0].start;
+        let end = phrase.last().unwrap().end;
+        let mut audio = cut_clip(samples, *sample_rate, start, end, config.padding_ms, PHRASE_FADE_MS);
+        if audio.is_empty() {
+            continue;
+        }
+        if let Some(fx) = config.voice_fx {
+            audio = fx.apply(&audio, *sample_rate);
+        }
+        total_dur += audio.len() as f64 / sr as f64;
+        selected.push((source, phrase, vec![audio]));
+    }
+
+    if config.volume_normalize {
+        let mut audio: Vec<Vec<Vec<f64>>> = selected.iter().map(|(_, _, a)| a.clone()).collect();
+        normalize_volume_clips(&mut audio, &config.volume_normalization);
+        for (entry, normalized) in selected.iter_mut().zip(audio) {
+            entry.2 = normalized;
+        }
+    }
+
+    // Assemble with natural pauses between phrases
+    progress.stage(Stage::Assembly, 0.0);
+    let (pause_min, pause_max) = parse_gap(&config.phrase_pause);
+    let run_name = output_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let concatenated_path = output_dir.join(format!("{}.wav", run_name));
+    let mut output = WavStreamWriter::create(&concatenated_path, 1, sr)?.limited(config.limiter);
+    let mut clips = Vec::with_capacity(selected.len());
+    for (i, (source, phrase, audio)) in selected.iter().enumerate() {
+        progress.stage(Stage::Assembly, 0.9 * i as f64 / selected.len() as f64);
+        let clip_path = clips_dir.join(format!("{:03}_phrase.wav", i + 1));
+        write_wav(&clip_path, &audio[0], sr)?;
+        if i > 0 {
+            let pause = if pause_max > pause_min {
+                rng.gen_range(pause_min..=pause_max)
+            } else {
+                pause_min
+            };
+            output.write_frames(&[generate_silence::<f64>(pause, sr)])?;
+        }
+        output.write_frames(audio)?;
+        clips.push(Clip {
+            syllables: phrase.clone(),
+            start: phrase[0].start,
+            end: phrase.last().unwrap().end,
+            source: SourceId::intern(source),
+            output_path: clip_path,
+        });
+    }
+
+    let output_frames = output.frames_written();
+    output.finalize()?;
+    let loudness = config.mastering.apply_wav(&concatenated_path)?;
+
+    let transcript = clips
+        .iter()
+        .map(|c| phrase_text(&c.syllables))
+        .collect::<Vec<_>>()
+        .join(" / ");
+
+    let sources = source_table(source_syllables.keys());
+    let manifest = serde_json::json!({
+        "mode": "phrase",
+        "sources": sources.iter().map(|s| relative_to(s.path(), output_dir)).collect::<Vec<_>>(),
+        "total_phrases": total_phrases,
+        "selected_phrases": clips.len(),
+        "duration": output_frames as f64 / sr as f64,
+        "loudness": loudness_table(&concatenated_path, &loudness)?,
+        "clips": clips.iter().map(|c| {
+            serde_json::json!({
+                "filename": c.output_path.file_name().unwrap_or_default().to_string_lossy(),
+                "source": sources.iter().position(|&s| s == c.source),
+                "text": phrase_text(&c.syllables),
+                "start": c.start,
+                "end": c.end,
+            })
+        }).collect::<Vec<_>>(),
+    });
+    let manifest_path = output_dir.join("manifest.json");
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+    progress.stage(Stage::Assembly, 1.0);
+
+    Ok(PipelineResult {
+        clips,
+        concatenated: concatenated_path,
+        transcript,
+        manifest,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::progress::NoProgress;
+
+    fn syl(word: &str, word_index: usize, start: f64, end: f64) -> Syllable {
+        Syllable {
+            phonemes: vec![],
+            start,
+            end,
+            word: word.to_string(),
+            word_index,
+        }
+    }
+
+    #[test]
+    fn test_detect_phrases_splits_on_pause_and_punctuation() {
+        let syls = vec![
+            syl("so", 0, 0.0, 0.2),
+            syl("anyway,", 1, 0.2, 0.4),
+            syl("anyway,", 1, 0.4, 0.6),
+            syl("we", 2, 0.6, 0.7),
+            syl("went", 3, 0.7, 0.9),
+            // long pause
+            syl("home", 4, 1.5, 1.8),
+        ];
+        let phrases = detect_phrases(&syls, 0.3);
+        assert_eq!(phrases.len(), 3);
+        assert_eq!(phrase_text(&phrases[0]), "so anyway,");
+        assert_eq!(phrase_text(&phrases[1]), "we went");
+        assert_eq!(phrase_text(&phrases[2]), "home");
+    }
+
+    #[test]
+    fn test_detect_phrases_keeps_words_whole() {
+        // A pause inside a word never splits it
+        let syls = vec![syl("hello", 0, 0.0, 0.2), syl("hello", 0, 0.8, 1.0)];
+        assert_eq!(detect_phrases(&syls, 0.3).len(), 1);
+    }
+
+    #[test]
+    fn test_process_phrases() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_phrases_{}", std::process::id()));
+        let sr = 16000u32;
+        let samples: Vec<f64> = (0..sr as usize * 6)
+            .map(|i| (i as f64 / sr as f64 * 200.0 * std::f64::consts::TAU).sin() * 0.3)
+            .collect();
+        // Four one-second phrases separated by half-second pauses
+        let syls: Vec<Syllable> = (0..8)
+            .map(|i| {
+                let phrase = i / 2;
+                let start = phrase as f64 * 1.5 + (i % 2) as f64 * 0.5;
+                syl(&format!("w{}", i), i, start, start + 0.5)
+            })
+            .collect();
+        let mut audio = HashMap::new();
+        audio.insert("src".to_string(), (samples, sr));
+        let mut syllables = HashMap::new();
+        syllables.insert("src".to_string(), syls);
+
+        let config = CollageConfig {
+            target_duration: 2.5,
+            seed: Some(7),
+            ..CollageConfig::default()
+        };
+        let result = process_phrases(&audio, &syllables, &dir, &config, &NoProgress).unwrap();
+        assert_eq!(result.manifest["total_phrases"], 4);
+        assert_eq!(result.clips.len(), 3);
+        for clip in &result.clips {
+            assert_eq!(clip.syllables.len(), 2);
+            assert!(clip.output_path.exists());
+        }
+        assert!(result.concatenated.exists());
+
+        // A telephone voice cuts the 200 Hz tone well below its clean level
+        let rms_of_first_clip = |voice_fx| {
+            let config = CollageConfig { voice_fx, volume_normalize: false, ..config.clone() };
+            let result = process_phrases(&audio, &syllables, &dir, &config, &NoProgress).unwrap();
+            let (clip, _) = crate::audio::io::read_wav(&result.clips[0].output_path).unwrap();
+            crate::audio::analysis::compute_rms(&clip)
+        };
+        let clean = rms_of_first_clip(None);
+        let telephone = rms_of_first_clip(Some(crate::audio::effects::VoiceFx::Telephone));
+        assert!(telephone < clean * 0.3, "clean {} telephone {}", clean, telephone);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,150 @@
+//! Rolling preview of a collage while it is assembled.
+//!
+//! Assembly can take a while on long targets. As word clips are made, the
+//! first seconds of them are written to `preview.wav` in the run directory
+//! so a front end can play them and stop a bad run early. The preview is
+//! mono, 16 kHz, and skips the noise bed, limiter, and global speed.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+
+use crate::audio::io::{mix_to_mono, resample, write_wav};
+
+/// File name of the preview in the run directory.
+pub const PREVIEW_FILENAME: &str = "preview.wav";
+
+/// Preview length front ends ask for by default (seconds).
+pub const DEFAULT_PREVIEW_S: f64 = 30.0;
+
+/// Sample rate of the preview file.
+const PREVIEW_RATE: u32 = 16000;
+
+/// Audio added since the last write before the file is rewritten (seconds).
+const REWRITE_STEP_S: f64 = 2.0;
+
+/// Silence between words in the rolling preview (seconds).
+const WORD_GAP_S: f64 = 0.15;
+
+/// Path of the preview in `run_dir`.
+pub fn preview_path(run_dir: &Path) -> PathBuf {
+    run_dir.join(PREVIEW_FILENAME)
+}
+
+/// The first seconds of a collage in progress, rewritten as it grows.
+pub(crate) struct RollingPreview {
+    path: PathBuf,
+    sr: u32,
+    /// Mono audio so far, at `sr`.
+    samples: Vec<f64>,
+    limit: usize,
+    /// Length of `samples` when the file was last written.
+    written: usize,
+}
+
+impl RollingPreview {
+    /// A preview of the first `seconds` of audio at `sr`, or None when
+    /// `seconds` is not positive.
+    pub(crate) fn new(run_dir: &Path, seconds: f64, sr: u32) -> Option<Self> {
+        (seconds > 0.0).then(|| Self {
+            path: preview_path(run_dir),
+            sr,
+            samples: Vec::new(),
+            limit: (seconds * sr as f64).round() as usize,
+            written: 0,
+        })
+    }
+
+    fn is_full(&self) -> bool {
+        self.samples.len() >= self.limit
+    }
+
+    /// Append a word clip (planar audio) after a short gap, rewriting the
+    /// file once enough new audio has built up.
+    pub(crate) fn push_word(&mut self, clip: &[Vec<f64>]) -> Result<()> {
+        if self.is_full() {
+            return Ok(());
+        }
+        if !self.samples.is_empty() {
+            let gap = (WORD_GAP_S * self.sr as f64).round() as usize;
+            self.samples.resize(self.samples.len() + gap, 0.0);
+        }
+        self.samples.extend(mix_to_mono(clip));
+        self.samples.truncate(self.limit);
+        let step = (REWRITE_STEP_S * self.sr as f64) as usize;
+        if self.is_full() || self.samples.len() - self.written >= step {
+            self.write()?;
+        }
+        Ok(())
+    }
+
+    /// Replace the preview with the start of the arranged collage, clips
+    /// played back to back, and write it.
+    pub(crate) fn replace(&mut self, clips: &[Vec<Vec<f64>>]) -> Result<()> {
+        self.samples.clear();
+        for clip in clips {
+            if self.is_full() {
+                break;
+            }
+            self.samples.extend(mix_to_mono(clip));
+        }
+        self.samples.truncate(self.limit);
+        self.write()
+    }
+
+    /// Write the preview, swapping it in whole so a reader never sees a
+    /// half-written file.
+    fn write(&mut self) -> Result<()> {
+        let audio = resample(&self.samples, self.sr, PREVIEW_RATE)?;
+        let partial = self.path.with_extension("wav.part");
+        write_wav(&partial, &audio, PREVIEW_RATE)?;
+        std::fs::rename(&partial, &self.path)
+            .with_context(|| format!("Failed to write preview: {}", self.path.display()))?;
+        self.written = self.samples.len();
+        Ok(())
+    }
+
+    /// Delete the preview once the full output exists.
+    pub(crate) fn remove(self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            log::debug!("Failed to remove preview {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::io::read_wav;
+
+    #[test]
+    fn test_rolling_preview_grows_and_stops_at_limit() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_preview_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        assert!(RollingPreview::new(&dir, 0.0, PREVIEW_RATE).is_none());
+
+        let mut preview = RollingPreview::new(&dir, 5.0, PREVIEW_RATE).unwrap();
+        let word = vec![vec![0.5; PREVIEW_RATE as usize]];
+        preview.push_word(&word).unwrap();
+        // One second is under the rewrite step
+        assert!(!preview_path(&dir).exists());
+        preview.push_word(&word).unwrap();
+        let (audio, sr) = read_wav(&preview_path(&dir)).unwrap();
+        assert_eq!(sr, PREVIEW_RATE);
+        assert_eq!(audio.len(), 2 * PREVIEW_RATE as usize + 2400);
+
+        for _ in 0..5 {
+            preview.push_word(&word).unwrap();
+        }
+        let (audio, _) = read_wav(&preview_path(&dir)).unwrap();
+        assert_eq!(audio.len(), 5 * PREVIEW_RATE as usize);
+
+        preview.replace(&[word.clone(), vec![vec![0.0; 100]]]).unwrap();
+        let (audio, _) = read_wav(&preview_path(&dir)).unwrap();
+        assert_eq!(audio.len(), PREVIEW_RATE as usize + 100);
+
+        preview.remove();
+        assert!(!preview_path(&dir).exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
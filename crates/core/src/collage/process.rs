@@ -1,27 +1,39 @@
 //! Glottisdale collage pipeline — syllable-level audio collage engine.
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 use rand::Rng;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 use rand::seq::SliceRandom;
+use rayon::prelude::*;
 
 use crate::audio::analysis::{
-    compute_rms, estimate_f0, find_breaths, find_room_tone, generate_pink_noise,
+    compute_rms, estimate_f0, find_breaths, find_room_tone, generate_pink_noise, PinkNoise,
 };
 use crate::audio::effects::{
-    adjust_volume, concatenate, cut_clip, generate_silence, mix_audio,
-    pitch_shift, time_stretch,
+    adjust_volume, concatenate, concatenate_channels, concatenated_offsets, cut_clip, generate_silence,
+    limit_peaks, map_channels, mix_audio, pan, pitch_shift, time_stretch, RmsNormalization, StreamingConcat,
+    VoiceFx,
 };
-use crate::audio::io::{read_wav, write_wav};
+use crate::audio::io::{
+    mix_to_mono, read_wav_channels, to_layout, write_wav_channels, Channels, WavStreamWriter,
+};
+use crate::audio::loudness::{loudness_table, Mastering};
+use crate::collage::intonation::{word_shifts, Contour};
+use crate::collage::preview::RollingPreview;
+use crate::collage::subtitles::{clip_caption, write_subtitles, Cue};
+use crate::collage::tempo::{place_on_grid, BeatGrid};
 use crate::collage::stretch::{
     StretchConfig, apply_stutter, apply_word_repeat, parse_count_range,
     resolve_stretch_factor, should_stretch_syllable,
 };
-use crate::language::phonotactics::order_syllables;
+use crate::language::phonotactics::{order_syllables, order_syllables_markov, PhonemeBigrams};
+use crate::language::syllabify::merge_into_words;
+use crate::progress::{ProgressReporter, Stage};
+use crate::source::{relative_to, source_table, SourceId};
 use crate::types::{Clip, PipelineResult, Syllable};
 
 /// Default weights for syllables-per-word: mimics natural speech word-length
@@ -42,7 +54,7 @@ fn parse_range(s: &str) -> (usize, usize) {
 }
 
 /// Parse gap string like "50-200" or "100" into (min_ms, max_ms).
-fn parse_gap(s: &str) -> (f64, f64) {
+pub(crate) fn parse_gap(s: &str) -> (f64, f64) {
     if let Some(idx) = s.find('-') {
         if let (Ok(a), Ok(b)) = (s[..idx].parse(), s[idx + 1..].parse()) {
             return (a, b);
@@ -71,13 +83,48 @@ fn weighted_word_length(min_syl: usize, max_syl: usize, rng: &mut StdRng) -> usi
     rng.gen_range(min_syl..=max_syl)
 }
 
-/// Group syllables into variable-length words with phonotactic ordering.
+/// Whether two syllables are the same cut of the same source audio.
+fn same_source_syllable(a: &Syllable, b: &Syllable) -> bool {
+    a.start == b.start && a.end == b.end && a.word_index == b.word_index && a.word == b.word
+}
+
+/// Keep copies of the same source syllable at least `min_distance`
+/// syllables apart, pulling a later syllable forward into each clash. A
+/// clash with nothing left to swap in stays.
+fn spread_reused_syllables(syllables: &mut [Syllable], min_distance: usize) {
+    if min_distance == 0 {
+        return;
+    }
+    let clashes = |syllables: &[Syllable], pos: usize, candidate: &Syllable| {
+        syllables[pos.saturating_sub(min_distance)..pos]
+            .iter()
+            .any(|prev| same_source_syllable(prev, candidate))
+    };
+    for i in 1..syllables.len() {
+        if !clashes(syllables, i, &syllables[i]) {
+            continue;
+        }
+        match (i + 1..syllables.len()).find(|&j| !clashes(syllables, i, &syllables[j])) {
+            Some(j) => syllables.swap(i, j),
+            None => log::debug!("Syllable {} repeats within {} syllables; no replacement left", i, min_distance),
+        }
+    }
+}
+
+/// Group syllables into variable-length words with phonotactic ordering,
+/// or by walking `model` when one is given. Repeats of a source syllable
+/// are first spread at least `min_reuse_distance` syllables apart.
 fn group_into_words(
     syllables: &[Syllable],
     spc_min: usize,
     spc_max: usize,
+    model: Option<&PhonemeBigrams>,
+    min_reuse_distance: usize,
     rng: &mut StdRng,
 ) -> Vec<Vec<Syllable>> {
+    let mut spread = syllables.to_vec();
+    spread_reused_syllables(&mut spread, min_reuse_distance);
+    let syllables = spread.as_slice();
     let mut words = Vec::new();
     let mut i = 0;
     while i < syllables.len() {
@@ -87,7 +134,10 @@ fn group_into_words(
         if !word.is_empty() {
             if word.len() > 1 {
                 let seed = rng.gen_range(0u64..=u64::MAX);
-                word = order_syllables(&word, Some(seed), 100);
+                word = match model {
+                    Some(model) => order_syllables_markov(&word, model, Some(seed)),
+                    None => order_syllables(&word, Some(seed), 100),
+                };
             }
             words.push(word);
         }
@@ -112,9 +162,93 @@ fn group_into_chunks<T: Clone>(items: &[T], min_len: usize, max_len: usize, rng:
     groups
 }
 
+/// Relative bias for a value against its pool mean.
+///
+/// "long"/"high" favor values above the mean, "short"/"low" favor values
+/// below it. Anything else (including no preference) is neutral.
+fn bias_weight(value: f64, mean: f64, preference: Option<&str>) -> f64 {
+    let value = value.max(1e-6);
+    let mean = mean.max(1e-6);
+    match preference {
+        Some("long") | Some("high") => value / mean,
+        Some("short") | Some("low") => mean / value,
+        _ => 1.0,
+    }
+}
+
+/// Compute per-syllable sampling weights from duration and energy preferences.
+///
+/// Returns one weight vector per source, parallel to that source's syllables.
+fn compute_sampling_weights(
+    sources: &HashMap<String, Vec<Syllable>>,
+    source_audio: &HashMap<String, (Vec<f64>, u32)>,
+    prefer_duration: Option<&str>,
+    prefer_energy: Option<&str>,
+) -> HashMap<String, Vec<f64>> {
+    let mut weights = HashMap::new();
+    for (name, syls) in sources {
+        if syls.is_empty() {
+            weights.insert(name.clone(), Vec::new());
+            continue;
+        }
+        let durations: Vec<f64> = syls.iter().map(|s| s.end - s.start).collect();
+        let energies: Vec<f64> = syls
+            .iter()
+            .map(|syl| match source_audio.get(name) {
+                Some((samples, sr)) => {
+                    let start_idx = (syl.start * *sr as f64) as usize;
+                    let end_idx = ((syl.end * *sr as f64) as usize).min(samples.len());
+                    if start_idx < end_idx {
+                        compute_rms(&samples[start_idx..end_idx])
+                    } else {
+                        0.0
+                    }
+                }
+                None => 0.0,
+            })
+            .collect();
+
+        let mean_dur = durations.iter().sum::<f64>() / durations.len() as f64;
+        let mean_rms = energies.iter().sum::<f64>() / energies.len() as f64;
+
+        let source_weights: Vec<f64> = durations
+            .iter()
+            .zip(&energies)
+            .map(|(&dur, &rms)| {
+                bias_weight(dur, mean_dur, prefer_duration)
+                    * bias_weight(rms, mean_rms, prefer_energy)
+            })
+            .collect();
+        weights.insert(name.clone(), source_weights);
+    }
+    weights
+}
+
+/// Order items by weighted random choice without replacement.
+///
+/// Uses Efraimidis-Spirakis keys (`u^(1/w)`), so heavier items tend to come
+/// first. Items with non-positive weight go last.
+fn weighted_shuffle<T: Clone>(items: &[T], weights: &[f64], rng: &mut StdRng) -> Vec<T> {
+    let mut keyed: Vec<(f64, &T)> = items
+        .iter()
+        .zip(weights.iter().chain(std::iter::repeat(&1.0)))
+        .map(|(item, &w)| {
+            let u: f64 = rng.gen();
+            let key = if w > 0.0 { u.powf(1.0 / w) } else { 0.0 };
+            (key, item)
+        })
+        .collect();
+    keyed.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    keyed.into_iter().map(|(_, item)| item.clone()).collect()
+}
+
 /// Sample and shuffle syllables to approximately hit target duration.
+///
+/// When `weights` is given, syllables are drawn by seeded weighted choice
+/// instead of a uniform shuffle.
 fn sample_syllables(
     syllables: &[Syllable],
+    weights: Option<&[f64]>,
     target_duration: f64,
     dispersal_gap: f64,
     rng: &mut StdRng,
@@ -123,8 +257,14 @@ fn sample_syllables(
         return Vec::new();
     }
 
-    let mut available: Vec<Syllable> = syllables.to_vec();
-    available.shuffle(rng);
+    let available: Vec<Syllable> = match weights {
+        Some(w) => weighted_shuffle(syllables, w, rng),
+        None => {
+            let mut v = syllables.to_vec();
+            v.shuffle(rng);
+            v
+        }
+    };
 
     let mut selected = Vec::new();
     let mut total = 0.0;
@@ -142,9 +282,72 @@ fn sample_syllables(
     selected
 }
 
-/// Round-robin sample across sources for variety, then shuffle.
+/// Normalize a word for anchor matching: lowercase with surrounding punctuation removed.
+fn normalize_anchor_word(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'')
+        .trim_matches('\'')
+        .to_lowercase()
+}
+
+/// Find one occurrence of each anchor word across all sources.
+///
+/// Returns the anchor words that were found, each as its syllables in
+/// source order. When a word occurs several times, one occurrence is
+/// picked at random so repeated runs with different seeds vary.
+fn find_anchor_words(
+    sources: &HashMap<String, Vec<Syllable>>,
+    anchors: &[String],
+    rng: &mut StdRng,
+) -> Vec<Vec<Syllable>> {
+    let mut source_names: Vec<&String> = sources.keys().collect();
+    source_names.sort();
+
+    let mut found = Vec::new();
+    for anchor in anchors {
+        let target = normalize_anchor_word(anchor);
+        if target.is_empty() {
+            continue;
+        }
+
+        // Collect every occurrence, keyed by (source, word_index)
+        let mut occurrences: Vec<Vec<Syllable>> = Vec::new();
+        for name in &source_names {
+            let mut by_word: Vec<(usize, Vec<Syllable>)> = Vec::new();
+            for syl in &sources[*name] {
+                if normalize_anchor_word(&syl.word) != target {
+                    continue;
+                }
+                match by_word.iter_mut().find(|(wi, _)| *wi == syl.word_index) {
+                    Some((_, syls)) => syls.push(syl.clone()),
+                    None => by_word.push((syl.word_index, vec![syl.clone()])),
+                }
+            }
+            for (_, mut syls) in by_word {
+                syls.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+                occurrences.push(syls);
+            }
+        }
+
+        if occurrences.is_empty() {
+            log::warn!("Anchor word not found in sources: {}", anchor);
+            continue;
+        }
+        let pick = rng.gen_range(0..occurrences.len());
+        found.push(occurrences.swap_remove(pick));
+    }
+    found
+}
+
+/// Sample across sources for variety, then shuffle.
+///
+/// Sources take turns in a smooth weighted round-robin: each gets a share
+/// of the picks proportional to its entry in `source_weights` (missing =
+/// 1.0, 0 = never sampled), interleaved as evenly as possible. Equal
+/// weights give plain round-robin.
 fn sample_syllables_multi_source(
     sources: &HashMap<String, Vec<Syllable>>,
+    weights: Option<&HashMap<String, Vec<f64>>>,
+    source_weights: &HashMap<String, f64>,
     target_duration: f64,
     dispersal_gap: f64,
     rng: &mut StdRng,
@@ -154,34 +357,59 @@ fn sample_syllables_multi_source(
     }
 
     // Assign each source a numeric tag for fast comparison
-    let source_names: Vec<String> = sources.keys().cloned().collect();
+    let mut source_names: Vec<String> = sources.keys().cloned().collect();
+    source_names.sort();
     let mut pools: Vec<(usize, Vec<Syllable>)> = Vec::new();
     for (idx, name) in source_names.iter().enumerate() {
-        let mut pool = sources[name].clone();
-        pool.shuffle(rng);
+        let pool = match weights.and_then(|w| w.get(name)) {
+            Some(w) => {
+                // Pools are drained from the back, so put the favored end there
+                let mut ordered = weighted_shuffle(&sources[name], w, rng);
+                ordered.reverse();
+                ordered
+            }
+            None => {
+                let mut pool = sources[name].clone();
+                pool.shuffle(rng);
+                pool
+            }
+        };
         pools.push((idx, pool));
     }
 
-    // Round-robin selection, keeping source tags
+    let mut shares: Vec<f64> = source_names
+        .iter()
+        .map(|name| source_weights.get(name).copied().unwrap_or(1.0).max(0.0))
+        .collect();
+    if shares.iter().all(|&w| w <= 0.0) {
+        log::warn!("All source weights are zero; sampling sources equally");
+        shares.fill(1.0);
+    }
+
+    // Weighted round-robin selection, keeping source tags
     let mut tagged: Vec<(usize, Syllable)> = Vec::new();
     let mut total = 0.0;
+    let mut credit = vec![0.0; pools.len()];
 
-    'outer: loop {
-        let mut any_remaining = false;
-        for (src_idx, pool) in pools.iter_mut() {
-            if let Some(syl) = pool.pop() {
-                any_remaining = true;
-                let syl_dur = syl.end - syl.start;
-                total += syl_dur;
-                tagged.push((*src_idx, syl));
-                if total >= target_duration {
-                    break 'outer;
-                }
-            }
-        }
-        if !any_remaining {
+    while total < target_duration {
+        let live: Vec<usize> = (0..pools.len())
+            .filter(|&i| shares[i] > 0.0 && !pools[i].1.is_empty())
+            .collect();
+        if live.is_empty() {
             break;
         }
+        let live_total: f64 = live.iter().map(|&i| shares[i]).sum();
+        for &i in &live {
+            credit[i] += shares[i];
+        }
+        // First of the highest credits, so equal weights keep source order
+        let pick = live.iter().copied().fold(live[0], |best, i| if credit[i] > credit[best] { i } else { best });
+        credit[pick] -= live_total;
+
+        let (src_idx, pool) = &mut pools[pick];
+        let syl = pool.pop().unwrap();
+        total += syl.end - syl.start;
+        tagged.push((*src_idx, syl));
     }
 
     tagged.shuffle(rng);
@@ -310,6 +538,47 @@ fn are_source_sequential(a: &Syllable, b: &Syllable, gap: f64) -> bool {
     ab_gap < gap || ba_gap < gap
 }
 
+pub use crate::audio::io::SourceChannels;
+
+/// A sampling weight for one input, from `--source-weight FILE=WEIGHT`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceWeight {
+    /// Input file the weight applies to, as given on the command line.
+    pub file: PathBuf,
+    pub weight: f64,
+}
+
+impl SourceWeight {
+    /// Parse a `FILE=WEIGHT` spec, e.g. `host.wav=0.7`.
+    ///
+    /// The last `=` separates the file from the weight so paths containing
+    /// `=` still work.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (file, weight) = spec
+            .rsplit_once('=')
+            .with_context(|| format!("Invalid source weight '{}': expected FILE=WEIGHT", spec))?;
+        let weight: f64 = weight
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid weight in '{}'", spec))?;
+        if file.is_empty() {
+            bail!("Invalid source weight '{}': missing file", spec);
+        }
+        if !weight.is_finite() || weight < 0.0 {
+            bail!("Invalid source weight '{}': weight must be 0 or more", spec);
+        }
+        Ok(Self {
+            file: PathBuf::from(file),
+            weight,
+        })
+    }
+
+    /// Whether this weight targets `input`, by full path or by file name.
+    pub fn matches(&self, input: &Path) -> bool {
+        self.file == input || input.file_name() == Some(self.file.as_os_str())
+    }
+}
+
 /// Configuration for the collage pipeline.
 #[derive(Debug, Clone)]
 pub struct CollageConfig {
@@ -331,7 +600,21 @@ pub struct CollageConfig {
     pub breaths: bool,
     pub breath_probability: f64,
     pub volume_normalize: bool,
+    /// Level and gain limits for `volume_normalize`.
+    pub volume_normalization: RmsNormalization,
     pub prosodic_dynamics: bool,
+    /// Run the final mix through a lookahead peak limiter before it is
+    /// written, so pink noise and overlaps cannot hard-clip.
+    pub limiter: bool,
+    /// Reverb and loudness normalization for the finished collage.
+    pub mastering: Mastering,
+    /// Pitch falls gradually across each sentence (and rises at the end
+    /// of questions), one shift per word.
+    pub intonation: bool,
+    /// Fraction of sentences given a rising question contour.
+    pub question_probability: f64,
+    /// Lo-fi voice character applied to each whole phrase (None = clean).
+    pub voice_fx: Option<VoiceFx>,
     // Stretch
     pub speed: Option<f64>,
     pub stretch_config: StretchConfig,
@@ -344,6 +627,50 @@ pub struct CollageConfig {
     pub stutter_count: String,
     // Dispersal
     pub dispersal_gap: f64,
+    // Anchors
+    /// Words whose syllables are guaranteed to appear in the collage.
+    pub anchor_words: Vec<String>,
+    /// Place anchor words at the start of sentences instead of at random.
+    pub anchor_sentence_start: bool,
+    // Stereo
+    /// Spread word clips across the stereo field, 0.0 (off) to 1.0 (hard
+    /// left/right). Any non-zero value produces stereo output.
+    pub pan_spread: f64,
+    // Sampling bias
+    /// Favor "long" or "short" syllables when sampling (None = uniform).
+    pub prefer_duration: Option<String>,
+    /// Favor "high" or "low" energy syllables when sampling (None = uniform).
+    pub prefer_energy: Option<String>,
+    // Granularity
+    /// Unit cut from the sources: "syllable" (default) or "word". In word
+    /// mode each aligned word is one unit and `syllables_per_clip` is ignored,
+    /// so every collage word is a real source word.
+    pub unit: String,
+    /// How syllables are ordered inside a word: "phonotactic" (default)
+    /// scores sonority at each junction; "markov" samples orders from a
+    /// phoneme-bigram model of the source transcript.
+    pub word_model: String,
+    /// Fewest other syllables between two uses of the same source
+    /// syllable (0 = no limit).
+    pub min_reuse_distance: usize,
+    // Phrase mode
+    /// Minimum silence between words that ends a phrase (ms).
+    pub phrase_min_pause_ms: f64,
+    // Tempo
+    /// Quantize word, phrase, and sentence onsets to a beat grid at this
+    /// tempo instead of random pauses (None = off).
+    pub bpm: Option<f64>,
+    /// Grid steps per beat (1 = quarters, 2 = eighths, 4 = sixteenths).
+    pub beat_subdivision: usize,
+    /// Off-beat delay as a fraction of a grid step (0 = straight).
+    pub swing: f64,
+    /// Relative share of sampled syllables per source, keyed like the
+    /// source maps. Missing sources get 1.0; 0 leaves a source out.
+    pub source_weights: HashMap<String, f64>,
+    // Preview
+    /// Seconds of rough preview kept in `preview.wav` in the run directory
+    /// while assembling (0 = none). Removed once the output is written.
+    pub preview_s: f64,
 }
 
 impl Default for CollageConfig {
@@ -366,7 +693,13 @@ impl Default for CollageConfig {
             breaths: true,
             breath_probability: 0.6,
             volume_normalize: true,
+            volume_normalization: RmsNormalization::default(),
             prosodic_dynamics: true,
+            limiter: true,
+            mastering: Mastering::default(),
+            intonation: true,
+            question_probability: 0.0,
+            voice_fx: None,
             speed: None,
             stretch_config: StretchConfig::default(),
             repeat_weight: None,
@@ -375,41 +708,38 @@ impl Default for CollageConfig {
             stutter: None,
             stutter_count: "1-2".to_string(),
             dispersal_gap: 1.0,
+            anchor_words: Vec::new(),
+            anchor_sentence_start: false,
+            pan_spread: 0.0,
+            prefer_duration: None,
+            prefer_energy: None,
+            unit: "syllable".to_string(),
+            word_model: "phonotactic".to_string(),
+            min_reuse_distance: 8,
+            phrase_min_pause_ms: 300.0,
+            bpm: None,
+            beat_subdivision: 2,
+            swing: 0.0,
+            source_weights: HashMap::new(),
+            preview_s: 0.0,
         }
     }
 }
 
-/// Normalize volume across clips to median RMS (in-memory).
-fn normalize_volume_clips(clips: &mut [Vec<f64>]) {
-    let rms_values: Vec<f64> = clips
-        .iter()
-        .map(|c| compute_rms(c))
-        .filter(|&r| r > 1e-6)
-        .collect();
-
-    if rms_values.is_empty() {
-        return;
-    }
-
-    let mut sorted = rms_values.clone();
-    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    let target_rms = sorted[sorted.len() / 2];
-
-    if target_rms < 1e-6 {
-        return;
-    }
-
-    for clip in clips.iter_mut() {
-        let clip_rms = compute_rms(clip);
-        if clip_rms < 1e-6 {
-            continue;
-        }
-        let db_adjust = 20.0 * (target_rms / clip_rms).log10();
-        let db_adjust = db_adjust.clamp(-20.0, 20.0);
-        if db_adjust.abs() >= 0.5 {
-            adjust_volume(clip, db_adjust);
+/// Normalize volume across clips toward a common RMS (in-memory).
+///
+/// Each clip is planar audio; RMS is measured on the mono mixdown and the
+/// same gain is applied to every channel.
+pub(crate) fn normalize_volume_clips(clips: &mut [Vec<Vec<f64>>], normalization: &RmsNormalization) {
+    let rms_values: Vec<f64> = clips.par_iter().map(|c| compute_rms(&mix_to_mono(c))).collect();
+    let gains = normalization.gains_db(&rms_values);
+    clips.par_iter_mut().zip(gains).for_each(|(clip, db_adjust)| {
+        if db_adjust != 0.0 {
+            for ch in clip.iter_mut() {
+                adjust_volume(ch, db_adjust);
+            }
         }
-    }
+    });
 }
 
 /// Minimum F0 target for pitch normalization (Hz).
@@ -417,11 +747,18 @@ fn normalize_volume_clips(clips: &mut [Vec<f64>]) {
 const MIN_PITCH_TARGET_HZ: f64 = 160.0;
 
 /// Normalize pitch across clips toward median F0 (in-memory).
-fn normalize_pitch_clips(clips: &mut [Vec<f64>], sr: u32, pitch_range: f64) {
-    let f0_values: Vec<(usize, f64)> = clips
+///
+/// F0 is estimated on each clip's mono mixdown; the shift is applied to
+/// every channel.
+fn normalize_pitch_clips(clips: &mut [Vec<Vec<f64>>], sr: u32, pitch_range: f64) {
+    let f0s: Vec<Option<f64>> = clips
+        .par_iter()
+        .map(|c| estimate_f0(&mix_to_mono(c), sr, 80, 600))
+        .collect();
+    let f0_values: Vec<(usize, f64)> = f0s
         .iter()
         .enumerate()
-        .filter_map(|(i, c)| estimate_f0(c, sr, 80, 600).map(|f0| (i, f0)))
+        .filter_map(|(i, f0)| f0.map(|f0| (i, f0)))
         .collect();
 
     if f0_values.is_empty() {
@@ -440,15 +777,103 @@ fn normalize_pitch_clips(clips: &mut [Vec<f64>], sr: u32, pitch_range: f64) {
         f0_values.len()
     );
 
-    for (i, f0) in &f0_values {
+    clips.par_iter_mut().zip(f0s).for_each(|(clip, f0)| {
+        let Some(f0) = f0 else { return };
         let semitones_shift = 12.0 * (target_f0 / f0).log2();
         let semitones_shift = semitones_shift.clamp(-pitch_range, pitch_range);
         if semitones_shift.abs() >= 0.1 {
-            if let Ok(shifted) = pitch_shift(&clips[*i], sr, semitones_shift) {
-                clips[*i] = shifted;
+            if let Ok(shifted) = map_channels(clip, |ch| pitch_shift(ch, sr, semitones_shift)) {
+                *clip = shifted;
+            }
+        }
+    });
+}
+
+/// Pink noise bed mixed into the output block by block.
+///
+/// Adds the same values as `mix_audio` with a `generate_pink_noise` buffer
+/// the length of the whole mix, without holding that buffer: each channel's
+/// generator runs once to find the normalization peak, then again to mix.
+struct NoiseBed {
+    channels: Vec<(u64, PinkNoise, f64)>,
+    len: usize,
+    gain: f64,
+    position: usize,
+}
+
+impl NoiseBed {
+    fn new(n_channels: usize, frames: usize, level_db: f64, seed: Option<u64>, sr: u32) -> Self {
+        // Same length as `generate_pink_noise(frames / sr)`, which can round down
+        let len = (frames as f64 / sr as f64 * sr as f64) as usize;
+        let base = seed.unwrap_or_else(rand::random);
+        let channels = (0..n_channels)
+            .map(|ch| {
+                let seed = base.wrapping_add(ch as u64);
+                (seed, PinkNoise::new(Some(seed)), PinkNoise::peak(seed, len))
+            })
+            .collect();
+        Self {
+            channels,
+            len,
+            gain: 10.0f64.powf(level_db / 20.0),
+            position: 0,
+        }
+    }
+
+    fn mix(&mut self, block: &mut [Vec<f64>]) {
+        let frames = block.first().map_or(0, Vec::len);
+        if self.len > 0 {
+            for (ch, (seed, noise, peak)) in block.iter_mut().zip(&mut self.channels) {
+                for (i, sample) in ch.iter_mut().enumerate() {
+                    // A noise buffer shorter than the mix loops
+                    let at = self.position + i;
+                    let wrapped = at % self.len;
+                    if at > 0 && wrapped == 0 {
+                        *noise = PinkNoise::new(Some(*seed));
+                    }
+                    let mut value = noise.next_sample();
+                    if *peak > 0.0 {
+                        value /= *peak;
+                    }
+                    *sample += value * self.gain;
+                }
             }
         }
+        self.position += frames;
+    }
+}
+
+/// Concatenate the final clips straight into the output WAV, mixing in the
+/// noise bed as it goes, so long outputs never exist whole in memory.
+fn write_mix_streaming(
+    path: &Path,
+    clips: Vec<Vec<Vec<f64>>>,
+    noise_level_db: f64,
+    seed: Option<u64>,
+    sr: u32,
+    limiter: bool,
+) -> Result<()> {
+    let n_channels = clips.first().map_or(1, Vec::len);
+    let frames: usize = clips.iter().map(|c| c.first().map_or(0, Vec::len)).sum();
+    let mut noise =
+        (noise_level_db != 0.0 && frames > 0).then(|| NoiseBed::new(n_channels, frames, noise_level_db, seed, sr));
+
+    let mut writer = WavStreamWriter::create(path, n_channels, sr)?.limited(limiter);
+    let mut concat = StreamingConcat::new(0);
+    for clip in clips {
+        let mut block = concat.push(&clip);
+        drop(clip);
+        if let Some(noise) = &mut noise {
+            noise.mix(&mut block);
+        }
+        writer.write_frames(&block)?;
     }
+    let mut block = concat.finish();
+    if let Some(noise) = &mut noise {
+        noise.mix(&mut block);
+    }
+    writer.write_frames(&block)?;
+    writer.finalize()
 }
 
 /// Apply prosodic dynamics to a clip: slight boost at start, taper at end.
@@ -478,12 +903,44 @@ pub fn apply_prosodic_dynamics(samples: &mut [f64], sr: u32) {
 ///
 /// Takes pre-aligned syllables per source (from an external alignment step)
 /// and the loaded audio samples. This function handles sampling, grouping,
-/// effects, and assembly.
+/// effects, and assembly, reporting the cutting and assembly stages to
+/// `progress` (alignment is the caller's).
 pub fn process(
     source_audio: &HashMap<String, (Vec<f64>, u32)>,
     source_syllables: &HashMap<String, Vec<Syllable>>,
     output_dir: &Path,
     config: &CollageConfig,
+    progress: &dyn ProgressReporter,
+) -> Result<PipelineResult> {
+    process_inner(source_audio, None, source_syllables, output_dir, config, progress)
+}
+
+/// Run the collage pipeline on multi-channel sources.
+///
+/// Like `process`, but each source is planar audio (see `audio::io::Channels`).
+/// Analysis runs on a mono mixdown while clips are cut from every channel,
+/// so stereo sources keep their image in the output.
+pub fn process_channels(
+    source_audio: &SourceChannels,
+    source_syllables: &HashMap<String, Vec<Syllable>>,
+    output_dir: &Path,
+    config: &CollageConfig,
+    progress: &dyn ProgressReporter,
+) -> Result<PipelineResult> {
+    let mono: HashMap<String, (Vec<f64>, u32)> = source_audio
+        .iter()
+        .map(|(name, (channels, sr))| (name.clone(), (mix_to_mono(channels), *sr)))
+        .collect();
+    process_inner(&mono, Some(source_audio), source_syllables, output_dir, config, progress)
+}
+
+fn process_inner(
+    source_audio: &HashMap<String, (Vec<f64>, u32)>,
+    source_channels: Option<&SourceChannels>,
+    source_syllables: &HashMap<String, Vec<Syllable>>,
+    output_dir: &Path,
+    config: &CollageConfig,
+    progress: &dyn ProgressReporter,
 ) -> Result<PipelineResult> {
     let mut rng = match config.seed {
         Some(s) => StdRng::seed_from_u64(s),
@@ -494,7 +951,17 @@ pub fn process(
     let clips_dir = output_dir.join("clips");
     std::fs::create_dir_all(&clips_dir)?;
 
-    let (spc_min, spc_max) = parse_range(&config.syllables_per_clip);
+    // Word mode: one unit per aligned word, one unit per collage word
+    let word_units: HashMap<String, Vec<Syllable>>;
+    let (source_syllables, (spc_min, spc_max)) = if config.unit == "word" {
+        word_units = source_syllables
+            .iter()
+            .map(|(name, syls)| (name.clone(), merge_into_words(syls)))
+            .collect();
+        (&word_units, (1, 1))
+    } else {
+        (source_syllables, parse_range(&config.syllables_per_clip))
+    };
     let (wpp_min, wpp_max) = parse_range(&config.words_per_phrase);
     let (pps_min, pps_max) = parse_range(&config.phrases_per_sentence);
     let (pp_min, pp_max) = parse_gap(&config.phrase_pause);
@@ -518,6 +985,17 @@ pub fn process(
         .map(|(_, sr)| *sr)
         .unwrap_or(16000);
 
+    // Output is stereo when any source is stereo or word panning is on
+    let layout = if config.pan_spread > 0.0 {
+        Channels::Stereo
+    } else {
+        Channels::from_count(
+            source_channels
+                .map(|m| m.values().map(|(ch, _)| ch.len()).max().unwrap_or(1))
+                .unwrap_or(1),
+        )
+    };
+
     // --- Audio polish: extract room tone and breaths ---
     let mut room_tone_samples: HashMap<String, Vec<f64>> = HashMap::new();
     let mut breath_clips: Vec<Vec<f64>> = Vec::new();
@@ -628,13 +1106,34 @@ pub fn process(
         );
     }
 
+    // --- Sampling weights from duration/energy preferences ---
+    let sampling_weights = if config.prefer_duration.is_some() || config.prefer_energy.is_some() {
+        Some(compute_sampling_weights(
+            &filtered_sources,
+            source_audio,
+            config.prefer_duration.as_deref(),
+            config.prefer_energy.as_deref(),
+        ))
+    } else {
+        None
+    };
+
     // --- Sample syllables across sources ---
     let selected = if filtered_sources.len() == 1 {
-        let syls = filtered_sources.values().next().unwrap();
-        sample_syllables(syls, config.target_duration, config.dispersal_gap, &mut rng)
+        let (name, syls) = filtered_sources.iter().next().unwrap();
+        let weights = sampling_weights.as_ref().and_then(|w| w.get(name));
+        sample_syllables(
+            syls,
+            weights.map(|w| w.as_slice()),
+            config.target_duration,
+            config.dispersal_gap,
+            &mut rng,
+        )
     } else {
         sample_syllables_multi_source(
             &filtered_sources,
+            sampling_weights.as_ref(),
+            &config.source_weights,
             config.target_duration,
             config.dispersal_gap,
             &mut rng,
@@ -652,61 +1151,114 @@ pub fn process(
     };
 
     // --- Group syllables into words ---
-    let words = group_into_words(&selected, spc_min, spc_max, &mut rng);
+    let word_model = (config.word_model == "markov").then(|| {
+        let mut names: Vec<&String> = source_syllables.keys().collect();
+        names.sort();
+        PhonemeBigrams::train(names.into_iter().map(|n| source_syllables[n].as_slice()))
+    });
+    let mut words = group_into_words(
+        &selected,
+        spc_min,
+        spc_max,
+        word_model.as_ref(),
+        config.min_reuse_distance,
+        &mut rng,
+    );
+
+    // --- Anchor words: splice intact source words into the word list ---
+    let anchor_words = if config.anchor_words.is_empty() {
+        Vec::new()
+    } else {
+        find_anchor_words(source_syllables, &config.anchor_words, &mut rng)
+    };
+    let mut anchor_word_indices: std::collections::HashSet<usize> =
+        std::collections::HashSet::new();
+    for anchor in &anchor_words {
+        let pos = rng.gen_range(0..=words.len());
+        words.insert(pos, anchor.clone());
+        // Shift indices of previously inserted anchors at or after pos
+        anchor_word_indices = anchor_word_indices
+            .into_iter()
+            .map(|i| if i >= pos { i + 1 } else { i })
+            .collect();
+        anchor_word_indices.insert(pos);
+    }
+    if !anchor_words.is_empty() {
+        log::info!(
+            "Anchored {}/{} word(s)",
+            anchor_words.len(),
+            config.anchor_words.len()
+        );
+    }
 
     // --- Cut all syllable clips ---
     struct SylClipInfo {
         word_idx: usize,
         syl_idx: usize,
-        samples: Vec<f64>,
+        /// Planar audio in the output layout.
+        samples: Vec<Vec<f64>>,
         syl: Syllable,
     }
 
-    let mut all_syl_clips: Vec<SylClipInfo> = Vec::new();
-
-    for (word_idx, word_syls) in words.iter().enumerate() {
-        for (syl_idx, syl) in word_syls.iter().enumerate() {
+    // Cut in parallel; collect keeps word/syllable order
+    progress.stage(Stage::Cutting, 0.0);
+    let syl_positions: Vec<(usize, usize, &Syllable)> = words
+        .iter()
+        .enumerate()
+        .flat_map(|(word_idx, word_syls)| {
+            word_syls
+                .iter()
+                .enumerate()
+                .map(move |(syl_idx, syl)| (word_idx, syl_idx, syl))
+        })
+        .collect();
+    let mut all_syl_clips: Vec<SylClipInfo> = syl_positions
+        .into_par_iter()
+        .filter_map(|(word_idx, syl_idx, syl)| {
             let syl_source = find_source(syl);
-            if let Some((source_samples, source_sr)) = source_audio.get(&syl_source) {
-                let clip = cut_clip(
-                    source_samples,
-                    *source_sr,
-                    syl.start,
-                    syl.end,
-                    config.padding_ms,
-                    0.0,
-                );
-                if !clip.is_empty() {
-                    all_syl_clips.push(SylClipInfo {
-                        word_idx,
-                        syl_idx,
-                        samples: clip,
-                        syl: syl.clone(),
-                    });
-                }
-            }
-        }
-    }
+            let cut = |samples: &[f64], source_sr: u32| {
+                cut_clip(samples, source_sr, syl.start, syl.end, config.padding_ms, 0.0)
+            };
+            let clip = match source_channels.and_then(|m| m.get(&syl_source)) {
+                Some((channels, source_sr)) => Some(
+                    channels.iter().map(|ch| cut(ch, *source_sr)).collect::<Vec<_>>(),
+                ),
+                None => source_audio
+                    .get(&syl_source)
+                    .map(|(samples, source_sr)| vec![cut(samples, *source_sr)]),
+            }?;
+            let clip = to_layout(&clip, layout);
+            (!clip[0].is_empty()).then(|| SylClipInfo {
+                word_idx,
+                syl_idx,
+                samples: clip,
+                syl: syl.clone(),
+            })
+        })
+        .collect();
+    progress.stage(Stage::Cutting, 0.3);
 
     // --- Pitch normalization ---
     if config.pitch_normalize && !all_syl_clips.is_empty() {
-        let mut clip_samples: Vec<Vec<f64>> =
+        let mut clip_samples: Vec<Vec<Vec<f64>>> =
             all_syl_clips.iter().map(|c| c.samples.clone()).collect();
         normalize_pitch_clips(&mut clip_samples, sr, config.pitch_range);
         for (i, samples) in clip_samples.into_iter().enumerate() {
             all_syl_clips[i].samples = samples;
         }
     }
+    progress.stage(Stage::Cutting, 0.8);
 
     // --- Volume normalization ---
     if config.volume_normalize && !all_syl_clips.is_empty() {
-        let mut clip_samples: Vec<Vec<f64>> =
+        let mut clip_samples: Vec<Vec<Vec<f64>>> =
             all_syl_clips.iter().map(|c| c.samples.clone()).collect();
-        normalize_volume_clips(&mut clip_samples);
+        normalize_volume_clips(&mut clip_samples, &config.volume_normalization);
         for (i, samples) in clip_samples.into_iter().enumerate() {
             all_syl_clips[i].samples = samples;
         }
     }
+    progress.stage(Stage::Cutting, 0.9);
 
     // --- Stutter ---
     if let Some(stutter_prob) = config.stutter {
@@ -720,7 +1272,7 @@ pub fn process(
                     .collect();
 
                 if !word_clips.is_empty() {
-                    let clip_refs: Vec<Vec<f64>> =
+                    let clip_refs: Vec<Vec<Vec<f64>>> =
                         word_clips.iter().map(|&i| all_syl_clips[i].samples.clone()).collect();
                     let stuttered = apply_stutter(&clip_refs, stutter_prob, count_range, &mut rng);
                     // Update the clips - remove old word entries and add stuttered ones
@@ -755,7 +1307,7 @@ pub fn process(
                 .collect();
 
             for &i in &word_clips {
-                let clip_dur = all_syl_clips[i].samples.len() as f64 / sr as f64;
+                let clip_dur = all_syl_clips[i].samples[0].len() as f64 / sr as f64;
                 if clip_dur >= 0.08 {
                     let syl_idx = all_syl_clips[i].syl_idx;
                     if should_stretch_syllable(
@@ -769,7 +1321,9 @@ pub fn process(
                             config.stretch_config.stretch_factor,
                             &mut rng,
                         );
-                        all_syl_clips[i].samples = time_stretch(&all_syl_clips[i].samples, sr, factor)?;
+                        all_syl_clips[i].samples = map_channels(&all_syl_clips[i].samples, |ch| {
+                            time_stretch(ch, sr, factor)
+                        })?;
                     }
                 }
                 global_syl_idx += 1;
@@ -778,12 +1332,21 @@ pub fn process(
     }
 
     // --- Fuse syllables into words ---
+    progress.stage(Stage::Assembly, 0.0);
     let crossfade_samples = (config.crossfade_ms / 1000.0 * sr as f64).round() as usize;
     let mut clips: Vec<Clip> = Vec::new();
-    let mut word_audio: Vec<Vec<f64>> = Vec::new();
+    let mut word_audio: Vec<Vec<Vec<f64>>> = Vec::new();
+    let mut anchor_paths: std::collections::HashSet<std::path::PathBuf> =
+        std::collections::HashSet::new();
+    let mut preview = RollingPreview::new(output_dir, config.preview_s, sr);
 
     for (word_idx, word_syls) in words.iter().enumerate() {
-        let syl_clips: Vec<&Vec<f64>> = all_syl_clips
+        if progress.cancelled() {
+            bail!("Collage cancelled");
+        }
+        // Writing word clips is the bulk of assembly
+        progress.stage(Stage::Assembly, 0.8 * word_idx as f64 / words.len() as f64);
+        let syl_clips: Vec<&Vec<Vec<f64>>> = all_syl_clips
             .iter()
             .filter(|c| c.word_idx == word_idx)
             .map(|c| &c.samples)
@@ -796,14 +1359,20 @@ pub fn process(
         let word_samples = if syl_clips.len() == 1 {
             syl_clips[0].clone()
         } else {
-            let owned: Vec<Vec<f64>> = syl_clips.iter().map(|c| c.to_vec()).collect();
-            concatenate(&owned, crossfade_samples)
+            let owned: Vec<Vec<Vec<f64>>> = syl_clips.iter().map(|c| c.to_vec()).collect();
+            concatenate_channels(&owned, crossfade_samples)
         };
 
         // Write word clip to clips_dir
         let word_filename = format!("{:03}_word.wav", word_idx + 1);
         let word_output = clips_dir.join(&word_filename);
-        write_wav(&word_output, &word_samples, sr)?;
+        write_wav_channels(&word_output, &word_samples, sr)?;
+        if let Some(preview) = &mut preview {
+            preview.push_word(&word_samples)?;
+        }
+        if anchor_word_indices.contains(&word_idx) {
+            anchor_paths.insert(word_output.clone());
+        }
 
         // Determine dominant source
         let word_sources: Vec<String> = word_syls.iter().map(&find_source).collect();
@@ -817,7 +1386,7 @@ pub fn process(
             syllables: word_syls.clone(),
             start: word_syls.iter().map(|s| s.start).fold(f64::INFINITY, f64::min),
             end: word_syls.iter().map(|s| s.end).fold(f64::NEG_INFINITY, f64::max),
-            source: dominant,
+            source: SourceId::intern(dominant),
             output_path: word_output,
         });
         word_audio.push(word_samples);
@@ -826,12 +1395,12 @@ pub fn process(
     // --- Word stretch ---
     if let Some(word_stretch_prob) = config.stretch_config.word_stretch {
         for (i, samples) in word_audio.iter_mut().enumerate() {
-            let clip_dur = samples.len() as f64 / sr as f64;
+            let clip_dur = samples[0].len() as f64 / sr as f64;
             if clip_dur >= 0.08 && rng.gen::<f64>() < word_stretch_prob {
                 let factor = resolve_stretch_factor(config.stretch_config.stretch_factor, &mut rng);
-                *samples = time_stretch(samples, sr, factor)?;
+                *samples = map_channels(samples, |ch| time_stretch(ch, sr, factor))?;
                 // Re-write the word file
-                if let Err(e) = write_wav(&clips[i].output_path, samples, sr) {
+                if let Err(e) = write_wav_channels(&clips[i].output_path, samples, sr) {
                     log::debug!("Failed to rewrite stretched word: {}", e);
                 }
             }
@@ -845,30 +1414,125 @@ pub fn process(
         }
     }
 
+    // --- Pull anchors out when they should lead sentences ---
+    // Consecutive copies (from word repeat) stay together with their anchor.
+    let mut sentence_anchors: Vec<Vec<Clip>> = Vec::new();
+    let phrase_source: Vec<Clip> = if config.anchor_sentence_start && !anchor_paths.is_empty() {
+        let mut rest = Vec::new();
+        for clip in &clips {
+            if anchor_paths.contains(&clip.output_path) {
+                match sentence_anchors.last_mut() {
+                    Some(group) if group[0].output_path == clip.output_path => {
+                        group.push(clip.clone())
+                    }
+                    _ => sentence_anchors.push(vec![clip.clone()]),
+                }
+            } else {
+                rest.push(clip.clone());
+            }
+        }
+        rest
+    } else {
+        clips.clone()
+    };
+
     // --- Group into phrases ---
     let word_cf_samples = (config.word_crossfade_ms / 1000.0 * sr as f64).round() as usize;
-    let phrase_groups = group_into_chunks(&clips, wpp_min, wpp_max, &mut rng);
+    let mut phrase_groups = group_into_chunks(&phrase_source, wpp_min, wpp_max, &mut rng);
+    if phrase_groups.is_empty() && !sentence_anchors.is_empty() {
+        phrase_groups.push(Vec::new());
+    }
+
+    // --- Group phrases into sentences ---
+    let sentence_groups = group_into_chunks(
+        &(0..phrase_groups.len()).collect::<Vec<_>>(),
+        pps_min,
+        pps_max,
+        &mut rng,
+    );
+
+    // Prepend each anchor to the first phrase of a sentence, round-robin
+    for (i, group) in sentence_anchors.into_iter().enumerate() {
+        let sentence = &sentence_groups[i % sentence_groups.len()];
+        let first_phrase = &mut phrase_groups[sentence[0]];
+        for (j, clip) in group.into_iter().enumerate() {
+            first_phrase.insert(j, clip);
+        }
+    }
+
+    // --- Sentence intonation: a pitch shift per word ---
+    let mut phrase_shifts: Vec<Vec<f64>> = vec![Vec::new(); phrase_groups.len()];
+    if config.intonation {
+        let mut questions = 0;
+        for sentence in &sentence_groups {
+            let contour = Contour::pick(config.question_probability, &mut rng);
+            questions += usize::from(contour == Contour::Question);
+            let n_words: usize = sentence.iter().map(|&p| phrase_groups[p].len()).sum();
+            let mut shifts = word_shifts(n_words, contour).into_iter();
+            for &p in sentence {
+                phrase_shifts[p] = shifts.by_ref().take(phrase_groups[p].len()).collect();
+            }
+        }
+        if questions > 0 {
+            log::info!("Intonation: {}/{} sentences are questions", questions, sentence_groups.len());
+        }
+    }
 
-    let mut phrase_audio: Vec<Vec<f64>> = Vec::new();
-    for phrase_clips in &phrase_groups {
+    let beat_grid = config
+        .bpm
+        .map(|bpm| BeatGrid::new(bpm, config.beat_subdivision, config.swing));
+    let mut phrase_audio: Vec<Vec<Vec<f64>>> = Vec::new();
+    // Beat mode keeps words separate so each lands on the grid
+    let mut phrase_words: Vec<Vec<Vec<Vec<f64>>>> = Vec::new();
+    // Per phrase: each word's (offset, length) in samples and its caption
+    let mut phrase_captions: Vec<Vec<(usize, usize, String)>> = Vec::new();
+    for (phrase_clips, shifts) in phrase_groups.iter().zip(&phrase_shifts) {
         // Load word audio for each clip in phrase
-        let mut phrase_word_samples: Vec<Vec<f64>> = Vec::new();
-        for clip in phrase_clips {
+        let mut phrase_word_samples: Vec<Vec<Vec<f64>>> = Vec::new();
+        let mut captions: Vec<String> = Vec::new();
+        for (w, clip) in phrase_clips.iter().enumerate() {
             if clip.output_path.exists() {
-                if let Ok((samples, _)) = read_wav(&clip.output_path) {
+                if let Ok((samples, _)) = read_wav_channels(&clip.output_path) {
+                    let samples = match shifts.get(w) {
+                        Some(&st) => map_channels(&samples, |ch| pitch_shift(ch, sr, st))?,
+                        None => samples,
+                    };
+                    let samples = if config.pan_spread > 0.0 {
+                        let spread = config.pan_spread.min(1.0);
+                        pan(&samples, rng.gen_range(-spread..=spread))
+                    } else {
+                        to_layout(&samples, layout)
+                    };
                     phrase_word_samples.push(samples);
+                    captions.push(clip_caption(clip));
                 }
             }
         }
 
+        let lengths: Vec<usize> = phrase_word_samples.iter().map(|w| w[0].len()).collect();
+        let offsets = concatenated_offsets(&lengths, word_cf_samples);
+        phrase_captions.push(
+            offsets
+                .into_iter()
+                .zip(lengths)
+                .zip(captions)
+                .map(|((offset, len), caption)| (offset, len, caption))
+                .collect(),
+        );
+
+        if beat_grid.is_some() {
+            phrase_words.push(phrase_word_samples.clone());
+        }
         if phrase_word_samples.is_empty() {
+            // Keep indices aligned with sentence_groups; skipped below
+            phrase_audio.push(Vec::new());
             continue;
         }
 
         let phrase = if phrase_word_samples.len() == 1 {
             phrase_word_samples.into_iter().next().unwrap()
         } else {
-            concatenate(&phrase_word_samples, word_cf_samples)
+            concatenate_channels(&phrase_word_samples, word_cf_samples)
         };
 
         phrase_audio.push(phrase);
@@ -877,114 +1541,194 @@ pub fn process(
     // --- Prosodic dynamics ---
     if config.prosodic_dynamics {
         for phrase in phrase_audio.iter_mut() {
-            apply_prosodic_dynamics(phrase, sr);
+            for ch in phrase.iter_mut() {
+                apply_prosodic_dynamics(ch, sr);
+            }
         }
     }
 
-    // --- Group phrases into sentences, compute gaps ---
-    let sentence_groups = group_into_chunks(
-        &(0..phrase_audio.len()).collect::<Vec<_>>(),
-        pps_min,
-        pps_max,
-        &mut rng,
-    );
-
-    let mut ordered_phrases: Vec<&Vec<f64>> = Vec::new();
-    let mut gap_durations: Vec<f64> = Vec::new();
-    let mut gap_types: Vec<&str> = Vec::new();
-
-    for (sent_idx, sent_phrase_indices) in sentence_groups.iter().enumerate() {
-        for (i, &phrase_idx) in sent_phrase_indices.iter().enumerate() {
-            if phrase_idx < phrase_audio.len() {
-                ordered_phrases.push(&phrase_audio[phrase_idx]);
+    // --- Voice effect, per phrase so filters don't ring into the gaps ---
+    if let Some(fx) = config.voice_fx {
+        for phrase in phrase_audio.iter_mut() {
+            for ch in phrase.iter_mut() {
+                *ch = fx.apply(ch, sr);
+            }
+        }
+        // Beat mode places words one by one
+        for word in phrase_words.iter_mut().flatten() {
+            for ch in word.iter_mut() {
+                *ch = fx.apply(ch, sr);
+            }
+        }
+    }
 
-                let is_last_in_sentence = i == sent_phrase_indices.len() - 1;
-                let is_last_sentence = sent_idx == sentence_groups.len() - 1;
+    // --- Compute gaps between phrases and sentences ---
+    let sentence_groups: Vec<Vec<usize>> = sentence_groups
+        .into_iter()
+        .map(|sent| {
+            sent.into_iter()
+                .filter(|&i| !phrase_audio[i].is_empty())
+                .collect::<Vec<_>>()
+        })
+        .filter(|sent| !sent.is_empty())
+        .collect();
 
-                if !(is_last_in_sentence && is_last_sentence) {
-                    if is_last_in_sentence {
-                        gap_durations.push(rng.gen_range(sp_min..=sp_max));
-                        gap_types.push("sentence");
-                    } else {
-                        gap_durations.push(rng.gen_range(pp_min..=pp_max));
-                        gap_types.push("phrase");
+    // Each word's (start, end) in output samples and its caption
+    let mut word_spans: Vec<(usize, usize, String)> = Vec::new();
+    let final_clips: Vec<Vec<Vec<f64>>> = if let Some(grid) = &beat_grid {
+        // --- Beat grid: quantized onsets replace pauses, room tone, and breaths ---
+        let sentences: Vec<Vec<&[Vec<Vec<f64>>]>> = sentence_groups
+            .iter()
+            .map(|sent| sent.iter().map(|&i| phrase_words[i].as_slice()).collect())
+            .collect();
+        if sentences.is_empty() {
+            bail!("No audio clips to concatenate");
+        }
+        log::info!(
+            "Beat grid: {:.1} BPM, {} steps/beat, swing {:.2}",
+            grid.bpm,
+            grid.subdivision,
+            grid.swing
+        );
+        let (placed, onsets) = place_on_grid(&sentences, grid, sr);
+        let words_in_order = sentence_groups.iter().flatten().flat_map(|&i| &phrase_captions[i]);
+        for (onset, (_, len, caption)) in onsets.into_iter().zip(words_in_order) {
+            word_spans.push((onset, onset + len, caption.clone()));
+        }
+        vec![placed]
+    } else {
+        let mut ordered_phrases: Vec<&Vec<Vec<f64>>> = Vec::new();
+        let mut ordered_captions: Vec<&[(usize, usize, String)]> = Vec::new();
+        let mut gap_durations: Vec<f64> = Vec::new();
+        let mut gap_types: Vec<&str> = Vec::new();
+
+        for (sent_idx, sent_phrase_indices) in sentence_groups.iter().enumerate() {
+            for (i, &phrase_idx) in sent_phrase_indices.iter().enumerate() {
+                if phrase_idx < phrase_audio.len() {
+                    ordered_phrases.push(&phrase_audio[phrase_idx]);
+                    ordered_captions.push(&phrase_captions[phrase_idx]);
+
+                    let is_last_in_sentence = i == sent_phrase_indices.len() - 1;
+                    let is_last_sentence = sent_idx == sentence_groups.len() - 1;
+
+                    if !(is_last_in_sentence && is_last_sentence) {
+                        if is_last_in_sentence {
+                            gap_durations.push(rng.gen_range(sp_min..=sp_max));
+                            gap_types.push("sentence");
+                        } else {
+                            gap_durations.push(rng.gen_range(pp_min..=pp_max));
+                            gap_types.push("phrase");
+                        }
                     }
                 }
             }
         }
-    }
 
-    // --- Build gap clips (room tone or silence, optionally with breaths) ---
-    let mut final_clips: Vec<Vec<f64>> = Vec::new();
-    let room_tone_list: Vec<&Vec<f64>> = room_tone_samples.values().collect();
+        // --- Build gap clips (room tone or silence, optionally with breaths) ---
+        let mut final_clips: Vec<Vec<Vec<f64>>> = Vec::new();
+        let room_tone_list: Vec<&Vec<f64>> = room_tone_samples.values().collect();
+
+        let mut position = 0usize;
+        for (i, phrase) in ordered_phrases.iter().enumerate() {
+            for (offset, len, caption) in ordered_captions[i] {
+                word_spans.push((position + offset, position + offset + len, caption.clone()));
+            }
+            position += phrase[0].len();
+            final_clips.push(phrase.to_vec());
 
-    for (i, phrase) in ordered_phrases.iter().enumerate() {
-        final_clips.push(phrase.to_vec());
+            if i < gap_durations.len() {
+                let gap_ms = gap_durations[i];
+                let mut gap_clip = generate_silence(gap_ms, sr);
 
-        if i < gap_durations.len() {
-            let gap_ms = gap_durations[i];
-            let mut gap_clip = generate_silence(gap_ms, sr);
+                // Mix room tone into gap if available
+                if !room_tone_list.is_empty() {
+                    let rt = room_tone_list[i % room_tone_list.len()];
+                    gap_clip = mix_audio(&gap_clip, rt, 0.0);
+                }
 
-            // Mix room tone into gap if available
-            if !room_tone_list.is_empty() {
-                let rt = room_tone_list[i % room_tone_list.len()];
-                gap_clip = mix_audio(&gap_clip, rt, 0.0);
-            }
+                // Optionally prepend breath at phrase boundaries
+                if !breath_clips.is_empty()
+                    && i < gap_types.len()
+                    && gap_types[i] == "phrase"
+                    && rng.gen::<f64>() < config.breath_probability
+                {
+                    let breath = breath_clips[rng.gen_range(0..breath_clips.len())].clone();
+                    let breath_and_gap = vec![breath, gap_clip];
+                    gap_clip = concatenate(&breath_and_gap, (10.0 / 1000.0 * sr as f64).round() as usize);
+                }
 
-            // Optionally prepend breath at phrase boundaries
-            if !breath_clips.is_empty()
-                && i < gap_types.len()
-                && gap_types[i] == "phrase"
-                && rng.gen::<f64>() < config.breath_probability
-            {
-                let breath = breath_clips[rng.gen_range(0..breath_clips.len())].clone();
-                let breath_and_gap = vec![breath, gap_clip];
-                gap_clip = concatenate(&breath_and_gap, (10.0 / 1000.0 * sr as f64).round() as usize);
+                position += gap_clip.len();
+                final_clips.push(to_layout(&[gap_clip], layout));
             }
-
-            final_clips.push(gap_clip);
         }
-    }
 
-    // --- Final concatenation ---
-    let mut output_samples = if final_clips.len() > 1 {
-        concatenate(&final_clips, 0)
-    } else if final_clips.len() == 1 {
-        final_clips.into_iter().next().unwrap()
-    } else {
-        bail!("No audio clips to concatenate");
+        if final_clips.is_empty() {
+            bail!("No audio clips to concatenate");
+        }
+        final_clips
     };
 
-    // --- Global speed ---
-    if let Some(speed) = config.speed {
-        let speed_factor = 1.0 / speed;
-        output_samples = time_stretch(&output_samples, sr, speed_factor)?;
+    // --- Final concatenation and write ---
+    progress.stage(Stage::Assembly, 0.9);
+    if progress.cancelled() {
+        bail!("Collage cancelled");
     }
-
-    // --- Mix pink noise bed ---
-    if config.noise_level_db != 0.0 && !output_samples.is_empty() {
-        let dur = output_samples.len() as f64 / sr as f64;
-        let noise = generate_pink_noise(dur, sr, config.seed);
-        output_samples = mix_audio(&output_samples, &noise, config.noise_level_db);
+    if let Some(preview) = &mut preview {
+        preview.replace(&final_clips)?;
     }
-
-    // --- Write output ---
     let run_name = output_dir
         .file_name()
         .unwrap_or_default()
         .to_string_lossy();
     let concatenated_path = output_dir.join(format!("{}.wav", run_name));
-    write_wav(&concatenated_path, &output_samples, sr)?;
+    // Word spans scale with the stretch
+    let time_scale = config.speed.map(|speed| 1.0 / speed).unwrap_or(1.0);
+    let loudness = if let Some(speed) = config.speed {
+        // --- Global speed: stretching needs the whole mix in memory ---
+        let mut output_samples = concatenate_channels(&final_clips, 0);
+        drop(final_clips);
+        let speed_factor = 1.0 / speed;
+        output_samples = map_channels(&output_samples, |ch| time_stretch(ch, sr, speed_factor))?;
+
+        // --- Mix pink noise bed (decorrelated per channel) ---
+        if config.noise_level_db != 0.0 && !output_samples[0].is_empty() {
+            let dur = output_samples[0].len() as f64 / sr as f64;
+            for (ch_idx, ch) in output_samples.iter_mut().enumerate() {
+                let seed = config.seed.map(|s| s.wrapping_add(ch_idx as u64));
+                let noise = generate_pink_noise(dur, sr, seed);
+                *ch = mix_audio(ch, &noise, config.noise_level_db);
+            }
+        }
+        if config.limiter {
+            output_samples = limit_peaks(&output_samples, sr);
+        }
+        config.mastering.write(&concatenated_path, &output_samples, sr)?
+    } else {
+        write_mix_streaming(&concatenated_path, final_clips, config.noise_level_db, config.seed, sr, config.limiter)?;
+        config.mastering.apply_wav(&concatenated_path)?
+    };
+    if let Some(preview) = preview {
+        preview.remove();
+    }
 
     // --- Write manifest ---
+    let sources = source_table(source_syllables.keys());
     let manifest = serde_json::json!({
-        "sources": source_syllables.keys().collect::<Vec<_>>(),
+        "sources": sources.iter().map(|s| relative_to(s.path(), output_dir)).collect::<Vec<_>>(),
         "total_syllables": source_syllables.values().map(|s| s.len()).sum::<usize>(),
         "selected_syllables": selected.len(),
+        "unit": config.unit,
+        "bpm": config.bpm,
+        "channels": layout.count(),
+        "anchor_words": anchor_words
+            .iter()
+            .filter_map(|w| w.first().map(|s| s.word.clone()))
+            .collect::<Vec<_>>(),
+        "loudness": loudness_table(&concatenated_path, &loudness)?,
         "clips": clips.iter().map(|c| {
             serde_json::json!({
                 "filename": c.output_path.file_name().unwrap_or_default().to_string_lossy(),
-                "source": c.source,
+                "source": sources.iter().position(|&s| s == c.source),
                 "word": c.syllables.first().map(|s| s.word.as_str()).unwrap_or(""),
                 "syllable_count": c.syllables.len(),
                 "start": c.start,
@@ -996,6 +1740,18 @@ pub fn process(
     let manifest_path = output_dir.join("manifest.json");
     std::fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
 
+    // --- Write captions ---
+    let cues: Vec<Cue> = word_spans
+        .into_iter()
+        .map(|(start, end, text)| Cue {
+            start: start as f64 / sr as f64 * time_scale,
+            end: end as f64 / sr as f64 * time_scale,
+            text,
+        })
+        .collect();
+    write_subtitles(output_dir, &run_name, &cues)?;
+    progress.stage(Stage::Assembly, 1.0);
+
     Ok(PipelineResult {
         clips,
         concatenated: concatenated_path,
@@ -1011,6 +1767,7 @@ pub fn process(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::progress::NoProgress;
 
     #[test]
     fn test_parse_range() {
@@ -1018,6 +1775,26 @@ mod tests {
         assert_eq!(parse_range("3"), (3, 3));
     }
 
+    #[test]
+    fn test_noise_bed_matches_mix_audio() {
+        let sr = 16000;
+        let frames = 3001;
+        let block: Vec<Vec<f64>> = (0..2).map(|ch| vec![0.1 * ch as f64; frames]).collect();
+        let mut bed = NoiseBed::new(2, frames, -30.0, Some(5), sr);
+        let mut streamed: Vec<Vec<f64>> = vec![Vec::new(); 2];
+        for range in [0..1, 1..1000, 1000..frames] {
+            let mut part: Vec<Vec<f64>> = block.iter().map(|c| c[range.clone()].to_vec()).collect();
+            bed.mix(&mut part);
+            for (out, ch) in streamed.iter_mut().zip(part) {
+                out.extend(ch);
+            }
+        }
+        for (ch_idx, ch) in block.iter().enumerate() {
+            let noise = generate_pink_noise(frames as f64 / sr as f64, sr, Some(5 + ch_idx as u64));
+            assert_eq!(streamed[ch_idx], mix_audio(ch, &noise, -30.0));
+        }
+    }
+
     #[test]
     fn test_parse_gap() {
         assert_eq!(parse_gap("50-200"), (50.0, 200.0));
@@ -1036,7 +1813,7 @@ mod tests {
     #[test]
     fn test_sample_syllables_empty() {
         let mut rng = StdRng::seed_from_u64(42);
-        assert!(sample_syllables(&[], 10.0, 1.0, &mut rng).is_empty());
+        assert!(sample_syllables(&[], None, 10.0, 1.0, &mut rng).is_empty());
     }
 
     #[test]
@@ -1051,12 +1828,130 @@ mod tests {
                 word_index: i,
             })
             .collect();
-        let selected = sample_syllables(&syls, 1.0, 1.0, &mut rng);
+        let selected = sample_syllables(&syls, None, 1.0, 1.0, &mut rng);
         assert!(!selected.is_empty());
         let total_dur: f64 = selected.iter().map(|s| s.end - s.start).sum();
         assert!(total_dur <= 2.0); // Approximately target + one syllable
     }
 
+    #[test]
+    fn test_source_weight_parse() {
+        let w = SourceWeight::parse("a=b.wav=0.7").unwrap();
+        assert_eq!(w.file, PathBuf::from("a=b.wav"));
+        assert_eq!(w.weight, 0.7);
+        assert!(w.matches(Path::new("/in/a=b.wav")));
+        assert!(SourceWeight::parse("x.wav").is_err());
+        assert!(SourceWeight::parse("x.wav=-1").is_err());
+        assert!(SourceWeight::parse("=1").is_err());
+    }
+
+    #[test]
+    fn test_multi_source_weights_set_shares() {
+        let source = |tag: &str| -> Vec<Syllable> {
+            (0..200)
+                .map(|i| Syllable {
+                    phonemes: vec![],
+                    start: i as f64 * 5.0,
+                    end: i as f64 * 5.0 + 0.125,
+                    word: tag.to_string(),
+                    word_index: i,
+                })
+                .collect()
+        };
+        let sources: HashMap<String, Vec<Syllable>> =
+            ["a", "b", "c"].iter().map(|t| (t.to_string(), source(t))).collect();
+        let count = |weights: &HashMap<String, f64>, tag: &str| {
+            let mut rng = StdRng::seed_from_u64(3);
+            sample_syllables_multi_source(&sources, None, weights, 7.5, 0.0, &mut rng)
+                .iter()
+                .filter(|s| s.word == tag)
+                .count()
+        };
+
+        let equal = HashMap::new();
+        assert_eq!((count(&equal, "a"), count(&equal, "b"), count(&equal, "c")), (20, 20, 20));
+
+        let weights: HashMap<String, f64> = [("a".to_string(), 4.0), ("c".to_string(), 0.0)].into();
+        assert_eq!(count(&weights, "a"), 48);
+        assert_eq!(count(&weights, "b"), 12);
+        assert_eq!(count(&weights, "c"), 0);
+    }
+
+    #[test]
+    fn test_bias_weight() {
+        assert_eq!(bias_weight(2.0, 1.0, None), 1.0);
+        assert!(bias_weight(2.0, 1.0, Some("long")) > 1.0);
+        assert!(bias_weight(2.0, 1.0, Some("short")) < 1.0);
+        assert!(bias_weight(0.5, 1.0, Some("high")) < 1.0);
+        assert!(bias_weight(0.5, 1.0, Some("low")) > 1.0);
+    }
+
+    #[test]
+    fn test_weighted_shuffle_keeps_items() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let items: Vec<i32> = (0..20).collect();
+        let weights = vec![1.0; 20];
+        let mut out = weighted_shuffle(&items, &weights, &mut rng);
+        out.sort();
+        assert_eq!(out, items);
+    }
+
+    #[test]
+    fn test_sample_syllables_weighted_is_deterministic() {
+        let syls: Vec<Syllable> = (0..10)
+            .map(|i| Syllable {
+                phonemes: vec![],
+                start: i as f64,
+                end: i as f64 + 0.1 + i as f64 * 0.05,
+                word: format!("w{}", i),
+                word_index: i,
+            })
+            .collect();
+        let weights: Vec<f64> = (0..10).map(|i| 1.0 + i as f64).collect();
+        let mut rng_a = StdRng::seed_from_u64(3);
+        let mut rng_b = StdRng::seed_from_u64(3);
+        let a = sample_syllables(&syls, Some(&weights), 1.0, 0.0, &mut rng_a);
+        let b = sample_syllables(&syls, Some(&weights), 1.0, 0.0, &mut rng_b);
+        let words_a: Vec<_> = a.iter().map(|s| s.word.clone()).collect();
+        let words_b: Vec<_> = b.iter().map(|s| s.word.clone()).collect();
+        assert_eq!(words_a, words_b);
+    }
+
+    #[test]
+    fn test_normalize_anchor_word() {
+        assert_eq!(normalize_anchor_word("Hello,"), "hello");
+        assert_eq!(normalize_anchor_word("\"don't\""), "don't");
+        assert_eq!(normalize_anchor_word("  World! "), "world");
+    }
+
+    #[test]
+    fn test_find_anchor_words() {
+        let mk = |word: &str, word_index: usize, start: f64| Syllable {
+            phonemes: vec![],
+            start,
+            end: start + 0.2,
+            word: word.to_string(),
+            word_index,
+        };
+        let mut sources = HashMap::new();
+        sources.insert(
+            "a.wav".to_string(),
+            vec![
+                mk("hello", 0, 0.0),
+                mk("hello", 0, 0.2),
+                mk("there", 1, 0.5),
+                mk("world", 2, 1.0),
+            ],
+        );
+        let mut rng = StdRng::seed_from_u64(42);
+        let anchors = vec!["Hello".to_string(), "missing".to_string(), "world".to_string()];
+        let found = find_anchor_words(&sources, &anchors, &mut rng);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].len(), 2);
+        assert!(found[0][0].start < found[0][1].start);
+        assert_eq!(found[1][0].word, "world");
+    }
+
     #[test]
     fn test_group_into_words() {
         let mut rng = StdRng::seed_from_u64(42);
@@ -1069,12 +1964,38 @@ mod tests {
                 word_index: i,
             })
             .collect();
-        let words = group_into_words(&syls, 1, 3, &mut rng);
+        let words = group_into_words(&syls, 1, 3, None, 0, &mut rng);
         assert!(!words.is_empty());
         let total: usize = words.iter().map(|w| w.len()).sum();
         assert_eq!(total, 10);
     }
 
+    #[test]
+    fn test_spread_reused_syllables() {
+        let syl = |i: usize| Syllable {
+            phonemes: vec![],
+            start: i as f64 * 0.3,
+            end: i as f64 * 0.3 + 0.3,
+            word: format!("w{}", i),
+            word_index: i,
+        };
+        // Syllable 0 back to back, and again two later
+        let mut syls: Vec<Syllable> = [0, 0, 1, 0, 2, 3, 4, 5].iter().map(|&i| syl(i)).collect();
+        spread_reused_syllables(&mut syls, 2);
+        let order: Vec<usize> = syls.iter().map(|s| s.word_index).collect();
+        assert_eq!(order.len(), 8);
+        for (i, a) in order.iter().enumerate() {
+            for b in &order[i + 1..(i + 3).min(order.len())] {
+                assert_ne!(a, b, "repeat within the window: {:?}", order);
+            }
+        }
+
+        // Nothing to swap in: the clash stays, nothing is lost
+        let mut stuck = vec![syl(0), syl(0)];
+        spread_reused_syllables(&mut stuck, 4);
+        assert_eq!(stuck.len(), 2);
+    }
+
     #[test]
     fn test_group_into_chunks() {
         let mut rng = StdRng::seed_from_u64(42);
@@ -1102,6 +2023,279 @@ mod tests {
         assert!(samples[fade_start] < original[fade_start]);
     }
 
+    /// Source audio and syllables, both keyed by source.
+    type Fixture = (HashMap<String, (Vec<f64>, u32)>, HashMap<String, Vec<Syllable>>);
+
+    /// A 4 s, 220 Hz sine source "src" with sixteen 0.2 s syllables 0.25 s apart.
+    fn sine_fixture() -> Fixture {
+        let sr = 16000u32;
+        let samples: Vec<f64> = (0..sr as usize * 4)
+            .map(|i| (i as f64 / sr as f64 * 220.0 * std::f64::consts::TAU).sin() * 0.5)
+            .collect();
+        let syls: Vec<Syllable> = (0..16)
+            .map(|i| Syllable {
+                phonemes: vec![],
+                start: i as f64 * 0.25,
+                end: i as f64 * 0.25 + 0.2,
+                word: format!("w{}", i),
+                word_index: i,
+            })
+            .collect();
+        let mut audio = HashMap::new();
+        audio.insert("src".to_string(), (samples, sr));
+        let mut syllables = HashMap::new();
+        syllables.insert("src".to_string(), syls);
+        (audio, syllables)
+    }
+
+    /// A 2 s collage with no noise bed, room tone, breaths, pitch
+    /// normalization or dynamics, so the output is the clips alone.
+    fn quiet_config(seed: u64) -> CollageConfig {
+        CollageConfig {
+            target_duration: 2.0,
+            seed: Some(seed),
+            noise_level_db: 0.0,
+            room_tone: false,
+            breaths: false,
+            pitch_normalize: false,
+            prosodic_dynamics: false,
+            ..CollageConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_process_prefer_duration_shifts_selection() {
+        // 40 syllables alternating 0.1 s and 0.5 s, far more than a 3 s
+        // collage needs, so the preference decides which ones get picked.
+        let (mut audio, mut syllables) = sine_fixture();
+        let sr = audio["src"].1;
+        let samples: Vec<f64> = (0..sr as usize * 40)
+            .map(|i| (i as f64 / sr as f64 * 220.0 * std::f64::consts::TAU).sin() * 0.5)
+            .collect();
+        audio.insert("src".to_string(), (samples, sr));
+        let syls: Vec<Syllable> = (0..40)
+            .map(|i| Syllable {
+                phonemes: vec![],
+                start: i as f64,
+                end: i as f64 + if i % 2 == 0 { 0.1 } else { 0.5 },
+                word: format!("w{}", i),
+                word_index: i,
+            })
+            .collect();
+        syllables.insert("src".to_string(), syls);
+
+        let mean_duration = |prefer: Option<&str>| {
+            let durations: Vec<f64> = (0..4)
+                .flat_map(|seed| {
+                    let dir = std::env::temp_dir().join(format!(
+                        "glottisdale_collage_prefer_{}_{}_{}",
+                        prefer.unwrap_or("none"),
+                        seed,
+                        std::process::id()
+                    ));
+                    let config = CollageConfig {
+                        target_duration: 3.0,
+                        dispersal_gap: 0.0,
+                        prefer_duration: prefer.map(str::to_string),
+                        ..quiet_config(seed)
+                    };
+                    let result = process(&audio, &syllables, &dir, &config, &NoProgress).unwrap();
+                    let _ = std::fs::remove_dir_all(&dir);
+                    result
+                        .clips
+                        .into_iter()
+                        .flat_map(|c| c.syllables)
+                        .map(|s| s.end - s.start)
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            durations.iter().sum::<f64>() / durations.len() as f64
+        };
+
+        let unbiased = mean_duration(None);
+        let long = mean_duration(Some("long"));
+        let short = mean_duration(Some("short"));
+        assert!(long > unbiased, "long {long:.3} vs unbiased {unbiased:.3}");
+        assert!(short < unbiased, "short {short:.3} vs unbiased {unbiased:.3}");
+    }
+
+    #[test]
+    fn test_process_channels_keeps_stereo_image() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_collage_stereo_{}", std::process::id()));
+        let (mono, syllables) = sine_fixture();
+        let (left, sr) = mono["src"].clone();
+        let right: Vec<f64> = left.iter().map(|v| v * 0.25).collect();
+        let mut audio = SourceChannels::new();
+        audio.insert("src".to_string(), (vec![left, right], sr));
+
+        let config = CollageConfig {
+            target_duration: 1.0,
+            volume_normalize: false,
+            dispersal_gap: 0.0,
+            ..quiet_config(1)
+        };
+        let result = process_channels(&audio, &syllables, &dir, &config, &NoProgress).unwrap();
+        let (channels, _) = read_wav_channels(&result.concatenated).unwrap();
+        assert_eq!(channels.len(), 2);
+        let left_rms = compute_rms(&channels[0]);
+        let right_rms = compute_rms(&channels[1]);
+        assert!(right_rms < left_rms * 0.5, "right should stay quieter: {} vs {}", right_rms, left_rms);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_process_word_unit_keeps_words_whole() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_collage_words_{}", std::process::id()));
+        let (audio, mut syllables) = sine_fixture();
+        // Eight two-syllable words
+        for (i, syl) in syllables.get_mut("src").unwrap().iter_mut().enumerate() {
+            syl.end = syl.start + 0.25;
+            syl.word = format!("w{}", i / 2);
+            syl.word_index = i / 2;
+        }
+
+        let config = CollageConfig {
+            dispersal_gap: 0.0,
+            unit: "word".to_string(),
+            ..quiet_config(3)
+        };
+        let result = process(&audio, &syllables, &dir, &config, &NoProgress).unwrap();
+        assert!(!result.clips.is_empty());
+        for clip in &result.clips {
+            assert_eq!(clip.syllables.len(), 1);
+            assert!((clip.syllables[0].end - clip.syllables[0].start - 0.5).abs() < 1e-9);
+        }
+        assert_eq!(result.manifest["total_syllables"], 8);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_process_normalizes_loudness_into_manifest() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_collage_loudness_{}", std::process::id()));
+        let (audio, syllables) = sine_fixture();
+        let result = process(&audio, &syllables, &dir, &quiet_config(6), &NoProgress).unwrap();
+
+        let (channels, sr) = read_wav_channels(&result.concatenated).unwrap();
+        let lufs = crate::audio::loudness::integrated_loudness(&channels, sr).unwrap();
+        assert!((lufs + 16.0).abs() < 0.5, "{}", lufs);
+        let name = result.concatenated.file_name().unwrap().to_str().unwrap();
+        let report = &result.manifest["loudness"][name];
+        assert_eq!(report["target_lufs"], -16.0);
+        assert!((report["output_lufs"].as_f64().unwrap() - lufs).abs() < 0.1);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_process_reports_cutting_and_assembly_progress() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_collage_progress_{}", std::process::id()));
+        let (audio, syllables) = sine_fixture();
+
+        let seen = std::cell::RefCell::new(Vec::new());
+        let reporter = |stage: &str, fraction: f64| seen.borrow_mut().push((stage.to_string(), fraction));
+        process(&audio, &syllables, &dir, &quiet_config(5), &reporter).unwrap();
+
+        let seen = seen.into_inner();
+        assert_eq!(seen.first().unwrap(), &("Cutting".to_string(), 0.4));
+        assert_eq!(seen.last().unwrap(), &("Assembling".to_string(), 1.0));
+        assert!(seen.windows(2).all(|w| w[0].1 <= w[1].1), "progress must not go backwards");
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_process_stops_when_cancelled_and_cleans_up_preview() {
+        struct Cancelled;
+        impl ProgressReporter for Cancelled {
+            fn report(&self, _stage: &str, _fraction: f64) {}
+            fn cancelled(&self) -> bool {
+                true
+            }
+        }
+
+        let dir = std::env::temp_dir().join(format!("glottisdale_collage_cancel_{}", std::process::id()));
+        let (audio, syllables) = sine_fixture();
+        let config = CollageConfig { preview_s: 1.0, ..quiet_config(5) };
+
+        let err = process(&audio, &syllables, &dir, &config, &Cancelled).unwrap_err();
+        assert_eq!(err.to_string(), "Collage cancelled");
+
+        let result = process(&audio, &syllables, &dir, &config, &crate::progress::NoProgress).unwrap();
+        assert!(result.concatenated.is_file());
+        assert!(!crate::collage::preview::preview_path(&dir).exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_process_is_reproducible_with_parallel_cutting() {
+        let (mut audio, syllables) = sine_fixture();
+        // A rising tone, so pitch normalization shifts each clip differently
+        let (samples, sr) = audio.get_mut("src").unwrap();
+        for (i, s) in samples.iter_mut().enumerate() {
+            let t = i as f64 / *sr as f64;
+            *s = (t * (150.0 + 20.0 * t) * std::f64::consts::TAU).sin() * 0.4;
+        }
+        let config = CollageConfig {
+            pitch_normalize: true,
+            prosodic_dynamics: true,
+            ..quiet_config(11)
+        };
+
+        let run = |name: &str| {
+            let dir = std::env::temp_dir().join(format!("glottisdale_collage_{}_{}", name, std::process::id()));
+            let result = process(&audio, &syllables, &dir, &config, &NoProgress).unwrap();
+            let (out, _) = crate::audio::io::read_wav(&result.concatenated).unwrap();
+            std::fs::remove_dir_all(&dir).ok();
+            out
+        };
+        assert_eq!(run("repro_a"), run("repro_b"));
+    }
+
+    #[test]
+    fn test_process_beat_grid_mode() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_collage_bpm_{}", std::process::id()));
+        let (audio, syllables) = sine_fixture();
+
+        let config = CollageConfig { bpm: Some(120.0), ..quiet_config(9) };
+        let result = process(&audio, &syllables, &dir, &config, &NoProgress).unwrap();
+        assert_eq!(result.manifest["bpm"], 120.0);
+        let (out, _) = crate::audio::io::read_wav(&result.concatenated).unwrap();
+        // Output starts on the grid: the first word begins at sample zero
+        assert!(out[..80].iter().any(|s| s.abs() > 0.01));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_process_writes_subtitles() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_collage_srt_{}", std::process::id()));
+        let (audio, syllables) = sine_fixture();
+
+        let result = process(&audio, &syllables, &dir, &quiet_config(4), &NoProgress).unwrap();
+        let srt = std::fs::read_to_string(result.concatenated.with_extension("srt")).unwrap();
+        let vtt = std::fs::read_to_string(result.concatenated.with_extension("vtt")).unwrap();
+        assert!(vtt.starts_with("WEBVTT"));
+
+        let cue_count = srt.lines().filter(|l| l.contains(" --> ")).count();
+        assert_eq!(cue_count, result.clips.len());
+        assert_eq!(vtt.lines().filter(|l| l.contains(" --> ")).count(), cue_count);
+        assert!(srt.contains("1\n00:00:00,000 --> "));
+        assert!(srt.lines().any(|l| l.starts_with('w')));
+
+        // The last word ends where the output ends
+        let (out, sr) = crate::audio::io::read_wav(&result.concatenated).unwrap();
+        let last = srt.lines().rfind(|l| l.contains(" --> ")).unwrap();
+        let end = last.split(" --> ").nth(1).unwrap();
+        let secs: f64 = end[6..].replace(',', ".").parse::<f64>().unwrap()
+            + end[3..5].parse::<f64>().unwrap() * 60.0;
+        assert!((secs - out.len() as f64 / sr as f64).abs() < 0.002);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn test_collage_config_default() {
         let config = CollageConfig::default();
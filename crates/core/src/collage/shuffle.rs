@@ -13,10 +13,11 @@ use rand::seq::SliceRandom;
 use rand::SeedableRng;
 
 use crate::audio::analysis::{compute_rms, estimate_f0};
-use crate::audio::effects::{concatenate, cut_clip, time_stretch};
-use crate::audio::io::write_wav;
+use crate::audio::effects::{concatenate, cut_clip, limit_peaks, time_stretch};
+use crate::audio::loudness::{loudness_table, Mastering};
 use crate::speak::matcher::MatchResult;
 use crate::speak::phonetic_distance::{normalize_phoneme, syllable_distance};
+use crate::source::{relative_to, source_table};
 use crate::speak::syllable_bank::{SyllableEntry, build_bank};
 use crate::types::{PipelineResult, Syllable};
 
@@ -33,6 +34,8 @@ pub fn process_shuffle(
     output_dir: &Path,
     target_duration: f64,
     crossfade_ms: f64,
+    limiter: bool,
+    mastering: &Mastering,
 ) -> Result<PipelineResult> {
     if source_syllables.len() < 2 {
         bail!("Shuffle mode requires at least 2 source files");
@@ -197,8 +200,8 @@ pub fn process_shuffle(
         let mut syllable_clips: Vec<Vec<f64>> = Vec::new();
 
         for (m, template_dur) in &matches {
-            let source_path = &m.entry.source_path;
-            let (samples, sample_rate) = match source_audio.get(source_path) {
+            let source_path = m.entry.source.path().to_string_lossy();
+            let (samples, sample_rate) = match source_audio.get(source_path.as_ref()) {
                 Some(s) => s,
                 None => continue,
             };
@@ -257,7 +260,10 @@ pub fn process_shuffle(
         .unwrap_or_default()
         .to_string_lossy();
     let concatenated_path = output_dir.join(format!("{}.wav", run_name));
-    write_wav(&concatenated_path, &all_output_samples, sr)?;
+    if limiter {
+        all_output_samples = limit_peaks(&[all_output_samples], sr).remove(0);
+    }
+    let loudness = mastering.write(&concatenated_path, std::slice::from_ref(&all_output_samples), sr)?;
 
     let output_duration = all_output_samples.len() as f64 / sr as f64;
     log::info!(
@@ -269,10 +275,11 @@ pub fn process_shuffle(
 
     let manifest = serde_json::json!({
         "mode": "shuffle",
-        "sources": source_names,
+        "sources": source_table(&source_names).iter().map(|s| relative_to(s.path(), output_dir)).collect::<Vec<_>>(),
         "total_syllables": total_after,
         "matched_syllables": total_matched,
         "duration": output_duration,
+        "loudness": loudness_table(&concatenated_path, &loudness)?,
     });
 
     let manifest_path = output_dir.join("manifest.json");
@@ -0,0 +1,126 @@
+//! SRT and WebVTT captions for collage output: one cue per word clip,
+//! timed to where it lands in the output, labeled with its source word(s).
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::types::Clip;
+
+/// One caption: an output-time span and its text.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cue {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Caption text for a word clip: the source words its syllables came from,
+/// joined with `/` when a collage word mixes several.
+pub fn clip_caption(clip: &Clip) -> String {
+    let mut words: Vec<&str> = Vec::new();
+    for syl in &clip.syllables {
+        if words.last() != Some(&syl.word.as_str()) {
+            words.push(&syl.word);
+        }
+    }
+    words.join("/")
+}
+
+/// `HH:MM:SS<sep>mmm`, rounded to the millisecond.
+fn format_timestamp(seconds: f64, separator: char) -> String {
+    let ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+/// Render cues as SubRip (`.srt`).
+pub fn to_srt(cues: &[Cue]) -> String {
+    let mut out = String::new();
+    for (i, cue) in cues.iter().enumerate() {
+        out.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            i + 1,
+            format_timestamp(cue.start, ','),
+            format_timestamp(cue.end, ','),
+            cue.text
+        ));
+    }
+    out
+}
+
+/// Render cues as WebVTT (`.vtt`).
+pub fn to_vtt(cues: &[Cue]) -> String {
+    let mut out = String::from("WEBVTT\n\n");
+    for cue in cues {
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+            cue.text
+        ));
+    }
+    out
+}
+
+/// Write `<stem>.srt` and `<stem>.vtt` into `dir`, returning both paths.
+pub fn write_subtitles(dir: &Path, stem: &str, cues: &[Cue]) -> Result<(PathBuf, PathBuf)> {
+    let srt = dir.join(format!("{}.srt", stem));
+    let vtt = dir.join(format!("{}.vtt", stem));
+    std::fs::write(&srt, to_srt(cues))?;
+    std::fs::write(&vtt, to_vtt(cues))?;
+    Ok((srt, vtt))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Syllable;
+
+    fn cues() -> Vec<Cue> {
+        vec![
+            Cue { start: 0.0, end: 0.4567, text: "hello".into() },
+            Cue { start: 3661.5, end: 3662.0, text: "wor/ld".into() },
+        ]
+    }
+
+    #[test]
+    fn test_srt_format() {
+        assert_eq!(
+            to_srt(&cues()),
+            "1\n00:00:00,000 --> 00:00:00,457\nhello\n\n2\n01:01:01,500 --> 01:01:02,000\nwor/ld\n\n"
+        );
+    }
+
+    #[test]
+    fn test_vtt_format() {
+        let vtt = to_vtt(&cues());
+        assert!(vtt.starts_with("WEBVTT\n\n00:00:00.000 --> 00:00:00.457\nhello\n\n"));
+        assert!(vtt.ends_with("01:01:01.500 --> 01:01:02.000\nwor/ld\n\n"));
+    }
+
+    #[test]
+    fn test_clip_caption_joins_source_words() {
+        let syl = |word: &str| Syllable {
+            phonemes: vec![],
+            start: 0.0,
+            end: 0.1,
+            word: word.into(),
+            word_index: 0,
+        };
+        let clip = Clip {
+            syllables: vec![syl("water"), syl("water"), syl("melon")],
+            start: 0.0,
+            end: 0.3,
+            source: "a".into(),
+            output_path: PathBuf::from("001_word.wav"),
+        };
+        assert_eq!(clip_caption(&clip), "water/melon");
+    }
+}
@@ -0,0 +1,204 @@
+//! Beat-grid assembly: place collage words on a tempo grid instead of
+//! separating phrases with random pauses.
+
+/// Beats per bar (4/4).
+const BEATS_PER_BAR: usize = 4;
+
+/// Taps further apart than this start a new tempo estimate (seconds).
+const MAX_TAP_INTERVAL_S: f64 = 2.0;
+
+/// A tempo grid of `subdivision` steps per beat, with swing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BeatGrid {
+    pub bpm: f64,
+    /// Grid steps per beat (1 = quarter notes, 2 = eighths, 4 = sixteenths).
+    pub subdivision: usize,
+    /// Delay of every off-beat step as a fraction of a step (0 = straight,
+    /// 0.33 ≈ triplet shuffle).
+    pub swing: f64,
+}
+
+impl BeatGrid {
+    pub fn new(bpm: f64, subdivision: usize, swing: f64) -> Self {
+        Self {
+            bpm,
+            subdivision: subdivision.max(1),
+            swing: swing.clamp(0.0, 0.5),
+        }
+    }
+
+    /// Length of one grid step in seconds.
+    pub fn step_s(&self) -> f64 {
+        60.0 / self.bpm / self.subdivision as f64
+    }
+
+    /// Onset of grid step `n` in seconds, with swing applied.
+    pub fn step_time(&self, n: usize) -> f64 {
+        let straight = n as f64 * self.step_s();
+        if n % 2 == 1 {
+            straight + self.swing * self.step_s()
+        } else {
+            straight
+        }
+    }
+
+    /// First step at or after `t` whose index is a multiple of `every`.
+    fn next_step_multiple(&self, t: f64, every: usize) -> usize {
+        let mut n = ((t / self.step_s()).floor().max(0.0) as usize) / every * every;
+        while self.step_time(n) < t - 1e-9 {
+            n += every;
+        }
+        n
+    }
+
+    /// First step at or after `t`.
+    pub fn next_step(&self, t: f64) -> usize {
+        self.next_step_multiple(t, 1)
+    }
+
+    /// First beat (as a step index) at or after `t`.
+    pub fn next_beat(&self, t: f64) -> usize {
+        self.next_step_multiple(t, self.subdivision)
+    }
+
+    /// First bar downbeat (as a step index) at or after `t`.
+    pub fn next_bar(&self, t: f64) -> usize {
+        self.next_step_multiple(t, self.subdivision * BEATS_PER_BAR)
+    }
+}
+
+/// Estimate tempo from tap times in seconds (median tap interval).
+///
+/// Only the last run of taps counts; a pause over two seconds starts over.
+/// Returns `None` until there are at least two taps in the run.
+pub fn bpm_from_taps(taps: &[f64]) -> Option<f64> {
+    let start = taps
+        .windows(2)
+        .rposition(|w| w[1] - w[0] > MAX_TAP_INTERVAL_S)
+        .map(|i| i + 1)
+        .unwrap_or(0);
+    let mut intervals: Vec<f64> = taps[start..]
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|&d| d > 0.0)
+        .collect();
+    if intervals.is_empty() {
+        return None;
+    }
+    intervals.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    Some(60.0 / intervals[intervals.len() / 2])
+}
+
+/// Place words on the grid and mix them into one planar buffer.
+///
+/// Also returns each word's onset in samples, in input order.
+///
+/// `sentences` holds phrases, each a list of planar word clips. Words start
+/// on the first step after the previous word ends, phrases on the next beat
+/// after a one-step rest, and sentences on the next bar after a one-beat
+/// rest.
+pub fn place_on_grid(
+    sentences: &[Vec<&[Vec<Vec<f64>>]>],
+    grid: &BeatGrid,
+    sr: u32,
+) -> (Vec<Vec<f64>>, Vec<usize>) {
+    let channels = sentences
+        .iter()
+        .flatten()
+        .flat_map(|phrase| phrase.iter())
+        .map(|w| w.len())
+        .max()
+        .unwrap_or(1);
+    let beat_s = grid.step_s() * grid.subdivision as f64;
+    let mut out: Vec<Vec<f64>> = vec![Vec::new(); channels];
+    let mut end_s: Option<f64> = None;
+    let mut onsets = Vec::new();
+
+    for sentence in sentences {
+        for (phrase_idx, phrase) in sentence.iter().enumerate() {
+            for (word_idx, word) in phrase.iter().enumerate() {
+                let step = match end_s {
+                    None => 0,
+                    Some(end) if word_idx > 0 => grid.next_step(end),
+                    Some(end) if phrase_idx > 0 => grid.next_beat(end + grid.step_s()),
+                    Some(end) => grid.next_bar(end + beat_s),
+                };
+                let onset = grid.step_time(step);
+                let start = (onset * sr as f64).round() as usize;
+                onsets.push(start);
+                let len = word.first().map(|c| c.len()).unwrap_or(0);
+                for (ch, out_ch) in out.iter_mut().enumerate() {
+                    // Mono words feed every channel
+                    let src = &word[ch.min(word.len() - 1)];
+                    if out_ch.len() < start + len {
+                        out_ch.resize(start + len, 0.0);
+                    }
+                    for (o, s) in out_ch[start..start + len].iter_mut().zip(src) {
+                        *o += s;
+                    }
+                }
+                end_s = Some(onset + len as f64 / sr as f64);
+            }
+        }
+    }
+    (out, onsets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grid_steps_and_swing() {
+        let straight = BeatGrid::new(120.0, 2, 0.0);
+        assert!((straight.step_s() - 0.25).abs() < 1e-12);
+        assert!((straight.step_time(3) - 0.75).abs() < 1e-12);
+
+        let swung = BeatGrid::new(120.0, 2, 1.0 / 3.0);
+        // Off-beats land two thirds of the way through the beat
+        assert!((swung.step_time(1) - 1.0 / 3.0).abs() < 1e-9);
+        assert!((swung.step_time(2) - 0.5).abs() < 1e-12);
+        assert_eq!(swung.next_step(0.3), 1);
+        assert_eq!(swung.next_step(0.34), 2);
+    }
+
+    #[test]
+    fn test_next_beat_and_bar() {
+        let grid = BeatGrid::new(120.0, 4, 0.0);
+        assert_eq!(grid.next_step(0.0), 0);
+        assert_eq!(grid.next_beat(0.1), 4);
+        assert_eq!(grid.next_beat(0.5), 4);
+        assert_eq!(grid.next_bar(0.6), 16);
+        assert_eq!(grid.next_bar(2.0), 16);
+    }
+
+    #[test]
+    fn test_bpm_from_taps() {
+        assert_eq!(bpm_from_taps(&[1.0]), None);
+        let taps = [0.0, 0.5, 1.0, 1.52, 2.0];
+        assert!((bpm_from_taps(&taps).unwrap() - 120.0).abs() < 1.0);
+        // A long pause discards the earlier run
+        let taps = [0.0, 1.0, 5.0, 5.25, 5.5];
+        assert!((bpm_from_taps(&taps).unwrap() - 240.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_place_on_grid_quantizes_onsets() {
+        let sr = 1000;
+        let grid = BeatGrid::new(120.0, 2, 0.0); // 250 ms steps, 500 ms beats
+        let word = vec![vec![1.0; 100]];
+        let phrase1 = vec![word.clone(), word.clone()];
+        let phrase2 = vec![word.clone()];
+        let sentences = vec![
+            vec![phrase1.as_slice(), phrase2.as_slice()],
+            vec![phrase2.as_slice()],
+        ];
+        let (out, onsets) = place_on_grid(&sentences, &grid, sr);
+        let heard: Vec<usize> = (0..out[0].len())
+            .filter(|&i| out[0][i] > 0.0 && (i == 0 || out[0][i - 1] == 0.0))
+            .collect();
+        // word, next step, next beat after a rest, next bar after a rest
+        assert_eq!(heard, vec![0, 250, 1000, 2000]);
+        assert_eq!(onsets, heard);
+    }
+}
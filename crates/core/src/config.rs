@@ -0,0 +1,63 @@
+//! Glottisdale's config file, `<config>/config.toml`: settings that apply
+//! to every run, like the commands and webhook run after it.
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::hooks::HooksConfig;
+use crate::names::config_dir;
+use crate::webhook::WebhookConfig;
+
+/// Settings from the config file. Every table is optional.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+}
+
+/// Path of the config file.
+pub fn config_path() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+impl Config {
+    /// Load the config file. A missing file sets nothing.
+    pub fn load() -> Result<Self> {
+        let path = config_path();
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Self::parse(&text).with_context(|| format!("Invalid config: {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read config: {}", path.display())),
+        }
+    }
+
+    /// Parse and check a config file's text.
+    pub fn parse(text: &str) -> Result<Self> {
+        let config: Self = toml::from_str(text)?;
+        config.hooks.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            "[hooks]\non_complete = \"echo {output}\"\n\n[webhook]\nurl = \"https://hooks.slack.com/services/T/B/X\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.hooks.on_complete.as_deref(), Some("echo {output}"));
+        assert_eq!(config.webhook.url.as_deref(), Some("https://hooks.slack.com/services/T/B/X"));
+        assert!(config.webhook.upload);
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+        assert!(Config::parse("[hooks]\non_complete = \"echo {outptu}\"\n").is_err());
+        assert!(Config::parse("[hooks]\non_finish = \"echo\"\n").is_err());
+    }
+}
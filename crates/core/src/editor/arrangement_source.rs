@@ -0,0 +1,585 @@
+//! Pull-based arrangement playback: clips are rendered as the playhead
+//! reaches them instead of pre-rendering the whole arrangement.
+
+use std::time::Duration;
+
+use anyhow::Result;
+
+use super::clip_audio::ClipAudio;
+use super::master_chain::MasterChain;
+use super::render::{breath_schedule, place_clip, PlacedClip, RenderSettings};
+use super::automation::Automation;
+use super::types::{crossfade_gain, Arrangement, ClipEffect};
+use crate::audio::analysis::generate_pink_noise;
+use crate::audio::io::Channels;
+
+/// Frames rendered per pull from the audio callback.
+const BLOCK_SAMPLES: usize = 1024;
+
+/// A timeline clip waiting to be rendered.
+#[derive(Clone)]
+struct ScheduledClip {
+    /// Position in the timeline.
+    index: usize,
+    start: usize,
+    /// Estimated end sample from the clip's effective duration.
+    end: usize,
+    /// Fade-in and fade-out lengths from `Arrangement::clip_fades`.
+    fades: (usize, usize),
+    source: ClipAudio,
+    effects: Vec<ClipEffect>,
+    pan: f64,
+}
+
+/// A rendered clip still overlapping the playhead.
+#[derive(Clone)]
+struct ActiveClip {
+    fades: (usize, usize),
+    placed: PlacedClip,
+}
+
+/// Streaming render of an arrangement, usable as a rodio `Source`.
+///
+/// Plays in the arrangement's `output_layout`, so panned clips and clips of
+/// stereo sources are heard in stereo, with interleaved samples.
+/// Matches `render_arrangement_channels` for per-clip effects, automation, crossfades, prosodic
+/// dynamics, breaths, the noise bed, and master effects. Whole-mix stages are approximated:
+/// volume normalization uses the peak of the source clips, and room tone
+/// fills the gaps between clips. Global speed is not supported; check
+/// `can_stream` and fall back to a full render.
+#[derive(Clone)]
+pub struct ArrangementSource {
+    sr: u32,
+    layout: Channels,
+    clips: Vec<ScheduledClip>,
+    automation: Automation,
+    next_clip: usize,
+    active: Vec<ActiveClip>,
+    total_samples: usize,
+    /// Sample where the stream stops (`total_samples` unless limited).
+    end: usize,
+    pos: usize,
+    norm_gain: f64,
+    prosodic: bool,
+    room_tone: Vec<(usize, usize, usize)>,
+    room_tone_clips: Vec<Vec<f64>>,
+    breaths: Vec<(usize, usize)>,
+    breath_clips: Vec<Vec<f64>>,
+    noise_gain: Option<f64>,
+    noise_seed: Option<u64>,
+    /// One second of noise per output channel.
+    noise_block: Option<(usize, Vec<Vec<f64>>)>,
+    /// Master effects, fed one block at a time.
+    master: Option<MasterChain>,
+    /// Frames of master chain latency still to drop after a seek.
+    master_skip: usize,
+    /// Interleaved samples of the last rendered block.
+    buffer: Vec<f32>,
+    buffer_pos: usize,
+}
+
+impl ArrangementSource {
+    /// Whether these settings can be previewed by streaming.
+    ///
+    /// Global speed changes need the whole mix, so they require a full render.
+    pub fn can_stream(settings: &RenderSettings) -> bool {
+        settings.speed.is_none_or(|speed| (speed - 1.0).abs() <= 0.01)
+    }
+
+    /// Prepare a stream starting at `start_s` seconds into the arrangement.
+    ///
+    /// Only cheap scheduling happens here; audio is rendered on demand.
+    pub fn new(arrangement: &Arrangement, settings: &RenderSettings, start_s: f64) -> Result<Self> {
+        let sr = arrangement.sample_rate;
+        let layout = arrangement.output_layout();
+        let total_samples = (arrangement.total_duration_s() * sr as f64).ceil() as usize;
+
+        let cf_samples = (settings.crossfade_ms / 1000.0 * sr as f64).round() as usize;
+        let fades = arrangement.clip_fades(cf_samples);
+        let mut clips = Vec::with_capacity(arrangement.timeline.len());
+        for (index, tc) in arrangement.timeline.iter().enumerate() {
+            // Views share the bank clip's buffer, so this copies no audio
+            let source = arrangement
+                .get_bank_clip(tc.source_clip_id)
+                .map(|bank_clip| tc.trimmed_audio(bank_clip))
+                .ok_or_else(|| anyhow::anyhow!("Missing source clip in bank"))?;
+            let start = (tc.position_s * sr as f64).round() as usize;
+            clips.push(ScheduledClip {
+                index,
+                start,
+                end: start + (tc.effective_duration_s * sr as f64).ceil() as usize,
+                fades: fades[index],
+                source,
+                effects: tc.effects.clone(),
+                pan: tc.pan,
+            });
+        }
+        clips.sort_by_key(|c| c.start);
+
+        let norm_gain = if settings.volume_normalize {
+            let peak = clips
+                .iter()
+                .flat_map(|c| match layout {
+                    Channels::Mono => vec![c.source.clone()],
+                    Channels::Stereo => (0..c.source.channel_count()).map(|ch| c.source.channel(ch)).collect(),
+                })
+                .flat_map(|audio| audio.iter().collect::<Vec<_>>())
+                .map(|s| s.abs() as f64)
+                .fold(0.0f64, f64::max);
+            if peak > 1e-10 {
+                10.0f64.powf(-1.0 / 20.0) / peak
+            } else {
+                1.0
+            }
+        } else {
+            1.0
+        };
+
+        let room_tone = if settings.room_tone && !arrangement.room_tone_clips.is_empty() {
+            room_tone_schedule(arrangement, total_samples)
+        } else {
+            Vec::new()
+        };
+
+        let noise_gain = if settings.noise_level_db < -0.1 || settings.noise_level_db > 0.1 {
+            Some(10.0f64.powf(settings.noise_level_db / 20.0))
+        } else {
+            None
+        };
+
+        // A reverb tail plays on past the last clip, as in a full render
+        let master = Some(MasterChain::new(&arrangement.master_effects, layout.count(), sr, BLOCK_SAMPLES)?)
+            .filter(|chain| !chain.is_empty());
+        let tail = master.as_ref().map_or(0, MasterChain::tail);
+
+        let start = ((start_s.max(0.0) * sr as f64).round() as usize).min(total_samples);
+        let mut source = Self {
+            sr,
+            layout,
+            clips,
+            automation: arrangement.automation.clone(),
+            next_clip: 0,
+            active: Vec::new(),
+            total_samples,
+            end: total_samples + tail,
+            pos: start,
+            norm_gain,
+            prosodic: settings.prosodic_dynamics,
+            room_tone,
+            room_tone_clips: arrangement.room_tone_clips.clone(),
+            breaths: breath_schedule(arrangement, settings),
+            breath_clips: arrangement.breath_clips.clone(),
+            noise_gain,
+            noise_seed: settings.seed,
+            noise_block: None,
+            master,
+            master_skip: 0,
+            buffer: Vec::new(),
+            buffer_pos: 0,
+        };
+        source.skip_to(start);
+        Ok(source)
+    }
+
+    /// A copy of this stream that plays `start_s..end_s` of the arrangement.
+    ///
+    /// Rendering restarts from the new position, so loop passes never
+    /// carry audio over from the previous pass.
+    pub fn range(&self, start_s: f64, end_s: f64) -> Self {
+        let to_sample = |s: f64| ((s.max(0.0) * self.sr as f64).round() as usize).min(self.total_samples);
+        let mut source = self.clone();
+        source.end = to_sample(end_s);
+        source.skip_to(to_sample(start_s).min(source.end));
+        source
+    }
+
+    /// Reset the playhead to `start` without rendering skipped clips.
+    fn skip_to(&mut self, start: usize) {
+        self.pos = start;
+        self.next_clip = 0;
+        self.active.clear();
+        self.buffer.clear();
+        self.buffer_pos = 0;
+        if let Some(master) = &mut self.master {
+            master.reset();
+            self.master_skip = master.latency();
+        }
+        while self.next_clip < self.clips.len() && self.clips[self.next_clip].end <= start {
+            self.next_clip += 1;
+        }
+    }
+
+    /// Frames left to play.
+    pub fn remaining_samples(&self) -> usize {
+        self.render_end().saturating_sub(self.pos).saturating_sub(self.master_skip)
+            + (self.buffer.len() - self.buffer_pos) / self.layout.count()
+    }
+
+    /// Where rendering stops: `end`, plus the master chain's latency so
+    /// its delayed output is flushed.
+    fn render_end(&self) -> usize {
+        self.end + self.master.as_ref().map_or(0, MasterChain::latency)
+    }
+
+    /// Render the next block into `buffer`. Returns false at the end.
+    fn render_block(&mut self) -> bool {
+        let render_end = self.render_end();
+        if self.pos >= render_end {
+            return false;
+        }
+        let block_start = self.pos;
+        let block_end = (block_start + BLOCK_SAMPLES).min(render_end);
+        let mut block = vec![vec![0.0f64; block_end - block_start]; self.layout.count()];
+
+        // Render clips that start inside this block
+        while self.next_clip < self.clips.len() && self.clips[self.next_clip].start < block_end {
+            let clip = &self.clips[self.next_clip];
+            let placed = place_clip(
+                &clip.source,
+                &clip.effects,
+                &self.automation,
+                clip.pan,
+                self.sr,
+                clip.start,
+                self.layout,
+            )
+            .unwrap_or_else(|e| {
+                log::warn!("Streaming render failed for clip {}: {}", clip.index, e);
+                PlacedClip { start: clip.start, channels: vec![Vec::new()], gains: vec![0.0; self.layout.count()] }
+            });
+            self.active.push(ActiveClip { fades: clip.fades, placed });
+            self.next_clip += 1;
+        }
+
+        // Overlap-add with the same crossfade rules as `render_arrangement`
+        for clip in &self.active {
+            let (start, len) = (clip.placed.start, clip.placed.len());
+            let from = block_start.max(start);
+            let to = block_end.min(start + len);
+            for out_idx in from..to {
+                let i = out_idx - start;
+                let gain = crossfade_gain(i, len, clip.fades);
+                for (ch, channel) in block.iter_mut().enumerate() {
+                    channel[out_idx - block_start] += clip.placed.sample(ch, i) * gain;
+                }
+            }
+        }
+        self.active.retain(|c| c.placed.start + c.placed.len() > block_end);
+
+        // Normalization and prosodic envelope
+        let total = self.total_samples;
+        let dynamics = self.prosodic && total as f64 / self.sr as f64 > 0.3;
+        let boost_end = (total as f64 * 0.2) as usize;
+        let fade_start = (total as f64 * 0.7) as usize;
+        let boost_ratio = 10.0f64.powf(1.12 / 20.0);
+        let fade_ratio = 10.0f64.powf(-3.0 / 20.0);
+        for channel in block.iter_mut() {
+            for (k, s) in channel.iter_mut().enumerate() {
+                *s *= self.norm_gain;
+                if dynamics {
+                    let t = block_start + k;
+                    if t < boost_end {
+                        *s *= boost_ratio;
+                    }
+                    if t >= fade_start {
+                        *s *= fade_ratio;
+                    }
+                }
+            }
+        }
+
+        // Room tone in gaps, breaths at boundaries
+        for channel in block.iter_mut() {
+            for &(start, len, rt_idx) in &self.room_tone {
+                add_overlap(channel, block_start, start, &self.room_tone_clips[rt_idx][..len], 0.3);
+            }
+            for &(start, breath_idx) in &self.breaths {
+                add_overlap(channel, block_start, start, &self.breath_clips[breath_idx], 0.5);
+            }
+        }
+
+        // Pink noise bed, generated one second at a time
+        if let Some(noise_gain) = self.noise_gain {
+            let sr = self.sr as usize;
+            for k in 0..block_end - block_start {
+                let t = block_start + k;
+                let second = t / sr;
+                if self.noise_block.as_ref().is_none_or(|(b, _)| *b != second) {
+                    let noise = (0..block.len())
+                        .map(|ch| {
+                            let seed = self.noise_seed.map(|s| s.wrapping_add(second as u64).wrapping_add((ch as u64) << 32));
+                            generate_pink_noise(1.0, self.sr, seed)
+                        })
+                        .collect();
+                    self.noise_block = Some((second, noise));
+                }
+                if let Some((_, noise)) = &self.noise_block {
+                    for (channel, noise) in block.iter_mut().zip(noise) {
+                        channel[k] += noise[t % sr] * noise_gain;
+                    }
+                }
+            }
+        }
+
+        // Master effects over the dry mix, which ends with the arrangement
+        if let Some(master) = &mut self.master {
+            let dry_len = total.saturating_sub(block_start).min(block_end - block_start);
+            for channel in block.iter_mut() {
+                channel[dry_len..].fill(0.0);
+            }
+            match master.process(block.clone()) {
+                Ok(processed) => block = processed,
+                Err(e) => log::warn!("Streaming master effects failed: {}", e),
+            }
+            let skip = self.master_skip.min(block.first().map_or(0, |c| c.len()));
+            for channel in block.iter_mut() {
+                channel.drain(..skip);
+            }
+            self.master_skip -= skip;
+        }
+
+        let frames = block.first().map_or(0, |c| c.len());
+        self.buffer = (0..frames)
+            .flat_map(|i| block.iter().map(move |channel| channel[i] as f32))
+            .collect();
+        self.buffer_pos = 0;
+        self.pos = block_end;
+        true
+    }
+}
+
+/// Add `gain * clip` placed at `clip_start` into a block starting at `block_start`.
+fn add_overlap(block: &mut [f64], block_start: usize, clip_start: usize, clip: &[f64], gain: f64) {
+    let block_end = block_start + block.len();
+    let from = block_start.max(clip_start);
+    let to = block_end.min(clip_start + clip.len());
+    for out_idx in from..to {
+        block[out_idx - block_start] += clip[out_idx - clip_start] * gain;
+    }
+}
+
+/// Place room tone clips in the gaps between timeline clips.
+///
+/// Returns `(start sample, length, room tone clip index)` entries. Gaps
+/// shorter than 25ms are left silent.
+fn room_tone_schedule(arrangement: &Arrangement, total_samples: usize) -> Vec<(usize, usize, usize)> {
+    let sr = arrangement.sample_rate as f64;
+    let window = (sr * 0.025) as usize;
+    let mut covered: Vec<(usize, usize)> = arrangement
+        .timeline
+        .iter()
+        .map(|tc| {
+            let start = (tc.position_s * sr).round() as usize;
+            let end = ((tc.position_s + tc.effective_duration_s) * sr).round() as usize;
+            (start, end)
+        })
+        .collect();
+    covered.sort();
+
+    let mut gaps = Vec::new();
+    let mut cursor = 0usize;
+    for (start, end) in covered {
+        if start > cursor {
+            gaps.push((cursor, start));
+        }
+        cursor = cursor.max(end);
+    }
+    if total_samples > cursor {
+        gaps.push((cursor, total_samples));
+    }
+
+    let clips = &arrangement.room_tone_clips;
+    let mut schedule = Vec::new();
+    let mut rt_idx = 0;
+    for (gap_start, gap_end) in gaps {
+        let mut i = gap_start;
+        while i + window < gap_end {
+            let idx = rt_idx % clips.len();
+            let len = clips[idx].len().min(gap_end - i);
+            schedule.push((i, len, idx));
+            rt_idx += 1;
+            i += clips[idx].len().max(window);
+        }
+    }
+    schedule
+}
+
+impl Iterator for ArrangementSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        // A block can come out empty while the master chain fills its lookahead
+        while self.buffer_pos >= self.buffer.len() {
+            if !self.render_block() {
+                return None;
+            }
+        }
+        let sample = self.buffer[self.buffer_pos];
+        self.buffer_pos += 1;
+        Some(sample)
+    }
+}
+
+impl rodio::Source for ArrangementSource {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.layout.count() as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sr
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs_f64(
+            self.remaining_samples() as f64 / self.sr as f64,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::clip_audio::SourceBuffer;
+    use crate::editor::render::render_arrangement_channels;
+    use crate::editor::types::*;
+    use crate::types::{Phoneme, Syllable};
+
+    fn make_clip(value: f64, duration_samples: usize) -> SyllableClip {
+        let syl = Syllable {
+            phonemes: vec![Phoneme {
+                label: "AH0".into(),
+                start: 0.0,
+                end: duration_samples as f64 / 16000.0,
+            }],
+            start: 0.0,
+            end: duration_samples as f64 / 16000.0,
+            word: "test".into(),
+            word_index: 0,
+        };
+        SyllableClip::new(syl, vec![value as f32; duration_samples].into(), 16000, "test.wav".into())
+    }
+
+    fn two_clip_arrangement(crossfade_ms: f64) -> Arrangement {
+        let clip1 = make_clip(0.3, 8000);
+        let clip2 = make_clip(0.6, 8000);
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.timeline.push(TimelineClip::new(&clip1));
+        arr.timeline.push(TimelineClip::new(&clip2));
+        arr.bank.push(clip1);
+        arr.bank.push(clip2);
+        arr.relayout_with_crossfade(crossfade_ms);
+        arr
+    }
+
+    fn assert_matches_render(arr: &Arrangement, settings: &RenderSettings) {
+        let layout = arr.output_layout();
+        let channels = render_arrangement_channels(arr, settings, layout).unwrap();
+        let rendered: Vec<f64> = (0..channels[0].len())
+            .flat_map(|i| channels.iter().map(move |c| c[i]))
+            .collect();
+        let streamed: Vec<f32> = ArrangementSource::new(arr, settings, 0.0).unwrap().collect();
+        assert_eq!(streamed.len(), rendered.len());
+        for (i, (a, b)) in streamed.iter().zip(&rendered).enumerate() {
+            assert!((*a as f64 - b).abs() < 1e-4, "sample {}: {} vs {}", i, a, b);
+        }
+    }
+
+    #[test]
+    fn test_stream_matches_render_bypass() {
+        assert_matches_render(&two_clip_arrangement(0.0), &RenderSettings::bypass());
+    }
+
+    #[test]
+    fn test_stream_matches_render_crossfade_and_dynamics() {
+        let mut settings = RenderSettings::bypass();
+        settings.crossfade_ms = 30.0;
+        settings.prosodic_dynamics = true;
+        assert_matches_render(&two_clip_arrangement(30.0), &settings);
+    }
+
+    #[test]
+    fn test_stream_matches_render_free_layout() {
+        let mut arr = two_clip_arrangement(0.0);
+        arr.layout_mode = LayoutMode::Free;
+        // Second clip overlaps the first by 0.05s, then a gap before a repeat
+        arr.timeline[1].position_s = arr.timeline[0].effective_duration_s - 0.05;
+        let mut repeat = arr.timeline[0].clone();
+        repeat.position_s = 1.5;
+        arr.timeline.push(repeat);
+        arr.relayout(0.0);
+        let mut settings = RenderSettings::bypass();
+        settings.crossfade_ms = 30.0;
+        assert_matches_render(&arr, &settings);
+    }
+
+    #[test]
+    fn test_stream_matches_render_master_effects() {
+        let mut arr = two_clip_arrangement(0.0);
+        arr.master_effects = vec![
+            MasterEffect::Eq { low_db: 4.0, mid_db: -2.0, high_db: 1.0 },
+            MasterEffect::Reverb { ir: crate::audio::effects::ReverbIr::Spring, send: 0.4 },
+            MasterEffect::Gain { gain_db: 6.0 },
+            MasterEffect::Limiter { ceiling_db: -2.0 },
+        ];
+        let mut settings = RenderSettings::bypass();
+        settings.prosodic_dynamics = true;
+        assert_matches_render(&arr, &settings);
+    }
+
+    #[test]
+    fn test_stream_matches_render_stereo() {
+        let mut arr = two_clip_arrangement(30.0);
+        arr.timeline[1].pan = -0.7;
+        let stereo = SyllableClip::new(
+            arr.bank[0].syllable.clone(),
+            ClipAudio::cut(&SourceBuffer::from_channels(vec![vec![0.5; 8000], vec![-0.2; 8000]]), 16000, 0.0, 0.5, 0.0, 0.0),
+            16000,
+            "stereo.wav".into(),
+        );
+        let mut tc = TimelineClip::new(&stereo);
+        tc.pan = 0.4;
+        arr.timeline.push(tc);
+        arr.bank.push(stereo);
+        arr.relayout_with_crossfade(30.0);
+        assert_eq!(arr.output_layout(), Channels::Stereo);
+        let mut settings = RenderSettings::bypass();
+        settings.crossfade_ms = 30.0;
+        assert_matches_render(&arr, &settings);
+    }
+
+    #[test]
+    fn test_stream_starts_at_cursor() {
+        let arr = two_clip_arrangement(0.0);
+        let source = ArrangementSource::new(&arr, &RenderSettings::bypass(), 0.5).unwrap();
+        assert_eq!(source.remaining_samples(), 8000);
+        let streamed: Vec<f32> = source.collect();
+        assert!((streamed[0] - 0.6).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_range_plays_only_the_region() {
+        let arr = two_clip_arrangement(0.0);
+        let source = ArrangementSource::new(&arr, &RenderSettings::bypass(), 0.0).unwrap();
+        // A region straddling the clip boundary, taken twice like loop passes
+        for _ in 0..2 {
+            let pass: Vec<f32> = source.range(0.25, 0.75).collect();
+            assert_eq!(pass.len(), 8000);
+            assert!((pass[0] - 0.3).abs() < 1e-4);
+            assert!((pass[7999] - 0.6).abs() < 1e-4);
+        }
+        assert_eq!(source.range(0.9, 5.0).remaining_samples(), 1600);
+        assert_eq!(source.range(0.8, 0.2).remaining_samples(), 0);
+    }
+
+    #[test]
+    fn test_can_stream_rejects_speed() {
+        let mut settings = RenderSettings::bypass();
+        assert!(ArrangementSource::can_stream(&settings));
+        settings.speed = Some(1.5);
+        assert!(!ArrangementSource::can_stream(&settings));
+    }
+}
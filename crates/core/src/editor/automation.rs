@@ -0,0 +1,191 @@
+//! Automation lanes: breakpoint envelopes of gain and pitch over the
+//! arrangement timeline, for sweeps and fades spanning many clips.
+//!
+//! A lane's value between two breakpoints is interpolated linearly and
+//! held flat before the first and after the last. Gain is applied per
+//! sample as the clip plays; pitch is applied per clip, shifting each clip
+//! by the lane's value at its middle.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::audio::effects::pitch_shift;
+use crate::audio::sample::Sample;
+
+/// What an automation lane controls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutomationParam {
+    /// Clip gain in dB.
+    Gain,
+    /// Clip pitch shift in semitones.
+    Pitch,
+}
+
+impl AutomationParam {
+    pub const ALL: [AutomationParam; 2] = [AutomationParam::Gain, AutomationParam::Pitch];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            AutomationParam::Gain => "Gain",
+            AutomationParam::Pitch => "Pitch",
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            AutomationParam::Gain => "dB",
+            AutomationParam::Pitch => "st",
+        }
+    }
+
+    /// Lowest and highest value a breakpoint can take.
+    pub fn range(self) -> (f64, f64) {
+        match self {
+            AutomationParam::Gain => (-36.0, 12.0),
+            AutomationParam::Pitch => (-12.0, 12.0),
+        }
+    }
+}
+
+/// A breakpoint: the lane's value at a time on the timeline.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AutomationPoint {
+    pub time_s: f64,
+    pub value: f64,
+}
+
+/// A lane's breakpoints, sorted by time. Empty lanes leave audio as is.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Envelope {
+    pub points: Vec<AutomationPoint>,
+}
+
+impl Envelope {
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// The lane's value at `time_s`, or None for an empty lane.
+    pub fn value_at(&self, time_s: f64) -> Option<f64> {
+        let after = self.points.partition_point(|p| p.time_s <= time_s);
+        match (after.checked_sub(1).map(|i| self.points[i]), self.points.get(after)) {
+            (Some(a), Some(b)) if b.time_s > a.time_s => {
+                let t = (time_s - a.time_s) / (b.time_s - a.time_s);
+                Some(a.value + (b.value - a.value) * t)
+            }
+            (Some(a), _) => Some(a.value),
+            (None, b) => b.map(|b| b.value),
+        }
+    }
+
+    /// Add a breakpoint, clamped to `param`'s range. Returns its index.
+    pub fn insert(&mut self, param: AutomationParam, time_s: f64, value: f64) -> usize {
+        let (min, max) = param.range();
+        let point = AutomationPoint { time_s: time_s.max(0.0), value: value.clamp(min, max) };
+        let index = self.points.partition_point(|p| p.time_s <= point.time_s);
+        self.points.insert(index, point);
+        index
+    }
+
+    /// Move breakpoint `index`, keeping the lane sorted. Returns its new
+    /// index, or None when there is no such breakpoint.
+    pub fn move_point(&mut self, param: AutomationParam, index: usize, time_s: f64, value: f64) -> Option<usize> {
+        (index < self.points.len()).then(|| {
+            self.points.remove(index);
+            self.insert(param, time_s, value)
+        })
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<AutomationPoint> {
+        (index < self.points.len()).then(|| self.points.remove(index))
+    }
+}
+
+/// The arrangement's automation lanes.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Automation {
+    #[serde(default)]
+    pub gain: Envelope,
+    #[serde(default)]
+    pub pitch: Envelope,
+}
+
+impl Automation {
+    pub fn lane(&self, param: AutomationParam) -> &Envelope {
+        match param {
+            AutomationParam::Gain => &self.gain,
+            AutomationParam::Pitch => &self.pitch,
+        }
+    }
+
+    pub fn lane_mut(&mut self, param: AutomationParam) -> &mut Envelope {
+        match param {
+            AutomationParam::Gain => &mut self.gain,
+            AutomationParam::Pitch => &mut self.pitch,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.gain.is_empty() && self.pitch.is_empty()
+    }
+
+    /// Bake the lanes into a clip's audio (after its own effects) that
+    /// starts at sample `start` of the timeline.
+    pub fn apply_to_clip<S: Sample>(&self, mut samples: Vec<S>, sr: u32, start: usize) -> Result<Vec<S>> {
+        let time_of = |i: usize| (start + i) as f64 / sr as f64;
+        if let Some(semitones) = self.pitch.value_at(time_of(samples.len() / 2)) {
+            samples = pitch_shift(&samples, sr, semitones)?;
+        }
+        if !self.gain.is_empty() {
+            for (i, s) in samples.iter_mut().enumerate() {
+                let gain_db = self.gain.value_at(time_of(i)).unwrap_or(0.0);
+                *s = S::from_f64(s.to_f64() * 10f64.powf(gain_db / 20.0));
+            }
+        }
+        Ok(samples)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lane(points: &[(f64, f64)]) -> Envelope {
+        Envelope { points: points.iter().map(|&(time_s, value)| AutomationPoint { time_s, value }).collect() }
+    }
+
+    #[test]
+    fn test_value_at_interpolates_and_holds_ends() {
+        assert_eq!(Envelope::default().value_at(1.0), None);
+        let env = lane(&[(1.0, -12.0), (3.0, 0.0)]);
+        assert_eq!(env.value_at(0.0), Some(-12.0));
+        assert_eq!(env.value_at(2.0), Some(-6.0));
+        assert_eq!(env.value_at(3.0), Some(0.0));
+        assert_eq!(env.value_at(10.0), Some(0.0));
+        // A vertical step takes the later value
+        assert_eq!(lane(&[(1.0, 0.0), (1.0, 5.0)]).value_at(1.0), Some(5.0));
+    }
+
+    #[test]
+    fn test_insert_move_remove_keep_lane_sorted() {
+        let mut env = Envelope::default();
+        assert_eq!(env.insert(AutomationParam::Pitch, 2.0, 30.0), 0);
+        assert_eq!(env.points[0].value, 12.0);
+        assert_eq!(env.insert(AutomationParam::Pitch, 1.0, -3.0), 0);
+        assert_eq!(env.move_point(AutomationParam::Pitch, 0, 4.0, -3.0), Some(1));
+        assert_eq!(env.points.iter().map(|p| p.time_s).collect::<Vec<_>>(), vec![2.0, 4.0]);
+        assert_eq!(env.move_point(AutomationParam::Pitch, 5, 0.0, 0.0), None);
+        assert_eq!(env.remove(0).map(|p| p.time_s), Some(2.0));
+        assert_eq!(env.remove(3), None);
+    }
+
+    #[test]
+    fn test_apply_to_clip_bakes_gain_sweep() {
+        let automation = Automation { gain: lane(&[(0.0, 0.0), (1.0, -20.0)]), ..Default::default() };
+        let out = automation.apply_to_clip(vec![1.0f64; 10], 10, 5).unwrap();
+        assert!((out[0] - 10f64.powf(-10.0 / 20.0)).abs() < 1e-9);
+        assert!((out[9] - 0.1).abs() < 1e-9);
+        assert_eq!(Automation::default().apply_to_clip(vec![0.5f32; 4], 10, 0).unwrap(), vec![0.5; 4]);
+    }
+}
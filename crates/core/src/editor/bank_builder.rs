@@ -1,59 +1,166 @@
-//! Build a syllable bank from aligned source audio.
+//! Build a syllable bank from aligned source audio, and search it.
 
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use anyhow::Result;
+use anyhow::{Result, bail};
 
+use super::clip_audio::{ClipAudio, SourceBuffer};
 use super::types::SyllableClip;
-use crate::audio::analysis::{find_breaths, find_room_tone};
+use crate::audio::analysis::{find_breaths, find_room_tone, syllable_quality};
 use crate::audio::effects::cut_clip;
+use crate::audio::io::{mix_to_mono, read_wav_channels, resample};
+use crate::audio::sample::convert;
+use crate::source::SourceId;
 use crate::types::Syllable;
 
+/// Longest one-shot sample accepted by `import_sample` (seconds).
+pub const MAX_SAMPLE_S: f64 = 30.0;
+
 /// Build SyllableClips from aligned syllables and their source audio.
 ///
 /// For each syllable, cuts the audio with 25ms padding and 5ms fade,
-/// computes waveform data, and creates a SyllableClip.
+/// computes waveform data, and creates a SyllableClip. Source audio is
+/// planar; clips from the same source share one `f32` copy of it, and
+/// clips of stereo sources keep both channels.
 pub fn build_bank_from_syllables(
     syllables: &[(Syllable, PathBuf)],
-    source_audio: &std::collections::HashMap<PathBuf, (Vec<f64>, u32)>,
+    source_audio: &HashMap<PathBuf, (Vec<Vec<f64>>, u32)>,
 ) -> Result<Vec<SyllableClip>> {
     let mut bank = Vec::with_capacity(syllables.len());
+    let mut buffers: HashMap<&Path, (SourceBuffer, SourceId)> = HashMap::new();
 
     for (syllable, source_path) in syllables {
-        let (samples, sr) = source_audio
+        let (channels, sr) = source_audio
             .get(source_path)
             .ok_or_else(|| anyhow::anyhow!("Source audio not found: {}", source_path.display()))?;
+        let (buffer, source) = buffers
+            .entry(source_path.as_path())
+            .or_insert_with(|| (shared_source_channels(channels), SourceId::intern(source_path)));
 
-        let clip_samples = cut_clip(samples, *sr, syllable.start, syllable.end, 25.0, 5.0);
-
-        if clip_samples.is_empty() {
-            continue;
+        let clip = aligned_clip(buffer, *sr, syllable, *source);
+        if !clip.samples.is_empty() {
+            bank.push(clip);
         }
-
-        bank.push(SyllableClip::new(
-            syllable.clone(),
-            clip_samples,
-            *sr,
-            source_path.clone(),
-        ));
     }
 
     Ok(bank)
 }
 
+/// Build SyllableClips for syllables from a single source.
+///
+/// Used to stream partial alignment results into a bank; skips syllables
+/// whose audio is empty. `source` is shared, not copied, so convert it with
+/// [`shared_source`] once per file rather than once per batch.
+pub fn clips_from_source(
+    syllables: &[Syllable],
+    source: &SourceBuffer,
+    sr: u32,
+    source_path: &std::path::Path,
+) -> Vec<SyllableClip> {
+    let source_id = SourceId::intern(source_path);
+    syllables
+        .iter()
+        .map(|syllable| aligned_clip(source, sr, syllable, source_id))
+        .filter(|clip| !clip.samples.is_empty())
+        .collect()
+}
+
+/// Convert mono source audio to the `f32` buffer its bank clips share.
+pub fn shared_source(samples: &[f64]) -> SourceBuffer {
+    SourceBuffer::mono(convert::<f64, f32>(samples).into())
+}
+
+/// Convert planar source audio to the buffers its bank clips share,
+/// keeping the channels of a stereo source.
+pub fn shared_source_channels(channels: &[Vec<f64>]) -> SourceBuffer {
+    SourceBuffer::from_channels(channels.iter().map(|c| convert::<f64, f32>(c)).collect())
+}
+
+/// Cut a syllable's bank audio: 25ms padding and 5ms fade.
+pub(crate) fn cut_syllable_audio(source: &SourceBuffer, sr: u32, syllable: &Syllable) -> ClipAudio {
+    ClipAudio::cut(source, sr, syllable.start, syllable.end, 25.0, 5.0)
+}
+
+/// Cut an aligned syllable's bank clip and score its audio quality.
+pub(crate) fn aligned_clip(source: &SourceBuffer, sr: u32, syllable: &Syllable, source_id: SourceId) -> SyllableClip {
+    let mut clip = SyllableClip::new(syllable.clone(), cut_syllable_audio(source, sr, syllable), sr, source_id);
+    clip.quality = Some(syllable_quality(source.samples(), sr, syllable));
+    clip
+}
+
+/// Cut an exact source range for a manual clip: no padding, 5ms fade.
+pub(crate) fn cut_range_audio(source: &SourceBuffer, sr: u32, start_s: f64, end_s: f64) -> ClipAudio {
+    ClipAudio::cut(source, sr, start_s, end_s, 0.0, 5.0)
+}
+
+/// Import a one-shot WAV (drum hit, FX) as an unaligned bank clip.
+///
+/// Stereo files stay stereo; files are resampled to `sample_rate`.
+/// Rejects files longer than `MAX_SAMPLE_S`.
+pub fn import_sample(path: &Path, label: &str, sample_rate: u32) -> Result<SyllableClip> {
+    let channels = read_source_channels(path, sample_rate)?;
+    let frames = channels.first().map_or(0, |c| c.len());
+    if frames == 0 {
+        bail!("Sample is empty: {}", path.display());
+    }
+    let duration = frames as f64 / sample_rate as f64;
+    if duration > MAX_SAMPLE_S {
+        bail!(
+            "Sample is {:.1}s long; imports are limited to {:.0}s",
+            duration,
+            MAX_SAMPLE_S
+        );
+    }
+    Ok(SyllableClip::from_range(
+        &shared_source_channels(&channels),
+        sample_rate,
+        SourceId::intern(path),
+        0.0,
+        duration,
+        label,
+    ))
+}
+
+/// Read a WAV's channels at `sample_rate`, resampling as needed.
+///
+/// Mono and stereo files keep their layout; more channels are mixed to mono.
+pub(crate) fn read_source_channels(path: &Path, sample_rate: u32) -> Result<Vec<Vec<f64>>> {
+    let (mut channels, sr) = read_wav_channels(path)?;
+    if channels.len() > 2 {
+        channels = vec![mix_to_mono(&channels)];
+    }
+    if sr == sample_rate {
+        Ok(channels)
+    } else {
+        channels.iter().map(|c| resample(c, sr, sample_rate)).collect()
+    }
+}
+
 /// Build bank and extract room tone + breath clips from source audio.
 ///
 /// Returns `(bank, room_tone_clips, breath_clips)`.
 pub fn build_bank_with_context(
     syllables: &[(Syllable, PathBuf)],
-    source_audio: &std::collections::HashMap<PathBuf, (Vec<f64>, u32)>,
+    source_audio: &HashMap<PathBuf, (Vec<Vec<f64>>, u32)>,
 ) -> Result<(Vec<SyllableClip>, Vec<Vec<f64>>, Vec<Vec<f64>>)> {
     let bank = build_bank_from_syllables(syllables, source_audio)?;
+    let (room_tone_clips, breath_clips) = extract_context_clips(syllables, source_audio);
+    Ok((bank, room_tone_clips, breath_clips))
+}
 
+/// Extract mono room tone and breath clips from planar source audio.
+///
+/// Returns `(room_tone_clips, breath_clips)`.
+pub fn extract_context_clips(
+    syllables: &[(Syllable, PathBuf)],
+    source_audio: &HashMap<PathBuf, (Vec<Vec<f64>>, u32)>,
+) -> (Vec<Vec<f64>>, Vec<Vec<f64>>) {
     let mut room_tone_clips = Vec::new();
     let mut breath_clips = Vec::new();
 
-    for (path, (samples, sr)) in source_audio {
+    for (path, (channels, sr)) in source_audio {
+        let samples = &mix_to_mono(channels);
         // Extract room tone (quietest region >= 100ms)
         if let Some((start, end)) = find_room_tone(samples, *sr, 100) {
             let start_idx = (start * *sr as f64).round() as usize;
@@ -79,7 +186,127 @@ pub fn build_bank_with_context(
         }
     }
 
-    Ok((bank, room_tone_clips, breath_clips))
+    (room_tone_clips, breath_clips)
+}
+
+/// How a `dur:` term compares a clip's duration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    /// Within 5 ms, as typed durations are rounded.
+    About,
+}
+
+/// One term of a [`BankQuery`].
+#[derive(Debug, Clone, PartialEq)]
+enum QueryTerm {
+    /// Plain text found in the clip's label or word.
+    Text(String),
+    /// `ph:AE+T`: the syllable has these phonemes in a row. A phoneme
+    /// typed without a stress digit matches any stress.
+    Phonemes(Vec<String>),
+    /// `stress:1`: a vowel in the syllable carries this stress.
+    Stress(char),
+    /// `dur:>0.3`: the clip's duration in seconds.
+    Duration(Comparison, f64),
+}
+
+/// A bank filter: whitespace-separated terms, all of which must match.
+///
+/// Besides plain text, terms can find clips by sound: `ph:AE+T` (phoneme
+/// sequence), `stress:1` (0, 1, or 2), and `dur:>0.3` (seconds, with `<`,
+/// `<=`, `>`, `>=`, or `=`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BankQuery {
+    terms: Vec<QueryTerm>,
+}
+
+impl BankQuery {
+    pub fn parse(query: &str) -> Result<Self> {
+        let terms = query.split_whitespace().map(parse_term).collect::<Result<_>>()?;
+        Ok(Self { terms })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, clip: &SyllableClip) -> bool {
+        self.terms.iter().all(|term| term_matches(term, clip))
+    }
+}
+
+fn parse_term(term: &str) -> Result<QueryTerm> {
+    let Some((key, value)) = term.split_once(':') else {
+        return Ok(QueryTerm::Text(term.to_lowercase()));
+    };
+    match key.to_ascii_lowercase().as_str() {
+        "ph" => {
+            let phonemes: Vec<String> = value.split('+').map(|p| p.trim().to_ascii_uppercase()).collect();
+            if phonemes.iter().any(|p| p.is_empty()) {
+                bail!("'{}' needs phonemes joined by +, like ph:AE+T", term);
+            }
+            Ok(QueryTerm::Phonemes(phonemes))
+        }
+        "stress" => match value {
+            "0" | "1" | "2" => Ok(QueryTerm::Stress(value.chars().next().unwrap_or('0'))),
+            _ => bail!("Unknown stress '{}' (expected 0, 1, or 2)", value),
+        },
+        "dur" => {
+            let (comparison, number) = [
+                ("<=", Comparison::LessOrEqual),
+                (">=", Comparison::GreaterOrEqual),
+                ("<", Comparison::Less),
+                (">", Comparison::Greater),
+                ("=", Comparison::About),
+            ]
+            .into_iter()
+            .find_map(|(op, comparison)| value.strip_prefix(op).map(|rest| (comparison, rest)))
+            .unwrap_or((Comparison::About, value));
+            match number.trim_end_matches('s').parse::<f64>() {
+                Ok(seconds) if seconds >= 0.0 => Ok(QueryTerm::Duration(comparison, seconds)),
+                _ => bail!("'{}' needs a duration in seconds, like dur:>0.3", term),
+            }
+        }
+        _ => bail!("Unknown filter '{}:' (expected ph:, stress:, or dur:)", key),
+    }
+}
+
+/// Whether an aligned phoneme label matches a typed one, ignoring the
+/// label's stress digit when none was typed.
+fn phoneme_matches(label: &str, typed: &str) -> bool {
+    let label = label.to_ascii_uppercase();
+    if typed.ends_with(|c: char| c.is_ascii_digit()) {
+        label == typed
+    } else {
+        label.trim_end_matches(|c: char| c.is_ascii_digit()) == typed
+    }
+}
+
+fn term_matches(term: &QueryTerm, clip: &SyllableClip) -> bool {
+    let phonemes = &clip.syllable.phonemes;
+    match term {
+        QueryTerm::Text(text) => {
+            clip.label.to_lowercase().contains(text) || clip.syllable.word.to_lowercase().contains(text)
+        }
+        QueryTerm::Phonemes(typed) => phonemes
+            .windows(typed.len())
+            .any(|run| run.iter().zip(typed).all(|(p, t)| phoneme_matches(&p.label, t))),
+        QueryTerm::Stress(stress) => phonemes.iter().any(|p| p.label.ends_with(*stress)),
+        QueryTerm::Duration(comparison, seconds) => {
+            let duration = clip.duration_s();
+            match comparison {
+                Comparison::Less => duration < *seconds,
+                Comparison::LessOrEqual => duration <= *seconds,
+                Comparison::Greater => duration > *seconds,
+                Comparison::GreaterOrEqual => duration >= *seconds,
+                Comparison::About => (duration - seconds).abs() <= 0.005,
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -107,7 +334,7 @@ mod tests {
         let path = PathBuf::from("test.wav");
         let samples = vec![0.5f64; 16000]; // 1 second
         let mut source_audio = HashMap::new();
-        source_audio.insert(path.clone(), (samples, 16000u32));
+        source_audio.insert(path.clone(), (vec![samples], 16000u32));
 
         let syllables = vec![
             (make_syllable(0.0, 0.3, "hello"), path.clone()),
@@ -119,6 +346,51 @@ mod tests {
         assert!(!bank[0].samples.is_empty());
         assert!(!bank[1].samples.is_empty());
         assert!(!bank[0].waveform.peaks.is_empty());
+        // Clips are views of one shared source buffer
+        assert!(bank[0].samples.shares_buffer(&bank[1].samples));
+        // Aligned clips carry a quality score; a 0.5 DC offset is loud and steady
+        assert!(bank.iter().all(|c| c.quality.is_some_and(|q| q.level == 1.0 && q.clipping == 1.0)));
+    }
+
+    #[test]
+    fn test_bank_query() {
+        let phoneme = |label: &str| Phoneme { label: label.into(), start: 0.0, end: 0.1 };
+        let syllable = Syllable {
+            phonemes: vec![phoneme("K"), phoneme("AE1"), phoneme("T")],
+            start: 0.0,
+            end: 0.4,
+            word: "Cat".into(),
+            word_index: 0,
+        };
+        let clip = SyllableClip::new(syllable, vec![0.0f32; 6400].into(), 16000, "test.wav".into());
+        let matches = |query: &str| BankQuery::parse(query).unwrap().matches(&clip);
+
+        assert!(BankQuery::parse("  ").unwrap().is_empty());
+        assert!(matches("cat"));
+        assert!(matches("ae1 t"));
+        assert!(matches("ph:AE+T"));
+        assert!(matches("ph:k+ae1"));
+        assert!(!matches("ph:AE0"));
+        assert!(!matches("ph:K+T"));
+        assert!(matches("stress:1"));
+        assert!(!matches("stress:2"));
+        assert!(matches("dur:>0.3 dur:<=0.4 dur:0.4"));
+        assert!(!matches("dur:>=0.5"));
+        assert!(!matches("ph:AE dog"));
+
+        for bad in ["stress:3", "dur:>long", "ph:AE++T", "pitch:high"] {
+            assert!(BankQuery::parse(bad).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_clips_from_source_skips_out_of_range() {
+        let samples = vec![0.5f64; 16000];
+        let syllables = vec![make_syllable(0.0, 0.3, "hello"), make_syllable(5.0, 5.3, "gone")];
+        let clips = clips_from_source(&syllables, &shared_source(&samples), 16000, std::path::Path::new("a.wav"));
+        assert_eq!(clips.len(), 1);
+        assert_eq!(clips[0].syllable.word, "hello");
+        assert_eq!(clips[0].source.path(), Path::new("a.wav"));
     }
 
     #[test]
@@ -136,7 +408,7 @@ mod tests {
         let mut samples = vec![0.001f64; sr as usize / 2]; // 0.5s quiet
         samples.extend(vec![0.5f64; sr as usize * 3 / 2]); // 1.5s signal
         let mut source_audio = HashMap::new();
-        source_audio.insert(path.clone(), (samples, sr));
+        source_audio.insert(path.clone(), (vec![samples], sr));
 
         let syllables = vec![(make_syllable(0.6, 0.9, "hello"), path.clone())];
 
@@ -157,4 +429,37 @@ mod tests {
         assert!(room_tone.is_empty());
         assert!(breaths.is_empty());
     }
+
+    #[test]
+    fn test_import_sample_resamples_and_keeps_stereo() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_import_{}", std::process::id()));
+        let path = dir.join("hit.wav");
+        let left = vec![0.4; 44100 / 2];
+        let right = vec![0.2; 44100 / 2];
+        crate::audio::io::write_wav_channels(&path, &[left, right], 44100).unwrap();
+
+        let clip = import_sample(&path, "kick", 16000).unwrap();
+        assert!(clip.manual);
+        assert_eq!(clip.label, "kick");
+        assert_eq!(clip.sample_rate, 16000);
+        assert!((clip.duration_s() - 0.5).abs() < 0.01, "{}", clip.duration_s());
+        let mid = clip.samples.get(clip.samples.len() / 2);
+        assert!((mid - 0.3).abs() < 0.01, "{}", mid);
+        let channels = clip.samples.to_channels::<f64>();
+        assert_eq!(channels.len(), 2);
+        let mid = channels[0].len() / 2;
+        assert!((channels[0][mid] - 0.4).abs() < 0.01 && (channels[1][mid] - 0.2).abs() < 0.01);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_import_sample_rejects_long_files() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_import_long_{}", std::process::id()));
+        let path = dir.join("long.wav");
+        let samples = vec![0.1; 8000 * (MAX_SAMPLE_S as usize + 1)];
+        crate::audio::io::write_wav(&path, &samples, 8000).unwrap();
+        assert!(import_sample(&path, "long", 16000).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
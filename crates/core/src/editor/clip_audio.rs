@@ -0,0 +1,284 @@
+//! Shared clip audio: bank clips are windows onto one `f32` buffer per
+//! source, with their edge fades applied on read, so cutting, cloning, and
+//! scheduling a clip never copies samples. Stereo sources also share their
+//! channels, so clips cut from them keep the stereo image.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::audio::sample::Sample;
+
+/// A source's audio as its bank clips share it: the mono mix, used for
+/// analysis, waveforms and mono output, plus each channel of a
+/// multi-channel source.
+#[derive(Clone)]
+pub struct SourceBuffer {
+    mono: Arc<[f32]>,
+    /// Empty for mono sources.
+    channels: Arc<[Arc<[f32]>]>,
+}
+
+impl SourceBuffer {
+    /// A mono source.
+    pub fn mono(samples: Arc<[f32]>) -> Self {
+        Self { mono: samples, channels: Arc::new([]) }
+    }
+
+    /// A source from planar channels; more than one keeps them alongside
+    /// their mix. The mix averages the channels, as `mix_to_mono` does.
+    pub fn from_channels(channels: Vec<Vec<f32>>) -> Self {
+        if channels.len() <= 1 {
+            return Self::mono(channels.into_iter().next().unwrap_or_default().into());
+        }
+        let frames = channels.iter().map(|c| c.len()).min().unwrap_or(0);
+        let n = channels.len() as f64;
+        let mono: Vec<f32> = (0..frames)
+            .map(|i| f32::from_f64(channels.iter().map(|c| c[i].to_f64()).sum::<f64>() / n))
+            .collect();
+        let channels = channels
+            .into_iter()
+            .map(|mut c| {
+                c.truncate(frames);
+                c.into()
+            })
+            .collect();
+        Self { mono: mono.into(), channels }
+    }
+
+    /// The mono mix.
+    pub fn samples(&self) -> &Arc<[f32]> {
+        &self.mono
+    }
+
+    /// Number of channels: 1 for a mono source.
+    pub fn channel_count(&self) -> usize {
+        self.channels.len().max(1)
+    }
+}
+
+impl From<Arc<[f32]>> for SourceBuffer {
+    fn from(samples: Arc<[f32]>) -> Self {
+        Self::mono(samples)
+    }
+}
+
+impl From<Vec<f32>> for SourceBuffer {
+    fn from(samples: Vec<f32>) -> Self {
+        Self::mono(samples.into())
+    }
+}
+
+/// A clip's samples: a range of a shared source buffer plus half-sine
+/// fades at both ends. Cloning shares the buffer.
+#[derive(Clone)]
+pub struct ClipAudio {
+    buffer: Arc<[f32]>,
+    /// The same range of each channel of a stereo source; empty for mono.
+    channels: Arc<[Arc<[f32]>]>,
+    start: usize,
+    len: usize,
+    /// Fade length at each end (samples); 0 for none.
+    fade: usize,
+}
+
+impl ClipAudio {
+    /// View `start..end` of a source (seconds), widened by `padding_ms` and
+    /// faded by `fade_ms` — the same cut as `audio::effects::cut_clip`.
+    pub fn cut(
+        source: &SourceBuffer,
+        sr: u32,
+        start: f64,
+        end: f64,
+        padding_ms: f64,
+        fade_ms: f64,
+    ) -> Self {
+        let channels = Arc::clone(&source.channels);
+        let source = &source.mono;
+        let file_duration = source.len() as f64 / sr as f64;
+        let padding_s = padding_ms / 1000.0;
+        let fade_s = fade_ms / 1000.0;
+
+        let actual_start = (start - padding_s).max(0.0);
+        let actual_end = (end + padding_s).min(file_duration);
+        let start_idx = ((actual_start * sr as f64).round() as usize).min(source.len());
+        let end_idx = ((actual_end * sr as f64).round() as usize).min(source.len());
+        let len = end_idx.saturating_sub(start_idx);
+
+        let duration = len as f64 / sr as f64;
+        let fade = if fade_s > 0.0 && duration > fade_s * 2.0 {
+            (fade_s * sr as f64).round() as usize
+        } else {
+            0
+        };
+        Self {
+            buffer: Arc::clone(source),
+            channels,
+            start: if len > 0 { start_idx } else { 0 },
+            len,
+            fade,
+        }
+    }
+
+    /// Wrap samples the clip owns outright, unfaded.
+    pub fn from_vec(samples: Vec<f32>) -> Self {
+        let len = samples.len();
+        Self {
+            buffer: samples.into(),
+            channels: Arc::new([]),
+            start: 0,
+            len,
+            fade: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of channels: 1 for a clip of a mono source.
+    pub fn channel_count(&self) -> usize {
+        self.channels.len().max(1)
+    }
+
+    /// Mono view of channel `ch` of the same range and fades. A mono
+    /// clip is its own only channel.
+    pub fn channel(&self, ch: usize) -> Self {
+        match self.channels.get(ch) {
+            Some(buffer) => Self {
+                buffer: Arc::clone(buffer),
+                channels: Arc::new([]),
+                ..self.clone()
+            },
+            None => self.clone(),
+        }
+    }
+
+    /// Materialize the faded samples of every channel.
+    pub fn to_channels<S: Sample>(&self) -> Vec<Vec<S>> {
+        (0..self.channel_count()).map(|ch| self.channel(ch).to_vec()).collect()
+    }
+
+    /// Sample `i` of the mono mix, with fades applied.
+    pub fn get(&self, i: usize) -> f32 {
+        let mut value = self.buffer[self.start + i];
+        if self.fade == 0 {
+            return value;
+        }
+        if i < self.fade {
+            let t = i as f64 / self.fade as f64;
+            value = f32::from_f64(value.to_f64() * (t * std::f64::consts::FRAC_PI_2).sin());
+        }
+        let out_start = self.len.saturating_sub(self.fade);
+        if i >= out_start {
+            let t = (i - out_start) as f64 / (self.len - out_start) as f64;
+            value = f32::from_f64(value.to_f64() * ((1.0 - t) * std::f64::consts::FRAC_PI_2).sin());
+        }
+        value
+    }
+
+    /// Iterate the faded samples.
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+
+    /// Materialize the faded samples, e.g. for effects or one-shot playback.
+    pub fn to_vec<S: Sample>(&self) -> Vec<S> {
+        self.iter().map(|v| S::from_f64(v.to_f64())).collect()
+    }
+
+    /// View `len` samples of this clip from `start`, faded at its new ends
+    /// like the clip itself. Out-of-range parts are dropped.
+    pub fn slice(&self, start: usize, len: usize) -> Self {
+        let start = start.min(self.len);
+        let len = len.min(self.len - start);
+        Self {
+            buffer: Arc::clone(&self.buffer),
+            channels: Arc::clone(&self.channels),
+            start: self.start + start,
+            len,
+            fade: if len > self.fade * 2 { self.fade } else { 0 },
+        }
+    }
+
+    /// Whether both views read the same underlying buffer.
+    pub fn shares_buffer(&self, other: &ClipAudio) -> bool {
+        Arc::ptr_eq(&self.buffer, &other.buffer)
+    }
+}
+
+impl From<Vec<f32>> for ClipAudio {
+    fn from(samples: Vec<f32>) -> Self {
+        Self::from_vec(samples)
+    }
+}
+
+impl fmt::Debug for ClipAudio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never dump the whole source buffer
+        f.debug_struct("ClipAudio")
+            .field("source_len", &self.buffer.len())
+            .field("channels", &self.channel_count())
+            .field("start", &self.start)
+            .field("len", &self.len)
+            .field("fade", &self.fade)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::effects::cut_clip;
+
+    #[test]
+    fn test_cut_matches_cut_clip() {
+        let source: Vec<f32> = (0..16000).map(|i| ((i as f32) * 0.01).sin() * 0.8).collect();
+        let shared: SourceBuffer = source.clone().into();
+        let cases = [
+            (0.2, 0.5, 25.0, 5.0),
+            (0.0, 0.3, 25.0, 5.0),
+            (0.9, 1.2, 25.0, 5.0),
+            (0.4, 0.405, 0.0, 5.0),
+            (0.5, 0.4, 0.0, 5.0),
+            (0.1, 0.6, 0.0, 0.0),
+        ];
+        for (start, end, padding, fade) in cases {
+            let view = ClipAudio::cut(&shared, 16000, start, end, padding, fade);
+            let expected = cut_clip(&source, 16000, start, end, padding, fade);
+            assert_eq!(view.to_vec::<f32>(), expected, "cut {}..{}", start, end);
+            assert!(view.shares_buffer(&ClipAudio::cut(&shared, 16000, 0.0, 0.1, 0.0, 0.0)));
+        }
+    }
+
+    #[test]
+    fn test_from_vec_is_unfaded() {
+        let audio = ClipAudio::from(vec![0.5f32; 100]);
+        assert_eq!(audio.len(), 100);
+        assert!(audio.iter().all(|v| v == 0.5));
+        assert!(!audio.shares_buffer(&ClipAudio::from_vec(vec![0.5; 100])));
+    }
+
+    #[test]
+    fn test_stereo_source_keeps_channels() {
+        let source = SourceBuffer::from_channels(vec![vec![0.2; 16000], vec![-0.4; 16000]]);
+        assert_eq!(source.channel_count(), 2);
+        assert!((source.samples()[0] + 0.1).abs() < 1e-6);
+
+        let clip = ClipAudio::cut(&source, 16000, 0.2, 0.5, 0.0, 5.0).slice(100, 1000);
+        assert_eq!(clip.channel_count(), 2);
+        let channels = clip.to_channels::<f32>();
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].len(), 1000);
+        // Channels share the mix's fades
+        assert!((channels[0][500] - 0.2).abs() < 1e-6 && (channels[1][500] + 0.4).abs() < 1e-6);
+        assert!(channels[0][0].abs() < 0.2 && channels[1][0].abs() < 0.4);
+
+        let mono = ClipAudio::cut(&SourceBuffer::from_channels(vec![vec![0.3; 100]]), 16000, 0.0, 1.0, 0.0, 0.0);
+        assert_eq!(mono.channel_count(), 1);
+        assert_eq!(mono.to_channels::<f32>(), vec![vec![0.3; 100]]);
+    }
+}
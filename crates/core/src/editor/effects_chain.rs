@@ -2,17 +2,27 @@
 
 use anyhow::Result;
 use super::types::ClipEffect;
+use crate::audio::sample::Sample;
 
 /// Apply a stack of effects to audio samples.
 ///
 /// Effects are applied in order. Each effect transforms the samples
 /// produced by the previous one.
-pub fn apply_effects(
-    source_samples: &[f64],
+pub fn apply_effects<S: Sample>(
+    source_samples: &[S],
     sr: u32,
     effects: &[ClipEffect],
-) -> Result<Vec<f64>> {
-    let mut samples = source_samples.to_vec();
+) -> Result<Vec<S>> {
+    apply_effects_owned(source_samples.to_vec(), sr, effects)
+}
+
+/// [`apply_effects`] on a buffer the caller already owns, so callers that
+/// materialize a clip view don't copy it twice.
+pub fn apply_effects_owned<S: Sample>(
+    mut samples: Vec<S>,
+    sr: u32,
+    effects: &[ClipEffect],
+) -> Result<Vec<S>> {
 
     for effect in effects {
         match effect {
@@ -35,6 +45,13 @@ pub fn apply_effects(
             ClipEffect::Reverse => {
                 samples.reverse();
             }
+            ClipEffect::Filter(filter) => {
+                samples = filter.apply(&samples, sr);
+            }
+            ClipEffect::Blend { effect, wet, gain_db } => {
+                let processed = apply_effects_owned(samples.clone(), sr, std::slice::from_ref(effect.as_ref()))?;
+                samples = crate::audio::effects::blend_wet_dry(&samples, processed, *wet, *gain_db);
+            }
         }
     }
 
@@ -52,8 +69,11 @@ pub fn compute_effective_duration(base_duration_s: f64, effects: &[ClipEffect])
             ClipEffect::TimeStretch { factor } => {
                 dur *= factor;
             }
-            ClipEffect::PitchShift { .. } | ClipEffect::Reverse => {
-                // Pitch shift and reverse preserve duration
+            ClipEffect::PitchShift { .. } | ClipEffect::Reverse | ClipEffect::Filter(_) => {
+                // Pitch shift, reverse, and filters preserve duration
+            }
+            ClipEffect::Blend { effect, .. } => {
+                dur = compute_effective_duration(dur, std::slice::from_ref(effect.as_ref()));
             }
         }
     }
@@ -193,6 +213,29 @@ mod tests {
         assert_eq!(result, samples);
     }
 
+    #[test]
+    fn test_blend_mixes_effect_with_input() {
+        let samples = vec![1.0, 2.0, 3.0, 4.0];
+        let half = ClipEffect::Reverse.blended(0.5, 0.0);
+        assert_eq!(apply_effects(&samples, 16000, &[half]).unwrap(), vec![2.5; 4]);
+
+        // Blending a blend replaces it; fully wet at 0 dB is the bare effect
+        let loud = ClipEffect::Reverse.blended(0.5, 0.0).blended(1.0, 6.0);
+        assert_eq!(loud.wet_gain(), (1.0, 6.0));
+        assert_eq!(loud.base(), &ClipEffect::Reverse);
+        assert_eq!(loud.clone().blended(1.0, 0.0), ClipEffect::Reverse);
+        let out = apply_effects(&samples, 16000, &[loud]).unwrap();
+        assert!((out[0] - 4.0 * 10f64.powf(0.3)).abs() < 1e-9);
+
+        // Length follows the wet signal
+        let stutter = ClipEffect::Stutter { count: 1 }.blended(0.3, -3.0);
+        let dur = compute_effective_duration(1.0, std::slice::from_ref(&stutter));
+        assert!((dur - 2.0).abs() < 0.001);
+        let plain = apply_effects(&sine_samples(0.5, 16000), 16000, &[ClipEffect::Stutter { count: 1 }]).unwrap();
+        let blended = apply_effects(&sine_samples(0.5, 16000), 16000, &[stutter]).unwrap();
+        assert_eq!(plain.len(), blended.len());
+    }
+
     #[test]
     fn test_compute_duration_reverse() {
         let dur = compute_effective_duration(1.0, &[ClipEffect::Reverse]);
@@ -233,4 +276,66 @@ mod tests {
         );
         assert!((dur - 3.0).abs() < 0.001);
     }
+
+    mod props {
+        use super::*;
+        use crate::audio::effects::Filter;
+        use proptest::prelude::*;
+
+        const SR: u32 = 16000;
+
+        fn effect_strategy() -> impl Strategy<Value = ClipEffect> {
+            prop_oneof![
+                (0usize..4).prop_map(|count| ClipEffect::Stutter { count }),
+                (0.5f64..2.0).prop_map(|factor| ClipEffect::TimeStretch { factor }),
+                (-12.0f64..12.0).prop_map(|semitones| ClipEffect::PitchShift { semitones }),
+                Just(ClipEffect::Reverse),
+                (200.0f64..4000.0).prop_map(|cutoff_hz| ClipEffect::Filter(Filter::LowPass { cutoff_hz })),
+            ]
+        }
+
+        /// Sample count after effects: stutter repeats overlap by the 5 ms
+        /// crossfade, and stretches round to whole samples.
+        fn expected_len(mut len: usize, effects: &[ClipEffect]) -> usize {
+            let crossfade = (5.0 / 1000.0 * SR as f64).round() as usize;
+            for effect in effects {
+                match effect {
+                    ClipEffect::Stutter { count } => {
+                        len = len * (1 + count) - count * crossfade.min(len);
+                    }
+                    ClipEffect::TimeStretch { factor } if (factor - 1.0).abs() >= 0.01 => {
+                        len = (len as f64 * factor).round() as usize;
+                    }
+                    _ => {}
+                }
+            }
+            len
+        }
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(32))]
+
+            #[test]
+            fn effects_length_matches_duration_math(
+                n in 0usize..1600,
+                effects in prop::collection::vec(effect_strategy(), 0..4),
+            ) {
+                let samples = sine_samples(n as f64 / SR as f64, SR);
+                let out = apply_effects(&samples, SR, &effects).unwrap();
+                prop_assert_eq!(out.len(), expected_len(samples.len(), &effects));
+
+                // Without stutter overlaps the estimate is exact up to
+                // per-stretch rounding and the near-1.0 stretch bypass
+                if !effects.iter().any(|e| matches!(e, ClipEffect::Stutter { .. })) {
+                    let estimate = compute_effective_duration(n as f64 / SR as f64, &effects);
+                    let actual = out.len() as f64 / SR as f64;
+                    let stretches = effects
+                        .iter()
+                        .filter(|e| matches!(e, ClipEffect::TimeStretch { .. }))
+                        .count() as f64;
+                    prop_assert!((actual - estimate).abs() <= estimate * 0.02 * stretches + stretches * 2.0 / SR as f64);
+                }
+            }
+        }
+    }
 }
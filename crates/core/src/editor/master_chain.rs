@@ -0,0 +1,272 @@
+//! Master bus processing: an arrangement's [`MasterEffect`] chain run over
+//! the rendered mix, whole or one block at a time.
+
+use anyhow::Result;
+
+use super::types::MasterEffect;
+use crate::audio::effects::{Biquad, BiquadKind, PartitionedConvolver, PeakLimiter, REVERB_BLOCK};
+
+/// Corner of the EQ's low shelf (Hz).
+pub const EQ_LOW_HZ: f64 = 200.0;
+/// Center of the EQ's mid peak (Hz).
+pub const EQ_MID_HZ: f64 = 1000.0;
+/// Corner of the EQ's high shelf (Hz).
+pub const EQ_HIGH_HZ: f64 = 4000.0;
+
+/// Shelf slope and width of the mid peak.
+const EQ_Q: f64 = 0.7;
+
+/// One effect with its running state.
+#[derive(Clone)]
+enum Stage {
+    Gain(f64),
+    /// Low, mid, and high bands for each channel.
+    Eq(Vec<[Biquad; 3]>),
+    Limiter { ceiling_db: f64, limiter: PeakLimiter },
+    Reverb { send: f64, tail: usize, convolvers: Vec<PartitionedConvolver> },
+}
+
+/// A master effect chain that keeps its state between blocks, so a mix
+/// can be processed as it is streamed.
+///
+/// Every block comes out as long as it went in. Output lags input by
+/// [`latency`](Self::latency) frames (the limiter's lookahead), and the
+/// chain rings on for [`tail`](Self::tail) frames after the input ends
+/// (reverb); feed silence to collect both.
+#[derive(Clone)]
+pub struct MasterChain {
+    stages: Vec<Stage>,
+    channels: usize,
+    sr: u32,
+}
+
+impl MasterChain {
+    /// A chain for `channels` channels, taking blocks of at most `block`
+    /// frames. Neutral effects are left out.
+    pub fn new(effects: &[MasterEffect], channels: usize, sr: u32, block: usize) -> Result<Self> {
+        let channels = channels.max(1);
+        let mut stages = Vec::new();
+        for effect in effects.iter().filter(|e| !e.is_neutral()) {
+            stages.push(match *effect {
+                MasterEffect::Gain { gain_db } => Stage::Gain(10f64.powf(gain_db / 20.0)),
+                MasterEffect::Eq { low_db, mid_db, high_db } => {
+                    Stage::Eq(vec![eq_bands([low_db, mid_db, high_db], sr); channels])
+                }
+                MasterEffect::Limiter { ceiling_db } => Stage::Limiter {
+                    ceiling_db,
+                    limiter: PeakLimiter::with_ceiling(channels, sr, ceiling_db),
+                },
+                MasterEffect::Reverb { ir, send } => {
+                    let ir = ir.impulse_response(sr);
+                    let convolver = PartitionedConvolver::new(&ir, block)?;
+                    Stage::Reverb { send, tail: ir.len().saturating_sub(1), convolvers: vec![convolver; channels] }
+                }
+            });
+        }
+        Ok(Self { stages, channels, sr })
+    }
+
+    /// Whether the chain leaves audio unchanged.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Frames of delay between input and output.
+    pub fn latency(&self) -> usize {
+        self.stages
+            .iter()
+            .map(|s| match s {
+                Stage::Limiter { limiter, .. } => limiter.latency(),
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Frames the chain keeps sounding after its input ends.
+    pub fn tail(&self) -> usize {
+        self.stages
+            .iter()
+            .map(|s| match s {
+                Stage::Reverb { tail, .. } => *tail,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Forget all input, e.g. after seeking.
+    pub fn reset(&mut self) {
+        for stage in &mut self.stages {
+            match stage {
+                Stage::Gain(_) => {}
+                Stage::Eq(bands) => bands.iter_mut().flatten().for_each(Biquad::reset),
+                Stage::Limiter { ceiling_db, limiter } => {
+                    *limiter = PeakLimiter::with_ceiling(self.channels, self.sr, *ceiling_db);
+                }
+                Stage::Reverb { convolvers, .. } => convolvers.iter_mut().for_each(PartitionedConvolver::reset),
+            }
+        }
+    }
+
+    /// Run the next block of planar audio through the chain.
+    pub fn process(&mut self, mut block: Vec<Vec<f64>>) -> Result<Vec<Vec<f64>>> {
+        let frames = block.iter().map(Vec::len).max().unwrap_or(0);
+        block.resize(self.channels, Vec::new());
+        for channel in &mut block {
+            channel.resize(frames, 0.0);
+        }
+        for stage in &mut self.stages {
+            match stage {
+                Stage::Gain(gain) => {
+                    for s in block.iter_mut().flatten() {
+                        *s *= *gain;
+                    }
+                }
+                Stage::Eq(bands) => {
+                    for (channel, filters) in block.iter_mut().zip(bands.iter_mut()) {
+                        for s in channel.iter_mut() {
+                            *s = filters.iter_mut().fold(*s, |x, f| f.process(x));
+                        }
+                    }
+                }
+                Stage::Limiter { limiter, .. } => {
+                    // Frames still held in the lookahead come out as
+                    // leading silence, keeping the block length
+                    let limited = limiter.process(&block);
+                    for (channel, limited) in block.iter_mut().zip(limited) {
+                        let held = frames - limited.len();
+                        channel[..held].fill(0.0);
+                        channel[held..].copy_from_slice(&limited);
+                    }
+                }
+                Stage::Reverb { send, convolvers, .. } => {
+                    for (channel, convolver) in block.iter_mut().zip(convolvers.iter_mut()) {
+                        let wet = convolver.process(channel)?;
+                        for (s, w) in channel.iter_mut().zip(wet) {
+                            *s += w * *send;
+                        }
+                    }
+                }
+            }
+        }
+        Ok(block)
+    }
+}
+
+/// Low shelf, mid peak, and high shelf filters at `gains` (dB).
+fn eq_bands(gains: [f64; 3], sr: u32) -> [Biquad; 3] {
+    [
+        Biquad::with_gain(BiquadKind::LowShelf, EQ_LOW_HZ, EQ_Q, gains[0], sr),
+        Biquad::with_gain(BiquadKind::Peaking, EQ_MID_HZ, EQ_Q, gains[1], sr),
+        Biquad::with_gain(BiquadKind::HighShelf, EQ_HIGH_HZ, EQ_Q, gains[2], sr),
+    ]
+}
+
+/// Run a whole mix through `effects`.
+///
+/// The output is longer than the input by the chain's reverb tail, and
+/// is not delayed by the limiter's lookahead.
+pub fn apply_master_effects(channels: Vec<Vec<f64>>, effects: &[MasterEffect], sr: u32) -> Result<Vec<Vec<f64>>> {
+    let len = channels.iter().map(Vec::len).max().unwrap_or(0);
+    let mut chain = MasterChain::new(effects, channels.len(), sr, REVERB_BLOCK)?;
+    if chain.is_empty() || len == 0 {
+        return Ok(channels);
+    }
+    let latency = chain.latency();
+    let total = len + chain.tail() + latency;
+    let mut out = vec![Vec::with_capacity(total); channels.len()];
+    for start in (0..total).step_by(REVERB_BLOCK) {
+        let end = (start + REVERB_BLOCK).min(total);
+        let block = channels
+            .iter()
+            .map(|c| (start..end).map(|i| c.get(i).copied().unwrap_or(0.0)).collect())
+            .collect();
+        for (out, processed) in out.iter_mut().zip(chain.process(block)?) {
+            out.extend(processed);
+        }
+    }
+    for channel in &mut out {
+        channel.drain(..latency);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio::effects::{ReverbIr, LIMITER_CEILING_DBFS};
+
+    fn sine(freq: f64, len: usize, sr: u32) -> Vec<f64> {
+        (0..len).map(|i| (2.0 * std::f64::consts::PI * freq * i as f64 / sr as f64).sin() * 0.5).collect()
+    }
+
+    fn rms(samples: &[f64]) -> f64 {
+        (samples.iter().map(|s| s * s).sum::<f64>() / samples.len() as f64).sqrt()
+    }
+
+    #[test]
+    fn test_neutral_chain_passes_audio() {
+        let mix = vec![sine(440.0, 4000, 16000)];
+        let effects = [MasterEffect::Gain { gain_db: 0.0 }, MasterEffect::Eq { low_db: 0.0, mid_db: 0.0, high_db: 0.0 }];
+        assert_eq!(apply_master_effects(mix.clone(), &effects, 16000).unwrap(), mix);
+    }
+
+    #[test]
+    fn test_gain_and_limiter_hold_the_ceiling() {
+        let mix = vec![sine(440.0, 8000, 16000), sine(220.0, 8000, 16000)];
+        let effects = [MasterEffect::Gain { gain_db: 12.0 }, MasterEffect::Limiter { ceiling_db: -3.0 }];
+        let out = apply_master_effects(mix, &effects, 16000).unwrap();
+        assert_eq!(out[0].len(), 8000);
+        let peak = out.iter().flatten().fold(0.0f64, |m, s| m.max(s.abs()));
+        assert!(peak <= 10f64.powf(-3.0 / 20.0) + 1e-9, "peak {}", peak);
+        assert!(peak > 10f64.powf(LIMITER_CEILING_DBFS / 20.0) * 0.5);
+    }
+
+    #[test]
+    fn test_eq_shapes_bands() {
+        let sr = 16000;
+        let effects = [MasterEffect::Eq { low_db: 6.0, mid_db: 0.0, high_db: -12.0 }];
+        let low = apply_master_effects(vec![sine(60.0, 16000, sr)], &effects, sr).unwrap();
+        let high = apply_master_effects(vec![sine(7000.0, 16000, sr)], &effects, sr).unwrap();
+        let settled = 4000..16000;
+        let gain_db = |out: &[f64], freq| 20.0 * (rms(&out[settled.clone()]) / rms(&sine(freq, 16000, sr)[settled.clone()])).log10();
+        assert!((gain_db(&low[0], 60.0) - 6.0).abs() < 1.0);
+        assert!(gain_db(&high[0], 7000.0) < -9.0);
+    }
+
+    #[test]
+    fn test_reverb_adds_tail() {
+        let sr = 16000;
+        let effects = [MasterEffect::Reverb { ir: ReverbIr::Room, send: 0.5 }];
+        let tail = ReverbIr::Room.impulse_response(sr).len() - 1;
+        let out = apply_master_effects(vec![sine(440.0, 3000, sr)], &effects, sr).unwrap();
+        assert_eq!(out[0].len(), 3000 + tail);
+        assert!(rms(&out[0][3000..3000 + tail / 4]) > 1e-3);
+    }
+
+    #[test]
+    fn test_blocks_match_one_pass() {
+        let sr = 16000;
+        let effects = [
+            MasterEffect::Eq { low_db: 2.0, mid_db: -4.0, high_db: 3.0 },
+            MasterEffect::Reverb { ir: ReverbIr::Spring, send: 0.4 },
+            MasterEffect::Gain { gain_db: 9.0 },
+            MasterEffect::Limiter { ceiling_db: -1.0 },
+        ];
+        let input = sine(330.0, 5000, sr);
+        let whole = apply_master_effects(vec![input.clone()], &effects, sr).unwrap().remove(0);
+
+        let block = 256;
+        let mut chain = MasterChain::new(&effects, 1, sr, block).unwrap();
+        let total = input.len() + chain.tail() + chain.latency();
+        let mut streamed = Vec::new();
+        for start in (0..total).step_by(block) {
+            let chunk = (start..(start + block).min(total)).map(|i| input.get(i).copied().unwrap_or(0.0)).collect();
+            streamed.extend(chain.process(vec![chunk]).unwrap().remove(0));
+        }
+        streamed.drain(..chain.latency());
+        assert_eq!(streamed.len(), whole.len());
+        for (i, (a, b)) in streamed.iter().zip(&whole).enumerate() {
+            assert!((a - b).abs() < 1e-9, "sample {}: {} vs {}", i, a, b);
+        }
+    }
+}
@@ -1,12 +1,20 @@
 //! Interactive syllable editor data model and processing.
 
 pub mod types;
+pub mod automation;
+pub mod clip_audio;
 pub mod waveform;
 pub mod effects_chain;
+pub mod master_chain;
 pub mod render;
 pub mod bank_builder;
 pub mod pipeline_bridge;
 pub mod playback_engine;
+pub mod arrangement_source;
+pub mod preroll;
+pub mod timeline_image;
 
 pub use types::*;
+pub use automation::{Automation, AutomationParam, AutomationPoint, Envelope};
+pub use clip_audio::{ClipAudio, SourceBuffer};
 pub use waveform::WaveformData;
@@ -6,16 +6,18 @@ use std::path::{Path, PathBuf};
 use anyhow::Result;
 
 use super::bank_builder::build_bank_with_context;
+use crate::audio::io::SourceChannels;
 use super::types::*;
+use crate::sing::vocal_mapper::{compute_target_pitch, NoteMapping};
 use crate::types::Syllable;
 
 /// Convert collage pipeline data into an editor arrangement.
 ///
-/// Takes the aligned syllables and source audio from the collage pipeline,
-/// builds a full bank, and optionally populates the timeline from selected clips.
+/// Takes the aligned syllables and planar source audio (one channel per
+/// `Vec`) from the collage pipeline, builds a full bank, and optionally populates the timeline from selected clips.
 pub fn arrangement_from_collage(
     all_syllables: &HashMap<String, Vec<Syllable>>,
-    source_audio: &HashMap<String, (Vec<f64>, u32)>,
+    source_audio: &SourceChannels,
     selected_syllable_indices: Option<&[(String, usize)]>,
 ) -> Result<Arrangement> {
     // Build bank from all syllables
@@ -27,7 +29,7 @@ pub fn arrangement_from_collage(
         })
         .collect();
 
-    let source_audio_pathbuf: HashMap<PathBuf, (Vec<f64>, u32)> = source_audio
+    let source_audio_pathbuf: HashMap<PathBuf, (Vec<Vec<f64>>, u32)> = source_audio
         .iter()
         .map(|(k, v)| (PathBuf::from(k), v.clone()))
         .collect();
@@ -42,7 +44,7 @@ pub fn arrangement_from_collage(
         for (source, idx) in indices {
             // Find matching bank clip by source path and syllable index
             if let Some(bank_clip) = bank.iter().find(|c| {
-                c.source_path == Path::new(source)
+                c.source.path() == Path::new(source)
                     && c.syllable.word_index == *idx
             }) {
                 arr.timeline.push(TimelineClip::new(bank_clip));
@@ -60,7 +62,7 @@ pub fn arrangement_from_collage(
 /// Create an empty arrangement with a populated bank for blank canvas mode.
 pub fn arrangement_blank_canvas(
     all_syllables: &HashMap<String, Vec<Syllable>>,
-    source_audio: &HashMap<String, (Vec<f64>, u32)>,
+    source_audio: &SourceChannels,
     pipeline: EditorPipelineMode,
 ) -> Result<Arrangement> {
     let syllable_pairs: Vec<(Syllable, PathBuf)> = all_syllables
@@ -71,7 +73,7 @@ pub fn arrangement_blank_canvas(
         })
         .collect();
 
-    let source_audio_pathbuf: HashMap<PathBuf, (Vec<f64>, u32)> = source_audio
+    let source_audio_pathbuf: HashMap<PathBuf, (Vec<Vec<f64>>, u32)> = source_audio
         .iter()
         .map(|(k, v)| (PathBuf::from(k), v.clone()))
         .collect();
@@ -86,12 +88,77 @@ pub fn arrangement_blank_canvas(
     Ok(arr)
 }
 
+/// Build a sing arrangement: each melody note's syllables are placed at
+/// the note in a free layout, pitched and stretched as the sing renderer
+/// does, and the notes are kept as the arrangement's melody lane.
+///
+/// `source_order` lists the source keys in the order their syllables were
+/// pooled, so the mappings' syllable indices address the bank. Both skip
+/// syllables whose padded cut is empty.
+pub fn arrangement_from_sing(
+    all_syllables: &HashMap<String, Vec<Syllable>>,
+    source_audio: &SourceChannels,
+    source_order: &[String],
+    mappings: &[NoteMapping],
+    median_f0: f64,
+) -> Result<Arrangement> {
+    let syllable_pairs: Vec<(Syllable, PathBuf)> = source_order
+        .iter()
+        .filter_map(|source| all_syllables.get(source).map(|syls| (source, syls)))
+        .flat_map(|(source, syls)| {
+            syls.iter()
+                .map(move |s| (s.clone(), PathBuf::from(source)))
+        })
+        .collect();
+
+    let source_audio_pathbuf: HashMap<PathBuf, (Vec<Vec<f64>>, u32)> = source_audio
+        .iter()
+        .map(|(k, v)| (PathBuf::from(k), v.clone()))
+        .collect();
+
+    let (bank, room_tone_clips, breath_clips) =
+        build_bank_with_context(&syllable_pairs, &source_audio_pathbuf)?;
+
+    let mut arr = Arrangement::new(16000, EditorPipelineMode::Sing);
+    arr.layout_mode = LayoutMode::Free;
+    for mapping in mappings {
+        let clips: Vec<&SyllableClip> = mapping
+            .syllable_indices
+            .iter()
+            .filter_map(|&i| bank.get(i))
+            .collect();
+        let semitones = compute_target_pitch(mapping.note_pitch, median_f0, mapping.pitch_shift_semitones)
+            .clamp(-12.0, 12.0);
+        let mut note = MelodyNote {
+            pitch: mapping.note_pitch,
+            start_s: mapping.note_start,
+            end_s: mapping.note_end,
+            semitones,
+            clip_ids: Vec::new(),
+        };
+        let per_syllable = mapping.note_duration / clips.len().max(1) as f64;
+        for (k, clip) in clips.into_iter().enumerate() {
+            let position = mapping.note_start + k as f64 * per_syllable;
+            let tc = sung_clip(clip, position, per_syllable, semitones);
+            note.clip_ids.push(tc.id);
+            arr.timeline.push(tc);
+        }
+        arr.melody.push(note);
+    }
+    arr.relayout(0.0);
+
+    arr.bank = bank;
+    arr.room_tone_clips = room_tone_clips;
+    arr.breath_clips = breath_clips;
+    Ok(arr)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::types::Phoneme;
 
-    fn make_test_data() -> (HashMap<String, Vec<Syllable>>, HashMap<String, (Vec<f64>, u32)>) {
+    fn make_test_data() -> (HashMap<String, Vec<Syllable>>, SourceChannels) {
         let mut syllables = HashMap::new();
         syllables.insert(
             "test.wav".to_string(),
@@ -114,7 +181,7 @@ mod tests {
         );
 
         let mut audio = HashMap::new();
-        audio.insert("test.wav".to_string(), (vec![0.5f64; 16000], 16000u32));
+        audio.insert("test.wav".to_string(), (vec![vec![0.5f64; 16000]], 16000u32));
 
         (syllables, audio)
     }
@@ -128,6 +195,52 @@ mod tests {
         assert_eq!(arr.source_pipeline, EditorPipelineMode::Collage);
     }
 
+    #[test]
+    fn test_arrangement_from_sing_places_clips_on_notes() {
+        use crate::sing::vocal_mapper::DurationClass;
+
+        let (syllables, audio) = make_test_data();
+        let mapping = |pitch: u8, start: f64, end: f64, syllable_indices: Vec<usize>| NoteMapping {
+            note_pitch: pitch,
+            note_start: start,
+            note_end: end,
+            note_duration: end - start,
+            syllable_indices,
+            pitch_shift_semitones: 0.0,
+            time_stretch_ratio: 1.0,
+            apply_vibrato: false,
+            apply_chorus: false,
+            duration_class: DurationClass::Medium,
+        };
+        let mappings = vec![mapping(57, 0.5, 1.1, vec![0, 1]), mapping(69, 1.5, 1.8, vec![1])];
+        let order = vec!["test.wav".to_string()];
+        let mut arr = arrangement_from_sing(&syllables, &audio, &order, &mappings, 440.0).unwrap();
+
+        assert_eq!(arr.layout_mode, LayoutMode::Free);
+        assert_eq!(arr.melody.len(), 2);
+        assert_eq!(arr.timeline.len(), 3);
+        // Two syllables split the first note; A3 is an octave under 440 Hz
+        assert_eq!(arr.melody[0].clip_ids.len(), 2);
+        assert!((arr.melody[0].semitones + 12.0).abs() < 1e-9);
+        assert!((arr.timeline[0].position_s - 0.5).abs() < 1e-9);
+        assert!((arr.timeline[1].position_s - 0.8).abs() < 1e-9);
+        assert!((arr.timeline[0].effective_duration_s - 0.3).abs() < 0.01);
+        assert_eq!(arr.note_for_clip(arr.timeline[2].id), Some(1));
+        assert_eq!(arr.timeline[2].source_clip_id, arr.bank[1].id);
+
+        // Swapping replaces both clips of the note with one that fills it
+        let bank_id = arr.bank[1].id;
+        let new_id = arr.swap_note_syllable(0, bank_id).unwrap();
+        assert_eq!(arr.timeline.len(), 2);
+        assert_eq!(arr.melody[0].clip_ids, vec![new_id]);
+        let tc = arr.timeline.iter().find(|tc| tc.id == new_id).unwrap();
+        assert_eq!(tc.source_clip_id, bank_id);
+        assert!((tc.position_s - 0.5).abs() < 1e-9);
+        assert!((tc.effective_duration_s - 0.6).abs() < 0.01);
+        assert!(tc.effects.contains(&ClipEffect::PitchShift { semitones: arr.melody[0].semitones }));
+        assert!(arr.swap_note_syllable(5, bank_id).is_none());
+    }
+
     #[test]
     fn test_arrangement_from_collage_no_selection() {
         let (syllables, audio) = make_test_data();
@@ -4,22 +4,116 @@ use std::sync::mpsc;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use rodio::{buffer::SamplesBuffer, OutputStream, Sink};
+use rodio::{buffer::SamplesBuffer, Sink, Source};
+
+use crate::audio::device::{AudioOutput, BufferSize};
+
+use super::arrangement_source::ArrangementSource;
+
+/// Shortest loop region accepted (seconds).
+pub const MIN_LOOP_S: f64 = 0.05;
+
+/// A span of the arrangement that playback repeats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoopRegion {
+    pub start_s: f64,
+    pub end_s: f64,
+}
+
+impl LoopRegion {
+    /// Region between two marker times in either order.
+    ///
+    /// Returns `None` if it would be shorter than [`MIN_LOOP_S`].
+    pub fn new(a: f64, b: f64) -> Option<Self> {
+        let (start_s, end_s) = (a.min(b).max(0.0), a.max(b).max(0.0));
+        (end_s - start_s >= MIN_LOOP_S).then_some(Self { start_s, end_s })
+    }
+
+    pub fn duration_s(&self) -> f64 {
+        self.end_s - self.start_s
+    }
+
+    pub fn contains(&self, time_s: f64) -> bool {
+        time_s >= self.start_s && time_s < self.end_s
+    }
+
+    /// Cursor position `elapsed_s` after starting at `start_cursor_s`:
+    /// plays through to the loop end, then wraps back to the loop start.
+    pub fn cursor_at(&self, start_cursor_s: f64, elapsed_s: f64) -> f64 {
+        let first_pass = self.end_s - start_cursor_s;
+        if elapsed_s < first_pass {
+            start_cursor_s + elapsed_s
+        } else {
+            self.start_s + (elapsed_s - first_pass) % self.duration_s()
+        }
+    }
+}
+
+/// Audio the playback thread cuts loop passes from.
+enum LoopMaterial {
+    /// Pre-rendered interleaved samples; frame 0 is at `base_s` on the timeline.
+    Samples {
+        samples: Arc<Vec<f32>>,
+        channels: u16,
+        sample_rate: u32,
+        base_s: f64,
+    },
+    /// A stream, re-rendered for each pass so edits are heard.
+    Source(Box<ArrangementSource>),
+}
+
+impl LoopMaterial {
+    /// One pass of `start_s..end_s`.
+    fn pass(&self, start_s: f64, end_s: f64) -> Box<dyn Source<Item = f32> + Send> {
+        match self {
+            LoopMaterial::Samples { samples, channels, sample_rate, base_s } => {
+                let n = *channels as usize;
+                let frames = samples.len() / n;
+                let idx = |t: f64| (((t - base_s) * *sample_rate as f64).round().max(0.0) as usize).min(frames) * n;
+                let (from, to) = (idx(start_s), idx(end_s));
+                Box::new(SamplesBuffer::new(*channels, *sample_rate, samples[from..to.max(from)].to_vec()))
+            }
+            LoopMaterial::Source(source) => Box::new(source.range(start_s, end_s)),
+        }
+    }
+}
+
+/// Region looping on the playback thread.
+struct Looper {
+    region: LoopRegion,
+    material: LoopMaterial,
+}
 
 /// Command sent to the playback thread.
 pub enum PlaybackCommand {
-    /// Play samples from a cursor position.
+    /// Play planar channels (one for mono) from a cursor position.
     PlaySamples {
-        samples: Vec<f64>,
+        channels: Vec<Vec<f64>>,
         sample_rate: u32,
         start_cursor_s: f64,
     },
+    /// Stream an arrangement, rendering clips as playback reaches them.
+    ///
+    /// `lead_in` (e.g. a count-in) plays first at the source's sample rate;
+    /// the cursor reaches `start_cursor_s` when the arrangement starts.
+    PlaySource {
+        source: Box<ArrangementSource>,
+        start_cursor_s: f64,
+        lead_in: Vec<f64>,
+    },
     /// Pause playback.
     Pause,
     /// Resume playback.
     Resume,
     /// Stop playback and reset cursor.
     Stop,
+    /// Reopen the output device with a new buffer size (stops playback).
+    SetBufferSize(BufferSize),
+    /// Loop region for the next play command (`None` = play through).
+    SetLoop(Option<LoopRegion>),
+    /// Replace the stream later loop passes are cut from, so edits made
+    /// while looping are heard on the following passes.
+    UpdateLoopSource(Box<ArrangementSource>),
 }
 
 /// Shared playback state readable from the GUI thread.
@@ -74,6 +168,8 @@ impl PlaybackState {
 pub struct PlaybackEngine {
     command_tx: mpsc::Sender<PlaybackCommand>,
     pub state: PlaybackState,
+    buffer_size: BufferSize,
+    loop_region: Option<LoopRegion>,
 }
 
 impl Default for PlaybackEngine {
@@ -85,6 +181,11 @@ impl Default for PlaybackEngine {
 impl PlaybackEngine {
     /// Create a new playback engine and spawn the audio thread.
     pub fn new() -> Self {
+        Self::with_buffer_size(BufferSize::Default)
+    }
+
+    /// Create a playback engine whose output uses the given buffer size.
+    pub fn with_buffer_size(buffer_size: BufferSize) -> Self {
         let (tx, rx) = mpsc::channel();
         let state = PlaybackState::new();
 
@@ -92,16 +193,55 @@ impl PlaybackEngine {
         std::thread::Builder::new()
             .name("playback-engine".into())
             .spawn(move || {
-                playback_thread(rx, thread_state);
+                playback_thread(rx, thread_state, buffer_size);
             })
             .expect("Failed to spawn playback thread");
 
         Self {
             command_tx: tx,
             state,
+            buffer_size,
+            loop_region: None,
+        }
+    }
+
+    /// Buffer size the output was last opened with.
+    pub fn buffer_size(&self) -> BufferSize {
+        self.buffer_size
+    }
+
+    /// Reopen the output with a new buffer size if it changed.
+    ///
+    /// Stops anything currently playing.
+    pub fn set_buffer_size(&mut self, buffer_size: BufferSize) {
+        if buffer_size != self.buffer_size {
+            self.buffer_size = buffer_size;
+            self.send(PlaybackCommand::SetBufferSize(buffer_size));
         }
     }
 
+    /// Loop region applied to the next play command.
+    pub fn loop_region(&self) -> Option<LoopRegion> {
+        self.loop_region
+    }
+
+    /// Set the loop region used from the next play command on.
+    ///
+    /// Returns true if it changed.
+    pub fn set_loop(&mut self, region: Option<LoopRegion>) -> bool {
+        if region == self.loop_region {
+            return false;
+        }
+        self.loop_region = region;
+        self.send(PlaybackCommand::SetLoop(region));
+        true
+    }
+
+    /// Cut later passes of the playing loop from a fresh stream.
+    pub fn update_loop_source(&self, source: ArrangementSource) {
+        self.send(PlaybackCommand::UpdateLoopSource(Box::new(source)));
+    }
+
     /// Send a command to the playback engine.
     pub fn send(&self, cmd: PlaybackCommand) {
         if self.command_tx.send(cmd).is_err() {
@@ -113,13 +253,28 @@ impl PlaybackEngine {
 
     /// Play audio samples starting at a given cursor position.
     pub fn play_samples(&self, samples: Vec<f64>, sample_rate: u32, start_cursor_s: f64) {
+        self.play_channels(vec![samples], sample_rate, start_cursor_s);
+    }
+
+    /// Play planar audio (e.g. a stereo render) starting at a given cursor position.
+    pub fn play_channels(&self, channels: Vec<Vec<f64>>, sample_rate: u32, start_cursor_s: f64) {
         self.send(PlaybackCommand::PlaySamples {
-            samples,
+            channels,
             sample_rate,
             start_cursor_s,
         });
     }
 
+    /// Stream an arrangement from a cursor position without pre-rendering it,
+    /// after an optional lead-in (pass an empty vec for none).
+    pub fn play_source(&self, source: ArrangementSource, start_cursor_s: f64, lead_in: Vec<f64>) {
+        self.send(PlaybackCommand::PlaySource {
+            source: Box::new(source),
+            start_cursor_s,
+            lead_in,
+        });
+    }
+
     /// Stop playback.
     pub fn stop(&self) {
         self.send(PlaybackCommand::Stop);
@@ -136,95 +291,218 @@ impl PlaybackEngine {
     }
 }
 
-fn process_command(
-    cmd: PlaybackCommand,
-    stream_handle: Option<&rodio::OutputStreamHandle>,
-    sink: &mut Option<Sink>,
-    play_start: &mut Option<(Instant, f64)>,
-    state: &PlaybackState,
-) {
+/// Playback thread state.
+struct Player {
+    output: Option<AudioOutput>,
+    /// Recreated for each play command because `Sink::stop()` permanently
+    /// kills the sink (sets a stopped flag that prevents new sources).
+    sink: Option<Sink>,
+    /// (wall start, cursor start) of the current playback.
+    play_start: Option<(Instant, f64)>,
+    /// Region applied to the next play command.
+    loop_region: Option<LoopRegion>,
+    /// Loop of the current playback, if it is looping.
+    looper: Option<Looper>,
+}
+
+impl Player {
+    /// Start `material` from `start_cursor_s`, looping if a region is set
+    /// and the cursor is before its end.
+    fn play(
+        &mut self,
+        material: LoopMaterial,
+        lead_in: Option<SamplesBuffer<f32>>,
+        start_cursor_s: f64,
+        end_s: f64,
+        state: &PlaybackState,
+    ) -> bool {
+        self.looper = None;
+        let region = self.loop_region.filter(|r| start_cursor_s < r.end_s);
+        let first = material.pass(start_cursor_s, region.map_or(end_s, |r| r.end_s));
+        let started = start_sink(
+            first,
+            lead_in,
+            self.output.as_ref(),
+            &mut self.sink,
+            &mut self.play_start,
+            start_cursor_s,
+            state,
+        );
+        if started {
+            self.looper = region.map(|region| Looper { region, material });
+            self.queue_loop_pass();
+        }
+        started
+    }
+
+    /// Keep the next loop pass queued behind the playing one so the loop
+    /// point is gapless.
+    fn queue_loop_pass(&self) {
+        if let (Some(looper), Some(sink)) = (&self.looper, &self.sink) {
+            if sink.len() < 2 {
+                sink.append(looper.material.pass(looper.region.start_s, looper.region.end_s));
+            }
+        }
+    }
+
+    /// Drop the sink and forget where playback started.
+    fn halt(&mut self, state: &PlaybackState) {
+        drop(self.sink.take());
+        self.play_start = None;
+        self.looper = None;
+        *state.is_playing.lock().unwrap() = false;
+    }
+}
+
+fn process_command(cmd: PlaybackCommand, player: &mut Player, state: &PlaybackState) {
     match cmd {
         PlaybackCommand::PlaySamples {
-            samples,
+            channels,
             sample_rate: sr,
             start_cursor_s,
         } => {
-            if samples.is_empty() {
+            let n_samples = channels.iter().map(Vec::len).min().unwrap_or(0);
+            if n_samples == 0 {
                 log::warn!("PlaySamples received empty audio buffer");
                 return;
             }
-            if let Some(handle) = stream_handle {
-                // Drop old sink, create a fresh one
-                drop(sink.take());
-                match Sink::try_new(handle) {
-                    Ok(new_sink) => {
-                        // Convert f64 → f32 and use rodio's built-in SamplesBuffer
-                        // (most battle-tested Source path through rodio internals)
-                        let f32_samples: Vec<f32> =
-                            samples.iter().map(|&s| s as f32).collect();
-                        let source = SamplesBuffer::new(1, sr, f32_samples);
-                        new_sink.append(source);
-                        new_sink.play();
-                        *sink = Some(new_sink);
-                        *play_start = Some((Instant::now(), start_cursor_s));
-                        *state.is_playing.lock().unwrap() = true;
-                        log::debug!(
-                            "Playing {} samples at {} Hz from cursor {:.3}s",
-                            samples.len(),
-                            sr,
-                            start_cursor_s
-                        );
-                    }
-                    Err(e) => {
-                        log::error!("Failed to create audio sink: {}", e);
-                        state.set_error(format!("Audio sink: {}", e));
-                    }
-                }
-            } else {
-                state.set_error("No audio output device available".into());
+            // Interleave as f32 and use rodio's built-in SamplesBuffer
+            // (most battle-tested Source path through rodio internals)
+            let end_s = start_cursor_s + n_samples as f64 / sr as f64;
+            let material = LoopMaterial::Samples {
+                samples: Arc::new(
+                    (0..n_samples)
+                        .flat_map(|i| channels.iter().map(move |c| c[i] as f32))
+                        .collect(),
+                ),
+                channels: channels.len() as u16,
+                sample_rate: sr,
+                base_s: start_cursor_s,
+            };
+            if player.play(material, None, start_cursor_s, end_s, state) {
+                log::debug!(
+                    "Playing {} samples at {} Hz from cursor {:.3}s",
+                    n_samples,
+                    sr,
+                    start_cursor_s
+                );
+            }
+        }
+        PlaybackCommand::PlaySource {
+            source,
+            start_cursor_s,
+            lead_in,
+        } => {
+            if source.remaining_samples() == 0 {
+                log::warn!("PlaySource received an empty arrangement");
+                return;
+            }
+            let lead_in = (!lead_in.is_empty()).then(|| {
+                let samples: Vec<f32> = lead_in.iter().map(|&s| s as f32).collect();
+                SamplesBuffer::new(1, source.sample_rate(), samples)
+            });
+            let end_s = start_cursor_s + source.remaining_samples() as f64 / source.sample_rate() as f64;
+            if player.play(LoopMaterial::Source(source), lead_in, start_cursor_s, end_s, state) {
+                log::debug!("Streaming arrangement from cursor {:.3}s", start_cursor_s);
             }
         }
         PlaybackCommand::Pause => {
-            if let Some(ref s) = sink {
+            if let Some(ref s) = player.sink {
                 s.pause();
                 *state.is_playing.lock().unwrap() = false;
             }
         }
         PlaybackCommand::Resume => {
-            if let Some(ref s) = sink {
+            if let Some(ref s) = player.sink {
                 s.play();
                 *state.is_playing.lock().unwrap() = true;
             }
         }
         PlaybackCommand::Stop => {
-            drop(sink.take());
-            *play_start = None;
-            *state.is_playing.lock().unwrap() = false;
+            player.halt(state);
             *state.cursor_s.lock().unwrap() = 0.0;
         }
+        PlaybackCommand::SetBufferSize(buffer_size) => {
+            player.halt(state);
+            // Close the old stream before opening the device again
+            drop(player.output.take());
+            player.output = open_output(buffer_size, state);
+        }
+        PlaybackCommand::SetLoop(region) => player.loop_region = region,
+        PlaybackCommand::UpdateLoopSource(source) => {
+            if let Some(looper) = player.looper.as_mut() {
+                if matches!(looper.material, LoopMaterial::Source(_)) {
+                    looper.material = LoopMaterial::Source(source);
+                }
+            }
+        }
     }
 }
 
-fn playback_thread(rx: mpsc::Receiver<PlaybackCommand>, state: PlaybackState) {
-    // Try to open audio output; if it fails, the thread just consumes commands.
-    // OutputStream must stay alive for the entire thread lifetime.
-    let audio = match OutputStream::try_default() {
-        Ok(pair) => {
+/// Replace the current sink with a fresh one playing `source`, preceded by
+/// `lead_in` if given. The cursor holds at `start_cursor_s` until the
+/// lead-in finishes.
+///
+/// Returns false (with the error stored in `state`) if playback could not start.
+fn start_sink<S>(
+    source: S,
+    lead_in: Option<SamplesBuffer<f32>>,
+    output: Option<&AudioOutput>,
+    sink: &mut Option<Sink>,
+    play_start: &mut Option<(Instant, f64)>,
+    start_cursor_s: f64,
+    state: &PlaybackState,
+) -> bool
+where
+    S: Source<Item = f32> + Send + 'static,
+{
+    let Some(output) = output else {
+        state.set_error("No audio output device available".into());
+        return false;
+    };
+    // Drop old sink, create a fresh one
+    drop(sink.take());
+    let new_sink = output.new_sink();
+    let lead = lead_in
+        .as_ref()
+        .and_then(|l| l.total_duration())
+        .unwrap_or_default();
+    if let Some(lead_in) = lead_in {
+        new_sink.append(lead_in);
+    }
+    new_sink.append(source);
+    new_sink.play();
+    *sink = Some(new_sink);
+    *play_start = Some((Instant::now() + lead, start_cursor_s));
+    *state.is_playing.lock().unwrap() = true;
+    true
+}
+
+/// Open the output device, storing the error in `state` on failure.
+fn open_output(buffer_size: BufferSize, state: &PlaybackState) -> Option<AudioOutput> {
+    match AudioOutput::open(buffer_size) {
+        Ok(output) => {
             log::info!("Playback engine: audio device opened successfully");
-            Some(pair)
+            Some(output)
         }
         Err(e) => {
-            log::error!("Failed to open audio output: {}", e);
-            state.set_error(format!("Audio device: {}", e));
+            log::error!("Failed to open audio output: {:#}", e);
+            state.set_error(format!("Audio device: {:#}", e));
             None
         }
-    };
-    let stream_handle = audio.as_ref().map(|(_, h)| h);
+    }
+}
 
-    // Sink is recreated for each PlaySamples command because Sink::stop()
-    // permanently kills the sink (sets a stopped flag that prevents new sources).
-    let mut sink: Option<Sink> = None;
-    let mut play_start: Option<(Instant, f64)> = None; // (wall_start, cursor_start)
+fn playback_thread(rx: mpsc::Receiver<PlaybackCommand>, state: PlaybackState, buffer_size: BufferSize) {
+    // Try to open audio output; if it fails, the thread just consumes commands.
+    // The output stream must stay alive until it is replaced or the thread ends.
+    let mut player = Player {
+        output: open_output(buffer_size, &state),
+        sink: None,
+        play_start: None,
+        loop_region: None,
+        looper: None,
+    };
 
     loop {
         // Wait for a command (blocks up to 10ms, then falls through for cursor updates).
@@ -232,16 +510,10 @@ fn playback_thread(rx: mpsc::Receiver<PlaybackCommand>, state: PlaybackState) {
         // where a separate disconnect-check try_recv would silently consume commands.
         match rx.recv_timeout(Duration::from_millis(10)) {
             Ok(cmd) => {
-                process_command(cmd, stream_handle, &mut sink, &mut play_start, &state);
+                process_command(cmd, &mut player, &state);
                 // Drain any additional pending commands without blocking
                 while let Ok(cmd) = rx.try_recv() {
-                    process_command(
-                        cmd,
-                        stream_handle,
-                        &mut sink,
-                        &mut play_start,
-                        &state,
-                    );
+                    process_command(cmd, &mut player, &state);
                 }
             }
             Err(mpsc::RecvTimeoutError::Timeout) => {
@@ -253,16 +525,25 @@ fn playback_thread(rx: mpsc::Receiver<PlaybackCommand>, state: PlaybackState) {
             }
         }
 
+        player.queue_loop_pass();
+
         // Update cursor position
-        if let Some((start_instant, start_cursor)) = play_start {
-            if let Some(ref s) = sink {
+        if let Some((start_instant, start_cursor)) = player.play_start {
+            if let Some(ref s) = player.sink {
                 if s.empty() {
                     // Playback finished
                     *state.is_playing.lock().unwrap() = false;
-                    play_start = None;
+                    player.play_start = None;
+                    player.looper = None;
                 } else if !s.is_paused() {
-                    let elapsed = start_instant.elapsed().as_secs_f64();
-                    *state.cursor_s.lock().unwrap() = start_cursor + elapsed;
+                    // Zero while a lead-in is still playing
+                    let elapsed = Instant::now()
+                        .saturating_duration_since(start_instant)
+                        .as_secs_f64();
+                    *state.cursor_s.lock().unwrap() = match &player.looper {
+                        Some(looper) => looper.region.cursor_at(start_cursor, elapsed),
+                        None => start_cursor + elapsed,
+                    };
                 }
             }
         }
@@ -280,6 +561,55 @@ mod tests {
         assert!(!state.is_playing());
     }
 
+    #[test]
+    fn test_loop_region_orders_markers_and_wraps_cursor() {
+        let region = LoopRegion::new(3.0, 1.0).unwrap();
+        assert_eq!((region.start_s, region.end_s), (1.0, 3.0));
+        assert!(LoopRegion::new(1.0, 1.01).is_none());
+        assert!(region.contains(1.0) && !region.contains(3.0));
+        // Starting before the region plays into it, then wraps
+        assert_eq!(region.cursor_at(0.5, 1.0), 1.5);
+        assert_eq!(region.cursor_at(0.5, 2.5), 1.0);
+        assert_eq!(region.cursor_at(0.5, 3.0), 1.5);
+        assert_eq!(region.cursor_at(2.0, 1.5), 1.5);
+    }
+
+    #[test]
+    fn test_loop_pass_cuts_samples_at_region() {
+        let material = LoopMaterial::Samples {
+            samples: Arc::new((0..16000).map(|i| i as f32).collect()),
+            channels: 1,
+            sample_rate: 16000,
+            base_s: 0.5,
+        };
+        let pass: Vec<f32> = material.pass(0.75, 1.0).collect();
+        assert_eq!(pass.len(), 4000);
+        assert_eq!(pass[0], 4000.0);
+        assert_eq!(material.pass(2.0, 3.0).count(), 0);
+
+        // Stereo material is cut on frame boundaries
+        let material = LoopMaterial::Samples {
+            samples: Arc::new((0..32000).map(|i| i as f32).collect()),
+            channels: 2,
+            sample_rate: 16000,
+            base_s: 0.5,
+        };
+        let pass: Vec<f32> = material.pass(0.75, 1.0).collect();
+        assert_eq!(pass.len(), 8000);
+        assert_eq!(&pass[..2], &[8000.0, 8001.0]);
+    }
+
+    #[test]
+    fn test_set_loop_only_sends_changes() {
+        let mut engine = PlaybackEngine::new();
+        let region = LoopRegion::new(0.0, 1.0);
+        assert!(engine.set_loop(region));
+        assert!(!engine.set_loop(region));
+        assert_eq!(engine.loop_region(), region);
+        assert!(engine.set_loop(None));
+        engine.stop();
+    }
+
     #[test]
     fn test_playback_engine_creation() {
         // Just verify it doesn't panic
@@ -303,6 +633,16 @@ mod tests {
         assert!(state.take_error().is_none());
     }
 
+    #[test]
+    fn test_set_buffer_size_only_sends_changes() {
+        let mut engine = PlaybackEngine::with_buffer_size(BufferSize::Frames(256));
+        assert_eq!(engine.buffer_size(), BufferSize::Frames(256));
+        engine.set_buffer_size(BufferSize::Frames(256));
+        engine.set_buffer_size(BufferSize::Default);
+        assert_eq!(engine.buffer_size(), BufferSize::Default);
+        engine.stop();
+    }
+
     #[test]
     fn test_playback_engine_reports_empty_play() {
         let engine = PlaybackEngine::new();
@@ -317,7 +657,7 @@ mod tests {
     fn test_play_command_not_silently_eaten() {
         let engine = PlaybackEngine::new();
 
-        // Wait for the thread to enter its main loop (past OutputStream init)
+        // Wait for the thread to enter its main loop (past audio device init)
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         // Generate a 0.5s tone
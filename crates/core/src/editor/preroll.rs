@@ -0,0 +1,99 @@
+//! Pre-roll and count-in before editor playback.
+
+/// Beats per count-in bar (4/4).
+const BEATS_PER_BAR: u32 = 4;
+
+/// Click length in seconds.
+const CLICK_S: f64 = 0.03;
+
+/// What plays before the cursor when playback starts.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreRoll {
+    /// Start exactly at the cursor.
+    Off,
+    /// Start this many seconds before the cursor.
+    Seconds(f64),
+    /// Metronome clicks for `bars` bars of 4/4 at `bpm`, then the cursor.
+    CountIn { bars: u32, bpm: f64 },
+}
+
+impl PreRoll {
+    /// Where arrangement playback starts for a given cursor.
+    pub fn start_s(&self, cursor_s: f64) -> f64 {
+        match *self {
+            Self::Seconds(s) => (cursor_s - s.max(0.0)).max(0.0),
+            Self::Off | Self::CountIn { .. } => cursor_s,
+        }
+    }
+
+    /// Click track played before the arrangement (empty unless counting in).
+    pub fn lead_in(&self, sample_rate: u32) -> Vec<f64> {
+        match *self {
+            Self::CountIn { bars, bpm } => count_in_clicks(bars, bpm, sample_rate),
+            Self::Off | Self::Seconds(_) => Vec::new(),
+        }
+    }
+}
+
+/// Render a metronome count-in: one click per beat, accented downbeats.
+pub fn count_in_clicks(bars: u32, bpm: f64, sample_rate: u32) -> Vec<f64> {
+    if bars == 0 || bpm <= 0.0 {
+        return Vec::new();
+    }
+    let sr = sample_rate as f64;
+    let beat_samples = (60.0 / bpm * sr).round() as usize;
+    let beats = (bars * BEATS_PER_BAR) as usize;
+    let click_samples = ((CLICK_S * sr) as usize).min(beat_samples);
+    let mut out = vec![0.0; beat_samples * beats];
+
+    for beat in 0..beats {
+        let downbeat = beat % BEATS_PER_BAR as usize == 0;
+        let (freq, amp) = if downbeat {
+            (1500.0, 0.6)
+        } else {
+            (1000.0, 0.4)
+        };
+        let offset = beat * beat_samples;
+        for i in 0..click_samples {
+            let t = i as f64 / sr;
+            let env = 1.0 - i as f64 / click_samples as f64;
+            out[offset + i] = (t * freq * std::f64::consts::TAU).sin() * amp * env;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_in_length_and_downbeats() {
+        let clicks = count_in_clicks(2, 120.0, 16000);
+        // 2 bars of 4/4 at 120 BPM = 4 seconds
+        assert_eq!(clicks.len(), 64000);
+        // Each beat starts with a click, silence before the next
+        let beat = 8000;
+        for b in 0..8 {
+            let peak = clicks[b * beat..b * beat + 480].iter().fold(0.0f64, |m, s| m.max(s.abs()));
+            assert!(peak > 0.2, "beat {} should click", b);
+            assert_eq!(clicks[b * beat + 4000], 0.0);
+        }
+        let first = clicks[..480].iter().fold(0.0f64, |m, s| m.max(s.abs()));
+        let second = clicks[beat..beat + 480].iter().fold(0.0f64, |m, s| m.max(s.abs()));
+        assert!(first > second, "downbeat should be accented");
+    }
+
+    #[test]
+    fn test_pre_roll_start_and_lead_in() {
+        assert_eq!(PreRoll::Off.start_s(3.0), 3.0);
+        assert_eq!(PreRoll::Seconds(2.0).start_s(3.0), 1.0);
+        assert_eq!(PreRoll::Seconds(2.0).start_s(0.5), 0.0);
+        assert!(PreRoll::Seconds(2.0).lead_in(16000).is_empty());
+
+        let count_in = PreRoll::CountIn { bars: 1, bpm: 60.0 };
+        assert_eq!(count_in.start_s(3.0), 3.0);
+        assert_eq!(count_in.lead_in(16000).len(), 64000);
+        assert!(count_in_clicks(0, 120.0, 16000).is_empty());
+    }
+}
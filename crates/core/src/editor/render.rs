@@ -3,16 +3,22 @@
 use std::collections::HashMap;
 use std::path::Path;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use super::effects_chain::apply_effects;
-use super::types::{Arrangement, ClipId, SyllableClip};
+use super::automation::Automation;
+use super::clip_audio::ClipAudio;
+use super::effects_chain::apply_effects_owned;
+use super::master_chain::apply_master_effects;
+use super::types::{crossfade_gain, Arrangement, ClipEffect, ClipId, SyllableClip};
 use crate::audio::analysis::{compute_rms, generate_pink_noise};
-use crate::audio::effects::{mix_audio, time_stretch};
-use crate::audio::io::write_wav;
+use crate::audio::effects::{limit_peaks, map_channels, mix_audio, pan_gains, time_stretch};
+use crate::audio::encode::{write_audio, OutputFormat};
+use crate::audio::io::{mix_to_mono, Channels};
 use crate::collage::process::apply_prosodic_dynamics;
+use crate::progress::{NoProgress, ProgressReporter};
 
 /// Settings that control how an arrangement is rendered to audio.
+#[derive(Debug, Clone)]
 pub struct RenderSettings {
     pub crossfade_ms: f64,
     pub volume_normalize: bool,
@@ -25,6 +31,8 @@ pub struct RenderSettings {
     pub breath_probability: f64,
     pub speed: Option<f64>,
     pub seed: Option<u64>,
+    /// Run exports through a lookahead peak limiter before writing.
+    pub limiter: bool,
 }
 
 impl Default for RenderSettings {
@@ -41,6 +49,7 @@ impl Default for RenderSettings {
             breath_probability: 0.6,
             speed: None,
             seed: None,
+            limiter: true,
         }
     }
 }
@@ -62,17 +71,43 @@ impl RenderSettings {
             breath_probability: 0.0,
             speed: None,
             seed: None,
+            limiter: false,
         }
     }
 }
 
 /// Render the full arrangement to a contiguous audio buffer.
 ///
-/// Uses overlap-add: each clip's audio (with effects applied) is placed
-/// at its timeline position into the output buffer.
+/// Uses overlap-add: each clip's audio (with effects and automation
+/// applied) is placed at its timeline position into the output buffer.
 pub fn render_arrangement(arrangement: &Arrangement, settings: &RenderSettings) -> Result<Vec<f64>> {
+    let mut channels = render_arrangement_channels(arrangement, settings, Channels::Mono)?;
+    Ok(channels.pop().unwrap_or_default())
+}
+
+/// Render the arrangement to planar audio in the given channel layout.
+///
+/// In stereo, each clip is placed in the field by its `pan` position and
+/// clips of stereo sources keep their image. Mono output uses each clip's
+/// mono mix and ignores pan, so previews match the classic render.
+pub fn render_arrangement_channels(
+    arrangement: &Arrangement,
+    settings: &RenderSettings,
+    layout: Channels,
+) -> Result<Vec<Vec<f64>>> {
+    render_arrangement_channels_with_progress(arrangement, settings, layout, &NoProgress)
+}
+
+/// Like [`render_arrangement_channels`], reporting "Rendering" progress per
+/// clip and stopping with an error once `progress` is cancelled.
+pub fn render_arrangement_channels_with_progress(
+    arrangement: &Arrangement,
+    settings: &RenderSettings,
+    layout: Channels,
+    progress: &dyn ProgressReporter,
+) -> Result<Vec<Vec<f64>>> {
     if arrangement.timeline.is_empty() {
-        return Ok(vec![]);
+        return Ok(vec![Vec::new(); layout.count()]);
     }
 
     let sr = arrangement.sample_rate;
@@ -88,56 +123,65 @@ pub fn render_arrangement(arrangement: &Arrangement, settings: &RenderSettings)
     let total_duration_s = arrangement.total_duration_s();
     let total_samples = (total_duration_s * sr as f64).ceil() as usize;
 
-    let mut output = vec![0.0f64; total_samples];
+    let mut output = vec![vec![0.0f64; total_samples]; layout.count()];
 
     let cf_samples = (settings.crossfade_ms / 1000.0 * sr as f64).round() as usize;
 
     // Collect per-clip rendered audio
-    let mut clip_buffers: Vec<(usize, Vec<f64>)> = Vec::new();
-    for timeline_clip in &arrangement.timeline {
+    let fades = arrangement.clip_fades(cf_samples);
+    let mut placed_clips: Vec<PlacedClip> = Vec::new();
+    let n_clips = arrangement.timeline.len();
+    for (clip_index, timeline_clip) in arrangement.timeline.iter().enumerate() {
+        if progress.cancelled() {
+            bail!("Render cancelled");
+        }
         let source = bank_map
             .get(&timeline_clip.source_clip_id)
             .ok_or_else(|| anyhow::anyhow!("Missing source clip in bank"))?;
 
-        let processed = apply_effects(&source.samples, sr, &timeline_clip.effects)?;
         let start_idx = (timeline_clip.position_s * sr as f64).round() as usize;
-        clip_buffers.push((start_idx, processed));
+        placed_clips.push(place_clip(
+            &timeline_clip.trimmed_audio(source),
+            &timeline_clip.effects,
+            &arrangement.automation,
+            timeline_clip.pan,
+            sr,
+            start_idx,
+            layout,
+        )?);
+        progress.report("Rendering", 0.7 * (clip_index + 1) as f64 / n_clips as f64);
     }
 
     // Mix with crossfade
-    for (clip_index, (start_idx, processed)) in clip_buffers.iter().enumerate() {
-        for (i, &sample) in processed.iter().enumerate() {
-            let out_idx = start_idx + i;
-            if out_idx >= output.len() {
+    for (placed, &clip_fades) in placed_clips.iter().zip(&fades) {
+        let len = placed.len();
+        for i in 0..len {
+            let out_idx = placed.start + i;
+            if out_idx >= total_samples {
                 break;
             }
 
-            let mut gain = 1.0;
-
-            // Fade-in at start of clip (except first clip)
-            if cf_samples > 0 && clip_index > 0 && i < cf_samples {
-                let t = i as f64 / cf_samples as f64;
-                gain = (t * std::f64::consts::FRAC_PI_2).sin();
-            }
+            let gain = crossfade_gain(i, len, clip_fades);
 
-            // Fade-out at end of clip (except last clip)
-            let samples_from_end = processed.len().saturating_sub(1).saturating_sub(i);
-            if cf_samples > 0 && clip_index < clip_buffers.len() - 1 && samples_from_end < cf_samples {
-                let t = samples_from_end as f64 / cf_samples as f64;
-                gain *= (t * std::f64::consts::FRAC_PI_2).sin();
+            for (ch, channel) in output.iter_mut().enumerate() {
+                channel[out_idx] += placed.sample(ch, i) * gain;
             }
-
-            output[out_idx] += sample * gain;
         }
     }
 
+    progress.report("Rendering", 0.75);
+
     // --- Volume normalize (peak to -1dB) ---
     if settings.volume_normalize {
-        let peak = output.iter().map(|s| s.abs()).fold(0.0f64, f64::max);
+        let peak = output
+            .iter()
+            .flatten()
+            .map(|s| s.abs())
+            .fold(0.0f64, f64::max);
         if peak > 1e-10 {
             let target = 10.0f64.powf(-1.0 / 20.0); // -1dB
             let gain = target / peak;
-            for s in output.iter_mut() {
+            for s in output.iter_mut().flatten() {
                 *s *= gain;
             }
         }
@@ -145,25 +189,30 @@ pub fn render_arrangement(arrangement: &Arrangement, settings: &RenderSettings)
 
     // --- Prosodic dynamics ---
     if settings.prosodic_dynamics {
-        apply_prosodic_dynamics(&mut output, sr);
+        for channel in output.iter_mut() {
+            apply_prosodic_dynamics(channel, sr);
+        }
     }
 
     // --- Room tone (mix into silent gaps) ---
     if settings.room_tone && !arrangement.room_tone_clips.is_empty() {
-        let overall_rms = compute_rms(&output);
+        let mono = mix_to_mono(&output);
+        let overall_rms = compute_rms(&mono);
         if overall_rms > 1e-10 {
             let threshold = overall_rms * 0.05;
             let window = (sr as f64 * 0.025) as usize; // 25ms
             let mut i = 0;
             let mut rt_idx = 0;
-            while i + window < output.len() {
-                let frame: &[f64] = &output[i..i + window];
+            while i + window < mono.len() {
+                let frame: &[f64] = &mono[i..i + window];
                 let frame_rms = compute_rms(frame);
                 if frame_rms < threshold {
                     let rt = &arrangement.room_tone_clips[rt_idx % arrangement.room_tone_clips.len()];
-                    for (j, &rt_sample) in rt.iter().enumerate() {
-                        if i + j < output.len() {
-                            output[i + j] += rt_sample * 0.3;
+                    for channel in output.iter_mut() {
+                        for (j, &rt_sample) in rt.iter().enumerate() {
+                            if i + j < channel.len() {
+                                channel[i + j] += rt_sample * 0.3;
+                            }
                         }
                     }
                     rt_idx += 1;
@@ -176,29 +225,13 @@ pub fn render_arrangement(arrangement: &Arrangement, settings: &RenderSettings)
     }
 
     // --- Breaths (insert at clip boundaries) ---
-    if settings.breaths && settings.breath_probability > 0.0 && !arrangement.breath_clips.is_empty() {
-        use rand::Rng;
-        use rand::rngs::StdRng;
-        use rand::SeedableRng;
-        let mut rng = match settings.seed {
-            Some(s) => StdRng::seed_from_u64(s),
-            None => StdRng::from_entropy(),
-        };
-        for i in 0..arrangement.timeline.len().saturating_sub(1) {
-            if rng.gen::<f64>() >= settings.breath_probability {
-                continue;
-            }
-            let clip_end_s = arrangement.timeline[i].position_s
-                + arrangement.timeline[i].effective_duration_s;
-            let next_start_s = arrangement.timeline[i + 1].position_s;
-            let gap_s = next_start_s - clip_end_s;
-            if gap_s < 0.05 { continue; }
-            let breath = &arrangement.breath_clips[rng.gen_range(0..arrangement.breath_clips.len())];
-            let insert_idx = (clip_end_s * sr as f64).round() as usize;
+    for (insert_idx, breath_idx) in breath_schedule(arrangement, settings) {
+        let breath = &arrangement.breath_clips[breath_idx];
+        for channel in output.iter_mut() {
             for (j, &sample) in breath.iter().enumerate() {
                 let out_idx = insert_idx + j;
-                if out_idx < output.len() {
-                    output[out_idx] += sample * 0.5;
+                if out_idx < channel.len() {
+                    channel[out_idx] += sample * 0.5;
                 }
             }
         }
@@ -206,35 +239,162 @@ pub fn render_arrangement(arrangement: &Arrangement, settings: &RenderSettings)
 
     // --- Pink noise bed ---
     if settings.noise_level_db < -0.1 || settings.noise_level_db > 0.1 {
-        let dur = output.len() as f64 / sr as f64;
-        let noise = generate_pink_noise(dur, sr, settings.seed);
-        output = mix_audio(&output, &noise, settings.noise_level_db);
+        for (ch_idx, channel) in output.iter_mut().enumerate() {
+            let dur = channel.len() as f64 / sr as f64;
+            let seed = settings.seed.map(|s| s.wrapping_add(ch_idx as u64));
+            let noise = generate_pink_noise(dur, sr, seed);
+            *channel = mix_audio(channel, &noise, settings.noise_level_db);
+        }
+    }
+
+    if progress.cancelled() {
+        bail!("Render cancelled");
     }
+    progress.report("Rendering", 0.85);
 
     // --- Global speed ---
     if let Some(speed) = settings.speed {
         if (speed - 1.0).abs() > 0.01 {
             let factor = 1.0 / speed;
-            output = time_stretch(&output, sr, factor)?;
+            output = map_channels(&output, |ch| time_stretch(ch, sr, factor))?;
         }
     }
 
+    // --- Master effects (after speed, so a reverb tail keeps its length) ---
+    if !arrangement.master_effects.is_empty() {
+        progress.report("Master effects", 0.88);
+        output = apply_master_effects(output, &arrangement.master_effects, sr)?;
+    }
+
     Ok(output)
 }
 
-/// Render and write the arrangement to a WAV file.
-pub fn export_arrangement(arrangement: &Arrangement, settings: &RenderSettings, output_path: &Path) -> Result<()> {
-    let samples = render_arrangement(arrangement, settings)?;
-    write_wav(output_path, &samples, arrangement.sample_rate)?;
+/// A timeline clip's processed audio, ready to mix at `start`.
+#[derive(Clone)]
+pub(crate) struct PlacedClip {
+    pub start: usize,
+    /// One buffer for a mono clip, which feeds every output channel; a
+    /// stereo clip feeds each output channel its own.
+    pub channels: Vec<Vec<f32>>,
+    /// Gain per output channel.
+    pub gains: Vec<f64>,
+}
+
+impl PlacedClip {
+    pub fn len(&self) -> usize {
+        self.channels.iter().map(|c| c.len()).min().unwrap_or(0)
+    }
+
+    /// Sample `i` for output channel `ch`, with its gain applied.
+    pub fn sample(&self, ch: usize, i: usize) -> f64 {
+        self.channels[ch.min(self.channels.len() - 1)][i] as f64 * self.gains[ch]
+    }
+}
+
+/// Apply a timeline clip's effects and the arrangement's automation to
+/// `audio`, and work out its gains in `layout`.
+///
+/// Mono output takes the clip's mono mix and ignores pan. In stereo, a
+/// mono clip is panned with constant power, and a stereo clip keeps its
+/// image with its balance shifted toward `pan`, as `effects::pan` does.
+pub(crate) fn place_clip(
+    audio: &ClipAudio,
+    effects: &[ClipEffect],
+    automation: &Automation,
+    pan: f64,
+    sr: u32,
+    start: usize,
+    layout: Channels,
+) -> Result<PlacedClip> {
+    let sources = match layout {
+        Channels::Mono => vec![audio.clone()],
+        Channels::Stereo => (0..audio.channel_count().min(2)).map(|ch| audio.channel(ch)).collect(),
+    };
+    let channels = sources
+        .iter()
+        .map(|source| {
+            let processed = apply_effects_owned(source.to_vec(), sr, effects)?;
+            automation.apply_to_clip(processed, sr, start)
+        })
+        .collect::<Result<Vec<Vec<f32>>>>()?;
+    let gains = match layout {
+        Channels::Mono => vec![1.0],
+        Channels::Stereo => {
+            let (left, right) = pan_gains(pan);
+            // Center leaves a stereo clip untouched
+            let norm = if channels.len() > 1 { std::f64::consts::SQRT_2 } else { 1.0 };
+            vec![left * norm, right * norm]
+        }
+    };
+    Ok(PlacedClip { start, channels, gains })
+}
+
+/// Decide where breaths go: `(output sample index, breath clip index)` pairs.
+///
+/// Breaths are considered at each clip boundary with a gap of at least 50ms
+/// and inserted with `breath_probability`. Deterministic for a given seed.
+pub(crate) fn breath_schedule(arrangement: &Arrangement, settings: &RenderSettings) -> Vec<(usize, usize)> {
+    use rand::Rng;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let mut schedule = Vec::new();
+    if !settings.breaths || settings.breath_probability <= 0.0 || arrangement.breath_clips.is_empty() {
+        return schedule;
+    }
+    let sr = arrangement.sample_rate;
+    let mut rng = match settings.seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => StdRng::from_entropy(),
+    };
+    for i in 0..arrangement.timeline.len().saturating_sub(1) {
+        if rng.gen::<f64>() >= settings.breath_probability {
+            continue;
+        }
+        let clip_end_s = arrangement.timeline[i].position_s
+            + arrangement.timeline[i].effective_duration_s;
+        let next_start_s = arrangement.timeline[i + 1].position_s;
+        let gap_s = next_start_s - clip_end_s;
+        if gap_s < 0.05 { continue; }
+        let breath_idx = rng.gen_range(0..arrangement.breath_clips.len());
+        let insert_idx = (clip_end_s * sr as f64).round() as usize;
+        schedule.push((insert_idx, breath_idx));
+    }
+    schedule
+}
+
+/// Render and write the arrangement to an audio file in `format`.
+///
+/// Writes stereo when any timeline clip is panned off center or comes from
+/// a stereo source, mono otherwise. Nothing is written if `progress` is
+/// cancelled before rendering finishes.
+pub fn export_arrangement(
+    arrangement: &Arrangement,
+    settings: &RenderSettings,
+    output_path: &Path,
+    format: OutputFormat,
+    progress: &dyn ProgressReporter,
+) -> Result<()> {
+    let layout = arrangement.output_layout();
+    let mut channels = render_arrangement_channels_with_progress(arrangement, settings, layout, progress)?;
+    if progress.cancelled() {
+        bail!("Render cancelled");
+    }
+    progress.report("Writing", 0.9);
+    if settings.limiter {
+        channels = limit_peaks(&channels, arrangement.sample_rate);
+    }
+    write_audio(output_path, &channels, arrangement.sample_rate, format)?;
+    progress.report("Writing", 1.0);
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::editor::automation::AutomationParam;
     use crate::editor::types::*;
     use crate::types::{Phoneme, Syllable};
-    use std::path::PathBuf;
 
     fn make_clip(value: f64, duration_samples: usize) -> SyllableClip {
         let syl = Syllable {
@@ -248,8 +408,8 @@ mod tests {
             word: "test".into(),
             word_index: 0,
         };
-        let samples = vec![value; duration_samples];
-        SyllableClip::new(syl, samples, 16000, PathBuf::from("test.wav"))
+        let samples = vec![value as f32; duration_samples];
+        SyllableClip::new(syl, samples.into(), 16000, "test.wav".into())
     }
 
     #[test]
@@ -294,6 +454,47 @@ mod tests {
         assert!((result[1600] - 0.7).abs() < 0.001);
     }
 
+    #[test]
+    fn test_render_honors_trims() {
+        // First half 0.2, second half 0.6
+        let mut samples = vec![0.2f32; 800];
+        samples.extend(vec![0.6f32; 800]);
+        let clip = SyllableClip::new(make_clip(0.0, 1600).syllable, samples.into(), 16000, "test.wav".into());
+        let mut tc = TimelineClip::new(&clip);
+        tc.trim_start_s = 0.05;
+        tc.trim_end_s = 0.025;
+        tc.update_duration(&clip);
+        assert!((tc.effective_duration_s - 0.025).abs() < 1e-9);
+
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip);
+        arr.timeline.push(tc);
+        arr.relayout(0.0);
+
+        let result = render_arrangement(&arr, &RenderSettings::bypass()).unwrap();
+        assert_eq!(result.len(), 400);
+        assert!(result.iter().all(|&s| (s - 0.6).abs() < 0.001));
+    }
+
+    #[test]
+    fn test_render_bakes_gain_automation_across_clips() {
+        let clip1 = make_clip(0.5, 1600);
+        let clip2 = make_clip(0.5, 1600);
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.timeline.push(TimelineClip::new(&clip1));
+        arr.timeline.push(TimelineClip::new(&clip2));
+        arr.bank.extend([clip1, clip2]);
+        arr.relayout(0.0);
+        // Fade out over both clips
+        arr.automation.gain.insert(AutomationParam::Gain, 0.0, 0.0);
+        arr.automation.gain.insert(AutomationParam::Gain, 0.2, -36.0);
+
+        let result = render_arrangement(&arr, &RenderSettings::bypass()).unwrap();
+        assert!((result[0] - 0.5).abs() < 1e-6);
+        assert!((result[1600] - 0.5 * 10f64.powf(-18.0 / 20.0)).abs() < 1e-6);
+        assert!(result[3199] < 0.01);
+    }
+
     #[test]
     fn test_render_with_effects() {
         let clip = make_clip(0.5, 1600);
@@ -329,7 +530,7 @@ mod tests {
         std::fs::create_dir_all(&dir).unwrap();
         let path = dir.join("test_export.wav");
 
-        export_arrangement(&arr, &RenderSettings::bypass(), &path).unwrap();
+        export_arrangement(&arr, &RenderSettings::bypass(), &path, OutputFormat::Wav, &NoProgress).unwrap();
         assert!(path.exists());
         let file_len = std::fs::metadata(&path).unwrap().len();
         assert!(file_len > 0);
@@ -337,6 +538,49 @@ mod tests {
         std::fs::remove_dir_all(&dir).ok();
     }
 
+    #[test]
+    fn test_export_cancelled_writes_nothing() {
+        struct Cancelled;
+        impl ProgressReporter for Cancelled {
+            fn report(&self, _stage: &str, _fraction: f64) {}
+            fn cancelled(&self) -> bool {
+                true
+            }
+        }
+
+        let clip = make_clip(0.5, 1600);
+        let tc = TimelineClip::new(&clip);
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip);
+        arr.timeline.push(tc);
+        arr.relayout(0.0);
+
+        let path = std::env::temp_dir().join(format!("glottisdale_cancelled_{}.wav", std::process::id()));
+        let result = export_arrangement(&arr, &RenderSettings::bypass(), &path, OutputFormat::Wav, &Cancelled);
+        assert!(result.is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_export_reports_progress_to_completion() {
+        let clip = make_clip(0.5, 1600);
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.timeline.push(TimelineClip::new(&clip));
+        arr.timeline.push(TimelineClip::new(&clip));
+        arr.bank.push(clip);
+        arr.relayout(0.0);
+
+        let path = std::env::temp_dir().join(format!("glottisdale_progress_{}.wav", std::process::id()));
+        let seen = std::cell::RefCell::new(Vec::new());
+        let reporter = |_: &str, fraction: f64| seen.borrow_mut().push(fraction);
+        export_arrangement(&arr, &RenderSettings::bypass(), &path, OutputFormat::Wav, &reporter).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        let seen = seen.into_inner();
+        assert!(seen.windows(2).all(|w| w[0] <= w[1]), "progress must not go backwards");
+        assert_eq!(seen.last(), Some(&1.0));
+    }
+
     #[test]
     fn test_render_with_settings_default() {
         let clip = make_clip(0.5, 1600);
@@ -430,6 +674,28 @@ mod tests {
         assert!(ratio < 0.6, "2x speed should halve duration, ratio={}", ratio);
     }
 
+    #[test]
+    fn test_render_master_effects() {
+        let clip = make_clip(0.5, 1600);
+        let tc = TimelineClip::new(&clip);
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip);
+        arr.timeline.push(tc);
+        arr.relayout(0.0);
+
+        let settings = RenderSettings::bypass();
+        let dry = render_arrangement(&arr, &settings).unwrap();
+        arr.master_effects = vec![MasterEffect::Gain { gain_db: -6.0 }];
+        let quiet = render_arrangement(&arr, &settings).unwrap();
+        assert_eq!(quiet.len(), dry.len());
+        assert!((quiet[800] - dry[800] * 10f64.powf(-6.0 / 20.0)).abs() < 1e-9);
+
+        arr.master_effects.push(MasterEffect::Reverb { ir: crate::audio::effects::ReverbIr::Room, send: 0.5 });
+        let wet = render_arrangement(&arr, &settings).unwrap();
+        assert!(wet.len() > dry.len());
+        assert!(wet[dry.len()..].iter().any(|s| s.abs() > 1e-4), "reverb tail is silent");
+    }
+
     #[test]
     fn test_render_settings_default_values() {
         let settings = RenderSettings::default();
@@ -458,4 +724,72 @@ mod tests {
         assert!(settings.speed.is_none());
         assert!(settings.seed.is_none());
     }
+
+    #[test]
+    fn test_render_channels_pans_clips() {
+        let clip = make_clip(0.5, 1600);
+        let mut tc = TimelineClip::new(&clip);
+        tc.pan = -1.0;
+
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip);
+        arr.timeline.push(tc);
+        arr.relayout(0.0);
+
+        let stereo = render_arrangement_channels(&arr, &RenderSettings::bypass(), Channels::Stereo).unwrap();
+        assert_eq!(stereo.len(), 2);
+        assert!((stereo[0][0] - 0.5).abs() < 0.001);
+        assert!(stereo[1][0].abs() < 0.001);
+
+        // Mono render ignores pan
+        let mono = render_arrangement(&arr, &RenderSettings::bypass()).unwrap();
+        assert!((mono[0] - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_render_stereo_clip_keeps_image() {
+        use crate::editor::clip_audio::SourceBuffer;
+
+        let source = SourceBuffer::from_channels(vec![vec![0.6; 1600], vec![0.2; 1600]]);
+        let clip = SyllableClip::from_range(&source, 16000, "stereo.wav".into(), 0.0, 0.1, "a");
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.timeline.push(TimelineClip::new(&clip));
+        arr.bank.push(clip);
+        arr.relayout(0.0);
+        assert_eq!(arr.output_layout(), Channels::Stereo);
+
+        let stereo = render_arrangement_channels(&arr, &RenderSettings::bypass(), Channels::Stereo).unwrap();
+        assert!((stereo[0][800] - 0.6).abs() < 0.001);
+        assert!((stereo[1][800] - 0.2).abs() < 0.001);
+
+        // Panning shifts the balance; mono output takes the mix
+        arr.timeline[0].pan = 1.0;
+        let panned = render_arrangement_channels(&arr, &RenderSettings::bypass(), Channels::Stereo).unwrap();
+        assert!(panned[0][800].abs() < 0.001);
+        assert!((panned[1][800] - 0.2 * std::f64::consts::SQRT_2).abs() < 0.001);
+        let mono = render_arrangement(&arr, &RenderSettings::bypass()).unwrap();
+        assert!((mono[800] - 0.4).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_export_panned_arrangement_is_stereo() {
+        let clip = make_clip(0.5, 1600);
+        let mut tc = TimelineClip::new(&clip);
+        tc.pan = 0.5;
+
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip);
+        arr.timeline.push(tc);
+        arr.relayout(0.0);
+
+        let dir = std::env::temp_dir().join("glottisdale_test_export_stereo");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("stereo.wav");
+
+        export_arrangement(&arr, &RenderSettings::bypass(), &path, OutputFormat::Wav, &NoProgress).unwrap();
+        let (channels, _) = crate::audio::io::read_wav_channels(&path).unwrap();
+        assert_eq!(channels.len(), 2);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
@@ -0,0 +1,379 @@
+//! Draw the timeline (clips, labels, source colors, waveforms) to a PNG or
+//! SVG image without a window, for sharing an arrangement's structure.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use ab_glyph::{point, Font, FontRef, PxScale, ScaleFont};
+use anyhow::{bail, Context, Result};
+
+use super::types::Arrangement;
+use crate::source::SourceId;
+use super::waveform::WaveformData;
+
+/// Colors for clips from the first source files; later sources get
+/// generated colors (see [`default_source_color`]).
+pub const SOURCE_COLORS: &[(u8, u8, u8)] = &[
+    (70, 130, 180),  // steel blue
+    (180, 100, 60),  // terracotta
+    (80, 160, 80),   // green
+    (160, 80, 160),  // purple
+    (180, 160, 50),  // gold
+    (80, 160, 160),  // teal
+];
+
+/// Default color of the `index`th source.
+///
+/// The first sources use [`SOURCE_COLORS`]. After that, hues step by the
+/// golden angle so no two sources share a color and neighbours stay far
+/// apart on the color wheel.
+pub fn default_source_color(index: usize) -> (u8, u8, u8) {
+    if let Some(&color) = SOURCE_COLORS.get(index) {
+        return color;
+    }
+    const GOLDEN_ANGLE_DEG: f64 = 137.507_764;
+    let n = index - SOURCE_COLORS.len();
+    let hue = (15.0 + n as f64 * GOLDEN_ANGLE_DEG) % 360.0;
+    // Alternate brightness so hues that land close still differ
+    let value = if n.is_multiple_of(2) { 0.72 } else { 0.58 };
+    hsv_to_rgb(hue, 0.55, value)
+}
+
+/// Convert hue (degrees), saturation, and value (0-1) to 8-bit RGB.
+fn hsv_to_rgb(hue: f64, saturation: f64, value: f64) -> (u8, u8, u8) {
+    let c = value * saturation;
+    let h = hue / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = value - c;
+    let to_u8 = |v: f64| ((v + m) * 255.0).round() as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+/// Default image width in pixels.
+pub const DEFAULT_WIDTH: u32 = 1600;
+
+const RULER_H: f32 = 18.0;
+const TRACK_H: f32 = 80.0;
+const LABEL_SIZE: f32 = 11.0;
+const RULER_LABEL_SIZE: f32 = 9.0;
+/// Advance of one Hack glyph as a fraction of the font size.
+const CHAR_WIDTH: f32 = 0.6;
+
+type Rgba = [u8; 4];
+
+const BACKGROUND: Rgba = [30, 30, 30, 255];
+const RULER_COLOR: Rgba = [150, 150, 150, 255];
+const LABEL_COLOR: Rgba = [230, 230, 230, 255];
+
+/// One drawing primitive, shared by the PNG and SVG back ends.
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Rect { x: f32, y: f32, w: f32, h: f32, radius: f32, color: Rgba },
+    /// Vertical line from `y0` to `y1`.
+    VLine { x: f32, y0: f32, y1: f32, color: Rgba },
+    /// Text with its baseline at `y`.
+    Text { x: f32, y: f32, size: f32, text: String, color: Rgba },
+}
+
+/// Source file → color index, in order of first appearance in the bank.
+fn source_indices(arrangement: &Arrangement) -> HashMap<SourceId, usize> {
+    let mut indices = HashMap::new();
+    for clip in &arrangement.bank {
+        let next = indices.len();
+        indices.entry(clip.source).or_insert(next);
+    }
+    indices
+}
+
+/// Ruler tick spacing giving at least ~60 px between labels.
+fn tick_interval(pixels_per_second: f32) -> f64 {
+    [0.1, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 30.0, 60.0]
+        .into_iter()
+        .find(|&t| t as f32 * pixels_per_second >= 60.0)
+        .unwrap_or(120.0)
+}
+
+/// Waveform columns as vertical lines, one per pixel.
+fn waveform_lines(waveform: &WaveformData, x: f32, y: f32, w: f32, h: f32, color: Rgba, out: &mut Vec<Shape>) {
+    let n_buckets = waveform.peaks.len();
+    if n_buckets == 0 || w < 1.0 || h < 1.0 {
+        return;
+    }
+    let mid = y + h / 2.0;
+    let half = h * 0.45;
+    for px in 0..w as usize {
+        let start = (px as f32 / w * n_buckets as f32) as usize;
+        let end = (((px + 1) as f32 / w * n_buckets as f32).ceil() as usize).clamp(start + 1, n_buckets);
+        let (lo, hi) = waveform.peaks[start.min(n_buckets - 1)..end]
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &(a, b)| (lo.min(a), hi.max(b)));
+        if lo <= hi {
+            out.push(Shape::VLine {
+                x: x + px as f32 + 0.5,
+                y0: mid - hi * half,
+                y1: mid - lo * half,
+                color,
+            });
+        }
+    }
+}
+
+/// Lay out the timeline at `width` pixels; returns shapes and image height.
+fn layout(arrangement: &Arrangement, width: u32) -> (Vec<Shape>, u32) {
+    let width_f = width as f32;
+    let height = RULER_H + TRACK_H;
+    let duration = arrangement.total_duration_s().max(1e-3);
+    let pps = width_f / duration as f32;
+    let mut shapes = vec![Shape::Rect { x: 0.0, y: 0.0, w: width_f, h: height, radius: 0.0, color: BACKGROUND }];
+
+    // Time ruler
+    let interval = tick_interval(pps);
+    let mut n = 0usize;
+    loop {
+        let t = n as f64 * interval;
+        let x = t as f32 * pps;
+        if x > width_f {
+            break;
+        }
+        shapes.push(Shape::VLine { x: x + 0.5, y0: RULER_H - 4.0, y1: RULER_H, color: RULER_COLOR });
+        let text = if interval >= 1.0 { format!("{:.0}s", t) } else { format!("{:.2}s", t) };
+        shapes.push(Shape::Text { x: x + 2.0, y: RULER_LABEL_SIZE + 1.0, size: RULER_LABEL_SIZE, text, color: RULER_COLOR });
+        n += 1;
+    }
+
+    // Clips
+    let sources = source_indices(arrangement);
+    for tc in &arrangement.timeline {
+        let Some(clip) = arrangement.get_bank_clip(tc.source_clip_id) else {
+            continue;
+        };
+        let (r, g, b) = arrangement.source_color(clip.source, sources.get(&clip.source).copied().unwrap_or(0));
+        let x = tc.position_s as f32 * pps;
+        let w = (tc.effective_duration_s as f32 * pps).max(1.0);
+        shapes.push(Shape::Rect { x, y: RULER_H, w, h: TRACK_H, radius: 3.0, color: [r, g, b, 77] });
+        waveform_lines(&tc.trimmed_waveform(clip), x + 2.0, RULER_H + 14.0, w - 4.0, TRACK_H - 16.0, [r, g, b, 255], &mut shapes);
+
+        let max_chars = ((w - 4.0) / (LABEL_SIZE * CHAR_WIDTH)).floor().max(0.0) as usize;
+        let text: String = clip.label.chars().take(max_chars).collect();
+        if !text.is_empty() {
+            shapes.push(Shape::Text { x: x + 2.0, y: RULER_H + LABEL_SIZE, size: LABEL_SIZE, text, color: LABEL_COLOR });
+        }
+    }
+
+    (shapes, height as u32)
+}
+
+fn svg_color(c: Rgba) -> String {
+    format!("rgb({},{},{})", c[0], c[1], c[2])
+}
+
+fn svg_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Render the timeline as an SVG document.
+pub fn timeline_svg(arrangement: &Arrangement, width: u32) -> String {
+    let (shapes, height) = layout(arrangement, width);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width,
+        h = height
+    );
+    for shape in &shapes {
+        match shape {
+            Shape::Rect { x, y, w, h, radius, color } => {
+                let _ = writeln!(
+                    svg,
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"{}\" fill=\"{}\" fill-opacity=\"{:.2}\"/>",
+                    x, y, w, h, radius, svg_color(*color), color[3] as f32 / 255.0
+                );
+            }
+            Shape::VLine { x, y0, y1, color } => {
+                let _ = writeln!(
+                    svg,
+                    "<line x1=\"{x:.1}\" y1=\"{:.1}\" x2=\"{x:.1}\" y2=\"{:.1}\" stroke=\"{}\"/>",
+                    y0, y1.max(y0 + 1.0), svg_color(*color)
+                );
+            }
+            Shape::Text { x, y, size, text, color } => {
+                let _ = writeln!(
+                    svg,
+                    "<text x=\"{:.1}\" y=\"{:.1}\" font-family=\"Hack, monospace\" font-size=\"{}\" fill=\"{}\">{}</text>",
+                    x, y, size, svg_color(*color), svg_escape(text)
+                );
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Blend `color` at `coverage` over the pixel at (x, y).
+fn blend(img: &mut image::RgbaImage, x: i32, y: i32, color: Rgba, coverage: f32) {
+    if x < 0 || y < 0 || x >= img.width() as i32 || y >= img.height() as i32 {
+        return;
+    }
+    let a = color[3] as f32 / 255.0 * coverage.clamp(0.0, 1.0);
+    let px = img.get_pixel_mut(x as u32, y as u32);
+    for (dst, &src) in px.0.iter_mut().zip(&color[..3]) {
+        *dst = (src as f32 * a + *dst as f32 * (1.0 - a)).round() as u8;
+    }
+    px.0[3] = 255;
+}
+
+/// Whether (px, py) falls outside the rounded corners of a rect.
+fn outside_corner(px: f32, py: f32, x: f32, y: f32, w: f32, h: f32, r: f32) -> bool {
+    let r = r.min(w / 2.0).min(h / 2.0);
+    let cx = px.clamp(x + r, x + w - r);
+    let cy = py.clamp(y + r, y + h - r);
+    (px - cx).powi(2) + (py - cy).powi(2) > r * r
+}
+
+/// Render the timeline as PNG bytes.
+pub fn timeline_png(arrangement: &Arrangement, width: u32) -> Result<Vec<u8>> {
+    let (shapes, height) = layout(arrangement, width);
+    let font = FontRef::try_from_slice(epaint_default_fonts::HACK_REGULAR).context("Failed to load label font")?;
+    let mut img = image::RgbaImage::new(width, height);
+
+    for shape in &shapes {
+        match shape {
+            Shape::Rect { x, y, w, h, radius, color } => {
+                for py in y.floor() as i32..(y + h).ceil() as i32 {
+                    for px in x.floor() as i32..(x + w).ceil() as i32 {
+                        let (cx, cy) = (px as f32 + 0.5, py as f32 + 0.5);
+                        if *radius > 0.0 && outside_corner(cx, cy, *x, *y, *w, *h, *radius) {
+                            continue;
+                        }
+                        blend(&mut img, px, py, *color, 1.0);
+                    }
+                }
+            }
+            Shape::VLine { x, y0, y1, color } => {
+                for py in y0.floor() as i32..=y1.floor() as i32 {
+                    blend(&mut img, x.floor() as i32, py, *color, 1.0);
+                }
+            }
+            Shape::Text { x, y, size, text, color } => {
+                let scale = PxScale::from(*size);
+                let scaled = font.as_scaled(scale);
+                let mut caret = *x;
+                for ch in text.chars() {
+                    let id = font.glyph_id(ch);
+                    let glyph = id.with_scale_and_position(scale, point(caret, *y));
+                    caret += scaled.h_advance(id);
+                    if let Some(outlined) = font.outline_glyph(glyph) {
+                        let bounds = outlined.px_bounds();
+                        outlined.draw(|gx, gy, coverage| {
+                            blend(
+                                &mut img,
+                                bounds.min.x as i32 + gx as i32,
+                                bounds.min.y as i32 + gy as i32,
+                                *color,
+                                coverage,
+                            );
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    let mut bytes = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .context("Failed to encode PNG")?;
+    Ok(bytes)
+}
+
+/// Write a timeline image, choosing PNG or SVG from the file extension.
+pub fn export_timeline_image(arrangement: &Arrangement, path: &Path, width: u32) -> Result<()> {
+    let ext = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let bytes = match ext.as_str() {
+        "png" => timeline_png(arrangement, width)?,
+        "svg" => timeline_svg(arrangement, width).into_bytes(),
+        _ => bail!("Unsupported image format for {} (use .png or .svg)", path.display()),
+    };
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+    std::fs::write(path, bytes).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::types::{EditorPipelineMode, SyllableClip, TimelineClip};
+
+    fn arrangement() -> Arrangement {
+        let samples: Vec<f64> = (0..8000).map(|i| (i as f64 * 0.05).sin() * 0.5).collect();
+        let samples = crate::editor::bank_builder::shared_source(&samples);
+        let a = SyllableClip::from_range(&samples, 16000, "a.wav".into(), 0.0, 0.25, "ka");
+        let b = SyllableClip::from_range(&samples, 16000, "b.wav".into(), 0.0, 0.25, "<bo>");
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.timeline.push(TimelineClip::new(&a));
+        arr.timeline.push(TimelineClip::new(&b));
+        arr.bank.push(a);
+        arr.bank.push(b);
+        arr.relayout(0.0);
+        arr
+    }
+
+    #[test]
+    fn test_svg_has_clips_labels_and_source_colors() {
+        let svg = timeline_svg(&arrangement(), 400);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">ka</text>"));
+        assert!(svg.contains(">&lt;bo&gt;</text>"));
+        let (r, g, b) = SOURCE_COLORS[1];
+        assert!(svg.contains(&format!("stroke=\"rgb({},{},{})\"", r, g, b)));
+    }
+
+    #[test]
+    fn test_custom_source_color_is_drawn() {
+        let mut arr = arrangement();
+        arr.set_source_color("b.wav".into(), Some((1, 2, 3)));
+        let svg = timeline_svg(&arr, 400);
+        assert!(svg.contains("stroke=\"rgb(1,2,3)\""));
+    }
+
+    #[test]
+    fn test_default_source_colors_are_distinct() {
+        let colors: Vec<(u8, u8, u8)> = (0..24).map(default_source_color).collect();
+        assert_eq!(colors[..SOURCE_COLORS.len()], *SOURCE_COLORS);
+        for (i, a) in colors.iter().enumerate() {
+            for b in &colors[i + 1..] {
+                let distance = (a.0 as i32 - b.0 as i32).abs() + (a.1 as i32 - b.1 as i32).abs() + (a.2 as i32 - b.2 as i32).abs();
+                assert!(distance > 12, "{:?} and {:?} are too close", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_png_is_decodable_at_requested_width() {
+        let bytes = timeline_png(&arrangement(), 400).unwrap();
+        let img = image::load_from_memory(&bytes).unwrap().to_rgba8();
+        assert_eq!(img.width(), 400);
+        assert_eq!(img.height(), (RULER_H + TRACK_H) as u32);
+        // Second clip is tinted with the second source color, not background
+        let px = img.get_pixel(300, (RULER_H + 4.0) as u32).0;
+        assert_ne!(&px[..3], &BACKGROUND[..3]);
+    }
+
+    #[test]
+    fn test_export_rejects_unknown_extension() {
+        let path = std::env::temp_dir().join("timeline.bmp");
+        assert!(export_timeline_image(&arrangement(), &path, 400).is_err());
+    }
+}
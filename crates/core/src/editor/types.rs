@@ -1,11 +1,43 @@
 //! Editor data model: syllable clips, timeline clips, arrangements.
 
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::audio::analysis::SyllableQuality;
+use crate::audio::effects::{Filter, ReverbIr, VoiceFx};
+use crate::audio::encode::encode_flac;
+use crate::audio::io::{decode_audio_bytes_native, write_wav_channels, Channels};
+use crate::source::{relative_to, relink, resolve_moved, Relink, SourceId};
 use crate::types::Syllable;
+use super::automation::Automation;
+use super::bank_builder::{
+    aligned_clip, cut_range_audio, extract_context_clips, read_source_channels, shared_source_channels,
+};
+use super::clip_audio::{ClipAudio, SourceBuffer};
+use super::effects_chain::compute_effective_duration;
+use super::timeline_image::default_source_color;
 use super::waveform::WaveformData;
 
+/// File extension for saved editor projects.
+pub const PROJECT_EXTENSION: &str = "glott";
+
+/// Current project file format version.
+const PROJECT_VERSION: u32 = 2;
+
+/// First bytes of a project saved with its audio (a zip archive).
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+/// Project JSON inside a project saved with its audio.
+const EMBEDDED_PROJECT_ENTRY: &str = "project.json";
+
+/// Folder of FLAC sources inside a project saved with its audio.
+const EMBEDDED_AUDIO_DIR: &str = "audio";
+
 /// Unique identifier for a clip.
 pub type ClipId = Uuid;
 
@@ -15,24 +47,30 @@ pub struct SyllableClip {
     pub id: ClipId,
     /// Source syllable metadata (phonemes, word, timing in source).
     pub syllable: Syllable,
-    /// Raw audio samples (f64, mono).
-    pub samples: Vec<f64>,
+    /// Audio samples (mono): a view of the shared source buffer, so
+    /// cloning a clip does not copy audio.
+    pub samples: ClipAudio,
     pub sample_rate: u32,
-    /// Path to source audio file.
-    pub source_path: PathBuf,
+    /// Source audio file.
+    pub source: SourceId,
     /// Pre-computed waveform thumbnail.
     pub waveform: WaveformData,
     /// Display label (e.g. "K AE1 T").
     pub label: String,
+    /// Cut by hand from a source time range rather than from alignment.
+    pub manual: bool,
+    /// Audio quality of the aligned syllable; `None` for manual clips
+    /// and imported samples.
+    pub quality: Option<SyllableQuality>,
 }
 
 impl SyllableClip {
     /// Create a new SyllableClip, computing the waveform automatically.
     pub fn new(
         syllable: Syllable,
-        samples: Vec<f64>,
+        samples: ClipAudio,
         sample_rate: u32,
-        source_path: PathBuf,
+        source: SourceId,
     ) -> Self {
         let label = syllable
             .phonemes
@@ -40,18 +78,46 @@ impl SyllableClip {
             .map(|p| p.label.as_str())
             .collect::<Vec<_>>()
             .join(" ");
-        let waveform = WaveformData::new(&samples);
+        let waveform = WaveformData::new(&samples.to_vec::<f32>());
         Self {
             id: Uuid::new_v4(),
             syllable,
             samples,
             sample_rate,
-            source_path,
+            source,
             waveform,
             label,
+            manual: false,
+            quality: None,
         }
     }
 
+    /// Create a manual clip from an exact time range of a source.
+    ///
+    /// Bypasses alignment: the clip has no phonemes, and `label` is used
+    /// as both its display label and word text.
+    pub fn from_range(
+        source: &SourceBuffer,
+        sample_rate: u32,
+        source_id: SourceId,
+        start_s: f64,
+        end_s: f64,
+        label: &str,
+    ) -> Self {
+        let syllable = Syllable {
+            phonemes: Vec::new(),
+            start: start_s,
+            end: end_s,
+            word: label.to_string(),
+            word_index: 0,
+        };
+        let samples = cut_range_audio(source, sample_rate, start_s, end_s);
+        let mut clip = Self::new(syllable, samples, sample_rate, source_id);
+        clip.label = label.to_string();
+        clip.manual = true;
+        clip
+    }
+
     /// Duration in seconds.
     pub fn duration_s(&self) -> f64 {
         self.samples.len() as f64 / self.sample_rate as f64
@@ -59,16 +125,146 @@ impl SyllableClip {
 }
 
 /// A non-destructive effect applied to a timeline clip.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ClipEffect {
     Stutter { count: usize },
     TimeStretch { factor: f64 },
     PitchShift { semitones: f64 },
     Reverse,
+    /// One filter-bank stage: high/low/band-pass, bit-crush, or saturation.
+    Filter(Filter),
+    /// Another effect blended with its input (`wet` 0-1) at an output gain.
+    Blend { effect: Box<ClipEffect>, wet: f64, gain_db: f64 },
+}
+
+impl ClipEffect {
+    /// The effect without any blend around it.
+    pub fn base(&self) -> &ClipEffect {
+        match self {
+            ClipEffect::Blend { effect, .. } => effect.base(),
+            effect => effect,
+        }
+    }
+
+    /// Wet share and output gain: fully wet at 0 dB unless blended.
+    pub fn wet_gain(&self) -> (f64, f64) {
+        match self {
+            ClipEffect::Blend { wet, gain_db, .. } => (*wet, *gain_db),
+            _ => (1.0, 0.0),
+        }
+    }
+
+    /// This effect at `wet` share and `gain_db` output gain, replacing any
+    /// earlier blend. Fully wet at 0 dB is the bare effect.
+    pub fn blended(self, wet: f64, gain_db: f64) -> ClipEffect {
+        let effect = match self {
+            ClipEffect::Blend { effect, .. } => *effect,
+            effect => effect,
+        };
+        let wet = wet.clamp(0.0, 1.0);
+        if wet >= 1.0 && gain_db == 0.0 {
+            effect
+        } else {
+            ClipEffect::Blend { effect: Box::new(effect), wet, gain_db }
+        }
+    }
+}
+
+/// A named effect chain that can be applied to any timeline clip.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectPreset {
+    pub name: String,
+    pub effects: Vec<ClipEffect>,
+}
+
+impl EffectPreset {
+    /// Presets offered in every project. A project preset with the same
+    /// name takes their place.
+    pub fn builtin() -> Vec<EffectPreset> {
+        let mut presets = vec![
+            EffectPreset {
+                name: "glitchy".to_string(),
+                effects: vec![
+                    ClipEffect::Stutter { count: 3 },
+                    ClipEffect::PitchShift { semitones: 3.0 },
+                    ClipEffect::Reverse.blended(0.5, 0.0),
+                ],
+            },
+            EffectPreset {
+                name: "slow ghost".to_string(),
+                effects: vec![
+                    ClipEffect::TimeStretch { factor: 2.0 },
+                    ClipEffect::PitchShift { semitones: -5.0 },
+                    ClipEffect::Reverse.blended(0.4, -3.0),
+                ],
+            },
+        ];
+        presets.extend([VoiceFx::Telephone, VoiceFx::Radio, VoiceFx::Megaphone].map(|fx| EffectPreset {
+            name: fx.name().to_string(),
+            effects: fx.filters().into_iter().map(ClipEffect::Filter).collect(),
+        }));
+        presets
+    }
+}
+
+/// An effect on the arrangement's master bus, run over the whole mix
+/// after rendering. The chain runs in order.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MasterEffect {
+    Gain { gain_db: f64 },
+    /// Three-band EQ: low shelf, mid peak, and high shelf gains in dB.
+    Eq { low_db: f64, mid_db: f64, high_db: f64 },
+    /// Peak limiter holding the mix at or below `ceiling_db` dBFS.
+    Limiter { ceiling_db: f64 },
+    /// Reverb added on top of the dry mix at `send` level, 0 to 1.
+    Reverb { ir: ReverbIr, send: f64 },
+}
+
+impl MasterEffect {
+    /// One effect of each kind at its starting settings, as offered when
+    /// adding to the chain.
+    pub fn defaults() -> [MasterEffect; 4] {
+        [
+            MasterEffect::Gain { gain_db: 0.0 },
+            MasterEffect::Eq { low_db: 0.0, mid_db: 0.0, high_db: 0.0 },
+            MasterEffect::Limiter { ceiling_db: crate::audio::effects::LIMITER_CEILING_DBFS },
+            MasterEffect::Reverb { ir: ReverbIr::Room, send: 0.2 },
+        ]
+    }
+
+    /// Kind name, e.g. "Limiter".
+    pub fn name(&self) -> &'static str {
+        match self {
+            MasterEffect::Gain { .. } => "Gain",
+            MasterEffect::Eq { .. } => "EQ",
+            MasterEffect::Limiter { .. } => "Limiter",
+            MasterEffect::Reverb { .. } => "Reverb",
+        }
+    }
+
+    /// Whether the effect leaves audio unchanged and can be skipped.
+    pub fn is_neutral(&self) -> bool {
+        match *self {
+            MasterEffect::Gain { gain_db } => gain_db == 0.0,
+            MasterEffect::Eq { low_db, mid_db, high_db } => low_db == 0.0 && mid_db == 0.0 && high_db == 0.0,
+            MasterEffect::Limiter { .. } => false,
+            MasterEffect::Reverb { send, .. } => send <= 0.0,
+        }
+    }
 }
 
+/// Reverb send as saved by projects from before the master chain.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct LegacyMasterReverb {
+    ir: ReverbIr,
+    send: f64,
+}
+
+/// Shortest part of a bank clip a timeline clip can be trimmed to (seconds).
+pub const MIN_TRIMMED_S: f64 = 0.01;
+
 /// A clip placed on the timeline.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimelineClip {
     pub id: ClipId,
     /// References a SyllableClip in the bank by ID.
@@ -79,6 +275,15 @@ pub struct TimelineClip {
     pub effects: Vec<ClipEffect>,
     /// Duration in seconds after effects. Recomputed when effects change.
     pub effective_duration_s: f64,
+    /// Stereo position from -1.0 (left) to 1.0 (right); 0.0 is center.
+    #[serde(default)]
+    pub pan: f64,
+    /// Seconds cut off the start of the bank clip, before effects.
+    #[serde(default)]
+    pub trim_start_s: f64,
+    /// Seconds cut off the end of the bank clip, before effects.
+    #[serde(default)]
+    pub trim_end_s: f64,
 }
 
 impl TimelineClip {
@@ -90,18 +295,95 @@ impl TimelineClip {
             position_s: 0.0,
             effects: Vec::new(),
             effective_duration_s: source_clip.duration_s(),
+            pan: 0.0,
+            trim_start_s: 0.0,
+            trim_end_s: 0.0,
+        }
+    }
+
+    /// Whether the effects play the clip backwards (an odd number of
+    /// reverses), putting its trimmed start on the right.
+    pub fn is_reversed(&self) -> bool {
+        self.effects.iter().filter(|e| matches!(e, ClipEffect::Reverse)).count() % 2 == 1
+    }
+
+    /// Length of the bank clip left after trimming (seconds).
+    pub fn trimmed_duration_s(&self, source_duration_s: f64) -> f64 {
+        (source_duration_s - self.trim_start_s - self.trim_end_s).max(0.0)
+    }
+
+    /// Samples of `source` left after trimming: `(skip, len)`.
+    fn trimmed_range(&self, source: &SyllableClip) -> (usize, usize) {
+        let sr = source.sample_rate as f64;
+        let skip = ((self.trim_start_s * sr).round() as usize).min(source.samples.len());
+        let len = ((self.trimmed_duration_s(source.duration_s()) * sr).round() as usize)
+            .min(source.samples.len() - skip);
+        (skip, len)
+    }
+
+    /// The part of the bank clip's audio this clip plays, before effects.
+    pub fn trimmed_audio(&self, source: &SyllableClip) -> ClipAudio {
+        let (skip, len) = self.trimmed_range(source);
+        source.samples.slice(skip, len)
+    }
+
+    /// The bank clip's waveform cut to the trimmed part.
+    pub fn trimmed_waveform<'a>(&self, source: &'a SyllableClip) -> std::borrow::Cow<'a, WaveformData> {
+        if self.trim_start_s <= 0.0 && self.trim_end_s <= 0.0 {
+            return std::borrow::Cow::Borrowed(&source.waveform);
         }
+        let (skip, len) = self.trimmed_range(source);
+        let bucket = source.waveform.samples_per_bucket.max(1);
+        let peaks = &source.waveform.peaks;
+        let first = (skip / bucket).min(peaks.len());
+        let last = (skip + len).div_ceil(bucket).clamp(first, peaks.len());
+        std::borrow::Cow::Owned(WaveformData {
+            peaks: peaks[first..last].to_vec(),
+            samples_per_bucket: bucket,
+        })
+    }
+
+    /// Recompute `effective_duration_s` from the trimmed bank clip and the
+    /// effects, after either changes.
+    pub fn update_duration(&mut self, source: &SyllableClip) {
+        self.effective_duration_s =
+            compute_effective_duration(self.trimmed_duration_s(source.duration_s()), &self.effects);
     }
 }
 
+/// A melody note in a sing arrangement, shown as a reference lane above
+/// the clips that sing it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MelodyNote {
+    /// MIDI pitch.
+    pub pitch: u8,
+    pub start_s: f64,
+    pub end_s: f64,
+    /// Pitch shift applied to the clips singing this note (semitones).
+    pub semitones: f64,
+    /// Timeline clips singing this note, in order.
+    pub clip_ids: Vec<ClipId>,
+}
+
 /// Which pipeline produced the arrangement.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum EditorPipelineMode {
     Collage,
     Sing,
     Speak,
 }
 
+/// How timeline clip positions are determined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LayoutMode {
+    /// Clips play back to back in timeline order; positions are derived.
+    #[default]
+    Sequential,
+    /// Clips keep their own `position_s`. Gaps stay silent and overlapping
+    /// clips crossfade across the overlap.
+    Free,
+}
+
 /// Full state of a syllable arrangement.
 #[derive(Debug, Clone)]
 pub struct Arrangement {
@@ -119,6 +401,18 @@ pub struct Arrangement {
     pub room_tone_clips: Vec<Vec<f64>>,
     /// Breath clips extracted from source audio for inserting between clips.
     pub breath_clips: Vec<Vec<f64>>,
+    /// Whether clips are packed back to back or placed freely.
+    pub layout_mode: LayoutMode,
+    /// Melody notes of a sing arrangement; empty for other pipelines.
+    pub melody: Vec<MelodyNote>,
+    /// Effect presets saved in this project.
+    pub effect_presets: Vec<EffectPreset>,
+    /// Colors picked for source files, in place of the default palette.
+    pub source_colors: HashMap<SourceId, (u8, u8, u8)>,
+    /// Effects on the rendered mix, in order.
+    pub master_effects: Vec<MasterEffect>,
+    /// Gain and pitch envelopes over the timeline.
+    pub automation: Automation,
 }
 
 impl Arrangement {
@@ -132,6 +426,12 @@ impl Arrangement {
             source_pipeline: pipeline,
             room_tone_clips: Vec::new(),
             breath_clips: Vec::new(),
+            layout_mode: LayoutMode::Sequential,
+            melody: Vec::new(),
+            effect_presets: Vec::new(),
+            source_colors: HashMap::new(),
+            master_effects: Vec::new(),
+            automation: Automation::default(),
         }
     }
 
@@ -140,16 +440,40 @@ impl Arrangement {
         self.bank.iter().find(|c| c.id == id)
     }
 
-    /// Total duration of the arrangement in seconds.
+    /// Channel layout for playback and export: stereo when a timeline
+    /// clip is panned off center or comes from a stereo source.
+    pub fn output_layout(&self) -> Channels {
+        let stereo_clips: HashSet<ClipId> = self
+            .bank
+            .iter()
+            .filter(|c| c.samples.channel_count() > 1)
+            .map(|c| c.id)
+            .collect();
+        if self.timeline.iter().any(|c| c.pan.abs() > 1e-6 || stereo_clips.contains(&c.source_clip_id)) {
+            Channels::Stereo
+        } else {
+            Channels::Mono
+        }
+    }
+
+    /// Total duration of the arrangement in seconds (end of the latest clip).
     pub fn total_duration_s(&self) -> f64 {
         self.timeline
-            .last()
+            .iter()
             .map(|c| c.position_s + c.effective_duration_s)
-            .unwrap_or(0.0)
+            .fold(0.0, f64::max)
     }
 
-    /// Recompute sequential positions for all timeline clips.
+    /// Recompute positions for all timeline clips.
+    ///
+    /// Sequential layouts pack clips back to back with `gap_s` between
+    /// them. Free layouts keep every position and only re-sort the timeline
+    /// by it.
     pub fn relayout(&mut self, gap_s: f64) {
+        if self.layout_mode == LayoutMode::Free {
+            self.timeline.sort_by(|a, b| a.position_s.total_cmp(&b.position_s));
+            return;
+        }
         let mut cursor = 0.0;
         for clip in &mut self.timeline {
             clip.position_s = cursor;
@@ -158,7 +482,13 @@ impl Arrangement {
     }
 
     /// Recompute positions with crossfade overlap between adjacent clips.
+    ///
+    /// Free layouts are only re-sorted, as in [`relayout`](Self::relayout).
     pub fn relayout_with_crossfade(&mut self, crossfade_ms: f64) {
+        if self.layout_mode == LayoutMode::Free {
+            self.relayout(0.0);
+            return;
+        }
         let overlap_s = crossfade_ms / 1000.0;
         let count = self.timeline.len();
         let mut cursor = 0.0;
@@ -170,6 +500,614 @@ impl Arrangement {
             }
         }
     }
+
+    /// Switch layout mode. Clips keep their current positions, so switching
+    /// to free placement starts from the packed layout.
+    pub fn set_layout_mode(&mut self, mode: LayoutMode) {
+        self.layout_mode = mode;
+        self.relayout(0.0);
+    }
+
+    /// Trim a timeline clip to play its bank clip without the first
+    /// `trim_start_s` and last `trim_end_s` seconds, keeping at least
+    /// [`MIN_TRIMMED_S`] of it, and re-layout.
+    ///
+    /// In free layout the untrimmed audio stays where it was on the
+    /// timeline, so trimming the left edge moves the clip's start.
+    /// Returns false when the clip or its bank clip is unknown.
+    pub fn trim_clip(&mut self, index: usize, trim_start_s: f64, trim_end_s: f64) -> bool {
+        let Some(tc) = self.timeline.get(index) else {
+            return false;
+        };
+        let Some(source) = self.bank.iter().find(|c| c.id == tc.source_clip_id) else {
+            return false;
+        };
+        let duration = source.duration_s();
+        let room = (duration - MIN_TRIMMED_S).max(0.0);
+        let trim_start_s = trim_start_s.clamp(0.0, room);
+        let trim_end_s = trim_end_s.clamp(0.0, room - trim_start_s);
+
+        let tc = &mut self.timeline[index];
+        let trimmed = tc.trimmed_duration_s(duration);
+        // Timeline seconds per second of source audio, through the effects
+        let scale = if trimmed > 0.0 { tc.effective_duration_s / trimmed } else { 1.0 };
+        let left = |start: f64, end: f64| if tc.is_reversed() { end } else { start };
+        let moved = left(trim_start_s, trim_end_s) - left(tc.trim_start_s, tc.trim_end_s);
+        if self.layout_mode == LayoutMode::Free {
+            tc.position_s = (tc.position_s + moved * scale).max(0.0);
+        }
+        tc.trim_start_s = trim_start_s;
+        tc.trim_end_s = trim_end_s;
+        tc.update_duration(source);
+        self.relayout(0.0);
+        true
+    }
+
+    /// Split a timeline clip in two at `time_s` on the timeline, and
+    /// re-layout. The halves keep the clip's effects and pan, and trims
+    /// divide its bank clip's audio between them, so together they play
+    /// what the clip did. A sung clip's later half sings the same note.
+    ///
+    /// Returns the later half's ID, or None when the clip is unknown or
+    /// `time_s` is not inside it, at least [`MIN_TRIMMED_S`] of audio
+    /// from either end.
+    pub fn split_clip(&mut self, index: usize, time_s: f64) -> Option<ClipId> {
+        let tc = self.timeline.get(index)?;
+        let source = self.bank.iter().find(|c| c.id == tc.source_clip_id)?;
+        if tc.effective_duration_s <= 0.0 {
+            return None;
+        }
+        let trimmed = tc.trimmed_duration_s(source.duration_s());
+        // Source seconds played before the split
+        let before = (time_s - tc.position_s) / tc.effective_duration_s * trimmed;
+        if !(MIN_TRIMMED_S..=trimmed - MIN_TRIMMED_S).contains(&before) {
+            return None;
+        }
+
+        let mut earlier = tc.clone();
+        let mut later = tc.clone();
+        later.id = Uuid::new_v4();
+        if tc.is_reversed() {
+            // Reversed, the timeline's left plays the end of the bank clip
+            earlier.trim_start_s += trimmed - before;
+            later.trim_end_s += before;
+        } else {
+            earlier.trim_end_s += trimmed - before;
+            later.trim_start_s += before;
+        }
+        earlier.update_duration(source);
+        later.update_duration(source);
+        later.position_s = earlier.position_s + earlier.effective_duration_s;
+
+        let (id, later_id) = (earlier.id, later.id);
+        self.timeline[index] = earlier;
+        self.timeline.insert(index + 1, later);
+        if let Some(note) = self.melody.iter_mut().find(|n| n.clip_ids.contains(&id)) {
+            note.clip_ids.push(later_id);
+        }
+        self.relayout(0.0);
+        Some(later_id)
+    }
+
+    /// Place a timeline clip at `position_s` (free layout) and re-sort.
+    ///
+    /// Returns the clip's new timeline index.
+    pub fn move_clip(&mut self, index: usize, position_s: f64) -> usize {
+        let id = self.timeline[index].id;
+        self.timeline[index].position_s = position_s.max(0.0);
+        self.relayout(0.0);
+        self.timeline.iter().position(|tc| tc.id == id).unwrap_or(index)
+    }
+
+    /// Display color of clips from `source`, the `index`th source in bank
+    /// order: the picked color, else the default palette's.
+    pub fn source_color(&self, source: SourceId, index: usize) -> (u8, u8, u8) {
+        self.source_colors
+            .get(&source)
+            .copied()
+            .unwrap_or_else(|| default_source_color(index))
+    }
+
+    /// Pick a color for a source file; `None` goes back to the default.
+    pub fn set_source_color(&mut self, source: SourceId, color: Option<(u8, u8, u8)>) {
+        match color {
+            Some(color) => self.source_colors.insert(source, color),
+            None => self.source_colors.remove(&source),
+        };
+    }
+
+    /// Built-in presets followed by this project's, by name.
+    pub fn available_effect_presets(&self) -> Vec<EffectPreset> {
+        let mut presets: Vec<EffectPreset> = EffectPreset::builtin()
+            .into_iter()
+            .filter(|b| !self.effect_presets.iter().any(|p| p.name == b.name))
+            .collect();
+        presets.extend(self.effect_presets.iter().cloned());
+        presets
+    }
+
+    /// Save a timeline clip's effect chain as preset `name`, replacing any
+    /// project preset of that name. Returns false for a blank name or an
+    /// unknown clip.
+    pub fn save_effect_preset(&mut self, name: &str, clip_id: ClipId) -> bool {
+        let name = name.trim();
+        let Some(tc) = self.timeline.iter().find(|tc| tc.id == clip_id) else {
+            return false;
+        };
+        if name.is_empty() {
+            return false;
+        }
+        let preset = EffectPreset { name: name.to_string(), effects: tc.effects.clone() };
+        match self.effect_presets.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = preset,
+            None => self.effect_presets.push(preset),
+        }
+        true
+    }
+
+    /// Replace a timeline clip's effects with preset `name` and re-layout.
+    /// Returns false when the preset or clip is unknown.
+    pub fn apply_effect_preset(&mut self, name: &str, clip_id: ClipId) -> bool {
+        let Some(preset) = self.available_effect_presets().into_iter().find(|p| p.name == name) else {
+            return false;
+        };
+        let Some(tc) = self.timeline.iter_mut().find(|tc| tc.id == clip_id) else {
+            return false;
+        };
+        tc.effects = preset.effects;
+        if let Some(source) = self.bank.iter().find(|c| c.id == tc.source_clip_id) {
+            tc.update_duration(source);
+        }
+        self.relayout(0.0);
+        true
+    }
+
+    /// Index of the melody note sung by a timeline clip.
+    pub fn note_for_clip(&self, clip_id: ClipId) -> Option<usize> {
+        self.melody.iter().position(|n| n.clip_ids.contains(&clip_id))
+    }
+
+    /// Sing melody note `note_index` with a different bank clip.
+    ///
+    /// The note's clips are replaced by one clip of `bank_clip_id`, placed
+    /// at the note start, shifted like the clips it replaces and stretched
+    /// to the note length. Returns the new timeline clip's ID.
+    pub fn swap_note_syllable(&mut self, note_index: usize, bank_clip_id: ClipId) -> Option<ClipId> {
+        let note = self.melody.get(note_index)?;
+        let clip = self.get_bank_clip(bank_clip_id)?;
+        let tc = sung_clip(clip, note.start_s, note.end_s - note.start_s, note.semitones);
+        let id = tc.id;
+        let old = std::mem::replace(&mut self.melody[note_index].clip_ids, vec![id]);
+        self.timeline.retain(|c| !old.contains(&c.id));
+        self.timeline.push(tc);
+        self.relayout(0.0);
+        Some(id)
+    }
+
+    /// Fade-in and fade-out lengths (samples) for each timeline clip.
+    ///
+    /// Sequential layouts crossfade every internal boundary over
+    /// `crossfade_samples`. Free layouts crossfade only where a clip
+    /// overlaps the next one, over the length of the overlap.
+    pub fn clip_fades(&self, crossfade_samples: usize) -> Vec<(usize, usize)> {
+        let n = self.timeline.len();
+        match self.layout_mode {
+            LayoutMode::Sequential => (0..n)
+                .map(|i| {
+                    let fade_in = if i > 0 { crossfade_samples } else { 0 };
+                    let fade_out = if i + 1 < n { crossfade_samples } else { 0 };
+                    (fade_in, fade_out)
+                })
+                .collect(),
+            LayoutMode::Free => {
+                let sr = self.sample_rate as f64;
+                let overlaps: Vec<usize> = self
+                    .timeline
+                    .windows(2)
+                    .map(|pair| {
+                        let end = ((pair[0].position_s + pair[0].effective_duration_s) * sr).round();
+                        let start = (pair[1].position_s * sr).round();
+                        (end - start).max(0.0) as usize
+                    })
+                    .collect();
+                (0..n)
+                    .map(|i| {
+                        let fade_in = if i > 0 { overlaps[i - 1] } else { 0 };
+                        (fade_in, overlaps.get(i).copied().unwrap_or(0))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// A timeline clip singing `clip` at `position_s` for `duration_s`:
+/// pitch-shifted by `semitones` and stretched to fit, within the same
+/// limits as the sing renderer.
+pub(crate) fn sung_clip(clip: &SyllableClip, position_s: f64, duration_s: f64, semitones: f64) -> TimelineClip {
+    let mut tc = TimelineClip::new(clip);
+    tc.position_s = position_s;
+    if semitones.abs() > 0.1 {
+        tc.effects.push(ClipEffect::PitchShift { semitones });
+    }
+    let factor = (duration_s / clip.duration_s()).clamp(0.25, 4.0);
+    if (factor - 1.0).abs() > 0.05 {
+        tc.effects.push(ClipEffect::TimeStretch { factor });
+    }
+    tc.effective_duration_s = compute_effective_duration(clip.duration_s(), &tc.effects);
+    tc
+}
+
+/// Gain for sample `i` of a `len`-sample clip with the given equal-power
+/// fade-in and fade-out lengths.
+pub(crate) fn crossfade_gain(i: usize, len: usize, (fade_in, fade_out): (usize, usize)) -> f64 {
+    let mut gain = 1.0;
+    if fade_in > 0 && i < fade_in {
+        let t = i as f64 / fade_in as f64;
+        gain = (t * std::f64::consts::FRAC_PI_2).sin();
+    }
+    let samples_from_end = len.saturating_sub(1).saturating_sub(i);
+    if fade_out > 0 && samples_from_end < fade_out {
+        let t = samples_from_end as f64 / fade_out as f64;
+        gain *= (t * std::f64::consts::FRAC_PI_2).sin();
+    }
+    gain
+}
+
+/// A bank clip as stored in a project file: audio is re-cut from the source.
+#[derive(Serialize, Deserialize)]
+struct ProjectBankClip {
+    id: ClipId,
+    syllable: Syllable,
+    /// Index into the project's source table.
+    #[serde(default)]
+    source: usize,
+    /// Source path of a format 1 project, which had no source table.
+    #[serde(default, skip_serializing)]
+    source_path: Option<PathBuf>,
+    #[serde(default)]
+    manual: bool,
+}
+
+/// On-disk layout of a `.glott` project file.
+#[derive(Serialize, Deserialize)]
+struct ProjectFile {
+    version: u32,
+    sample_rate: u32,
+    crossfade_ms: f64,
+    source_pipeline: EditorPipelineMode,
+    /// Source files, relative to the project file when saved beside it.
+    #[serde(default)]
+    sources: Vec<PathBuf>,
+    bank: Vec<ProjectBankClip>,
+    timeline: Vec<TimelineClip>,
+    #[serde(default)]
+    layout_mode: LayoutMode,
+    #[serde(default)]
+    melody: Vec<MelodyNote>,
+    #[serde(default)]
+    effect_presets: Vec<EffectPreset>,
+    /// Sorted so saving the same arrangement always writes the same file.
+    /// Keyed by path as written in `sources`.
+    #[serde(default)]
+    source_colors: BTreeMap<PathBuf, (u8, u8, u8)>,
+    #[serde(default)]
+    master_effects: Vec<MasterEffect>,
+    #[serde(default)]
+    automation: Automation,
+    /// Read from older projects into `master_effects`; never written.
+    #[serde(default, skip_serializing)]
+    master_reverb: Option<LegacyMasterReverb>,
+}
+
+/// Sources a project refers to that are neither where it recorded them nor
+/// beside it, nor covered by a relink.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error(
+    "{} source{} missing from project {}: {}",
+    missing.len(),
+    if missing.len() == 1 { "" } else { "s" },
+    project.display(),
+    missing.iter().map(|p| p.display().to_string()).collect::<Vec<_>>().join(", ")
+)]
+pub struct MissingSources {
+    pub project: PathBuf,
+    /// Where each missing source was looked for.
+    pub missing: Vec<PathBuf>,
+}
+
+impl Arrangement {
+    /// Save the arrangement as a `.glott` project file (JSON).
+    ///
+    /// Bank audio is not embedded; clips reference their source WAV files
+    /// and syllable timings, and are re-cut on load. Sources in or below
+    /// the project's folder are saved relative to it, so the folder can be
+    /// moved as a whole.
+    pub fn save_project(&self, path: &Path) -> Result<()> {
+        let base_dir = path.parent();
+        let json = serde_json::to_string_pretty(&self.project_file(&|source| match base_dir {
+            Some(dir) => relative_to(source.path(), dir).to_path_buf(),
+            None => source.path().to_path_buf(),
+        }))?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write project: {}", path.display()))?;
+        Ok(())
+    }
+
+    /// Save the arrangement with its source audio embedded, for sharing
+    /// with someone who doesn't have the sources.
+    ///
+    /// The file is a zip archive holding the project JSON and each bank
+    /// source as FLAC, in its own channels at the project rate. `load_project` unpacks
+    /// the audio into a `<name>-audio` folder beside the project.
+    pub fn save_project_with_audio(&self, path: &Path) -> Result<()> {
+        use std::io::Write;
+
+        let sources = self.bank_sources();
+        let names: HashMap<SourceId, PathBuf> = sources
+            .iter()
+            .enumerate()
+            .map(|(i, &source)| {
+                let stem = source.path().file_stem().unwrap_or_default().to_string_lossy();
+                (source, PathBuf::from(format!("{:02}-{}.wav", i, stem)))
+            })
+            .collect();
+        let project = self.project_file(&|source| names.get(&source).cloned().unwrap_or_else(|| source.path().to_path_buf()));
+
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("Failed to write project: {}", path.display()))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let deflated = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        // FLAC is already compressed
+        let stored = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file(EMBEDDED_PROJECT_ENTRY, deflated)?;
+        zip.write_all(serde_json::to_string_pretty(&project)?.as_bytes())?;
+        for source in sources {
+            let channels = read_source_channels(source.path(), self.sample_rate)
+                .with_context(|| format!("Project source missing: {}", source))?;
+            let entry = Path::new(EMBEDDED_AUDIO_DIR).join(names[&source].with_extension("flac"));
+            zip.start_file(entry.to_string_lossy(), stored)?;
+            zip.write_all(&encode_flac(&channels, self.sample_rate)?)?;
+        }
+        zip.finish()?;
+        Ok(())
+    }
+
+    /// Hash of everything `save_project` writes.
+    ///
+    /// Equal fingerprints mean saving again would write the same project,
+    /// so comparing against the one taken at the last save tells whether
+    /// there are unsaved changes.
+    pub fn project_fingerprint(&self) -> u64 {
+        let json = serde_json::to_vec(&self.project_file(&|source| source.path().to_path_buf())).unwrap_or_default();
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        json.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Sources of the bank's clips, in order of first use.
+    fn bank_sources(&self) -> Vec<SourceId> {
+        let mut sources: Vec<SourceId> = Vec::new();
+        for clip in &self.bank {
+            if !sources.contains(&clip.source) {
+                sources.push(clip.source);
+            }
+        }
+        sources
+    }
+
+    /// The project as written to disk, with each source's path as `stored`
+    /// gives it.
+    fn project_file(&self, stored: &dyn Fn(SourceId) -> PathBuf) -> ProjectFile {
+        let sources = self.bank_sources();
+        let bank = self
+            .bank
+            .iter()
+            .map(|c| ProjectBankClip {
+                id: c.id,
+                syllable: c.syllable.clone(),
+                source: sources.iter().position(|&s| s == c.source).unwrap_or_default(),
+                source_path: None,
+                manual: c.manual,
+            })
+            .collect();
+        ProjectFile {
+            version: PROJECT_VERSION,
+            sample_rate: self.sample_rate,
+            crossfade_ms: self.crossfade_ms,
+            source_pipeline: self.source_pipeline,
+            sources: sources.into_iter().map(stored).collect(),
+            bank,
+            timeline: self.timeline.clone(),
+            layout_mode: self.layout_mode,
+            melody: self.melody.clone(),
+            effect_presets: self.effect_presets.clone(),
+            source_colors: self.source_colors.iter().map(|(&source, &color)| (stored(source), color)).collect(),
+            master_effects: self.master_effects.clone(),
+            automation: self.automation.clone(),
+            master_reverb: None,
+        }
+    }
+
+    /// Load a project saved with `save_project` or
+    /// `save_project_with_audio`.
+    ///
+    /// Re-reads every referenced source WAV; fails with [`MissingSources`]
+    /// if any are missing. A source that moved is found beside the project
+    /// if its file name is unchanged. Room tone and breath clips are
+    /// re-extracted from the sources.
+    pub fn load_project(path: &Path) -> Result<Self> {
+        Self::load_project_relinked(path, &[])
+    }
+
+    /// Like [`load_project`](Self::load_project), pointing sources that are
+    /// not found at the locations `relinks` give them.
+    pub fn load_project_relinked(path: &Path, relinks: &[Relink]) -> Result<Self> {
+        let bytes = std::fs::read(path)
+            .with_context(|| format!("Failed to read project: {}", path.display()))?;
+        let (json, base_dir) = if bytes.starts_with(ZIP_MAGIC) {
+            unpack_embedded_project(path, bytes)?
+        } else {
+            (bytes, path.parent().unwrap_or(Path::new("")).to_path_buf())
+        };
+        let mut project: ProjectFile = serde_json::from_slice(&json)
+            .with_context(|| format!("Invalid project file: {}", path.display()))?;
+        if project.version > PROJECT_VERSION {
+            bail!(
+                "Project {} was saved by a newer version (format {})",
+                path.display(),
+                project.version
+            );
+        }
+        if project.version < 2 {
+            // Format 1 stored each clip's path; build the table from them
+            for clip in &mut project.bank {
+                let source_path = clip.source_path.take().unwrap_or_default();
+                clip.source = project.sources.iter().position(|p| *p == source_path).unwrap_or_else(|| {
+                    project.sources.push(source_path);
+                    project.sources.len() - 1
+                });
+            }
+        }
+
+        let resolve = |stored: &Path| {
+            let found = resolve_moved(stored, &base_dir).unwrap_or_else(|| base_dir.join(stored));
+            SourceId::intern(found)
+        };
+        let mut sources: Vec<SourceId> = project.sources.iter().map(|p| resolve(p)).collect();
+        if let Some(clip) = project.bank.iter().find(|c| c.source >= sources.len()) {
+            bail!("Project {} refers to unknown source {}", path.display(), clip.source);
+        }
+        let mut missing = Vec::new();
+        for (i, source) in sources.iter_mut().enumerate() {
+            if source.path().exists() || !project.bank.iter().any(|c| c.source == i) {
+                continue;
+            }
+            let looked_for = source.path();
+            match relink(*source, &project.sources[i], relinks) {
+                Some(moved) if moved.path().exists() => *source = moved,
+                _ => missing.push(looked_for.to_path_buf()),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(MissingSources { project: path.to_path_buf(), missing }.into());
+        }
+
+        // Sources are read at the project rate, so imported samples at
+        // other rates line up with aligned sources; stereo stays stereo
+        let mut source_audio: HashMap<PathBuf, (Vec<Vec<f64>>, u32)> = HashMap::new();
+        let mut buffers: HashMap<SourceId, SourceBuffer> = HashMap::new();
+        for &source in &sources {
+            if !project.bank.iter().any(|c| sources[c.source] == source) {
+                continue;
+            }
+            let channels = read_source_channels(source.path(), project.sample_rate)
+                .with_context(|| format!("Project source missing: {}", source))?;
+            buffers.insert(source, shared_source_channels(&channels));
+            source_audio.insert(source.path().to_path_buf(), (channels, project.sample_rate));
+        }
+
+        let sr = project.sample_rate;
+        let mut bank = Vec::with_capacity(project.bank.len());
+        for clip in &project.bank {
+            let source = sources[clip.source];
+            let buffer = &buffers[&source];
+            let mut bank_clip = if clip.manual {
+                SyllableClip::from_range(
+                    buffer,
+                    sr,
+                    source,
+                    clip.syllable.start,
+                    clip.syllable.end,
+                    &clip.syllable.word,
+                )
+            } else {
+                aligned_clip(buffer, sr, &clip.syllable, source)
+            };
+            bank_clip.id = clip.id;
+            bank.push(bank_clip);
+        }
+
+        // Manual clips are not speech boundaries, so they don't guide extraction
+        let syllable_pairs: Vec<(Syllable, PathBuf)> = project
+            .bank
+            .into_iter()
+            .filter(|c| !c.manual)
+            .map(|c| (c.syllable, sources[c.source].path().to_path_buf()))
+            .collect();
+        let source_colors = project
+            .source_colors
+            .iter()
+            .map(|(stored, &color)| {
+                let source = match project.sources.iter().position(|p| p == stored) {
+                    Some(i) => sources[i],
+                    None => resolve(stored),
+                };
+                (source, color)
+            })
+            .collect();
+        let (room_tone_clips, breath_clips) = extract_context_clips(&syllable_pairs, &source_audio);
+
+        Ok(Self {
+            bank,
+            timeline: project.timeline,
+            crossfade_ms: project.crossfade_ms,
+            sample_rate: project.sample_rate,
+            source_pipeline: project.source_pipeline,
+            room_tone_clips,
+            breath_clips,
+            layout_mode: project.layout_mode,
+            melody: project.melody,
+            effect_presets: project.effect_presets,
+            source_colors,
+            master_effects: match project.master_reverb {
+                Some(LegacyMasterReverb { ir, send }) if project.master_effects.is_empty() => {
+                    vec![MasterEffect::Reverb { ir, send }]
+                }
+                _ => project.master_effects,
+            },
+            automation: project.automation,
+        })
+    }
+}
+
+/// Unpack a project saved with `save_project_with_audio`.
+///
+/// Sources are written as WAV into a `<name>-audio` folder beside the
+/// project. Returns the project JSON and the folder its sources are
+/// relative to.
+fn unpack_embedded_project(path: &Path, bytes: Vec<u8>) -> Result<(Vec<u8>, PathBuf)> {
+    use std::io::Read;
+
+    let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+        .with_context(|| format!("Invalid project file: {}", path.display()))?;
+    let mut json = Vec::new();
+    archive
+        .by_name(EMBEDDED_PROJECT_ENTRY)
+        .with_context(|| format!("No {} in {}", EMBEDDED_PROJECT_ENTRY, path.display()))?
+        .read_to_end(&mut json)?;
+
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let audio_dir = path.parent().unwrap_or(Path::new("")).join(format!("{}-audio", stem));
+    std::fs::create_dir_all(&audio_dir)
+        .with_context(|| format!("Failed to create {}", audio_dir.display()))?;
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        // Only plain file names directly under audio/, so an entry can't
+        // write outside the folder
+        let Some(name) = entry.enclosed_name().filter(|n| n.parent() == Some(Path::new(EMBEDDED_AUDIO_DIR))) else {
+            continue;
+        };
+        let wav_path = audio_dir.join(Path::new(name.file_name().unwrap_or_default()).with_extension("wav"));
+        let mut flac = Vec::new();
+        entry.read_to_end(&mut flac)?;
+        // At the project rate it was saved at, so nothing is lost to a
+        // round trip through 16kHz
+        let (channels, sr) = decode_audio_bytes_native(flac, Some("flac"), Channels::Stereo)
+            .with_context(|| format!("Failed to decode {} in {}", name.display(), path.display()))?;
+        write_wav_channels(&wav_path, &channels, sr)?;
+    }
+    Ok((json, audio_dir))
 }
 
 #[cfg(test)]
@@ -193,8 +1131,8 @@ mod tests {
 
     fn make_test_clip() -> SyllableClip {
         let syl = make_test_syllable();
-        let samples = vec![0.0f64; 4800]; // 0.3s at 16kHz
-        SyllableClip::new(syl, samples, 16000, PathBuf::from("test.wav"))
+        let samples = vec![0.0f32; 4800]; // 0.3s at 16kHz
+        SyllableClip::new(syl, samples.into(), 16000, "test.wav".into())
     }
 
     #[test]
@@ -289,4 +1227,424 @@ mod tests {
             arr.timeline[1].position_s
         );
     }
+
+    #[test]
+    fn test_free_layout_keeps_positions_and_fades_overlaps() {
+        let clip = make_test_clip(); // 0.3s
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        for position_s in [1.0, 0.0, 0.2] {
+            let mut tc = TimelineClip::new(&clip);
+            tc.position_s = position_s;
+            arr.timeline.push(tc);
+        }
+        arr.bank.push(clip);
+
+        // Sequential: packed back to back, every boundary crossfaded
+        let mut packed = arr.clone();
+        packed.relayout(0.0);
+        assert_eq!(packed.clip_fades(480), vec![(0, 480), (480, 480), (480, 0)]);
+
+        arr.set_layout_mode(LayoutMode::Free);
+        let positions: Vec<f64> = arr.timeline.iter().map(|tc| tc.position_s).collect();
+        assert_eq!(positions, vec![0.0, 0.2, 1.0]);
+        assert!((arr.total_duration_s() - 1.3).abs() < 1e-9);
+        // 0.1s overlap between the first two, silence before the last
+        assert_eq!(arr.clip_fades(480), vec![(0, 1600), (1600, 0), (0, 0)]);
+
+        // Moves clamp at zero; ties keep timeline order
+        let index = arr.move_clip(2, -1.0);
+        assert_eq!(index, 1);
+        assert_eq!(arr.timeline[index].position_s, 0.0);
+    }
+
+    #[test]
+    fn test_trim_clip() {
+        let clip = make_test_clip(); // 0.3s
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.set_layout_mode(LayoutMode::Free);
+        let mut tc = TimelineClip::new(&clip);
+        tc.position_s = 1.0;
+        tc.effects.push(ClipEffect::TimeStretch { factor: 2.0 });
+        tc.update_duration(&clip);
+        arr.timeline.push(tc);
+        arr.bank.push(clip);
+
+        // Trimming the start keeps the rest of the (stretched) audio in place
+        assert!(arr.trim_clip(0, 0.05, 0.1));
+        let tc = &arr.timeline[0];
+        assert!((tc.position_s - 1.1).abs() < 1e-9);
+        assert!((tc.effective_duration_s - 0.3).abs() < 1e-9);
+        assert_eq!(tc.trimmed_audio(&arr.bank[0]).len(), 2400);
+
+        // Reversed, the start trim is on the right: the clip stays put
+        arr.timeline[0].effects.push(ClipEffect::Reverse);
+        assert!(arr.trim_clip(0, 0.1, 0.1));
+        assert!((arr.timeline[0].position_s - 1.1).abs() < 1e-9);
+
+        // Trims always leave some audio
+        assert!(arr.trim_clip(0, 0.25, 0.25));
+        let tc = &arr.timeline[0];
+        assert!((tc.trimmed_duration_s(0.3) - MIN_TRIMMED_S).abs() < 1e-9);
+        assert!(!arr.trim_clip(5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_split_clip() {
+        let clip = make_test_clip(); // 0.3s
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.set_layout_mode(LayoutMode::Free);
+        let mut tc = TimelineClip::new(&clip);
+        tc.position_s = 1.0;
+        tc.trim_start_s = 0.05;
+        tc.effects.push(ClipEffect::TimeStretch { factor: 2.0 });
+        tc.update_duration(&clip);
+        let id = tc.id;
+        arr.timeline.push(tc);
+        arr.bank.push(clip);
+
+        // 0.5s stretched audio; split 0.2s in = 0.1s of the bank clip
+        let later_id = arr.split_clip(0, 1.2).unwrap();
+        assert_eq!(arr.timeline.len(), 2);
+        let (earlier, later) = (&arr.timeline[0], &arr.timeline[1]);
+        assert_eq!((earlier.id, later.id), (id, later_id));
+        assert!((earlier.trim_start_s - 0.05).abs() < 1e-9);
+        assert!((earlier.trim_end_s - 0.15).abs() < 1e-9);
+        assert!((later.trim_start_s - 0.15).abs() < 1e-9);
+        assert!((later.trim_end_s).abs() < 1e-9);
+        assert!((earlier.effective_duration_s - 0.2).abs() < 1e-9);
+        assert!((later.position_s - 1.2).abs() < 1e-9);
+        assert!((later.effective_duration_s - 0.3).abs() < 1e-9);
+        assert_eq!(later.effects, earlier.effects);
+
+        // Reversed, the first half on the timeline is the end of the audio
+        arr.timeline[1].effects.push(ClipEffect::Reverse);
+        arr.split_clip(1, 1.3).unwrap();
+        assert!((arr.timeline[1].trim_start_s - 0.25).abs() < 1e-9);
+        assert!((arr.timeline[2].trim_end_s - 0.05).abs() < 1e-9);
+
+        // Not at the very edge, outside the clip, or past the timeline
+        assert!(arr.split_clip(0, 1.0).is_none());
+        assert!(arr.split_clip(0, 3.0).is_none());
+        assert!(arr.split_clip(9, 1.1).is_none());
+    }
+
+    #[test]
+    fn test_project_save_load_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_project_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("source.wav");
+        crate::audio::io::write_wav(&source, &vec![0.25; 16000], 16000).unwrap();
+
+        let clip = SyllableClip::new(make_test_syllable(), vec![0.25; 4800].into(), 16000, SourceId::intern(&source));
+        let mut tc = TimelineClip::new(&clip);
+        tc.effects.push(ClipEffect::Stutter { count: 2 });
+        tc.pan = 0.5;
+        tc.trim_start_s = 0.05;
+
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Speak);
+        arr.crossfade_ms = 12.0;
+        arr.layout_mode = LayoutMode::Free;
+        arr.bank.push(clip.clone());
+        arr.timeline.push(tc.clone());
+        assert!(arr.save_effect_preset("double", tc.id));
+        arr.set_source_color(clip.source, Some((200, 10, 90)));
+        arr.master_effects = vec![
+            MasterEffect::Eq { low_db: 3.0, mid_db: 0.0, high_db: -2.0 },
+            MasterEffect::Reverb { ir: ReverbIr::Hall, send: 0.25 },
+        ];
+        arr.automation.pitch.insert(crate::editor::AutomationParam::Pitch, 0.5, -2.0);
+
+        let path = dir.join(format!("test.{}", PROJECT_EXTENSION));
+        arr.save_project(&path).unwrap();
+        let loaded = Arrangement::load_project(&path).unwrap();
+
+        assert_eq!(loaded.source_pipeline, EditorPipelineMode::Speak);
+        assert_eq!(loaded.crossfade_ms, 12.0);
+        assert_eq!(loaded.layout_mode, LayoutMode::Free);
+        assert_eq!(loaded.bank.len(), 1);
+        assert_eq!(loaded.bank[0].id, clip.id);
+        assert_eq!(loaded.bank[0].source.path(), source);
+        assert!(!loaded.bank[0].samples.is_empty());
+        assert_eq!(loaded.timeline.len(), 1);
+        assert_eq!(loaded.timeline[0].id, tc.id);
+        assert_eq!(loaded.timeline[0].source_clip_id, clip.id);
+        assert_eq!(loaded.timeline[0].effects, tc.effects);
+        assert_eq!(loaded.timeline[0].pan, 0.5);
+        assert_eq!(loaded.timeline[0].trim_start_s, 0.05);
+        assert_eq!(loaded.effect_presets, arr.effect_presets);
+        assert_eq!(loaded.source_color(clip.source, 0), (200, 10, 90));
+        assert_eq!(loaded.master_effects, arr.master_effects);
+        assert_eq!(loaded.automation, arr.automation);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_project_reads_legacy_master_reverb() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_project_legacy_reverb_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(format!("old.{}", PROJECT_EXTENSION));
+        Arrangement::new(16000, EditorPipelineMode::Collage).save_project(&path).unwrap();
+        let mut json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("master_effects");
+        fields.insert("master_reverb".into(), serde_json::json!({ "ir": "Spring", "send": 0.3 }));
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let loaded = Arrangement::load_project(&path).unwrap();
+        assert_eq!(loaded.master_effects, [MasterEffect::Reverb { ir: ReverbIr::Spring, send: 0.3 }]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_project_follows_moved_folder() {
+        let root = std::env::temp_dir().join(format!("glottisdale_project_moved_{}", std::process::id()));
+        let (before, after) = (root.join("before"), root.join("after"));
+        std::fs::create_dir_all(before.join("takes")).unwrap();
+        let source = before.join("takes").join("voice.wav");
+        crate::audio::io::write_wav(&source, &vec![0.25; 16000], 16000).unwrap();
+
+        let clip = SyllableClip::new(make_test_syllable(), vec![0.25; 4800].into(), 16000, SourceId::intern(&source));
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip.clone());
+        arr.set_source_color(clip.source, Some((1, 2, 3)));
+        let path = before.join("moved.glott");
+        arr.save_project(&path).unwrap();
+        let json = std::fs::read_to_string(&path).unwrap();
+        assert!(json.contains("takes/voice.wav") && !json.contains(before.to_str().unwrap()), "{}", json);
+
+        std::fs::rename(&before, &after).unwrap();
+        let loaded = Arrangement::load_project(&after.join("moved.glott")).unwrap();
+        let moved = loaded.bank[0].source;
+        assert_eq!(moved.path(), after.join("takes").join("voice.wav"));
+        assert_eq!(loaded.source_color(moved, 0), (1, 2, 3));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_project_relinks_missing_sources() {
+        let root = std::env::temp_dir().join(format!("glottisdale_project_relink_{}", std::process::id()));
+        let (project_dir, old_dir, new_dir) = (root.join("project"), root.join("recordings"), root.join("archive"));
+        std::fs::create_dir_all(&project_dir).unwrap();
+        std::fs::create_dir_all(&old_dir).unwrap();
+        let source = old_dir.join("voice.wav");
+        crate::audio::io::write_wav(&source, &vec![0.25; 16000], 16000).unwrap();
+
+        let clip = SyllableClip::new(make_test_syllable(), vec![0.25; 4800].into(), 16000, SourceId::intern(&source));
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip);
+        let path = project_dir.join("relink.glott");
+        arr.save_project(&path).unwrap();
+        std::fs::rename(&old_dir, &new_dir).unwrap();
+
+        let err = Arrangement::load_project(&path).unwrap_err();
+        let missing = err.downcast_ref::<MissingSources>().expect("missing sources error");
+        assert_eq!(missing.missing, std::slice::from_ref(&source));
+        assert_eq!(
+            missing.to_string(),
+            format!("1 source missing from project {}: {}", path.display(), source.display())
+        );
+
+        let relinks = [Relink { from: old_dir, to: new_dir.clone() }];
+        let loaded = Arrangement::load_project_relinked(&path, &relinks).unwrap();
+        assert_eq!(loaded.bank[0].source.path(), new_dir.join("voice.wav"));
+        assert!(!loaded.bank[0].samples.is_empty());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_project_with_audio_opens_without_sources() {
+        let root = std::env::temp_dir().join(format!("glottisdale_project_embedded_{}", std::process::id()));
+        let (recordings, shared) = (root.join("recordings"), root.join("shared"));
+        std::fs::create_dir_all(&recordings).unwrap();
+        std::fs::create_dir_all(&shared).unwrap();
+        let source = recordings.join("voice.wav");
+        let samples: Vec<f64> = (0..16000).map(|i| (i as f64 * 0.01).sin() * 0.5).collect();
+        crate::audio::io::write_wav(&source, &samples, 16000).unwrap();
+
+        let clip = SyllableClip::new(make_test_syllable(), vec![0.25; 4800].into(), 16000, SourceId::intern(&source));
+        let mut tc = TimelineClip::new(&clip);
+        tc.effects.push(ClipEffect::Reverse);
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip.clone());
+        arr.timeline.push(tc);
+        arr.set_source_color(clip.source, Some((4, 5, 6)));
+        let path = shared.join(format!("song.{}", PROJECT_EXTENSION));
+        arr.save_project_with_audio(&path).unwrap();
+        std::fs::remove_dir_all(&recordings).unwrap();
+
+        let loaded = Arrangement::load_project(&path).unwrap();
+        let unpacked = loaded.bank[0].source;
+        assert_eq!(unpacked.path(), shared.join("song-audio").join("00-voice.wav"));
+        assert_eq!(loaded.timeline[0].effects, [ClipEffect::Reverse]);
+        assert_eq!(loaded.source_color(unpacked, 0), (4, 5, 6));
+        let (audio, sr) = crate::audio::io::read_wav(unpacked.path()).unwrap();
+        assert_eq!((audio.len(), sr), (samples.len(), 16000));
+        assert!(audio.iter().zip(&samples).all(|(a, b)| (a - b).abs() < 1e-3));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_project_with_audio_keeps_sample_rate() {
+        let root = std::env::temp_dir().join(format!("glottisdale_project_embedded_44k_{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+        let sr = 44100;
+        let source = root.join("voice.wav");
+        // 12 kHz is above what a 16kHz round trip could keep
+        let samples: Vec<f64> = (0..sr as usize)
+            .map(|i| (i as f64 / sr as f64 * 12000.0 * std::f64::consts::TAU).sin() * 0.5)
+            .collect();
+        crate::audio::io::write_wav(&source, &samples, sr).unwrap();
+
+        let clip = SyllableClip::new(make_test_syllable(), vec![0.25; 13230].into(), sr, SourceId::intern(&source));
+        let mut arr = Arrangement::new(sr, EditorPipelineMode::Collage);
+        arr.bank.push(clip.clone());
+        arr.timeline.push(TimelineClip::new(&clip));
+        let path = root.join(format!("song.{}", PROJECT_EXTENSION));
+        arr.save_project_with_audio(&path).unwrap();
+        std::fs::remove_file(&source).unwrap();
+
+        let loaded = Arrangement::load_project(&path).unwrap();
+        assert_eq!(loaded.sample_rate, sr);
+        let (audio, unpacked_sr) = crate::audio::io::read_wav(loaded.bank[0].source.path()).unwrap();
+        assert_eq!((audio.len(), unpacked_sr), (samples.len(), sr));
+        assert!(audio.iter().zip(&samples).all(|(a, b)| (a - b).abs() < 1e-3));
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_load_format_1_project() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_project_v1_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("source.wav");
+        crate::audio::io::write_wav(&source, &vec![0.25; 16000], 16000).unwrap();
+
+        let clip = SyllableClip::new(make_test_syllable(), vec![0.25; 4800].into(), 16000, SourceId::intern(&source));
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip.clone());
+        arr.set_source_color(clip.source, Some((9, 9, 9)));
+        let path = dir.join("v1.glott");
+        arr.save_project(&path).unwrap();
+
+        // Rewrite as format 1: a path on each clip and no source table
+        let mut json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        json["version"] = 1.into();
+        json.as_object_mut().unwrap().remove("sources");
+        json["bank"][0].as_object_mut().unwrap().remove("source");
+        json["bank"][0]["source_path"] = source.to_str().unwrap().into();
+        json["source_colors"] = serde_json::json!({ source.to_str().unwrap(): [9, 9, 9] });
+        std::fs::write(&path, json.to_string()).unwrap();
+
+        let loaded = Arrangement::load_project(&path).unwrap();
+        assert_eq!(loaded.bank[0].source, clip.source);
+        assert_eq!(loaded.source_color(clip.source, 0), (9, 9, 9));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_project_fingerprint_tracks_saved_state() {
+        let clip = SyllableClip::new(make_test_syllable(), vec![0.25; 4800].into(), 16000, "a.wav".into());
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip.clone());
+        let saved = arr.project_fingerprint();
+        assert_eq!(arr.project_fingerprint(), saved);
+
+        arr.timeline.push(TimelineClip::new(&clip));
+        assert_ne!(arr.project_fingerprint(), saved);
+        arr.timeline.clear();
+        assert_eq!(arr.project_fingerprint(), saved);
+
+        // Color changes count, and undoing them restores the fingerprint
+        for i in 0..8 {
+            arr.set_source_color(SourceId::intern(format!("s{}.wav", i)), Some((i, 0, 0)));
+        }
+        assert_ne!(arr.project_fingerprint(), saved);
+        for i in 0..8 {
+            arr.set_source_color(SourceId::intern(format!("s{}.wav", i)), None);
+        }
+        assert_eq!(arr.project_fingerprint(), saved);
+    }
+
+    #[test]
+    fn test_effect_presets_save_and_apply() {
+        let clip = SyllableClip::new(make_test_syllable(), vec![0.25; 16000].into(), 16000, "a.wav".into());
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip.clone());
+        let mut first = TimelineClip::new(&clip);
+        first.effects = vec![ClipEffect::TimeStretch { factor: 2.0 }];
+        first.effective_duration_s = 2.0;
+        let second = TimelineClip::new(&clip);
+        let (first_id, second_id) = (first.id, second.id);
+        arr.timeline.extend([first, second]);
+
+        assert!(!arr.save_effect_preset("  ", first_id));
+        assert!(!arr.save_effect_preset("slow", Uuid::new_v4()));
+        assert!(arr.save_effect_preset(" slow ", first_id));
+        assert!(arr.save_effect_preset("glitchy", second_id));
+        assert_eq!(arr.effect_presets.len(), 2);
+        assert_eq!(arr.effect_presets[0].name, "slow");
+
+        // The project's "glitchy" replaces the built-in one
+        let names: Vec<String> = arr.available_effect_presets().into_iter().map(|p| p.name).collect();
+        assert_eq!(names, vec!["slow ghost", "telephone", "radio", "megaphone", "slow", "glitchy"]);
+
+        assert!(arr.apply_effect_preset("slow", second_id));
+        assert_eq!(arr.timeline[1].effects, vec![ClipEffect::TimeStretch { factor: 2.0 }]);
+        assert!((arr.timeline[1].effective_duration_s - 2.0).abs() < 1e-6);
+        assert!((arr.timeline[1].position_s - 2.0).abs() < 1e-6);
+
+        assert!(arr.apply_effect_preset("glitchy", first_id));
+        assert!(arr.timeline[0].effects.is_empty());
+        assert!((arr.timeline[0].effective_duration_s - 1.0).abs() < 1e-6);
+        assert!(!arr.apply_effect_preset("missing", first_id));
+    }
+
+    #[test]
+    fn test_manual_clip_from_range_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_project_manual_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let source = dir.join("source.wav");
+        let samples: Vec<f64> = (0..32000).map(|i| if i < 16000 { 0.1 } else { 0.5 }).collect();
+        crate::audio::io::write_wav(&source, &samples, 16000).unwrap();
+
+        let clip = SyllableClip::from_range(&crate::editor::bank_builder::shared_source(&samples), 16000, SourceId::intern(&source), 1.0, 1.5, "door slam");
+        assert!(clip.manual);
+        assert_eq!(clip.label, "door slam");
+        assert_eq!(clip.syllable.word, "door slam");
+        assert_eq!(clip.samples.len(), 8000);
+        assert!((clip.samples.get(4000) - 0.5).abs() < 1e-9);
+
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip.clone());
+        let path = dir.join(format!("manual.{}", PROJECT_EXTENSION));
+        arr.save_project(&path).unwrap();
+        let loaded = Arrangement::load_project(&path).unwrap();
+        assert!(loaded.bank[0].manual);
+        assert_eq!(loaded.bank[0].label, "door slam");
+        assert_eq!(loaded.bank[0].samples.len(), clip.samples.len());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_load_project_missing_source_fails() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_project_missing_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let clip = SyllableClip::new(make_test_syllable(), vec![0.0; 4800].into(), 16000, SourceId::intern(dir.join("gone.wav")));
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.bank.push(clip);
+
+        let path = dir.join("missing.glott");
+        arr.save_project(&path).unwrap();
+        let err = Arrangement::load_project(&path).unwrap_err();
+        assert!(err.to_string().contains("source missing"), "{}", err);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
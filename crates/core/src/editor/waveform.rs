@@ -1,5 +1,7 @@
 //! Pre-computed waveform peak data for efficient rendering.
 
+use crate::audio::sample::Sample;
+
 /// Pre-computed waveform data for efficient rendering.
 ///
 /// Stores (min_peak, max_peak) pairs at a fixed bucket size.
@@ -16,7 +18,7 @@ const DEFAULT_BUCKET_SIZE: usize = 256;
 
 impl WaveformData {
     /// Compute waveform peaks from audio samples.
-    pub fn from_samples(samples: &[f64], bucket_size: usize) -> Self {
+    pub fn from_samples<S: Sample>(samples: &[S], bucket_size: usize) -> Self {
         if samples.is_empty() {
             return Self {
                 peaks: Vec::new(),
@@ -28,7 +30,7 @@ impl WaveformData {
         for chunk in samples.chunks(bucket_size) {
             let mut min = f64::INFINITY;
             let mut max = f64::NEG_INFINITY;
-            for &s in chunk {
+            for s in chunk.iter().map(|s| s.to_f64()) {
                 if s < min { min = s; }
                 if s > max { max = s; }
             }
@@ -42,7 +44,7 @@ impl WaveformData {
     }
 
     /// Compute waveform peaks with default bucket size (256).
-    pub fn new(samples: &[f64]) -> Self {
+    pub fn new<S: Sample>(samples: &[S]) -> Self {
         Self::from_samples(samples, DEFAULT_BUCKET_SIZE)
     }
 
@@ -100,7 +102,7 @@ mod tests {
 
     #[test]
     fn test_waveform_empty() {
-        let wf = WaveformData::new(&[]);
+        let wf = WaveformData::new::<f64>(&[]);
         assert!(wf.peaks.is_empty());
     }
 
@@ -0,0 +1,198 @@
+//! Commands run after a pipeline finishes, e.g. to post its output to a
+//! Slack or Discord channel.
+//!
+//! A hook is a shell command line with placeholders for the run's paths:
+//!
+//! | Placeholder  | Replaced with                                    |
+//! |--------------|--------------------------------------------------|
+//! | `{output}`   | the run's main output file                       |
+//! | `{outputs}`  | every output file, separated by spaces           |
+//! | `{run_dir}`  | the run directory                                |
+//! | `{run_name}` | the run's name                                   |
+//!
+//! Paths are quoted for the shell, so they can be passed as they are.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+/// Placeholders a hook command may use.
+pub const PLACEHOLDERS: &[&str] = &["output", "outputs", "run_dir", "run_name"];
+
+/// The `[hooks]` table of the config file:
+///
+/// ```toml
+/// [hooks]
+/// on_complete = "curl -F file=@{output} https://example.com/upload"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Command run after a successful run.
+    pub on_complete: Option<String>,
+}
+
+impl HooksConfig {
+    /// Check each hook's placeholders.
+    pub fn validate(&self) -> Result<()> {
+        if let Some(command) = &self.on_complete {
+            check_placeholders(command).context("hooks.on_complete")?;
+        }
+        Ok(())
+    }
+}
+
+/// What a finished run produced, for filling in a hook's placeholders.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunOutputs {
+    pub run_dir: PathBuf,
+    /// Output files, the main one first.
+    pub outputs: Vec<PathBuf>,
+}
+
+/// Fail if `command` uses a placeholder that isn't in [`PLACEHOLDERS`],
+/// so a typo is caught before the run rather than after it.
+pub fn check_placeholders(command: &str) -> Result<()> {
+    for (_, name) in placeholders(command) {
+        if !PLACEHOLDERS.contains(&name) {
+            bail!("Unknown placeholder {{{}}} (expected one of: {})", name, PLACEHOLDERS.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Byte offset of each `{...}` in `command` and the name inside it,
+/// skipping the shell's own `${...}`.
+fn placeholders(command: &str) -> impl Iterator<Item = (usize, &str)> {
+    command.match_indices('{').filter(|(i, _)| !command[..*i].ends_with('$')).filter_map(|(i, _)| {
+        let rest = &command[i + 1..];
+        let name = &rest[..rest.find('}')?];
+        let is_name = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        is_name.then_some((i, name))
+    })
+}
+
+/// `command` with its placeholders replaced by `run`'s quoted paths.
+///
+/// Substitution is a single pass over the command as written, so a path
+/// that itself contains `{output}` or the like is never expanded again.
+pub fn expand_placeholders(command: &str, run: &RunOutputs) -> Result<String> {
+    check_placeholders(command)?;
+    let run_name = run.run_dir.file_name().unwrap_or_default().to_string_lossy();
+    let mut expanded = String::with_capacity(command.len());
+    let mut copied = 0;
+    for (i, name) in placeholders(command) {
+        let value = match name {
+            "output" => run.outputs.first().map(|p| shell_quote(p)).unwrap_or_default(),
+            "outputs" => run.outputs.iter().map(|p| shell_quote(p)).collect::<Vec<_>>().join(" "),
+            "run_dir" => shell_quote(&run.run_dir),
+            "run_name" => shell_quote(Path::new(run_name.as_ref())),
+            _ => unreachable!("placeholders are checked above"),
+        };
+        expanded.push_str(&command[copied..i]);
+        expanded.push_str(&value);
+        copied = i + name.len() + 2;
+    }
+    expanded.push_str(&command[copied..]);
+    Ok(expanded)
+}
+
+/// Quote a path as one shell word.
+fn shell_quote(path: &Path) -> String {
+    let s = path.to_string_lossy();
+    let plain = |c: char| c.is_ascii_alphanumeric() || "/._-+=:,@%".contains(c);
+    if !s.is_empty() && s.chars().all(plain) {
+        return s.to_string();
+    }
+    if cfg!(windows) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        format!("'{}'", s.replace('\'', r"'\''"))
+    }
+}
+
+/// Run the `command` hook for a finished run through the shell, waiting
+/// for it. Fails if it can't start or exits unsuccessfully.
+pub fn run_hook(command: &str, run: &RunOutputs) -> Result<()> {
+    let line = expand_placeholders(command, run)?;
+    log::info!("Running: {}", line);
+    let mut shell = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.arg("/C");
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c");
+        c
+    };
+    let status = shell
+        .arg(&line)
+        .current_dir(&run.run_dir)
+        .status()
+        .with_context(|| format!("Failed to start hook: {}", line))?;
+    if !status.success() {
+        bail!("Hook exited with {}: {}", status, line);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run() -> RunOutputs {
+        RunOutputs {
+            run_dir: PathBuf::from("/runs/breathy-bassoon"),
+            outputs: vec![
+                PathBuf::from("/runs/breathy-bassoon/full_mix.wav"),
+                PathBuf::from("/runs/breathy-bassoon/it's a cappella.wav"),
+            ],
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_placeholders_quotes_paths() {
+        let line = expand_placeholders("post {run_name} {output} && ls {outputs}", &run()).unwrap();
+        assert_eq!(
+            line,
+            "post breathy-bassoon /runs/breathy-bassoon/full_mix.wav && \
+             ls /runs/breathy-bassoon/full_mix.wav '/runs/breathy-bassoon/it'\\''s a cappella.wav'"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_expand_placeholders_is_single_pass() {
+        let run = RunOutputs {
+            run_dir: PathBuf::from("/runs/{run_name}"),
+            outputs: vec![PathBuf::from("/runs/{run_name}/{outputs}.wav")],
+        };
+        let line = expand_placeholders("post {output} from {run_dir}; echo \"${output}\"", &run).unwrap();
+        assert_eq!(line, "post '/runs/{run_name}/{outputs}.wav' from '/runs/{run_name}'; echo \"${output}\"");
+    }
+
+    #[test]
+    fn test_unknown_placeholder_is_rejected() {
+        assert!(check_placeholders("echo {output} {run_dir}").is_ok());
+        // Shell braces that aren't placeholder names are left alone
+        assert!(check_placeholders("for f in {outputs}; do echo \"${f}\"; done").is_ok());
+        let err = check_placeholders("upload {ouput}").unwrap_err().to_string();
+        assert!(err.contains("{ouput}"), "{}", err);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_hook_in_run_dir() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_hook_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let run = RunOutputs { run_dir: dir.clone(), outputs: vec![dir.join("out put.wav")] };
+        run_hook("echo {output} > hook.txt", &run).unwrap();
+        let written = std::fs::read_to_string(dir.join("hook.txt")).unwrap();
+        assert_eq!(written.trim(), dir.join("out put.wav").to_string_lossy());
+        assert!(run_hook("exit 3", &run).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -4,16 +4,33 @@
 //! alignment strategies:
 //! - DefaultAligner: Whisper ASR + G2P + ARPABET syllabifier
 //! - BfaAligner: Planned native forced alignment (see issue #21)
+//!
+//! `VadAligner` wraps any backend to align only the detected speech.
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
 
-use crate::types::AlignmentResult;
+use crate::types::{AlignmentResult, Syllable};
 
+use super::g2p::Language;
 use super::syllabify;
 use super::transcribe;
 
+/// Progress event emitted by `Aligner::process_with_progress`.
+#[derive(Debug, Clone)]
+pub enum AlignmentProgress {
+    /// Fraction of the file aligned so far, 0.0 to 1.0.
+    Fraction(f64),
+    /// Syllables from a newly finished segment. Timing is provisional;
+    /// the returned `AlignmentResult` is authoritative.
+    Partial(Vec<Syllable>),
+}
+
+/// Callback receiving `AlignmentProgress` events, possibly from another thread.
+pub type AlignmentCallback = Box<dyn FnMut(AlignmentProgress) + Send>;
+
 /// Alignment backend trait.
 pub trait Aligner: Send + Sync {
     /// Backend name for caching/display.
@@ -25,15 +42,33 @@ pub trait Aligner: Send + Sync {
         audio_path: &Path,
         model_dir: Option<&Path>,
     ) -> Result<AlignmentResult>;
+
+    /// Like `process`, but reports progress and streams partial syllables.
+    ///
+    /// Backends that cannot stream report everything once at the end.
+    fn process_with_progress(
+        &self,
+        audio_path: &Path,
+        model_dir: Option<&Path>,
+        mut on_progress: AlignmentCallback,
+    ) -> Result<AlignmentResult> {
+        let result = self.process(audio_path, model_dir)?;
+        on_progress(AlignmentProgress::Partial(result.syllables.clone()));
+        on_progress(AlignmentProgress::Fraction(1.0));
+        Ok(result)
+    }
 }
 
 /// Whisper ASR + G2P + ARPABET syllabifier.
 ///
-/// Word-level timestamps from Whisper, phoneme conversion via CMU dict,
-/// syllable timing estimated by proportional distribution.
+/// Word-level timestamps from Whisper, phoneme conversion via the G2P
+/// backend for `language` (CMU dict for English), syllable timing
+/// estimated by proportional distribution.
 pub struct DefaultAligner {
     pub whisper_model: String,
     pub language: String,
+    /// Run Whisper inference on the GPU (falls back to CPU on failure).
+    pub use_gpu: bool,
 }
 
 impl DefaultAligner {
@@ -41,8 +76,23 @@ impl DefaultAligner {
         Self {
             whisper_model: whisper_model.to_string(),
             language: language.to_string(),
+            use_gpu: false,
         }
     }
+
+    /// Set whether Whisper inference should use the GPU.
+    pub fn with_gpu(mut self, use_gpu: bool) -> Self {
+        self.use_gpu = use_gpu;
+        self
+    }
+
+    /// G2P language for the transcript; English when there is no backend.
+    fn g2p_language(&self) -> Language {
+        Language::from_code(&self.language).unwrap_or_else(|_| {
+            log::warn!("No G2P for language '{}', syllabifying as English", self.language);
+            Language::English
+        })
+    }
 }
 
 impl Default for DefaultAligner {
@@ -66,9 +116,10 @@ impl Aligner for DefaultAligner {
             &self.whisper_model,
             &self.language,
             model_dir,
+            self.use_gpu,
         )?;
 
-        let syllables = syllabify::syllabify_words(&result.words);
+        let syllables = syllabify::syllabify_words_in(&result.words, self.g2p_language());
 
         Ok(AlignmentResult {
             text: result.text,
@@ -76,6 +127,241 @@ impl Aligner for DefaultAligner {
             syllables,
         })
     }
+
+    fn process_with_progress(
+        &self,
+        audio_path: &Path,
+        model_dir: Option<&Path>,
+        mut on_progress: AlignmentCallback,
+    ) -> Result<AlignmentResult> {
+        let language = self.g2p_language();
+        let callback: transcribe::TranscribeCallback = Box::new(move |event| match event {
+            transcribe::TranscribeProgress::Fraction(f) => {
+                on_progress(AlignmentProgress::Fraction(f));
+            }
+            transcribe::TranscribeProgress::Segment(words) => {
+                let syllables = syllabify::syllabify_words_in(&words, language);
+                if !syllables.is_empty() {
+                    on_progress(AlignmentProgress::Partial(syllables));
+                }
+            }
+        });
+        let result = transcribe::transcribe_with_progress(
+            audio_path,
+            &self.whisper_model,
+            &self.language,
+            model_dir,
+            self.use_gpu,
+            Some(callback),
+        )?;
+
+        let syllables = syllabify::syllabify_words_in(&result.words, language);
+
+        Ok(AlignmentResult {
+            text: result.text,
+            words: result.words,
+            syllables,
+        })
+    }
+}
+
+/// Default longest stretch of speech `VadAligner` aligns in one pass.
+pub const DEFAULT_VAD_CHUNK_S: f64 = 300.0;
+
+/// Aligns only the speech in a file, skipping silence.
+///
+/// Runs voice activity detection, writes each speech chunk (at most
+/// `max_chunk_s` long) next to the input, aligns it with `inner`, and
+/// shifts the results back onto the original timeline. Files that are
+/// speech from end to end go straight to `inner`.
+pub struct VadAligner {
+    inner: Box<dyn Aligner>,
+    max_chunk_s: f64,
+}
+
+impl VadAligner {
+    pub fn new(inner: Box<dyn Aligner>, max_chunk_s: f64) -> Self {
+        Self { inner, max_chunk_s }
+    }
+
+    /// Speech chunks of `audio_path` as sample ranges, with the samples.
+    fn chunks(&self, audio_path: &Path) -> Result<(Vec<std::ops::Range<usize>>, Vec<f64>, u32)> {
+        let (samples, sr) = crate::audio::io::read_wav(audio_path)?;
+        let regions = crate::audio::analysis::detect_speech(&samples, sr);
+        let chunks = crate::audio::analysis::speech_chunks(&regions, sr, self.max_chunk_s);
+        Ok((chunks, samples, sr))
+    }
+
+    /// Align each chunk of `samples` with `align` and merge the shifted
+    /// results. `None` when the whole file is speech.
+    fn align_chunks(
+        audio_path: &Path,
+        chunks: &[std::ops::Range<usize>],
+        samples: &[f64],
+        sr: u32,
+        mut align: impl FnMut(usize, &Path) -> Result<AlignmentResult>,
+    ) -> Result<Option<AlignmentResult>> {
+        if chunks.is_empty() {
+            log::warn!("No speech detected in {}", audio_path.display());
+            return Ok(Some(AlignmentResult { text: String::new(), words: vec![], syllables: vec![] }));
+        }
+        if chunks.len() == 1 && chunks[0].len() == samples.len() {
+            return Ok(None);
+        }
+
+        let stem = audio_path.file_stem().unwrap_or_default().to_string_lossy();
+        let mut merged = AlignmentResult { text: String::new(), words: vec![], syllables: vec![] };
+        for (i, chunk) in chunks.iter().enumerate() {
+            let chunk_path = audio_path.with_file_name(format!("{}_speech{:03}.wav", stem, i));
+            crate::audio::io::write_wav(&chunk_path, &samples[chunk.clone()], sr)?;
+            let result = align(i, &chunk_path);
+            let _ = std::fs::remove_file(&chunk_path);
+            let mut result = result?;
+            shift_result(&mut result, chunk.start as f64 / sr as f64, merged.words.len());
+            if !result.text.trim().is_empty() {
+                if !merged.text.is_empty() {
+                    merged.text.push(' ');
+                }
+                merged.text.push_str(result.text.trim());
+            }
+            merged.words.extend(result.words);
+            merged.syllables.extend(result.syllables);
+        }
+        Ok(Some(merged))
+    }
+}
+
+/// Move an alignment `offset_s` later and its words `word_offset` places on.
+fn shift_result(result: &mut AlignmentResult, offset_s: f64, word_offset: usize) {
+    for word in &mut result.words {
+        word.start += offset_s;
+        word.end += offset_s;
+    }
+    shift_syllables(&mut result.syllables, offset_s);
+    for syllable in &mut result.syllables {
+        syllable.word_index += word_offset;
+    }
+}
+
+fn shift_syllables(syllables: &mut [Syllable], offset_s: f64) {
+    for syllable in syllables {
+        syllable.start += offset_s;
+        syllable.end += offset_s;
+        for phoneme in &mut syllable.phonemes {
+            phoneme.start += offset_s;
+            phoneme.end += offset_s;
+        }
+    }
+}
+
+impl Aligner for VadAligner {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn process(
+        &self,
+        audio_path: &Path,
+        model_dir: Option<&Path>,
+    ) -> Result<AlignmentResult> {
+        let (chunks, samples, sr) = self.chunks(audio_path)?;
+        let merged = Self::align_chunks(audio_path, &chunks, &samples, sr, |_, chunk| {
+            self.inner.process(chunk, model_dir)
+        })?;
+        match merged {
+            Some(result) => Ok(result),
+            None => self.inner.process(audio_path, model_dir),
+        }
+    }
+
+    fn process_with_progress(
+        &self,
+        audio_path: &Path,
+        model_dir: Option<&Path>,
+        on_progress: AlignmentCallback,
+    ) -> Result<AlignmentResult> {
+        let (chunks, samples, sr) = self.chunks(audio_path)?;
+        let total: usize = chunks.iter().map(|c| c.len()).sum::<usize>().max(1);
+        let on_progress = Arc::new(Mutex::new(on_progress));
+        let mut done = 0;
+        let merged = Self::align_chunks(audio_path, &chunks, &samples, sr, |i, chunk_path| {
+            let chunk = chunks[i].clone();
+            let (before, share) = (done as f64 / total as f64, chunk.len() as f64 / total as f64);
+            done += chunk.len();
+            let offset_s = chunk.start as f64 / sr as f64;
+            let sink = Arc::clone(&on_progress);
+            let callback: AlignmentCallback = Box::new(move |event| {
+                let event = match event {
+                    AlignmentProgress::Fraction(f) => AlignmentProgress::Fraction(before + f * share),
+                    AlignmentProgress::Partial(mut syllables) => {
+                        shift_syllables(&mut syllables, offset_s);
+                        AlignmentProgress::Partial(syllables)
+                    }
+                };
+                (sink.lock().unwrap())(event);
+            });
+            self.inner.process_with_progress(chunk_path, model_dir, callback)
+        })?;
+        match merged {
+            Some(result) => {
+                (on_progress.lock().unwrap())(AlignmentProgress::Fraction(1.0));
+                Ok(result)
+            }
+            None => {
+                let on_progress = Arc::clone(&on_progress);
+                self.inner.process_with_progress(
+                    audio_path,
+                    model_dir,
+                    Box::new(move |event| (on_progress.lock().unwrap())(event)),
+                )
+            }
+        }
+    }
+}
+
+/// Inference devices accepted by `get_aligner`.
+pub const DEVICES: &[&str] = &["auto", "cpu", "cuda"];
+
+/// Whether this build can run Whisper on a GPU and one appears present.
+///
+/// GPU support is compiled in with the `cuda` or `metal` features. For CUDA
+/// we additionally require an NVIDIA driver and respect `CUDA_VISIBLE_DEVICES`.
+pub fn gpu_available() -> bool {
+    if cfg!(all(feature = "metal", target_os = "macos")) {
+        return true;
+    }
+    if !cfg!(feature = "cuda") {
+        return false;
+    }
+    if let Ok(visible) = std::env::var("CUDA_VISIBLE_DEVICES") {
+        let visible = visible.trim();
+        if visible.is_empty() || visible == "-1" {
+            return false;
+        }
+    }
+    Path::new("/proc/driver/nvidia/version").exists()
+        || Path::new("/dev/nvidia0").exists()
+        || cfg!(target_os = "windows")
+}
+
+/// Resolve a device name to whether the GPU should be used.
+///
+/// "auto" picks the GPU when available. "cuda" requests it explicitly and
+/// falls back to CPU with a warning when it is not available.
+pub fn resolve_use_gpu(device: &str) -> Result<bool> {
+    match device {
+        "cpu" => Ok(false),
+        "auto" => Ok(gpu_available()),
+        "cuda" => {
+            if gpu_available() {
+                Ok(true)
+            } else {
+                log::warn!("GPU requested but not available in this build, using CPU");
+                Ok(false)
+            }
+        }
+        _ => bail!("Unknown device: '{}'. Available: {}", device, DEVICES.join(", ")),
+    }
 }
 
 /// Get an aligner backend by name.
@@ -83,14 +369,19 @@ impl Aligner for DefaultAligner {
 /// Modes:
 /// - "default" / "auto" — Whisper + G2P + ARPABET proportional timing.
 /// - "bfa" — Not yet available natively (see issue #21).
+///
+/// `device` is one of `DEVICES`; see `resolve_use_gpu`.
 pub fn get_aligner(
     name: &str,
     whisper_model: &str,
     language: &str,
-    _device: &str,
+    device: &str,
 ) -> Result<Box<dyn Aligner>> {
+    let use_gpu = resolve_use_gpu(device)?;
     match name {
-        "auto" | "default" => Ok(Box::new(DefaultAligner::new(whisper_model, language))),
+        "auto" | "default" => Ok(Box::new(
+            DefaultAligner::new(whisper_model, language).with_gpu(use_gpu),
+        )),
         "bfa" => {
             bail!(
                 "BFA aligner is not yet available in the native build. \
@@ -102,6 +393,123 @@ pub fn get_aligner(
     }
 }
 
+/// Aligner backend names accepted by `get_aligner`.
+const ALIGNER_NAMES: &[&str] = &["auto", "default", "bfa"];
+
+/// Whisper model sizes accepted for per-input overrides.
+const WHISPER_MODELS: &[&str] = &["tiny", "base", "small", "medium"];
+
+/// A per-input aligner choice, parsed from `FILE=ALIGNER[:MODEL]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlignerOverride {
+    /// Input file the override applies to, as given on the command line.
+    pub file: PathBuf,
+    pub aligner: String,
+    /// Whisper model for this input; falls back to the run's model if unset.
+    pub whisper_model: Option<String>,
+}
+
+impl AlignerOverride {
+    /// Parse a `FILE=ALIGNER[:MODEL]` spec, e.g. `studio.wav=bfa` or
+    /// `phone.m4a=default:small`.
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (file, choice) = spec
+            .rsplit_once('=')
+            .with_context(|| format!("Invalid aligner override '{}': expected FILE=ALIGNER[:MODEL]", spec))?;
+        if file.is_empty() {
+            bail!("Invalid aligner override '{}': missing file", spec);
+        }
+        let (aligner, whisper_model) = match choice.split_once(':') {
+            Some((a, m)) => (a, Some(m)),
+            None => (choice, None),
+        };
+        if !ALIGNER_NAMES.contains(&aligner) {
+            bail!(
+                "Unknown aligner '{}' in '{}'. Available: {}",
+                aligner,
+                spec,
+                ALIGNER_NAMES.join(", ")
+            );
+        }
+        if let Some(m) = whisper_model {
+            if !WHISPER_MODELS.contains(&m) {
+                bail!(
+                    "Unknown whisper model '{}' in '{}'. Available: {}",
+                    m,
+                    spec,
+                    WHISPER_MODELS.join(", ")
+                );
+            }
+        }
+        Ok(Self {
+            file: PathBuf::from(file),
+            aligner: aligner.to_string(),
+            whisper_model: whisper_model.map(str::to_string),
+        })
+    }
+
+    /// Whether this override targets `input`, by full path or by file name.
+    pub fn matches(&self, input: &Path) -> bool {
+        self.file == input || input.file_name() == Some(self.file.as_os_str())
+    }
+}
+
+/// Get the aligner for one input, honoring the first matching override.
+///
+/// Inputs without an override use `default_name` and `whisper_model`.
+pub fn get_aligner_for_input(
+    input: &Path,
+    overrides: &[AlignerOverride],
+    default_name: &str,
+    whisper_model: &str,
+    language: &str,
+    device: &str,
+) -> Result<Box<dyn Aligner>> {
+    match overrides.iter().find(|o| o.matches(input)) {
+        Some(o) => get_aligner(
+            &o.aligner,
+            o.whisper_model.as_deref().unwrap_or(whisper_model),
+            language,
+            device,
+        ),
+        None => get_aligner(default_name, whisper_model, language, device),
+    }
+}
+
+/// Build one aligner per input, applying any overrides and, given a
+/// `vad_max_chunk` (seconds), aligning only the detected speech.
+///
+/// Returned aligners are parallel to `inputs`. Fails if an override
+/// names a file that is not among the inputs.
+pub fn aligners_for_inputs(
+    inputs: &[PathBuf],
+    overrides: &[AlignerOverride],
+    default_name: &str,
+    whisper_model: &str,
+    language: &str,
+    device: &str,
+    vad_max_chunk: Option<f64>,
+) -> Result<Vec<Box<dyn Aligner>>> {
+    if let Some(max_chunk) = vad_max_chunk.filter(|&s| s <= 0.0) {
+        bail!("VAD chunk length must be positive, got {}", max_chunk);
+    }
+    for o in overrides {
+        if !inputs.iter().any(|input| o.matches(input)) {
+            bail!("Aligner override refers to unknown input: {}", o.file.display());
+        }
+    }
+    inputs
+        .iter()
+        .map(|input| {
+            let aligner = get_aligner_for_input(input, overrides, default_name, whisper_model, language, device)?;
+            Ok(match vad_max_chunk {
+                Some(max_chunk) => Box::new(VadAligner::new(aligner, max_chunk)) as Box<dyn Aligner>,
+                None => aligner,
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,4 +546,168 @@ mod tests {
         let aligner = get_aligner("auto", "base", "en", "cpu").unwrap();
         assert_eq!(aligner.name(), "default");
     }
+
+    #[test]
+    fn test_aligner_override_parse() {
+        let o = AlignerOverride::parse("studio.wav=bfa").unwrap();
+        assert_eq!(o.file, PathBuf::from("studio.wav"));
+        assert_eq!(o.aligner, "bfa");
+        assert_eq!(o.whisper_model, None);
+
+        let o = AlignerOverride::parse("phone.m4a=default:small").unwrap();
+        assert_eq!(o.aligner, "default");
+        assert_eq!(o.whisper_model.as_deref(), Some("small"));
+
+        assert!(AlignerOverride::parse("studio.wav").is_err());
+        assert!(AlignerOverride::parse("studio.wav=nope").is_err());
+        assert!(AlignerOverride::parse("studio.wav=default:huge").is_err());
+        assert!(AlignerOverride::parse("studio.wav=default:medium").is_ok());
+        assert!(AlignerOverride::parse("=bfa").is_err());
+    }
+
+    #[test]
+    fn test_get_aligner_for_input_uses_override() {
+        let overrides = vec![AlignerOverride::parse("studio.wav=bfa").unwrap()];
+
+        let result = get_aligner_for_input(
+            Path::new("/data/studio.wav"),
+            &overrides,
+            "default",
+            "base",
+            "en",
+            "cpu",
+        );
+        assert!(result.is_err(), "override to bfa should be honored");
+
+        let aligner = get_aligner_for_input(
+            Path::new("/data/phone.wav"),
+            &overrides,
+            "default",
+            "base",
+            "en",
+            "cpu",
+        )
+        .unwrap();
+        assert_eq!(aligner.name(), "default");
+    }
+
+    #[test]
+    fn test_aligners_for_inputs() {
+        let inputs = vec![PathBuf::from("/data/studio.wav"), PathBuf::from("/data/phone.wav")];
+        let overrides = vec![AlignerOverride::parse("phone.wav=default:small").unwrap()];
+        let aligners = aligners_for_inputs(&inputs, &overrides, "default", "base", "en", "cpu", Some(30.0)).unwrap();
+        assert_eq!(aligners.len(), 2);
+        assert!(aligners.iter().all(|a| a.name() == "default"));
+
+        let stray = vec![AlignerOverride::parse("other.wav=default").unwrap()];
+        let err = aligners_for_inputs(&inputs, &stray, "default", "base", "en", "cpu", None).err().unwrap();
+        assert!(err.to_string().contains("other.wav"), "{}", err);
+        assert!(aligners_for_inputs(&inputs, &[], "default", "base", "en", "cpu", Some(0.0)).is_err());
+        // An override to an unavailable backend fails for its input
+        let bfa = vec![AlignerOverride::parse("studio.wav=bfa").unwrap()];
+        assert!(aligners_for_inputs(&inputs, &bfa, "default", "base", "en", "cpu", None).is_err());
+    }
+
+    #[test]
+    fn test_resolve_use_gpu() {
+        assert!(!resolve_use_gpu("cpu").unwrap());
+        assert_eq!(resolve_use_gpu("auto").unwrap(), gpu_available());
+        // Explicit GPU requests fall back to CPU rather than failing
+        assert_eq!(resolve_use_gpu("cuda").unwrap(), gpu_available());
+        assert!(resolve_use_gpu("tpu").is_err());
+    }
+
+    #[test]
+    fn test_get_aligner_rejects_unknown_device() {
+        assert!(get_aligner("default", "base", "en", "tpu").is_err());
+    }
+
+    struct FixedAligner;
+
+    impl Aligner for FixedAligner {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn process(&self, _audio_path: &Path, _model_dir: Option<&Path>) -> Result<AlignmentResult> {
+            let syllables = syllabify::syllabify_words(&[crate::types::WordTimestamp {
+                word: "hello".into(),
+                start: 0.0,
+                end: 0.5,
+            }]);
+            Ok(AlignmentResult {
+                text: "hello".into(),
+                words: vec![],
+                syllables,
+            })
+        }
+    }
+
+    #[test]
+    fn test_process_with_progress_default_reports_once() {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let result = FixedAligner
+            .process_with_progress(
+                Path::new("unused.wav"),
+                None,
+                Box::new(move |e| sink.lock().unwrap().push(e)),
+            )
+            .unwrap();
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 2);
+        match &events[0] {
+            AlignmentProgress::Partial(syls) => assert_eq!(syls.len(), result.syllables.len()),
+            other => panic!("expected partial, got {:?}", other),
+        }
+        assert!(matches!(events[1], AlignmentProgress::Fraction(f) if f == 1.0));
+    }
+    /// Hears one word at the start of whatever it is given.
+    struct OneWordAligner;
+
+    impl Aligner for OneWordAligner {
+        fn name(&self) -> &str {
+            "one-word"
+        }
+
+        fn process(&self, audio_path: &Path, _model_dir: Option<&Path>) -> Result<AlignmentResult> {
+            assert!(audio_path.exists());
+            let words = vec![crate::types::WordTimestamp { word: "hello".into(), start: 0.1, end: 0.4 }];
+            let syllables = syllabify::syllabify_words(&words);
+            Ok(AlignmentResult { text: "hello".into(), words, syllables })
+        }
+    }
+
+    #[test]
+    fn test_vad_aligner_shifts_chunks_onto_timeline() {
+        let sr = 16000;
+        // 3s silence, 1s tone, 4s silence, 1s tone
+        let mut samples = vec![0.0; sr as usize * 9];
+        for from in [3 * sr as usize, 8 * sr as usize] {
+            for (i, s) in samples[from..from + sr as usize].iter_mut().enumerate() {
+                *s = 0.3 * (i as f64 / sr as f64 * 220.0 * std::f64::consts::TAU).sin();
+            }
+        }
+        let dir = std::env::temp_dir().join(format!("glottisdale_vad_align_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("talk.wav");
+        crate::audio::io::write_wav(&path, &samples, sr).unwrap();
+
+        let aligner = VadAligner::new(Box::new(OneWordAligner), DEFAULT_VAD_CHUNK_S);
+        assert_eq!(aligner.name(), "one-word");
+        let result = aligner.process(&path, None).unwrap();
+        assert_eq!(result.text, "hello hello");
+        assert_eq!(result.words.len(), 2);
+        assert!((result.words[0].start - 3.0).abs() < 0.2, "{:?}", result.words);
+        assert!((result.words[1].start - 8.0).abs() < 0.2, "{:?}", result.words);
+        let last = result.syllables.last().unwrap();
+        assert_eq!(last.word_index, 1);
+        assert!(last.start > 7.8 && last.phonemes[0].start >= last.start);
+        // Chunk files are cleaned up
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).ok();
+    }
 }
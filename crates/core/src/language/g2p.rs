@@ -1,12 +1,84 @@
-//! Grapheme-to-phoneme conversion using the CMU Pronouncing Dictionary.
+//! Grapheme-to-phoneme conversion.
 //!
-//! Provides ARPABET pronunciations for English words. The CMU dict is
-//! embedded at compile time. For out-of-vocabulary words, a simple
-//! rule-based fallback is used.
+//! English uses the CMU Pronouncing Dictionary, embedded at compile time,
+//! with spelling rules (see `g2p_english`) for out-of-vocabulary words.
+//! Spanish and German use rule-based backends (see `g2p_spanish` and
+//! `g2p_german`).
+//! Every backend produces ARPABET, extended with a few phones English lacks
+//! (`X`, `UE`, `OE`), so the syllabifier and phonetic distances are shared.
 
 use std::collections::HashMap;
 use std::sync::OnceLock;
 
+use anyhow::{bail, Result};
+
+use super::{g2p_english, g2p_german, g2p_spanish};
+
+/// Language codes accepted by `Language::from_code` (ISO 639-1, as Whisper uses).
+pub const LANGUAGES: &[&str] = &["en", "es", "de"];
+
+/// A language with a G2P backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Spanish,
+    German,
+}
+
+impl Language {
+    /// Parse a language code from `LANGUAGES`.
+    pub fn from_code(code: &str) -> Result<Self> {
+        match code {
+            "en" => Ok(Self::English),
+            "es" => Ok(Self::Spanish),
+            "de" => Ok(Self::German),
+            _ => bail!("Unsupported language: '{}'. Available: {}", code, LANGUAGES.join(", ")),
+        }
+    }
+
+    /// ISO 639-1 code, as passed to Whisper.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::Spanish => "es",
+            Self::German => "de",
+        }
+    }
+
+    /// Display name.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::Spanish => "Spanish",
+            Self::German => "German",
+        }
+    }
+
+    /// The G2P backend for this language.
+    pub fn g2p(&self) -> &'static dyn G2p {
+        match self {
+            Self::English => &CmuDict,
+            Self::Spanish => &g2p_spanish::SpanishG2p,
+            Self::German => &g2p_german::GermanG2p,
+        }
+    }
+}
+
+/// Converts a word to ARPABET phonemes, with stress digits on vowels.
+pub trait G2p: Send + Sync {
+    fn word_to_phonemes(&self, word: &str) -> Vec<String>;
+}
+
+/// English G2P: CMU dictionary with a rule-based fallback.
+pub struct CmuDict;
+
+impl G2p for CmuDict {
+    fn word_to_phonemes(&self, word: &str) -> Vec<String> {
+        word_to_phonemes(word)
+    }
+}
+
 /// The embedded CMU Pronouncing Dictionary.
 ///
 /// Format: one word per line, "WORD  PH1 PH2 PH3 ..."
@@ -72,114 +144,14 @@ pub fn lookup_all(word: &str) -> Option<&'static Vec<Vec<String>>> {
 
 /// Convert a word to ARPABET phonemes.
 ///
-/// First tries the CMU dictionary, then falls back to a simple
-/// rule-based conversion for OOV words.
+/// First tries the CMU dictionary, then falls back to English spelling
+/// rules for OOV words (see `g2p_english`).
 pub fn word_to_phonemes(word: &str) -> Vec<String> {
     if let Some(phonemes) = lookup(word) {
         return phonemes;
     }
     // Rule-based fallback for OOV words
-    simple_g2p(word)
-}
-
-/// Simple rule-based G2P fallback for out-of-vocabulary words.
-///
-/// This is a best-effort approximation. For production use, a proper
-/// neural G2P model or espeak-ng would be better.
-fn simple_g2p(word: &str) -> Vec<String> {
-    let word = word.to_lowercase();
-    let chars: Vec<char> = word.chars().collect();
-    let mut phonemes = Vec::new();
-    let mut i = 0;
-
-    while i < chars.len() {
-        let remaining = &word[i..];
-
-        // Try digraphs first
-        if remaining.len() >= 2 {
-            let digraph = &remaining[..2];
-            match digraph {
-                "th" => { phonemes.push("TH".to_string()); i += 2; continue; }
-                "sh" => { phonemes.push("SH".to_string()); i += 2; continue; }
-                "ch" => { phonemes.push("CH".to_string()); i += 2; continue; }
-                "ng" => { phonemes.push("NG".to_string()); i += 2; continue; }
-                "ph" => { phonemes.push("F".to_string()); i += 2; continue; }
-                "wh" => { phonemes.push("W".to_string()); i += 2; continue; }
-                "ck" => { phonemes.push("K".to_string()); i += 2; continue; }
-                "ee" => { phonemes.push("IY1".to_string()); i += 2; continue; }
-                "ea" => { phonemes.push("IY1".to_string()); i += 2; continue; }
-                "oo" => { phonemes.push("UW1".to_string()); i += 2; continue; }
-                "ou" => { phonemes.push("AW1".to_string()); i += 2; continue; }
-                "ow" => { phonemes.push("OW1".to_string()); i += 2; continue; }
-                "ai" => { phonemes.push("EY1".to_string()); i += 2; continue; }
-                "ay" => { phonemes.push("EY1".to_string()); i += 2; continue; }
-                "oi" => { phonemes.push("OY1".to_string()); i += 2; continue; }
-                "oy" => { phonemes.push("OY1".to_string()); i += 2; continue; }
-                _ => {}
-            }
-        }
-
-        // Single character mappings
-        match chars[i] {
-            'a' => phonemes.push("AE1".to_string()),
-            'b' => phonemes.push("B".to_string()),
-            'c' => {
-                // c before e/i/y = S, otherwise K
-                if i + 1 < chars.len() && matches!(chars[i + 1], 'e' | 'i' | 'y') {
-                    phonemes.push("S".to_string());
-                } else {
-                    phonemes.push("K".to_string());
-                }
-            }
-            'd' => phonemes.push("D".to_string()),
-            'e' => {
-                // Silent e at end of word
-                if i == chars.len() - 1 && !phonemes.is_empty() {
-                    // skip
-                } else {
-                    phonemes.push("EH1".to_string());
-                }
-            }
-            'f' => phonemes.push("F".to_string()),
-            'g' => phonemes.push("G".to_string()),
-            'h' => phonemes.push("HH".to_string()),
-            'i' => phonemes.push("IH1".to_string()),
-            'j' => phonemes.push("JH".to_string()),
-            'k' => phonemes.push("K".to_string()),
-            'l' => phonemes.push("L".to_string()),
-            'm' => phonemes.push("M".to_string()),
-            'n' => phonemes.push("N".to_string()),
-            'o' => phonemes.push("AA1".to_string()),
-            'p' => phonemes.push("P".to_string()),
-            'q' => phonemes.push("K".to_string()),
-            'r' => phonemes.push("R".to_string()),
-            's' => phonemes.push("S".to_string()),
-            't' => phonemes.push("T".to_string()),
-            'u' => phonemes.push("AH1".to_string()),
-            'v' => phonemes.push("V".to_string()),
-            'w' => phonemes.push("W".to_string()),
-            'x' => {
-                phonemes.push("K".to_string());
-                phonemes.push("S".to_string());
-            }
-            'y' => {
-                if phonemes.is_empty() {
-                    phonemes.push("Y".to_string());
-                } else {
-                    phonemes.push("IY1".to_string());
-                }
-            }
-            'z' => phonemes.push("Z".to_string()),
-            _ => {} // Skip non-alphabetic
-        }
-        i += 1;
-    }
-
-    if phonemes.is_empty() {
-        phonemes.push("AH0".to_string());
-    }
-
-    phonemes
+    g2p_english::letter_to_sound(word)
 }
 
 /// Check if a phoneme is a vowel (has stress marker or is known vowel base).
@@ -188,10 +160,28 @@ pub fn is_vowel(phoneme: &str) -> bool {
     matches!(
         base,
         "AA" | "AE" | "AH" | "AO" | "AW" | "AY" | "EH" | "ER" | "EY" | "IH" | "IY" | "OW"
-            | "OY" | "UH" | "UW"
+            | "OY" | "UH" | "UW" | "UE" | "OE"
     )
 }
 
+/// Add stress digits to vowel phones: `1` on vowel number `stressed`
+/// (counting from zero), `0` on the rest.
+pub(crate) fn mark_stress(phones: &[&str], stressed: usize) -> Vec<String> {
+    let mut nucleus = 0;
+    phones
+        .iter()
+        .map(|p| {
+            if is_vowel(p) {
+                let digit = if nucleus == stressed { 1 } else { 0 };
+                nucleus += 1;
+                format!("{}{}", p, digit)
+            } else {
+                p.to_string()
+            }
+        })
+        .collect()
+}
+
 /// Strip stress markers from an ARPABET phoneme.
 pub fn strip_stress(phoneme: &str) -> &str {
     phoneme.trim_end_matches(|c: char| c.is_ascii_digit())
@@ -257,10 +247,19 @@ mod tests {
     }
 
     #[test]
-    fn test_simple_g2p_basic() {
-        let result = simple_g2p("bat");
-        assert!(!result.is_empty());
-        // Should produce something like B AE T
-        assert_eq!(result[0], "B");
+    fn test_language_codes_round_trip() {
+        for code in LANGUAGES {
+            assert_eq!(Language::from_code(code).unwrap().code(), *code);
+        }
+        assert!(Language::from_code("xx").is_err());
+        assert_eq!(Language::English.g2p().word_to_phonemes("cat"), vec!["K", "AE1", "T"]);
+    }
+
+    #[test]
+    fn test_word_to_phonemes_oov_rules() {
+        let result = word_to_phonemes("glottisdale");
+        assert!(lookup("glottisdale").is_none());
+        assert_eq!(result[0], "G");
+        assert_eq!(result.iter().filter(|p| p.ends_with('1')).count(), 1);
     }
 }
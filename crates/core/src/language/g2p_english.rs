@@ -0,0 +1,314 @@
+//! Rule-based English letter-to-sound conversion, the fallback for words
+//! missing from the CMU dictionary (names, slang, invented words).
+//!
+//! Covers the regular spelling patterns: consonant digraphs, silent
+//! initial letters (kn, wr, gn), vowel teams, magic e, r-colored vowels,
+//! soft c and g, -tion/-sion/-ture, and the -ed/-s endings. Accented Latin
+//! letters fold to their base letter first. Stress falls on the first
+//! syllable unless a suffix such as -tion, -ic, or -ity pulls it later.
+
+use super::g2p::{is_vowel, mark_stress};
+
+/// Multi-letter spellings, longest first within each starting letter.
+const CLUSTERS: &[(&str, &[&str])] = &[
+    ("tch", &["CH"]),
+    ("tion", &["SH", "AH", "N"]),
+    ("ture", &["CH", "ER"]),
+    ("cious", &["SH", "AH", "S"]),
+    ("tious", &["SH", "AH", "S"]),
+    ("eigh", &["EY"]),
+    ("ough", &["AO"]),
+    ("augh", &["AO"]),
+    ("igh", &["AY"]),
+    ("dg", &["JH"]),
+    ("ch", &["CH"]),
+    ("ck", &["K"]),
+    ("gh", &[]),
+    ("ph", &["F"]),
+    ("qu", &["K", "W"]),
+    ("sh", &["SH"]),
+    ("th", &["TH"]),
+    ("wh", &["W"]),
+    ("nk", &["NG", "K"]),
+    ("ee", &["IY"]),
+    ("ea", &["IY"]),
+    ("ei", &["EY"]),
+    ("ai", &["EY"]),
+    ("ay", &["EY"]),
+    ("oa", &["OW"]),
+    ("oo", &["UW"]),
+    ("ou", &["AW"]),
+    ("oi", &["OY"]),
+    ("oy", &["OY"]),
+    ("au", &["AO"]),
+    ("aw", &["AO"]),
+    ("ew", &["UW"]),
+];
+
+/// Silent first letters at the start of a word.
+const SILENT_ONSETS: &[&str] = &["kn", "wr", "gn", "ps", "pn"];
+
+/// Suffixes that put stress on the syllable `n` nuclei before the end.
+const STRESS_SUFFIXES: &[(&str, usize)] = &[
+    ("ity", 3),
+    ("ical", 3),
+    ("tion", 2),
+    ("sion", 2),
+    ("cious", 2),
+    ("tious", 2),
+    ("ic", 2),
+];
+
+/// Fold an accented Latin letter to its base letter.
+fn fold(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'æ' => 'a',
+        'ç' => 'c',
+        'è' | 'é' | 'ê' | 'ë' => 'e',
+        'ì' | 'í' | 'î' | 'ï' => 'i',
+        'ñ' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'œ' => 'o',
+        'ù' | 'ú' | 'û' | 'ü' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ß' => 's',
+        c => c,
+    }
+}
+
+fn is_vowel_char(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
+}
+
+fn short_vowel(c: char) -> &'static str {
+    match c {
+        'a' => "AE",
+        'e' => "EH",
+        'i' | 'y' => "IH",
+        'o' => "AA",
+        _ => "AH",
+    }
+}
+
+fn long_vowel(c: char) -> &'static str {
+    match c {
+        'a' => "EY",
+        'e' => "IY",
+        'i' | 'y' => "AY",
+        'o' => "OW",
+        _ => "UW",
+    }
+}
+
+/// Pronounce a word from its spelling alone. Always returns at least one
+/// vowel, so every word yields a syllable.
+pub fn letter_to_sound(word: &str) -> Vec<String> {
+    let chars: Vec<char> = word
+        .to_lowercase()
+        .chars()
+        .map(fold)
+        .filter(|c| c.is_ascii_lowercase())
+        .collect();
+    let n = chars.len();
+    let at = |j: usize| chars.get(j).copied();
+    let matches_at = |j: usize, s: &str| s.chars().enumerate().all(|(k, c)| at(j + k) == Some(c));
+    let vowel_at = |j: usize| at(j).is_some_and(is_vowel_char);
+    let mut phones: Vec<&str> = Vec::new();
+    let mut i = 0;
+
+    if SILENT_ONSETS.iter().any(|s| matches_at(0, s)) && n > 2 {
+        i = 1;
+    }
+
+    'outer: while i < n {
+        let c = chars[i];
+        let next = at(i + 1);
+        let prev = if i > 0 { Some(chars[i - 1]) } else { None };
+        let has_vowel = phones.iter().any(|p| is_vowel(p));
+
+        // -sion is /ʒən/ after a vowel, /ʃən/ after a consonant
+        if matches_at(i, "sion") {
+            let voiced = prev.is_some_and(is_vowel_char);
+            phones.extend([if voiced { "ZH" } else { "SH" }, "AH", "N"]);
+            i += 4;
+            continue;
+        }
+        // Final -ed: /ɪd/ after t or d, /d/ elsewhere
+        if c == 'e' && next == Some('d') && i + 2 == n && n > 3 && has_vowel {
+            if matches!(prev, Some('t' | 'd')) {
+                phones.push("IH");
+            }
+            phones.push("D");
+            break;
+        }
+        // Final -le (-led, -les) after a consonant is a syllabic l
+        if c == 'l'
+            && next == Some('e')
+            && (i + 2 == n || (i + 3 == n && matches!(at(i + 2), Some('d' | 's'))))
+            && prev.is_some_and(|p| !is_vowel_char(p))
+            && has_vowel
+        {
+            phones.extend(["AH", "L"]);
+            match at(i + 2) {
+                Some('d') => phones.push("D"),
+                Some('s') => phones.push("Z"),
+                _ => {}
+            }
+            break;
+        }
+        // Magic e: vowel, one consonant, then a final e (or -es/-ed)
+        if is_vowel_char(c)
+            && !prev.is_some_and(is_vowel_char)
+            && next.is_some_and(|d| !is_vowel_char(d) && !matches!(d, 'r' | 'w' | 'x' | 'y'))
+            && at(i + 2) == Some('e')
+            && (i + 3 == n || (i + 4 == n && matches!(at(i + 3), Some('s' | 'd'))))
+        {
+            phones.push(long_vowel(c));
+            i += 1;
+            continue;
+        }
+        // r-colored vowels before a consonant or the end
+        if is_vowel_char(c) && next == Some('r') && !vowel_at(i + 2) {
+            match c {
+                'a' => phones.extend(["AA", "R"]),
+                'o' => phones.extend(["AO", "R"]),
+                _ => phones.push("ER"),
+            }
+            i += 2;
+            if at(i) == Some('r') {
+                i += 1;
+            }
+            continue;
+        }
+        // Word-final spellings of /i/, /aɪ/, /oʊ/, /u/
+        if i + 2 == n {
+            let last = match (c, next) {
+                ('i', Some('e')) if has_vowel => Some("IY"),
+                ('i', Some('e')) => Some("AY"),
+                ('e', Some('y')) => Some("IY"),
+                ('o', Some('w')) => Some("OW"),
+                ('u', Some('e')) => Some("UW"),
+                _ => None,
+            };
+            if let Some(v) = last {
+                phones.push(v);
+                break;
+            }
+        }
+        for (spelling, cluster) in CLUSTERS {
+            if matches_at(i, spelling) {
+                // Initial gh is a hard g
+                if *spelling == "gh" && i == 0 {
+                    phones.push("G");
+                }
+                phones.extend(cluster.iter());
+                i += spelling.len();
+                continue 'outer;
+            }
+        }
+
+        if is_vowel_char(c) {
+            if c == 'e' && i + 1 == n && has_vowel {
+                // Silent final e
+            } else if c == 'e' && i + 1 == n {
+                phones.push("IY");
+            } else {
+                phones.push(short_vowel(c));
+            }
+            i += 1;
+            continue;
+        }
+
+        // Doubled consonants sound once
+        if next == Some(c) && c != 'c' {
+            i += 1;
+            continue;
+        }
+        let soft = matches!(next, Some('e' | 'i' | 'y'));
+        match c {
+            'b' => phones.push("B"),
+            'c' if soft => phones.push("S"),
+            'c' => phones.push("K"),
+            'd' => phones.push("D"),
+            'f' => phones.push("F"),
+            'g' if next == Some('n') && i + 2 == n => {}
+            'g' if soft && i > 0 => phones.push("JH"),
+            'g' => phones.push("G"),
+            'h' if has_vowel && !next.is_some_and(is_vowel_char) => {}
+            'h' => phones.push("HH"),
+            'j' => phones.push("JH"),
+            'k' => phones.push("K"),
+            'l' => phones.push("L"),
+            'm' => phones.push("M"),
+            'n' if next == Some('g') && !matches!(at(i + 2), Some('e' | 'i' | 'y')) => {
+                phones.push("NG");
+                i += 1;
+            }
+            'n' => phones.push("N"),
+            'p' => phones.push("P"),
+            'q' => phones.push("K"),
+            'r' => phones.push("R"),
+            's' if i + 1 == n && prev.is_some_and(|p| is_vowel_char(p) || "bdglmnrv".contains(p)) && has_vowel => {
+                phones.push("Z")
+            }
+            's' => phones.push("S"),
+            't' => phones.push("T"),
+            'v' => phones.push("V"),
+            'w' if next.is_some_and(is_vowel_char) || i == 0 => phones.push("W"),
+            'w' => phones.push("UW"),
+            'x' if i == 0 => phones.push("Z"),
+            'x' => phones.extend(["K", "S"]),
+            'y' if i == 0 || next.is_some_and(is_vowel_char) => phones.push("Y"),
+            // Final y: /i/ after another vowel sound, /aɪ/ in "my", "fly"
+            'y' if i + 1 == n => phones.push(if has_vowel { "IY" } else { "AY" }),
+            'y' => phones.push("IH"),
+            'z' => phones.push("Z"),
+            _ => {}
+        }
+        i += 1;
+    }
+
+    // Every syllable needs a nucleus: "brr", "pfft"
+    if !phones.iter().any(|p| is_vowel(p)) {
+        let at = if phones.len() > 1 { 1 } else { phones.len() };
+        phones.insert(at, "AH");
+    }
+
+    let spelled: String = chars.iter().collect();
+    let nuclei = phones.iter().filter(|p| is_vowel(p)).count();
+    let stressed = STRESS_SUFFIXES
+        .iter()
+        .find(|(suffix, _)| spelled.ends_with(suffix))
+        .map_or(0, |&(_, back)| nuclei.saturating_sub(back));
+    mark_stress(&phones, stressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter_to_sound_spelling_rules() {
+        assert_eq!(letter_to_sound("bat"), vec!["B", "AE1", "T"]);
+        assert_eq!(letter_to_sound("knight"), vec!["N", "AY1", "T"]);
+        assert_eq!(letter_to_sound("zope"), vec!["Z", "OW1", "P"]);
+        assert_eq!(letter_to_sound("glorp"), vec!["G", "L", "AO1", "R", "P"]);
+        assert_eq!(letter_to_sound("shreek"), vec!["SH", "R", "IY1", "K"]);
+        assert_eq!(letter_to_sound("zibbled"), vec!["Z", "IH1", "B", "AH0", "L", "D"]);
+        assert_eq!(letter_to_sound("Renée"), vec!["R", "EH1", "N", "IY0"]);
+    }
+
+    #[test]
+    fn test_letter_to_sound_stress_and_nucleus() {
+        // -ic pulls stress onto the syllable before it
+        assert_eq!(
+            letter_to_sound("blorptastic"),
+            vec!["B", "L", "AO0", "R", "P", "T", "AE1", "S", "T", "IH0", "K"]
+        );
+        // One primary stress per word
+        let phones = letter_to_sound("flimbertaxion");
+        assert_eq!(phones.iter().filter(|p| p.ends_with('1')).count(), 1);
+        // Vowelless words still get a nucleus
+        assert_eq!(letter_to_sound("brr"), vec!["B", "AH1", "R"]);
+        assert_eq!(letter_to_sound("'"), vec!["AH1"]);
+    }
+}
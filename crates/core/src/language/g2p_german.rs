@@ -0,0 +1,181 @@
+//! Rule-based German grapheme-to-phoneme conversion.
+//!
+//! Covers the regular spelling rules: consonant clusters (sch, tsch, ch,
+//! pf, tz), diphthongs, vowel length marks (doubled vowels, ie, silent h),
+//! umlauts, and final devoicing. Stress falls on the first syllable unless
+//! the word starts with an unstressed prefix.
+
+use super::g2p::{is_vowel, mark_stress, G2p};
+
+/// German G2P backend.
+pub struct GermanG2p;
+
+/// Prefixes that never take stress.
+const UNSTRESSED_PREFIXES: &[&str] = &["be", "emp", "ent", "er", "ge", "ver", "zer"];
+
+/// Multi-letter spellings, longest first within each starting letter.
+const CLUSTERS: &[(&str, &[&str])] = &[
+    ("tsch", &["CH"]),
+    ("sch", &["SH"]),
+    ("chs", &["K", "S"]),
+    ("ck", &["K"]),
+    ("ph", &["F"]),
+    ("pf", &["P", "F"]),
+    ("qu", &["K", "V"]),
+    ("th", &["T"]),
+    ("dt", &["T"]),
+    ("tz", &["T", "S"]),
+    ("ng", &["NG"]),
+    ("nk", &["NG", "K"]),
+    ("ss", &["S"]),
+    ("ie", &["IY"]),
+    ("ei", &["AY"]),
+    ("ai", &["AY"]),
+    ("ey", &["AY"]),
+    ("eu", &["OY"]),
+    ("äu", &["OY"]),
+    ("au", &["AW"]),
+    ("aa", &["AA"]),
+    ("ee", &["EY"]),
+    ("oo", &["OW"]),
+];
+
+fn vowel_phone(c: char) -> Option<&'static str> {
+    match c {
+        'a' => Some("AA"),
+        'e' | 'ä' => Some("EH"),
+        'i' => Some("IH"),
+        'o' => Some("OW"),
+        'u' => Some("UW"),
+        'ö' => Some("OE"),
+        'ü' | 'y' => Some("UE"),
+        _ => None,
+    }
+}
+
+fn is_vowel_char(c: char) -> bool {
+    vowel_phone(c).is_some()
+}
+
+impl G2p for GermanG2p {
+    fn word_to_phonemes(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.to_lowercase().chars().filter(|c| c.is_alphabetic()).collect();
+        let n = chars.len();
+        let at = |j: usize| chars.get(j).copied();
+        let matches_at = |j: usize, s: &str| s.chars().enumerate().all(|(k, c)| at(j + k) == Some(c));
+        let mut phones: Vec<&str> = Vec::new();
+        let mut i = 0;
+
+        'outer: while i < n {
+            let c = chars[i];
+            let next = at(i + 1);
+            let prev = if i > 0 { Some(chars[i - 1]) } else { None };
+
+            // Word-initial sp/st are /ʃp/, /ʃt/
+            if i == 0 && c == 's' && matches!(next, Some('p' | 't')) {
+                phones.extend(["SH", if next == Some('p') { "P" } else { "T" }]);
+                i += 2;
+                continue;
+            }
+            // Final -er is a syllabic r
+            if c == 'e' && next == Some('r') && i + 2 == n && phones.iter().any(|p| is_vowel(p)) {
+                phones.push("ER");
+                break;
+            }
+            for (spelling, cluster) in CLUSTERS {
+                if matches_at(i, spelling) {
+                    phones.extend(cluster.iter());
+                    i += spelling.chars().count();
+                    // Silent lengthening h after a vowel
+                    if is_vowel(cluster[cluster.len() - 1]) && at(i) == Some('h') {
+                        i += 1;
+                    }
+                    continue 'outer;
+                }
+            }
+
+            if let Some(v) = vowel_phone(c) {
+                let reduced = c == 'e'
+                    && phones.iter().any(|p| is_vowel(p))
+                    && (i + 1 == n || (i + 2 == n && matches!(next, Some('n' | 'l' | 's'))));
+                i += 1;
+                if at(i) == Some('h') {
+                    // Silent h marks a long vowel
+                    phones.push(if c == 'e' { "EY" } else if c == 'i' { "IY" } else { v });
+                    i += 1;
+                } else {
+                    phones.push(if reduced { "AH" } else { v });
+                }
+                continue;
+            }
+
+            let last = i + 1 == n;
+            match c {
+                'b' => phones.push(if last { "P" } else { "B" }),
+                'd' => phones.push(if last { "T" } else { "D" }),
+                'g' => phones.push(if last { "K" } else { "G" }),
+                'c' if next == Some('h') => {
+                    // Ach-laut after back vowels, ich-laut elsewhere
+                    let back = matches!(prev, Some('a' | 'o' | 'u')) && !(i >= 2 && matches!(chars[i - 2], 'e' | 'ä'));
+                    phones.push(if back { "X" } else { "HH" });
+                    i += 1;
+                }
+                'c' if matches!(next, Some('e' | 'i' | 'ä')) => phones.extend(["T", "S"]),
+                'c' | 'k' => phones.push("K"),
+                'f' | 'v' => phones.push("F"),
+                'h' => phones.push("HH"),
+                'j' => phones.push("Y"),
+                'l' => phones.push("L"),
+                'm' => phones.push("M"),
+                'n' => phones.push("N"),
+                'p' => phones.push("P"),
+                'r' => phones.push("R"),
+                's' if next.is_some_and(is_vowel_char) && prev.is_none_or(is_vowel_char) => phones.push("Z"),
+                's' | 'ß' => phones.push("S"),
+                't' => phones.push("T"),
+                'w' => phones.push("V"),
+                'x' => phones.extend(["K", "S"]),
+                'z' => phones.extend(["T", "S"]),
+                _ => {}
+            }
+            i += 1;
+        }
+
+        let word: String = chars.iter().collect();
+        let nuclei = phones.iter().filter(|p| is_vowel(p)).count();
+        let prefixed = UNSTRESSED_PREFIXES
+            .iter()
+            .any(|p| word.starts_with(p) && n > p.len() + 2);
+        let stressed = if prefixed && nuclei > 1 { 1 } else { 0 };
+        mark_stress(&phones, stressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn g2p(word: &str) -> Vec<String> {
+        GermanG2p.word_to_phonemes(word)
+    }
+
+    #[test]
+    fn test_german_clusters_and_vowels() {
+        assert_eq!(g2p("Schule"), vec!["SH", "UW1", "L", "AH0"]);
+        assert_eq!(g2p("Straße"), vec!["SH", "T", "R", "AA1", "S", "AH0"]);
+        assert_eq!(g2p("Zeit"), vec!["T", "S", "AY1", "T"]);
+        assert_eq!(g2p("Buch"), vec!["B", "UW1", "X"]);
+        assert_eq!(g2p("ich"), vec!["IH1", "HH"]);
+        assert_eq!(g2p("schön"), vec!["SH", "OE1", "N"]);
+        assert_eq!(g2p("Kühe"), vec!["K", "UE1", "AH0"]);
+        assert_eq!(g2p("Vater"), vec!["F", "AA1", "T", "ER0"]);
+    }
+
+    #[test]
+    fn test_german_devoicing_and_stress() {
+        assert_eq!(g2p("Hund"), vec!["HH", "UW1", "N", "T"]);
+        assert_eq!(g2p("Wasser"), vec!["V", "AA1", "S", "ER0"]);
+        // Unstressed prefix moves stress to the stem
+        assert_eq!(g2p("verstehen"), vec!["F", "EH0", "R", "S", "T", "EY1", "AH0", "N"]);
+    }
+}
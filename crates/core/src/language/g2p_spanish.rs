@@ -0,0 +1,157 @@
+//! Rule-based Spanish grapheme-to-phoneme conversion.
+//!
+//! Spanish spelling is close to phonemic, so a few context rules cover
+//! most words. Uses seseo (`c` before e/i and `z` → S) and yeísmo
+//! (`ll` → Y), as in most of Latin America. Stress follows the written
+//! accent, otherwise the penultimate/final syllable rule.
+
+use super::g2p::{is_vowel, mark_stress, G2p};
+
+/// Spanish G2P backend.
+pub struct SpanishG2p;
+
+fn vowel_phone(c: char) -> Option<&'static str> {
+    match c {
+        'a' | 'á' => Some("AA"),
+        'e' | 'é' => Some("EY"),
+        'i' | 'í' => Some("IY"),
+        'o' | 'ó' => Some("OW"),
+        'u' | 'ú' | 'ü' => Some("UW"),
+        _ => None,
+    }
+}
+
+fn is_vowel_char(c: char) -> bool {
+    vowel_phone(c).is_some()
+}
+
+fn is_front(c: char) -> bool {
+    matches!(c, 'e' | 'é' | 'i' | 'í')
+}
+
+fn is_strong(c: char) -> bool {
+    matches!(c, 'a' | 'e' | 'o' | 'á' | 'é' | 'í' | 'ó' | 'ú')
+}
+
+impl G2p for SpanishG2p {
+    fn word_to_phonemes(&self, word: &str) -> Vec<String> {
+        let chars: Vec<char> = word.to_lowercase().chars().filter(|c| c.is_alphabetic()).collect();
+        let at = |j: usize| chars.get(j).copied();
+        let mut phones: Vec<&str> = Vec::new();
+        let mut accented: Option<usize> = None;
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let next = at(i + 1);
+            if let Some(v) = vowel_phone(c) {
+                // Unaccented i/u next to another vowel is a glide
+                let weak = matches!(c, 'i' | 'u' | 'ü');
+                let before_vowel = next.is_some_and(is_vowel_char);
+                let after_strong = i > 0 && is_strong(chars[i - 1]);
+                if weak && (before_vowel || after_strong) {
+                    phones.push(if c == 'i' { "Y" } else { "W" });
+                } else {
+                    if matches!(c, 'á' | 'é' | 'í' | 'ó' | 'ú') {
+                        accented = Some(phones.iter().filter(|p| is_vowel(p)).count());
+                    }
+                    phones.push(v);
+                }
+                i += 1;
+                continue;
+            }
+
+            match c {
+                'b' | 'v' => phones.push("B"),
+                'c' if next == Some('h') => {
+                    phones.push("CH");
+                    i += 1;
+                }
+                'c' if next.is_some_and(is_front) => phones.push("S"),
+                'c' | 'k' => phones.push("K"),
+                'd' => phones.push("D"),
+                'f' => phones.push("F"),
+                'g' if next.is_some_and(is_front) => phones.push("X"),
+                'g' if next == Some('u') && at(i + 2).is_some_and(is_front) => {
+                    // Silent u in gue/gui
+                    phones.push("G");
+                    i += 1;
+                }
+                'g' => phones.push("G"),
+                'h' => {}
+                'j' => phones.push("X"),
+                'l' if next == Some('l') => {
+                    phones.push("Y");
+                    i += 1;
+                }
+                'l' => phones.push("L"),
+                'm' => phones.push("M"),
+                'n' => phones.push("N"),
+                'ñ' => phones.extend(["N", "Y"]),
+                'p' => phones.push("P"),
+                'q' => {
+                    phones.push("K");
+                    if next == Some('u') {
+                        i += 1;
+                    }
+                }
+                'r' if next == Some('r') => {
+                    phones.push("R");
+                    i += 1;
+                }
+                'r' => phones.push("R"),
+                's' | 'z' => phones.push("S"),
+                't' => phones.push("T"),
+                'w' => phones.push("W"),
+                'x' => phones.extend(["K", "S"]),
+                'y' if next.is_some_and(is_vowel_char) => phones.push("Y"),
+                // Final y after a vowel ("hoy") is a glide; otherwise "y" is /i/
+                'y' if i > 0 && is_vowel_char(chars[i - 1]) => phones.push("Y"),
+                'y' => phones.push("IY"),
+                _ => {}
+            }
+            i += 1;
+        }
+
+        let nuclei = phones.iter().filter(|p| is_vowel(p)).count();
+        let stressed = accented.unwrap_or_else(|| {
+            if matches!(chars.last(), Some('n' | 's')) || chars.last().is_some_and(|&c| is_vowel_char(c)) {
+                nuclei.saturating_sub(2)
+            } else {
+                nuclei.saturating_sub(1)
+            }
+        });
+        mark_stress(&phones, stressed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn g2p(word: &str) -> Vec<String> {
+        SpanishG2p.word_to_phonemes(word)
+    }
+
+    #[test]
+    fn test_spanish_context_rules() {
+        assert_eq!(g2p("casa"), vec!["K", "AA1", "S", "AA0"]);
+        assert_eq!(g2p("gente"), vec!["X", "EY1", "N", "T", "EY0"]);
+        assert_eq!(g2p("guitarra"), vec!["G", "IY0", "T", "AA1", "R", "AA0"]);
+        assert_eq!(g2p("queso"), vec!["K", "EY1", "S", "OW0"]);
+        assert_eq!(g2p("llama"), vec!["Y", "AA1", "M", "AA0"]);
+        assert_eq!(g2p("niño"), vec!["N", "IY1", "N", "Y", "OW0"]);
+        assert_eq!(g2p("hola"), vec!["OW1", "L", "AA0"]);
+    }
+
+    #[test]
+    fn test_spanish_stress_and_glides() {
+        // Final consonant other than n/s: final stress
+        assert_eq!(g2p("ciudad"), vec!["S", "Y", "UW0", "D", "AA1", "D"]);
+        // Written accent wins
+        assert_eq!(g2p("canción"), vec!["K", "AA0", "N", "S", "Y", "OW1", "N"]);
+        assert_eq!(g2p("¡Bien!"), vec!["B", "Y", "EY1", "N"]);
+        assert_eq!(g2p("hoy"), vec!["OW1", "Y"]);
+        assert_eq!(g2p("y"), vec!["IY1"]);
+    }
+}
@@ -1,4 +1,7 @@
 pub mod g2p;
+pub mod g2p_english;
+pub mod g2p_spanish;
+pub mod g2p_german;
 pub mod syllabify_arpabet;
 pub mod syllabify_ipa;
 pub mod syllabify;
@@ -1,9 +1,9 @@
 //! Phonotactic scoring for natural-sounding syllable ordering.
 
 use rand::seq::SliceRandom;
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
 use crate::types::Syllable;
 
@@ -16,7 +16,7 @@ lazy_static::lazy_static! {
         // 2: Affricates
         for p in &["CH", "JH"] { m.insert(*p, 2); }
         // 3: Fricatives
-        for p in &["F", "V", "TH", "DH", "S", "Z", "SH", "ZH", "HH"] { m.insert(*p, 3); }
+        for p in &["F", "V", "TH", "DH", "S", "Z", "SH", "ZH", "HH", "X"] { m.insert(*p, 3); }
         // 4: Nasals
         for p in &["M", "N", "NG"] { m.insert(*p, 4); }
         // 5: Liquids
@@ -36,7 +36,7 @@ lazy_static::lazy_static! {
         [
             "AA", "AE", "AH", "AO", "AW", "AY",
             "EH", "ER", "EY", "IH", "IY",
-            "OW", "OY", "UH", "UW",
+            "OW", "OY", "UH", "UW", "UE", "OE",
         ].into_iter().collect()
     };
 
@@ -222,6 +222,117 @@ pub fn order_syllables(
     best
 }
 
+/// Marks a word edge in the bigram model.
+const WORD_EDGE: &str = "#";
+
+/// Phoneme-bigram model of the source transcript: how often each phoneme
+/// follows another, with word edges as their own symbol.
+#[derive(Debug, Clone, Default)]
+pub struct PhonemeBigrams {
+    counts: HashMap<(String, String), usize>,
+    totals: HashMap<String, usize>,
+    vocabulary: usize,
+}
+
+/// Phoneme label without stress digits, so "AH0" and "AH1" count together.
+fn bigram_symbol(label: &str) -> String {
+    label.trim_end_matches(|c: char| c.is_ascii_digit()).to_string()
+}
+
+impl PhonemeBigrams {
+    /// Count phoneme pairs over each source's syllables in source order.
+    /// A change of `word_index` ends one word and starts the next.
+    pub fn train<'a>(sources: impl IntoIterator<Item = &'a [Syllable]>) -> Self {
+        let mut model = Self::default();
+        for syllables in sources {
+            let mut prev = WORD_EDGE.to_string();
+            let mut word = None;
+            for syl in syllables {
+                if word.is_some_and(|w| w != syl.word_index) && prev != WORD_EDGE {
+                    model.add(&prev, WORD_EDGE);
+                    prev = WORD_EDGE.to_string();
+                }
+                word = Some(syl.word_index);
+                for phone in &syl.phonemes {
+                    let symbol = bigram_symbol(&phone.label);
+                    model.add(&prev, &symbol);
+                    prev = symbol;
+                }
+            }
+            if prev != WORD_EDGE {
+                model.add(&prev, WORD_EDGE);
+            }
+        }
+        let symbols: HashSet<&String> = model.counts.keys().flat_map(|(a, b)| [a, b]).collect();
+        model.vocabulary = symbols.len();
+        model
+    }
+
+    fn add(&mut self, a: &str, b: &str) {
+        *self.counts.entry((a.to_string(), b.to_string())).or_default() += 1;
+        *self.totals.entry(a.to_string()).or_default() += 1;
+    }
+
+    /// Add-one smoothed probability that phoneme `b` follows `a`. Pass
+    /// `None` for a word edge.
+    pub fn probability(&self, a: Option<&str>, b: Option<&str>) -> f64 {
+        let a = a.map_or(WORD_EDGE.to_string(), bigram_symbol);
+        let b = b.map_or(WORD_EDGE.to_string(), bigram_symbol);
+        let count = self.counts.get(&(a.clone(), b)).copied().unwrap_or(0);
+        let total = self.totals.get(&a).copied().unwrap_or(0);
+        (count + 1) as f64 / (total + self.vocabulary.max(1)) as f64
+    }
+
+    /// Whether the model saw any phonemes.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+/// Order syllables into a word by walking the bigram model: each next
+/// syllable is drawn with probability proportional to how likely its first
+/// phoneme is to follow the previous syllable's last phoneme in the source.
+pub fn order_syllables_markov(
+    syllables: &[Syllable],
+    model: &PhonemeBigrams,
+    seed: Option<u64>,
+) -> Vec<Syllable> {
+    if syllables.len() <= 1 {
+        return syllables.to_vec();
+    }
+
+    let mut rng = match seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => StdRng::from_entropy(),
+    };
+
+    let mut remaining: Vec<&Syllable> = syllables.iter().collect();
+    let mut ordered = Vec::with_capacity(syllables.len());
+    let mut prev: Option<&str> = None;
+    while !remaining.is_empty() {
+        let weights: Vec<f64> = remaining
+            .iter()
+            .map(|syl| model.probability(prev, syl.phonemes.first().map(|p| p.label.as_str())))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let mut r = rng.gen::<f64>() * total;
+        let mut pick = remaining.len() - 1;
+        for (i, w) in weights.iter().enumerate() {
+            if r < *w {
+                pick = i;
+                break;
+            }
+            r -= w;
+        }
+        let syl = remaining.remove(pick);
+        if let Some(last) = syl.phonemes.last() {
+            prev = Some(last.label.as_str());
+        }
+        ordered.push(syl.clone());
+    }
+    ordered
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -309,6 +420,41 @@ mod tests {
         assert_eq!(result.len(), 1);
     }
 
+    #[test]
+    fn test_phoneme_bigrams_and_markov_order() {
+        let mut source = Vec::new();
+        for i in 0..20 {
+            let mut a = make_syl(&["S", "T", "AA1"]);
+            let mut b = make_syl(&["T", "IY0"]);
+            a.word_index = i;
+            b.word_index = i;
+            source.extend([a, b]);
+        }
+        let model = PhonemeBigrams::train([source.as_slice()]);
+        assert!(!model.is_empty());
+        // AA -> T is seen forty times, AA -> S never
+        assert!(model.probability(Some("AA1"), Some("T")) > model.probability(Some("AA0"), Some("S")));
+        // Words start with S and end with IY
+        assert!(model.probability(None, Some("S")) > model.probability(None, Some("T")));
+        assert!(model.probability(Some("IY"), None) > model.probability(Some("IY"), Some("S")));
+
+        let word = vec![make_syl(&["T", "IY0"]), make_syl(&["S", "T", "AA1"])];
+        let mut source_order = 0;
+        for seed in 0..50 {
+            let ordered = order_syllables_markov(&word, &model, Some(seed));
+            assert_eq!(ordered.len(), 2);
+            if ordered[0].phonemes[0].label == "S" {
+                source_order += 1;
+            }
+        }
+        assert!(source_order > 35, "source phonotactics should win most draws: {}", source_order);
+        assert_eq!(
+            order_syllables_markov(&word, &model, Some(3))[0].phonemes[0].label,
+            order_syllables_markov(&word, &model, Some(3))[0].phonemes[0].label
+        );
+        assert!(order_syllables_markov(&[], &model, None).is_empty());
+    }
+
     #[test]
     fn test_order_syllables_empty() {
         let result = order_syllables(&[], None, 10);
@@ -5,7 +5,7 @@
 
 use crate::types::{Phoneme, Syllable, WordTimestamp};
 
-use super::g2p;
+use super::g2p::Language;
 use super::syllabify_arpabet;
 
 /// Split a word's phonemes into syllables with estimated timestamps.
@@ -98,6 +98,12 @@ pub fn syllabify_word(
 /// Uses G2P to get phonemes for each word, then the ARPABET syllabifier
 /// to split into syllables with proportionally distributed timestamps.
 pub fn syllabify_words(words: &[WordTimestamp]) -> Vec<Syllable> {
+    syllabify_words_in(words, Language::English)
+}
+
+/// `syllabify_words` with the G2P backend for `language`.
+pub fn syllabify_words_in(words: &[WordTimestamp], language: Language) -> Vec<Syllable> {
+    let g2p = language.g2p();
     let mut all_syllables = Vec::new();
 
     for (i, w) in words.iter().enumerate() {
@@ -106,7 +112,7 @@ pub fn syllabify_words(words: &[WordTimestamp]) -> Vec<Syllable> {
             continue;
         }
 
-        let phonemes = g2p::word_to_phonemes(text);
+        let phonemes = g2p.word_to_phonemes(text);
         if phonemes.is_empty() {
             continue;
         }
@@ -118,6 +124,26 @@ pub fn syllabify_words(words: &[WordTimestamp]) -> Vec<Syllable> {
     all_syllables
 }
 
+/// Merge aligned syllables into one unit per word.
+///
+/// Consecutive syllables sharing a word index become a single `Syllable`
+/// spanning the whole word with all of its phonemes. Already-merged input
+/// is returned unchanged.
+pub fn merge_into_words(syllables: &[Syllable]) -> Vec<Syllable> {
+    let mut words: Vec<Syllable> = Vec::new();
+    for syl in syllables {
+        match words.last_mut() {
+            Some(w) if w.word_index == syl.word_index && w.word == syl.word => {
+                w.phonemes.extend(syl.phonemes.iter().cloned());
+                w.start = w.start.min(syl.start);
+                w.end = w.end.max(syl.end);
+            }
+            _ => words.push(syl.clone()),
+        }
+    }
+    words
+}
+
 fn round4(v: f64) -> f64 {
     (v * 10000.0).round() / 10000.0
 }
@@ -178,6 +204,16 @@ mod tests {
         assert!(result.len() >= 2);
     }
 
+    #[test]
+    fn test_syllabify_words_in_other_languages() {
+        let word = |w: &str| WordTimestamp { word: w.to_string(), start: 0.0, end: 0.6 };
+        let es = syllabify_words_in(&[word("guitarra")], Language::Spanish);
+        assert_eq!(es.len(), 3);
+        assert_eq!(es[1].phonemes[1].label, "AA1");
+        let de = syllabify_words_in(&[word("Mädchen")], Language::German);
+        assert_eq!(de.len(), 2);
+    }
+
     #[test]
     fn test_syllabify_words_empty() {
         let result = syllabify_words(&[]);
@@ -197,4 +233,37 @@ mod tests {
         assert!(result[0].phonemes[0].start >= 1.0);
         assert!(result[0].phonemes[2].end <= 2.001);
     }
+
+    #[test]
+    fn test_merge_into_words() {
+        let words = vec![
+            WordTimestamp {
+                word: "banana".to_string(),
+                start: 0.0,
+                end: 0.9,
+            },
+            WordTimestamp {
+                word: "cat".to_string(),
+                start: 1.0,
+                end: 1.3,
+            },
+        ];
+        let syllables = syllabify_words(&words);
+        assert!(syllables.len() > 2);
+
+        let merged = merge_into_words(&syllables);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].word, "banana");
+        assert!((merged[0].start - 0.0).abs() < 0.001);
+        assert!((merged[0].end - 0.9).abs() < 0.001);
+        let banana_phonemes: usize = syllables
+            .iter()
+            .filter(|s| s.word_index == 0)
+            .map(|s| s.phonemes.len())
+            .sum();
+        assert_eq!(merged[0].phonemes.len(), banana_phonemes);
+
+        // Merging is idempotent
+        assert_eq!(merge_into_words(&merged), merged);
+    }
 }
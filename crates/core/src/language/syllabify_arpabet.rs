@@ -26,6 +26,8 @@ lazy_static::lazy_static! {
             "AH0", "UH0", "UW1", "UW2", "UW0", "UW",
             "IY", "EY", "AA", "ER", "AW", "AO", "AY",
             "OW", "OY", "UH", "IH", "EH", "AE", "AH",
+            // Front rounded vowels from the German G2P
+            "UE1", "UE2", "UE0", "OE1", "OE2", "OE0", "UE", "OE",
         ].into_iter().collect();
         for s in SLAX.iter() {
             v.insert(s);
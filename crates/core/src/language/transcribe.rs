@@ -8,24 +8,51 @@ use anyhow::{Context, Result, bail};
 
 use crate::types::{TranscriptionResult, WordTimestamp};
 
+/// Progress event emitted during transcription.
+#[derive(Debug, Clone)]
+pub enum TranscribeProgress {
+    /// Fraction of the audio processed so far, 0.0 to 1.0.
+    Fraction(f64),
+    /// Words from a newly decoded segment, with timing spread proportionally
+    /// across the segment. The final result carries token-level timing.
+    Segment(Vec<WordTimestamp>),
+}
+
+/// Callback receiving `TranscribeProgress` events from the inference thread.
+pub type TranscribeCallback = Box<dyn FnMut(TranscribeProgress) + Send>;
+
 /// Transcribe audio and return word-level timestamps.
 ///
 /// Uses native whisper-rs bindings. The whisper model is automatically
-/// downloaded on first use if not found locally.
+/// downloaded on first use if not found locally. With `use_gpu`, inference
+/// runs on the GPU and falls back to CPU if the GPU context cannot be created.
 pub fn transcribe(
     audio_path: &Path,
     model_name: &str,
     language: &str,
     model_dir: Option<&Path>,
+    use_gpu: bool,
+) -> Result<TranscriptionResult> {
+    transcribe_with_progress(audio_path, model_name, language, model_dir, use_gpu, None)
+}
+
+/// Transcribe audio, reporting progress and per-segment words as they decode.
+pub fn transcribe_with_progress(
+    audio_path: &Path,
+    model_name: &str,
+    language: &str,
+    model_dir: Option<&Path>,
+    use_gpu: bool,
+    on_progress: Option<TranscribeCallback>,
 ) -> Result<TranscriptionResult> {
     #[cfg(feature = "whisper-native")]
     {
-        transcribe_native(audio_path, model_name, language, model_dir)
+        transcribe_native(audio_path, model_name, language, model_dir, use_gpu, on_progress)
     }
 
     #[cfg(not(feature = "whisper-native"))]
     {
-        let _ = (audio_path, model_name, language, model_dir);
+        let _ = (audio_path, model_name, language, model_dir, use_gpu, on_progress);
         bail!(
             "Whisper transcription requires the 'whisper-native' feature. \
              Build with: cargo build --features whisper-native"
@@ -33,6 +60,38 @@ pub fn transcribe(
     }
 }
 
+/// Split a decoded segment into words with proportional timing.
+///
+/// Each word gets a share of `[start, end]` proportional to its length.
+/// Bracketed markers like `[BLANK_AUDIO]` are dropped.
+#[cfg_attr(not(any(test, feature = "whisper-native")), allow(dead_code))]
+fn segment_words(text: &str, start: f64, end: f64) -> Vec<WordTimestamp> {
+    let words: Vec<&str> = text
+        .split_whitespace()
+        .filter(|w| !(w.starts_with('[') && w.ends_with(']')))
+        .collect();
+    let total_chars: usize = words.iter().map(|w| w.chars().count()).sum();
+    if total_chars == 0 || end <= start {
+        return Vec::new();
+    }
+
+    let span = end - start;
+    let mut cursor = start;
+    words
+        .into_iter()
+        .map(|w| {
+            let dur = span * w.chars().count() as f64 / total_chars as f64;
+            let word = WordTimestamp {
+                word: w.to_string(),
+                start: cursor,
+                end: cursor + dur,
+            };
+            cursor += dur;
+            word
+        })
+        .collect()
+}
+
 /// Parse Whisper's JSON output into our TranscriptionResult.
 #[cfg(test)]
 fn parse_whisper_json(json_str: &str, default_language: &str) -> Result<TranscriptionResult> {
@@ -85,17 +144,32 @@ fn transcribe_native(
     model_name: &str,
     language: &str,
     model_dir: Option<&Path>,
+    use_gpu: bool,
+    on_progress: Option<TranscribeCallback>,
 ) -> Result<TranscriptionResult> {
-    use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
+    use std::sync::{Arc, Mutex};
+    use whisper_rs::{
+        FullParams, SamplingStrategy, SegmentCallbackData, WhisperContext,
+        WhisperContextParameters,
+    };
 
     // Find or download model
     let model_path = find_model(model_name, model_dir)?;
+    let model_str = model_path.to_str().unwrap();
 
-    let ctx = WhisperContext::new_with_params(
-        model_path.to_str().unwrap(),
-        WhisperContextParameters::default(),
-    )
-    .context("Failed to load whisper model")?;
+    let load = |gpu: bool| {
+        let mut ctx_params = WhisperContextParameters::default();
+        ctx_params.use_gpu(gpu);
+        WhisperContext::new_with_params(model_str, ctx_params)
+    };
+    let ctx = match load(use_gpu) {
+        Ok(ctx) => ctx,
+        Err(e) if use_gpu => {
+            log::warn!("GPU whisper context failed ({}), falling back to CPU", e);
+            load(false).context("Failed to load whisper model")?
+        }
+        Err(e) => return Err(e).context("Failed to load whisper model"),
+    };
 
     // Load and convert audio to f32 mono 16kHz
     let (samples, sr) = crate::audio::io::read_wav(audio_path)?;
@@ -110,6 +184,27 @@ fn transcribe_native(
     params.set_language(Some(language));
     params.set_token_timestamps(true);
 
+    if let Some(callback) = on_progress {
+        let callback = Arc::new(Mutex::new(callback));
+        let segment_callback = Arc::clone(&callback);
+        params.set_segment_callback_safe_lossy(Some(move |data: SegmentCallbackData| {
+            // Segment timestamps are in centiseconds
+            let words = segment_words(
+                &data.text,
+                data.start_timestamp as f64 / 100.0,
+                data.end_timestamp as f64 / 100.0,
+            );
+            if !words.is_empty() {
+                let mut f = segment_callback.lock().unwrap();
+                (*f)(TranscribeProgress::Segment(words));
+            }
+        }));
+        params.set_progress_callback_safe(Some(move |percent: i32| {
+            let mut f = callback.lock().unwrap();
+            (*f)(TranscribeProgress::Fraction(percent.clamp(0, 100) as f64 / 100.0));
+        }));
+    }
+
     let mut state = ctx.create_state().context("Failed to create whisper state")?;
     state
         .full(params, &samples_f32)
@@ -290,6 +385,22 @@ fn dirs_or_default() -> std::path::PathBuf {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_segment_words_proportional() {
+        let words = segment_words(" hi there [BLANK_AUDIO]", 1.0, 2.4);
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[0].word, "hi");
+        assert!((words[0].start - 1.0).abs() < 1e-9);
+        assert!((words[0].end - 1.4).abs() < 1e-9);
+        assert!((words[1].end - 2.4).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_segment_words_empty() {
+        assert!(segment_words("[BLANK_AUDIO]", 0.0, 1.0).is_empty());
+        assert!(segment_words("hello", 1.0, 1.0).is_empty());
+    }
+
     #[test]
     fn test_parse_whisper_json() {
         let json = r#"{
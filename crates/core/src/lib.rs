@@ -2,8 +2,16 @@ pub mod types;
 pub mod audio;
 pub mod language;
 pub mod cache;
+pub mod temp;
 pub mod names;
 pub mod speak;
 pub mod collage;
 pub mod sing;
 pub mod editor;
+pub mod progress;
+pub mod profile;
+pub mod run_diff;
+pub mod hooks;
+pub mod config;
+pub mod webhook;
+pub mod source;
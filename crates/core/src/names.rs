@@ -1,11 +1,14 @@
 //! Generate unique, memorable run names for glottisdale output directories.
 //!
-//! Names are speech/voice/music-themed adjective-noun pairs like
-//! "breathy-bassoon" or "staccato-tenor".
+//! Names are adjective-noun pairs. The default "voice" theme gives
+//! speech/voice/music names like "breathy-bassoon" or "staccato-tenor";
+//! other built-in themes and user word lists are picked with `NameTheme`.
 
+use std::io::Write as _;
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use rand::seq::SliceRandom;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
@@ -90,17 +93,220 @@ pub const NOUNS: &[&str] = &[
     "sarabande", "solfege", "tarantella", "vibraphone", "bagpipe",
 ];
 
+/// Bird-themed adjectives.
+const BIRD_ADJECTIVES: &[&str] = &[
+    "banded", "barred", "beady", "billed", "brooding", "cackling", "cawing",
+    "chirping", "cooing", "crested", "diving", "downy", "feathered", "fledgling",
+    "flocking", "fluffed", "gliding", "hooded", "hopping", "migrating",
+    "molting", "nesting", "nocturnal", "perched", "pecking", "plumed",
+    "preening", "roosting", "ruffled", "soaring", "speckled", "spotted",
+    "squawking", "strutting", "swooping", "tufted", "twittering", "wading",
+    "warbling", "webbed", "winged",
+];
+
+/// Bird-themed nouns.
+const BIRD_NOUNS: &[&str] = &[
+    "albatross", "bittern", "blackbird", "bunting", "buzzard", "cardinal",
+    "chickadee", "cormorant", "corncrake", "crane", "crow", "cuckoo",
+    "curlew", "dipper", "dove", "egret", "finch", "flamingo", "gannet",
+    "goldfinch", "grebe", "heron", "hoopoe", "ibis", "jackdaw", "jay",
+    "kestrel", "kingfisher", "kiwi", "lapwing", "lark", "loon", "lyrebird",
+    "magpie", "mockingbird", "nightingale", "nuthatch", "oriole", "owl",
+    "parakeet", "pelican", "pipit", "plover", "puffin", "quail", "raven",
+    "robin", "sandpiper", "shrike", "skylark", "sparrow", "starling",
+    "swallow", "swift", "tanager", "thrush", "toucan", "vireo", "warbler",
+    "waxwing", "whippoorwill", "wren",
+];
+
+/// Weather-themed adjectives.
+const WEATHER_ADJECTIVES: &[&str] = &[
+    "arctic", "balmy", "blustery", "breezy", "brisk", "clammy", "clear",
+    "cloudy", "crisp", "damp", "dewy", "drizzly", "dusky", "foggy", "freezing",
+    "frosty", "gusty", "hazy", "humid", "icy", "misty", "muggy", "overcast",
+    "pattering", "polar", "rainy", "sleety", "slushy", "snowy", "squally",
+    "steamy", "stormy", "sultry", "sunny", "sweltering", "temperate",
+    "thundery", "torrid", "tropical", "wintry", "windswept",
+];
+
+/// Weather-themed nouns.
+const WEATHER_NOUNS: &[&str] = &[
+    "aurora", "avalanche", "blizzard", "breeze", "chinook", "cirrus",
+    "cloudburst", "cumulus", "cyclone", "deluge", "dewpoint", "downpour",
+    "drizzle", "drought", "dust-devil", "flurry", "fog", "front", "frost",
+    "gale", "graupel", "gust", "hail", "haze", "heatwave", "hoarfrost",
+    "hurricane", "isobar", "jetstream", "lightning", "mistral", "monsoon",
+    "nimbus", "rainbow", "rime", "sirocco", "sleet", "squall", "stratus",
+    "sunshower", "tempest", "thaw", "thunder", "tornado", "typhoon",
+    "updraft", "whirlwind", "zephyr",
+];
+
+/// Built-in name themes.
+pub const THEMES: &[&str] = &["voice", "birds", "weather"];
+
+/// Default theme.
+pub const DEFAULT_THEME: &str = "voice";
+
+/// Adjective and noun lists to draw run names from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameTheme {
+    pub adjectives: Vec<String>,
+    pub nouns: Vec<String>,
+}
+
+impl Default for NameTheme {
+    fn default() -> Self {
+        Self::builtin(DEFAULT_THEME).unwrap()
+    }
+}
+
+impl NameTheme {
+    /// A theme from `THEMES`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        let (adjectives, nouns) = match name {
+            "voice" => (ADJECTIVES, NOUNS),
+            "birds" => (BIRD_ADJECTIVES, BIRD_NOUNS),
+            "weather" => (WEATHER_ADJECTIVES, WEATHER_NOUNS),
+            _ => return None,
+        };
+        Some(Self {
+            adjectives: adjectives.iter().map(|s| s.to_string()).collect(),
+            nouns: nouns.iter().map(|s| s.to_string()).collect(),
+        })
+    }
+
+    /// Resolve a theme by name or path.
+    ///
+    /// Tries the built-in themes, then a user theme directory
+    /// `<config>/names/<name>/`, then `name` itself as a directory. A theme
+    /// directory holds `adjectives.txt` and `nouns.txt`.
+    pub fn load(name: &str) -> Result<Self> {
+        if let Some(theme) = Self::builtin(name) {
+            return Ok(theme);
+        }
+        let user_dir = themes_dir().join(name);
+        if user_dir.is_dir() {
+            return Self::from_dir(&user_dir);
+        }
+        let path = Path::new(name);
+        if path.is_dir() {
+            return Self::from_dir(path);
+        }
+        bail!(
+            "Unknown name theme: '{}'. Built-in: {}; user themes go in {}",
+            name,
+            THEMES.join(", "),
+            themes_dir().display()
+        )
+    }
+
+    /// Load `adjectives.txt` and `nouns.txt` from a directory.
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        let read = |file: &str| -> Result<Vec<String>> {
+            let path = dir.join(file);
+            let text = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read word list: {}", path.display()))?;
+            let words = parse_word_list(&text);
+            if words.is_empty() {
+                bail!("Word list has no usable words: {}", path.display());
+            }
+            Ok(words)
+        };
+        Ok(Self {
+            adjectives: read("adjectives.txt")?,
+            nouns: read("nouns.txt")?,
+        })
+    }
+
+    /// Generate an adjective-noun name from this theme.
+    ///
+    /// If seed is provided, the name is deterministic.
+    pub fn generate(&self, seed: Option<u64>) -> String {
+        let mut rng = match seed {
+            Some(s) => StdRng::seed_from_u64(s),
+            None => StdRng::from_entropy(),
+        };
+        let adj = self.adjectives.choose(&mut rng).unwrap();
+        let noun = self.nouns.choose(&mut rng).unwrap();
+        format!("{}-{}", adj, noun)
+    }
+}
+
+/// Parse a word list: one word per line, `#` comments and blank lines
+/// skipped. Words are lowercased, inner spaces become hyphens, and
+/// characters that are unsafe in directory names are dropped.
+pub fn parse_word_list(text: &str) -> Vec<String> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            line.to_lowercase()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join("-")
+                .chars()
+                .filter(|c| c.is_alphanumeric() || *c == '-')
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect()
+}
+
+/// Glottisdale's config directory.
+///
+/// Uses `GLOTTISDALE_CONFIG_DIR` env var if set, otherwise `~/.config/glottisdale`.
+pub fn config_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("GLOTTISDALE_CONFIG_DIR") {
+        return PathBuf::from(dir);
+    }
+    let home = std::env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+    PathBuf::from(home).join(".config").join("glottisdale")
+}
+
+/// Directory holding user name themes.
+pub fn themes_dir() -> PathBuf {
+    config_dir().join("names")
+}
+
+/// Built-in themes followed by the user themes found in `themes_dir()`.
+pub fn available_themes() -> Vec<String> {
+    let mut themes: Vec<String> = THEMES.iter().map(|s| s.to_string()).collect();
+    let mut user: Vec<String> = std::fs::read_dir(themes_dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().into_string().ok())
+        .filter(|name| !themes.contains(name))
+        .collect();
+    user.sort();
+    themes.extend(user);
+    themes
+}
+
 /// Generate an adjective-noun name like "breathy-bassoon".
 ///
 /// If seed is provided, the name is deterministic.
 pub fn generate_name(seed: Option<u64>) -> String {
-    let mut rng = match seed {
-        Some(s) => StdRng::seed_from_u64(s),
-        None => StdRng::from_entropy(),
-    };
-    let adj = ADJECTIVES.choose(&mut rng).unwrap();
-    let noun = NOUNS.choose(&mut rng).unwrap();
-    format!("{}-{}", adj, noun)
+    NameTheme::default().generate(seed)
+}
+
+/// Derive an RNG seed from a memorable name like "breathy-bassoon".
+///
+/// Case and surrounding whitespace are ignored, so a name shared as a
+/// "recipe" gives the same seed however it is typed. Stable across
+/// platforms and releases (first 8 bytes of its SHA-256).
+pub fn seed_from_name(name: &str) -> u64 {
+    use sha2::{Digest, Sha256};
+    let digest = Sha256::digest(name.trim().to_lowercase().as_bytes());
+    u64::from_be_bytes(digest[..8].try_into().unwrap())
+}
+
+/// Write `seed.json` to a run directory recording the seed and, when it
+/// was derived with [`seed_from_name`], the name it came from.
+pub fn write_seed_record(run_dir: &Path, seed: u64, name: Option<&str>) -> Result<()> {
+    let record = serde_json::json!({ "seed": seed, "seed_name": name });
+    std::fs::write(run_dir.join("seed.json"), serde_json::to_string_pretty(&record)?)
+        .with_context(|| format!("Failed to write seed record in {}", run_dir.display()))
 }
 
 /// Generate a run ID like "2026-02-19-breathy-bassoon".
@@ -110,38 +316,134 @@ pub fn generate_run_id(seed: Option<u64>) -> String {
     format!("{}-{}", today, name)
 }
 
+/// Environment variable that makes run directory creation take the
+/// output root lock (any value but "0" or empty).
+pub const LOCK_OUTPUT_ENV: &str = "GLOTTISDALE_LOCK_OUTPUT";
+
+/// Lock file name inside the output root.
+const LOCK_FILE: &str = ".glottisdale.lock";
+
+/// How long to wait for another process's lock.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A lock older than this was left by a crashed process.
+const STALE_LOCK: Duration = Duration::from_secs(60);
+
+/// Exclusive lock on an output root, held as a lock file that is removed
+/// on drop.
+///
+/// Run directory creation is already atomic; the lock is for setups that
+/// want every process sharing a root (GUI, CLI, batch scripts) to take
+/// turns, e.g. on network filesystems.
+pub struct OutputLock {
+    path: PathBuf,
+}
+
+impl OutputLock {
+    /// Take the lock on `root`, waiting up to `timeout` for another holder.
+    pub fn acquire(root: &Path, timeout: Duration) -> Result<Self> {
+        std::fs::create_dir_all(root)
+            .with_context(|| format!("Failed to create output directory: {}", root.display()))?;
+        let path = root.join(LOCK_FILE);
+        let started = Instant::now();
+        loop {
+            match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", std::process::id())?;
+                    return Ok(Self { path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let age = std::fs::metadata(&path)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|t| t.elapsed().ok());
+                    if age.is_some_and(|a| a > STALE_LOCK) {
+                        log::warn!("Removing stale lock {}", path.display());
+                        std::fs::remove_file(&path).ok();
+                        continue;
+                    }
+                    if started.elapsed() >= timeout {
+                        bail!(
+                            "Output directory is locked by another run: {} (delete it if no other glottisdale is running)",
+                            path.display()
+                        );
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to create lock file: {}", path.display()))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for OutputLock {
+    fn drop(&mut self) {
+        std::fs::remove_file(&self.path).ok();
+    }
+}
+
+/// Whether `GLOTTISDALE_LOCK_OUTPUT` asks for the output root lock.
+fn lock_output_requested() -> bool {
+    std::env::var(LOCK_OUTPUT_ENV).is_ok_and(|v| !v.is_empty() && v != "0")
+}
+
 /// Create a unique run directory inside root.
 ///
 /// If `run_name` is provided, it overrides the adjective-noun part
 /// (date prefix is still added). Handles collisions by appending -2, -3, etc.
+/// Each candidate is claimed with a single `create_dir`, so concurrent
+/// processes never share a directory. Set `GLOTTISDALE_LOCK_OUTPUT=1` to
+/// also hold an [`OutputLock`] on the root while choosing the name.
 pub fn create_run_dir(
     root: &Path,
     seed: Option<u64>,
     run_name: Option<&str>,
 ) -> Result<PathBuf> {
+    create_run_dir_with(root, seed, run_name, &NameTheme::default())
+}
+
+/// `create_run_dir` drawing generated names from `theme`.
+pub fn create_run_dir_with(
+    root: &Path,
+    seed: Option<u64>,
+    run_name: Option<&str>,
+    theme: &NameTheme,
+) -> Result<PathBuf> {
+    let _lock = if lock_output_requested() {
+        Some(OutputLock::acquire(root, LOCK_TIMEOUT)?)
+    } else {
+        None
+    };
+
     let today = chrono_today();
     let base_name = if let Some(name) = run_name {
         format!("{}-{}", today, name)
     } else {
-        let name = generate_name(seed);
+        let name = theme.generate(seed);
         format!("{}-{}", today, name)
     };
 
-    let candidate = root.join(&base_name);
-    if !candidate.exists() {
-        std::fs::create_dir_all(&candidate)?;
-        return Ok(candidate);
-    }
+    std::fs::create_dir_all(root)
+        .with_context(|| format!("Failed to create output directory: {}", root.display()))?;
 
     // Collision: append -2, -3, ...
-    let mut counter = 2u32;
+    let mut counter = 1u32;
     loop {
-        let candidate = root.join(format!("{}-{}", base_name, counter));
-        if !candidate.exists() {
-            std::fs::create_dir_all(&candidate)?;
-            return Ok(candidate);
+        let candidate = if counter == 1 {
+            root.join(&base_name)
+        } else {
+            root.join(format!("{}-{}", base_name, counter))
+        };
+        match std::fs::create_dir(&candidate) {
+            Ok(()) => return Ok(candidate),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => counter += 1,
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to create run directory: {}", candidate.display()))
+            }
         }
-        counter += 1;
     }
 }
 
@@ -178,6 +480,15 @@ fn days_to_date(days: i64) -> (i32, u32, u32) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_seed_from_name_is_stable() {
+        let seed = seed_from_name("breathy-bassoon");
+        assert_eq!(seed, seed_from_name("  Breathy-Bassoon\n"));
+        assert_ne!(seed, seed_from_name("breathy-tenor"));
+        // Pinned so shared names keep producing the same output
+        assert_eq!(seed_from_name(""), 0xe3b0c44298fc1c14);
+    }
+
     #[test]
     fn test_adjectives_all_lowercase() {
         for adj in ADJECTIVES {
@@ -240,6 +551,38 @@ mod tests {
         assert_ne!(a, b);
     }
 
+    #[test]
+    fn test_builtin_themes() {
+        for name in THEMES {
+            let theme = NameTheme::builtin(name).unwrap();
+            for word in theme.adjectives.iter().chain(&theme.nouns) {
+                assert_eq!(parse_word_list(word), vec![word.clone()], "{}: {}", name, word);
+            }
+        }
+        let birds = NameTheme::load("birds").unwrap();
+        let name = birds.generate(Some(7));
+        let noun = name.split_once('-').unwrap().1;
+        assert!(BIRD_NOUNS.contains(&noun));
+        assert_eq!(NameTheme::default().generate(Some(42)), generate_name(Some(42)));
+        assert!(NameTheme::load("no-such-theme").is_err());
+    }
+
+    #[test]
+    fn test_theme_from_dir() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_theme_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("adjectives.txt"), "# mine\nBlue Green\n\n  red/ \n").unwrap();
+        std::fs::write(dir.join("nouns.txt"), "teapot\n").unwrap();
+
+        let theme = NameTheme::load(dir.to_str().unwrap()).unwrap();
+        assert_eq!(theme.adjectives, vec!["blue-green", "red"]);
+        assert!(theme.generate(None).ends_with("-teapot"));
+
+        std::fs::write(dir.join("nouns.txt"), "# nothing\n").unwrap();
+        assert!(NameTheme::from_dir(&dir).is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
     #[test]
     fn test_generate_run_id_format() {
         let id = generate_run_id(Some(42));
@@ -283,6 +626,40 @@ mod tests {
         std::fs::remove_dir_all(&root).ok();
     }
 
+    #[test]
+    fn test_create_run_dir_concurrent() {
+        let root = std::env::temp_dir().join(format!("glottisdale_names_race_{}", std::process::id()));
+        std::fs::remove_dir_all(&root).ok();
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let root = root.clone();
+                std::thread::spawn(move || create_run_dir(&root, Some(42), None).unwrap())
+            })
+            .collect();
+        let mut dirs: Vec<PathBuf> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        dirs.sort();
+        dirs.dedup();
+        assert_eq!(dirs.len(), 8);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn test_output_lock_is_exclusive() {
+        let root = std::env::temp_dir().join(format!("glottisdale_names_lock_{}", std::process::id()));
+        std::fs::remove_dir_all(&root).ok();
+
+        let lock = OutputLock::acquire(&root, Duration::ZERO).unwrap();
+        assert!(root.join(LOCK_FILE).exists());
+        assert!(OutputLock::acquire(&root, Duration::from_millis(50)).is_err());
+        drop(lock);
+        assert!(!root.join(LOCK_FILE).exists());
+        assert!(OutputLock::acquire(&root, Duration::ZERO).is_ok());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
     #[test]
     fn test_create_run_dir_custom_name() {
         let root = std::env::temp_dir().join("glottisdale_names_custom");
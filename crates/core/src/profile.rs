@@ -0,0 +1,295 @@
+//! Opt-in timing of pipeline stages and DSP calls (`--profile`).
+//!
+//! Timed code opens a [`span`]; the span records its wall time when it is
+//! dropped. Recording is off until [`enable`] is called, and a disabled
+//! span costs one atomic load. A run's spans can be summarized per name
+//! with [`report`], or written as a Chrome trace (`chrome://tracing`,
+//! Perfetto) with [`chrome_trace`] to see where threads spent their time.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+/// What a span timed, for grouping in the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Category {
+    /// A pipeline stage, as reported to the progress line.
+    Stage,
+    /// Transcription and alignment of a source.
+    Align,
+    /// Audio processing on a clip or mix.
+    Dsp,
+    /// Decoding and writing audio files.
+    Io,
+}
+
+impl Category {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Stage => "stage",
+            Self::Align => "align",
+            Self::Dsp => "dsp",
+            Self::Io => "io",
+        }
+    }
+}
+
+/// One finished span.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub category: Category,
+    pub name: String,
+    /// Small per-thread number, in order of each thread's first span.
+    pub thread: u64,
+    /// Start, from when profiling was enabled.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static NEXT_THREAD: AtomicU64 = AtomicU64::new(0);
+
+lazy_static::lazy_static! {
+    static ref STARTED: Mutex<Option<Instant>> = Mutex::new(None);
+    static ref EVENTS: Mutex<Vec<Event>> = Mutex::new(Vec::new());
+    /// Stage in progress: its name and when it started.
+    static ref STAGE: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+}
+
+thread_local! {
+    static THREAD: u64 = NEXT_THREAD.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Start recording spans, clearing any recorded before.
+pub fn enable() {
+    *STARTED.lock().unwrap() = Some(Instant::now());
+    EVENTS.lock().unwrap().clear();
+    *STAGE.lock().unwrap() = None;
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Times from creation until dropped.
+#[must_use = "a span records when it is dropped"]
+pub struct Span {
+    /// None when profiling is off.
+    open: Option<(Category, &'static str, Instant)>,
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some((category, name, started)) = self.open.take() {
+            record(category, name.to_string(), started, Instant::now());
+        }
+    }
+}
+
+/// Time the rest of the enclosing scope under `name`.
+pub fn span(category: Category, name: &'static str) -> Span {
+    Span { open: is_enabled().then(|| (category, name, Instant::now())) }
+}
+
+fn record(category: Category, name: String, started: Instant, ended: Instant) {
+    let Some(origin) = *STARTED.lock().unwrap() else {
+        return;
+    };
+    let event = Event {
+        category,
+        name,
+        thread: THREAD.with(|t| *t),
+        start: started.saturating_duration_since(origin),
+        duration: ended.saturating_duration_since(started),
+    };
+    EVENTS.lock().unwrap().push(event);
+}
+
+/// Note the pipeline stage now running, ending the previous one when the
+/// name changes. Progress reporters call this with each update.
+pub fn stage(name: &str) {
+    if !is_enabled() {
+        return;
+    }
+    let mut current = STAGE.lock().unwrap();
+    if current.as_ref().is_some_and(|(stage, _)| stage == name) {
+        return;
+    }
+    let now = Instant::now();
+    if let Some((stage, started)) = current.replace((name.to_string(), now)) {
+        record(Category::Stage, stage, started, now);
+    }
+}
+
+/// End the stage in progress and return every recorded span, in the
+/// order they finished, with the time since profiling was enabled.
+pub fn finish() -> (Vec<Event>, Duration) {
+    if let Some((stage, started)) = STAGE.lock().unwrap().take() {
+        record(Category::Stage, stage, started, Instant::now());
+    }
+    let elapsed = STARTED.lock().unwrap().map(|s| s.elapsed()).unwrap_or_default();
+    (std::mem::take(&mut *EVENTS.lock().unwrap()), elapsed)
+}
+
+/// Spans of one name added up.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Total {
+    pub category: Category,
+    pub name: String,
+    pub count: usize,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+/// Add up `events` by category and name. Stages come first, in the order
+/// they ran; the rest follow, longest total first.
+pub fn totals(events: &[Event]) -> Vec<Total> {
+    let mut index: HashMap<(Category, &str), usize> = HashMap::new();
+    let mut totals: Vec<(Duration, Total)> = Vec::new();
+    for event in events {
+        let i = *index.entry((event.category, event.name.as_str())).or_insert_with(|| {
+            let total = Total {
+                category: event.category,
+                name: event.name.clone(),
+                count: 0,
+                total: Duration::ZERO,
+                max: Duration::ZERO,
+            };
+            totals.push((event.start, total));
+            totals.len() - 1
+        });
+        let (first, total) = &mut totals[i];
+        *first = (*first).min(event.start);
+        total.count += 1;
+        total.total += event.duration;
+        total.max = total.max.max(event.duration);
+    }
+    totals.sort_by(|(a_start, a), (b_start, b)| match (a.category, b.category) {
+        (Category::Stage, Category::Stage) => a_start.cmp(b_start),
+        (Category::Stage, _) => std::cmp::Ordering::Less,
+        (_, Category::Stage) => std::cmp::Ordering::Greater,
+        _ => b.total.cmp(&a.total),
+    });
+    totals.into_iter().map(|(_, total)| total).collect()
+}
+
+fn ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+/// Plain-text table of `events`' totals. A stage's share is of the run's
+/// `elapsed` wall time; other spans can run on many threads at once, so
+/// their totals may add up to more than the run took.
+pub fn report(events: &[Event], elapsed: Duration) -> String {
+    let mut out = format!("Profile: {:.2}s wall time\n\n", elapsed.as_secs_f64());
+    out.push_str(&format!(
+        "{:<6} {:<16} {:>6} {:>11} {:>9} {:>9} {:>6}\n",
+        "kind", "name", "calls", "total ms", "mean ms", "max ms", "share"
+    ));
+    for total in totals(events) {
+        let share = match (total.category, elapsed.is_zero()) {
+            (Category::Stage, false) => format!("{:.0}%", total.total.as_secs_f64() / elapsed.as_secs_f64() * 100.0),
+            _ => String::new(),
+        };
+        out.push_str(&format!(
+            "{:<6} {:<16} {:>6} {:>11.1} {:>9.2} {:>9.2} {:>6}\n",
+            total.category.name(),
+            total.name,
+            total.count,
+            ms(total.total),
+            ms(total.total) / total.count as f64,
+            ms(total.max),
+            share
+        ));
+    }
+    out
+}
+
+/// `events` in the Chrome trace event format, one complete event each.
+pub fn chrome_trace(events: &[Event]) -> Value {
+    let events: Vec<Value> = events
+        .iter()
+        .map(|e| {
+            json!({
+                "name": e.name,
+                "cat": e.category.name(),
+                "ph": "X",
+                "ts": e.start.as_micros() as u64,
+                "dur": e.duration.as_micros() as u64,
+                "pid": 1,
+                "tid": e.thread,
+            })
+        })
+        .collect();
+    json!({ "traceEvents": events, "displayTimeUnit": "ms" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(category: Category, name: &str, start_ms: u64, duration_ms: u64) -> Event {
+        Event {
+            category,
+            name: name.to_string(),
+            thread: 0,
+            start: Duration::from_millis(start_ms),
+            duration: Duration::from_millis(duration_ms),
+        }
+    }
+
+    #[test]
+    fn test_totals_and_report() {
+        let events = vec![
+            event(Category::Dsp, "stretch", 5, 10),
+            event(Category::Stage, "Cutting", 40, 60),
+            event(Category::Dsp, "stretch", 20, 30),
+            event(Category::Stage, "Aligning", 0, 40),
+            event(Category::Dsp, "pitch shift", 50, 50),
+        ];
+        let totals = totals(&events);
+        let names: Vec<&str> = totals.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["Aligning", "Cutting", "pitch shift", "stretch"]);
+        assert_eq!(totals[3].count, 2);
+        assert_eq!(totals[3].total, Duration::from_millis(40));
+        assert_eq!(totals[3].max, Duration::from_millis(30));
+
+        let report = report(&events, Duration::from_millis(100));
+        assert!(report.starts_with("Profile: 0.10s wall time"));
+        let aligning = report.lines().find(|l| l.contains("Aligning")).unwrap();
+        assert!(aligning.ends_with("40%"), "{}", aligning);
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let trace = chrome_trace(&[event(Category::Align, "align", 2, 3)]);
+        let e = &trace["traceEvents"][0];
+        assert_eq!(e["ph"], "X");
+        assert_eq!(e["cat"], "align");
+        assert_eq!(e["ts"], 2000);
+        assert_eq!(e["dur"], 3000);
+    }
+
+    #[test]
+    fn test_spans_record_only_when_enabled() {
+        // The recorder is global, so spans from other tests running at the
+        // same time are skipped
+        drop(span(Category::Dsp, "before"));
+        enable();
+        drop(span(Category::Dsp, "test span"));
+        stage("Cutting");
+        stage("Cutting");
+        stage("Assembling");
+        let (events, _) = finish();
+        let names: Vec<&str> = events
+            .iter()
+            .filter(|e| e.category == Category::Stage || e.name.contains("span"))
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["test span", "Cutting", "Assembling"]);
+    }
+}
@@ -0,0 +1,123 @@
+//! Progress reporting for pipeline runs.
+//!
+//! Pipelines report a stage name and overall completion in 0–1. Each
+//! [`Stage`] owns a fixed slice of the run — alignment 0–40%, cutting
+//! (or, for speak, matching) 40–60%, assembly 60–100% — so front ends can
+//! draw a single bar.
+
+use std::time::Duration;
+
+/// Pipeline stage, each covering a fixed slice of overall progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    Alignment,
+    Cutting,
+    /// Speak's search for source syllables matching the target text.
+    Matching,
+    Assembly,
+}
+
+impl Stage {
+    /// Display name passed to reporters.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Alignment => "Aligning",
+            Self::Cutting => "Cutting",
+            Self::Matching => "Matching",
+            Self::Assembly => "Assembling",
+        }
+    }
+
+    /// Overall-progress range `(start, end)` this stage covers.
+    pub fn span(self) -> (f64, f64) {
+        match self {
+            Self::Alignment => (0.0, 0.4),
+            Self::Cutting | Self::Matching => (0.4, 0.6),
+            Self::Assembly => (0.6, 1.0),
+        }
+    }
+
+    /// Map completion within this stage (0–1) to overall completion.
+    pub fn overall(self, within: f64) -> f64 {
+        let (start, end) = self.span();
+        start + (end - start) * within.clamp(0.0, 1.0)
+    }
+}
+
+/// Receives progress updates from a pipeline.
+pub trait ProgressReporter {
+    /// Called with the current stage name and overall completion (0–1).
+    fn report(&self, stage: &str, fraction: f64);
+
+    /// Report completion within `stage`, mapped onto overall progress.
+    fn stage(&self, stage: Stage, within: f64) {
+        self.report(stage.name(), stage.overall(within));
+    }
+
+    /// Whether the caller asked to stop. Long-running work checks this
+    /// between steps and bails out early.
+    fn cancelled(&self) -> bool {
+        false
+    }
+}
+
+impl<F: Fn(&str, f64)> ProgressReporter for F {
+    fn report(&self, stage: &str, fraction: f64) {
+        self(stage, fraction)
+    }
+}
+
+/// Reporter that ignores all updates.
+pub struct NoProgress;
+
+impl ProgressReporter for NoProgress {
+    fn report(&self, _stage: &str, _fraction: f64) {}
+}
+
+/// Estimate remaining time from elapsed time and overall completion.
+///
+/// Returns `None` until enough progress has been made for a useful guess.
+pub fn estimate_remaining(elapsed: Duration, fraction: f64) -> Option<Duration> {
+    if !(0.01..1.0).contains(&fraction) {
+        return None;
+    }
+    Some(elapsed.mul_f64((1.0 - fraction) / fraction))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_stage_spans_cover_full_range() {
+        assert_eq!(Stage::Alignment.overall(0.0), 0.0);
+        assert_eq!(Stage::Alignment.overall(1.0), Stage::Cutting.overall(0.0));
+        assert_eq!(Stage::Cutting.overall(1.0), Stage::Assembly.overall(0.0));
+        assert_eq!(Stage::Assembly.overall(1.0), 1.0);
+        assert!((Stage::Cutting.overall(0.5) - 0.5).abs() < 1e-12);
+        assert_eq!(Stage::Assembly.overall(2.0), 1.0);
+        // Speak matches where the other pipelines cut
+        assert_eq!(Stage::Matching.span(), Stage::Cutting.span());
+        assert_eq!(Stage::Matching.name(), "Matching");
+    }
+
+    #[test]
+    fn test_closure_reporter_receives_overall_fraction() {
+        let seen = RefCell::new(Vec::new());
+        let reporter = |stage: &str, fraction: f64| seen.borrow_mut().push((stage.to_string(), fraction));
+        reporter.stage(Stage::Alignment, 0.5);
+        reporter.stage(Stage::Assembly, 0.5);
+        let seen = seen.into_inner();
+        assert_eq!(seen[0], ("Aligning".to_string(), 0.2));
+        assert_eq!(seen[1], ("Assembling".to_string(), 0.8));
+    }
+
+    #[test]
+    fn test_estimate_remaining() {
+        assert_eq!(estimate_remaining(Duration::from_secs(10), 0.0), None);
+        assert_eq!(estimate_remaining(Duration::from_secs(10), 1.0), None);
+        let eta = estimate_remaining(Duration::from_secs(10), 0.25).unwrap();
+        assert!((eta.as_secs_f64() - 30.0).abs() < 1e-6);
+    }
+}
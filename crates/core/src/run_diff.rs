@@ -0,0 +1,353 @@
+//! Compare two runs from the records they leave in their run directory:
+//! the seed (`seed.json`), the options they were started with
+//! (`config.json`), and what they produced (`manifest.json`, and
+//! `matches.json` for speak runs).
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+
+/// File in a run directory recording the options the run started with.
+pub const CONFIG_RECORD: &str = "config.json";
+
+/// Write `config.json` to a run directory: the subcommand that ran and
+/// every option it used, defaults included, keyed by flag (e.g. "--bpm").
+pub fn write_config_record(run_dir: &Path, command: &str, options: &BTreeMap<String, String>) -> Result<()> {
+    let record = serde_json::json!({ "command": command, "options": options });
+    std::fs::write(run_dir.join(CONFIG_RECORD), serde_json::to_string_pretty(&record)?)
+        .with_context(|| format!("Failed to write config record in {}", run_dir.display()))
+}
+
+/// What a run directory says about how the run was made.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunRecord {
+    pub dir: PathBuf,
+    /// Subcommand that ran, e.g. "collage".
+    pub command: Option<String>,
+    pub seed: Option<u64>,
+    /// Name the seed was derived from, if any.
+    pub seed_name: Option<String>,
+    /// Options from `config.json`, keyed by flag.
+    pub options: BTreeMap<String, String>,
+    /// Manifest values, nested ones under dotted keys. The run's own name
+    /// is replaced by `<run>` so keys line up across runs.
+    pub results: BTreeMap<String, String>,
+    /// Source files, as listed in the manifest.
+    pub sources: Vec<String>,
+    /// Chosen clips in output order, one description each.
+    pub clips: Vec<String>,
+}
+
+impl RunRecord {
+    /// Read the records in `dir`. Fails if it holds none of them.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let read = |name: &str| -> Result<Option<Value>> {
+            let path = dir.join(name);
+            match std::fs::read_to_string(&path) {
+                Ok(text) => serde_json::from_str(&text)
+                    .map(Some)
+                    .with_context(|| format!("Failed to parse {}", path.display())),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e).with_context(|| format!("Failed to read {}", path.display())),
+            }
+        };
+        let (seed, config, manifest, matches) =
+            (read("seed.json")?, read(CONFIG_RECORD)?, read("manifest.json")?, read("matches.json")?);
+        if seed.is_none() && config.is_none() && manifest.is_none() && matches.is_none() {
+            bail!("{} is not a run directory (no seed.json, config.json, or manifest.json)", dir.display());
+        }
+
+        let mut record = RunRecord { dir: dir.to_path_buf(), ..Default::default() };
+        if let Some(seed) = seed {
+            record.seed = seed["seed"].as_u64();
+            record.seed_name = seed["seed_name"].as_str().map(str::to_string);
+        }
+        if let Some(config) = config {
+            record.command = config["command"].as_str().map(str::to_string);
+            if let Some(options) = config["options"].as_object() {
+                record.options = options.iter().map(|(k, v)| (k.clone(), display_value(v))).collect();
+            }
+        }
+        if let Some(manifest) = manifest {
+            let run_name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+            flatten_results(&manifest, "", &run_name, &mut record.results);
+            record.sources = manifest["sources"]
+                .as_array()
+                .map(|s| s.iter().map(display_value).collect())
+                .unwrap_or_default();
+            if let Some(clips) = manifest["clips"].as_array() {
+                record.clips = clips.iter().map(|c| describe_clip(c, &record.sources)).collect();
+            }
+        }
+        if let Some(syllables) = matches.as_ref().and_then(|m| m["syllables"].as_array()) {
+            record.clips = syllables.iter().map(|s| describe_match(&s["chosen"])).collect();
+        }
+        Ok(record)
+    }
+}
+
+/// Manifest fields compared as a whole elsewhere.
+const LISTED_FIELDS: &[&str] = &["sources", "clips"];
+
+fn flatten_results(value: &Value, prefix: &str, run_name: &str, out: &mut BTreeMap<String, String>) {
+    let Some(fields) = value.as_object() else {
+        return;
+    };
+    for (key, value) in fields {
+        if prefix.is_empty() && LISTED_FIELDS.contains(&key.as_str()) {
+            continue;
+        }
+        let key = key.replace(run_name, "<run>");
+        let key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+        match value {
+            Value::Object(_) => flatten_results(value, &key, run_name, out),
+            value => {
+                out.insert(key, display_value(value));
+            }
+        }
+    }
+}
+
+/// A JSON value as shown in the report: strings unquoted, null as "null".
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items.iter().map(display_value).collect::<Vec<_>>().join(", "),
+        value => value.to_string(),
+    }
+}
+
+/// A manifest clip as "voice.wav 1.200-1.450 \"hello\"".
+fn describe_clip(clip: &Value, sources: &[String]) -> String {
+    let source = clip["source"]
+        .as_u64()
+        .and_then(|i| sources.get(i as usize))
+        .map(|s| Path::new(s).file_name().map_or(s.clone(), |n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "?".to_string());
+    let text = clip["word"].as_str().or(clip["text"].as_str()).unwrap_or("");
+    format!(
+        "{} {:.3}-{:.3} \"{}\"",
+        source,
+        clip["start"].as_f64().unwrap_or(0.0),
+        clip["end"].as_f64().unwrap_or(0.0),
+        text
+    )
+}
+
+/// A speak match as "\"hello\" [HH AH0] #12".
+fn describe_match(chosen: &Value) -> String {
+    format!(
+        "\"{}\" [{}] #{}",
+        chosen["matched_word"].as_str().unwrap_or(""),
+        display_value(&chosen["matched"]).replace(", ", " "),
+        chosen["source_index"]
+    )
+}
+
+/// A value that differs between the runs; `None` where a run has none.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub key: String,
+    pub a: Option<String>,
+    pub b: Option<String>,
+}
+
+/// How the two runs' clip choices compare.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClipDivergence {
+    pub a_count: usize,
+    pub b_count: usize,
+    /// Clips the runs chose in the same order before they first differ.
+    pub common_prefix: usize,
+    /// Clips of A that B also chose, anywhere.
+    pub shared: usize,
+    /// The first pair that differs, if any.
+    pub first_difference: Option<(Option<String>, Option<String>)>,
+}
+
+/// Differences between two runs, from [`diff_runs`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunDiff {
+    pub a: PathBuf,
+    pub b: PathBuf,
+    /// Command, seed, and seed name.
+    pub seed: Vec<Change>,
+    pub options: Vec<Change>,
+    pub results: Vec<Change>,
+    pub sources_only_a: Vec<String>,
+    pub sources_only_b: Vec<String>,
+    pub clips: ClipDivergence,
+}
+
+impl RunDiff {
+    /// Whether the records show no difference at all.
+    pub fn is_empty(&self) -> bool {
+        self.seed.is_empty()
+            && self.options.is_empty()
+            && self.results.is_empty()
+            && self.sources_only_a.is_empty()
+            && self.sources_only_b.is_empty()
+            && self.clips.first_difference.is_none()
+    }
+}
+
+/// Compare two run records.
+pub fn diff_runs(a: &RunRecord, b: &RunRecord) -> RunDiff {
+    let mut seed = Vec::new();
+    let mut push = |key: &str, x: Option<String>, y: Option<String>| {
+        if x != y {
+            seed.push(Change { key: key.to_string(), a: x, b: y });
+        }
+    };
+    push("command", a.command.clone(), b.command.clone());
+    push("seed", a.seed.map(|s| s.to_string()), b.seed.map(|s| s.to_string()));
+    push("seed name", a.seed_name.clone(), b.seed_name.clone());
+
+    let only_in = |x: &[String], y: &[String]| x.iter().filter(|s| !y.contains(s)).cloned().collect();
+    RunDiff {
+        a: a.dir.clone(),
+        b: b.dir.clone(),
+        seed,
+        options: diff_maps(&a.options, &b.options),
+        results: diff_maps(&a.results, &b.results),
+        sources_only_a: only_in(&a.sources, &b.sources),
+        sources_only_b: only_in(&b.sources, &a.sources),
+        clips: diff_clips(&a.clips, &b.clips),
+    }
+}
+
+fn diff_maps(a: &BTreeMap<String, String>, b: &BTreeMap<String, String>) -> Vec<Change> {
+    let mut keys: Vec<&String> = a.keys().chain(b.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|k| a.get(*k) != b.get(*k))
+        .map(|k| Change { key: k.clone(), a: a.get(k).cloned(), b: b.get(k).cloned() })
+        .collect()
+}
+
+fn diff_clips(a: &[String], b: &[String]) -> ClipDivergence {
+    let common_prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let mut remaining: HashMap<&String, usize> = HashMap::new();
+    for clip in b {
+        *remaining.entry(clip).or_default() += 1;
+    }
+    let shared = a
+        .iter()
+        .filter(|clip| match remaining.get_mut(clip) {
+            Some(n) if *n > 0 => {
+                *n -= 1;
+                true
+            }
+            _ => false,
+        })
+        .count();
+    let first_difference = (common_prefix < a.len().max(b.len()))
+        .then(|| (a.get(common_prefix).cloned(), b.get(common_prefix).cloned()));
+    ClipDivergence { a_count: a.len(), b_count: b.len(), common_prefix, shared, first_difference }
+}
+
+impl fmt::Display for RunDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "A: {}", self.a.display())?;
+        writeln!(f, "B: {}", self.b.display())?;
+        if self.is_empty() {
+            return write!(f, "\nNo differences found.");
+        }
+        let value = |v: &Option<String>| v.clone().unwrap_or_else(|| "(none)".to_string());
+        for (title, changes) in [("Seed", &self.seed), ("Options", &self.options), ("Results", &self.results)] {
+            if changes.is_empty() {
+                continue;
+            }
+            writeln!(f, "\n{}", title)?;
+            for change in changes {
+                writeln!(f, "  {}: {} -> {}", change.key, value(&change.a), value(&change.b))?;
+            }
+        }
+        if !self.sources_only_a.is_empty() || !self.sources_only_b.is_empty() {
+            writeln!(f, "\nSources")?;
+            for (side, sources) in [("A", &self.sources_only_a), ("B", &self.sources_only_b)] {
+                for source in sources {
+                    writeln!(f, "  only in {}: {}", side, source)?;
+                }
+            }
+        }
+        let clips = &self.clips;
+        if let Some((a, b)) = &clips.first_difference {
+            writeln!(f, "\nClips")?;
+            writeln!(
+                f,
+                "  A chose {}, B chose {}; {} of A's clips are also in B",
+                clips.a_count, clips.b_count, clips.shared
+            )?;
+            writeln!(
+                f,
+                "  The first {} match; clip {} differs:",
+                clips.common_prefix,
+                clips.common_prefix + 1
+            )?;
+            writeln!(f, "    A: {}", value(a))?;
+            writeln!(f, "    B: {}", value(b))?;
+        } else if clips.a_count > 0 {
+            writeln!(f, "\nClips\n  Both chose the same {} clips", clips.a_count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_run(root: &Path, name: &str, seed: u64, bpm: &str, words: &[&str]) -> PathBuf {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        crate::names::write_seed_record(&dir, seed, None).unwrap();
+        let options = BTreeMap::from([
+            ("--bpm".to_string(), bpm.to_string()),
+            ("--target-duration".to_string(), "30".to_string()),
+        ]);
+        write_config_record(&dir, "collage", &options).unwrap();
+        let clips: Vec<Value> = words
+            .iter()
+            .enumerate()
+            .map(|(i, w)| serde_json::json!({ "source": 0, "word": w, "start": i as f64, "end": i as f64 + 0.25 }))
+            .collect();
+        let manifest = serde_json::json!({
+            "sources": ["work/voice.wav"],
+            "selected_syllables": words.len(),
+            "clips": clips,
+            "loudness": { format!("{}.wav", name): { "measured_lufs": -20.0 - words.len() as f64 } },
+        });
+        std::fs::write(dir.join("manifest.json"), manifest.to_string()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_diff_reports_changed_options_and_divergence() {
+        let root = std::env::temp_dir().join(format!("glottisdale_run_diff_{}", std::process::id()));
+        let a = RunRecord::load(&write_run(&root, "2026-01-01-airy-alto", 1, "120", &["the", "cat", "sat"])).unwrap();
+        let b = RunRecord::load(&write_run(&root, "2026-01-02-bowed-bass", 2, "90", &["the", "dog", "sat", "up"])).unwrap();
+        assert_eq!(a.clips[1], "voice.wav 1.000-1.250 \"cat\"");
+
+        let diff = diff_runs(&a, &b);
+        assert_eq!(diff.seed, [Change { key: "seed".into(), a: Some("1".into()), b: Some("2".into()) }]);
+        assert_eq!(diff.options, [Change { key: "--bpm".into(), a: Some("120".into()), b: Some("90".into()) }]);
+        let keys: Vec<&str> = diff.results.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(keys, ["loudness.<run>.wav.measured_lufs", "selected_syllables"]);
+        assert_eq!(diff.clips.common_prefix, 1);
+        assert_eq!(diff.clips.shared, 2);
+        assert_eq!(diff.clips.first_difference.as_ref().unwrap().1.as_deref(), Some("voice.wav 1.000-1.250 \"dog\""));
+        let report = diff.to_string();
+        assert!(report.contains("--bpm: 120 -> 90"), "{}", report);
+        assert!(report.contains("clip 2 differs"), "{}", report);
+
+        assert!(diff_runs(&a, &a).is_empty());
+        assert!(diff_runs(&a, &a).to_string().ends_with("No differences found."));
+        assert!(RunRecord::load(&root).is_err());
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
@@ -0,0 +1,201 @@
+//! Harmony voices: extra vocal lines pitched to the tones of a chord track.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::sing::midi_parser::{MidiTrack, Note};
+use crate::sing::vocal_mapper::{plan_note_mapping_with, NoteMapping, SingConfig};
+
+/// Most harmony voices sung under the lead.
+pub const MAX_HARMONY_VOICES: usize = 3;
+
+/// Intervals (semitones below the melody) sung by each voice when no
+/// chord tone is in reach: a fifth, an octave, and a twelfth.
+const FALLBACK_INTERVALS: [u8; MAX_HARMONY_VOICES] = [7, 12, 19];
+
+/// Lowest pitch a harmony voice searches down to, relative to the melody.
+const MAX_DROP: u8 = 24;
+
+/// Pick the chord part among `parts`: the non-drum part with the most notes
+/// that start while another of its notes is sounding. Returns `None` if no
+/// part plays two notes at once.
+pub fn pick_chords(parts: &[MidiTrack]) -> Option<usize> {
+    parts
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| !p.is_drum)
+        .map(|(i, p)| (i, stacked_notes(&p.notes)))
+        .filter(|&(_, stacked)| stacked > 0)
+        .max_by_key(|&(_, stacked)| stacked)
+        .map(|(i, _)| i)
+}
+
+/// Number of notes that start while an earlier-or-equal note still sounds.
+fn stacked_notes(notes: &[Note]) -> usize {
+    notes
+        .iter()
+        .enumerate()
+        .filter(|(i, n)| {
+            notes
+                .iter()
+                .enumerate()
+                .any(|(j, m)| j != *i && m.start <= n.start && n.start < m.end && (m.start, j) < (n.start, *i))
+        })
+        .count()
+}
+
+/// Pitch classes of the chord notes sounding at `time`.
+pub fn chord_at(chords: &[Note], time: f64) -> Vec<u8> {
+    let mut classes: Vec<u8> = chords
+        .iter()
+        .filter(|n| n.start <= time && time < n.end)
+        .map(|n| n.pitch % 12)
+        .collect();
+    classes.sort_unstable();
+    classes.dedup();
+    classes
+}
+
+/// Pitch for harmony voice `voice` (0-based) under a melody note: the
+/// voice's chord tone counting down from the melody, skipping the melody's
+/// own pitch class. Voices without a chord tone within two octaves fall
+/// back to a fixed consonant interval below the melody.
+pub fn harmony_pitch(melody_pitch: u8, chord: &[u8], voice: usize) -> u8 {
+    let own = melody_pitch % 12;
+    let tone = (1..=MAX_DROP.min(melody_pitch))
+        .map(|drop| melody_pitch - drop)
+        .filter(|p| p % 12 != own && chord.contains(&(p % 12)))
+        .nth(voice);
+    tone.unwrap_or_else(|| {
+        let interval = FALLBACK_INTERVALS[voice.min(MAX_HARMONY_VOICES - 1)];
+        melody_pitch.saturating_sub(interval)
+    })
+}
+
+/// Notes for harmony voice `voice`: one per melody note, pitched to the
+/// chord sounding near the note's onset and entering up to `jitter_s` late.
+pub fn harmony_notes(melody: &[Note], chords: &[Note], voice: usize, jitter_s: f64, rng: &mut StdRng) -> Vec<Note> {
+    melody
+        .iter()
+        .map(|note| {
+            let probe = note.start + (note.duration() / 2.0).min(0.05);
+            let chord = chord_at(chords, probe);
+            // Delay only, so each note keeps its end and the line realigns
+            let delay = if jitter_s > 0.0 {
+                rng.gen_range(0.0..jitter_s).min(note.duration() / 2.0)
+            } else {
+                0.0
+            };
+            Note {
+                pitch: harmony_pitch(note.pitch, &chord, voice),
+                start: note.start + delay,
+                end: note.end,
+                velocity: note.velocity,
+            }
+        })
+        .collect()
+}
+
+/// Plan `voices` harmony lines under `melody`. Each voice draws its own
+/// syllables, starting at a random point in the pool, with its own drift
+/// and timing jitter. Harmony never adds chorus, whatever `config` says.
+pub fn plan_harmony(
+    melody: &[Note],
+    chords: &[Note],
+    voices: usize,
+    pool_size: usize,
+    seed: Option<u64>,
+    config: &SingConfig,
+    jitter_s: f64,
+) -> Vec<Vec<NoteMapping>> {
+    if pool_size == 0 {
+        return Vec::new();
+    }
+    let config = SingConfig { chorus_probability: 0.0, ..config.clone() };
+    (0..voices.min(MAX_HARMONY_VOICES))
+        .map(|voice| {
+            let voice_seed = seed.map(|s| s.wrapping_add(voice as u64 + 1));
+            let mut rng = match voice_seed {
+                Some(s) => StdRng::seed_from_u64(s),
+                None => StdRng::from_entropy(),
+            };
+            let notes = harmony_notes(melody, chords, voice, jitter_s, &mut rng);
+            let offset = rng.gen_range(0..pool_size);
+            let mut mappings = plan_note_mapping_with(&notes, pool_size, voice_seed, &config);
+            for mapping in &mut mappings {
+                for index in &mut mapping.syllable_indices {
+                    *index = (*index + offset) % pool_size;
+                }
+            }
+            mappings
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(pitch: u8, start: f64, end: f64) -> Note {
+        Note { pitch, start, end, velocity: 100 }
+    }
+
+    #[test]
+    fn test_chord_at_and_harmony_pitch() {
+        // C major triad for two seconds, then nothing
+        let chords = vec![note(48, 0.0, 2.0), note(52, 0.0, 2.0), note(55, 0.0, 2.0), note(60, 0.0, 2.0)];
+        assert_eq!(chord_at(&chords, 1.0), vec![0, 4, 7]);
+        assert!(chord_at(&chords, 2.0).is_empty());
+
+        // Melody E4 over C major, counting down and skipping E: C4, G3, C3
+        let c = chord_at(&chords, 0.5);
+        assert_eq!(harmony_pitch(64, &c, 0), 60);
+        assert_eq!(harmony_pitch(64, &c, 1), 55);
+        assert_eq!(harmony_pitch(64, &c, 2), 48);
+        // No chord: fixed intervals below
+        assert_eq!(harmony_pitch(64, &[], 0), 57);
+        assert_eq!(harmony_pitch(64, &[], 1), 52);
+        assert_eq!(harmony_pitch(5, &[], 2), 0);
+    }
+
+    #[test]
+    fn test_plan_harmony_voices_are_independent() {
+        let melody = vec![note(64, 0.0, 0.5), note(67, 0.5, 1.0), note(72, 1.0, 2.0)];
+        let chords = vec![note(48, 0.0, 2.0), note(52, 0.0, 2.0), note(55, 0.0, 2.0)];
+        let no_drift = SingConfig { drift_range: 0.0, ..SingConfig::default() };
+        let voices = plan_harmony(&melody, &chords, 5, 20, Some(7), &no_drift, 0.03);
+        assert_eq!(voices.len(), MAX_HARMONY_VOICES);
+        for (v, mappings) in voices.iter().enumerate() {
+            assert_eq!(mappings.len(), melody.len());
+            for (m, lead) in mappings.iter().zip(&melody) {
+                assert!(m.note_pitch < lead.pitch, "voice {} sits under the lead", v);
+                assert!(m.note_start >= lead.start && m.note_start <= lead.start + 0.03);
+                assert_eq!(m.note_end, lead.end);
+                assert!(m.syllable_indices.iter().all(|&i| i < 20));
+            }
+        }
+        let first: Vec<Vec<usize>> = voices.iter().map(|v| v[0].syllable_indices.clone()).collect();
+        assert!(first[0] != first[1] || first[1] != first[2]);
+        let again = plan_harmony(&melody, &chords, 3, 20, Some(7), &no_drift, 0.03);
+        assert_eq!(again[2][1].syllable_indices, voices[2][1].syllable_indices);
+        assert_eq!(again[2][1].note_start, voices[2][1].note_start);
+        assert!(plan_harmony(&melody, &chords, 2, 0, Some(7), &no_drift, 0.03).is_empty());
+    }
+
+    #[test]
+    fn test_pick_chords() {
+        let track = |notes: Vec<Note>, is_drum: bool| MidiTrack {
+            notes,
+            tempo: 120.0,
+            program: 0,
+            is_drum,
+            total_duration: 2.0,
+        };
+        let bass = track(vec![note(36, 0.0, 1.0), note(38, 1.0, 2.0)], false);
+        let pad = track(vec![note(48, 0.0, 2.0), note(52, 0.0, 2.0), note(55, 0.0, 2.0)], false);
+        let drums = track(vec![note(36, 0.0, 0.1), note(42, 0.0, 0.1), note(38, 0.0, 0.1)], true);
+        assert_eq!(pick_chords(&[bass.clone(), drums.clone(), pad]), Some(2));
+        assert_eq!(pick_chords(&[bass, drums]), None);
+        assert_eq!(stacked_notes(&[note(48, 0.0, 1.0), note(52, 0.0, 1.0)]), 1);
+    }
+}
@@ -0,0 +1,125 @@
+//! Lyrics-driven singing: pick the source syllables that best match the
+//! lyric phonemes with the speak matcher, then sing them on the melody
+//! notes in order.
+
+use anyhow::{bail, Result};
+
+use crate::language::g2p::Language;
+use crate::sing::syllable_prep::NormalizedSyllable;
+use crate::sing::vocal_mapper::NoteMapping;
+use crate::speak::matcher::{match_syllables_with, MatchStrategy};
+use crate::speak::phonetic_distance::normalize_phoneme;
+use crate::speak::syllable_bank::SyllableEntry;
+use crate::speak::target_text::text_to_syllables_in;
+
+/// Speak bank over the sing syllable pool. Entry `index` is the position
+/// in `pool`, so adjacent pool syllables count as contiguous source runs.
+/// Syllables without phoneme labels are left out.
+pub fn lyric_bank(pool: &[NormalizedSyllable]) -> Vec<SyllableEntry> {
+    pool.iter()
+        .enumerate()
+        .filter_map(|(i, syl)| {
+            let labels: Vec<String> = syl
+                .phonemes
+                .iter()
+                .filter(|p| p.starts_with(|c: char| c.is_alphabetic()))
+                .map(|p| normalize_phoneme(p))
+                .collect();
+            if labels.is_empty() {
+                return None;
+            }
+            let stress = labels
+                .iter()
+                .find_map(|p| p.bytes().last().filter(u8::is_ascii_digit).map(|d| d - b'0'));
+            Some(SyllableEntry {
+                phoneme_labels: labels,
+                start: 0.0,
+                end: syl.duration,
+                word: syl.word.clone(),
+                stress,
+                source: "".into(),
+                index: i,
+                quality: None,
+            })
+        })
+        .collect()
+}
+
+/// Pool indices of the syllables that sing `lyrics`, one per lyric
+/// syllable, in order.
+pub fn match_lyrics(
+    lyrics: &str,
+    pool: &[NormalizedSyllable],
+    language: Language,
+    strategy: MatchStrategy,
+) -> Result<Vec<usize>> {
+    let targets = text_to_syllables_in(lyrics, language);
+    if targets.is_empty() {
+        bail!("Lyrics have no pronounceable words: '{}'", lyrics);
+    }
+    let bank = lyric_bank(pool);
+    if bank.is_empty() {
+        bail!("No source syllables have phoneme labels to match lyrics against");
+    }
+    let phonemes: Vec<Vec<String>> = targets.iter().map(|t| t.phonemes.clone()).collect();
+    let stresses: Vec<Option<u8>> = targets.iter().map(|t| t.stress).collect();
+    let matches = match_syllables_with(&phonemes, &bank, Some(&stresses), None, strategy, language);
+    Ok(matches.iter().map(|m| m.entry.index).collect())
+}
+
+/// Sing `lyric` (pool indices from `match_lyrics`) on `mappings`, one
+/// syllable per note so each word stays intact. The lyrics start over when
+/// the melody outlasts them.
+pub fn apply_lyrics(mappings: &mut [NoteMapping], lyric: &[usize]) {
+    if lyric.is_empty() {
+        return;
+    }
+    for (i, mapping) in mappings.iter_mut().enumerate() {
+        mapping.syllable_indices = vec![lyric[i % lyric.len()]];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sing::midi_parser::Note;
+    use crate::sing::vocal_mapper::plan_note_mapping;
+
+    fn syllable(word: &str, phonemes: &[&str]) -> NormalizedSyllable {
+        NormalizedSyllable {
+            samples: vec![0.0; 1600],
+            sr: 16000,
+            f0: Some(220.0),
+            duration: 0.1,
+            phonemes: phonemes.iter().map(|p| p.to_string()).collect(),
+            word: word.to_string(),
+            word_index: 0,
+        }
+    }
+
+    #[test]
+    fn test_match_and_apply_lyrics() {
+        let pool = vec![
+            syllable("dog", &["D", "AO1", "G"]),
+            syllable("hello", &["HH", "AH0"]),
+            syllable("hello", &["L", "OW1"]),
+            syllable("", &[]),
+            syllable("moon", &["M", "UW1", "N"]),
+        ];
+        assert_eq!(lyric_bank(&pool).len(), 4);
+
+        let lyric = match_lyrics("hello moon", &pool, Language::English, MatchStrategy::Viterbi).unwrap();
+        assert_eq!(lyric, vec![1, 2, 4]);
+
+        let notes: Vec<Note> = (0..5)
+            .map(|i| Note { pitch: 60, start: i as f64, end: i as f64 + 1.5, velocity: 100 })
+            .collect();
+        let mut mappings = plan_note_mapping(&notes, pool.len(), Some(1), 0.0, 0.0);
+        apply_lyrics(&mut mappings, &lyric);
+        let sung: Vec<Vec<usize>> = mappings.iter().map(|m| m.syllable_indices.clone()).collect();
+        assert_eq!(sung, vec![vec![1], vec![2], vec![4], vec![1], vec![2]]);
+
+        assert!(match_lyrics("...", &pool, Language::English, MatchStrategy::Viterbi).is_err());
+        assert!(match_lyrics("moon", &pool[3..4], Language::English, MatchStrategy::Viterbi).is_err());
+    }
+}
@@ -1,5 +1,6 @@
 //! Parse MIDI files into structured note sequences.
 
+use std::collections::HashMap;
 use std::path::Path;
 
 use anyhow::{Result, Context};
@@ -39,120 +40,294 @@ pub fn midi_to_hz(midi_note: u8) -> f64 {
     440.0 * 2.0f64.powf((midi_note as f64 - 69.0) / 12.0)
 }
 
+/// Scientific pitch name of a MIDI note, e.g. "C4" for 60.
+pub fn note_name(midi_note: u8) -> String {
+    const NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+    format!("{}{}", NAMES[midi_note as usize % 12], midi_note as i32 / 12 - 1)
+}
+
 /// Parse a MIDI file into a MidiTrack.
 ///
 /// Merges all non-drum instruments. Extracts tempo from meta events.
 pub fn parse_midi(path: &Path) -> Result<MidiTrack> {
     let data = std::fs::read(path)
         .with_context(|| format!("Failed to read MIDI file: {}", path.display()))?;
-    let smf = Smf::parse(&data)
-        .map_err(|e| anyhow::anyhow!("Failed to parse MIDI: {}", e))?;
+    parse_midi_bytes(&data).with_context(|| format!("Invalid MIDI file: {}", path.display()))
+}
 
-    let ticks_per_beat = match smf.header.timing {
-        midly::Timing::Metrical(tpb) => tpb.as_int() as f64,
-        midly::Timing::Timecode(fps, sub) => {
-            // For timecode, compute equivalent ticks per beat at 120 BPM
-            let frames_per_sec = match fps {
-                midly::Fps::Fps24 => 24.0,
-                midly::Fps::Fps25 => 25.0,
-                midly::Fps::Fps29 => 29.97,
-                midly::Fps::Fps30 => 30.0,
-            };
-            frames_per_sec * sub as f64 / 2.0 // assume 120 BPM
-        }
-    };
+/// Parse in-memory MIDI data into a MidiTrack.
+///
+/// Malformed input is an error, never a panic; the fuzz targets hold this.
+pub fn parse_midi_bytes(data: &[u8]) -> Result<MidiTrack> {
+    let parts = parse_midi_parts_bytes(data)?;
+    let melodic: Vec<&MidiTrack> = parts.iter().filter(|p| !p.is_drum).collect();
+    let mut notes: Vec<Note> = melodic.iter().flat_map(|p| p.notes.iter().cloned()).collect();
+    notes.sort_by(|a, b| a.start.total_cmp(&b.start));
+    let (tempo, total_duration) = parts.first().map(|p| (p.tempo, p.total_duration)).unwrap_or((120.0, 0.0));
+
+    Ok(MidiTrack {
+        notes,
+        tempo,
+        program: melodic.first().map(|p| p.program).unwrap_or(0),
+        is_drum: false,
+        total_duration,
+    })
+}
+
+/// Parse every part of a MIDI file: one `MidiTrack` per (track, channel)
+/// that plays notes, in file order. Channel 10 parts are flagged
+/// `is_drum`. All parts share the file's tempo and total duration, so
+/// they stay in step when looped.
+pub fn parse_midi_parts(path: &Path) -> Result<Vec<MidiTrack>> {
+    let data = std::fs::read(path)
+        .with_context(|| format!("Failed to read MIDI file: {}", path.display()))?;
+    parse_midi_parts_bytes(&data).with_context(|| format!("Invalid MIDI file: {}", path.display()))
+}
 
-    let mut tempo_us_per_beat = 500_000.0; // default 120 BPM
-    let mut notes: Vec<Note> = Vec::new();
-    let mut program: u8 = 0;
-    let is_drum = false;
+/// Parse in-memory MIDI data into parts, as `parse_midi_parts` does.
+pub fn parse_midi_parts_bytes(data: &[u8]) -> Result<Vec<MidiTrack>> {
+    let smf = Smf::parse(data)
+        .map_err(|e| anyhow::anyhow!("Failed to parse MIDI: {}", e))?;
+    let clock = TempoMap::new(&smf)?;
 
-    // Track active notes: (pitch) -> (start_time, velocity)
-    let mut active: std::collections::HashMap<u8, (f64, u8)> = std::collections::HashMap::new();
+    // (track, channel) -> notes, program
+    let mut parts: Vec<((usize, u8), Vec<Note>, u8)> = Vec::new();
     let mut max_time = 0.0f64;
 
-    for track in &smf.tracks {
-        let mut time_s = 0.0f64;
-        let mut current_tempo = tempo_us_per_beat;
-        active.clear();
+    for (track_idx, track) in smf.tracks.iter().enumerate() {
+        let mut tick = 0u64;
+        // Active notes: (channel, pitch) -> (start_time, velocity)
+        let mut active: HashMap<(u8, u8), (f64, u8)> = HashMap::new();
+        let mut programs: HashMap<u8, u8> = HashMap::new();
+        let mut finished: Vec<(u8, Note)> = Vec::new();
 
         for event in track {
-            let delta_ticks = event.delta.as_int() as f64;
-            let delta_s = (delta_ticks / ticks_per_beat) * (current_tempo / 1_000_000.0);
-            time_s += delta_s;
-
-            match event.kind {
-                TrackEventKind::Meta(MetaMessage::Tempo(t)) => {
-                    current_tempo = t.as_int() as f64;
-                    tempo_us_per_beat = current_tempo;
-                }
-                TrackEventKind::Midi { channel, message } => {
-                    // Skip channel 10 (drums, 0-indexed = 9)
-                    if channel.as_int() == 9 {
-                        continue;
-                    }
+            tick += event.delta.as_int() as u64;
+            let time_s = clock.seconds(tick);
+            max_time = max_time.max(time_s);
 
-                    match message {
-                        MidiMessage::ProgramChange { program: p } => {
-                            program = p.as_int();
-                        }
-                        MidiMessage::NoteOn { key, vel } => {
-                            if vel.as_int() > 0 {
-                                active.insert(key.as_int(), (time_s, vel.as_int()));
-                            } else {
-                                // Note-on with velocity 0 = note-off
-                                if let Some((start, velocity)) = active.remove(&key.as_int()) {
-                                    notes.push(Note {
-                                        pitch: key.as_int(),
-                                        start: (start * 10000.0).round() / 10000.0,
-                                        end: (time_s * 10000.0).round() / 10000.0,
-                                        velocity,
-                                    });
-                                }
-                            }
-                        }
-                        MidiMessage::NoteOff { key, .. } => {
-                            if let Some((start, velocity)) = active.remove(&key.as_int()) {
-                                notes.push(Note {
-                                    pitch: key.as_int(),
-                                    start: (start * 10000.0).round() / 10000.0,
-                                    end: (time_s * 10000.0).round() / 10000.0,
-                                    velocity,
-                                });
-                            }
-                        }
-                        _ => {}
+            let TrackEventKind::Midi { channel, message } = event.kind else {
+                continue;
+            };
+            let channel = channel.as_int();
+            match message {
+                MidiMessage::ProgramChange { program } => {
+                    programs.insert(channel, program.as_int());
+                }
+                MidiMessage::NoteOn { key, vel } if vel.as_int() > 0 => {
+                    active.insert((channel, key.as_int()), (time_s, vel.as_int()));
+                }
+                // Note-on with velocity 0 = note-off
+                MidiMessage::NoteOn { key, .. } | MidiMessage::NoteOff { key, .. } => {
+                    if let Some((start, velocity)) = active.remove(&(channel, key.as_int())) {
+                        finished.push((channel, make_note(key.as_int(), start, time_s, velocity)));
                     }
                 }
                 _ => {}
             }
-
-            max_time = max_time.max(time_s);
         }
 
         // Close any remaining active notes
-        for (pitch, (start, velocity)) in active.drain() {
-            notes.push(Note {
-                pitch,
-                start: (start * 10000.0).round() / 10000.0,
-                end: (max_time * 10000.0).round() / 10000.0,
-                velocity,
-            });
+        let mut open: Vec<_> = active.into_iter().collect();
+        open.sort_by_key(|&(key, _)| key);
+        for ((channel, pitch), (start, velocity)) in open {
+            finished.push((channel, make_note(pitch, start, max_time, velocity)));
+        }
+
+        for (channel, note) in finished {
+            let key = (track_idx, channel);
+            match parts.iter_mut().find(|(k, _, _)| *k == key) {
+                Some((_, notes, _)) => notes.push(note),
+                None => parts.push((key, vec![note], programs.get(&channel).copied().unwrap_or(0))),
+            }
         }
     }
 
-    // Sort by start time
-    notes.sort_by(|a, b| a.start.partial_cmp(&b.start).unwrap());
+    parts.sort_by_key(|(key, _, _)| *key);
+    let tempo = (60_000_000.0 / clock.initial_tempo()).round();
+    Ok(parts
+        .into_iter()
+        .map(|((_, channel), mut notes, program)| {
+            notes.sort_by(|a, b| a.start.total_cmp(&b.start));
+            MidiTrack {
+                notes,
+                tempo,
+                program,
+                is_drum: channel == 9,
+                total_duration: max_time,
+            }
+        })
+        .collect())
+}
 
-    let tempo_bpm = 60_000_000.0 / tempo_us_per_beat;
+/// Pick the melody among `parts`: of the non-drum parts with at least a
+/// quarter as many notes as the busiest one, the highest on average.
+/// Returns `None` if every part is drums.
+pub fn pick_melody(parts: &[MidiTrack]) -> Option<usize> {
+    let busiest = parts.iter().filter(|p| !p.is_drum).map(|p| p.notes.len()).max()?;
+    parts
+        .iter()
+        .enumerate()
+        .filter(|(_, p)| !p.is_drum && p.notes.len() * 4 >= busiest)
+        .max_by(|(_, a), (_, b)| mean_pitch(a).total_cmp(&mean_pitch(b)))
+        .map(|(i, _)| i)
+}
 
-    Ok(MidiTrack {
-        notes,
-        tempo: tempo_bpm.round(),
-        program,
-        is_drum,
-        total_duration: max_time,
-    })
+/// Average MIDI pitch of a part's notes (0 if it has none).
+pub fn mean_pitch(track: &MidiTrack) -> f64 {
+    if track.notes.is_empty() {
+        return 0.0;
+    }
+    track.notes.iter().map(|n| n.pitch as f64).sum::<f64>() / track.notes.len() as f64
+}
+
+/// A melody and the parts that accompany it.
+#[derive(Debug, Clone)]
+pub struct MidiSong {
+    pub melody: MidiTrack,
+    pub backing: Vec<MidiTrack>,
+}
+
+/// Load the song for `sing --midi`: either a directory holding
+/// `melody.mid` plus backing `.mid` files, or a single MIDI file whose
+/// parts are split into melody and backing. For a single file,
+/// `melody_part` (1-based, as logged) overrides `pick_melody`.
+pub fn load_song(path: &Path, melody_part: Option<usize>) -> Result<MidiSong> {
+    if path.is_dir() {
+        if melody_part.is_some() {
+            log::warn!("Melody track choice ignored: {} is a directory", path.display());
+        }
+        return load_song_dir(path);
+    }
+
+    let mut parts = parse_midi_parts(path)?;
+    if parts.is_empty() {
+        anyhow::bail!("No notes in MIDI file: {}", path.display());
+    }
+    for (i, part) in parts.iter().enumerate() {
+        log::info!(
+            "Part {}: {} notes, mean pitch {:.0}, program {}{}",
+            i + 1,
+            part.notes.len(),
+            mean_pitch(part),
+            part.program,
+            if part.is_drum { " (drums)" } else { "" }
+        );
+    }
+    let index = match melody_part {
+        Some(n) if n >= 1 && n <= parts.len() => n - 1,
+        Some(n) => anyhow::bail!("Melody track {} out of range: the file has {} parts", n, parts.len()),
+        None => pick_melody(&parts).context("MIDI file has only drum parts")?,
+    };
+    log::info!("Melody: part {}", index + 1);
+    let melody = parts.remove(index);
+    Ok(MidiSong { melody, backing: parts })
+}
+
+fn load_song_dir(dir: &Path) -> Result<MidiSong> {
+    let melody_path = dir.join("melody.mid");
+    if !melody_path.exists() {
+        anyhow::bail!("MIDI melody not found: {}", melody_path.display());
+    }
+    let melody = parse_midi(&melody_path)?;
+
+    // Backing tracks: every other .mid file
+    let mut backing = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().map(|e| e == "mid" || e == "midi").unwrap_or(false)
+                && path != melody_path
+            {
+                if let Ok(t) = parse_midi(&path) {
+                    backing.push(t);
+                }
+            }
+        }
+    }
+    Ok(MidiSong { melody, backing })
+}
+
+fn make_note(pitch: u8, start: f64, end: f64, velocity: u8) -> Note {
+    Note {
+        pitch,
+        start: (start * 10000.0).round() / 10000.0,
+        end: (end * 10000.0).round() / 10000.0,
+        velocity,
+    }
+}
+
+/// Converts tick positions to seconds, following tempo changes from every
+/// track (format 1 files keep them in a conductor track).
+struct TempoMap {
+    /// Seconds per tick for timecode files; `None` for metrical timing
+    fixed: Option<f64>,
+    ticks_per_beat: f64,
+    /// (tick, seconds at tick, microseconds per beat from here on)
+    segments: Vec<(u64, f64, f64)>,
+}
+
+impl TempoMap {
+    fn new(smf: &Smf) -> Result<Self> {
+        let ticks_per_beat = match smf.header.timing {
+            midly::Timing::Metrical(tpb) => tpb.as_int() as f64,
+            midly::Timing::Timecode(fps, sub) => {
+                let ticks_per_sec = fps.as_f32() as f64 * sub as f64;
+                if ticks_per_sec <= 0.0 {
+                    anyhow::bail!("MIDI header has zero ticks per frame");
+                }
+                return Ok(Self {
+                    fixed: Some(1.0 / ticks_per_sec),
+                    ticks_per_beat: 0.0,
+                    segments: vec![(0, 0.0, 500_000.0)],
+                });
+            }
+        };
+        if ticks_per_beat <= 0.0 {
+            anyhow::bail!("MIDI header has zero ticks per beat");
+        }
+
+        let mut changes: Vec<(u64, f64)> = Vec::new();
+        for track in &smf.tracks {
+            let mut tick = 0u64;
+            for event in track {
+                tick += event.delta.as_int() as u64;
+                // A zero tempo would stop time; ignore it
+                if let TrackEventKind::Meta(MetaMessage::Tempo(t)) = event.kind {
+                    if t.as_int() > 0 {
+                        changes.push((tick, t.as_int() as f64));
+                    }
+                }
+            }
+        }
+        changes.sort_by_key(|&(tick, _)| tick);
+
+        // Default 120 BPM until the first tempo event
+        let mut segments = vec![(0u64, 0.0f64, 500_000.0f64)];
+        for (tick, tempo) in changes {
+            let (last_tick, last_s, last_tempo) = *segments.last().unwrap();
+            let at_s = last_s + (tick - last_tick) as f64 / ticks_per_beat * last_tempo / 1_000_000.0;
+            if tick == last_tick {
+                segments.pop();
+            }
+            segments.push((tick, at_s, tempo));
+        }
+        Ok(Self { fixed: None, ticks_per_beat, segments })
+    }
+
+    fn seconds(&self, tick: u64) -> f64 {
+        if let Some(per_tick) = self.fixed {
+            return tick as f64 * per_tick;
+        }
+        let idx = self.segments.partition_point(|&(t, _, _)| t <= tick) - 1;
+        let (seg_tick, seg_s, tempo) = self.segments[idx];
+        seg_s + (tick - seg_tick) as f64 / self.ticks_per_beat * tempo / 1_000_000.0
+    }
+
+    /// Microseconds per beat at the start of the file.
+    fn initial_tempo(&self) -> f64 {
+        self.segments[0].2
+    }
 }
 
 #[cfg(test)]
@@ -169,6 +344,14 @@ mod tests {
         assert!((midi_to_hz(57) - 220.0).abs() < 0.01);
     }
 
+    #[test]
+    fn test_note_name() {
+        assert_eq!(note_name(60), "C4");
+        assert_eq!(note_name(69), "A4");
+        assert_eq!(note_name(61), "C#4");
+        assert_eq!(note_name(0), "C-1");
+    }
+
     #[test]
     fn test_note_duration() {
         let note = Note {
@@ -180,6 +363,106 @@ mod tests {
         assert!((note.duration() - 1.5).abs() < 1e-10);
     }
 
+    /// Single-track SMF with the given division and track events.
+    fn smf(division: u16, events: &[u8]) -> Vec<u8> {
+        smf_tracks(0, division, &[events])
+    }
+
+    fn smf_tracks(format: u16, division: u16, tracks: &[&[u8]]) -> Vec<u8> {
+        let mut data = b"MThd\x00\x00\x00\x06".to_vec();
+        data.extend_from_slice(&format.to_be_bytes());
+        data.extend_from_slice(&(tracks.len() as u16).to_be_bytes());
+        data.extend_from_slice(&division.to_be_bytes());
+        for events in tracks {
+            data.extend_from_slice(b"MTrk");
+            data.extend_from_slice(&(events.len() as u32).to_be_bytes());
+            data.extend_from_slice(events);
+        }
+        data
+    }
+
+    /// Format 1 song: conductor track at 60 BPM, a busy bass line, a
+    /// higher melody, and a drum part.
+    fn song() -> Vec<u8> {
+        let conductor: &[u8] = &[0x00, 0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40, 0x00, 0xFF, 0x2F, 0x00];
+        let mut bass = Vec::new();
+        for _ in 0..4 {
+            bass.extend_from_slice(&[0x00, 0x91, 40, 90, 0x83, 0x60, 0x81, 40, 0]);
+        }
+        bass.extend_from_slice(&[0x00, 0xFF, 0x2F, 0x00]);
+        let melody: &[u8] = &[
+            0x00, 0xC0, 53, 0x00, 0x90, 72, 100, 0x87, 0x40, 0x80, 72, 0,
+            0x00, 0x90, 76, 100, 0x87, 0x40, 0x80, 76, 0, 0x00, 0xFF, 0x2F, 0x00,
+        ];
+        let drums: &[u8] = &[0x00, 0x99, 36, 100, 0x10, 0x89, 36, 0, 0x00, 0xFF, 0x2F, 0x00];
+        smf_tracks(1, 480, &[conductor, &bass, melody, drums])
+    }
+
+    #[test]
+    fn test_parse_midi_parts() {
+        let parts = parse_midi_parts_bytes(&song()).unwrap();
+        assert_eq!(parts.len(), 3);
+        // The conductor's tempo applies to every track: one beat is 1s
+        assert_eq!(parts[0].tempo, 60.0);
+        assert_eq!(parts[0].notes.len(), 4);
+        assert!((parts[0].notes[3].start - 3.0).abs() < 1e-9);
+        assert_eq!(parts[1].program, 53);
+        assert!((parts[1].notes[1].end - 4.0).abs() < 1e-9);
+        assert!(parts[2].is_drum);
+        assert!(parts.iter().all(|p| p.total_duration == 4.0));
+
+        // The merged view still drops drums
+        let merged = parse_midi_bytes(&song()).unwrap();
+        assert_eq!(merged.notes.len(), 6);
+    }
+
+    #[test]
+    fn test_pick_melody() {
+        let parts = parse_midi_parts_bytes(&song()).unwrap();
+        assert_eq!(pick_melody(&parts), Some(1));
+        assert_eq!(pick_melody(&parts[2..]), None);
+    }
+
+    #[test]
+    fn test_load_song_from_file() {
+        let path = std::env::temp_dir().join(format!("glottisdale_song_{}.mid", std::process::id()));
+        std::fs::write(&path, song()).unwrap();
+
+        let song = load_song(&path, None).unwrap();
+        assert_eq!(song.melody.notes[0].pitch, 72);
+        assert_eq!(song.backing.len(), 2);
+        let song = load_song(&path, Some(1)).unwrap();
+        assert_eq!(song.melody.notes[0].pitch, 40);
+        assert!(load_song(&path, Some(4)).is_err());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_parse_midi_bytes() {
+        // Note on C4, off after one beat (480 ticks at 120 BPM = 0.5s)
+        let events = [0x00, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0x00, 0xFF, 0x2F, 0x00];
+        let track = parse_midi_bytes(&smf(480, &events)).unwrap();
+        assert_eq!(track.notes.len(), 1);
+        assert_eq!(track.notes[0].pitch, 60);
+        assert!((track.notes[0].end - 0.5).abs() < 1e-9);
+        assert_eq!(track.tempo, 120.0);
+    }
+
+    #[test]
+    fn test_parse_midi_bytes_rejects_malformed() {
+        assert!(parse_midi_bytes(b"").is_err());
+        assert!(parse_midi_bytes(b"MThd garbage").is_err());
+        // Zero ticks per beat would make every timestamp NaN
+        let events = [0x00, 0x90, 60, 100, 0x10, 0x80, 60, 0, 0x00, 0xFF, 0x2F, 0x00];
+        assert!(parse_midi_bytes(&smf(0, &events)).is_err());
+        // Zero tempo is ignored rather than stopping time
+        let events = [0x00, 0xFF, 0x51, 0x03, 0, 0, 0, 0x00, 0x90, 60, 100, 0x83, 0x60, 0x80, 60, 0, 0x00, 0xFF, 0x2F, 0x00];
+        let track = parse_midi_bytes(&smf(480, &events)).unwrap();
+        assert_eq!(track.tempo, 120.0);
+        assert!((track.total_duration - 0.5).abs() < 1e-9);
+    }
+
     #[test]
     fn test_parse_midi_nonexistent() {
         let result = parse_midi(Path::new("/nonexistent.mid"));
@@ -4,21 +4,89 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 
-use crate::audio::effects::mix_audio;
-use crate::audio::io::{read_wav, write_wav};
-use crate::sing::midi_parser::MidiTrack;
-use crate::sing::synthesize::synthesize_preview;
+use crate::audio::effects::{limit_peaks, mix_audio};
+use crate::audio::io::{get_wav_duration, read_wav};
+use crate::audio::loudness::{record_loudness, Mastering};
+use crate::sing::midi_parser::{MidiTrack, Note};
+use crate::sing::synthesize::{synthesize_preview_with, Instruments};
 
-/// Mix vocal audio with MIDI backing.
+/// Length of a "musical" fade-out: one 4/4 bar at `tempo` BPM.
+pub fn bar_seconds(tempo: f64) -> f64 {
+    if tempo > 0.0 {
+        4.0 * 60.0 / tempo
+    } else {
+        2.0
+    }
+}
+
+/// Cut `samples` to at most `duration` seconds and fade out over the last
+/// `fade_s` seconds (capped at half the output), so a cut melody ends
+/// instead of stopping mid-phrase.
+pub fn truncate_with_fade(samples: &mut Vec<f64>, sr: u32, duration: f64, fade_s: f64) {
+    let max_len = (duration * sr as f64).round() as usize;
+    samples.truncate(max_len);
+    let len = samples.len();
+    let fade_len = ((fade_s * sr as f64).round() as usize).min(len / 2);
+    let fade_start = len - fade_len;
+    for (i, s) in samples[fade_start..].iter_mut().enumerate() {
+        let t = i as f64 / fade_len as f64;
+        *s *= (1.0 - t) * (1.0 - t);
+    }
+}
+
+/// Mix harmony voices under a lead vocal, each `voice_db` below it.
+///
+/// The result has the lead's length: voices are cut to it, and shorter
+/// voices simply end early rather than looping. A mix that would clip is
+/// scaled back to full scale.
+pub fn mix_harmony(lead: &[f64], voices: &[Vec<f64>], voice_db: f64) -> Vec<f64> {
+    let voices: Vec<Vec<Vec<f64>>> = voices.iter().map(|v| vec![v.clone()]).collect();
+    mix_harmony_channels(&[lead.to_vec()], &voices, voice_db)
+        .pop()
+        .unwrap_or_default()
+}
+
+/// [`mix_harmony`] for planar vocals: each voice has one buffer per lead
+/// channel. A mix that would clip is scaled by its overall peak, so the
+/// channels keep their balance.
+pub fn mix_harmony_channels(lead: &[Vec<f64>], voices: &[Vec<Vec<f64>>], voice_db: f64) -> Vec<Vec<f64>> {
+    let gain = 10.0f64.powf(voice_db / 20.0);
+    let mut mixed = lead.to_vec();
+    for voice in voices {
+        for (channel, voice_channel) in mixed.iter_mut().zip(voice) {
+            for (out, &s) in channel.iter_mut().zip(voice_channel) {
+                *out += s * gain;
+            }
+        }
+    }
+    let peak = mixed.iter().flatten().map(|s| s.abs()).fold(0.0f64, f64::max);
+    if peak > 1.0 {
+        for s in mixed.iter_mut().flatten() {
+            *s /= peak;
+        }
+    }
+    mixed
+}
+
+/// Mix planar (mono or stereo) vocal audio with MIDI backing played on
+/// `instruments`.
 ///
-/// Returns (full_mix_path, acappella_path).
-pub fn mix_tracks(
-    vocal_samples: &[f64],
+/// The MIDI backing is mono and is mixed equally into every vocal channel,
+/// so a stereo vocal keeps its image. Returns (full_mix_path,
+/// acappella_path). With `limiter` set, both are run through a peak
+/// limiter, then both are mastered before they are written, with their
+/// loudness recorded in the run's manifest.json.
+#[allow(clippy::too_many_arguments)]
+pub fn mix_tracks_channels(
+    vocal_channels: &[Vec<f64>],
     vocal_sr: u32,
     midi_tracks: &[MidiTrack],
+    instruments: &Instruments,
     output_dir: &Path,
     vocal_db: f64,
     midi_db: f64,
+    limiter: bool,
+    mastering: &Mastering,
 ) -> Result<(PathBuf, PathBuf)> {
     std::fs::create_dir_all(output_dir)?;
     let run_name = output_dir
@@ -28,23 +96,26 @@ pub fn mix_tracks(
     let acappella_path = output_dir.join(format!("{}-acappella.wav", run_name));
     let full_mix_path = output_dir.join(format!("{}.wav", run_name));
 
+    let finish = |path: &Path, channels: &[Vec<f64>]| -> Result<()> {
+        let report = if limiter {
+            mastering.write(path, &limit_peaks(channels, vocal_sr), vocal_sr)?
+        } else {
+            mastering.write(path, channels, vocal_sr)?
+        };
+        record_loudness(output_dir, path, &report)
+    };
+
     // Write a cappella
-    write_wav(&acappella_path, vocal_samples, vocal_sr)?;
+    finish(&acappella_path, vocal_channels)?;
 
     // Synthesize MIDI backing
     let midi_wav = output_dir.join("midi_backing.wav");
-    let has_midi = synthesize_preview(midi_tracks, &midi_wav).is_ok();
+    let has_midi = synthesize_preview_with(midi_tracks, &midi_wav, instruments).is_ok();
 
     if has_midi && midi_wav.exists() {
         // Load the MIDI backing and mix
         let (midi_samples, _midi_sr) = read_wav(&midi_wav)?;
 
-        // Apply volume adjustments
-        let mut vocals = vocal_samples.to_vec();
-        if vocal_db.abs() > 0.1 {
-            crate::audio::effects::adjust_volume(&mut vocals, vocal_db);
-        }
-
         let mut midi = midi_samples;
         // Resample MIDI to match vocal sample rate if needed
         // (synthesizer outputs at 22050, vocals at 16000)
@@ -57,21 +128,107 @@ pub fn mix_tracks(
             }
         }
 
-        let mixed = mix_audio(&vocals, &midi, midi_db);
-        write_wav(&full_mix_path, &mixed, vocal_sr)?;
+        // Apply volume adjustments and mix per channel
+        let mixed: Vec<Vec<f64>> = vocal_channels
+            .iter()
+            .map(|channel| {
+                let mut vocals = channel.clone();
+                if vocal_db.abs() > 0.1 {
+                    crate::audio::effects::adjust_volume(&mut vocals, vocal_db);
+                }
+                mix_audio(&vocals, &midi, midi_db)
+            })
+            .collect();
+        finish(&full_mix_path, &mixed)?;
     } else {
         log::warn!("MIDI synthesis failed, using a cappella as full mix");
-        write_wav(&full_mix_path, vocal_samples, vocal_sr)?;
+        finish(&full_mix_path, vocal_channels)?;
     }
 
     Ok((full_mix_path, acappella_path))
 }
 
+/// Write `stems.json` next to the stems: where the a cappella and the
+/// synthesized backing (`midi_backing.wav`, if any) start on the song's
+/// timeline, their lengths, the tempo, and the sung notes, so the stems can
+/// be lined up in a DAW. Both stems start at song time zero.
+pub fn write_stem_metadata(
+    output_dir: &Path,
+    acappella: &Path,
+    vocal_duration: f64,
+    tempo: f64,
+    notes: &[Note],
+) -> Result<PathBuf> {
+    let file_name = |p: &Path| p.file_name().unwrap_or_default().to_string_lossy().to_string();
+    let backing_path = output_dir.join("midi_backing.wav");
+    let backing = match get_wav_duration(&backing_path) {
+        Ok(duration) => serde_json::json!({
+            "file": file_name(&backing_path),
+            "offset_s": 0.0,
+            "duration_s": duration,
+        }),
+        Err(_) => serde_json::Value::Null,
+    };
+    let metadata = serde_json::json!({
+        "tempo_bpm": tempo,
+        "note_count": notes.len(),
+        "first_note_s": notes.first().map_or(0.0, |n| n.start),
+        "last_note_end_s": notes.iter().map(|n| n.end).fold(0.0, f64::max),
+        "vocal": {
+            "file": file_name(acappella),
+            "offset_s": 0.0,
+            "duration_s": vocal_duration,
+        },
+        "backing": backing,
+    });
+    let path = output_dir.join("stems.json");
+    std::fs::write(&path, serde_json::to_string_pretty(&metadata)?)?;
+    Ok(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::sing::midi_parser::Note;
 
+    #[test]
+    fn test_mix_harmony() {
+        let lead = vec![0.5; 4];
+        let voices = vec![vec![0.2; 6], vec![0.4; 2]];
+        let mixed = mix_harmony(&lead, &voices, -6.0);
+        assert_eq!(mixed.len(), 4);
+        let gain = 10.0f64.powf(-6.0 / 20.0);
+        assert!((mixed[0] - (0.5 + 0.6 * gain)).abs() < 1e-12);
+        assert!((mixed[3] - (0.5 + 0.2 * gain)).abs() < 1e-12);
+        assert_eq!(mix_harmony(&lead, &[], -6.0), lead);
+        let loud = mix_harmony(&[0.9, -0.9], &[vec![0.9, 0.9]], 0.0);
+        assert_eq!(loud[0], 1.0);
+        assert_eq!(loud[1], 0.0);
+
+        // Stereo scales both channels by the overall peak
+        let stereo = mix_harmony_channels(&[vec![0.9], vec![0.3]], &[vec![vec![0.9], vec![0.3]]], 0.0);
+        assert_eq!(stereo[0][0], 1.0);
+        assert!((stereo[1][0] - 1.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_truncate_with_fade() {
+        let mut samples = vec![1.0; 32000];
+        truncate_with_fade(&mut samples, 16000, 1.5, bar_seconds(240.0));
+        assert_eq!(samples.len(), 24000);
+        // Full level before the one-second fade, silent at the end
+        assert_eq!(samples[7999], 1.0);
+        assert!(samples[8000] <= 1.0 && samples[16000] < 0.5);
+        assert!(samples[23999] < 1e-6);
+
+        // Shorter audio keeps its length but still fades out
+        let mut short = vec![1.0; 100];
+        truncate_with_fade(&mut short, 16000, 1.0, 1.0);
+        assert_eq!(short.len(), 100);
+        assert_eq!(short[49], 1.0);
+        assert!(short[99] < 0.01);
+    }
+
     #[test]
     fn test_mix_tracks_no_midi() {
         let dir = std::env::temp_dir().join(format!("glottisdale_mixer_{}", std::process::id()));
@@ -81,13 +238,22 @@ mod tests {
             .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / 16000.0).sin() * 0.5)
             .collect();
 
-        let result = mix_tracks(&vocals, 16000, &[], &dir, 0.0, -12.0);
+        let mastering = Mastering::default();
+        let result = mix_tracks_channels(&[vocals], 16000, &[], &Instruments::default(), &dir, 0.0, -12.0, true, &mastering);
         assert!(result.is_ok());
 
         let (full_mix, acappella) = result.unwrap();
         assert!(acappella.exists());
         assert!(full_mix.exists());
 
+        // Both outputs are normalized and reported in the manifest
+        let manifest: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("manifest.json")).unwrap()).unwrap();
+        for path in [&full_mix, &acappella] {
+            let report = &manifest["loudness"][path.file_name().unwrap().to_str().unwrap()];
+            assert!((report["output_lufs"].as_f64().unwrap() + 16.0).abs() < 0.5, "{}", report);
+        }
+
         std::fs::remove_dir_all(&dir).ok();
     }
 
@@ -111,13 +277,48 @@ mod tests {
             total_duration: 1.0,
         }];
 
-        let result = mix_tracks(&vocals, 16000, &tracks, &dir, 0.0, -12.0);
+        let mastering = Mastering::default();
+        let result = mix_tracks_channels(&[vocals], 16000, &tracks, &Instruments::default(), &dir, 0.0, -12.0, true, &mastering);
         assert!(result.is_ok());
 
         let (full_mix, acappella) = result.unwrap();
         assert!(acappella.exists());
         assert!(full_mix.exists());
 
+        let path = write_stem_metadata(&dir, &acappella, 1.0, 120.0, &tracks[0].notes).unwrap();
+        let metadata: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(metadata["note_count"], 2);
+        assert_eq!(metadata["tempo_bpm"], 120.0);
+        assert_eq!(metadata["last_note_end_s"], 1.0);
+        assert_eq!(metadata["vocal"]["file"], acappella.file_name().unwrap().to_str().unwrap());
+        assert_eq!(metadata["backing"]["file"], "midi_backing.wav");
+        assert!(metadata["backing"]["duration_s"].as_f64().unwrap() > 0.9);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_mix_tracks_channels_writes_stereo() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_mixer_stereo_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let left: Vec<f64> = (0..8000)
+            .map(|i| (2.0 * std::f64::consts::PI * 440.0 * i as f64 / 16000.0).sin() * 0.5)
+            .collect();
+        let right = vec![0.0; 8000];
+
+        let mastering = Mastering::default();
+        let (full_mix, acappella) =
+            mix_tracks_channels(&[left, right], 16000, &[], &Instruments::default(), &dir, 0.0, -12.0, true, &mastering)
+                .unwrap();
+
+        let (channels, sr) = crate::audio::io::read_wav_channels(&acappella).unwrap();
+        assert_eq!(sr, 16000);
+        assert_eq!(channels.len(), 2);
+        assert!(channels[1].iter().all(|s| s.abs() < 1e-3));
+        let (channels, _) = crate::audio::io::read_wav_channels(&full_mix).unwrap();
+        assert_eq!(channels.len(), 2);
+
         std::fs::remove_dir_all(&dir).ok();
     }
 }
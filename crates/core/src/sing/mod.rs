@@ -3,3 +3,6 @@ pub mod syllable_prep;
 pub mod vocal_mapper;
 pub mod synthesize;
 pub mod mixer;
+pub mod harmony;
+pub mod soundfont;
+pub mod lyrics;
@@ -0,0 +1,734 @@
+//! SoundFont 2 playback for MIDI backing tracks.
+//!
+//! Covers what a backing band needs: presets and instruments with key and
+//! velocity ranges, sample loops, tuning, attenuation, and the volume
+//! envelope. Modulators, filters, LFOs, and effects sends are ignored, and
+//! output is mono.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+
+use crate::sing::midi_parser::Note;
+
+// Generator numbers from the SF2 2.01 spec, section 8.1.2
+const GEN_START_OFFSET: u16 = 0;
+const GEN_END_OFFSET: u16 = 1;
+const GEN_LOOP_START_OFFSET: u16 = 2;
+const GEN_LOOP_END_OFFSET: u16 = 3;
+const GEN_START_COARSE: u16 = 4;
+const GEN_END_COARSE: u16 = 12;
+const GEN_DELAY_VOL_ENV: u16 = 33;
+const GEN_ATTACK_VOL_ENV: u16 = 34;
+const GEN_HOLD_VOL_ENV: u16 = 35;
+const GEN_DECAY_VOL_ENV: u16 = 36;
+const GEN_SUSTAIN_VOL_ENV: u16 = 37;
+const GEN_RELEASE_VOL_ENV: u16 = 38;
+const GEN_INSTRUMENT: u16 = 41;
+const GEN_KEY_RANGE: u16 = 43;
+const GEN_VEL_RANGE: u16 = 44;
+const GEN_LOOP_START_COARSE: u16 = 45;
+const GEN_INITIAL_ATTENUATION: u16 = 48;
+const GEN_LOOP_END_COARSE: u16 = 50;
+const GEN_COARSE_TUNE: u16 = 51;
+const GEN_FINE_TUNE: u16 = 52;
+const GEN_SAMPLE_ID: u16 = 53;
+const GEN_SAMPLE_MODES: u16 = 54;
+const GEN_SCALE_TUNING: u16 = 56;
+const GEN_OVERRIDING_ROOT_KEY: u16 = 58;
+
+/// Generators a preset zone adds to its instrument's value.
+const ADDITIVE_GENS: &[u16] = &[
+    GEN_DELAY_VOL_ENV,
+    GEN_ATTACK_VOL_ENV,
+    GEN_HOLD_VOL_ENV,
+    GEN_DECAY_VOL_ENV,
+    GEN_SUSTAIN_VOL_ENV,
+    GEN_RELEASE_VOL_ENV,
+    GEN_INITIAL_ATTENUATION,
+    GEN_COARSE_TUNE,
+    GEN_FINE_TUNE,
+];
+
+/// Bank number GM percussion kits live in.
+pub const PERCUSSION_BANK: u16 = 128;
+
+/// Longest release tail rendered, in seconds.
+const MAX_RELEASE: f64 = 5.0;
+
+/// A parsed SoundFont with its sample data in memory.
+#[derive(Debug, Clone)]
+pub struct Soundfont {
+    presets: Vec<Preset>,
+    instruments: Vec<Instrument>,
+    samples: Vec<SampleHeader>,
+    data: Vec<i16>,
+}
+
+#[derive(Debug, Clone)]
+struct Preset {
+    name: String,
+    program: u16,
+    bank: u16,
+    global: Option<Zone>,
+    zones: Vec<Zone>,
+}
+
+#[derive(Debug, Clone)]
+struct Instrument {
+    global: Option<Zone>,
+    zones: Vec<Zone>,
+}
+
+/// A key/velocity region: its generators and the instrument (preset
+/// zones) or sample (instrument zones) it plays.
+#[derive(Debug, Clone, Default)]
+struct Zone {
+    gens: HashMap<u16, i16>,
+    link: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct SampleHeader {
+    start: u32,
+    end: u32,
+    loop_start: u32,
+    loop_end: u32,
+    sample_rate: u32,
+    original_pitch: u8,
+    pitch_correction: i8,
+}
+
+impl Zone {
+    fn range(&self, id: u16) -> (u8, u8) {
+        match self.gens.get(&id) {
+            Some(&v) => {
+                let v = v as u16;
+                ((v & 0xff) as u8, (v >> 8) as u8)
+            }
+            None => (0, 127),
+        }
+    }
+
+    fn matches(&self, key: u8, vel: u8) -> bool {
+        let (klo, khi) = self.range(GEN_KEY_RANGE);
+        let (vlo, vhi) = self.range(GEN_VEL_RANGE);
+        (klo..=khi).contains(&key) && (vlo..=vhi).contains(&vel)
+    }
+}
+
+/// Value of generator `id` for a sounding zone pair: the instrument zone
+/// (over its global zone, over the spec default), plus the preset's offset
+/// for additive generators.
+fn generator(id: u16, inst: &Zone, inst_global: Option<&Zone>, preset: &Zone, preset_global: Option<&Zone>) -> i32 {
+    let default = match id {
+        GEN_DELAY_VOL_ENV | GEN_ATTACK_VOL_ENV | GEN_HOLD_VOL_ENV | GEN_DECAY_VOL_ENV | GEN_RELEASE_VOL_ENV => -12000,
+        GEN_SCALE_TUNING => 100,
+        GEN_OVERRIDING_ROOT_KEY => -1,
+        _ => 0,
+    };
+    let base = inst
+        .gens
+        .get(&id)
+        .or_else(|| inst_global.and_then(|z| z.gens.get(&id)))
+        .map(|&v| v as i32)
+        .unwrap_or(default);
+    let offset = if ADDITIVE_GENS.contains(&id) {
+        preset
+            .gens
+            .get(&id)
+            .or_else(|| preset_global.and_then(|z| z.gens.get(&id)))
+            .map(|&v| v as i32)
+            .unwrap_or(0)
+    } else {
+        0
+    };
+    base + offset
+}
+
+fn timecents_to_seconds(tc: i32) -> f64 {
+    2f64.powf(tc as f64 / 1200.0)
+}
+
+/// A RIFF chunk: four-character id and body.
+fn chunks(mut data: &[u8]) -> Result<Vec<(&[u8], &[u8])>> {
+    let mut out = Vec::new();
+    while data.len() >= 8 {
+        let id = &data[..4];
+        let size = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+        let body = data.get(8..8 + size).context("Truncated SoundFont chunk")?;
+        out.push((id, body));
+        // Chunks are padded to even sizes
+        let next = (8 + size + (size & 1)).min(data.len());
+        data = &data[next..];
+    }
+    Ok(out)
+}
+
+fn u16_at(r: &[u8], i: usize) -> u16 {
+    u16::from_le_bytes([r[i], r[i + 1]])
+}
+
+fn u32_at(r: &[u8], i: usize) -> u32 {
+    u32::from_le_bytes([r[i], r[i + 1], r[i + 2], r[i + 3]])
+}
+
+/// Fixed-size records of a pdta sub-chunk.
+fn records<'a>(pdta: &HashMap<&[u8], &'a [u8]>, id: &str, size: usize) -> Result<Vec<&'a [u8]>> {
+    let chunk = pdta
+        .get(id.as_bytes())
+        .with_context(|| format!("SoundFont is missing its {} chunk", id))?;
+    Ok(chunk.chunks_exact(size).collect())
+}
+
+/// Split bags `[first, last)` into a global zone and linked zones.
+fn zones(
+    bag_range: (usize, usize),
+    bags: &[&[u8]],
+    gens: &[&[u8]],
+    link_gen: u16,
+) -> Result<(Option<Zone>, Vec<Zone>)> {
+    let (first, last) = bag_range;
+    let mut global = None;
+    let mut linked = Vec::new();
+    for b in first..last {
+        let (Some(bag), Some(next)) = (bags.get(b), bags.get(b + 1)) else {
+            bail!("SoundFont zone index out of range");
+        };
+        let (g0, g1) = (u16_at(bag, 0) as usize, u16_at(next, 0) as usize);
+        let mut zone = Zone::default();
+        for g in gens.get(g0..g1.max(g0)).context("SoundFont generator index out of range")? {
+            let (oper, amount) = (u16_at(g, 0), u16_at(g, 2) as i16);
+            if oper == link_gen {
+                zone.link = Some(amount as u16 as usize);
+            } else {
+                zone.gens.insert(oper, amount);
+            }
+        }
+        if zone.link.is_some() {
+            linked.push(zone);
+        } else if b == first {
+            global = Some(zone);
+        }
+    }
+    Ok((global, linked))
+}
+
+impl Soundfont {
+    /// Load an `.sf2` file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = std::fs::read(path)
+            .with_context(|| format!("Failed to read SoundFont: {}", path.display()))?;
+        Self::parse(&data).with_context(|| format!("Invalid SoundFont: {}", path.display()))
+    }
+
+    /// Parse SoundFont 2 data.
+    pub fn parse(data: &[u8]) -> Result<Self> {
+        if data.len() < 12 || &data[..4] != b"RIFF" || &data[8..12] != b"sfbk" {
+            bail!("Not a SoundFont 2 file");
+        }
+        let mut sample_data: Option<&[u8]> = None;
+        let mut pdta: HashMap<&[u8], &[u8]> = HashMap::new();
+        for (id, body) in chunks(&data[12..])? {
+            if id != b"LIST" || body.len() < 4 {
+                continue;
+            }
+            for (sub_id, sub_body) in chunks(&body[4..])? {
+                match &body[..4] {
+                    b"sdta" if sub_id == b"smpl" => sample_data = Some(sub_body),
+                    b"pdta" => {
+                        pdta.insert(sub_id, sub_body);
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let sample_data = sample_data.context("SoundFont has no sample data")?;
+
+        let phdr = records(&pdta, "phdr", 38)?;
+        let pbag = records(&pdta, "pbag", 4)?;
+        let pgen = records(&pdta, "pgen", 4)?;
+        let inst = records(&pdta, "inst", 22)?;
+        let ibag = records(&pdta, "ibag", 4)?;
+        let igen = records(&pdta, "igen", 4)?;
+        let shdr = records(&pdta, "shdr", 46)?;
+
+        // The last phdr/inst/shdr record is a terminator
+        let mut presets = Vec::new();
+        for pair in phdr.windows(2) {
+            let (h, next) = (pair[0], pair[1]);
+            let bags = (u16_at(h, 24) as usize, u16_at(next, 24) as usize);
+            let (global, zones) = zones(bags, &pbag, &pgen, GEN_INSTRUMENT)?;
+            let name = String::from_utf8_lossy(&h[..20]).trim_end_matches('\0').trim().to_string();
+            presets.push(Preset {
+                name,
+                program: u16_at(h, 20),
+                bank: u16_at(h, 22),
+                global,
+                zones,
+            });
+        }
+        let mut instruments = Vec::new();
+        for pair in inst.windows(2) {
+            let bags = (u16_at(pair[0], 20) as usize, u16_at(pair[1], 20) as usize);
+            let (global, zones) = zones(bags, &ibag, &igen, GEN_SAMPLE_ID)?;
+            instruments.push(Instrument { global, zones });
+        }
+        let samples = shdr
+            .iter()
+            .take(shdr.len().saturating_sub(1))
+            .map(|r| SampleHeader {
+                start: u32_at(r, 20),
+                end: u32_at(r, 24),
+                loop_start: u32_at(r, 28),
+                loop_end: u32_at(r, 32),
+                sample_rate: u32_at(r, 36),
+                original_pitch: r[40],
+                pitch_correction: r[41] as i8,
+            })
+            .collect();
+
+        let data = sample_data
+            .chunks_exact(2)
+            .map(|b| i16::from_le_bytes([b[0], b[1]]))
+            .collect();
+        Ok(Self { presets, instruments, samples, data })
+    }
+
+    /// Names of the presets, as "bank:program name".
+    pub fn preset_names(&self) -> Vec<String> {
+        self.presets
+            .iter()
+            .map(|p| format!("{}:{} {}", p.bank, p.program, p.name))
+            .collect()
+    }
+
+    /// Preset for a GM program: exact bank and program, then the same
+    /// program in bank 0, then the bank's first preset. Percussion only
+    /// falls back within the percussion bank.
+    fn find_preset(&self, bank: u16, program: u16) -> Option<usize> {
+        let exact = |b: u16| self.presets.iter().position(|p| p.bank == b && p.program == program);
+        exact(bank)
+            .or_else(|| if bank == PERCUSSION_BANK { None } else { exact(0) })
+            .or_else(|| self.presets.iter().position(|p| p.bank == bank))
+    }
+
+    /// Whether the font has a percussion kit.
+    pub fn has_percussion(&self) -> bool {
+        self.presets.iter().any(|p| p.bank == PERCUSSION_BANK)
+    }
+
+    /// Mix every note of a part into `out` (sampled at `sr`). Returns
+    /// false if the font has no preset for it.
+    pub fn render_notes(&self, notes: &[Note], program: u8, is_drum: bool, out: &mut [f64], sr: u32) -> bool {
+        let bank = if is_drum { PERCUSSION_BANK } else { 0 };
+        let program = if is_drum { 0 } else { program as u16 };
+        let Some(preset) = self.find_preset(bank, program) else {
+            return false;
+        };
+        for note in notes {
+            self.render_note(preset, note, out, sr);
+        }
+        true
+    }
+
+    fn render_note(&self, preset_idx: usize, note: &Note, out: &mut [f64], sr: u32) {
+        let preset = &self.presets[preset_idx];
+        for pzone in preset.zones.iter().filter(|z| z.matches(note.pitch, note.velocity)) {
+            let Some(inst) = pzone.link.and_then(|i| self.instruments.get(i)) else {
+                continue;
+            };
+            for izone in inst.zones.iter().filter(|z| z.matches(note.pitch, note.velocity)) {
+                let Some(sample) = izone.link.and_then(|s| self.samples.get(s)) else {
+                    continue;
+                };
+                let param = |g| generator(g, izone, inst.global.as_ref(), pzone, preset.global.as_ref());
+                self.render_voice(sample, &param, note, out, sr);
+            }
+        }
+    }
+
+    fn render_voice(&self, sample: &SampleHeader, param: &dyn Fn(u16) -> i32, note: &Note, out: &mut [f64], sr: u32) {
+        let addr = |fine: u16, coarse: u16| param(fine) as i64 + param(coarse) as i64 * 32768;
+        let limit = self.data.len() as i64;
+        let start = (sample.start as i64 + addr(GEN_START_OFFSET, GEN_START_COARSE)).clamp(0, limit);
+        let end = (sample.end as i64 + addr(GEN_END_OFFSET, GEN_END_COARSE)).clamp(start, limit);
+        let loop_start = (sample.loop_start as i64 + addr(GEN_LOOP_START_OFFSET, GEN_LOOP_START_COARSE)).clamp(start, end);
+        let loop_end = (sample.loop_end as i64 + addr(GEN_LOOP_END_OFFSET, GEN_LOOP_END_COARSE)).clamp(loop_start, end);
+        if end - start < 2 || sample.sample_rate == 0 {
+            return;
+        }
+        let mode = param(GEN_SAMPLE_MODES) & 3;
+        let looping = (mode == 1 || mode == 3) && loop_end - loop_start >= 2;
+
+        let root = match param(GEN_OVERRIDING_ROOT_KEY) {
+            k @ 0..=127 => k,
+            _ => sample.original_pitch.min(127) as i32,
+        };
+        let cents = (note.pitch as i32 - root) * param(GEN_SCALE_TUNING)
+            + param(GEN_COARSE_TUNE) * 100
+            + param(GEN_FINE_TUNE)
+            + sample.pitch_correction as i32;
+        let step = 2f64.powf(cents as f64 / 1200.0) * sample.sample_rate as f64 / sr as f64;
+
+        // Attenuation in centibels, plus the default velocity curve
+        let vel = (note.velocity as f64 / 127.0).max(1e-3);
+        let gain = 10f64.powf(-param(GEN_INITIAL_ATTENUATION).max(0) as f64 / 200.0) * vel * vel / 32768.0;
+
+        let delay = timecents_to_seconds(param(GEN_DELAY_VOL_ENV));
+        let attack = timecents_to_seconds(param(GEN_ATTACK_VOL_ENV));
+        let hold = timecents_to_seconds(param(GEN_HOLD_VOL_ENV));
+        let decay = timecents_to_seconds(param(GEN_DECAY_VOL_ENV));
+        let sustain_db = -(param(GEN_SUSTAIN_VOL_ENV).clamp(0, 1440) as f64) / 10.0;
+        let release = timecents_to_seconds(param(GEN_RELEASE_VOL_ENV)).min(MAX_RELEASE);
+        let held = note.duration().max(0.0);
+
+        // Envelope level (linear) while the key is down
+        let level_at = |t: f64| -> f64 {
+            if t < delay {
+                0.0
+            } else if t < delay + attack {
+                (t - delay) / attack
+            } else if t < delay + attack + hold {
+                1.0
+            } else {
+                let db = (-96.0 * (t - delay - attack - hold) / decay).max(sustain_db);
+                10f64.powf(db / 20.0)
+            }
+        };
+        let release_from = level_at(held);
+
+        let first = (note.start * sr as f64).round().max(0.0) as usize;
+        let total = ((held + release) * sr as f64).ceil() as usize;
+        let mut pos = start as f64;
+        for i in 0..total {
+            let Some(slot) = out.get_mut(first + i) else {
+                break;
+            };
+            let t = i as f64 / sr as f64;
+            let released = t >= held;
+            let env = if released {
+                release_from * 10f64.powf(-96.0 * (t - held) / release / 20.0)
+            } else {
+                level_at(t)
+            };
+
+            if looping && (mode == 1 || !released) {
+                while pos >= loop_end as f64 {
+                    pos -= (loop_end - loop_start) as f64;
+                }
+            }
+            let idx = pos as i64;
+            if idx + 1 >= end {
+                break;
+            }
+            let frac = pos - idx as f64;
+            let a = self.data[idx as usize] as f64;
+            let b = self.data[idx as usize + 1] as f64;
+            *slot += (a + (b - a) * frac) * gain * env;
+            pos += step;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    fn chunk(id: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        out.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            out.push(0);
+        }
+        out
+    }
+
+    fn list(kind: &[u8], chunks: &[Vec<u8>]) -> Vec<u8> {
+        let mut body = kind.to_vec();
+        for c in chunks {
+            body.extend_from_slice(c);
+        }
+        chunk(b"LIST", &body)
+    }
+
+    fn gens(pairs: &[(u16, u16)]) -> Vec<u8> {
+        pairs.iter().flat_map(|&(o, a)| [o.to_le_bytes(), a.to_le_bytes()].concat()).collect()
+    }
+
+    fn name(s: &str, len: usize) -> Vec<u8> {
+        let mut out = s.as_bytes().to_vec();
+        out.resize(len, 0);
+        out
+    }
+
+    /// A font with one looped 441 Hz sine (root A4) as preset 0:0 and a
+    /// percussion preset 128:0 playing the same sample unlooped.
+    pub(crate) fn test_font() -> Vec<u8> {
+        let sr = 44100u32;
+        let smpl: Vec<u8> = (0..4410)
+            .flat_map(|i| {
+                let v = ((i as f64 * 441.0 / sr as f64) * std::f64::consts::TAU).sin() * 16000.0;
+                (v as i16).to_le_bytes()
+            })
+            .collect();
+
+        let mut phdr = Vec::new();
+        for (n, program, bank, bag) in [("Sine", 0u16, 0u16, 0u16), ("Kit", 0, 128, 1), ("EOP", 0, 0, 2)] {
+            phdr.extend(name(n, 20));
+            for v in [program, bank, bag] {
+                phdr.extend(v.to_le_bytes());
+            }
+            phdr.extend([0u8; 12]);
+        }
+        let pbag = gens(&[(0, 0), (1, 0), (2, 0)]);
+        let pgen = gens(&[(GEN_INSTRUMENT, 0), (GEN_INSTRUMENT, 1), (0, 0)]);
+
+        let mut inst = Vec::new();
+        for (n, bag) in [("Sine", 0u16), ("Hit", 1), ("EOI", 2)] {
+            inst.extend(name(n, 20));
+            inst.extend(bag.to_le_bytes());
+        }
+        let ibag = gens(&[(0, 0), (3, 0), (6, 0)]);
+        let igen = gens(&[
+            (GEN_SAMPLE_MODES, 1),
+            (GEN_RELEASE_VOL_ENV, (-2400i16) as u16),
+            (GEN_SAMPLE_ID, 0),
+            (GEN_INITIAL_ATTENUATION, 60),
+            (GEN_OVERRIDING_ROOT_KEY, 36),
+            (GEN_SAMPLE_ID, 0),
+            (0, 0),
+        ]);
+
+        let mut shdr = Vec::new();
+        for (n, end) in [("sine", 4410u32), ("EOS", 0)] {
+            shdr.extend(name(n, 20));
+            for v in [0, end, 100, 4300, sr] {
+                shdr.extend(v.to_le_bytes());
+            }
+            shdr.extend([69u8, 0]);
+            shdr.extend([0u8; 4]);
+        }
+
+        let body = [
+            b"sfbk".to_vec(),
+            list(b"INFO", &[chunk(b"ifil", &[2, 0, 1, 0])]),
+            list(b"sdta", &[chunk(b"smpl", &smpl)]),
+            list(
+                b"pdta",
+                &[
+                    chunk(b"phdr", &phdr),
+                    chunk(b"pbag", &pbag),
+                    chunk(b"pmod", &[0; 10]),
+                    chunk(b"pgen", &pgen),
+                    chunk(b"inst", &inst),
+                    chunk(b"ibag", &ibag),
+                    chunk(b"imod", &[0; 10]),
+                    chunk(b"igen", &igen),
+                    chunk(b"shdr", &shdr),
+                ],
+            ),
+        ]
+        .concat();
+        chunk(b"RIFF", &body)
+    }
+
+    fn crossings_per_second(samples: &[f64], sr: u32) -> f64 {
+        let n = samples.windows(2).filter(|w| w[0] <= 0.0 && w[1] > 0.0).count();
+        n as f64 * sr as f64 / samples.len() as f64
+    }
+
+    #[test]
+    fn test_parse_presets() {
+        let font = Soundfont::parse(&test_font()).unwrap();
+        assert_eq!(font.preset_names(), vec!["0:0 Sine", "128:0 Kit"]);
+        assert!(font.has_percussion());
+        // Unknown programs fall back to the bank's first preset
+        assert_eq!(font.find_preset(0, 40), Some(0));
+        assert_eq!(font.find_preset(PERCUSSION_BANK, 5), Some(1));
+    }
+
+    #[test]
+    fn test_render_pitch_loop_and_velocity() {
+        let font = Soundfont::parse(&test_font()).unwrap();
+        let sr = 22050;
+        let render = |pitch, velocity| {
+            let mut out = vec![0.0; sr as usize];
+            let note = Note { pitch, start: 0.0, end: 0.5, velocity };
+            assert!(font.render_notes(&[note], 0, false, &mut out, sr));
+            out
+        };
+
+        // An octave above the root doubles the frequency; the 0.1s sample
+        // loops to fill the 0.5s note
+        let a5 = render(81, 127);
+        let f = crossings_per_second(&a5[1000..10000], sr);
+        assert!((f - 882.0).abs() < 15.0, "got {} Hz", f);
+        assert!(a5[10000..11000].iter().any(|s| s.abs() > 0.1));
+        // Released after 0.5s with a quick release
+        assert!(a5[16000..].iter().all(|s| s.abs() < 1e-3));
+
+        let peak = |v: &[f64]| v.iter().fold(0.0f64, |m, s| m.max(s.abs()));
+        assert!(peak(&render(69, 40)) < peak(&render(69, 127)) * 0.2);
+    }
+
+    #[test]
+    fn test_drum_preset_plays_unlooped_sample() {
+        let font = Soundfont::parse(&test_font()).unwrap();
+        let mut out = vec![0.0; 22050];
+        let hit = Note { pitch: 36, start: 0.0, end: 1.0, velocity: 127 };
+        assert!(font.render_notes(&[hit], 0, true, &mut out, 22050));
+        // The 0.1s sample plays once and stops
+        assert!(out[..1000].iter().any(|s| s.abs() > 0.05));
+        assert!(out[3000..].iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed() {
+        assert!(Soundfont::parse(b"").is_err());
+        assert!(Soundfont::parse(b"RIFF\x04\x00\x00\x00sfbk").is_err());
+        assert!(Soundfont::parse(b"RIFF\x04\x00\x00\x00WAVE").is_err());
+        let font = test_font();
+        assert!(Soundfont::parse(&font[..font.len() / 2]).is_err());
+    }
+
+    /// Byte offset of the body of the first chunk with `id` in `font`.
+    fn chunk_body(font: &[u8], id: &[u8]) -> usize {
+        font.windows(4).position(|w| w == id).unwrap() + 8
+    }
+
+    #[test]
+    fn test_parse_truncated_anywhere_fails_cleanly() {
+        // Every cut either fails or parses; none may panic
+        let font = test_font();
+        for len in 0..font.len() {
+            let _ = Soundfont::parse(&font[..len]);
+        }
+        // Cuts inside the preset data lose a chunk it needs
+        let pdta = chunk_body(&font, b"phdr");
+        for len in (pdta..font.len() - 8).step_by(7) {
+            assert!(Soundfont::parse(&font[..len]).is_err(), "cut at {} parsed", len);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_missing_chunks_and_bad_indices() {
+        let font = test_font();
+        let err = |data: &[u8]| format!("{:#}", Soundfont::parse(data).unwrap_err());
+
+        let mut no_samples = font.clone();
+        let smpl = chunk_body(&font, b"smpl") - 8;
+        no_samples[smpl..smpl + 4].copy_from_slice(b"xxxx");
+        assert!(err(&no_samples).contains("no sample data"));
+
+        let mut no_igen = font.clone();
+        let igen = chunk_body(&font, b"igen") - 8;
+        no_igen[igen..igen + 4].copy_from_slice(b"xxxx");
+        assert!(err(&no_igen).contains("igen"));
+
+        // A preset whose bag index runs past the pbag records
+        let mut bad_bag = font.clone();
+        let phdr = chunk_body(&font, b"phdr");
+        bad_bag[phdr + 38 + 24..phdr + 38 + 26].copy_from_slice(&40u16.to_le_bytes());
+        assert!(err(&bad_bag).contains("zone index out of range"));
+
+        // An instrument zone whose generator index runs past the igen records
+        let mut bad_gen = font.clone();
+        let ibag = chunk_body(&font, b"ibag");
+        bad_gen[ibag + 4..ibag + 6].copy_from_slice(&40u16.to_le_bytes());
+        assert!(err(&bad_gen).contains("generator index out of range"));
+    }
+
+    #[test]
+    fn test_render_ignores_dangling_links_and_wild_sample_bounds() {
+        let mut font = Soundfont::parse(&test_font()).unwrap();
+        font.presets[0].zones[0].link = Some(99);
+        let mut out = vec![0.0; 4410];
+        let note = Note { pitch: 69, start: 0.0, end: 0.1, velocity: 100 };
+        assert!(font.render_notes(std::slice::from_ref(&note), 0, false, &mut out, 22050));
+        assert!(out.iter().all(|&s| s == 0.0));
+
+        let mut font = Soundfont::parse(&test_font()).unwrap();
+        font.samples[0].end = u32::MAX;
+        font.samples[0].loop_end = u32::MAX;
+        font.render_notes(&[note], 0, false, &mut out, 22050);
+        assert!(out.iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn test_zones_split_global_and_linked() {
+        let font = Soundfont::parse(&test_font()).unwrap();
+        // "Sine" has one sample zone; "Hit" a sample zone too, no global
+        assert_eq!(font.instruments.len(), 2);
+        assert!(font.instruments.iter().all(|i| i.global.is_none() && i.zones.len() == 1));
+        assert_eq!(font.instruments[0].zones[0].link, Some(0));
+        assert_eq!(font.instruments[0].zones[0].gens.get(&GEN_SAMPLE_MODES), Some(&1));
+        assert_eq!(font.presets[1].zones[0].link, Some(1));
+
+        // A leading zone without a link is the global zone; a later one is dropped
+        let bags = [[0u8, 0, 0, 0], [1, 0, 0, 0], [2, 0, 0, 0], [3, 0, 0, 0]];
+        let gens = [
+            [GEN_COARSE_TUNE.to_le_bytes(), 2u16.to_le_bytes()].concat(),
+            [GEN_SAMPLE_ID.to_le_bytes(), 7u16.to_le_bytes()].concat(),
+            [GEN_FINE_TUNE.to_le_bytes(), 5u16.to_le_bytes()].concat(),
+        ];
+        let bags: Vec<&[u8]> = bags.iter().map(|b| &b[..]).collect();
+        let gens: Vec<&[u8]> = gens.iter().map(|g| &g[..]).collect();
+        let (global, linked) = zones((0, 3), &bags, &gens, GEN_SAMPLE_ID).unwrap();
+        assert_eq!(global.unwrap().gens.get(&GEN_COARSE_TUNE), Some(&2));
+        assert_eq!(linked.len(), 1);
+        assert_eq!(linked[0].link, Some(7));
+    }
+
+    #[test]
+    fn test_generator_lookup_order() {
+        let zone = |pairs: &[(u16, i16)]| Zone { gens: pairs.iter().copied().collect(), link: None };
+        let inst = zone(&[(GEN_COARSE_TUNE, 2)]);
+        let inst_global = zone(&[(GEN_COARSE_TUNE, 9), (GEN_FINE_TUNE, 30), (GEN_SAMPLE_MODES, 1)]);
+        let preset = zone(&[(GEN_FINE_TUNE, -10), (GEN_SAMPLE_MODES, 3)]);
+        let preset_global = zone(&[(GEN_COARSE_TUNE, 1), (GEN_FINE_TUNE, 50)]);
+        let gen = |id| generator(id, &inst, Some(&inst_global), &preset, Some(&preset_global));
+
+        // The zone's own value beats its global zone; the preset adds its offset
+        assert_eq!(gen(GEN_COARSE_TUNE), 2 + 1);
+        // The global zone fills in; the preset zone beats the preset global
+        assert_eq!(gen(GEN_FINE_TUNE), 30 - 10);
+        // Non-additive generators ignore the preset
+        assert_eq!(gen(GEN_SAMPLE_MODES), 1);
+        // Unset generators take the spec defaults
+        assert_eq!(gen(GEN_RELEASE_VOL_ENV), -12000);
+        assert_eq!(gen(GEN_SCALE_TUNING), 100);
+        assert_eq!(gen(GEN_OVERRIDING_ROOT_KEY), -1);
+        assert_eq!(generator(GEN_FINE_TUNE, &Zone::default(), None, &Zone::default(), None), 0);
+    }
+
+    #[test]
+    fn test_key_and_velocity_ranges() {
+        let range = |lo: u8, hi: u8| (lo as u16 | (hi as u16) << 8) as i16;
+        let zone = Zone {
+            gens: [(GEN_KEY_RANGE, range(60, 72)), (GEN_VEL_RANGE, range(64, 127))].into_iter().collect(),
+            link: None,
+        };
+        assert!(zone.matches(60, 64) && zone.matches(72, 127));
+        assert!(!zone.matches(59, 100) && !zone.matches(73, 100) && !zone.matches(66, 63));
+        assert!(Zone::default().matches(0, 0) && Zone::default().matches(127, 127));
+
+        // Ranges on either level gate what a note sounds
+        let sr = 22050;
+        let sounds = |font: &Soundfont, pitch, velocity| {
+            let mut out = vec![0.0; 4410];
+            let note = Note { pitch, start: 0.0, end: 0.1, velocity };
+            font.render_notes(&[note], 0, false, &mut out, sr);
+            out.iter().any(|s| s.abs() > 1e-3)
+        };
+        let mut font = Soundfont::parse(&test_font()).unwrap();
+        font.instruments[0].zones[0].gens.insert(GEN_KEY_RANGE, range(60, 72));
+        assert!(sounds(&font, 69, 100));
+        assert!(!sounds(&font, 81, 100));
+        font.presets[0].zones[0].gens.insert(GEN_VEL_RANGE, range(64, 127));
+        assert!(sounds(&font, 69, 100));
+        assert!(!sounds(&font, 69, 40));
+    }
+}
@@ -2,6 +2,7 @@
 
 use crate::audio::analysis::{compute_rms, estimate_f0};
 use crate::audio::effects::{adjust_volume, cut_clip, pitch_shift};
+use crate::audio::io::mix_to_mono;
 use crate::types::Syllable;
 
 /// A pitch- and volume-normalized syllable clip (in-memory).
@@ -19,6 +20,8 @@ pub struct NormalizedSyllable {
     pub phonemes: Vec<String>,
     /// Parent word text
     pub word: String,
+    /// Index of the parent word in its source's transcript
+    pub word_index: usize,
 }
 
 /// Compute semitone shifts to normalize all F0s to the median.
@@ -58,54 +61,94 @@ pub fn prepare_syllables(
     sr: u32,
     max_semitone_shift: f64,
 ) -> Vec<NormalizedSyllable> {
-    if syllables.is_empty() {
-        return Vec::new();
+    prepare(syllables, &[source_samples], sr, max_semitone_shift)
+        .pop()
+        .unwrap_or_default()
+}
+
+/// Like [`prepare_syllables`] for planar source audio: returns one list of
+/// clips per channel, parallel to each other.
+///
+/// F0 and level are measured on the channels' mix, so every channel of a
+/// syllable gets the same pitch shift and gain and the stereo image holds.
+/// Each list carries the same metadata, so any of them can plan notes.
+pub fn prepare_syllables_channels(
+    syllables: &[Syllable],
+    source_channels: &[Vec<f64>],
+    sr: u32,
+    max_semitone_shift: f64,
+) -> Vec<Vec<NormalizedSyllable>> {
+    let channels: Vec<&[f64]> = source_channels.iter().map(Vec::as_slice).collect();
+    prepare(syllables, &channels, sr, max_semitone_shift)
+}
+
+fn prepare(
+    syllables: &[Syllable],
+    channels: &[&[f64]],
+    sr: u32,
+    max_semitone_shift: f64,
+) -> Vec<Vec<NormalizedSyllable>> {
+    let mut per_channel: Vec<Vec<NormalizedSyllable>> = vec![Vec::new(); channels.len()];
+    if syllables.is_empty() || channels.is_empty() {
+        return per_channel;
     }
 
     // Cut and analyze each syllable
-    let mut all_syls: Vec<NormalizedSyllable> = Vec::new();
-
     for syl in syllables {
-        let clip = cut_clip(source_samples, sr, syl.start, syl.end, 25.0, 0.0);
-        if clip.is_empty() {
+        let clips: Vec<Vec<f64>> = channels
+            .iter()
+            .map(|source| cut_clip(source, sr, syl.start, syl.end, 25.0, 0.0))
+            .collect();
+        if clips.iter().any(|clip| clip.is_empty()) {
             continue;
         }
 
-        let f0 = estimate_f0(&clip, sr, 80, 600);
-        let duration = clip.len() as f64 / sr as f64;
+        let mix = mix_to_mono(&clips);
+        let f0 = estimate_f0(&mix, sr, 80, 600);
+        let duration = mix.len() as f64 / sr as f64;
         let phoneme_labels: Vec<String> = syl.phonemes.iter().map(|p| p.label.clone()).collect();
 
-        all_syls.push(NormalizedSyllable {
-            samples: clip,
-            sr,
-            f0,
-            duration,
-            phonemes: phoneme_labels,
-            word: syl.word.clone(),
-        });
+        for (list, clip) in per_channel.iter_mut().zip(clips) {
+            list.push(NormalizedSyllable {
+                samples: clip,
+                sr,
+                f0,
+                duration,
+                phonemes: phoneme_labels.clone(),
+                word: syl.word.clone(),
+                word_index: syl.word_index,
+            });
+        }
     }
 
-    if all_syls.is_empty() {
-        return all_syls;
+    if per_channel[0].is_empty() {
+        return per_channel;
     }
 
     // Normalize pitch to median F0
-    let f0_values: Vec<Option<f64>> = all_syls.iter().map(|s| s.f0).collect();
+    let f0_values: Vec<Option<f64>> = per_channel[0].iter().map(|s| s.f0).collect();
     let shifts = compute_pitch_shifts(&f0_values);
 
-    for (syl, shift) in all_syls.iter_mut().zip(shifts.iter()) {
-        if shift.abs() < 0.1 {
-            continue;
-        }
-        let clamped = shift.clamp(-max_semitone_shift, max_semitone_shift);
-        if let Ok(shifted) = pitch_shift(&syl.samples, syl.sr, clamped) {
-            syl.samples = shifted;
-            syl.duration = syl.samples.len() as f64 / syl.sr as f64;
+    for list in per_channel.iter_mut() {
+        for (syl, shift) in list.iter_mut().zip(shifts.iter()) {
+            if shift.abs() < 0.1 {
+                continue;
+            }
+            let clamped = shift.clamp(-max_semitone_shift, max_semitone_shift);
+            if let Ok(shifted) = pitch_shift(&syl.samples, syl.sr, clamped) {
+                syl.samples = shifted;
+                syl.duration = syl.samples.len() as f64 / syl.sr as f64;
+            }
         }
     }
 
     // Volume normalize to median RMS
-    let rms_values: Vec<f64> = all_syls.iter().map(|s| compute_rms(&s.samples)).collect();
+    let rms_values: Vec<f64> = (0..per_channel[0].len())
+        .map(|i| {
+            let clips: Vec<Vec<f64>> = per_channel.iter().map(|list| list[i].samples.clone()).collect();
+            compute_rms(&mix_to_mono(&clips))
+        })
+        .collect();
     let voiced_rms: Vec<f64> = rms_values.iter().filter(|&&r| r > 0.0).copied().collect();
 
     if !voiced_rms.is_empty() {
@@ -113,20 +156,22 @@ pub fn prepare_syllables(
         sorted_rms.sort_by(|a, b| a.partial_cmp(b).unwrap());
         let target_rms = sorted_rms[sorted_rms.len() / 2];
 
-        for (syl, &rms) in all_syls.iter_mut().zip(rms_values.iter()) {
-            if rms <= 0.0 {
-                continue;
-            }
-            let db_adjust = 20.0 * (target_rms / rms).log10();
-            let db_adjust = db_adjust.clamp(-20.0, 20.0);
-            if db_adjust.abs() >= 0.5 {
-                adjust_volume(&mut syl.samples, db_adjust);
-                syl.duration = syl.samples.len() as f64 / syl.sr as f64;
+        for list in per_channel.iter_mut() {
+            for (syl, &rms) in list.iter_mut().zip(rms_values.iter()) {
+                if rms <= 0.0 {
+                    continue;
+                }
+                let db_adjust = 20.0 * (target_rms / rms).log10();
+                let db_adjust = db_adjust.clamp(-20.0, 20.0);
+                if db_adjust.abs() >= 0.5 {
+                    adjust_volume(&mut syl.samples, db_adjust);
+                    syl.duration = syl.samples.len() as f64 / syl.sr as f64;
+                }
             }
         }
     }
 
-    all_syls
+    per_channel
 }
 
 /// Get median F0 from a list of normalized syllables.
@@ -182,6 +227,46 @@ mod tests {
         assert!(shifts[2].abs() < 0.01);
     }
 
+    #[test]
+    fn test_prepare_syllables_channels_keeps_balance() {
+        use crate::types::Phoneme;
+
+        let sr = 16000;
+        let tone = |amp: f64, hz: f64| -> Vec<f64> {
+            (0..sr as usize)
+                .map(|i| amp * (2.0 * std::f64::consts::PI * hz * i as f64 / sr as f64).sin())
+                .collect()
+        };
+        let syllable = |start: f64, end: f64| Syllable {
+            phonemes: vec![Phoneme { label: "AH0".into(), start, end }],
+            start,
+            end,
+            word: "ah".into(),
+            word_index: 0,
+        };
+        // A quiet 200 Hz syllable then a loud 250 Hz one, twice as loud on the left
+        let mut left = tone(0.1, 200.0);
+        left[8000..].copy_from_slice(&tone(0.4, 250.0)[8000..]);
+        let right: Vec<f64> = left.iter().map(|s| s * 0.5).collect();
+        let syllables = vec![syllable(0.1, 0.4), syllable(0.6, 0.9)];
+
+        let channels = prepare_syllables_channels(&syllables, &[left.clone(), right], sr, 12.0);
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].len(), 2);
+        for (l, r) in channels[0].iter().zip(&channels[1]) {
+            assert_eq!(l.f0, r.f0);
+            assert_eq!(l.samples.len(), r.samples.len());
+            let ratio = compute_rms(&l.samples) / compute_rms(&r.samples);
+            assert!((ratio - 2.0).abs() < 0.01, "ratio {}", ratio);
+        }
+
+        // One channel prepares exactly as the mono path does
+        let mono = prepare_syllables(&syllables, &left, sr, 12.0);
+        let single = prepare_syllables_channels(&syllables, &[left], sr, 12.0);
+        assert_eq!(mono.len(), single[0].len());
+        assert_eq!(mono[1].samples, single[0][1].samples);
+    }
+
     #[test]
     fn test_median_f0_empty() {
         assert!(median_f0(&[]).is_none());
@@ -197,6 +282,7 @@ mod tests {
                 duration: 0.3,
                 phonemes: vec![],
                 word: "a".to_string(),
+                word_index: 0,
             },
             NormalizedSyllable {
                 samples: vec![],
@@ -205,6 +291,7 @@ mod tests {
                 duration: 0.3,
                 phonemes: vec![],
                 word: "b".to_string(),
+                word_index: 0,
             },
             NormalizedSyllable {
                 samples: vec![],
@@ -213,6 +300,7 @@ mod tests {
                 duration: 0.3,
                 phonemes: vec![],
                 word: "c".to_string(),
+                word_index: 0,
             },
         ];
         let median = median_f0(&syls);
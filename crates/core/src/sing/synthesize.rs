@@ -1,14 +1,21 @@
 //! Synthesize MIDI notes to WAV preview using sine waves.
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
 
-use crate::audio::io::write_wav;
+use crate::audio::io::{read_wav, resample, write_wav};
 use crate::sing::midi_parser::{midi_to_hz, MidiTrack, Note};
+use crate::sing::soundfont::Soundfont;
 
 const SAMPLE_RATE: u32 = 22050;
-const MAX_DURATION: f64 = 30.0;
+/// Longest backing rendered, in seconds. Looped sing output can run to
+/// several minutes, and a shorter backing would drift out of step when
+/// `mix_audio` repeats it.
+const MAX_DURATION: f64 = 600.0;
 
 /// Synthesize a single note to audio samples using a sine wave with envelope.
 fn synthesize_note(note: &Note, sr: u32) -> Vec<f64> {
@@ -39,68 +46,180 @@ fn synthesize_note(note: &Note, sr: u32) -> Vec<f64> {
         .collect()
 }
 
-/// Synthesize a drum hit (noise burst with envelope).
-fn synthesize_drum(pitch: u8, velocity: u8, sr: u32) -> Vec<f64> {
-    let vel = velocity as f64 / 127.0;
-    let mut rng_state = pitch as u64 * 12345;
+/// How one drum note sounds: a sine body (with optional downward pitch
+/// sweep) mixed with noise under an exponential decay, or a user sample.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DrumVoice {
+    /// Body frequency in Hz
+    pub freq: f64,
+    /// Pitch sweep rate (1/s); 0 keeps the body at `freq`
+    pub sweep: f64,
+    /// Noise share of the mix, 0 (pure tone) to 1 (pure noise)
+    pub noise: f64,
+    /// Amplitude decay rate (1/s) at full velocity
+    pub decay: f64,
+    /// Hit length in seconds (ignored for samples)
+    pub length: f64,
+    pub gain: f64,
+    /// WAV file to play instead of synthesizing, relative to the map file
+    pub sample: Option<PathBuf>,
+    #[serde(skip)]
+    sample_audio: Option<Arc<Vec<f64>>>,
+}
 
-    // Simple LCG for deterministic noise
-    let mut next_noise = || -> f64 {
-        rng_state = rng_state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
-        (rng_state as f64 / u64::MAX as f64) * 2.0 - 1.0
-    };
+impl Default for DrumVoice {
+    /// The generic "other percussion" hit.
+    fn default() -> Self {
+        Self::synth(0.0, 0.0, 1.0, 40.0, 0.06, 0.4)
+    }
+}
 
-    match pitch {
-        35 | 36 => {
-            // Kick
-            let length = (0.08 * sr as f64) as usize;
-            (0..length)
-                .map(|i| {
-                    let t = i as f64 / sr as f64;
-                    (2.0 * std::f64::consts::PI * 80.0 * t * (-t * 30.0).exp()).sin()
-                        * (-t * 25.0).exp()
-                        * vel
-                })
-                .collect()
+impl DrumVoice {
+    fn synth(freq: f64, sweep: f64, noise: f64, decay: f64, length: f64, gain: f64) -> Self {
+        Self { freq, sweep, noise, decay, length, gain, sample: None, sample_audio: None }
+    }
+}
+
+/// GM drum notes mapped to voices, plus the velocity response.
+///
+/// A TOML drum map overrides or extends the built-in kit:
+///
+/// ```toml
+/// velocity_curve = 1.5   # gain = (velocity / 127) ^ curve
+///
+/// [notes.36]             # kick
+/// freq = 60.0
+/// sweep = 30.0
+/// decay = 18.0
+/// length = 0.15
+///
+/// [notes.39]             # hand clap from a sample
+/// sample = "clap.wav"
+/// gain = 0.8
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrumMap {
+    pub voices: HashMap<u8, DrumVoice>,
+    /// Exponent of the velocity-to-gain curve; 1 is linear
+    pub velocity_curve: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DrumMapFile {
+    velocity_curve: Option<f64>,
+    #[serde(default)]
+    notes: HashMap<String, DrumVoice>,
+}
+
+impl Default for DrumMap {
+    /// The built-in sine/noise kit.
+    fn default() -> Self {
+        let kick = DrumVoice::synth(80.0, 30.0, 0.0, 25.0, 0.08, 1.0);
+        let snare = DrumVoice::synth(180.0, 0.0, 0.7, 20.0, 0.1, 1.0);
+        let hihat = DrumVoice::synth(0.0, 0.0, 1.0, 60.0, 0.05, 0.5);
+        let mut voices = HashMap::new();
+        for (notes, voice) in [(&[35u8, 36][..], kick), (&[38, 40], snare), (&[42, 44, 46], hihat)] {
+            for &note in notes {
+                voices.insert(note, voice.clone());
+            }
         }
-        38 | 40 => {
-            // Snare
-            let length = (0.1 * sr as f64) as usize;
-            (0..length)
-                .map(|i| {
-                    let t = i as f64 / sr as f64;
-                    (next_noise() * 0.7
-                        + (2.0 * std::f64::consts::PI * 180.0 * t).sin() * 0.3)
-                        * (-t * 20.0).exp()
-                        * vel
-                })
-                .collect()
+        Self { voices, velocity_curve: 1.0 }
+    }
+}
+
+impl DrumMap {
+    /// Load a TOML drum map on top of the built-in kit. Sample paths are
+    /// resolved against the map's directory and loaded up front.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read drum map: {}", path.display()))?;
+        let base = path.parent().unwrap_or(Path::new("."));
+        Self::parse(&text, base).with_context(|| format!("Invalid drum map: {}", path.display()))
+    }
+
+    /// Parse a TOML drum map; relative sample paths are joined to `base`.
+    pub fn parse(text: &str, base: &Path) -> Result<Self> {
+        let file: DrumMapFile = toml::from_str(text)?;
+        let mut map = Self::default();
+        if let Some(curve) = file.velocity_curve {
+            if curve.is_nan() || curve <= 0.0 {
+                bail!("velocity_curve must be positive, got {}", curve);
+            }
+            map.velocity_curve = curve;
         }
-        42 | 44 | 46 => {
-            // Hihat
-            let length = (0.05 * sr as f64) as usize;
-            (0..length)
-                .map(|i| {
-                    let t = i as f64 / sr as f64;
-                    next_noise() * (-t * 60.0).exp() * 0.5 * vel
-                })
-                .collect()
+        for (key, mut voice) in file.notes {
+            let note: u8 = key
+                .parse()
+                .ok()
+                .filter(|&n| n < 128)
+                .with_context(|| format!("Drum note must be a MIDI note number 0-127, got '{}'", key))?;
+            if let Some(sample) = &voice.sample {
+                let path = base.join(sample);
+                let (audio, sr) = read_wav(&path)
+                    .with_context(|| format!("Failed to load drum sample for note {}: {}", note, path.display()))?;
+                let audio = if sr != SAMPLE_RATE { resample(&audio, sr, SAMPLE_RATE)? } else { audio };
+                voice.sample_audio = Some(Arc::new(audio));
+            }
+            map.voices.insert(note, voice);
         }
-        _ => {
-            // Other percussion
-            let length = (0.06 * sr as f64) as usize;
-            (0..length)
-                .map(|i| {
-                    let t = i as f64 / sr as f64;
-                    next_noise() * (-t * 40.0).exp() * 0.4 * vel
-                })
-                .collect()
+        Ok(map)
+    }
+
+    /// The voice for a drum note (the generic hit if unmapped).
+    pub fn voice(&self, pitch: u8) -> DrumVoice {
+        self.voices.get(&pitch).cloned().unwrap_or_default()
+    }
+
+    /// Render one hit. Velocity sets the gain through `velocity_curve`,
+    /// and softer hits also decay faster, as on an acoustic kit.
+    pub fn render(&self, pitch: u8, velocity: u8, sr: u32) -> Vec<f64> {
+        let voice = self.voice(pitch);
+        let vel = velocity as f64 / 127.0;
+        let amp = vel.powf(self.velocity_curve) * voice.gain;
+
+        if let Some(sample) = &voice.sample_audio {
+            return sample.iter().map(|s| s * amp).collect();
         }
+
+        let decay = voice.decay * (1.5 - 0.5 * vel);
+        // Deterministic noise per drum note (LCG)
+        let mut rng_state = pitch as u64 * 12345;
+        let mut next_noise = || -> f64 {
+            rng_state = rng_state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (rng_state as f64 / u64::MAX as f64) * 2.0 - 1.0
+        };
+
+        let length = (voice.length.max(0.0) * sr as f64) as usize;
+        (0..length)
+            .map(|i| {
+                let t = i as f64 / sr as f64;
+                let noise = if voice.noise > 0.0 { next_noise() * voice.noise } else { 0.0 };
+                let phase = 2.0 * std::f64::consts::PI * voice.freq * t * (-voice.sweep * t).exp();
+                (noise + phase.sin() * (1.0 - voice.noise)) * (-t * decay).exp() * amp
+            })
+            .collect()
     }
 }
 
+/// What the backing is played with. Without a SoundFont, melodic parts
+/// are sine tones and drum parts use the built-in kit.
+#[derive(Debug, Clone, Default)]
+pub struct Instruments {
+    pub soundfont: Option<Soundfont>,
+    /// Drum map for drum parts; takes precedence over the SoundFont's kit
+    pub drum_map: Option<DrumMap>,
+}
+
 /// Synthesize a MIDI track to audio samples.
 pub fn synthesize_track(track: &MidiTrack, sr: u32) -> Vec<f64> {
+    synthesize_track_with(track, sr, &Instruments::default())
+}
+
+/// Synthesize a MIDI track with the given instruments. Parts the SoundFont
+/// has no preset for fall back to sine (or drum) synthesis.
+pub fn synthesize_track_with(track: &MidiTrack, sr: u32, instruments: &Instruments) -> Vec<f64> {
     if track.notes.is_empty() {
         return Vec::new();
     }
@@ -110,10 +229,26 @@ pub fn synthesize_track(track: &MidiTrack, sr: u32) -> Vec<f64> {
     let len = total_samples.min(max_samples);
     let mut audio = vec![0.0f64; len];
 
+    let mapped_drums = track.is_drum && instruments.drum_map.is_some();
+    if let Some(font) = instruments.soundfont.as_ref().filter(|_| !mapped_drums) {
+        if font.render_notes(&track.notes, track.program, track.is_drum, &mut audio, sr) {
+            return audio;
+        }
+    }
+
+    let default_kit;
+    let drums = match &instruments.drum_map {
+        Some(map) => map,
+        None => {
+            default_kit = DrumMap::default();
+            &default_kit
+        }
+    };
+
     for note in &track.notes {
         let start_idx = (note.start * sr as f64).round() as usize;
         let samples = if track.is_drum {
-            synthesize_drum(note.pitch, note.velocity, sr)
+            drums.render(note.pitch, note.velocity, sr)
         } else {
             synthesize_note(note, sr)
         };
@@ -133,12 +268,21 @@ pub fn synthesize_track(track: &MidiTrack, sr: u32) -> Vec<f64> {
 pub fn synthesize_preview(
     tracks: &[MidiTrack],
     output_path: &Path,
+) -> Result<()> {
+    synthesize_preview_with(tracks, output_path, &Instruments::default())
+}
+
+/// Synthesize and mix multiple MIDI tracks with the given instruments.
+pub fn synthesize_preview_with(
+    tracks: &[MidiTrack],
+    output_path: &Path,
+    instruments: &Instruments,
 ) -> Result<()> {
     let sr = SAMPLE_RATE;
 
     let mut track_audio: Vec<Vec<f64>> = Vec::new();
     for track in tracks {
-        let audio = synthesize_track(track, sr);
+        let audio = synthesize_track_with(track, sr, instruments);
         if !audio.is_empty() {
             track_audio.push(audio);
         }
@@ -178,6 +322,10 @@ pub fn synthesize_preview(
 mod tests {
     use super::*;
 
+    fn synthesize_drum(pitch: u8, velocity: u8, sr: u32) -> Vec<f64> {
+        DrumMap::default().render(pitch, velocity, sr)
+    }
+
     #[test]
     fn test_synthesize_note() {
         let note = Note {
@@ -206,6 +354,71 @@ mod tests {
         assert!(!samples.is_empty());
     }
 
+    #[test]
+    fn test_drum_velocity_response() {
+        let kit = DrumMap::default();
+        let peak = |v: &[f64]| v.iter().fold(0.0f64, |m, s| m.max(s.abs()));
+        let hard = kit.render(38, 127, SAMPLE_RATE);
+        let soft = kit.render(38, 40, SAMPLE_RATE);
+        assert_eq!(hard.len(), soft.len());
+        assert!(peak(&soft) < peak(&hard) * 0.5);
+        // Soft hits also die away faster
+        let tail = |v: &[f64]| peak(&v[v.len() / 2..]) / peak(v);
+        assert!(tail(&soft) < tail(&hard));
+
+        let steep = DrumMap { velocity_curve: 2.0, ..DrumMap::default() };
+        assert!(peak(&steep.render(38, 64, SAMPLE_RATE)) < peak(&kit.render(38, 64, SAMPLE_RATE)));
+    }
+
+    #[test]
+    fn test_drum_map_parse() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_drums_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        write_wav(&dir.join("clap.wav"), &vec![0.5f64; 2205], SAMPLE_RATE).unwrap();
+
+        let map = DrumMap::parse(
+            "velocity_curve = 1.5\n[notes.36]\nfreq = 60.0\nlength = 0.2\n[notes.39]\nsample = \"clap.wav\"\ngain = 0.5\n",
+            &dir,
+        )
+        .unwrap();
+        assert_eq!(map.velocity_curve, 1.5);
+        // Unlisted fields take the generic defaults; unlisted notes keep the kit
+        assert_eq!(map.voice(36).freq, 60.0);
+        assert_eq!(map.voice(36).decay, DrumVoice::default().decay);
+        assert_eq!(map.voice(42), DrumMap::default().voice(42));
+        assert_eq!(map.render(36, 127, SAMPLE_RATE).len(), 4410);
+        let clap = map.render(39, 127, SAMPLE_RATE);
+        assert_eq!(clap.len(), 2205);
+        assert!((clap[100] - 0.25).abs() < 1e-3);
+
+        assert!(DrumMap::parse("[notes.kick]\nfreq = 1.0", &dir).is_err());
+        assert!(DrumMap::parse("[notes.36]\nfrequency = 1.0", &dir).is_err());
+        assert!(DrumMap::parse("[notes.36]\nsample = \"missing.wav\"", &dir).is_err());
+        assert!(DrumMap::parse("velocity_curve = 0.0", &dir).is_err());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_soundfont_backing_with_fallbacks() {
+        let font = Soundfont::parse(&crate::sing::soundfont::tests::test_font()).unwrap();
+        let notes = vec![Note { pitch: 69, start: 0.0, end: 0.5, velocity: 100 }];
+        let melodic = MidiTrack { notes: notes.clone(), tempo: 120.0, program: 0, is_drum: false, total_duration: 0.5 };
+        let drums = MidiTrack { is_drum: true, ..melodic.clone() };
+
+        let sine = synthesize_track(&melodic, SAMPLE_RATE);
+        let with_font = Instruments { soundfont: Some(font), drum_map: None };
+        let sampled = synthesize_track_with(&melodic, SAMPLE_RATE, &with_font);
+        assert_eq!(sampled.len(), sine.len());
+        assert_ne!(sampled, sine);
+
+        // An explicit drum map wins over the font's kit
+        let kit = synthesize_track_with(&drums, SAMPLE_RATE, &with_font);
+        let mapped = Instruments { drum_map: Some(DrumMap::default()), ..with_font };
+        assert_ne!(synthesize_track_with(&drums, SAMPLE_RATE, &mapped), kit);
+        assert_eq!(synthesize_track_with(&drums, SAMPLE_RATE, &mapped), synthesize_track(&drums, SAMPLE_RATE));
+    }
+
     #[test]
     fn test_synthesize_track_empty() {
         let track = MidiTrack {
@@ -4,8 +4,14 @@ use rand::Rng;
 use rand::rngs::StdRng;
 use rand::SeedableRng;
 
-use crate::audio::effects::{concatenate, generate_silence, pitch_shift, time_stretch};
-use crate::sing::midi_parser::{midi_to_hz, Note};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::audio::effects::{blend_wet_dry, concatenate, generate_silence, pitch_shift, time_stretch};
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::sing::midi_parser::{midi_to_hz, MidiTrack, Note};
 use crate::sing::syllable_prep::NormalizedSyllable;
 
 /// How a melody note maps to syllable(s).
@@ -30,15 +36,426 @@ pub enum DurationClass {
     Long,
 }
 
+/// Detuned copies layered over chorused notes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChorusConfig {
+    /// Number of detuned copies.
+    pub voices: usize,
+    /// Detune of each copy in cents, drawn from this range, up or down.
+    pub detune_cents: (f64, f64),
+    /// Delay of each copy in milliseconds, drawn from this range.
+    pub delay_ms: (f64, f64),
+    /// Each note's copies are drawn from this seed and the note's start.
+    pub seed: u64,
+    /// Share of the chorused signal against the dry note, 0-1.
+    pub mix: f64,
+    /// Gain on chorused notes, in dB.
+    pub gain_db: f64,
+}
+
+impl Default for ChorusConfig {
+    fn default() -> Self {
+        Self {
+            voices: 2,
+            detune_cents: (10.0, 15.0),
+            delay_ms: (15.0, 30.0),
+            seed: 42,
+            mix: 1.0,
+            gain_db: 0.0,
+        }
+    }
+}
+
+impl ChorusConfig {
+    /// Parse a range like "10-15", or a single value like "12".
+    pub fn parse_range(s: &str) -> Result<(f64, f64)> {
+        let s = s.trim();
+        let (lo, hi) = match s.split_once('-') {
+            Some((a, b)) => (a.trim(), b.trim()),
+            None => (s, s),
+        };
+        let parse = |v: &str| {
+            v.parse::<f64>()
+                .ok()
+                .filter(|x| x.is_finite() && *x >= 0.0)
+                .with_context(|| format!("Invalid range '{}': expected MIN-MAX or a single value", s))
+        };
+        let (lo, hi) = (parse(lo)?, parse(hi)?);
+        if lo > hi {
+            bail!("Invalid range '{}': {} is above {}", s, lo, hi);
+        }
+        Ok((lo, hi))
+    }
+}
+
+/// Pitch wobble on held syllables.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VibratoConfig {
+    /// Peak pitch swing in cents (0 = off).
+    pub depth_cents: f64,
+    /// Wobbles per second.
+    pub rate_hz: f64,
+    /// Time into the syllable before the vibrato starts fading in, in ms.
+    pub onset_ms: f64,
+    /// Share of the wobbling signal against the steady one, 0-1. Below 1
+    /// the two beat against each other like a light chorus.
+    pub mix: f64,
+    /// Gain on syllables with vibrato, in dB.
+    pub gain_db: f64,
+}
+
+impl Default for VibratoConfig {
+    fn default() -> Self {
+        Self {
+            depth_cents: 50.0,
+            rate_hz: 5.5,
+            onset_ms: 300.0,
+            mix: 1.0,
+            gain_db: 0.0,
+        }
+    }
+}
+
+/// How long vibrato takes to reach full depth after its onset (ms).
+const VIBRATO_FADE_MS: f64 = 200.0;
+
+/// What a note does in the gap before the next one.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ReleaseConfig {
+    /// Fade-out tail past the end of a note before a rest, in ms (0 =
+    /// cut off at the note's end). Never runs into the next note.
+    pub release_ms: f64,
+    /// Gaps up to this long (ms) are filled by holding the note into the
+    /// next one instead of leaving silence (0 = never).
+    pub legato_gap_ms: f64,
+}
+
+/// How far each note rings past its end, in seconds, and whether that
+/// extension fades out (a release) or holds full level (a sustain).
+fn note_tails(mappings: &[NoteMapping], release: &ReleaseConfig) -> Vec<(f64, bool)> {
+    mappings
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let gap = mappings.get(i + 1).map_or(f64::INFINITY, |next| next.note_start - m.note_end);
+            if gap <= 0.0 {
+                (0.0, false)
+            } else if gap <= release.legato_gap_ms / 1000.0 {
+                (gap, false)
+            } else {
+                ((release.release_ms / 1000.0).min(gap), true)
+            }
+        })
+        .collect()
+}
+
+/// Fade the last `tail_s` seconds of `samples` out to silence.
+fn apply_release_fade(samples: &mut [f64], sr: u32, tail_s: f64) {
+    let n = ((tail_s * sr as f64).round() as usize).min(samples.len());
+    let start = samples.len() - n;
+    for (i, s) in samples[start..].iter_mut().enumerate() {
+        *s *= 1.0 - (i + 1) as f64 / n as f64;
+    }
+}
+
+/// Loop mode names accepted by `LoopMode::parse`.
+pub const LOOP_MODES: &[&str] = &["loop", "truncate", "once"];
+
+/// How the melody is fitted to the target duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopMode {
+    /// Repeat the melody until the target, cutting the last pass.
+    #[default]
+    Loop,
+    /// Play the melody once, cut at the target if it runs longer.
+    Truncate,
+    /// Play the melody once and ignore the target.
+    Once,
+}
+
+impl LoopMode {
+    /// Parse a mode name (case-insensitive): "loop", "truncate", or "once".
+    pub fn parse(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "loop" => Ok(Self::Loop),
+            "truncate" => Ok(Self::Truncate),
+            "once" => Ok(Self::Once),
+            other => anyhow::bail!(
+                "Unknown loop mode '{}' (expected one of: {})",
+                other,
+                LOOP_MODES.join(", ")
+            ),
+        }
+    }
+
+    /// Whether fitting a melody of length `period` to `target_duration`
+    /// cuts it off, so the output needs a fade-out.
+    pub fn cuts_melody(self, period: f64, target_duration: f64) -> bool {
+        match self {
+            Self::Loop => true,
+            Self::Truncate => target_duration < period,
+            Self::Once => false,
+        }
+    }
+}
+
+/// Fit a melody to `target_duration` seconds.
+///
+/// `period` is the melody's length (normally `MidiTrack::total_duration`);
+/// each loop pass is offset by it. Notes starting at or after the target
+/// are dropped and notes crossing it are shortened. Looped notes are new
+/// notes, so `plan_note_mapping` keeps cycling the syllable pool and each
+/// pass sings different syllables.
+pub fn arrange_melody(notes: &[Note], period: f64, target_duration: f64, mode: LoopMode) -> Vec<Note> {
+    let passes = match mode {
+        LoopMode::Once => return notes.to_vec(),
+        LoopMode::Truncate => 1,
+        LoopMode::Loop if period > 0.0 => (target_duration / period).ceil().max(1.0) as usize,
+        LoopMode::Loop => 1,
+    };
+
+    let mut arranged = Vec::new();
+    for pass in 0..passes {
+        let offset = pass as f64 * period;
+        for note in notes {
+            let start = note.start + offset;
+            if start >= target_duration {
+                continue;
+            }
+            arranged.push(Note {
+                start,
+                end: (note.end + offset).min(target_duration),
+                ..note.clone()
+            });
+        }
+    }
+    arranged
+}
+
+/// Fit a whole track (e.g. MIDI backing) with `arrange_melody`, using the
+/// melody's `period` so every part loops in step.
+pub fn arrange_track(track: &MidiTrack, period: f64, target_duration: f64, mode: LoopMode) -> MidiTrack {
+    let notes = arrange_melody(&track.notes, period, target_duration, mode);
+    let total_duration = match mode {
+        LoopMode::Once => track.total_duration,
+        _ => notes.iter().map(|n| n.end).fold(0.0, f64::max),
+    };
+    MidiTrack {
+        notes,
+        total_duration,
+        ..track.clone()
+    }
+}
+
+/// Melody notes pinned to words of the source audio, from a TOML file:
+///
+/// ```toml
+/// [notes]
+/// 1 = "glottis"      # note 1 sings the whole word
+/// 9 = "glottis:2"    # note 9 sings only its second syllable
+/// ```
+///
+/// Notes are numbered from 1 within one pass of the melody, so a looped
+/// melody sings its pins on every pass.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NotePins {
+    /// Pinned word (with optional `:N` syllable) by 0-based note index.
+    pub notes: BTreeMap<usize, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NotePinsFile {
+    #[serde(default)]
+    notes: HashMap<String, String>,
+}
+
+/// Lowercase a word and drop punctuation, for matching pins to transcripts.
+fn pin_key(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric() || *c == '\'')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+impl NotePins {
+    /// Load a TOML pin file.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read note pins: {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid note pins: {}", path.display()))
+    }
+
+    /// Parse a TOML pin file.
+    pub fn parse(text: &str) -> Result<Self> {
+        let file: NotePinsFile = toml::from_str(text)?;
+        let mut notes = BTreeMap::new();
+        for (key, word) in file.notes {
+            let note: usize = key
+                .parse()
+                .ok()
+                .filter(|&n| n >= 1)
+                .with_context(|| format!("Pinned note must be a note number from 1, got '{}'", key))?;
+            notes.insert(note - 1, word);
+        }
+        Ok(Self { notes })
+    }
+
+    /// Point pinned notes' mappings at their words in `pool`, leaving the
+    /// rest as planned. Repeated pins of a word cycle through its takes.
+    /// Fails if a pin is past the `notes_per_pass` melody notes or its
+    /// word isn't in the pool.
+    pub fn apply(&self, mappings: &mut [NoteMapping], notes_per_pass: usize, pool: &[NormalizedSyllable]) -> Result<()> {
+        if self.notes.is_empty() {
+            return Ok(());
+        }
+        if let Some((&last, _)) = self.notes.last_key_value() {
+            if last >= notes_per_pass {
+                bail!("Pinned note {} is past the melody's {} notes", last + 1, notes_per_pass);
+            }
+        }
+
+        // Takes of each word: runs of syllables sharing a transcript word
+        let mut takes: HashMap<String, Vec<Vec<usize>>> = HashMap::new();
+        let mut i = 0;
+        while i < pool.len() {
+            let mut end = i + 1;
+            while end < pool.len() && pool[end].word_index == pool[i].word_index && pool[end].word == pool[i].word {
+                end += 1;
+            }
+            takes.entry(pin_key(&pool[i].word)).or_default().push((i..end).collect());
+            i = end;
+        }
+
+        let mut choices: HashMap<usize, (String, Option<usize>)> = HashMap::new();
+        for (&note, pin) in &self.notes {
+            let (word, syllable) = match pin.rsplit_once(':') {
+                Some((word, n)) => match n.trim().parse::<usize>() {
+                    Ok(n) if n >= 1 => (word, Some(n - 1)),
+                    _ => bail!("Pin '{}' (note {}): syllable must be a number from 1", pin, note + 1),
+                },
+                None => (pin.as_str(), None),
+            };
+            let key = pin_key(word);
+            let word_takes = takes
+                .get(&key)
+                .with_context(|| format!("Pinned word '{}' (note {}) is not in the source audio", word, note + 1))?;
+            if let Some(n) = syllable {
+                if word_takes.iter().all(|t| n >= t.len()) {
+                    bail!("Pinned word '{}' (note {}) has no syllable {}", word, note + 1, n + 1);
+                }
+            }
+            choices.insert(note, (key, syllable));
+        }
+
+        let mut used: HashMap<&str, usize> = HashMap::new();
+        for (i, mapping) in mappings.iter_mut().enumerate() {
+            let Some((key, syllable)) = choices.get(&(i % notes_per_pass)) else {
+                continue;
+            };
+            // Only takes long enough for the chosen syllable
+            let eligible: Vec<&Vec<usize>> = takes[key]
+                .iter()
+                .filter(|t| syllable.is_none_or(|n| n < t.len()))
+                .collect();
+            let count = used.entry(key.as_str()).or_default();
+            let take = eligible[*count % eligible.len()];
+            *count += 1;
+            mapping.syllable_indices = match *syllable {
+                Some(n) => vec![take[n]],
+                None => take.clone(),
+            };
+        }
+        Ok(())
+    }
+}
+
+/// How melody notes are mapped to syllables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SingConfig {
+    /// Max random pitch drift from the melody, in semitones.
+    pub drift_range: f64,
+    /// Chance of chorus on notes too short to get it anyway.
+    pub chorus_probability: f64,
+    /// Notes shorter than this (seconds) are short.
+    pub short_note_s: f64,
+    /// Notes at least this long (seconds) are long.
+    pub long_note_s: f64,
+    /// Syllable counts a short note draws from, uniformly. Repeat a count
+    /// to make it more likely.
+    pub short_syllables: Vec<usize>,
+    /// Syllable counts a medium note draws from.
+    pub medium_syllables: Vec<usize>,
+    /// Syllable counts a long note draws from.
+    pub long_syllables: Vec<usize>,
+}
+
+impl Default for SingConfig {
+    fn default() -> Self {
+        Self {
+            drift_range: 2.0,
+            chorus_probability: 0.3,
+            short_note_s: 0.2,
+            long_note_s: 1.0,
+            short_syllables: vec![1],
+            medium_syllables: vec![1, 1, 1, 2, 2, 3],
+            long_syllables: vec![1, 2, 2, 3, 3, 4],
+        }
+    }
+}
+
+impl SingConfig {
+    /// Classify a note by duration.
+    pub fn classify(&self, duration: f64) -> DurationClass {
+        if duration < self.short_note_s {
+            DurationClass::Short
+        } else if duration < self.long_note_s {
+            DurationClass::Medium
+        } else {
+            DurationClass::Long
+        }
+    }
+
+    /// Parse a syllable count table like "1,1,2,3".
+    pub fn parse_counts(s: &str) -> Result<Vec<usize>> {
+        let counts = s
+            .split(',')
+            .map(|c| {
+                c.trim()
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .with_context(|| format!("Invalid syllable count '{}' in '{}'", c.trim(), s))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(counts)
+    }
+
+    /// Check the thresholds and tables.
+    pub fn validate(&self) -> Result<()> {
+        if !(self.short_note_s >= 0.0 && self.short_note_s <= self.long_note_s) {
+            bail!(
+                "Short note limit {}s must not exceed the long note limit {}s",
+                self.short_note_s,
+                self.long_note_s
+            );
+        }
+        for (name, table) in [
+            ("short", &self.short_syllables),
+            ("medium", &self.medium_syllables),
+            ("long", &self.long_syllables),
+        ] {
+            if table.is_empty() || table.contains(&0) {
+                bail!("The {} note syllable counts must be one or more", name);
+            }
+        }
+        Ok(())
+    }
+}
+
 /// Classify a note by duration for mapping strategy.
 pub fn classify_note_duration(duration: f64) -> DurationClass {
-    if duration < 0.2 {
-        DurationClass::Short
-    } else if duration < 1.0 {
-        DurationClass::Medium
-    } else {
-        DurationClass::Long
-    }
+    SingConfig::default().classify(duration)
 }
 
 /// Compute semitone shift from source F0 to target MIDI note (with optional drift).
@@ -55,6 +472,21 @@ pub fn plan_note_mapping(
     seed: Option<u64>,
     drift_range: f64,
     chorus_probability: f64,
+) -> Vec<NoteMapping> {
+    let config = SingConfig {
+        drift_range,
+        chorus_probability,
+        ..SingConfig::default()
+    };
+    plan_note_mapping_with(notes, pool_size, seed, &config)
+}
+
+/// [`plan_note_mapping`] with custom duration classes and syllable counts.
+pub fn plan_note_mapping_with(
+    notes: &[Note],
+    pool_size: usize,
+    seed: Option<u64>,
+    config: &SingConfig,
 ) -> Vec<NoteMapping> {
     let mut rng = match seed {
         Some(s) => StdRng::seed_from_u64(s),
@@ -63,20 +495,22 @@ pub fn plan_note_mapping(
 
     let mut mappings = Vec::new();
     let mut syl_cursor = 0usize;
-
-    let short_choices = [1usize];
-    let medium_choices = [1, 1, 1, 2, 2, 3];
-    let long_choices = [1, 2, 2, 3, 3, 4];
+    let drift_range = config.drift_range;
 
     for note in notes {
         let duration = note.duration();
-        let dur_class = classify_note_duration(duration);
+        let dur_class = config.classify(duration);
 
         // Determine how many syllables this note gets
-        let n_syls = match dur_class {
-            DurationClass::Short => short_choices[rng.gen_range(0..short_choices.len())],
-            DurationClass::Medium => medium_choices[rng.gen_range(0..medium_choices.len())],
-            DurationClass::Long => long_choices[rng.gen_range(0..long_choices.len())],
+        let choices = match dur_class {
+            DurationClass::Short => &config.short_syllables,
+            DurationClass::Medium => &config.medium_syllables,
+            DurationClass::Long => &config.long_syllables,
+        };
+        let n_syls = if choices.is_empty() {
+            1
+        } else {
+            choices[rng.gen_range(0..choices.len())].max(1)
         };
 
         // Assign syllable indices (cycle through pool)
@@ -96,7 +530,7 @@ pub fn plan_note_mapping(
             || (dur_class == DurationClass::Medium && duration > 0.6);
 
         // Chorus on sustained notes or random chance
-        let apply_chorus = duration > 0.6 || rng.gen::<f64>() < chorus_probability;
+        let apply_chorus = duration > 0.6 || rng.gen::<f64>() < config.chorus_probability;
 
         mappings.push(NoteMapping {
             note_pitch: note.pitch,
@@ -115,12 +549,17 @@ pub fn plan_note_mapping(
     mappings
 }
 
-/// Apply vibrato effect (pitch modulation) to audio samples.
-fn apply_vibrato_effect(samples: &[f64], sr: u32, depth_cents: f64, rate_hz: f64) -> Vec<f64> {
+/// Apply vibrato effect (pitch modulation) to audio samples. The depth
+/// is zero until `onset_ms`, then fades in over `VIBRATO_FADE_MS`.
+fn apply_vibrato_effect(samples: &[f64], sr: u32, vibrato: &VibratoConfig) -> Vec<f64> {
+    let onset_s = vibrato.onset_ms.max(0.0) / 1000.0;
+    let fade_s = VIBRATO_FADE_MS / 1000.0;
     let mut output = Vec::with_capacity(samples.len());
     for i in 0..samples.len() {
         let t = i as f64 / sr as f64;
-        let mod_factor = (2.0f64).powf(depth_cents / 1200.0 * (2.0 * std::f64::consts::PI * rate_hz * t).sin());
+        let depth = vibrato.depth_cents * ((t - onset_s) / fade_s).clamp(0.0, 1.0);
+        let mod_factor =
+            (2.0f64).powf(depth / 1200.0 * (2.0 * std::f64::consts::PI * vibrato.rate_hz * t).sin());
         // Simple pitch modulation by resampling
         let src_idx = i as f64 * mod_factor;
         let idx = src_idx as usize;
@@ -131,17 +570,25 @@ fn apply_vibrato_effect(samples: &[f64], sr: u32, depth_cents: f64, rate_hz: f64
             output.push(samples[idx]);
         }
     }
-    output
+    blend_wet_dry(samples, output, vibrato.mix, vibrato.gain_db)
+}
+
+/// Draw from `lo..=hi`, or `lo` when the range is empty.
+fn gen_in(rng: &mut StdRng, (lo, hi): (f64, f64)) -> f64 {
+    if hi > lo {
+        rng.gen_range(lo..=hi)
+    } else {
+        lo
+    }
 }
 
 /// Apply chorus effect by layering detuned copies.
-fn apply_chorus_effect(samples: &[f64], sr: u32, n_voices: usize) -> Vec<f64> {
-    let mut rng = StdRng::seed_from_u64(42);
+fn apply_chorus_effect(samples: &[f64], sr: u32, chorus: &ChorusConfig, rng: &mut StdRng) -> Vec<f64> {
     let mut result = samples.to_vec();
 
-    for _ in 0..n_voices {
-        let detune_cents = rng.gen_range(10.0..15.0) * if rng.gen::<bool>() { 1.0 } else { -1.0 };
-        let delay_samples = (rng.gen_range(15.0..30.0) / 1000.0 * sr as f64).round() as usize;
+    for _ in 0..chorus.voices {
+        let detune_cents = gen_in(rng, chorus.detune_cents) * if rng.gen::<bool>() { 1.0 } else { -1.0 };
+        let delay_samples = (gen_in(rng, chorus.delay_ms) / 1000.0 * sr as f64).round() as usize;
 
         // Detune by resampling
         let ratio = (2.0f64).powf(detune_cents / 1200.0);
@@ -175,7 +622,7 @@ fn apply_chorus_effect(samples: &[f64], sr: u32, n_voices: usize) -> Vec<f64> {
         }
     }
 
-    result
+    blend_wet_dry(samples, result, chorus.mix, chorus.gain_db)
 }
 
 /// Render a single note mapping to audio samples.
@@ -185,6 +632,27 @@ pub fn render_mapping(
     median_f0: f64,
     max_shift: f64,
     sr: u32,
+) -> Option<Vec<f64>> {
+    render_mapping_with(
+        mapping,
+        syllable_clips,
+        median_f0,
+        max_shift,
+        sr,
+        &ChorusConfig::default(),
+        &VibratoConfig::default(),
+    )
+}
+
+/// [`render_mapping`] with chorus and vibrato settings.
+pub fn render_mapping_with(
+    mapping: &NoteMapping,
+    syllable_clips: &[NormalizedSyllable],
+    median_f0: f64,
+    max_shift: f64,
+    sr: u32,
+    chorus: &ChorusConfig,
+    vibrato: &VibratoConfig,
 ) -> Option<Vec<f64>> {
     let target_duration = mapping.note_duration;
     let n_syls = mapping.syllable_indices.len();
@@ -245,8 +713,8 @@ pub fn render_mapping(
         }
 
         // Apply vibrato if flagged
-        if mapping.apply_vibrato && syl_dur > 0.3 {
-            part = apply_vibrato_effect(&part, sr, 50.0, 5.5);
+        if mapping.apply_vibrato && syl_dur > 0.3 && vibrato.depth_cents > 0.0 {
+            part = apply_vibrato_effect(&part, sr, vibrato);
         }
 
         if !part.is_empty() {
@@ -268,23 +736,53 @@ pub fn render_mapping(
 
     // Apply chorus if flagged
     if mapping.apply_chorus {
-        result = apply_chorus_effect(&result, sr, 2);
+        let mut chorus_rng = StdRng::seed_from_u64(chorus.seed ^ mapping.note_start.to_bits());
+        result = apply_chorus_effect(&result, sr, chorus, &mut chorus_rng);
     }
 
     Some(result)
 }
 
-/// Render all mappings into a complete vocal track.
+/// Render all mappings into a complete vocal track. The track starts at
+/// time zero, with silence before the first note, like the backing.
 pub fn render_vocal_track(
     mappings: &[NoteMapping],
     syllable_clips: &[NormalizedSyllable],
     median_f0: f64,
     sr: u32,
+) -> Vec<f64> {
+    render_vocal_track_with(
+        mappings,
+        syllable_clips,
+        median_f0,
+        sr,
+        &ChorusConfig::default(),
+        &VibratoConfig::default(),
+        &ReleaseConfig::default(),
+    )
+}
+
+/// [`render_vocal_track`] with chorus, vibrato, and release settings.
+pub fn render_vocal_track_with(
+    mappings: &[NoteMapping],
+    syllable_clips: &[NormalizedSyllable],
+    median_f0: f64,
+    sr: u32,
+    chorus: &ChorusConfig,
+    vibrato: &VibratoConfig,
+    release: &ReleaseConfig,
 ) -> Vec<f64> {
     let mut rendered_notes: Vec<(f64, f64, Vec<f64>)> = Vec::new(); // (start, end, samples)
 
-    for mapping in mappings {
-        if let Some(rendered) = render_mapping(mapping, syllable_clips, median_f0, 12.0, sr) {
+    for (mapping, (tail, fade)) in mappings.iter().zip(note_tails(mappings, release)) {
+        // A ringing note is sung longer, so its syllables stretch over the tail
+        let mut mapping = mapping.clone();
+        mapping.note_end += tail;
+        mapping.note_duration += tail;
+        if let Some(mut rendered) = render_mapping_with(&mapping, syllable_clips, median_f0, 12.0, sr, chorus, vibrato) {
+            if fade {
+                apply_release_fade(&mut rendered, sr, tail);
+            }
             rendered_notes.push((mapping.note_start, mapping.note_end, rendered));
         }
     }
@@ -298,13 +796,12 @@ pub fn render_vocal_track(
     let crossfade = (30.0 / 1000.0 * sr as f64).round() as usize;
 
     for (idx, (start, _end, samples)) in rendered_notes.iter().enumerate() {
-        if idx > 0 {
-            let prev_end = rendered_notes[idx - 1].1;
-            let gap_duration = start - prev_end;
-            if gap_duration > 0.01 {
-                let gap = generate_silence(gap_duration * 1000.0, sr);
-                parts.push(gap);
-            }
+        // Lead in with silence up to the first note, so the track lines up with the backing
+        let prev_end = if idx > 0 { rendered_notes[idx - 1].1 } else { 0.0 };
+        let gap_duration = start - prev_end;
+        if gap_duration > 0.01 {
+            let gap = generate_silence(gap_duration * 1000.0, sr);
+            parts.push(gap);
         }
         parts.push(samples.clone());
     }
@@ -320,6 +817,56 @@ pub fn render_vocal_track(
 mod tests {
     use super::*;
 
+    fn mapping_at(start: f64, end: f64) -> NoteMapping {
+        NoteMapping {
+            note_pitch: 60,
+            note_start: start,
+            note_end: end,
+            note_duration: end - start,
+            syllable_indices: vec![0],
+            pitch_shift_semitones: 0.0,
+            time_stretch_ratio: 1.0,
+            apply_vibrato: false,
+            apply_chorus: false,
+            duration_class: DurationClass::Medium,
+        }
+    }
+
+    #[test]
+    fn test_note_tails() {
+        let mappings = vec![
+            mapping_at(0.0, 0.5),
+            mapping_at(0.55, 1.0), // 50 ms gap
+            mapping_at(1.0, 1.5),  // no gap
+            mapping_at(1.6, 2.0),  // 100 ms gap
+            mapping_at(3.0, 3.5),  // 1 s gap, then the end
+        ];
+        let off = note_tails(&mappings, &ReleaseConfig::default());
+        assert!(off.iter().all(|&(tail, _)| tail == 0.0));
+
+        let release = ReleaseConfig { release_ms: 150.0, legato_gap_ms: 60.0 };
+        let tails = note_tails(&mappings, &release);
+        assert!((tails[0].0 - 0.05).abs() < 1e-9 && !tails[0].1, "short gap is sustained");
+        assert_eq!(tails[1], (0.0, false));
+        assert!((tails[2].0 - 0.1).abs() < 1e-9 && tails[2].1, "release stops at the next note");
+        assert!((tails[3].0 - 0.15).abs() < 1e-9 && tails[3].1);
+        assert!((tails[4].0 - 0.15).abs() < 1e-9 && tails[4].1);
+    }
+
+    #[test]
+    fn test_apply_release_fade() {
+        let mut samples = vec![1.0; 100];
+        apply_release_fade(&mut samples, 1000, 0.02);
+        assert_eq!(samples[79], 1.0);
+        assert!(samples[80] < 1.0 && samples[80] > 0.9);
+        assert_eq!(samples[99], 0.0);
+        assert!(samples[80..].windows(2).all(|w| w[1] < w[0]));
+
+        let mut short = vec![1.0; 5];
+        apply_release_fade(&mut short, 1000, 1.0);
+        assert_eq!(short[4], 0.0);
+    }
+
     #[test]
     fn test_classify_note_duration() {
         assert_eq!(classify_note_duration(0.1), DurationClass::Short);
@@ -362,6 +909,36 @@ mod tests {
         assert_eq!(mappings[1].note_pitch, 64);
     }
 
+    #[test]
+    fn test_plan_note_mapping_with_custom_classes() {
+        let notes = vec![
+            Note { pitch: 60, start: 0.0, end: 0.3, velocity: 100 },
+            Note { pitch: 62, start: 0.3, end: 0.9, velocity: 100 },
+            Note { pitch: 64, start: 0.9, end: 3.0, velocity: 100 },
+        ];
+        let config = SingConfig {
+            short_note_s: 0.5,
+            long_note_s: 2.0,
+            short_syllables: vec![1],
+            medium_syllables: vec![2],
+            long_syllables: vec![5],
+            ..SingConfig::default()
+        };
+        assert_eq!(config.classify(0.3), DurationClass::Short);
+        assert_eq!(config.classify(0.6), DurationClass::Medium);
+        assert_eq!(config.classify(2.0), DurationClass::Long);
+        let mappings = plan_note_mapping_with(&notes, 20, Some(3), &config);
+        let counts: Vec<usize> = mappings.iter().map(|m| m.syllable_indices.len()).collect();
+        assert_eq!(counts, vec![1, 2, 5]);
+
+        assert_eq!(SingConfig::parse_counts("1, 2,2,4").unwrap(), vec![1, 2, 2, 4]);
+        assert!(SingConfig::parse_counts("1,0").is_err());
+        assert!(SingConfig::parse_counts("").is_err());
+        assert!(SingConfig { short_note_s: 1.5, ..SingConfig::default() }.validate().is_err());
+        assert!(SingConfig { long_syllables: vec![], ..SingConfig::default() }.validate().is_err());
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn test_plan_note_mapping_deterministic() {
         let notes = vec![
@@ -373,14 +950,114 @@ mod tests {
         assert_eq!(a[0].pitch_shift_semitones, b[0].pitch_shift_semitones);
     }
 
+    #[test]
+    fn test_arrange_melody_modes() {
+        let notes = vec![
+            Note { pitch: 60, start: 0.0, end: 1.0, velocity: 100 },
+            Note { pitch: 64, start: 1.5, end: 2.5, velocity: 80 },
+        ];
+        // Loop: passes every 3s, last note cut at the target
+        let looped = arrange_melody(&notes, 3.0, 8.0, LoopMode::Loop);
+        let starts: Vec<f64> = looped.iter().map(|n| n.start).collect();
+        assert_eq!(starts, vec![0.0, 1.5, 3.0, 4.5, 6.0, 7.5]);
+        assert_eq!(looped.last().unwrap().end, 8.0);
+
+        let truncated = arrange_melody(&notes, 3.0, 2.0, LoopMode::Truncate);
+        assert_eq!(truncated.len(), 2);
+        assert_eq!(truncated[1].end, 2.0);
+        assert_eq!(arrange_melody(&notes, 3.0, 8.0, LoopMode::Truncate).len(), 2);
+
+        let once = arrange_melody(&notes, 3.0, 1.0, LoopMode::Once);
+        assert_eq!(once[1].end, 2.5);
+
+        let track = MidiTrack { notes, tempo: 120.0, program: 0, is_drum: false, total_duration: 3.0 };
+        let looped = arrange_track(&track, 3.0, 7.0, LoopMode::Loop);
+        assert_eq!(looped.notes.len(), 5);
+        assert_eq!(looped.total_duration, 7.0);
+        assert!(LoopMode::Loop.cuts_melody(3.0, 6.0));
+        assert!(!LoopMode::Truncate.cuts_melody(3.0, 6.0));
+    }
+
+    #[test]
+    fn test_looped_passes_use_fresh_syllables() {
+        let notes = vec![Note { pitch: 60, start: 0.0, end: 0.1, velocity: 100 }];
+        let looped = arrange_melody(&notes, 0.5, 1.5, LoopMode::Loop);
+        let mappings = plan_note_mapping(&looped, 10, Some(42), 2.0, 0.0);
+        let used: Vec<usize> = mappings.iter().flat_map(|m| m.syllable_indices.clone()).collect();
+        assert_eq!(used, vec![0, 1, 2]);
+    }
+
+    fn pool(words: &[(&str, usize)]) -> Vec<NormalizedSyllable> {
+        words
+            .iter()
+            .map(|&(word, word_index)| NormalizedSyllable {
+                samples: vec![0.0; 160],
+                sr: 16000,
+                f0: None,
+                duration: 0.01,
+                phonemes: vec![],
+                word: word.to_string(),
+                word_index,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_note_pins() {
+        let pins = NotePins::parse("[notes]\n1 = \"Glottis!\"\n3 = \"glottis:2\"\n").unwrap();
+        assert_eq!(pins.notes.get(&0).map(String::as_str), Some("Glottis!"));
+        // Two takes of "glottis" (two syllables each) around other words
+        let pool = pool(&[
+            ("glottis", 0), ("glottis", 0), ("the", 1), ("glottis.", 2), ("glottis.", 2), ("dale", 3),
+        ]);
+        let notes: Vec<Note> = (0..6)
+            .map(|i| Note { pitch: 60, start: i as f64, end: i as f64 + 0.5, velocity: 100 })
+            .collect();
+        let mut mappings = plan_note_mapping(&notes, pool.len(), Some(1), 0.0, 0.0);
+        let auto: Vec<Vec<usize>> = mappings.iter().map(|m| m.syllable_indices.clone()).collect();
+        // Melody of 3 notes looped twice: pins land on both passes, and
+        // each use of "glottis" takes the next take
+        pins.apply(&mut mappings, 3, &pool).unwrap();
+        assert_eq!(mappings[0].syllable_indices, vec![0, 1]);
+        assert_eq!(mappings[2].syllable_indices, vec![4]);
+        assert_eq!(mappings[3].syllable_indices, vec![0, 1]);
+        assert_eq!(mappings[5].syllable_indices, vec![4]);
+        assert_eq!(mappings[1].syllable_indices, auto[1]);
+        assert_eq!(mappings[4].syllable_indices, auto[4]);
+
+        assert!(NotePins::parse("[notes]\n0 = \"x\"").is_err());
+        assert!(NotePins::parse("[pins]\n1 = \"x\"").is_err());
+        let missing = NotePins::parse("[notes]\n1 = \"larynx\"").unwrap();
+        assert!(missing.apply(&mut mappings, 3, &pool).is_err());
+        let past = NotePins::parse("[notes]\n4 = \"dale\"").unwrap();
+        assert!(past.apply(&mut mappings, 3, &pool).is_err());
+        let no_syllable = NotePins::parse("[notes]\n1 = \"dale:2\"").unwrap();
+        assert!(no_syllable.apply(&mut mappings, 3, &pool).is_err());
+    }
+
+    #[test]
+    fn test_loop_mode_parse() {
+        assert_eq!(LoopMode::parse("Truncate").unwrap(), LoopMode::Truncate);
+        assert_eq!(LoopMode::default(), LoopMode::Loop);
+        assert!(LoopMode::parse("forever").is_err());
+    }
+
     #[test]
     fn test_apply_vibrato_effect() {
         let sr = 16000u32;
         let samples: Vec<f64> = (0..sr as usize).map(|i| {
             (2.0 * std::f64::consts::PI * 440.0 * i as f64 / sr as f64).sin()
         }).collect();
-        let result = apply_vibrato_effect(&samples, sr, 50.0, 5.5);
+        let result = apply_vibrato_effect(&samples, sr, &VibratoConfig::default());
         assert!(!result.is_empty());
+
+        // Untouched until the onset, then wobbling
+        let onset = (0.3 * sr as f64) as usize;
+        assert_eq!(result[..onset], samples[..onset]);
+        assert!(result[onset + 4000..].iter().zip(&samples[onset + 4000..]).any(|(a, b)| (a - b).abs() > 1e-3));
+        let immediate = VibratoConfig { onset_ms: 0.0, ..VibratoConfig::default() };
+        let early = apply_vibrato_effect(&samples, sr, &immediate);
+        assert!(early[..onset].iter().zip(&samples[..onset]).any(|(a, b)| (a - b).abs() > 1e-3));
     }
 
     #[test]
@@ -389,7 +1066,40 @@ mod tests {
         let samples: Vec<f64> = (0..sr as usize).map(|i| {
             (2.0 * std::f64::consts::PI * 440.0 * i as f64 / sr as f64).sin()
         }).collect();
-        let result = apply_chorus_effect(&samples, sr, 2);
+        let mut rng = StdRng::seed_from_u64(7);
+        let result = apply_chorus_effect(&samples, sr, &ChorusConfig::default(), &mut rng);
         assert!(!result.is_empty());
+
+        // No voices leaves the note dry; the same seed gives the same copies
+        let dry = ChorusConfig { voices: 0, ..ChorusConfig::default() };
+        assert_eq!(apply_chorus_effect(&samples, sr, &dry, &mut rng), samples);
+        let wide = ChorusConfig {
+            voices: 4,
+            detune_cents: (20.0, 30.0),
+            delay_ms: (40.0, 40.0),
+            seed: 1,
+            ..ChorusConfig::default()
+        };
+        let a = apply_chorus_effect(&samples, sr, &wide, &mut StdRng::seed_from_u64(3));
+        let b = apply_chorus_effect(&samples, sr, &wide, &mut StdRng::seed_from_u64(3));
+        assert_eq!(a, b);
+        assert_ne!(a, result);
+
+        // Half wet sits halfway between the dry note and the full chorus
+        let half = ChorusConfig { mix: 0.5, ..wide };
+        let c = apply_chorus_effect(&samples, sr, &half, &mut StdRng::seed_from_u64(3));
+        assert!(c.iter().zip(&a).zip(&samples).all(|((c, a), d)| (c - (a + d) / 2.0).abs() < 1e-9));
+        let muted = ChorusConfig { mix: 0.0, gain_db: -6.0, ..wide };
+        let m = apply_chorus_effect(&samples, sr, &muted, &mut StdRng::seed_from_u64(3));
+        assert!((m[100] - samples[100] * 10f64.powf(-0.3)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_chorus_parse_range() {
+        assert_eq!(ChorusConfig::parse_range("10-15").unwrap(), (10.0, 15.0));
+        assert_eq!(ChorusConfig::parse_range(" 20 ").unwrap(), (20.0, 20.0));
+        assert!(ChorusConfig::parse_range("15-10").is_err());
+        assert!(ChorusConfig::parse_range("-5").is_err());
+        assert!(ChorusConfig::parse_range("lots").is_err());
     }
 }
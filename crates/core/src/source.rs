@@ -0,0 +1,272 @@
+//! Interned source file keys.
+//!
+//! Clips and bank entries refer to their source recording by [`SourceId`],
+//! a small copyable key, rather than each holding its own copy of the path.
+//! A process-wide registry maps ids to paths, so pointing a source at a new
+//! location (after the file moved) updates everything that refers to it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Key of a source audio file in the process-wide registry.
+///
+/// Serializes as the source's path, so JSON output stays readable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SourceId(u32);
+
+/// id <-> path tables. Paths are leaked: a session sees a handful of
+/// sources, and leaking lets [`SourceId::path`] hand out `&'static Path`.
+#[derive(Default)]
+struct Registry {
+    paths: Vec<&'static Path>,
+    ids: HashMap<&'static Path, SourceId>,
+}
+
+static REGISTRY: LazyLock<RwLock<Registry>> = LazyLock::new(Default::default);
+
+impl SourceId {
+    /// The id of `path`, registering it on first use.
+    pub fn intern(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        if let Some(id) = Self::lookup(path) {
+            return id;
+        }
+        let mut registry = REGISTRY.write().unwrap();
+        // Another thread may have registered it between the two locks
+        if let Some(&id) = registry.ids.get(path) {
+            return id;
+        }
+        let id = SourceId(registry.paths.len() as u32);
+        let leaked: &'static Path = Box::leak(path.to_path_buf().into_boxed_path());
+        registry.paths.push(leaked);
+        registry.ids.insert(leaked, id);
+        id
+    }
+
+    /// The id of `path`, if it has been registered.
+    pub fn lookup(path: impl AsRef<Path>) -> Option<Self> {
+        REGISTRY.read().unwrap().ids.get(path.as_ref()).copied()
+    }
+
+    /// Where the source currently lives.
+    pub fn path(self) -> &'static Path {
+        REGISTRY.read().unwrap().paths[self.0 as usize]
+    }
+
+    /// Point this source at `new_path`, e.g. after the file moved.
+    ///
+    /// Everything holding the id follows. Interning the old path afterwards
+    /// registers a new source; interning `new_path` returns this id.
+    pub fn relocate(self, new_path: impl AsRef<Path>) {
+        let new_path = new_path.as_ref();
+        let mut registry = REGISTRY.write().unwrap();
+        let old = registry.paths[self.0 as usize];
+        if old == new_path {
+            return;
+        }
+        if registry.ids.get(old) == Some(&self) {
+            registry.ids.remove(old);
+        }
+        let leaked: &'static Path = Box::leak(new_path.to_path_buf().into_boxed_path());
+        registry.paths[self.0 as usize] = leaked;
+        registry.ids.insert(leaked, self);
+    }
+}
+
+impl fmt::Display for SourceId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.path().display().fmt(f)
+    }
+}
+
+impl From<&Path> for SourceId {
+    fn from(path: &Path) -> Self {
+        Self::intern(path)
+    }
+}
+
+impl From<PathBuf> for SourceId {
+    fn from(path: PathBuf) -> Self {
+        Self::intern(path)
+    }
+}
+
+impl From<&str> for SourceId {
+    fn from(path: &str) -> Self {
+        Self::intern(path)
+    }
+}
+
+impl Serialize for SourceId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.path().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for SourceId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        PathBuf::deserialize(deserializer).map(Self::intern)
+    }
+}
+
+/// Ids of the sources keyed by `paths`, sorted by path, as listed in a run
+/// manifest. Clips refer to a source by its index in this table.
+pub fn source_table<'a>(paths: impl IntoIterator<Item = &'a String>) -> Vec<SourceId> {
+    let mut ids: Vec<SourceId> = paths.into_iter().map(SourceId::intern).collect();
+    ids.sort_by_key(|id| id.path());
+    ids
+}
+
+/// `path` relative to `base_dir` if it lies inside it, else `path` as is.
+///
+/// Written into manifests and projects so a run or project folder can be
+/// moved as a whole.
+pub fn relative_to<'a>(path: &'a Path, base_dir: &Path) -> &'a Path {
+    if base_dir.as_os_str().is_empty() {
+        return path;
+    }
+    path.strip_prefix(base_dir).unwrap_or(path)
+}
+
+/// Find a source that is no longer where it was recorded.
+///
+/// Relative paths are taken relative to `base_dir`. An absolute path that
+/// no longer exists falls back to a file of the same name in `base_dir`.
+/// `None` if neither exists.
+pub fn resolve_moved(path: &Path, base_dir: &Path) -> Option<PathBuf> {
+    let recorded = if path.is_absolute() { path.to_path_buf() } else { base_dir.join(path) };
+    if recorded.exists() {
+        return Some(recorded);
+    }
+    path.file_name().map(|name| base_dir.join(name)).filter(|p| p.exists())
+}
+
+/// A moved source: `from` now lives at `to`.
+///
+/// `from` may be a file or a folder; relinking a folder moves every source
+/// below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relink {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl Relink {
+    /// Parse "OLD=NEW".
+    pub fn parse(s: &str) -> anyhow::Result<Self> {
+        match s.split_once('=') {
+            Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => Ok(Self {
+                from: PathBuf::from(from.trim()),
+                to: PathBuf::from(to.trim()),
+            }),
+            _ => anyhow::bail!("Invalid relink '{}' (expected OLD=NEW)", s),
+        }
+    }
+
+    /// Where `path` lives after this relink, if it is `from` or below it.
+    pub fn apply(&self, path: &Path) -> Option<PathBuf> {
+        let rest = path.strip_prefix(&self.from).ok()?;
+        Some(if rest.as_os_str().is_empty() { self.to.clone() } else { self.to.join(rest) })
+    }
+}
+
+/// Point `source` at the first location `relinks` give it.
+///
+/// `recorded` is the path as written in the project or manifest, which
+/// may differ from where the source was looked for. Returns the id to use:
+/// `source` itself after [`SourceId::relocate`], or the id the new path
+/// already had. `None` if no relink applies.
+pub fn relink(source: SourceId, recorded: &Path, relinks: &[Relink]) -> Option<SourceId> {
+    let to = relinks.iter().find_map(|r| r.apply(source.path()).or_else(|| r.apply(recorded)))?;
+    Some(SourceId::lookup(&to).unwrap_or_else(|| {
+        source.relocate(&to);
+        source
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The registry is shared by every test, so each test uses its own paths
+
+    #[test]
+    fn test_intern_is_stable() {
+        let a = SourceId::intern("/interned/a.wav");
+        let b = SourceId::intern("/interned/b.wav");
+        assert_ne!(a, b);
+        assert_eq!(SourceId::intern(PathBuf::from("/interned/a.wav")), a);
+        assert_eq!(a.path(), Path::new("/interned/a.wav"));
+        assert_eq!(a.to_string(), "/interned/a.wav");
+        assert_eq!(SourceId::lookup("/interned/never.wav"), None);
+    }
+
+    #[test]
+    fn test_relocate_moves_every_holder() {
+        let id = SourceId::intern("/old/take.wav");
+        let held = [id, id];
+        id.relocate("/new/take.wav");
+        assert!(held.iter().all(|h| h.path() == Path::new("/new/take.wav")));
+        assert_eq!(SourceId::lookup("/new/take.wav"), Some(id));
+        assert_ne!(SourceId::intern("/old/take.wav"), id);
+    }
+
+    #[test]
+    fn test_serializes_as_path() {
+        let id = SourceId::intern("/serde/x.wav");
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(json, "\"/serde/x.wav\"");
+        assert_eq!(serde_json::from_str::<SourceId>(&json).unwrap(), id);
+    }
+
+    #[test]
+    fn test_source_table_and_relative_paths() {
+        let keys = ["/table/run/work/b.wav".to_string(), "/table/run/work/a.wav".to_string()];
+        let table = source_table(&keys);
+        assert_eq!(table, [SourceId::intern("/table/run/work/a.wav"), SourceId::intern(&keys[0])]);
+
+        let run = Path::new("/table/run");
+        assert_eq!(relative_to(table[0].path(), run), Path::new("work/a.wav"));
+        assert_eq!(relative_to(Path::new("/elsewhere/c.wav"), run), Path::new("/elsewhere/c.wav"));
+        assert_eq!(relative_to(Path::new("c.wav"), Path::new("")), Path::new("c.wav"));
+    }
+
+    #[test]
+    fn test_relink_files_and_folders() {
+        let file = Relink::parse("/old/take.wav = /new/take 2.wav").unwrap();
+        assert_eq!(file.apply(Path::new("/old/take.wav")), Some(PathBuf::from("/new/take 2.wav")));
+        assert_eq!(file.apply(Path::new("/old/other.wav")), None);
+
+        let folder = Relink::parse("/relink/old=/relink/new").unwrap();
+        assert_eq!(folder.apply(Path::new("/relink/old/a/b.wav")), Some(PathBuf::from("/relink/new/a/b.wav")));
+        assert_eq!(folder.apply(Path::new("/relink/older/b.wav")), None);
+        assert!(Relink::parse("no-equals").is_err());
+        assert!(Relink::parse("=/new").is_err());
+
+        let id = SourceId::intern("/relink/old/x.wav");
+        assert_eq!(relink(id, Path::new("x.wav"), std::slice::from_ref(&folder)), Some(id));
+        assert_eq!(id.path(), Path::new("/relink/new/x.wav"));
+        // A path that already has an id keeps it
+        let taken = SourceId::intern("/relink/new/y.wav");
+        let other = SourceId::intern("/relink/old/y.wav");
+        assert_eq!(relink(other, Path::new("y.wav"), &[folder]), Some(taken));
+        assert_eq!(relink(other, Path::new("y.wav"), &[file]), None);
+    }
+
+    #[test]
+    fn test_resolve_moved() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_resolve_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("voice.wav"), b"").unwrap();
+
+        let moved = Path::new("/nowhere/recordings/voice.wav");
+        assert_eq!(resolve_moved(moved, &dir), Some(dir.join("voice.wav")));
+        assert_eq!(resolve_moved(Path::new("voice.wav"), &dir), Some(dir.join("voice.wav")));
+        assert_eq!(resolve_moved(Path::new("/nowhere/gone.wav"), &dir), None);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -7,14 +7,23 @@ use anyhow::Result;
 
 use crate::audio::analysis::{compute_rms, estimate_f0};
 use crate::audio::effects::{
-    adjust_volume, concatenate, concatenate_with_gaps, cut_clip, pitch_shift, time_stretch,
+    adjust_volume, concatenate, concatenate_with_gaps, cut_clip, limit_peaks, pitch_shift, time_stretch,
+    RmsNormalization,
 };
-use crate::audio::io::write_wav;
+use crate::audio::loudness::{record_loudness, Mastering};
 use crate::speak::matcher::MatchResult;
+use crate::speak::target_text::Prosody;
 
 /// Pause durations in seconds.
 const WORD_PAUSE_S: f64 = 0.12;
 
+/// Emphasized syllables are this much longer...
+const EMPHASIS_STRETCH: f64 = 1.2;
+/// ...this much louder (dB)...
+const EMPHASIS_GAIN_DB: f64 = 4.0;
+/// ...and this much higher (semitones).
+const EMPHASIS_PITCH_ST: f64 = 1.5;
+
 /// Timing for a single output syllable.
 #[derive(Debug, Clone)]
 pub struct TimingPlan {
@@ -24,6 +33,10 @@ pub struct TimingPlan {
     pub target_duration: f64,
     /// Time-stretch factor to apply (1.0 = no stretch)
     pub stretch_factor: f64,
+    /// Marked-up silence before the syllable (seconds), included in `target_start`
+    pub pause_before: f64,
+    /// Marked up for emphasis
+    pub emphasis: bool,
 }
 
 /// Plan output timing for matched syllables.
@@ -36,6 +49,27 @@ pub fn plan_timing(
     avg_syllable_dur: f64,
     reference_timings: Option<&[(f64, f64)]>,
     timing_strictness: f64,
+) -> Vec<TimingPlan> {
+    plan_timing_with(
+        matches,
+        word_boundaries,
+        avg_syllable_dur,
+        reference_timings,
+        timing_strictness,
+        &[],
+    )
+}
+
+/// `plan_timing` honoring markup: `prosody[i]` (one per match; missing
+/// entries are plain) adds pauses, scales durations by the rate, and
+/// lengthens emphasized syllables.
+pub fn plan_timing_with(
+    matches: &[MatchResult],
+    word_boundaries: &[usize],
+    avg_syllable_dur: f64,
+    reference_timings: Option<&[(f64, f64)]>,
+    timing_strictness: f64,
+    prosody: &[Prosody],
 ) -> Vec<TimingPlan> {
     let word_starts: HashSet<usize> = word_boundaries.iter().copied().collect();
     let mut plans = Vec::new();
@@ -75,6 +109,13 @@ pub fn plan_timing(
             target_start
         };
 
+        let marks = prosody.get(i).copied().unwrap_or_default();
+        let target_start = target_start + marks.pause_before;
+        let mut target_dur = target_dur / marks.rate;
+        if marks.emphasis {
+            target_dur *= EMPHASIS_STRETCH;
+        }
+
         let stretch = if source_dur > 0.0 {
             target_dur / source_dur
         } else {
@@ -85,6 +126,8 @@ pub fn plan_timing(
             target_start,
             target_duration: target_dur,
             stretch_factor: stretch,
+            pause_before: marks.pause_before,
+            emphasis: marks.emphasis,
         });
         cursor = target_start + target_dur;
     }
@@ -107,7 +150,7 @@ fn group_contiguous_runs(matches: &[MatchResult]) -> Vec<Vec<usize>> {
     for i in 1..matches.len() {
         let prev = &matches[runs.last().unwrap().last().copied().unwrap()].entry;
         let curr = &matches[i].entry;
-        if curr.source_path == prev.source_path && curr.index == prev.index + 1 {
+        if curr.source == prev.source && curr.index == prev.index + 1 {
             runs.last_mut().unwrap().push(i);
         } else {
             runs.push(vec![i]);
@@ -117,36 +160,30 @@ fn group_contiguous_runs(matches: &[MatchResult]) -> Vec<Vec<usize>> {
     runs
 }
 
-/// Normalize volume across clips to median RMS.
-fn normalize_volume_clips(clips: &mut [Vec<f64>]) {
-    let rms_values: Vec<f64> = clips
-        .iter()
-        .map(|c| compute_rms(c))
-        .filter(|&r| r > 1e-6)
-        .collect();
-
-    if rms_values.is_empty() {
-        return;
-    }
-
-    let mut sorted = rms_values.clone();
-    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
-    let target_rms = sorted[sorted.len() / 2];
-
-    if target_rms < 1e-6 {
-        return;
+/// Split runs where markup asks for a pause or emphasis starts or stops,
+/// so each piece is cut, timed, and colored on its own.
+fn split_runs_at_marks(runs: Vec<Vec<usize>>, timing: &[TimingPlan]) -> Vec<Vec<usize>> {
+    let mut split = Vec::new();
+    for run in runs {
+        let mut piece: Vec<usize> = Vec::new();
+        for i in run {
+            if let Some(&prev) = piece.last() {
+                if timing[i].pause_before > 0.0 || timing[i].emphasis != timing[prev].emphasis {
+                    split.push(std::mem::take(&mut piece));
+                }
+            }
+            piece.push(i);
+        }
+        split.push(piece);
     }
+    split
+}
 
-    for clip in clips.iter_mut() {
-        let clip_rms = compute_rms(clip);
-        if clip_rms < 1e-6 {
-            continue;
-        }
-        let db_adjust = 20.0 * (target_rms / clip_rms).log10();
-        let db_adjust = db_adjust.clamp(-20.0, 20.0);
-        if db_adjust.abs() >= 0.5 {
-            adjust_volume(clip, db_adjust);
-        }
+/// Normalize volume across clips toward a common RMS.
+fn normalize_volume_clips(clips: &mut [Vec<f64>], normalization: &RmsNormalization) {
+    let rms_values: Vec<f64> = clips.iter().map(|c| compute_rms(c)).collect();
+    for (clip, db_adjust) in clips.iter_mut().zip(normalization.gains_db(&rms_values)) {
+        adjust_volume(clip, db_adjust);
     }
 }
 
@@ -191,7 +228,10 @@ fn normalize_pitch_clips(clips: &mut [Vec<f64>], sr: u32, pitch_range: f64) {
 /// Cut, stretch, and concatenate matched syllables into output audio.
 ///
 /// Consecutive matches from adjacent positions in the same source file
-/// are cut as a single clip to preserve natural coarticulation.
+/// are cut as a single clip to preserve natural coarticulation. With
+/// `limiter` set, the output is run through a peak limiter; it is then
+/// mastered as it is written, with its loudness recorded in the run's
+/// manifest.json.
 #[allow(clippy::too_many_arguments)]
 pub fn assemble(
     matches: &[MatchResult],
@@ -200,10 +240,12 @@ pub fn assemble(
     output_dir: &Path,
     crossfade_ms: f64,
     pitch_shifts: Option<&[f64]>,
-    do_normalize_volume: bool,
+    normalize_volume: Option<&RmsNormalization>,
     do_normalize_pitch: bool,
+    limiter: bool,
+    mastering: &Mastering,
 ) -> Result<PathBuf> {
-    let runs = group_contiguous_runs(matches);
+    let runs = split_runs_at_marks(group_contiguous_runs(matches), timing);
 
     let mut clips: Vec<Vec<f64>> = Vec::new();
     let mut gap_durations: Vec<f64> = Vec::new();
@@ -213,10 +255,10 @@ pub fn assemble(
         let first = run[0];
         let last = *run.last().unwrap();
 
-        let source_path = &matches[first].entry.source_path;
+        let source = matches[first].entry.source;
         let (samples, sr) = source_samples
-            .get(source_path)
-            .ok_or_else(|| anyhow::anyhow!("Source audio not loaded: {}", source_path))?;
+            .get(source.path().to_string_lossy().as_ref())
+            .ok_or_else(|| anyhow::anyhow!("Source audio not loaded: {}", source))?;
         sample_rate = *sr;
 
         // Cut the entire contiguous span as one clip
@@ -271,18 +313,28 @@ pub fn assemble(
     }
 
     // Normalize volume and pitch across clips
-    if do_normalize_volume {
-        normalize_volume_clips(&mut clips);
+    if let Some(normalization) = normalize_volume {
+        normalize_volume_clips(&mut clips, normalization);
     }
 
     if do_normalize_pitch {
         normalize_pitch_clips(&mut clips, sample_rate, 8.0);
     }
 
+    // Emphasis after normalization, which would otherwise flatten it
+    for (clip, run) in clips.iter_mut().zip(&runs) {
+        if timing[run[0]].emphasis {
+            adjust_volume(clip, EMPHASIS_GAIN_DB);
+            if let Ok(shifted) = pitch_shift(clip, sample_rate, EMPHASIS_PITCH_ST) {
+                *clip = shifted;
+            }
+        }
+    }
+
     // Concatenate all clips
     let crossfade_samples = ((crossfade_ms / 1000.0) * sample_rate as f64).round() as usize;
 
-    let output_samples = if !gap_durations.is_empty() {
+    let mut output_samples = if !gap_durations.is_empty() {
         concatenate_with_gaps(&clips, &gap_durations, crossfade_ms, sample_rate)
     } else {
         concatenate(&clips, crossfade_samples)
@@ -293,7 +345,11 @@ pub fn assemble(
         .unwrap_or_default()
         .to_string_lossy();
     let output_path = output_dir.join(format!("{}.wav", run_name));
-    write_wav(&output_path, &output_samples, sample_rate)?;
+    if limiter {
+        output_samples = limit_peaks(&[output_samples], sample_rate).remove(0);
+    }
+    let report = mastering.write(&output_path, &[output_samples], sample_rate)?;
+    record_loudness(output_dir, &output_path, &report)?;
 
     Ok(output_path)
 }
@@ -319,8 +375,9 @@ mod tests {
                 end,
                 word: "test".to_string(),
                 stress: Some(1),
-                source_path: source.to_string(),
+                source: source.into(),
                 index,
+                quality: None,
             },
             distance: 0,
             target_index: index,
@@ -364,6 +421,34 @@ mod tests {
         assert!((timing[0].target_duration - 0.46).abs() < 1e-10);
     }
 
+    #[test]
+    fn test_plan_timing_with_markup() {
+        let matches = vec![
+            make_match(&["K"], &["K"], 0, "a.wav", 0.0, 0.2),
+            make_match(&["AE"], &["AE"], 1, "a.wav", 0.2, 0.4),
+            make_match(&["T"], &["T"], 2, "a.wav", 0.4, 0.6),
+        ];
+        let prosody = [
+            Prosody { rate: 0.5, ..Prosody::default() },
+            Prosody { pause_before: 0.4, emphasis: true, rate: 1.0 },
+        ];
+        let timing = plan_timing_with(&matches, &[0], 0.25, None, 0.8, &prosody);
+        // Half rate doubles the first syllable
+        assert!((timing[0].target_duration - 0.4).abs() < 1e-10);
+        assert!((timing[0].stretch_factor - 2.0).abs() < 1e-10);
+        assert!((timing[1].target_start - 0.8).abs() < 1e-10);
+        assert!((timing[1].target_duration - 0.2 * EMPHASIS_STRETCH).abs() < 1e-10);
+        assert!(timing[1].emphasis && !timing[2].emphasis);
+        // Missing prosody is plain
+        assert!((timing[2].target_duration - 0.2).abs() < 1e-10);
+
+        // The contiguous run splits at the pause and where emphasis ends
+        let runs = split_runs_at_marks(group_contiguous_runs(&matches), &timing);
+        assert_eq!(runs, vec![vec![0], vec![1], vec![2]]);
+        let plain = plan_timing(&matches, &[0], 0.25, None, 0.8);
+        assert_eq!(split_runs_at_marks(group_contiguous_runs(&matches), &plain), vec![vec![0, 1, 2]]);
+    }
+
     #[test]
     fn test_group_contiguous_runs() {
         let matches = vec![
@@ -402,7 +487,7 @@ mod tests {
             vec![0.1; 100],   // RMS ~0.1
             vec![0.3; 100],   // RMS ~0.3
         ];
-        normalize_volume_clips(&mut clips);
+        normalize_volume_clips(&mut clips, &RmsNormalization::default());
         // After normalization, RMS values should be closer together
         let rms_after: Vec<f64> = clips.iter().map(|c| compute_rms(c)).collect();
         let range_before = 0.5 - 0.1; // 0.4
@@ -418,7 +503,20 @@ mod tests {
             vec![0.5; 100],
         ];
         // Should not crash on silent clips
-        normalize_volume_clips(&mut clips);
+        normalize_volume_clips(&mut clips, &RmsNormalization::default());
+    }
+
+    #[test]
+    fn test_normalize_volume_limits_boost() {
+        let mut clips = vec![vec![0.8; 100], vec![0.01; 100]];
+        use crate::audio::effects::RmsTarget;
+
+        let normalization = RmsNormalization { target: RmsTarget::Fixed(-6.0), max_boost_db: 6.0, max_cut_db: 20.0 };
+        normalize_volume_clips(&mut clips, &normalization);
+        let rms: Vec<f64> = clips.iter().map(|c| compute_rms(c)).collect();
+        assert!((rms[0] - 10f64.powf(-6.0 / 20.0)).abs() < 1e-3);
+        // The quiet clip is raised only 6 dB, not the 28 dB to the target
+        assert!((rms[1] - 0.01 * 10f64.powf(6.0 / 20.0)).abs() < 1e-6);
     }
 
     #[test]
@@ -1,12 +1,18 @@
 //! Match target syllables/phonemes to a source syllable bank.
 //!
-//! Uses Viterbi DP with a continuity bonus to prefer adjacent source
-//! syllables, preserving natural coarticulation.
+//! Syllables are matched by Viterbi DP (default), a greedy per-syllable
+//! pick, or a beam search; the DP and beam search add a continuity bonus to
+//! prefer adjacent source syllables, preserving natural coarticulation.
 
+use std::path::Path;
+
+use anyhow::Result;
 use serde::Serialize;
 
-use crate::speak::phonetic_distance::{phoneme_distance, syllable_distance};
+use crate::language::g2p::Language;
+use crate::speak::phonetic_distance::{phoneme_distance_in, syllable_distance_in};
 use crate::speak::syllable_bank::SyllableEntry;
+use crate::speak::target_text::TextSyllable;
 
 /// Default bonus applied when consecutive target syllables match to adjacent
 /// source syllables. A value of 7 means the DP will prefer a contiguous
@@ -14,6 +20,40 @@ use crate::speak::syllable_bank::SyllableEntry;
 /// globally-best non-contiguous alternative.
 const CONTINUITY_BONUS: i32 = 7;
 
+/// Default number of partial paths kept by beam search.
+pub const DEFAULT_BEAM_WIDTH: usize = 8;
+
+/// Default number of alternates listed per target syllable.
+pub const DEFAULT_ALTERNATES: usize = 5;
+
+/// Penalty per earlier use when beam search reuses a source syllable
+/// outside a contiguous run. The DP only sees the previous match, so it
+/// cannot discourage the same clip recurring across an utterance.
+const REUSE_PENALTY: f64 = 3.0;
+
+/// How target syllables are assigned to bank entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchStrategy {
+    /// Closest entry for each syllable on its own.
+    Greedy,
+    /// Exact minimum of distance minus continuity bonus.
+    Viterbi,
+    /// Keep the best `width` partial paths, also penalizing reuse.
+    Beam { width: usize },
+}
+
+impl MatchStrategy {
+    /// Parse a CLI/GUI name: "greedy", "viterbi", or "beam".
+    pub fn from_name(name: &str, beam_width: usize) -> Option<Self> {
+        match name {
+            "greedy" => Some(Self::Greedy),
+            "viterbi" => Some(Self::Viterbi),
+            "beam" => Some(Self::Beam { width: beam_width.max(1) }),
+            _ => None,
+        }
+    }
+}
+
 /// Result of matching a target syllable/phoneme to a source entry.
 #[derive(Debug, Clone, Serialize)]
 pub struct MatchResult {
@@ -43,7 +83,7 @@ impl MatchResult {
 
 /// True if `b` immediately follows `a` in the same source file.
 fn are_adjacent(a: &SyllableEntry, b: &SyllableEntry) -> bool {
-    a.source_path == b.source_path && b.index == a.index + 1
+    a.source == b.source && b.index == a.index + 1
 }
 
 /// Match target syllables to source bank using Viterbi DP.
@@ -56,6 +96,26 @@ pub fn match_syllables(
     bank: &[SyllableEntry],
     target_stresses: Option<&[Option<u8>]>,
     continuity_bonus: Option<i32>,
+) -> Vec<MatchResult> {
+    match_syllables_with(
+        target_syllables,
+        bank,
+        target_stresses,
+        continuity_bonus,
+        MatchStrategy::Viterbi,
+        Language::English,
+    )
+}
+
+/// Match target syllables to source bank with the given strategy, scoring
+/// distances with the phone contrasts of `language`.
+pub fn match_syllables_with(
+    target_syllables: &[Vec<String>],
+    bank: &[SyllableEntry],
+    target_stresses: Option<&[Option<u8>]>,
+    continuity_bonus: Option<i32>,
+    strategy: MatchStrategy,
+    language: Language,
 ) -> Vec<MatchResult> {
     let n = target_syllables.len();
     let b = bank.len();
@@ -63,24 +123,8 @@ pub fn match_syllables(
         return Vec::new();
     }
 
-    let bonus = continuity_bonus.unwrap_or(CONTINUITY_BONUS);
-
-    // Pre-compute pairwise distances (with small stress penalty for ties)
-    let mut dists: Vec<Vec<f64>> = Vec::with_capacity(n);
-    for (i, target) in target_syllables.iter().enumerate() {
-        let stress = target_stresses.and_then(|ts| ts.get(i).copied().flatten());
-        let mut row = Vec::with_capacity(b);
-        for entry in bank {
-            let d = syllable_distance(target, &entry.phoneme_labels) as f64;
-            let penalty = if stress.is_some() && entry.stress != stress {
-                0.1
-            } else {
-                0.0
-            };
-            row.push(d + penalty);
-        }
-        dists.push(row);
-    }
+    let bonus = continuity_bonus.unwrap_or(CONTINUITY_BONUS) as f64;
+    let dists = distance_rows(target_syllables, bank, target_stresses, language);
 
     // Pre-compute predecessor map: pred[j] = k iff bank[k] → bank[j]
     let mut pred: Vec<Option<usize>> = vec![None; b];
@@ -93,6 +137,130 @@ pub fn match_syllables(
         }
     }
 
+    let path = match strategy {
+        MatchStrategy::Greedy => dists.iter().map(|row| argmin(row)).collect(),
+        MatchStrategy::Viterbi => viterbi_path(&dists, &pred, bonus),
+        MatchStrategy::Beam { width } => beam_path(&dists, &pred, bonus, width.max(1)),
+    };
+
+    (0..n)
+        .map(|i| MatchResult {
+            target_phonemes: target_syllables[i].clone(),
+            entry: bank[path[i]].clone(),
+            distance: dists[i][path[i]] as i32,
+            target_index: i,
+        })
+        .collect()
+}
+
+/// Distance from each target syllable to each bank entry, with a small
+/// stress penalty to break ties.
+fn distance_rows(
+    target_syllables: &[Vec<String>],
+    bank: &[SyllableEntry],
+    target_stresses: Option<&[Option<u8>]>,
+    language: Language,
+) -> Vec<Vec<f64>> {
+    target_syllables
+        .iter()
+        .enumerate()
+        .map(|(i, target)| {
+            let stress = target_stresses.and_then(|ts| ts.get(i).copied().flatten());
+            bank.iter()
+                .map(|entry| {
+                    let d = syllable_distance_in(target, &entry.phoneme_labels, language) as f64;
+                    let penalty = if stress.is_some() && entry.stress != stress {
+                        0.1
+                    } else {
+                        0.0
+                    };
+                    d + penalty
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The `count` closest bank entries for each target syllable, closest
+/// first, scored as `match_syllables_with` scores them (without the
+/// continuity bonus).
+pub fn alternates(
+    target_syllables: &[Vec<String>],
+    bank: &[SyllableEntry],
+    target_stresses: Option<&[Option<u8>]>,
+    count: usize,
+    language: Language,
+) -> Vec<Vec<MatchResult>> {
+    let dists = distance_rows(target_syllables, bank, target_stresses, language);
+    dists
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut order: Vec<usize> = (0..row.len()).collect();
+            order.sort_by(|&a, &b| row[a].partial_cmp(&row[b]).unwrap());
+            order
+                .into_iter()
+                .take(count)
+                .map(|j| MatchResult {
+                    target_phonemes: target_syllables[i].clone(),
+                    entry: bank[j].clone(),
+                    distance: row[j] as i32,
+                    target_index: i,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Write `matches.json`: for each target syllable, its word, the chosen
+/// bank entry, and the alternates from `alternates`, each with its
+/// source span and distance.
+pub fn write_matches_report(
+    path: &Path,
+    targets: &[TextSyllable],
+    matches: &[MatchResult],
+    alternates: &[Vec<MatchResult>],
+) -> Result<()> {
+    let candidate = |m: &MatchResult| {
+        let mut value = m.entry.to_json_value();
+        value["distance"] = m.distance.into();
+        value
+    };
+    let syllables: Vec<serde_json::Value> = matches
+        .iter()
+        .enumerate()
+        .map(|(i, m)| {
+            let target = targets.get(i);
+            serde_json::json!({
+                "target_index": i,
+                "word": target.map(|t| t.word.as_str()),
+                "word_index": target.map(|t| t.word_index),
+                "target": m.target_phonemes,
+                "chosen": candidate(m),
+                "alternates": alternates.get(i).map_or(Vec::new(), |alts| alts.iter().map(candidate).collect()),
+            })
+        })
+        .collect();
+    let report = serde_json::json!({ "syllables": syllables });
+    std::fs::write(path, serde_json::to_string_pretty(&report)?)?;
+    Ok(())
+}
+
+/// Index of the smallest value (first on ties).
+fn argmin(values: &[f64]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Bank path minimising total distance minus continuity bonus.
+fn viterbi_path(dists: &[Vec<f64>], pred: &[Option<usize>], bonus: f64) -> Vec<usize> {
+    let n = dists.len();
+    let b = pred.len();
+
     // --- Viterbi DP ---
     let inf = f64::INFINITY;
 
@@ -121,7 +289,7 @@ pub fn match_syllables(
 
             // Contiguous: predecessor in the same source
             if let Some(k) = pred[j] {
-                let contiguous = dp[k] + cost - bonus as f64;
+                let contiguous = dp[k] + cost - bonus;
                 if contiguous < best {
                     best = contiguous;
                     best_k = k;
@@ -137,27 +305,70 @@ pub fn match_syllables(
     }
 
     // --- Backtrace ---
-    let best_last = dp
-        .iter()
-        .enumerate()
-        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-        .unwrap()
-        .0;
-
-    let mut path = vec![best_last];
+    let mut path = vec![argmin(&dp)];
     for i in (1..n).rev() {
         path.push(parents[i][*path.last().unwrap()]);
     }
     path.reverse();
+    path
+}
 
-    (0..n)
-        .map(|i| MatchResult {
-            target_phonemes: target_syllables[i].clone(),
-            entry: bank[path[i]].clone(),
-            distance: dists[i][path[i]] as i32,
-            target_index: i,
-        })
-        .collect()
+/// A partial match path in beam search.
+struct Beam {
+    path: Vec<usize>,
+    cost: f64,
+}
+
+/// Bank path found by beam search over the `width` best partial paths.
+///
+/// Each step extends every path with the `width` closest entries for the
+/// next syllable plus the source successor of its last entry, so contiguous
+/// runs are always considered.
+fn beam_path(dists: &[Vec<f64>], pred: &[Option<usize>], bonus: f64, width: usize) -> Vec<usize> {
+    let b = pred.len();
+    let mut succ: Vec<Option<usize>> = vec![None; b];
+    for (j, p) in pred.iter().enumerate() {
+        if let Some(k) = p {
+            succ[*k] = Some(j);
+        }
+    }
+    let closest = |row: &[f64]| -> Vec<usize> {
+        let mut idx: Vec<usize> = (0..row.len()).collect();
+        idx.sort_by(|&a, &b| row[a].partial_cmp(&row[b]).unwrap());
+        idx.truncate(width);
+        idx
+    };
+
+    let mut beams: Vec<Beam> = closest(&dists[0])
+        .into_iter()
+        .map(|j| Beam { path: vec![j], cost: dists[0][j] })
+        .collect();
+
+    for row in dists.iter().skip(1) {
+        let candidates = closest(row);
+        let mut next: Vec<Beam> = Vec::with_capacity(beams.len() * (width + 1));
+        for beam in &beams {
+            let last = *beam.path.last().unwrap();
+            let follow = succ[last].filter(|j| !candidates.contains(j));
+            for j in candidates.iter().copied().chain(follow) {
+                let mut cost = beam.cost + row[j];
+                if pred[j] == Some(last) {
+                    cost -= bonus;
+                } else {
+                    let uses = beam.path.iter().filter(|&&k| k == j).count();
+                    cost += REUSE_PENALTY * uses as f64;
+                }
+                let mut path = beam.path.clone();
+                path.push(j);
+                next.push(Beam { path, cost });
+            }
+        }
+        next.sort_by(|a, b| a.cost.partial_cmp(&b.cost).unwrap());
+        next.truncate(width);
+        beams = next;
+    }
+
+    beams.swap_remove(0).path
 }
 
 /// Match each target phoneme to the best source phoneme.
@@ -167,6 +378,15 @@ pub fn match_syllables(
 pub fn match_phonemes(
     target_phonemes: &[String],
     bank: &[SyllableEntry],
+) -> Vec<MatchResult> {
+    match_phonemes_in(target_phonemes, bank, Language::English)
+}
+
+/// `match_phonemes` using the phone contrasts of `language`.
+pub fn match_phonemes_in(
+    target_phonemes: &[String],
+    bank: &[SyllableEntry],
+    language: Language,
 ) -> Vec<MatchResult> {
     // Flatten bank into (phoneme_label, entry) tuples
     let flat: Vec<(&str, &SyllableEntry)> = bank
@@ -187,7 +407,7 @@ pub fn match_phonemes(
             let mut best_dist = i32::MAX;
 
             for (label, entry) in &flat {
-                let d = phoneme_distance(target_ph, label);
+                let d = phoneme_distance_in(target_ph, label, language);
                 if d < best_dist {
                     best_dist = d;
                     best_entry = Some(entry);
@@ -224,11 +444,39 @@ mod tests {
             end: index as f64 * 0.3 + 0.3,
             word: word.to_string(),
             stress,
-            source_path: source.to_string(),
+            source: source.into(),
             index,
+            quality: None,
         }
     }
 
+    #[test]
+    fn test_alternates_and_report() {
+        let bank = vec![
+            make_entry(&["K", "AE1", "T"], 0, "a.wav", "cat", Some(1)),
+            make_entry(&["D", "AO1", "G"], 1, "a.wav", "dog", Some(1)),
+            make_entry(&["K", "AH0", "T"], 2, "a.wav", "cut", Some(0)),
+        ];
+        let targets = vec![vec!["K".into(), "AE1".into(), "T".into()]];
+        let alts = alternates(&targets, &bank, None, 2, Language::English);
+        assert_eq!(alts.len(), 1);
+        let words: Vec<&str> = alts[0].iter().map(|m| m.entry.word.as_str()).collect();
+        assert_eq!(words, vec!["cat", "cut"]);
+        assert!(alts[0][0].distance <= alts[0][1].distance);
+
+        let matches = match_syllables(&targets, &bank, None, None);
+        let syls = crate::speak::target_text::text_to_syllables("cat");
+        let path = std::env::temp_dir().join(format!("glottisdale_matches_{}.json", std::process::id()));
+        write_matches_report(&path, &syls, &matches, &alts).unwrap();
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        let first = &report["syllables"][0];
+        assert_eq!(first["word"], "cat");
+        assert_eq!(first["chosen"]["word"], "cat");
+        assert_eq!(first["alternates"].as_array().unwrap().len(), 2);
+        assert_eq!(first["alternates"][1]["source"], "a.wav");
+        std::fs::remove_file(path).ok();
+    }
+
     #[test]
     fn test_match_exact() {
         let bank = vec![
@@ -283,11 +531,55 @@ mod tests {
         ];
         let matches = match_syllables(&targets, &bank, None, None);
         // Should prefer adjacent pair (cat@0,dog@1 in a.wav)
-        assert_eq!(matches[0].entry.source_path, "a.wav");
-        assert_eq!(matches[1].entry.source_path, "a.wav");
+        assert_eq!(matches[0].entry.source, "a.wav".into());
+        assert_eq!(matches[1].entry.source, "a.wav".into());
         assert_eq!(matches[1].entry.index, 1);
     }
 
+    #[test]
+    fn test_strategies_on_contiguous_run() {
+        let bank = vec![
+            make_entry(&["K", "AE1", "T"], 0, "a.wav", "cat", Some(1)),
+            make_entry(&["D", "AO1", "K"], 1, "a.wav", "dock", Some(1)),
+            make_entry(&["D", "AO1", "G"], 0, "b.wav", "dog", Some(1)),
+        ];
+        let targets = vec![
+            vec!["K".into(), "AE1".into(), "T".into()],
+            vec!["D".into(), "AO1".into(), "G".into()],
+        ];
+        let words = |strategy| -> Vec<String> {
+            match_syllables_with(&targets, &bank, None, None, strategy, Language::English)
+                .into_iter()
+                .map(|m| m.entry.word)
+                .collect()
+        };
+        // Greedy takes the exact match; the others keep the source run
+        assert_eq!(words(MatchStrategy::Greedy), vec!["cat", "dog"]);
+        assert_eq!(words(MatchStrategy::Viterbi), vec!["cat", "dock"]);
+        assert_eq!(words(MatchStrategy::Beam { width: 1 }), vec!["cat", "dock"]);
+    }
+
+    #[test]
+    fn test_beam_discourages_reuse() {
+        let bank = vec![
+            make_entry(&["B", "AH1"], 0, "a.wav", "ba", Some(1)),
+            make_entry(&["P", "AH1"], 0, "b.wav", "pa", Some(1)),
+        ];
+        let targets: Vec<Vec<String>> = (0..4).map(|_| vec!["B".into(), "AH1".into()]).collect();
+        let viterbi = match_syllables_with(&targets, &bank, None, None, MatchStrategy::Viterbi, Language::English);
+        assert!(viterbi.iter().all(|m| m.entry.word == "ba"));
+        let beam = match_syllables_with(&targets, &bank, None, None, MatchStrategy::Beam { width: 4 }, Language::English);
+        assert_eq!(beam.len(), 4);
+        assert!(beam.iter().any(|m| m.entry.word == "pa"));
+    }
+
+    #[test]
+    fn test_match_strategy_from_name() {
+        assert_eq!(MatchStrategy::from_name("greedy", 8), Some(MatchStrategy::Greedy));
+        assert_eq!(MatchStrategy::from_name("beam", 0), Some(MatchStrategy::Beam { width: 1 }));
+        assert_eq!(MatchStrategy::from_name("best", 8), None);
+    }
+
     #[test]
     fn test_match_empty_inputs() {
         let bank = vec![make_entry(&["K"], 0, "a.wav", "k", None)];
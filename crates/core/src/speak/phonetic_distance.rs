@@ -1,7 +1,13 @@
 //! ARPABET phonetic feature matrix and distance calculations.
+//!
+//! The `_in` variants fold phones a language does not contrast (Spanish
+//! has no tense/lax vowel pairs, German no /w/–/v/ split) before comparing,
+//! so near-homophones in that language score as matches.
 
 use std::collections::HashMap;
 
+use crate::language::g2p::Language;
+
 lazy_static::lazy_static! {
     /// IPA-to-ARPABET mapping for phonemes produced by BFA aligner.
     static ref IPA_TO_ARPABET: Vec<(&'static str, &'static str)> = vec![
@@ -11,7 +17,7 @@ lazy_static::lazy_static! {
         ("i", "IY"), ("ɪ", "IH"), ("e", "EY"), ("ɛ", "EH"), ("æ", "AE"),
         ("ɑ", "AA"), ("ɒ", "AA"), ("ɔ", "AO"), ("o", "OW"), ("ʊ", "UH"),
         ("u", "UW"), ("ə", "AH"), ("ɜ", "ER"), ("ɐ", "AH"), ("ʌ", "AH"),
-        ("a", "AA"),
+        ("a", "AA"), ("y", "UE"), ("ʏ", "UE"), ("ø", "OE"), ("œ", "OE"),
         // Consonants — stops
         ("p", "P"), ("b", "B"), ("t", "T"), ("d", "D"), ("k", "K"), ("g", "G"),
         // Consonants — nasals
@@ -46,6 +52,7 @@ lazy_static::lazy_static! {
         m.insert("SH", &["consonant", "fricative", "postalveolar", "voiceless"][..]);
         m.insert("ZH", &["consonant", "fricative", "postalveolar", "voiced"][..]);
         m.insert("HH", &["consonant", "fricative", "glottal", "voiceless"][..]);
+        m.insert("X",  &["consonant", "fricative", "velar", "voiceless"][..]);
         m.insert("CH", &["consonant", "affricate", "postalveolar", "voiceless"][..]);
         m.insert("JH", &["consonant", "affricate", "postalveolar", "voiced"][..]);
         m.insert("M",  &["consonant", "nasal", "bilabial", "voiced"][..]);
@@ -71,6 +78,25 @@ lazy_static::lazy_static! {
         m.insert("AY", &["vowel", "low", "central", "unrounded", "tense"][..]);
         m.insert("OY", &["vowel", "mid", "back", "rounded", "tense"][..]);
         m.insert("ER", &["vowel", "mid", "central", "rounded", "tense"][..]);
+        m.insert("UE", &["vowel", "high", "front", "rounded", "tense"][..]);
+        m.insert("OE", &["vowel", "mid", "front", "rounded", "tense"][..]);
+        m
+    };
+
+    /// Phones each language does not contrast, mapped to one representative.
+    static ref FOLDS: HashMap<Language, HashMap<&'static str, &'static str>> = {
+        let mut m = HashMap::new();
+        m.insert(Language::English, HashMap::new());
+        // Five vowels, b/v and d/ð allophones, seseo
+        m.insert(Language::Spanish, [
+            ("IH", "IY"), ("EH", "EY"), ("UH", "UW"), ("AE", "AA"), ("AH", "AA"),
+            ("AO", "OW"), ("V", "B"), ("DH", "D"), ("Z", "S"), ("TH", "S"),
+            ("JH", "Y"), ("HH", "X"),
+        ].into_iter().collect());
+        // No /w/ or dental fricatives; English /æ/ is heard as /ɛ/
+        m.insert(Language::German, [
+            ("W", "V"), ("TH", "S"), ("DH", "Z"), ("AE", "EH"),
+        ].into_iter().collect());
         m
     };
 }
@@ -132,6 +158,28 @@ pub fn phoneme_distance(a: &str, b: &str) -> i32 {
     }
 }
 
+/// Phone `phoneme` is heard as in `language`, stress stripped.
+fn fold(phoneme: &str, language: Language) -> &str {
+    let base = strip_stress(phoneme);
+    FOLDS[&language].get(base).copied().unwrap_or(base)
+}
+
+/// `phoneme_distance` using the contrasts of `language`.
+pub fn phoneme_distance_in(a: &str, b: &str, language: Language) -> i32 {
+    phoneme_distance(fold(a, language), fold(b, language))
+}
+
+/// `syllable_distance` using the contrasts of `language`.
+pub fn syllable_distance_in(a: &[String], b: &[String], language: Language) -> i32 {
+    let max_len = a.len().max(b.len());
+    (0..max_len)
+        .map(|i| match (a.get(i), b.get(i)) {
+            (Some(pa), Some(pb)) => phoneme_distance_in(pa, pb, language),
+            _ => CROSS_TYPE_DISTANCE,
+        })
+        .sum()
+}
+
 /// Compute distance between two syllables (lists of ARPABET phonemes).
 pub fn syllable_distance(a: &[String], b: &[String]) -> i32 {
     let max_len = a.len().max(b.len());
@@ -215,6 +263,19 @@ mod tests {
         assert_eq!(normalize_phoneme("oʊ"), "OW");
     }
 
+    #[test]
+    fn test_language_folds() {
+        assert_eq!(phoneme_distance("IH1", "IY1"), 1);
+        assert_eq!(phoneme_distance_in("IH1", "IY0", Language::Spanish), 0);
+        assert_eq!(phoneme_distance_in("W", "V", Language::German), 0);
+        assert_eq!(phoneme_distance_in("W", "V", Language::English), phoneme_distance("W", "V"));
+        assert_eq!(phoneme_distance("UE1", "IY1"), 1);
+        let a: Vec<String> = ["B", "EH1"].iter().map(|s| s.to_string()).collect();
+        let b: Vec<String> = ["V", "EY1"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(syllable_distance_in(&a, &b, Language::Spanish), 0);
+        assert!(syllable_distance(&a, &b) > 0);
+    }
+
     #[test]
     fn test_strip_stress() {
         assert_eq!(strip_stress("AE1"), "AE");
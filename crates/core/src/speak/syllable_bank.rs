@@ -2,6 +2,8 @@
 
 use serde::Serialize;
 
+use crate::audio::analysis::assess_quality;
+use crate::source::SourceId;
 use crate::speak::phonetic_distance::normalize_phoneme;
 use crate::types::Syllable;
 
@@ -18,10 +20,12 @@ pub struct SyllableEntry {
     pub word: String,
     /// Stress level (0, 1, 2) or None
     pub stress: Option<u8>,
-    /// Path to source audio file
-    pub source_path: String,
+    /// Source audio file
+    pub source: SourceId,
     /// Position in the original syllable list
     pub index: usize,
+    /// Audio quality from 0 to 1, once scored with `score_bank`
+    pub quality: Option<f64>,
 }
 
 impl SyllableEntry {
@@ -39,8 +43,9 @@ impl SyllableEntry {
             "duration": (self.duration() * 10000.0).round() / 10000.0,
             "word": self.word,
             "stress": self.stress,
-            "source": self.source_path,
+            "source": self.source,
             "index": self.index,
+            "quality": self.quality.map(|q| (q * 1000.0).round() / 1000.0),
         })
     }
 }
@@ -67,6 +72,7 @@ fn is_phoneme(label: &str) -> bool {
 /// Filters out punctuation labels from phoneme lists and skips
 /// syllables that have no real phonemes after filtering.
 pub fn build_bank(syllables: &[Syllable], source_path: &str) -> Vec<SyllableEntry> {
+    let source = SourceId::intern(source_path);
     let mut entries = Vec::new();
     for (i, syl) in syllables.iter().enumerate() {
         let labels: Vec<String> = syl
@@ -86,13 +92,23 @@ pub fn build_bank(syllables: &[Syllable], source_path: &str) -> Vec<SyllableEntr
             start: syl.start,
             end: syl.end,
             word: syl.word.clone(),
-            source_path: source_path.to_string(),
+            source,
             index: i,
+            quality: None,
         });
     }
     entries
 }
 
+/// Score each entry's audio quality from its source recording.
+pub fn score_bank(entries: &mut [SyllableEntry], source: &[f64], sr: u32) {
+    for entry in entries {
+        let start = ((entry.start.max(0.0) * sr as f64) as usize).min(source.len());
+        let end = ((entry.end.max(0.0) * sr as f64) as usize).clamp(start, source.len());
+        entry.quality = Some(assess_quality(&source[start..end], sr).score());
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -129,6 +145,22 @@ mod tests {
         assert_eq!(bank[0].word, "cat");
         assert_eq!(bank[0].start, 0.0);
         assert_eq!(bank[0].end, 0.4);
+        assert_eq!(bank[0].quality, None);
+    }
+
+    #[test]
+    fn test_score_bank() {
+        let syls = vec![
+            make_syl(&[("AA1", 0.0, 0.3)], 0.0, 0.3, "ah"),
+            make_syl(&[("AA1", 0.5, 0.8)], 0.5, 0.8, "ah"),
+        ];
+        let mut bank = build_bank(&syls, "test.wav");
+        let source: Vec<f64> = (0..16000)
+            .map(|i| if i < 8000 { 0.3 * (i as f64 * 150.0 / 16000.0 * std::f64::consts::TAU).sin() } else { 0.0 })
+            .collect();
+        score_bank(&mut bank, &source, 16000);
+        assert!(bank[0].quality.unwrap() > 0.9);
+        assert_eq!(bank[1].quality, Some(0.0));
     }
 
     #[test]
@@ -200,8 +232,9 @@ mod tests {
             end: 1.5,
             word: "test".to_string(),
             stress: None,
-            source_path: "test.wav".to_string(),
+            source: "test.wav".into(),
             index: 0,
+            quality: None,
         };
         assert!((entry.duration() - 0.5).abs() < 1e-10);
     }
@@ -214,12 +247,14 @@ mod tests {
             end: 0.5678,
             word: "cat".to_string(),
             stress: Some(1),
-            source_path: "test.wav".to_string(),
+            source: "test.wav".into(),
             index: 3,
+            quality: Some(0.87654),
         };
         let v = entry.to_json_value();
         assert_eq!(v["word"], "cat");
         assert_eq!(v["index"], 3);
         assert_eq!(v["stress"], 1);
+        assert_eq!(v["quality"], 0.877);
     }
 }
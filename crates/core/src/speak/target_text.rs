@@ -1,6 +1,13 @@
 //! Convert target text to ARPABET syllables for matching.
+//!
+//! `markup_to_syllables` also reads a small SSML-like markup for scripting
+//! delivery without a reference recording: `<pause ms="400"/>`,
+//! `<emph>...</emph>`, and `<rate value="0.8">...</rate>`.
 
-use crate::language::g2p;
+use anyhow::{bail, Context, Result};
+
+use crate::language::g2p::{lookup, Language};
+use crate::language::g2p_english::letter_to_sound;
 use crate::language::syllabify_arpabet;
 
 /// A syllable derived from target text (no audio timing).
@@ -14,6 +21,29 @@ pub struct TextSyllable {
     pub word_index: usize,
     /// Stress level (0, 1, 2) or None
     pub stress: Option<u8>,
+    /// Delivery from markup
+    pub prosody: Prosody,
+}
+
+/// How a syllable is delivered, as marked up in the target text.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Prosody {
+    /// Silence before the syllable, from `<pause>` (seconds)
+    pub pause_before: f64,
+    /// Inside `<emph>`
+    pub emphasis: bool,
+    /// Speaking rate from `<rate>`: 1.0 as matched, below 1 slower
+    pub rate: f64,
+}
+
+impl Default for Prosody {
+    fn default() -> Self {
+        Self {
+            pause_before: 0.0,
+            emphasis: false,
+            rate: 1.0,
+        }
+    }
 }
 
 /// Extract stress level from ARPABET phonemes.
@@ -30,29 +60,174 @@ fn extract_stress(phonemes: &[String]) -> Option<u8> {
 
 /// Strip punctuation from edges of a word.
 fn strip_punct(word: &str) -> String {
-    word.trim_matches(|c: char| ".,!?;:\"'()-".contains(c))
+    word.trim_matches(|c: char| ".,!?;:\"'()-¡¿".contains(c))
         .to_string()
 }
 
+/// Letter names, for spelling out acronyms.
+const LETTER_NAMES: [&[&str]; 26] = [
+    &["EY1"],
+    &["B", "IY1"],
+    &["S", "IY1"],
+    &["D", "IY1"],
+    &["IY1"],
+    &["EH1", "F"],
+    &["JH", "IY1"],
+    &["EY1", "CH"],
+    &["AY1"],
+    &["JH", "EY1"],
+    &["K", "EY1"],
+    &["EH1", "L"],
+    &["EH1", "M"],
+    &["EH1", "N"],
+    &["OW1"],
+    &["P", "IY1"],
+    &["K", "Y", "UW1"],
+    &["AA1", "R"],
+    &["EH1", "S"],
+    &["T", "IY1"],
+    &["Y", "UW1"],
+    &["V", "IY1"],
+    &["D", "AH1", "B", "AH0", "L", "Y", "UW0"],
+    &["EH1", "K", "S"],
+    &["W", "AY1"],
+    &["Z", "IY1"],
+];
+
+const ONES: [&str; 20] = [
+    "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+    "twelve", "thirteen", "fourteen", "fifteen", "sixteen", "seventeen", "eighteen", "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// English words for a number below a million.
+fn number_words(n: u32, out: &mut Vec<&'static str>) {
+    match n {
+        0..=19 => out.push(ONES[n as usize]),
+        20..=99 => {
+            out.push(TENS[(n / 10) as usize]);
+            let rest = n % 10;
+            if rest > 0 {
+                out.push(ONES[rest as usize]);
+            }
+        }
+        100..=999 => {
+            out.extend([ONES[(n / 100) as usize], "hundred"]);
+            let rest = n % 100;
+            if rest > 0 {
+                number_words(rest, out);
+            }
+        }
+        _ => {
+            number_words(n / 1000, out);
+            out.push("thousand");
+            let rest = n % 1000;
+            if rest > 0 {
+                number_words(rest, out);
+            }
+        }
+    }
+}
+
+/// English words for a run of digits: a number below a million, read
+/// digit by digit if longer or zero-padded.
+fn digits_to_words(digits: &str) -> Vec<&'static str> {
+    let mut words = Vec::new();
+    match digits.parse::<u32>() {
+        Ok(n) if n < 1_000_000 && (n == 0 || !digits.starts_with('0')) => number_words(n, &mut words),
+        _ => words.extend(digits.bytes().map(|d| ONES[(d - b'0') as usize])),
+    }
+    words
+}
+
+/// Short all-caps words the dictionary doesn't know are read as letters.
+fn is_acronym(run: &str) -> bool {
+    let len = run.chars().count();
+    (2..=5).contains(&len)
+        && run.chars().all(|c| c.is_ascii_uppercase())
+        && (len <= 3 || !run.chars().any(|c| "AEIOU".contains(c)))
+}
+
+/// English phonemes for a word the dictionary doesn't know: digit runs
+/// are read as numbers, acronyms letter by letter, and everything else by
+/// spelling rules.
+fn english_oov_phonemes(word: &str) -> Vec<String> {
+    let mut phonemes = Vec::new();
+    let chars: Vec<char> = word.chars().filter(|c| c.is_alphanumeric()).collect();
+    for run in chars.chunk_by(|a, b| a.is_ascii_digit() == b.is_ascii_digit()) {
+        let run: String = run.iter().collect();
+        if run.starts_with(|c: char| c.is_ascii_digit()) {
+            for number in digits_to_words(&run) {
+                phonemes.extend(lookup(number).unwrap_or_default());
+            }
+        } else if let Some(known) = lookup(&run) {
+            phonemes.extend(known);
+        } else if is_acronym(&run) {
+            for letter in run.bytes() {
+                phonemes.extend(LETTER_NAMES[(letter - b'A') as usize].iter().map(|p| p.to_string()));
+            }
+        } else {
+            phonemes.extend(letter_to_sound(&run));
+        }
+    }
+    phonemes
+}
+
+/// Phonemes for one whitespace-separated word. Hyphenated and slashed
+/// compounds are pronounced part by part. English words missing from the
+/// CMU dictionary fall back to `english_oov_phonemes`, so names, slang,
+/// numbers, and invented words still get targets.
+fn word_phonemes(word: &str, language: Language) -> Vec<String> {
+    let g2p = language.g2p();
+    word.split(['-', '/', '_'])
+        .filter(|part| !part.is_empty())
+        .flat_map(|part| match language {
+            Language::English if lookup(part).is_none() => english_oov_phonemes(part),
+            _ => g2p.word_to_phonemes(part),
+        })
+        .collect()
+}
+
 /// Convert raw text to a list of ARPABET syllables.
 ///
 /// Uses G2P (CMU dictionary + rule-based fallback) for grapheme-to-phoneme
 /// conversion, then the ARPABET syllabifier to split into syllables.
 pub fn text_to_syllables(text: &str) -> Vec<TextSyllable> {
-    let text = text.trim();
-    if text.is_empty() {
-        return Vec::new();
-    }
+    text_to_syllables_in(text, Language::English)
+}
 
+/// `text_to_syllables` with the G2P backend for `language`.
+pub fn text_to_syllables_in(text: &str, language: Language) -> Vec<TextSyllable> {
     let mut result = Vec::new();
+    let mut word_index = 0;
+    push_words(text, language, Prosody::default(), &mut word_index, &mut result);
+    result
+}
 
-    for (wi, word) in text.split_whitespace().enumerate() {
+/// Syllabify the words of `text`, numbering them from `word_index`, and
+/// append them to `out`. The first syllable keeps `prosody.pause_before`;
+/// the rest get none. Returns whether any syllable was added.
+fn push_words(
+    text: &str,
+    language: Language,
+    prosody: Prosody,
+    word_index: &mut usize,
+    out: &mut Vec<TextSyllable>,
+) -> bool {
+    let start = out.len();
+
+    for word in text.split_whitespace() {
+        let wi = *word_index;
+        *word_index += 1;
         let clean = strip_punct(word);
         if clean.is_empty() {
             continue;
         }
 
-        let phonemes = g2p::word_to_phonemes(&clean);
+        let phonemes = word_phonemes(&clean, language);
         if phonemes.is_empty() {
             continue;
         }
@@ -92,16 +267,151 @@ pub fn text_to_syllables(text: &str) -> Vec<TextSyllable> {
             syl_phonemes.extend(nucleus);
             syl_phonemes.extend(coda);
 
-            result.push(TextSyllable {
+            let pause_before = if out.len() == start { prosody.pause_before } else { 0.0 };
+            out.push(TextSyllable {
                 stress: extract_stress(&syl_phonemes),
                 phonemes: syl_phonemes,
                 word: strip_punct(word),
                 word_index: wi,
+                prosody: Prosody { pause_before, ..prosody },
             });
         }
     }
 
-    result
+    out.len() > start
+}
+
+/// An open markup tag.
+enum Tag {
+    Emph,
+    Rate(f64),
+}
+
+impl Tag {
+    fn name(&self) -> &'static str {
+        match self {
+            Tag::Emph => "emph",
+            Tag::Rate(_) => "rate",
+        }
+    }
+}
+
+/// Value of `name="..."` (or single-quoted) in a tag body.
+fn attribute<'a>(body: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = body;
+    while let Some(at) = rest.find(name) {
+        let after = rest[at + name.len()..].trim_start();
+        let boundary = at == 0 || rest[..at].ends_with(char::is_whitespace);
+        if let (true, Some(value)) = (boundary, after.strip_prefix('=')) {
+            let value = value.trim_start();
+            let quote = value.chars().next()?;
+            if quote == '"' || quote == '\'' {
+                return value[1..].find(quote).map(|end| &value[1..1 + end]);
+            }
+        }
+        rest = &rest[at + name.len()..];
+    }
+    None
+}
+
+/// Number in attribute `name` of the tag `tag`.
+fn number_attribute(body: &str, tag: &str, name: &str) -> Result<f64> {
+    let value = attribute(body, name).with_context(|| format!("<{}> needs a {}=\"...\" attribute", tag, name))?;
+    value
+        .trim()
+        .trim_end_matches("ms")
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .with_context(|| format!("Invalid {} in <{}>: '{}'", name, tag, value))
+}
+
+/// Convert target text with markup to ARPABET syllables.
+///
+/// Supports `<pause ms="400"/>` (silence before the next word),
+/// `<emph>...</emph>` (longer, louder, higher), and
+/// `<rate value="0.8">...</rate>` (slower below 1, faster above; nested
+/// rates multiply). Text without markup gives the same syllables as
+/// `text_to_syllables_in`. Unknown or unbalanced tags are errors.
+pub fn markup_to_syllables(text: &str, language: Language) -> Result<Vec<TextSyllable>> {
+    let mut result = Vec::new();
+    let mut word_index = 0;
+    let mut stack: Vec<Tag> = Vec::new();
+    let mut pause = 0.0;
+    let mut rest = text;
+
+    loop {
+        let (plain, tag) = match rest.find('<') {
+            Some(open) => (&rest[..open], Some(&rest[open + 1..])),
+            None => (rest, None),
+        };
+        let prosody = Prosody {
+            pause_before: pause,
+            emphasis: stack.iter().any(|t| matches!(t, Tag::Emph)),
+            rate: stack
+                .iter()
+                .map(|t| if let Tag::Rate(r) = t { *r } else { 1.0 })
+                .product(),
+        };
+        if push_words(plain, language, prosody, &mut word_index, &mut result) {
+            pause = 0.0;
+        }
+        let Some(tag) = tag else { break };
+        let close = tag.find('>').context("Unclosed '<' in markup")?;
+        let body = tag[..close].trim();
+        rest = &tag[close + 1..];
+
+        if let Some(name) = body.strip_prefix('/') {
+            let name = name.trim();
+            match stack.pop() {
+                Some(open) if open.name() == name => {}
+                Some(open) => bail!("</{}> closes <{}> in markup", name, open.name()),
+                None => bail!("</{}> has no opening tag in markup", name),
+            }
+            continue;
+        }
+        let self_closing = body.ends_with('/');
+        let body = body.trim_end_matches('/').trim();
+        let name = body.split_whitespace().next().unwrap_or("");
+        match name {
+            "pause" => {
+                let ms = number_attribute(body, "pause", "ms")?;
+                if ms < 0.0 {
+                    bail!("Negative pause in markup: {} ms", ms);
+                }
+                pause += ms / 1000.0;
+            }
+            "emph" if !self_closing => stack.push(Tag::Emph),
+            "rate" if !self_closing => {
+                let rate = number_attribute(body, "rate", "value")?;
+                if rate <= 0.0 {
+                    bail!("Rate must be positive in markup: {}", rate);
+                }
+                stack.push(Tag::Rate(rate));
+            }
+            "emph" | "rate" => bail!("<{}/> must wrap text: <{}>...</{}>", name, name, name),
+            _ => bail!("Unknown markup tag <{}> (expected pause, emph, or rate)", name),
+        }
+    }
+
+    if let Some(open) = stack.pop() {
+        bail!("<{}> is never closed in markup", open.name());
+    }
+    Ok(result)
+}
+
+/// Prosody for each phoneme of `syllables`, for phoneme-level matching.
+/// A syllable's pause goes to its first phoneme.
+pub fn phoneme_prosody(syllables: &[TextSyllable]) -> Vec<Prosody> {
+    syllables
+        .iter()
+        .flat_map(|syl| {
+            syl.phonemes.iter().enumerate().map(|(i, _)| Prosody {
+                pause_before: if i == 0 { syl.prosody.pause_before } else { 0.0 },
+                ..syl.prosody
+            })
+        })
+        .collect()
 }
 
 /// Return indices where new words begin.
@@ -120,6 +430,7 @@ pub fn word_boundaries_from_syllables(syllables: &[TextSyllable]) -> Vec<usize>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::language::g2p::is_vowel;
 
     #[test]
     fn test_text_to_syllables_single_word() {
@@ -171,6 +482,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_text_to_syllables_spanish() {
+        let syls = text_to_syllables_in("¿Qué tal?", Language::Spanish);
+        let words: Vec<&str> = syls.iter().map(|s| s.word.as_str()).collect();
+        assert_eq!(words, vec!["Qué", "tal"]);
+        assert_eq!(syls[0].phonemes, vec!["K", "EY1"]);
+        assert_eq!(syls[1].stress, Some(1));
+    }
+
+    #[test]
+    fn test_text_to_syllables_out_of_vocabulary() {
+        let text = "Zorbleflax met Glottisdale-bot and R2D2 at 9:30 on the GPU";
+        let syls = text_to_syllables(text);
+        let words: std::collections::BTreeSet<usize> = syls.iter().map(|s| s.word_index).collect();
+        assert_eq!(words.len(), text.split_whitespace().count(), "every word has syllables");
+        for syl in &syls {
+            assert!(syl.phonemes.iter().any(|p| is_vowel(p)), "{:?} has a nucleus", syl);
+        }
+        let phones = |word: &str| -> Vec<String> {
+            syls.iter().filter(|s| s.word == word).flat_map(|s| s.phonemes.clone()).collect()
+        };
+        assert_eq!(phones("GPU"), vec!["JH", "IY1", "P", "IY1", "Y", "UW1"]);
+        // R2D2: letters by rule, digits as number words
+        assert_eq!(phones("R2D2").iter().filter(|p| p.as_str() == "UW1").count(), 2);
+        assert_eq!(phones("9:30").first().map(String::as_str), Some("N"));
+        assert_eq!(phones("Zorbleflax").first().map(String::as_str), Some("Z"));
+    }
+
+    #[test]
+    fn test_digits_to_words() {
+        assert_eq!(digits_to_words("0"), vec!["zero"]);
+        assert_eq!(digits_to_words("42"), vec!["forty", "two"]);
+        assert_eq!(digits_to_words("1905"), vec!["one", "thousand", "nine", "hundred", "five"]);
+        assert_eq!(digits_to_words("007"), vec!["zero", "zero", "seven"]);
+    }
+
+    #[test]
+    fn test_markup_to_syllables() {
+        let plain = markup_to_syllables("hello big world", Language::English).unwrap();
+        assert_eq!(plain.len(), text_to_syllables("hello big world").len());
+        assert!(plain.iter().all(|s| s.prosody == Prosody::default()));
+
+        let text = "hello <pause ms=\"400\"/> <emph>big</emph> <rate value='0.5'>wide <rate value=\"2\">world</rate></rate>";
+        let syls = markup_to_syllables(text, Language::English).unwrap();
+        let words: Vec<&str> = syls.iter().map(|s| s.word.as_str()).collect();
+        assert_eq!(words, vec!["hello", "hello", "big", "wide", "world"]);
+        assert_eq!(syls.iter().map(|s| s.word_index).collect::<Vec<_>>(), vec![0, 0, 1, 2, 3]);
+        assert_eq!(syls[2].prosody, Prosody { pause_before: 0.4, emphasis: true, rate: 1.0 });
+        assert_eq!(syls[3].prosody.rate, 0.5);
+        assert_eq!(syls[4].prosody.rate, 1.0);
+        assert!(!syls[4].prosody.emphasis);
+
+        let per_phoneme = phoneme_prosody(&syls[2..3]);
+        assert_eq!(per_phoneme.len(), syls[2].phonemes.len());
+        assert_eq!(per_phoneme[0].pause_before, 0.4);
+        assert_eq!(per_phoneme[1].pause_before, 0.0);
+        assert!(per_phoneme[1].emphasis);
+
+        for bad in ["<emph>open", "a</emph>", "<emph>a</rate>", "<shout>a</shout>", "<pause/>", "<pause ms=\"x\"/>", "<rate value=\"0\">a</rate>", "a <pause"] {
+            assert!(markup_to_syllables(bad, Language::English).is_err(), "{}", bad);
+        }
+    }
+
     #[test]
     fn test_word_boundaries() {
         let syls = text_to_syllables("hello world");
@@ -0,0 +1,182 @@
+//! Per-run scratch directories under the system temp dir.
+//!
+//! Work that has no run directory of its own (the GUI's alignment-only
+//! bank builds) gets a fresh directory per run, so runs never share or
+//! overwrite each other's files. Old directories are pruned, oldest
+//! first, once together they pass a size cap, and `glottisdale clean
+//! --temp` removes them all.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+/// Total size of scratch directories kept before old ones are pruned.
+pub const DEFAULT_TEMP_CAP_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+/// Directories touched more recently than this are left alone when
+/// pruning, as another run may still be using them.
+pub const IN_USE_AGE: Duration = Duration::from_secs(60 * 60);
+
+/// Fixed directory older versions shared between alignment runs.
+const LEGACY_ALIGNMENT_DIR: &str = "glottisdale-alignment";
+
+/// Directory holding the scratch directories.
+///
+/// Uses `GLOTTISDALE_TEMP_DIR` if set, otherwise `glottisdale` in the
+/// system temp dir.
+pub fn temp_root() -> PathBuf {
+    match std::env::var_os("GLOTTISDALE_TEMP_DIR") {
+        Some(dir) => PathBuf::from(dir),
+        None => std::env::temp_dir().join("glottisdale"),
+    }
+}
+
+/// Create a new, empty scratch directory named after `kind`, e.g.
+/// `alignment-3f2a...`.
+pub fn create_temp_dir(kind: &str) -> Result<PathBuf> {
+    let dir = temp_root().join(format!("{}-{}", kind, uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create temp directory: {}", dir.display()))?;
+    Ok(dir)
+}
+
+/// A scratch directory and how much it holds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempDir {
+    pub path: PathBuf,
+    pub bytes: u64,
+    pub modified: SystemTime,
+}
+
+/// What a prune or clean removed and left.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CleanReport {
+    pub removed: Vec<PathBuf>,
+    pub freed_bytes: u64,
+    pub kept_bytes: u64,
+}
+
+/// Bytes in the files under `path`.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            _ => entry.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .sum()
+}
+
+/// Whether `path` is named like a directory from [`create_temp_dir`], so
+/// nothing else in a shared `GLOTTISDALE_TEMP_DIR` is ever removed.
+fn is_scratch_dir(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    name.rsplit_once('-')
+        .is_some_and(|(kind, id)| !kind.is_empty() && id.len() == 32 && id.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Scratch directories in `root`, plus the legacy shared directory beside
+/// it if present, oldest first.
+fn list_dirs(root: &Path, legacy: Option<PathBuf>) -> Vec<TempDir> {
+    let children = std::fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|path| is_scratch_dir(path));
+    let mut dirs: Vec<TempDir> = children
+        .chain(legacy)
+        .filter(|path| path.is_dir())
+        .map(|path| TempDir {
+            bytes: dir_size(&path),
+            modified: std::fs::metadata(&path).and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH),
+            path,
+        })
+        .collect();
+    dirs.sort_by_key(|d| d.modified);
+    dirs
+}
+
+/// Every scratch directory, oldest first.
+pub fn list_temp_dirs() -> Vec<TempDir> {
+    list_dirs(&temp_root(), Some(std::env::temp_dir().join(LEGACY_ALIGNMENT_DIR)))
+}
+
+/// Remove the oldest of `dirs` until the rest fit in `cap_bytes` (all of
+/// them without a cap), skipping any modified within `min_age`. With
+/// `dry_run` nothing is deleted.
+fn prune(mut dirs: Vec<TempDir>, cap_bytes: Option<u64>, min_age: Duration, dry_run: bool) -> Result<CleanReport> {
+    dirs.sort_by_key(|d| d.modified);
+    let mut total: u64 = dirs.iter().map(|d| d.bytes).sum();
+    let now = SystemTime::now();
+    let mut report = CleanReport::default();
+    for dir in dirs {
+        let age = now.duration_since(dir.modified).unwrap_or_default();
+        if cap_bytes.is_some_and(|cap| total <= cap) || age < min_age {
+            continue;
+        }
+        if !dry_run {
+            std::fs::remove_dir_all(&dir.path)
+                .with_context(|| format!("Failed to remove {}", dir.path.display()))?;
+        }
+        total -= dir.bytes;
+        report.freed_bytes += dir.bytes;
+        report.removed.push(dir.path);
+    }
+    report.kept_bytes = total;
+    Ok(report)
+}
+
+/// Remove old scratch directories until the rest fit in `cap_bytes`.
+/// Directories in use by a recent run are kept even over the cap.
+pub fn prune_temp_dirs(cap_bytes: u64) -> Result<CleanReport> {
+    prune(list_temp_dirs(), Some(cap_bytes), IN_USE_AGE, false)
+}
+
+/// Remove every scratch directory (with `dry_run`, only report them).
+pub fn clean_temp_dirs(dry_run: bool) -> Result<CleanReport> {
+    prune(list_temp_dirs(), None, Duration::ZERO, dry_run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_removes_oldest_over_cap() {
+        let root = std::env::temp_dir().join(format!("glottisdale_temp_prune_{}", std::process::id()));
+        let [a, b, c, d] = ["a", "b", "c", "d"].map(|id| root.join(format!("alignment-{}", id.repeat(32))));
+        for (dir, bytes) in [(&a, 300), (&b, 200), (&c, 100)] {
+            std::fs::create_dir_all(dir.join("sub")).unwrap();
+            std::fs::write(dir.join("sub").join("audio.wav"), vec![0u8; bytes]).unwrap();
+        }
+        // Only directories named like scratch directories are listed
+        std::fs::create_dir_all(root.join("keep-me")).unwrap();
+        std::fs::create_dir_all(&d).unwrap();
+        let mut dirs = list_dirs(&root, None);
+        assert_eq!(dirs.len(), 4);
+        assert_eq!(dirs.iter().map(|d| d.bytes).sum::<u64>(), 600);
+        // Set ages explicitly rather than rely on file system timestamps
+        dirs.sort_by_key(|d| d.path.clone());
+        for (dir, age) in dirs.iter_mut().zip([30, 20, 10, 40]) {
+            dir.modified = SystemTime::now() - Duration::from_secs(age);
+        }
+
+        // Without a cap, even the empty one goes
+        let report = prune(dirs.clone(), None, Duration::from_secs(15), true).unwrap();
+        assert_eq!(report.removed.len(), 3);
+        assert_eq!(report.kept_bytes, 100);
+        assert!(a.exists());
+
+        let report = prune(dirs, Some(350), Duration::ZERO, false).unwrap();
+        assert_eq!(report.removed, vec![d, a.clone()]);
+        assert_eq!(report.freed_bytes, 300);
+        assert!(!a.exists());
+        assert!(b.exists() && c.exists());
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
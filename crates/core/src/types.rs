@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
+use crate::source::SourceId;
+
 /// A single phoneme with timing information.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Phoneme {
@@ -34,8 +36,8 @@ pub struct Clip {
     pub start: f64,
     /// End time with padding applied (seconds)
     pub end: f64,
-    /// Input filename
-    pub source: String,
+    /// Input file
+    pub source: SourceId,
     #[serde(default)]
     pub output_path: PathBuf,
 }
@@ -119,7 +121,7 @@ mod tests {
             source: "test.wav".into(),
             output_path: PathBuf::new(),
         };
-        assert_eq!(clip.source, "test.wav");
+        assert_eq!(clip.source, SourceId::intern("test.wav"));
         assert!((clip.start - 0.075).abs() < f64::EPSILON);
     }
 
@@ -0,0 +1,305 @@
+//! Post finished runs to a Discord or Slack channel through an incoming
+//! webhook: a message with the run's stats and where its files are, plus,
+//! for Discord, the audio itself.
+//!
+//! Sending needs the `webhook` feature; without it [`post_run`] fails.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Largest file attached to a Discord post (bytes), the limit for
+/// servers without boosts. Bigger outputs are only named.
+pub const DISCORD_UPLOAD_LIMIT: u64 = 10 * 1024 * 1024;
+
+/// Name runs are posted under.
+const USERNAME: &str = "Glottisdale";
+
+/// A file to attach: its name and contents.
+#[cfg(feature = "webhook")]
+type Attachment = (String, Vec<u8>);
+
+/// The `[webhook]` table of the config file:
+///
+/// ```toml
+/// [webhook]
+/// url = "https://discord.com/api/webhooks/..."
+/// upload = true   # attach the audio (Discord only)
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// Incoming webhook URL runs are posted to.
+    pub url: Option<String>,
+    /// Attach output files where the service allows it.
+    #[serde(default = "default_upload")]
+    pub upload: bool,
+}
+
+fn default_upload() -> bool {
+    true
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self { url: None, upload: default_upload() }
+    }
+}
+
+/// Which service a webhook URL belongs to, deciding the payload shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookKind {
+    Discord,
+    Slack,
+    /// Anything else gets a plain JSON description of the run.
+    Generic,
+}
+
+impl WebhookKind {
+    pub fn detect(url: &str) -> Self {
+        let host = url.split("://").nth(1).unwrap_or(url).split(['/', '?']).next().unwrap_or("");
+        if host.ends_with("discord.com") || host.ends_with("discordapp.com") {
+            Self::Discord
+        } else if host == "hooks.slack.com" {
+            Self::Slack
+        } else {
+            Self::Generic
+        }
+    }
+}
+
+/// What to say about a finished run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunPost {
+    /// Headline, e.g. "Collage run breathy-bassoon finished".
+    pub title: String,
+    /// One stat per line, e.g. "Seed: 1234".
+    pub lines: Vec<String>,
+    /// Output files, the main one first.
+    pub files: Vec<PathBuf>,
+}
+
+impl RunPost {
+    /// The post as chat text, with the headline in bold.
+    pub fn message(&self, kind: WebhookKind) -> String {
+        let bold = match kind {
+            WebhookKind::Slack => format!("*{}*", self.title),
+            _ => format!("**{}**", self.title),
+        };
+        let files = self.files.iter().map(|f| format!("`{}`", f.display()));
+        std::iter::once(bold).chain(self.lines.iter().cloned()).chain(files).collect::<Vec<_>>().join("\n")
+    }
+
+    /// JSON payload describing the run, without attachments.
+    pub fn payload(&self, kind: WebhookKind) -> Value {
+        let text = self.message(kind);
+        match kind {
+            WebhookKind::Discord => json!({ "username": USERNAME, "content": text }),
+            WebhookKind::Slack => json!({ "username": USERNAME, "text": text }),
+            WebhookKind::Generic => json!({
+                "text": text,
+                "title": self.title,
+                "lines": self.lines,
+                "files": self.files,
+            }),
+        }
+    }
+}
+
+/// A `multipart/form-data` body with `payload_json` and one part per
+/// file, as Discord takes attachments. Returns the body and its boundary.
+#[cfg(feature = "webhook")]
+fn multipart_body(payload: &Value, files: &[Attachment]) -> (Vec<u8>, String) {
+    let boundary = format!("glottisdale-{:016x}", rand::random::<u64>());
+    let mut body = Vec::new();
+    let mut part = |headers: String, data: &[u8]| {
+        body.extend_from_slice(format!("--{}\r\n{}\r\n\r\n", boundary, headers).as_bytes());
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    };
+    part(
+        "Content-Disposition: form-data; name=\"payload_json\"\r\nContent-Type: application/json".to_string(),
+        payload.to_string().as_bytes(),
+    );
+    for (i, (name, data)) in files.iter().enumerate() {
+        let name = name.replace(['"', '\r', '\n'], "_");
+        part(
+            format!(
+                "Content-Disposition: form-data; name=\"files[{}]\"; filename=\"{}\"\r\nContent-Type: application/octet-stream",
+                i, name
+            ),
+            data,
+        );
+    }
+    body.extend_from_slice(format!("--{}--\r\n", boundary).as_bytes());
+    (body, boundary)
+}
+
+/// Files of `post` small enough to attach on Discord, read into memory,
+/// and a note for each one left off.
+#[cfg(feature = "webhook")]
+fn discord_attachments(post: &RunPost) -> Result<(Vec<Attachment>, Vec<String>)> {
+    let mut attached = Vec::new();
+    let mut skipped = Vec::new();
+    for path in &post.files {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if std::fs::metadata(path)?.len() > DISCORD_UPLOAD_LIMIT {
+            skipped.push(format!("{} is too big to attach", name));
+            continue;
+        }
+        attached.push((name, std::fs::read(path)?));
+    }
+    Ok((attached, skipped))
+}
+
+/// Post `post` to the webhook at `url`, attaching its files when `upload`
+/// is set and the service takes attachments.
+#[cfg(feature = "webhook")]
+pub fn post_run(url: &str, post: &RunPost, upload: bool) -> Result<()> {
+    use anyhow::{bail, Context};
+
+    let kind = WebhookKind::detect(url);
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs(120))
+        .build()?;
+    let request = if kind == WebhookKind::Discord && upload && !post.files.is_empty() {
+        let (files, skipped) = discord_attachments(post)?;
+        let mut post = post.clone();
+        post.lines.extend(skipped);
+        let (body, boundary) = multipart_body(&post.payload(kind), &files);
+        client
+            .post(url)
+            .header("Content-Type", format!("multipart/form-data; boundary={}", boundary))
+            .body(body)
+    } else {
+        client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .body(post.payload(kind).to_string())
+    };
+    let response = request.send().context("Failed to reach the webhook")?;
+    let status = response.status();
+    if !status.is_success() {
+        let detail = response.text().unwrap_or_default();
+        bail!("Webhook answered {}: {}", status, detail.trim());
+    }
+    Ok(())
+}
+
+/// Without the `webhook` feature there is no HTTP client to post with.
+#[cfg(not(feature = "webhook"))]
+pub fn post_run(_url: &str, _post: &RunPost, _upload: bool) -> Result<()> {
+    anyhow::bail!("This build has no webhook support (built without the \"webhook\" feature)")
+}
+
+/// Format seconds of audio as `m:ss`.
+pub fn format_length(seconds: f64) -> String {
+    let secs = seconds.max(0.0).round() as u64;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// "Length: m:ss" for an audio file, if its length can be read.
+pub fn length_line(path: &Path) -> Option<String> {
+    crate::audio::io::probe_duration(path).ok().map(|s| format!("Length: {}", format_length(s)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn post() -> RunPost {
+        RunPost {
+            title: "Collage run breathy-bassoon finished".into(),
+            lines: vec!["Seed: 42".into(), "Clips: 31".into()],
+            files: vec![PathBuf::from("/runs/breathy-bassoon/breathy-bassoon.wav")],
+        }
+    }
+
+    #[test]
+    fn test_detect_kind() {
+        assert_eq!(WebhookKind::detect("https://discord.com/api/webhooks/1/abc"), WebhookKind::Discord);
+        assert_eq!(WebhookKind::detect("https://canary.discordapp.com/api/webhooks/1/abc"), WebhookKind::Discord);
+        assert_eq!(WebhookKind::detect("https://hooks.slack.com/services/T/B/X"), WebhookKind::Slack);
+        assert_eq!(WebhookKind::detect("https://example.com/?next=discord.com"), WebhookKind::Generic);
+    }
+
+    #[test]
+    fn test_payloads() {
+        let slack = post().payload(WebhookKind::Slack);
+        assert_eq!(
+            slack["text"],
+            "*Collage run breathy-bassoon finished*\nSeed: 42\nClips: 31\n`/runs/breathy-bassoon/breathy-bassoon.wav`"
+        );
+        let discord = post().payload(WebhookKind::Discord);
+        assert!(discord["content"].as_str().unwrap().starts_with("**Collage run"));
+        let generic = post().payload(WebhookKind::Generic);
+        assert_eq!(generic["lines"][1], "Clips: 31");
+        assert_eq!(generic["files"][0], "/runs/breathy-bassoon/breathy-bassoon.wav");
+    }
+
+    #[cfg(feature = "webhook")]
+    #[test]
+    fn test_multipart_body() {
+        let (body, boundary) = multipart_body(&json!({ "content": "hi" }), &[("a\"b.wav".into(), vec![1, 2, 3])]);
+        let text = String::from_utf8_lossy(&body);
+        assert!(text.starts_with(&format!("--{}\r\n", boundary)));
+        assert!(text.contains("name=\"payload_json\"\r\nContent-Type: application/json\r\n\r\n{\"content\":\"hi\"}\r\n"));
+        assert!(text.contains("name=\"files[0]\"; filename=\"a_b.wav\""));
+        assert!(body.windows(3).any(|w| w == [1, 2, 3]));
+        assert!(text.ends_with(&format!("--{}--\r\n", boundary)));
+    }
+
+    #[test]
+    fn test_format_length() {
+        assert_eq!(format_length(0.4), "0:00");
+        assert_eq!(format_length(75.6), "1:16");
+    }
+
+    /// Post to a one-shot local server and return the request it got.
+    #[cfg(feature = "webhook")]
+    fn capture_post(status: &str, url_path: &str, post: &RunPost, upload: bool) -> (Result<()>, String) {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}{}", listener.local_addr().unwrap(), url_path);
+        let status = status.to_string();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut head = String::new();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                head.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let length: usize = head
+                .lines()
+                .find_map(|l| l.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse().unwrap()))
+                .unwrap_or(0);
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            write!(reader.get_mut(), "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status).unwrap();
+            head + &String::from_utf8_lossy(&body)
+        });
+        let result = post_run(&url, post, upload);
+        (result, server.join().unwrap())
+    }
+
+    #[cfg(feature = "webhook")]
+    #[test]
+    fn test_post_run() {
+        let (result, request) = capture_post("204 No Content", "/hook", &post(), true);
+        result.unwrap();
+        assert!(request.starts_with("POST /hook HTTP/1.1"));
+        assert!(request.contains("content-type: application/json"));
+        assert!(request.contains("\"text\":\"**Collage run breathy-bassoon finished**"));
+
+        let (result, _) = capture_post("400 Bad Request", "/hook", &post(), true);
+        assert!(result.unwrap_err().to_string().contains("400"));
+    }
+}
@@ -1,14 +1,34 @@
 //! Main application state and UI layout.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use eframe::egui;
-use glottisdale_core::editor::pipeline_bridge::arrangement_blank_canvas;
-use glottisdale_core::editor::EditorPipelineMode;
-use glottisdale_core::types::Syllable;
+use glottisdale_core::audio::capture::{save_recording, Recorder, MAX_RECORDING_S};
+use glottisdale_core::audio::device::{BufferSize, BUFFER_SIZE_CHOICES};
+use glottisdale_core::audio::effects::{
+    DenoiseLevel, ReverbIr, RmsNormalization, RmsTarget, VoiceFx, DENOISE_LEVELS, REVERB_IRS, VOICE_FX,
+};
+use glottisdale_core::audio::io::{is_media_file, probe_duration, stereo_wav_path, SourceChannels, MEDIA_EXTENSIONS};
+use glottisdale_core::audio::loudness::{Mastering, DEFAULT_TARGET_LUFS};
+use glottisdale_core::collage::tempo::bpm_from_taps;
+use glottisdale_core::editor::pipeline_bridge::{arrangement_blank_canvas, arrangement_from_sing};
+use glottisdale_core::editor::{Arrangement, EditorPipelineMode, SyllableClip};
+use glottisdale_core::language::align::{Aligner, AlignerOverride, AlignmentProgress};
+use glottisdale_core::language::g2p::{Language, LANGUAGES};
+use glottisdale_core::language::syllabify::merge_into_words;
+use glottisdale_core::names::{available_themes, themes_dir, DEFAULT_THEME};
+use glottisdale_core::progress::{ProgressReporter, Stage};
+use glottisdale_core::sing::vocal_mapper::NoteMapping;
+use glottisdale_core::editor::playback_engine::PlaybackEngine;
+use glottisdale_core::speak::matcher::{MatchResult, DEFAULT_BEAM_WIDTH};
+use glottisdale_core::speak::target_text::{Prosody, TextSyllable};
+use glottisdale_core::types::{AlignmentResult, Syllable};
+use glottisdale_core::webhook::{length_line, post_run, RunPost, WebhookConfig};
 
 // ─── Pipeline mode ───────────────────────────────────────────────
 
@@ -39,6 +59,64 @@ impl PipelineMode {
 
 // ─── Processing status ──────────────────────────────────────────
 
+/// Warn in the top bar when a run has reported nothing for this long.
+const STALL_WARNING: Duration = Duration::from_secs(120);
+
+/// Log lines kept for the log panel; older lines are dropped first.
+const MAX_LOG_LINES: usize = 5000;
+
+/// One line in the log panel.
+#[derive(Debug, Clone)]
+struct LogLine {
+    level: log::Level,
+    text: String,
+}
+
+impl LogLine {
+    /// Level of a plain message: errors are prefixed "ERROR:", indented
+    /// lines are detail, everything else is info.
+    fn new(msg: &str) -> Self {
+        let level = if msg.starts_with("ERROR:") {
+            log::Level::Error
+        } else if msg.starts_with("WARNING:") {
+            log::Level::Warn
+        } else if msg.starts_with(' ') {
+            log::Level::Debug
+        } else {
+            log::Level::Info
+        };
+        Self { level, text: msg.to_string() }
+    }
+}
+
+/// What the log panel shows.
+struct LogFilter {
+    error: bool,
+    warn: bool,
+    info: bool,
+    debug: bool,
+    /// Case-insensitive substring; empty shows everything.
+    search: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self { error: true, warn: true, info: true, debug: true, search: String::new() }
+    }
+}
+
+impl LogFilter {
+    fn matches(&self, line: &LogLine) -> bool {
+        let level_shown = match line.level {
+            log::Level::Error => self.error,
+            log::Level::Warn => self.warn,
+            log::Level::Info => self.info,
+            log::Level::Debug | log::Level::Trace => self.debug,
+        };
+        level_shown && (self.search.is_empty() || line.text.to_lowercase().contains(&self.search.to_lowercase()))
+    }
+}
+
 #[derive(Debug, Clone)]
 enum ProcessingStatus {
     Idle,
@@ -53,7 +131,126 @@ enum ProcessingStatus {
 struct AlignmentData {
     syllables: HashMap<String, Vec<Syllable>>,
     audio: HashMap<String, (Vec<f64>, u32)>,
+    /// Planar audio of the sources a stereo run kept stereo, keyed as `audio`.
+    stereo: SourceChannels,
     pipeline_mode: EditorPipelineMode,
+    /// Note mapping from a sing run, so the editor can lay out its melody.
+    melody: Option<SingMelody>,
+}
+
+impl AlignmentData {
+    /// Key a source has in the editor bank: stereo sources point at their
+    /// stereo extraction, so a project saved without audio reloads in stereo.
+    fn editor_key(&self, key: &str) -> String {
+        if self.stereo.contains_key(key) {
+            stereo_wav_path(Path::new(key)).to_string_lossy().to_string()
+        } else {
+            key.to_string()
+        }
+    }
+
+    /// Syllables and planar audio for the editor bank, under `editor_key`.
+    fn editor_sources(&self) -> (HashMap<String, Vec<Syllable>>, SourceChannels) {
+        let syllables = self
+            .syllables
+            .iter()
+            .map(|(key, syllables)| (self.editor_key(key), syllables.clone()))
+            .collect();
+        let audio = self
+            .audio
+            .iter()
+            .map(|(key, (samples, sr))| {
+                let audio = self.stereo.get(key).cloned().unwrap_or_else(|| (vec![samples.clone()], *sr));
+                (self.editor_key(key), audio)
+            })
+            .collect();
+        (syllables, audio)
+    }
+}
+
+/// How a sing run mapped its pooled syllables onto the melody.
+struct SingMelody {
+    /// Source keys in the order their syllables were pooled.
+    source_order: Vec<String>,
+    mappings: Vec<NoteMapping>,
+    median_f0: f64,
+}
+
+/// Settings a speak run was assembled with, kept so the run can be
+/// assembled again after swapping matches.
+#[derive(Clone)]
+struct SpeakTiming {
+    word_bounds: Vec<usize>,
+    avg_dur: f64,
+    reference_timings: Option<Vec<(f64, f64)>>,
+    prosody: Vec<Prosody>,
+    timing_strictness: f64,
+    crossfade_ms: f64,
+    normalize_volume: Option<RmsNormalization>,
+    pitch_correct: bool,
+}
+
+/// A finished syllable-mode speak run whose matches can be swapped for
+/// alternates before assembling again.
+#[derive(Clone)]
+struct SpeakReview {
+    targets: Vec<TextSyllable>,
+    /// The match the run picked for each target syllable.
+    matches: Vec<MatchResult>,
+    /// Closest bank entries for each target syllable, best first.
+    alternates: Vec<Vec<MatchResult>>,
+    /// Index into `options(i)` picked for each target syllable.
+    chosen: Vec<usize>,
+    timing: SpeakTiming,
+    run_dir: PathBuf,
+}
+
+impl SpeakReview {
+    fn new(
+        targets: Vec<TextSyllable>,
+        matches: Vec<MatchResult>,
+        alternates: Vec<Vec<MatchResult>>,
+        timing: SpeakTiming,
+        run_dir: PathBuf,
+    ) -> Self {
+        let chosen = vec![0; matches.len()];
+        Self { targets, matches, alternates, chosen, timing, run_dir }
+    }
+
+    /// Candidates for target syllable `i`: the run's match, then the
+    /// alternates that use a different source syllable.
+    fn options(&self, i: usize) -> Vec<&MatchResult> {
+        let mut options = vec![&self.matches[i]];
+        for alt in &self.alternates[i] {
+            let same = |m: &&MatchResult| {
+                m.entry.source == alt.entry.source && m.entry.index == alt.entry.index
+            };
+            if !options.iter().any(same) {
+                options.push(alt);
+            }
+        }
+        options
+    }
+
+    /// The currently picked match for every target syllable.
+    fn chosen_matches(&self) -> Vec<MatchResult> {
+        (0..self.matches.len())
+            .map(|i| {
+                let options = self.options(i);
+                options[self.chosen[i].min(options.len() - 1)].clone()
+            })
+            .collect()
+    }
+
+    /// Target syllable range of each word.
+    fn words(&self) -> Vec<std::ops::Range<usize>> {
+        let bounds = &self.timing.word_bounds;
+        bounds
+            .iter()
+            .enumerate()
+            .map(|(w, &start)| start..bounds.get(w + 1).copied().unwrap_or(self.targets.len()))
+            .collect()
+    }
 }
 
 // ─── Shared processing state ────────────────────────────────────
@@ -61,28 +258,77 @@ struct AlignmentData {
 #[derive(Clone)]
 struct ProcessingState {
     status: Arc<Mutex<ProcessingStatus>>,
-    log_lines: Arc<Mutex<Vec<String>>>,
+    log_lines: Arc<Mutex<VecDeque<LogLine>>>,
     /// Output file paths parsed from CLI stdout (e.g. "Output: path/to/file.wav")
     output_paths: Arc<Mutex<Vec<(String, PathBuf)>>>,
     /// Alignment data from the most recent pipeline run (for editor).
     alignment: Arc<Mutex<Option<Arc<AlignmentData>>>>,
     /// When true, automatically open the editor on next frame.
     auto_open_editor: Arc<Mutex<bool>>,
+    /// Request to open an editor that receives streamed bank clips.
+    stream_editor: Arc<Mutex<Option<EditorPipelineMode>>>,
+    /// Bank clips from partial alignment results, not yet shown.
+    partial_clips: Arc<Mutex<Vec<SyllableClip>>>,
+    /// Latest pipeline progress: (stage name, overall fraction).
+    progress: Arc<Mutex<Option<(String, f64)>>>,
+    /// When the pipeline last reported status, log, or progress.
+    last_activity: Arc<Mutex<Instant>>,
+    /// Matches and alternates from the last syllable-mode speak run.
+    speak_review: Arc<Mutex<Option<SpeakReview>>>,
+    /// Rolling preview the running collage writes while assembling.
+    preview: Arc<Mutex<Option<PathBuf>>>,
+    /// Set by Stop; the pipeline checks it between steps.
+    cancel: Arc<AtomicBool>,
 }
 
 impl ProcessingState {
     fn new() -> Self {
         Self {
             status: Arc::new(Mutex::new(ProcessingStatus::Idle)),
-            log_lines: Arc::new(Mutex::new(Vec::new())),
+            log_lines: Arc::new(Mutex::new(VecDeque::new())),
             output_paths: Arc::new(Mutex::new(Vec::new())),
             alignment: Arc::new(Mutex::new(None)),
             auto_open_editor: Arc::new(Mutex::new(false)),
+            stream_editor: Arc::new(Mutex::new(None)),
+            partial_clips: Arc::new(Mutex::new(Vec::new())),
+            progress: Arc::new(Mutex::new(None)),
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            speak_review: Arc::new(Mutex::new(None)),
+            preview: Arc::new(Mutex::new(None)),
+            cancel: Arc::new(AtomicBool::new(false)),
         }
     }
 
     fn set_status(&self, status: ProcessingStatus) {
         *self.status.lock().unwrap() = status;
+        self.touch();
+    }
+
+    /// Note that the pipeline is alive.
+    fn touch(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Time since the pipeline last reported anything.
+    fn idle_for(&self) -> Duration {
+        self.last_activity.lock().unwrap().elapsed()
+    }
+
+    /// Record how a pipeline thread ended. A panic, or returning without
+    /// setting a final status, fails the run so the Run buttons come back.
+    fn thread_finished(&self, name: &str, result: thread::Result<()>) {
+        match result {
+            Err(payload) => {
+                let msg = crate::crash::payload_message(payload.as_ref());
+                self.add_log(&format!("ERROR: {} thread crashed: {}", name, msg));
+                self.set_status(ProcessingStatus::Error(format!("Crashed: {}", msg)));
+            }
+            Ok(()) if matches!(self.get_status(), ProcessingStatus::Running(_)) => {
+                self.add_log(&format!("ERROR: {} thread exited without finishing", name));
+                self.set_status(ProcessingStatus::Error("Pipeline stopped unexpectedly".into()));
+            }
+            Ok(()) => {}
+        }
     }
 
     fn get_status(&self) -> ProcessingStatus {
@@ -90,17 +336,38 @@ impl ProcessingState {
     }
 
     fn add_log(&self, msg: &str) {
-        self.log_lines.lock().unwrap().push(msg.to_string());
+        let mut lines = self.log_lines.lock().unwrap();
+        if lines.len() == MAX_LOG_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(LogLine::new(msg));
+        drop(lines);
+        self.touch();
     }
 
-    fn get_logs(&self) -> Vec<String> {
-        self.log_lines.lock().unwrap().clone()
+    fn get_logs(&self) -> Vec<LogLine> {
+        self.log_lines.lock().unwrap().iter().cloned().collect()
     }
 
     fn add_output(&self, label: &str, path: PathBuf) {
         self.output_paths.lock().unwrap().push((label.to_string(), path));
     }
 
+    /// List a finished output, logging the loudness its pipeline
+    /// recorded in the run manifest.
+    fn finish_output(&self, label: &str, run_dir: &Path, wav: PathBuf) {
+        let name = wav.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let manifest: Option<serde_json::Value> = std::fs::read_to_string(run_dir.join("manifest.json"))
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok());
+        if let Some(report) = manifest.as_ref().map(|m| &m["loudness"][&name]) {
+            if let (Some(lufs), Some(gain_db)) = (report["measured_lufs"].as_f64(), report["gain_db"].as_f64()) {
+                self.add_log(&format!("{}: {:.1} LUFS, gain {:+.1} dB", label, lufs, gain_db));
+            }
+        }
+        self.add_output(label, wav);
+    }
+
     fn get_outputs(&self) -> Vec<(String, PathBuf)> {
         self.output_paths.lock().unwrap().clone()
     }
@@ -111,6 +378,29 @@ impl ProcessingState {
         self.output_paths.lock().unwrap().clear();
         *self.alignment.lock().unwrap() = None;
         *self.auto_open_editor.lock().unwrap() = false;
+        *self.stream_editor.lock().unwrap() = None;
+        self.partial_clips.lock().unwrap().clear();
+        *self.progress.lock().unwrap() = None;
+        *self.speak_review.lock().unwrap() = None;
+        *self.preview.lock().unwrap() = None;
+        self.cancel.store(false, Ordering::Relaxed);
+    }
+
+    fn set_preview(&self, path: PathBuf) {
+        *self.preview.lock().unwrap() = Some(path);
+    }
+
+    /// The running collage's preview, once it has been written.
+    fn get_preview(&self) -> Option<PathBuf> {
+        self.preview.lock().unwrap().clone().filter(|p| p.is_file())
+    }
+
+    fn request_cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    fn store_speak_review(&self, review: SpeakReview) {
+        *self.speak_review.lock().unwrap() = Some(review);
     }
 
     fn store_alignment(&self, data: AlignmentData) {
@@ -138,6 +428,86 @@ impl ProcessingState {
             false
         }
     }
+
+    fn request_stream_editor(&self, mode: EditorPipelineMode) {
+        *self.stream_editor.lock().unwrap() = Some(mode);
+    }
+
+    fn take_stream_editor(&self) -> Option<EditorPipelineMode> {
+        self.stream_editor.lock().unwrap().take()
+    }
+
+    fn push_partial_clips(&self, clips: Vec<SyllableClip>) {
+        self.partial_clips.lock().unwrap().extend(clips);
+    }
+
+    fn take_partial_clips(&self) -> Vec<SyllableClip> {
+        std::mem::take(&mut *self.partial_clips.lock().unwrap())
+    }
+
+    fn get_progress(&self) -> Option<(String, f64)> {
+        self.progress.lock().unwrap().clone()
+    }
+}
+
+impl ProgressReporter for ProcessingState {
+    fn report(&self, stage: &str, fraction: f64) {
+        *self.progress.lock().unwrap() = Some((stage.to_string(), fraction));
+        self.touch();
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+/// Align sources in parallel, each with its aligner from `aligners`,
+/// reporting their combined share of the alignment stage. Results come
+/// back in input order.
+fn align_sources(
+    aligners: &[Box<dyn Aligner>],
+    audio_paths: &[PathBuf],
+    state: &ProcessingState,
+    min_quality: f64,
+) -> anyhow::Result<Vec<AlignmentResult>> {
+    use rayon::prelude::*;
+
+    let fractions = Arc::new(Mutex::new(vec![0.0; audio_paths.len()]));
+    state.stage(Stage::Alignment, 0.0);
+    audio_paths
+        .par_iter()
+        .enumerate()
+        .map(|(i, audio_path)| {
+            state.add_log(&format!("Aligning: {}", audio_path.file_name().unwrap().to_string_lossy()));
+            let progress_state = state.clone();
+            let fractions = Arc::clone(&fractions);
+            let mut alignment = aligners[i].process_with_progress(
+                audio_path,
+                None,
+                Box::new(move |event| {
+                    if let AlignmentProgress::Fraction(f) = event {
+                        let mut fractions = fractions.lock().unwrap();
+                        fractions[i] = f;
+                        let overall = fractions.iter().sum::<f64>() / fractions.len() as f64;
+                        progress_state.stage(Stage::Alignment, overall);
+                    }
+                }),
+            )?;
+            if min_quality > 0.0 {
+                let (samples, sr) = glottisdale_core::audio::io::read_wav(audio_path)?;
+                let dropped = glottisdale_core::audio::analysis::retain_quality_syllables(
+                    &mut alignment.syllables,
+                    &samples,
+                    sr,
+                    min_quality,
+                );
+                if dropped > 0 {
+                    state.add_log(&format!("  Dropped {} low-quality syllables", dropped));
+                }
+            }
+            Ok(alignment)
+        })
+        .collect()
 }
 
 // ─── Collage settings ───────────────────────────────────────────
@@ -161,7 +531,12 @@ struct CollageSettings {
     breaths: bool,
     breath_probability: f64,
     volume_normalize: bool,
+    volume_normalization: RmsNormalization,
     prosodic_dynamics: bool,
+    intonation: bool,
+    question_probability: f64,
+    /// Voice effect preset name; empty for none.
+    voice_fx: String,
     // Stretch
     speed: String,
     random_stretch: String,
@@ -175,6 +550,27 @@ struct CollageSettings {
     // Stutter
     stutter: String,
     stutter_count: String,
+    // Stereo
+    stereo: bool,
+    pan_spread: f64,
+    // Anchors (comma-separated)
+    anchor_words: String,
+    anchor_sentence_start: bool,
+    // Sampling bias ("" = none)
+    prefer_duration: String,
+    prefer_energy: String,
+    /// Syllable order within words: "phonotactic" or "markov"
+    word_model: String,
+    /// Fewest syllables between two uses of one source syllable (0 = no limit)
+    min_reuse_distance: usize,
+    /// Per-source sampling weight (missing = 1.0)
+    source_weights: HashMap<PathBuf, f64>,
+    // Beat grid ("" = off)
+    bpm: String,
+    beat_subdivision: usize,
+    swing: f64,
+    /// Tap-tempo button presses (egui input time, seconds)
+    tap_times: Vec<f64>,
 }
 
 impl Default for CollageSettings {
@@ -196,7 +592,11 @@ impl Default for CollageSettings {
             breaths: true,
             breath_probability: 0.6,
             volume_normalize: true,
+            volume_normalization: RmsNormalization::default(),
             prosodic_dynamics: true,
+            intonation: true,
+            question_probability: 0.0,
+            voice_fx: String::new(),
             speed: String::new(),
             random_stretch: String::new(),
             alternating_stretch: String::new(),
@@ -207,6 +607,19 @@ impl Default for CollageSettings {
             repeat_count: "1-2".to_string(),
             stutter: String::new(),
             stutter_count: "1-2".to_string(),
+            stereo: false,
+            pan_spread: 0.0,
+            anchor_words: String::new(),
+            anchor_sentence_start: false,
+            prefer_duration: String::new(),
+            prefer_energy: String::new(),
+            word_model: "phonotactic".to_string(),
+            min_reuse_distance: 8,
+            source_weights: HashMap::new(),
+            bpm: String::new(),
+            beat_subdivision: 2,
+            swing: 0.0,
+            tap_times: Vec::new(),
         }
     }
 }
@@ -215,21 +628,96 @@ impl Default for CollageSettings {
 
 #[derive(Debug, Clone)]
 struct SingSettings {
-    midi_dir: String,
+    /// MIDI file, or a directory with melody.mid plus backing files
+    midi_path: String,
+    /// 1-based part of a single MIDI file; empty = pick automatically
+    melody_track: String,
+    /// Lyrics to sing; empty = random syllables
+    lyrics: String,
+    /// TOML note pins; empty = no pinned notes
+    pins: String,
+    /// TOML drum map; empty = built-in kit
+    drum_map: String,
+    /// SF2 soundfont for the backing; empty = sine tones
+    soundfont: String,
     target_duration: f64,
     vibrato: bool,
+    vibrato_depth: f64,
+    vibrato_rate: f64,
+    /// Time into a held syllable before vibrato fades in (ms)
+    vibrato_onset: f64,
+    /// Wet/dry mix of vibrato and chorus, 0-1, and their output gains (dB)
+    vibrato_mix: f64,
+    vibrato_gain: f64,
     chorus: bool,
+    chorus_voices: usize,
+    /// Detune range in cents: "10-15" or "12"
+    chorus_detune: String,
+    /// Copy delay range in ms: "15-30" or "20"
+    chorus_delay: String,
+    chorus_mix: f64,
+    chorus_gain: f64,
+    /// Fade-out tail past a note's end into a rest (ms)
+    release: f64,
+    /// Rests up to this long are filled by holding the note (ms)
+    legato_gap: f64,
     drift_range: f64,
+    /// Notes shorter than this are short (s)
+    short_note: f64,
+    /// Notes at least this long are long (s)
+    long_note: f64,
+    /// Syllables per note, picked at random: "1" or "1,1,2"
+    short_syllables: String,
+    medium_syllables: String,
+    long_syllables: String,
+    /// "loop", "truncate", or "once"
+    loop_mode: String,
+    harmony: bool,
+    harmony_voices: u8,
+    /// Chord MIDI for harmony; empty = most chordal backing part
+    chords: String,
+    /// Max late entry of each harmony note (ms)
+    harmony_jitter: f64,
+    /// Keep the stereo image of stereo sources
+    stereo: bool,
 }
 
 impl Default for SingSettings {
     fn default() -> Self {
         Self {
-            midi_dir: String::new(),
+            midi_path: String::new(),
+            melody_track: String::new(),
+            lyrics: String::new(),
+            pins: String::new(),
+            drum_map: String::new(),
+            soundfont: String::new(),
             target_duration: 30.0,
             vibrato: true,
+            vibrato_depth: 50.0,
+            vibrato_rate: 5.5,
+            vibrato_onset: 300.0,
+            vibrato_mix: 1.0,
+            vibrato_gain: 0.0,
             chorus: true,
+            chorus_voices: 2,
+            chorus_detune: "10-15".to_string(),
+            chorus_delay: "15-30".to_string(),
+            chorus_mix: 1.0,
+            chorus_gain: 0.0,
+            release: 0.0,
+            legato_gap: 0.0,
             drift_range: 2.0,
+            short_note: 0.2,
+            long_note: 1.0,
+            short_syllables: "1".to_string(),
+            medium_syllables: "1,1,1,2,2,3".to_string(),
+            long_syllables: "1,2,2,3,3,4".to_string(),
+            loop_mode: "loop".to_string(),
+            harmony: false,
+            harmony_voices: 2,
+            chords: String::new(),
+            harmony_jitter: 30.0,
+            stereo: false,
         }
     }
 }
@@ -241,10 +729,14 @@ struct SpeakSettings {
     target_text: String,
     reference_path: String,
     match_unit: String,
+    /// "greedy", "viterbi", or "beam"
+    match_strategy: String,
+    beam_width: usize,
     pitch_correct: bool,
     timing_strictness: f64,
     crossfade_ms: f64,
     normalize_volume: bool,
+    volume_normalization: RmsNormalization,
 }
 
 impl Default for SpeakSettings {
@@ -253,10 +745,13 @@ impl Default for SpeakSettings {
             target_text: String::new(),
             reference_path: String::new(),
             match_unit: "syllable".to_string(),
+            match_strategy: "viterbi".to_string(),
+            beam_width: DEFAULT_BEAM_WIDTH,
             pitch_correct: true,
             timing_strictness: 0.8,
             crossfade_ms: 10.0,
             normalize_volume: true,
+            volume_normalization: RmsNormalization::default(),
         }
     }
 }
@@ -267,12 +762,44 @@ pub struct GlottisdaleApp {
     mode: PipelineMode,
     // Source files
     source_files: Vec<PathBuf>,
+    /// Spectral-gate each source before alignment.
+    denoise: bool,
+    /// How hard `denoise` works.
+    denoise_level: DenoiseLevel,
+    /// Align only the detected speech in each source.
+    vad: bool,
+    /// Aligner choices for single sources, overriding `aligner`.
+    source_aligners: HashMap<PathBuf, AlignerOverride>,
+    /// Drop aligned syllables scoring below this quality (0 keeps all).
+    min_quality: f64,
+    /// Probed source durations in seconds (`None` = unknown), filled in
+    /// by a background thread as files are added.
+    source_durations: Arc<Mutex<HashMap<PathBuf, Option<f64>>>>,
     // Output
     output_dir: String,
     whisper_model: String,
+    /// Spoken language of the sources.
+    language: Language,
     seed: String,
     run_name: String,
+    /// Normalize final outputs to `target_lufs`; off = measure only.
+    loudness_normalize: bool,
+    target_lufs: f64,
+    /// Run final mixes through the peak limiter before writing.
+    limiter: bool,
+    /// Convolution reverb on final outputs (see `REVERB_IRS`; empty = none).
+    reverb: String,
+    /// Wet/dry balance of `reverb`.
+    reverb_mix: f64,
+    /// Word lists for generated run names (see `names::NameTheme::load`).
+    name_theme: String,
     aligner: String,
+    /// Alignment inference device: "auto", "cpu", or "cuda".
+    device: String,
+    /// Bank granularity for collage and the editor: "syllable" or "word".
+    unit: String,
+    /// Device buffer size for editor playback and recording.
+    audio_buffer: BufferSize,
     // Per-pipeline settings
     collage: CollageSettings,
     sing: SingSettings,
@@ -281,13 +808,37 @@ pub struct GlottisdaleApp {
     processing: ProcessingState,
     // UI state
     show_log: bool,
+    log_filter: LogFilter,
     /// Editor state (None = editor not open)
     editor: Option<crate::editor::EditorState>,
+    /// Microphone recording in progress (None = not recording)
+    recorder: Option<Recorder>,
+    /// Last recording error to display in the source panel
+    record_error: Option<String>,
+    /// Worker thread panics not yet dismissed
+    crashes: Vec<crate::crash::CrashNotice>,
+    /// Plays speak alternates; opened on first audition.
+    audition: Option<PlaybackEngine>,
+    /// Mode that started the run in progress, to report when it ends.
+    run_mode: Option<PipelineMode>,
+    /// Finished run the user has not seen yet.
+    run_toast: Option<crate::notify::RunToast>,
+    /// Also post a desktop notification when a run finishes unfocused.
+    os_notifications: bool,
+    /// Where finished runs are posted, from the config file; an empty URL
+    /// posts nothing.
+    webhook_url: String,
+    webhook: WebhookConfig,
+    /// Project opened from the File menu whose moved sources need locating.
+    relink: Option<crate::editor::relink::RelinkDialog>,
     // Branding textures
     icon_texture: egui::TextureHandle,
     banner_texture: egui::TextureHandle,
 }
 
+/// Sample rate used to show buffer latency before a device is open.
+const NOMINAL_DEVICE_RATE: u32 = 48000;
+
 fn default_output_dir() -> String {
     let home = std::env::var("HOME").unwrap_or_else(|_| ".".to_string());
     format!("{home}/Documents/Glottisdale")
@@ -358,30 +909,181 @@ impl GlottisdaleApp {
             "app-banner",
             include_bytes!("../assets/banner.jpg"),
         );
+        let webhook = glottisdale_core::config::Config::load()
+            .map(|config| config.webhook)
+            .unwrap_or_else(|e| {
+                log::warn!("{:#}", e);
+                WebhookConfig::default()
+            });
 
         Self {
             mode: PipelineMode::Collage,
             source_files: Vec::new(),
+            denoise: false,
+            denoise_level: DenoiseLevel::Light,
+            vad: false,
+            source_aligners: HashMap::new(),
+            min_quality: 0.0,
+            source_durations: Arc::new(Mutex::new(HashMap::new())),
             output_dir: default_output_dir(),
             whisper_model: "base".to_string(),
+            language: Language::English,
             seed: String::new(),
             run_name: String::new(),
+            loudness_normalize: true,
+            target_lufs: DEFAULT_TARGET_LUFS,
+            limiter: true,
+            reverb: String::new(),
+            reverb_mix: 0.2,
+            name_theme: DEFAULT_THEME.to_string(),
             aligner: "auto".to_string(),
+            device: "auto".to_string(),
+            unit: "syllable".to_string(),
+            audio_buffer: BufferSize::Default,
             collage: CollageSettings::default(),
             sing: SingSettings::default(),
             speak: SpeakSettings::default(),
             processing: ProcessingState::new(),
             show_log: false,
+            log_filter: LogFilter::default(),
             editor: None,
+            recorder: None,
+            record_error: None,
+            crashes: Vec::new(),
+            audition: None,
+            run_mode: None,
+            run_toast: None,
+            os_notifications: false,
+            webhook_url: webhook.url.clone().unwrap_or_default(),
+            webhook,
+            relink: None,
             icon_texture,
             banner_texture,
         }
     }
 
+    /// Start recording from the default input device.
+    fn start_recording(&mut self) {
+        match Recorder::start_with_buffer(self.audio_buffer) {
+            Ok(recorder) => {
+                self.recorder = Some(recorder);
+                self.record_error = None;
+            }
+            Err(e) => self.record_error = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Stop recording, save the take under the output dir, and add it as a source.
+    fn stop_recording(&mut self) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+        let secs = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let path = PathBuf::from(&self.output_dir)
+            .join("recordings")
+            .join(format!("recording-{}.wav", secs));
+        let result = recorder.finish().and_then(|(samples, sr)| {
+            save_recording(&samples, sr, &path)?;
+            Ok(samples.len() as f64 / sr as f64)
+        });
+        match result {
+            Ok(duration) => {
+                self.processing.add_log(&format!("Recorded {}", path.display()));
+                self.source_durations.lock().unwrap().insert(path.clone(), Some(duration));
+                self.source_files.push(path);
+            }
+            Err(e) => self.record_error = Some(format!("{:#}", e)),
+        }
+    }
+
+    /// Add source files, skipping ones already listed, and probe their
+    /// durations in the background.
+    fn add_sources(&mut self, ctx: &egui::Context, paths: Vec<PathBuf>) {
+        let mut added = Vec::new();
+        for p in paths {
+            if !self.source_files.contains(&p) {
+                self.source_files.push(p.clone());
+                added.push(p);
+            }
+        }
+        if added.is_empty() {
+            return;
+        }
+        let durations = Arc::clone(&self.source_durations);
+        let ctx = ctx.clone();
+        thread::spawn(move || {
+            for path in added {
+                let duration = probe_duration(&path).ok();
+                durations.lock().unwrap().insert(path, duration);
+                ctx.request_repaint();
+            }
+        });
+    }
+
     fn is_processing(&self) -> bool {
         matches!(self.processing.get_status(), ProcessingStatus::Running(_))
     }
 
+    /// Aligner and whisper model picks for individual sources; "(default)"
+    /// uses the run's settings.
+    fn source_aligners_ui(&mut self, ui: &mut egui::Ui) {
+        ui.collapsing("Per-Source Aligners", |ui| {
+            for (i, path) in self.source_files.iter().enumerate() {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                let current = self.source_aligners.get(path);
+                let mut aligner = current.map(|o| o.aligner.clone());
+                let mut model = current.and_then(|o| o.whisper_model.clone());
+                ui.label(name);
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt(("source_aligner", i))
+                        .selected_text(aligner.as_deref().unwrap_or("(default)"))
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut aligner, None, "(default)");
+                            for a in ["auto", "default", "bfa"] {
+                                ui.selectable_value(&mut aligner, Some(a.to_string()), a);
+                            }
+                        });
+                    if aligner.is_some() {
+                        egui::ComboBox::from_id_salt(("source_whisper_model", i))
+                            .selected_text(model.as_deref().unwrap_or("(default)"))
+                            .show_ui(ui, |ui| {
+                                ui.selectable_value(&mut model, None, "(default)");
+                                for m in ["tiny", "base", "small", "medium"] {
+                                    ui.selectable_value(&mut model, Some(m.to_string()), m);
+                                }
+                            });
+                    }
+                });
+                match aligner {
+                    Some(aligner) => {
+                        self.source_aligners.insert(
+                            path.clone(),
+                            AlignerOverride { file: path.clone(), aligner, whisper_model: model },
+                        );
+                    }
+                    None => {
+                        self.source_aligners.remove(path);
+                    }
+                }
+            }
+            if ui.small_button("Reset").clicked() {
+                self.source_aligners.clear();
+            }
+        });
+    }
+
+    /// Reverb (if one is picked) and loudness target (`None` to only
+    /// measure) for final outputs.
+    fn mastering(&self) -> Mastering {
+        Mastering {
+            reverb: ReverbIr::parse(&self.reverb).ok().map(|ir| (ir, self.reverb_mix)),
+            target_lufs: self.loudness_normalize.then_some(self.target_lufs),
+        }
+    }
+
     fn build_render_settings(&self) -> glottisdale_core::editor::render::RenderSettings {
         glottisdale_core::editor::render::RenderSettings {
             crossfade_ms: self.collage.crossfade_ms,
@@ -395,6 +1097,109 @@ impl GlottisdaleApp {
             breath_probability: self.collage.breath_probability,
             speed: self.collage.speed.parse::<f64>().ok(),
             seed: self.seed.parse::<u64>().ok(),
+            limiter: self.limiter,
+        }
+    }
+
+    /// Toast a run that ended while its workspace was out of sight, and
+    /// notify the desktop if the window is in the background.
+    fn report_finished_run(&mut self, ctx: &egui::Context, run_mode: PipelineMode, status: ProcessingStatus) {
+        let (ok, message) = match status {
+            ProcessingStatus::Done(message) => (true, message),
+            ProcessingStatus::Error(message) => (false, message),
+            _ => return,
+        };
+        if ok && !self.webhook_url.trim().is_empty() {
+            self.post_to_webhook(run_mode, &message);
+        }
+        let focused = ctx.input(|i| i.viewport().focused).unwrap_or(true);
+        if self.os_notifications && !focused {
+            crate::notify::notify_os(&format!("Glottisdale {} run", run_mode.label().to_lowercase()), &message);
+        }
+        let workspace_visible = self.mode == run_mode && self.editor.as_ref().is_none_or(|e| e.detached);
+        if workspace_visible && focused {
+            return;
+        }
+        self.run_toast = Some(crate::notify::RunToast {
+            pipeline: run_mode.label().to_string(),
+            message,
+            ok,
+            output: self.processing.get_outputs().first().map(|(_, path)| path.clone()),
+            editable: ok && self.processing.has_alignment(),
+            shown_at: ctx.input(|i| i.time),
+        });
+    }
+
+    /// Post a finished run's outputs to the webhook in the background,
+    /// logging if it fails.
+    fn post_to_webhook(&self, run_mode: PipelineMode, message: &str) {
+        let files: Vec<PathBuf> = self.processing.get_outputs().into_iter().map(|(_, path)| path).collect();
+        let run_name = files
+            .first()
+            .and_then(|f| f.parent())
+            .and_then(|dir| dir.file_name())
+            .map(|name| format!(" {}", name.to_string_lossy()))
+            .unwrap_or_default();
+        let lines = std::iter::once(message.to_string())
+            .chain(parse_seed(&self.seed).map(|seed| format!("Seed: {}", seed)))
+            .chain(files.first().and_then(|f| length_line(f)))
+            .collect();
+        let post = RunPost { title: format!("{} run{} finished", run_mode.label(), run_name), lines, files };
+        let url = self.webhook_url.trim().to_string();
+        let upload = self.webhook.upload;
+        let processing = self.processing.clone();
+        thread::spawn(move || {
+            if let Err(e) = post_run(&url, &post, upload) {
+                processing.add_log(&format!("Webhook post failed: {:#}", e));
+            }
+        });
+    }
+
+    /// Play a running collage's preview, replacing any audition.
+    fn play_preview(&mut self, path: &Path) {
+        match glottisdale_core::audio::io::read_wav(path) {
+            Ok((samples, sr)) => {
+                let buffer = self.audio_buffer;
+                self.audition
+                    .get_or_insert_with(|| PlaybackEngine::with_buffer_size(buffer))
+                    .play_samples(samples, sr, 0.0);
+            }
+            Err(e) => self.processing.add_log(&format!("Failed to play preview: {:#}", e)),
+        }
+    }
+
+    /// Show the editor in its own OS window. Closing that window docks the
+    /// editor back into the main one rather than closing it.
+    fn show_detached_editor(&mut self, ctx: &egui::Context) {
+        let render_settings = self.build_render_settings();
+        let audio_buffer = self.audio_buffer;
+        let Some(editor_state) = self.editor.as_mut() else {
+            return;
+        };
+        let mut close = false;
+        ctx.show_viewport_immediate(
+            egui::ViewportId::from_hash_of("editor"),
+            egui::ViewportBuilder::default()
+                .with_title("Glottisdale Editor")
+                .with_inner_size([1200.0, 700.0]),
+            |ctx, class| {
+                if ctx.input(|i| i.viewport().close_requested()) {
+                    editor_state.detached = false;
+                }
+                editor_state.playback.set_buffer_size(audio_buffer);
+                let mut show = |ui: &mut egui::Ui| {
+                    close = crate::editor::show_editor(ui, editor_state, ctx, &render_settings);
+                };
+                // Backends without multi-window support embed it as a window
+                if class == egui::ViewportClass::Embedded {
+                    egui::Window::new("Editor").default_size([1000.0, 600.0]).show(ctx, |ui| show(ui));
+                } else {
+                    egui::CentralPanel::default().show(ctx, |ui| show(ui));
+                }
+            },
+        );
+        if close {
+            self.editor = None;
         }
     }
 }
@@ -406,12 +1211,87 @@ impl eframe::App for GlottisdaleApp {
             ctx.request_repaint();
         }
 
-        // Auto-open editor after alignment-only run
-        if self.editor.is_none() && self.processing.take_auto_open_editor() {
+        // Files dropped onto the window become sources; folders are scanned
+        let dropped: Vec<PathBuf> = ctx.input(|i| i.raw.dropped_files.iter().filter_map(|f| f.path.clone()).collect());
+        if !dropped.is_empty() {
+            let files = collect_media_files(&dropped);
+            if files.is_empty() {
+                self.processing.add_log("WARNING: dropped files contain no supported audio or video");
+            }
+            self.add_sources(ctx, files);
+        }
+        show_drop_overlay(ctx);
+
+        // Quitting with unsaved editor changes asks first
+        if ctx.input(|i| i.viewport().close_requested()) {
+            if let Some(editor) = self.editor.as_mut().filter(|e| !e.quit_confirmed) {
+                if !editor.confirm_discard(crate::editor::PendingClose::App) {
+                    ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+                }
+            }
+        }
+
+        self.crashes.extend(crate::crash::take_crashes());
+        show_crash_window(ctx, &mut self.crashes);
+
+        if let Some(dialog) = &mut self.relink {
+            match dialog.show(ctx) {
+                crate::editor::relink::RelinkOutcome::Loaded(arrangement) => {
+                    if let Some(editor) = &self.editor {
+                        editor.playback.stop();
+                    }
+                    self.editor = Some(crate::editor::EditorState::new(*arrangement));
+                    self.relink = None;
+                }
+                crate::editor::relink::RelinkOutcome::Cancelled => self.relink = None,
+                crate::editor::relink::RelinkOutcome::Pending => {}
+            }
+        }
+
+        // Runs that finish out of sight get a toast with quick actions
+        match self.processing.get_status() {
+            ProcessingStatus::Running(_) => {
+                self.run_mode.get_or_insert(self.mode);
+            }
+            status => {
+                if let Some(run_mode) = self.run_mode.take() {
+                    self.report_finished_run(ctx, run_mode, status);
+                }
+            }
+        }
+        let can_edit = self.editor.as_ref().is_none_or(|e| !e.has_unsaved_changes());
+        match crate::notify::show_run_toast(ctx, &mut self.run_toast, can_edit) {
+            Some(crate::notify::ToastAction::Play(path) | crate::notify::ToastAction::Open(path)) => open_path(&path),
+            Some(crate::notify::ToastAction::Edit) => try_open_editor_from_alignment(self),
+            None => {}
+        }
+
+        // Open an editor to browse the bank while alignment streams in
+        if let Some(mode) = self.processing.take_stream_editor() {
+            if self.editor.is_none() {
+                let mut editor = crate::editor::EditorState::new(Arrangement::new(16000, mode));
+                editor.bank_streaming = true;
+                self.editor = Some(editor);
+            }
+        }
+        if let Some(editor) = self.editor.as_mut().filter(|e| e.bank_streaming) {
+            let partial = self.processing.take_partial_clips();
+            if !partial.is_empty() {
+                editor.append_bank_clips(partial);
+            }
+            if matches!(self.processing.get_status(), ProcessingStatus::Error(_)) {
+                editor.bank_streaming = false;
+            }
+        }
+
+        // Auto-open editor (or finish the streamed bank) after alignment-only run
+        let streaming = self.editor.as_ref().is_some_and(|e| e.bank_streaming);
+        if (self.editor.is_none() || streaming) && self.processing.take_auto_open_editor() {
             try_open_editor_from_alignment(self);
         }
 
         // Top menu bar
+        let mut play_preview = None;
         egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 let icon_size = self.icon_texture.size_vec2() * (20.0 / self.icon_texture.size_vec2().y);
@@ -419,6 +1299,28 @@ impl eframe::App for GlottisdaleApp {
                 ui.label(egui::RichText::new("Glottisdale").strong());
                 ui.separator();
 
+                ui.menu_button("File", |ui| {
+                    if ui.button("Open Project...").clicked() {
+                        ui.close_menu();
+                        // An open editor with unsaved changes asks, then opens it itself
+                        let confirmed = self
+                            .editor
+                            .as_mut()
+                            .is_none_or(|e| e.confirm_discard(crate::editor::PendingClose::OpenProject));
+                        if confirmed {
+                            match crate::editor::open_project_dialog() {
+                                Some(crate::editor::ProjectOpen::Loaded(arrangement)) => {
+                                    self.editor = Some(crate::editor::EditorState::new(*arrangement));
+                                }
+                                Some(crate::editor::ProjectOpen::Relink(dialog)) => self.relink = Some(dialog),
+                                Some(crate::editor::ProjectOpen::Failed(e)) => self.processing.add_log(&e),
+                                None => {}
+                            }
+                        }
+                    }
+                });
+                ui.separator();
+
                 for mode in [PipelineMode::Collage, PipelineMode::Sing, PipelineMode::Speak] {
                     if ui.selectable_label(self.mode == mode, mode.label()).clicked() {
                         self.mode = mode;
@@ -431,8 +1333,39 @@ impl eframe::App for GlottisdaleApp {
                             ui.label("Ready");
                         }
                         ProcessingStatus::Running(msg) => {
-                            ui.spinner();
+                            if let Some(preview) = self.processing.get_preview() {
+                                if ui
+                                    .button("⏹ Stop")
+                                    .on_hover_text("Stop the run; its files so far stay in the run folder")
+                                    .clicked()
+                                {
+                                    self.processing.request_cancel();
+                                }
+                                if ui.button("▶ Preview").on_hover_text("Play the start of the collage so far").clicked() {
+                                    play_preview = Some(preview);
+                                }
+                            }
+                            match self.processing.get_progress() {
+                                Some((stage, fraction)) => {
+                                    ui.add(
+                                        egui::ProgressBar::new(fraction as f32)
+                                            .desired_width(160.0)
+                                            .text(format!("{} {:.0}%", stage, fraction * 100.0)),
+                                    );
+                                }
+                                None => {
+                                    ui.spinner();
+                                }
+                            }
                             ui.label(msg);
+                            let idle = self.processing.idle_for();
+                            if idle >= STALL_WARNING {
+                                ui.colored_label(
+                                    egui::Color32::YELLOW,
+                                    format!("no progress for {}m", idle.as_secs() / 60),
+                                )
+                                .on_hover_text("The run may be stuck; check the log for the last step");
+                            }
                         }
                         ProcessingStatus::Done(msg) => {
                             ui.colored_label(egui::Color32::GREEN, msg);
@@ -445,6 +1378,10 @@ impl eframe::App for GlottisdaleApp {
             });
         });
 
+        if let Some(path) = play_preview {
+            self.play_preview(&path);
+        }
+
         // Bottom status bar
         egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
             ui.horizontal(|ui| {
@@ -465,14 +1402,41 @@ impl eframe::App for GlottisdaleApp {
                 .min_height(100.0)
                 .default_height(150.0)
                 .show(ctx, |ui| {
-                    ui.heading("Log");
+                    let logs = self.processing.get_logs();
+                    let shown: Vec<&LogLine> = logs.iter().filter(|l| self.log_filter.matches(l)).collect();
+                    ui.horizontal(|ui| {
+                        ui.heading("Log");
+                        ui.separator();
+                        let filter = &mut self.log_filter;
+                        ui.toggle_value(&mut filter.error, "Error");
+                        ui.toggle_value(&mut filter.warn, "Warn");
+                        ui.toggle_value(&mut filter.info, "Info");
+                        ui.toggle_value(&mut filter.debug, "Debug");
+                        ui.separator();
+                        ui.add(egui::TextEdit::singleline(&mut filter.search).hint_text("Search").desired_width(160.0));
+                        if ui
+                            .add_enabled(!shown.is_empty(), egui::Button::new("Copy all"))
+                            .on_hover_text("Copy the lines shown to the clipboard")
+                            .clicked()
+                        {
+                            let text: Vec<&str> = shown.iter().map(|l| l.text.as_str()).collect();
+                            ui.ctx().copy_text(text.join("\n"));
+                        }
+                    });
                     egui::ScrollArea::vertical().stick_to_bottom(true).show(ui, |ui| {
-                        let logs = self.processing.get_logs();
-                        for line in &logs {
-                            ui.monospace(line);
+                        for line in &shown {
+                            let text = egui::RichText::new(&line.text).monospace();
+                            match line.level {
+                                log::Level::Error => ui.label(text.color(ui.visuals().error_fg_color)),
+                                log::Level::Warn => ui.label(text.color(ui.visuals().warn_fg_color)),
+                                log::Level::Info => ui.label(text),
+                                log::Level::Debug | log::Level::Trace => ui.label(text.weak()),
+                            };
                         }
                         if logs.is_empty() {
                             ui.weak("No log messages yet");
+                        } else if shown.is_empty() {
+                            ui.weak("No log messages match the filter");
                         }
                     });
                 });
@@ -487,23 +1451,61 @@ impl eframe::App for GlottisdaleApp {
                 ui.heading("Source Files");
                 ui.separator();
 
-                if ui.button("Add Files...").clicked() {
-                    if let Some(paths) = rfd::FileDialog::new()
-                        .add_filter("Audio/Video", &["wav", "mp3", "mp4", "mov", "mkv", "flac", "ogg", "m4a"])
-                        .pick_files()
+                ui.horizontal(|ui| {
+                    if ui.button("Add Files...").clicked() {
+                        if let Some(paths) = rfd::FileDialog::new()
+                            .add_filter("Audio/Video", MEDIA_EXTENSIONS)
+                            .pick_files()
+                        {
+                            self.add_sources(ctx, paths);
+                        }
+                    }
+                    let recording = self.recorder.is_some();
+                    let label = if recording { "⏹ Stop" } else { "⏺ Record" };
+                    if ui
+                        .button(label)
+                        .on_hover_text("Record from the default microphone and add the take as a source")
+                        .clicked()
                     {
-                        for p in paths {
-                            if !self.source_files.contains(&p) {
-                                self.source_files.push(p);
-                            }
+                        if recording {
+                            self.stop_recording();
+                        } else {
+                            self.start_recording();
                         }
                     }
+                });
+
+                if let Some(recorder) = &self.recorder {
+                    let elapsed = recorder.elapsed_s();
+                    let level = recorder.level();
+                    let error = recorder.error();
+                    ui.horizontal(|ui| {
+                        ui.colored_label(egui::Color32::RED, "●");
+                        ui.label(format!("{:.1}s", elapsed));
+                        let color = if level > 0.9 {
+                            egui::Color32::RED
+                        } else {
+                            egui::Color32::from_rgb(80, 180, 80)
+                        };
+                        ui.add(egui::ProgressBar::new(level).fill(color).desired_height(8.0));
+                    });
+                    ctx.request_repaint();
+                    if error.is_some() || elapsed >= MAX_RECORDING_S {
+                        self.stop_recording();
+                    }
+                }
+                if let Some(err) = &self.record_error {
+                    ui.colored_label(egui::Color32::RED, err);
                 }
 
                 ui.separator();
 
                 let mut to_remove = None;
+                let durations = self.source_durations.lock().unwrap().clone();
                 egui::ScrollArea::vertical().show(ui, |ui| {
+                    if self.source_files.is_empty() {
+                        ui.weak("Drop audio/video files or folders here");
+                    }
                     for (i, path) in self.source_files.iter().enumerate() {
                         ui.horizontal(|ui| {
                             let name = path
@@ -513,7 +1515,12 @@ impl eframe::App for GlottisdaleApp {
                             if ui.small_button("x").clicked() {
                                 to_remove = Some(i);
                             }
-                            ui.add(egui::Label::new(&name).truncate());
+                            match durations.get(path) {
+                                Some(Some(d)) => ui.weak(format_duration(*d)),
+                                Some(None) => ui.weak("?:??").on_hover_text("Duration not stored in the file header"),
+                                None => ui.weak("…"),
+                            };
+                            ui.add(egui::Label::new(&name).truncate()).on_hover_text(path.display().to_string());
                         });
                     }
                 });
@@ -526,6 +1533,30 @@ impl eframe::App for GlottisdaleApp {
                 if ui.button("Clear All").clicked() {
                     self.source_files.clear();
                 }
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.denoise, "De-noise sources")
+                        .on_hover_text("Reduce background hiss using a noise profile learned from each source's room tone");
+                    if self.denoise {
+                        egui::ComboBox::from_id_salt("denoise_level")
+                            .selected_text(self.denoise_level.name())
+                            .show_ui(ui, |ui| {
+                                for &name in DENOISE_LEVELS {
+                                    if let Ok(level) = DenoiseLevel::parse(name) {
+                                        ui.selectable_value(&mut self.denoise_level, level, name);
+                                    }
+                                }
+                            })
+                            .response
+                            .on_hover_text("Aggressive also filters out hum and rumble and gates harder, for hissy phone recordings");
+                    }
+                });
+                ui.checkbox(&mut self.vad, "Skip silence when aligning")
+                    .on_hover_text("Detect speech and align only that, in chunks of up to 5 minutes");
+                ui.horizontal(|ui| {
+                    ui.label("Min quality:");
+                    ui.add(egui::Slider::new(&mut self.min_quality, 0.0..=1.0).step_by(0.05))
+                        .on_hover_text("Drop syllables that are silent, clipped, pitch-jumping, or implausibly long or short (0 keeps all)");
+                });
             });
 
         // Right panel: settings for current mode
@@ -553,6 +1584,18 @@ impl eframe::App for GlottisdaleApp {
                                     }
                                 });
                         });
+                        ui.horizontal(|ui| {
+                            ui.label("Language:");
+                            egui::ComboBox::from_id_salt("language")
+                                .selected_text(self.language.name())
+                                .show_ui(ui, |ui| {
+                                    for code in LANGUAGES {
+                                        if let Ok(l) = Language::from_code(code) {
+                                            ui.selectable_value(&mut self.language, l, l.name());
+                                        }
+                                    }
+                                });
+                        });
                         ui.horizontal(|ui| {
                             ui.label("Aligner:");
                             egui::ComboBox::from_id_salt("aligner")
@@ -563,21 +1606,118 @@ impl eframe::App for GlottisdaleApp {
                                     }
                                 });
                         });
+                        if self.source_files.len() > 1 {
+                            self.source_aligners_ui(ui);
+                        }
+                        ui.horizontal(|ui| {
+                            ui.label("Device:");
+                            egui::ComboBox::from_id_salt("device")
+                                .selected_text(&self.device)
+                                .show_ui(ui, |ui| {
+                                    for d in glottisdale_core::language::align::DEVICES {
+                                        ui.selectable_value(&mut self.device, d.to_string(), *d);
+                                    }
+                                });
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Unit:");
+                            egui::ComboBox::from_id_salt("unit")
+                                .selected_text(&self.unit)
+                                .show_ui(ui, |ui| {
+                                    for u in ["syllable", "word"] {
+                                        ui.selectable_value(&mut self.unit, u.to_string(), u);
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Cut whole words instead of syllables (collage and editor bank)");
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Audio buffer:");
+                            egui::ComboBox::from_id_salt("audio_buffer")
+                                .selected_text(self.audio_buffer.label(NOMINAL_DEVICE_RATE))
+                                .show_ui(ui, |ui| {
+                                    let choices = std::iter::once(BufferSize::Default)
+                                        .chain(BUFFER_SIZE_CHOICES.iter().map(|&n| BufferSize::Frames(n)));
+                                    for b in choices {
+                                        ui.selectable_value(&mut self.audio_buffer, b, b.label(NOMINAL_DEVICE_RATE));
+                                    }
+                                })
+                                .response
+                                .on_hover_text(
+                                    "Smaller buffers make playback and scrubbing respond faster but may crackle \
+                                     on slow machines (latency shown at 48 kHz)",
+                                );
+                        });
                         ui.horizontal(|ui| {
                             ui.label("Seed:");
-                            ui.text_edit_singleline(&mut self.seed);
+                            ui.text_edit_singleline(&mut self.seed)
+                                .on_hover_text("A number, or a name like \"breathy-bassoon\" to derive one from");
                         });
                         ui.horizontal(|ui| {
                             ui.label("Run name:");
                             ui.text_edit_singleline(&mut self.run_name);
                         });
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut self.loudness_normalize, "Normalize loudness")
+                                .on_hover_text("Bring final outputs to one integrated loudness (EBU R128)");
+                            ui.add_enabled(
+                                self.loudness_normalize,
+                                egui::Slider::new(&mut self.target_lufs, -30.0..=-6.0).suffix(" LUFS"),
+                            );
+                        });
+                        ui.checkbox(&mut self.limiter, "Peak limiter")
+                            .on_hover_text("Hold final mixes under -1 dBFS instead of letting them hard-clip");
+                        ui.horizontal(|ui| {
+                            ui.label("Reverb:");
+                            egui::ComboBox::from_id_salt("reverb")
+                                .selected_text(if self.reverb.is_empty() { "none" } else { &self.reverb })
+                                .show_ui(ui, |ui| {
+                                    ui.selectable_value(&mut self.reverb, String::new(), "none");
+                                    for &name in REVERB_IRS {
+                                        ui.selectable_value(&mut self.reverb, name.to_string(), name);
+                                    }
+                                })
+                                .response
+                                .on_hover_text("Place final outputs in a room, hall, or spring tank");
+                            ui.add_enabled(
+                                !self.reverb.is_empty(),
+                                egui::Slider::new(&mut self.reverb_mix, 0.0..=1.0).text("mix"),
+                            );
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Name theme:");
+                            egui::ComboBox::from_id_salt("name_theme")
+                                .selected_text(&self.name_theme)
+                                .show_ui(ui, |ui| {
+                                    for t in available_themes() {
+                                        ui.selectable_value(&mut self.name_theme, t.clone(), t);
+                                    }
+                                })
+                                .response
+                                .on_hover_text(format!(
+                                    "Word lists for generated run names. Add your own as folders with \
+                                     adjectives.txt and nouns.txt in {}",
+                                    themes_dir().display()
+                                ));
+                        });
+                        ui.checkbox(&mut self.os_notifications, "Desktop notifications")
+                            .on_hover_text("Notify the desktop when a run finishes while Glottisdale is in the background");
+                        ui.horizontal(|ui| {
+                            ui.label("Webhook:");
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.webhook_url)
+                                    .hint_text("Discord or Slack webhook URL")
+                                    .desired_width(220.0),
+                            )
+                            .on_hover_text("Post each finished run here. Set a default under [webhook] in config.toml");
+                        });
                     });
 
                     ui.separator();
 
                     // Mode-specific settings
                     match self.mode {
-                        PipelineMode::Collage => show_collage_settings(ui, &mut self.collage),
+                        PipelineMode::Collage => show_collage_settings(ui, &mut self.collage, &self.source_files),
                         PipelineMode::Sing => show_sing_settings(ui, &mut self.sing),
                         PipelineMode::Speak => show_speak_settings(ui, &mut self.speak),
                     }
@@ -587,11 +1727,20 @@ impl eframe::App for GlottisdaleApp {
         // Central panel: main workspace or editor
         egui::CentralPanel::default().show(ctx, |ui| {
             let render_settings = self.build_render_settings();
-            if let Some(ref mut editor_state) = self.editor {
+            if let Some(editor_state) = self.editor.as_mut().filter(|e| !e.detached) {
+                editor_state.playback.set_buffer_size(self.audio_buffer);
                 if crate::editor::show_editor(ui, editor_state, ctx, &render_settings) {
                     self.editor = None; // Close editor
                 }
             } else {
+                if let Some(editor_state) = self.editor.as_mut() {
+                    ui.horizontal(|ui| {
+                        ui.label("The editor is open in its own window.");
+                        if ui.button("Dock Editor").clicked() {
+                            editor_state.detached = false;
+                        }
+                    });
+                }
                 ui.vertical_centered(|ui| {
                     ui.add_space(12.0);
 
@@ -620,12 +1769,16 @@ impl eframe::App for GlottisdaleApp {
                 }
             }
         });
+
+        if self.editor.as_ref().is_some_and(|e| e.detached) {
+            self.show_detached_editor(ctx);
+        }
     }
 }
 
 // ─── Settings panels ─────────────────────────────────────────────
 
-fn show_collage_settings(ui: &mut egui::Ui, s: &mut CollageSettings) {
+fn show_collage_settings(ui: &mut egui::Ui, s: &mut CollageSettings, sources: &[PathBuf]) {
     ui.collapsing("Prosodic Grouping", |ui| {
         ui.horizontal(|ui| {
             ui.label("Target duration (s):");
@@ -665,6 +1818,91 @@ fn show_collage_settings(ui: &mut egui::Ui, s: &mut CollageSettings) {
         });
     });
 
+    ui.collapsing("Sampling", |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Anchor words:");
+            ui.text_edit_singleline(&mut s.anchor_words);
+        });
+        ui.checkbox(&mut s.anchor_sentence_start, "Anchors start sentences");
+        ui.horizontal(|ui| {
+            ui.label("Prefer duration:");
+            egui::ComboBox::from_id_salt("prefer_duration")
+                .selected_text(if s.prefer_duration.is_empty() { "any" } else { &s.prefer_duration })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut s.prefer_duration, String::new(), "any");
+                    ui.selectable_value(&mut s.prefer_duration, "long".to_string(), "long");
+                    ui.selectable_value(&mut s.prefer_duration, "short".to_string(), "short");
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Prefer energy:");
+            egui::ComboBox::from_id_salt("prefer_energy")
+                .selected_text(if s.prefer_energy.is_empty() { "any" } else { &s.prefer_energy })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut s.prefer_energy, String::new(), "any");
+                    ui.selectable_value(&mut s.prefer_energy, "high".to_string(), "high");
+                    ui.selectable_value(&mut s.prefer_energy, "low".to_string(), "low");
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Word model:");
+            egui::ComboBox::from_id_salt("word_model")
+                .selected_text(&s.word_model)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut s.word_model, "phonotactic".to_string(), "phonotactic");
+                    ui.selectable_value(&mut s.word_model, "markov".to_string(), "markov");
+                })
+                .response
+                .on_hover_text("markov orders syllables by phoneme pairs learned from the sources");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Min reuse distance:");
+            ui.add(egui::DragValue::new(&mut s.min_reuse_distance).range(0..=64))
+                .on_hover_text("Fewest syllables between two uses of the same source syllable (0 = no limit)");
+        });
+    });
+
+    if sources.len() > 1 {
+        ui.collapsing("Source Weights", |ui| {
+            ui.label("Share of syllables drawn from each source (0 leaves it out)");
+            for path in sources {
+                let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+                let weight = s.source_weights.entry(path.clone()).or_insert(1.0);
+                ui.add(egui::Slider::new(weight, 0.0..=1.0).text(name));
+            }
+            if ui.small_button("Reset").clicked() {
+                s.source_weights.clear();
+            }
+        });
+    }
+
+    ui.collapsing("Tempo", |ui| {
+        ui.horizontal(|ui| {
+            ui.label("BPM:");
+            ui.add(egui::TextEdit::singleline(&mut s.bpm).hint_text("off").desired_width(60.0));
+            if ui.button("Tap").on_hover_text("Click on the beat to set the tempo").clicked() {
+                s.tap_times.push(ui.input(|i| i.time));
+                if let Some(bpm) = bpm_from_taps(&s.tap_times) {
+                    s.bpm = format!("{:.1}", bpm);
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Subdivision:");
+            egui::ComboBox::from_id_salt("beat_subdivision")
+                .selected_text(format!("1/{}", s.beat_subdivision * 4))
+                .show_ui(ui, |ui| {
+                    for sub in [1, 2, 3, 4] {
+                        ui.selectable_value(&mut s.beat_subdivision, sub, format!("1/{}", sub * 4));
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Swing:");
+            ui.add(egui::Slider::new(&mut s.swing, 0.0..=0.5));
+        });
+    });
+
     ui.collapsing("Audio Polish", |ui| {
         ui.horizontal(|ui| {
             ui.label("Noise level (dB):");
@@ -682,7 +1920,39 @@ fn show_collage_settings(ui: &mut egui::Ui, s: &mut CollageSettings) {
             ui.add(egui::Slider::new(&mut s.breath_probability, 0.0..=1.0));
         });
         ui.checkbox(&mut s.volume_normalize, "Volume normalize");
+        if s.volume_normalize {
+            show_volume_normalization(ui, "collage", &mut s.volume_normalization);
+        }
         ui.checkbox(&mut s.prosodic_dynamics, "Prosodic dynamics");
+        ui.checkbox(&mut s.intonation, "Sentence intonation")
+            .on_hover_text("Pitch falls across each sentence; questions rise at the end");
+        if s.intonation {
+            ui.horizontal(|ui| {
+                ui.label("Question prob:");
+                ui.add(egui::Slider::new(&mut s.question_probability, 0.0..=1.0));
+            });
+        }
+        ui.horizontal(|ui| {
+            ui.label("Voice FX:");
+            egui::ComboBox::from_id_salt("voice_fx")
+                .selected_text(if s.voice_fx.is_empty() { "none" } else { &s.voice_fx })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut s.voice_fx, String::new(), "none");
+                    for &name in VOICE_FX {
+                        ui.selectable_value(&mut s.voice_fx, name.to_string(), name);
+                    }
+                })
+                .response
+                .on_hover_text("Band-limit and distort each phrase like a telephone, radio, or megaphone");
+        });
+    });
+
+    ui.collapsing("Stereo", |ui| {
+        ui.checkbox(&mut s.stereo, "Keep source stereo image");
+        ui.horizontal(|ui| {
+            ui.label("Pan spread:");
+            ui.add(egui::Slider::new(&mut s.pan_spread, 0.0..=1.0));
+        });
     });
 
     ui.collapsing("Stretch", |ui| {
@@ -732,36 +2002,193 @@ fn show_collage_settings(ui: &mut egui::Ui, s: &mut CollageSettings) {
     });
 }
 
+/// Wet/dry mix and output gain sliders for one effect.
+fn show_wet_gain(ui: &mut egui::Ui, mix: &mut f64, gain_db: &mut f64) {
+    ui.horizontal(|ui| {
+        ui.label("Mix:");
+        ui.add(egui::Slider::new(mix, 0.0..=1.0))
+            .on_hover_text("0 = dry, 1 = fully processed");
+        ui.label("Gain (dB):");
+        ui.add(egui::Slider::new(gain_db, -12.0..=12.0));
+    });
+}
+
 fn show_sing_settings(ui: &mut egui::Ui, s: &mut SingSettings) {
     ui.collapsing("MIDI", |ui| {
         ui.horizontal(|ui| {
-            ui.label("MIDI dir:");
-            ui.text_edit_singleline(&mut s.midi_dir);
+            ui.label("MIDI:");
+            ui.text_edit_singleline(&mut s.midi_path);
         });
-        if ui.button("Browse...").clicked() {
-            if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                s.midi_dir = path.display().to_string();
+        ui.horizontal(|ui| {
+            if ui.button("File...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("MIDI", &["mid", "midi"])
+                    .pick_file()
+                {
+                    s.midi_path = path.display().to_string();
+                }
+            }
+            if ui.button("Folder...").clicked() {
+                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                    s.midi_path = path.display().to_string();
+                }
             }
+        });
+        if !Path::new(&s.midi_path).is_dir() {
+            ui.horizontal(|ui| {
+                ui.label("Melody track:");
+                ui.add(egui::TextEdit::singleline(&mut s.melody_track).hint_text("auto").desired_width(40.0));
+            });
         }
+        ui.label("Lyrics:")
+            .on_hover_text("Sing source syllables matching these words, one per note; empty = random syllables");
+        ui.text_edit_multiline(&mut s.lyrics);
+        ui.horizontal(|ui| {
+            ui.label("Note pins:");
+            ui.add(egui::TextEdit::singleline(&mut s.pins).hint_text("none"))
+                .on_hover_text("TOML file pinning melody notes to words, e.g. 1 = \"glottis\" under [notes]");
+            if ui.button("Browse...").clicked() {
+                if let Some(path) = rfd::FileDialog::new().add_filter("TOML", &["toml"]).pick_file() {
+                    s.pins = path.display().to_string();
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Soundfont:");
+            ui.add(egui::TextEdit::singleline(&mut s.soundfont).hint_text("sine tones"));
+            if ui.button("Browse...").clicked() {
+                if let Some(path) = rfd::FileDialog::new().add_filter("SoundFont", &["sf2"]).pick_file() {
+                    s.soundfont = path.display().to_string();
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Drum map:");
+            ui.add(egui::TextEdit::singleline(&mut s.drum_map).hint_text("built-in kit"));
+            if ui.button("Browse...").clicked() {
+                if let Some(path) = rfd::FileDialog::new().add_filter("TOML", &["toml"]).pick_file() {
+                    s.drum_map = path.display().to_string();
+                }
+            }
+        });
     });
 
     ui.collapsing("Parameters", |ui| {
         ui.horizontal(|ui| {
-            ui.label("Target duration (s):");
-            ui.add(egui::DragValue::new(&mut s.target_duration).range(1.0..=300.0).speed(0.5));
+            ui.label("Target duration (s):");
+            ui.add(egui::DragValue::new(&mut s.target_duration).range(1.0..=300.0).speed(0.5));
+        });
+        ui.horizontal(|ui| {
+            ui.label("Fit melody:");
+            egui::ComboBox::from_id_salt("sing_loop_mode")
+                .selected_text(&s.loop_mode)
+                .show_ui(ui, |ui| {
+                    for mode in ["loop", "truncate", "once"] {
+                        ui.selectable_value(&mut s.loop_mode, mode.to_string(), mode);
+                    }
+                });
+        });
+        ui.checkbox(&mut s.vibrato, "Vibrato");
+        if s.vibrato {
+            ui.horizontal(|ui| {
+                ui.label("Depth (cents):");
+                ui.add(egui::Slider::new(&mut s.vibrato_depth, 0.0..=150.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Rate (Hz):");
+                ui.add(egui::Slider::new(&mut s.vibrato_rate, 2.0..=10.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Onset (ms):");
+                ui.add(egui::Slider::new(&mut s.vibrato_onset, 0.0..=1000.0))
+                    .on_hover_text("Vibrato fades in this long after a held syllable starts");
+            });
+            show_wet_gain(ui, &mut s.vibrato_mix, &mut s.vibrato_gain);
+        }
+        ui.checkbox(&mut s.chorus, "Chorus");
+        if s.chorus {
+            ui.horizontal(|ui| {
+                ui.label("Chorus voices:");
+                ui.add(egui::Slider::new(&mut s.chorus_voices, 1..=8));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Detune (cents):");
+                ui.text_edit_singleline(&mut s.chorus_detune);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Delay (ms):");
+                ui.text_edit_singleline(&mut s.chorus_delay);
+            });
+            show_wet_gain(ui, &mut s.chorus_mix, &mut s.chorus_gain);
+        }
+        ui.horizontal(|ui| {
+            ui.label("Release (ms):");
+            ui.add(egui::Slider::new(&mut s.release, 0.0..=500.0))
+                .on_hover_text("Fade notes out past their end into rests instead of cutting them off");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Legato gap (ms):");
+            ui.add(egui::Slider::new(&mut s.legato_gap, 0.0..=500.0))
+                .on_hover_text("Hold a note into the next one across rests up to this long");
         });
-        ui.checkbox(&mut s.vibrato, "Vibrato");
-        ui.checkbox(&mut s.chorus, "Chorus");
         ui.horizontal(|ui| {
             ui.label("Drift range (st):");
             ui.add(egui::Slider::new(&mut s.drift_range, 0.0..=6.0));
         });
+        egui::CollapsingHeader::new("Note durations").show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Short below (s):");
+                ui.add(egui::Slider::new(&mut s.short_note, 0.0..=2.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Long from (s):");
+                ui.add(egui::Slider::new(&mut s.long_note, 0.0..=4.0));
+            });
+            for (label, counts) in [
+                ("Short syllables:", &mut s.short_syllables),
+                ("Medium syllables:", &mut s.medium_syllables),
+                ("Long syllables:", &mut s.long_syllables),
+            ] {
+                ui.horizontal(|ui| {
+                    ui.label(label);
+                    ui.text_edit_singleline(counts)
+                        .on_hover_text("Syllable counts picked at random per note; repeat one to favor it");
+                });
+            }
+        });
+        ui.checkbox(&mut s.harmony, "Harmony")
+            .on_hover_text("Sing extra voices under the melody, pitched to the chords");
+        if s.harmony {
+            ui.horizontal(|ui| {
+                ui.label("Voices:");
+                ui.add(egui::Slider::new(&mut s.harmony_voices, 1..=3));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Chords:");
+                ui.add(egui::TextEdit::singleline(&mut s.chords).hint_text("from backing"));
+                if ui.button("Browse...").clicked() {
+                    if let Some(path) = rfd::FileDialog::new().add_filter("MIDI", &["mid", "midi"]).pick_file() {
+                        s.chords = path.display().to_string();
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Jitter (ms):");
+                ui.add(egui::Slider::new(&mut s.harmony_jitter, 0.0..=100.0));
+            });
+        }
+    });
+
+    ui.collapsing("Stereo", |ui| {
+        ui.checkbox(&mut s.stereo, "Keep source stereo image");
     });
 }
 
 fn show_speak_settings(ui: &mut egui::Ui, s: &mut SpeakSettings) {
     ui.collapsing("Target", |ui| {
-        ui.label("Target text:");
+        ui.label("Target text:").on_hover_text(
+            "Markup: <pause ms=\"400\"/>, <emph>word</emph>, <rate value=\"0.8\">slower</rate>",
+        );
         ui.text_edit_multiline(&mut s.target_text);
         ui.separator();
         ui.horizontal(|ui| {
@@ -770,7 +2197,7 @@ fn show_speak_settings(ui: &mut egui::Ui, s: &mut SpeakSettings) {
         });
         if ui.button("Browse...").clicked() {
             if let Some(path) = rfd::FileDialog::new()
-                .add_filter("Audio", &["wav", "mp3", "flac", "m4a"])
+                .add_filter("Audio", &["wav", "mp3", "flac", "ogg", "m4a"])
                 .pick_file()
             {
                 s.reference_path = path.display().to_string();
@@ -788,6 +2215,29 @@ fn show_speak_settings(ui: &mut egui::Ui, s: &mut SpeakSettings) {
                     ui.selectable_value(&mut s.match_unit, "phoneme".to_string(), "phoneme");
                 });
         });
+        if s.match_unit == "syllable" {
+            ui.horizontal(|ui| {
+                ui.label("Match strategy:");
+                egui::ComboBox::from_id_salt("match_strategy")
+                    .selected_text(&s.match_strategy)
+                    .show_ui(ui, |ui| {
+                        for m in ["greedy", "viterbi", "beam"] {
+                            ui.selectable_value(&mut s.match_strategy, m.to_string(), m);
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "greedy: closest syllable each time; viterbi: best overall with a bonus for \
+                         contiguous source runs; beam: like viterbi but also avoids reusing the same clip",
+                    );
+            });
+            if s.match_strategy == "beam" {
+                ui.horizontal(|ui| {
+                    ui.label("Beam width:");
+                    ui.add(egui::DragValue::new(&mut s.beam_width).range(1..=64));
+                });
+            }
+        }
         ui.checkbox(&mut s.pitch_correct, "Pitch correct");
         ui.horizontal(|ui| {
             ui.label("Timing strictness:");
@@ -798,6 +2248,45 @@ fn show_speak_settings(ui: &mut egui::Ui, s: &mut SpeakSettings) {
             ui.add(egui::DragValue::new(&mut s.crossfade_ms).range(0.0..=100.0).speed(1.0));
         });
         ui.checkbox(&mut s.normalize_volume, "Normalize volume");
+        if s.normalize_volume {
+            show_volume_normalization(ui, "speak", &mut s.volume_normalization);
+        }
+    });
+}
+
+/// Target level and gain limits for volume normalization.
+fn show_volume_normalization(ui: &mut egui::Ui, id: &str, n: &mut RmsNormalization) {
+    ui.horizontal(|ui| {
+        ui.label("Level target:");
+        let fixed_db = match n.target {
+            RmsTarget::Fixed(db) => db,
+            _ => -20.0,
+        };
+        egui::ComboBox::from_id_salt(("volume_target", id))
+            .selected_text(match n.target {
+                RmsTarget::Median => "Median",
+                RmsTarget::Mean => "Mean",
+                RmsTarget::Fixed(_) => "Fixed",
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut n.target, RmsTarget::Median, "Median");
+                ui.selectable_value(&mut n.target, RmsTarget::Mean, "Mean");
+                if ui.selectable_label(matches!(n.target, RmsTarget::Fixed(_)), "Fixed").clicked() {
+                    n.target = RmsTarget::Fixed(fixed_db);
+                }
+            })
+            .response
+            .on_hover_text("Median or mean of the clips' levels, or a fixed RMS level");
+        if let RmsTarget::Fixed(db) = &mut n.target {
+            ui.add(egui::DragValue::new(db).range(-60.0..=0.0).speed(0.5).suffix(" dBFS"));
+        }
+    });
+    ui.horizontal(|ui| {
+        ui.label("Max boost (dB):");
+        ui.add(egui::DragValue::new(&mut n.max_boost_db).range(0.0..=40.0).speed(0.5))
+            .on_hover_text("Lower this to keep whispered sources from being boosted into noise");
+        ui.label("Max cut (dB):");
+        ui.add(egui::DragValue::new(&mut n.max_cut_db).range(0.0..=40.0).speed(0.5));
     });
 }
 
@@ -862,13 +2351,25 @@ fn show_output_section(ui: &mut egui::Ui, processing: &ProcessingState) -> bool
 /// Build an arrangement from stored alignment data and open the editor.
 fn try_open_editor_from_alignment(app: &mut GlottisdaleApp) {
     if let Some(data) = app.processing.get_alignment() {
-        match arrangement_blank_canvas(&data.syllables, &data.audio, data.pipeline_mode) {
-            Ok(arrangement) => {
-                app.editor = Some(crate::editor::EditorState::new(arrangement));
-            }
+        let (syllables, audio) = data.editor_sources();
+        let arrangement = match &data.melody {
+            Some(melody) => arrangement_from_sing(
+                &syllables,
+                &audio,
+                &melody.source_order.iter().map(|key| data.editor_key(key)).collect::<Vec<_>>(),
+                &melody.mappings,
+                melody.median_f0,
+            ),
+            None => arrangement_blank_canvas(&syllables, &audio, data.pipeline_mode),
+        };
+        match arrangement {
+            Ok(arrangement) => match app.editor.as_mut() {
+                Some(editor) if editor.bank_streaming => editor.finish_streaming(arrangement),
+                _ => app.editor = Some(crate::editor::EditorState::new(arrangement)),
+            },
             Err(e) => {
                 log::error!("Failed to build arrangement: {}", e);
-                app.processing.add_log(&format!("Failed to open editor: {}", e));
+                app.processing.add_log(&format!("ERROR: failed to open editor: {:#}", e));
             }
         }
     }
@@ -927,15 +2428,15 @@ fn show_sing_workspace(ui: &mut egui::Ui, app: &mut GlottisdaleApp) {
                 banner_size,
             ));
             ui.add_space(12.0);
-            ui.label("Add source audio files and set MIDI directory to get started.");
+            ui.label("Add source audio files and choose a MIDI file or folder to get started.");
         });
         return;
     }
 
-    if app.sing.midi_dir.is_empty() {
+    if app.sing.midi_path.is_empty() {
         ui.vertical_centered(|ui| {
             ui.add_space(40.0);
-            ui.label("Set the MIDI directory in the settings panel.");
+            ui.label("Choose a MIDI file or folder in the settings panel.");
         });
         return;
     }
@@ -955,7 +2456,7 @@ fn show_sing_workspace(ui: &mut egui::Ui, app: &mut GlottisdaleApp) {
 
     ui.separator();
     ui.label(format!("{} source file(s)", app.source_files.len()));
-    ui.label(format!("MIDI: {}", app.sing.midi_dir));
+    ui.label(format!("MIDI: {}", app.sing.midi_path));
 
     if show_output_section(ui, &app.processing) {
         try_open_editor_from_alignment(app);
@@ -1010,14 +2511,109 @@ fn show_speak_workspace(ui: &mut egui::Ui, app: &mut GlottisdaleApp) {
     if show_output_section(ui, &app.processing) {
         try_open_editor_from_alignment(app);
     }
+    if matches!(app.processing.get_status(), ProcessingStatus::Done(_)) {
+        show_speak_alternates(ui, app);
+    }
+}
+
+/// Per-word alternates from the last speak run: pick a different source
+/// syllable for any target syllable, audition it, and reassemble.
+fn show_speak_alternates(ui: &mut egui::Ui, app: &mut GlottisdaleApp) {
+    let review_slot = app.processing.speak_review.clone();
+    let mut guard = review_slot.lock().unwrap();
+    let Some(review) = guard.as_mut() else {
+        return;
+    };
+    let mut audition: Option<Vec<MatchResult>> = None;
+    let mut reassemble = false;
+
+    ui.add_space(8.0);
+    egui::CollapsingHeader::new("Alternates")
+        .default_open(false)
+        .show(ui, |ui| {
+            ui.weak("Swap the source syllable used for each target syllable, then reassemble.");
+            egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                for word in review.words() {
+                    ui.horizontal(|ui| {
+                        if ui.small_button("▶").on_hover_text("Audition this word").clicked() {
+                            audition = Some(review.chosen_matches()[word.clone()].to_vec());
+                        }
+                        ui.label(egui::RichText::new(&review.targets[word.start].word).strong());
+                        for i in word.clone() {
+                            let options: Vec<String> = review
+                                .options(i)
+                                .iter()
+                                .map(|m| {
+                                    format!("{} [{}] d={}", m.entry.word, m.entry.phoneme_labels.join(" "), m.distance)
+                                })
+                                .collect();
+                            let current = review.chosen[i].min(options.len() - 1);
+                            egui::ComboBox::from_id_salt(("speak_alternate", i))
+                                .selected_text(&options[current])
+                                .show_ui(ui, |ui| {
+                                    for (k, option) in options.iter().enumerate() {
+                                        ui.selectable_value(&mut review.chosen[i], k, option);
+                                    }
+                                })
+                                .response
+                                .on_hover_text(format!("Target: {}", review.targets[i].phonemes.join(" ")));
+                        }
+                    });
+                }
+            });
+            ui.add_space(4.0);
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!app.is_processing(), egui::Button::new("Reassemble")).clicked() {
+                    reassemble = true;
+                }
+                ui.weak("Overwrites the run's output and matches.json");
+            });
+        });
+    drop(guard);
+
+    if let Some(matches) = audition {
+        audition_matches(app, &matches);
+    }
+    if reassemble {
+        start_speak_reassemble(app);
+    }
+}
+
+/// Play the source clips of `matches` back to back.
+fn audition_matches(app: &mut GlottisdaleApp, matches: &[MatchResult]) {
+    use glottisdale_core::audio::effects::cut_clip;
+
+    let Some(alignment) = app.processing.get_alignment() else {
+        return;
+    };
+    let mut samples = Vec::new();
+    let mut rate = None;
+    for m in matches {
+        if let Some((audio, sr)) = alignment.audio.get(m.entry.source.path().to_string_lossy().as_ref()) {
+            if rate.is_some_and(|r| r != *sr) {
+                continue;
+            }
+            rate = Some(*sr);
+            samples.extend(cut_clip(audio, *sr, m.entry.start, m.entry.end, 5.0, 3.0));
+        }
+    }
+    let Some(sr) = rate else {
+        return;
+    };
+    let buffer = app.audio_buffer;
+    app.audition
+        .get_or_insert_with(|| PlaybackEngine::with_buffer_size(buffer))
+        .play_samples(samples, sr, 0.0);
 }
 
 // ─── Pipeline runners (background threads) ──────────────────────
 
-/// Extract audio from input files to 16kHz mono WAV in a work directory.
+/// Extract audio from input files to 16kHz mono WAV in a work directory,
+/// de-noising each one when `denoise` is set.
 fn prepare_audio(
     inputs: &[PathBuf],
     work_dir: &Path,
+    denoise: Option<DenoiseLevel>,
     state: &ProcessingState,
 ) -> anyhow::Result<Vec<PathBuf>> {
     use glottisdale_core::audio::io::extract_audio;
@@ -1032,61 +2628,242 @@ fn prepare_audio(
         let wav_path = work_dir.join(format!("{}_16k.wav", stem));
         state.add_log(&format!("Extracting audio: {}", input.display()));
         extract_audio(input, &wav_path)?;
+        if let Some(level) = denoise {
+            denoise_source(&wav_path, level, state)?;
+        }
         audio_paths.push(wav_path);
     }
     Ok(audio_paths)
 }
 
+/// De-noise an extracted WAV in place, logging when it has no room tone.
+fn denoise_source(wav_path: &Path, level: DenoiseLevel, state: &ProcessingState) -> anyhow::Result<()> {
+    let name = wav_path.file_name().unwrap_or_default().to_string_lossy();
+    if glottisdale_core::audio::io::denoise_wav(wav_path, level)? {
+        state.add_log(&format!("De-noised {}", name));
+    } else {
+        state.add_log(&format!("WARNING: no room tone found in {}; left as is", name));
+    }
+    Ok(())
+}
+
+/// The per-source aligner picks for `inputs`, in input order.
+fn source_aligner_overrides(
+    inputs: &[PathBuf],
+    source_aligners: &HashMap<PathBuf, AlignerOverride>,
+) -> Vec<AlignerOverride> {
+    inputs.iter().filter_map(|input| source_aligners.get(input).cloned()).collect()
+}
+
+/// One aligner per input, aligning only detected speech when `vad` is set.
+fn input_aligners(
+    inputs: &[PathBuf],
+    overrides: &[AlignerOverride],
+    aligner_name: &str,
+    whisper_model: &str,
+    language: Language,
+    device: &str,
+    vad: bool,
+) -> anyhow::Result<Vec<Box<dyn Aligner>>> {
+    use glottisdale_core::language::align::{aligners_for_inputs, DEFAULT_VAD_CHUNK_S};
+    let vad_max_chunk = vad.then_some(DEFAULT_VAD_CHUNK_S);
+    aligners_for_inputs(inputs, overrides, aligner_name, whisper_model, language.code(), device, vad_max_chunk)
+}
+
+/// Tell the user about the oldest worker thread panic, with its crash report.
+/// Expand dropped paths into supported media files, scanning folders
+/// recursively. Files in a folder are sorted by path.
+fn collect_media_files(paths: &[PathBuf]) -> Vec<PathBuf> {
+    fn walk(dir: &Path, out: &mut Vec<PathBuf>) {
+        let Ok(entries) = std::fs::read_dir(dir) else { return };
+        let mut entries: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                walk(&path, out);
+            } else if is_media_file(&path) {
+                out.push(path);
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            walk(path, &mut out);
+        } else if is_media_file(path) {
+            out.push(path.clone());
+        }
+    }
+    out
+}
+
+/// `m:ss` (or `h:mm:ss`) for the source list.
+fn format_duration(seconds: f64) -> String {
+    let total = seconds.round() as u64;
+    if total >= 3600 {
+        format!("{}:{:02}:{:02}", total / 3600, total / 60 % 60, total % 60)
+    } else {
+        format!("{}:{:02}", total / 60, total % 60)
+    }
+}
+
+/// Dim the window and show a hint while files are dragged over it.
+fn show_drop_overlay(ctx: &egui::Context) {
+    if ctx.input(|i| i.raw.hovered_files.is_empty()) {
+        return;
+    }
+    let screen = ctx.screen_rect();
+    let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_overlay")));
+    painter.rect_filled(screen, 0.0, egui::Color32::from_black_alpha(160));
+    painter.text(
+        screen.center(),
+        egui::Align2::CENTER_CENTER,
+        "Drop audio/video files or folders to add them",
+        egui::FontId::proportional(20.0),
+        egui::Color32::WHITE,
+    );
+}
+
+fn show_crash_window(ctx: &egui::Context, crashes: &mut Vec<crate::crash::CrashNotice>) {
+    let Some(crash) = crashes.first() else {
+        return;
+    };
+    let mut dismiss = false;
+    egui::Window::new("Something Went Wrong")
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+        .show(ctx, |ui| {
+            ui.label(format!("The {} thread crashed:", crash.thread));
+            ui.colored_label(egui::Color32::from_rgb(255, 100, 100), &crash.message);
+            match &crash.report {
+                Some(path) => {
+                    ui.label(format!("A crash report was saved to {}", path.display()));
+                }
+                None => {
+                    ui.label("The crash report could not be saved; see the terminal output.");
+                }
+            }
+            ui.horizontal(|ui| {
+                if let Some(path) = &crash.report {
+                    if ui.button("Open Report").clicked() {
+                        open_path(path);
+                    }
+                    if ui.button("Open Folder").clicked() {
+                        if let Some(dir) = path.parent() {
+                            open_path(dir);
+                        }
+                    }
+                }
+                if ui.button("Dismiss").clicked() {
+                    dismiss = true;
+                }
+            });
+        });
+    if dismiss {
+        crashes.remove(0);
+    }
+}
+
+/// Run a pipeline on a named thread, so crash reports say which one died,
+/// with a watchdog that fails the run if the thread dies.
+fn spawn_pipeline(name: &str, state: &ProcessingState, f: impl FnOnce() + Send + 'static) {
+    let worker = thread::Builder::new()
+        .name(name.to_string())
+        .spawn(f)
+        .expect("Failed to spawn pipeline thread");
+    let state = state.clone();
+    let name = name.to_string();
+    thread::Builder::new()
+        .name(format!("{}-watchdog", name))
+        .spawn(move || state.thread_finished(&name, worker.join()))
+        .expect("Failed to spawn watchdog thread");
+}
+
+/// Record the app settings for a run that is starting, for crash reports.
+fn note_crash_context(app: &GlottisdaleApp, settings: &dyn std::fmt::Debug) {
+    let context = format!(
+        "mode: {:?}\nsources: {:?}\nseed: {:?}\nwhisper model: {}\nlanguage: {}\naligner: {}\ndevice: {}\nunit: {}\n{:#?}",
+        app.mode, app.source_files, app.seed, app.whisper_model, app.language.code(), app.aligner, app.device, app.unit, settings
+    );
+    crate::crash::set_context(Path::new(&app.output_dir), context);
+}
+
 /// Parse a seed string into Option<u64>.
+/// Seed from the Seed field: a number, or any other text hashed with
+/// `names::seed_from_name` (matching the CLI's `--seed-from-name`).
 fn parse_seed(s: &str) -> Option<u64> {
-    if s.is_empty() { None } else { s.parse().ok() }
+    let s = s.trim();
+    if s.is_empty() {
+        None
+    } else {
+        Some(s.parse().unwrap_or_else(|_| glottisdale_core::names::seed_from_name(s)))
+    }
 }
 
 fn start_collage(app: &mut GlottisdaleApp) {
-    use glottisdale_core::audio::io::read_wav;
-    use glottisdale_core::collage::process::{CollageConfig, process};
+    use glottisdale_core::audio::io::{load_stereo_sources, read_wav};
+    use glottisdale_core::collage::preview::{preview_path, DEFAULT_PREVIEW_S};
+    use glottisdale_core::collage::process::{CollageConfig, process, process_channels};
     use glottisdale_core::collage::stretch::{StretchConfig, parse_stretch_factor};
-    use glottisdale_core::language::align::get_aligner;
-    use glottisdale_core::names::create_run_dir;
+    use glottisdale_core::names::{create_run_dir_with, NameTheme};
 
     let state = app.processing.clone();
     state.clear();
     state.set_status(ProcessingStatus::Running("Starting collage...".into()));
 
     let inputs = app.source_files.clone();
+    let aligner_overrides = source_aligner_overrides(&inputs, &app.source_aligners);
+    let denoise = app.denoise.then_some(app.denoise_level);
+    let vad = app.vad;
+    let min_quality = app.min_quality;
     let output_dir = PathBuf::from(&app.output_dir);
     let seed = parse_seed(&app.seed);
     let run_name = if app.run_name.is_empty() { None } else { Some(app.run_name.clone()) };
+    let name_theme = app.name_theme.clone();
     let whisper_model = app.whisper_model.clone();
+    let language = app.language;
+    let device = app.device.clone();
     let aligner_name = app.aligner.clone();
+    let unit = app.unit.clone();
+    let mastering = app.mastering();
+    let limiter = app.limiter;
     let settings = app.collage.clone();
 
-    thread::spawn(move || {
+    note_crash_context(app, &settings);
+    spawn_pipeline("collage", &app.processing, move || {
         let result: anyhow::Result<()> = (|| {
-            let run_dir = create_run_dir(&output_dir, seed, run_name.as_deref())?;
+            let theme = NameTheme::load(&name_theme)?;
+            let run_dir = create_run_dir_with(&output_dir, seed, run_name.as_deref(), &theme)?;
             let run_dir_name = run_dir.file_name().unwrap().to_string_lossy().to_string();
             state.add_log(&format!("Run: {}", run_dir_name));
+            state.set_preview(preview_path(&run_dir));
 
             let work_dir = run_dir.join("work");
-            let audio_paths = prepare_audio(&inputs, &work_dir, &state)?;
+            let audio_paths = prepare_audio(&inputs, &work_dir, denoise, &state)?;
 
             state.add_log("Aligning syllables...");
             state.set_status(ProcessingStatus::Running("Aligning...".into()));
-            let aligner = get_aligner(&aligner_name, &whisper_model, "en", "cpu")?;
+            let aligners = input_aligners(&inputs, &aligner_overrides, &aligner_name, &whisper_model, language, &device, vad)?;
             let mut source_audio = std::collections::HashMap::new();
             let mut source_syllables = std::collections::HashMap::new();
 
-            for audio_path in &audio_paths {
+            let alignments = align_sources(&aligners, &audio_paths, &state, min_quality)?;
+            for (audio_path, alignment) in audio_paths.iter().zip(alignments) {
                 let key = audio_path.to_string_lossy().to_string();
-                state.add_log(&format!("Aligning: {}", audio_path.file_name().unwrap().to_string_lossy()));
-                let alignment = aligner.process(audio_path, None)?;
                 let (samples, sr) = read_wav(audio_path)?;
                 source_audio.insert(key.clone(), (samples, sr));
-                source_syllables.insert(key, alignment.syllables);
+                let syllables = if unit == "word" {
+                    merge_into_words(&alignment.syllables)
+                } else {
+                    alignment.syllables
+                };
+                source_syllables.insert(key, syllables);
             }
 
             let total_syls: usize = source_syllables.values().map(|v| v.len()).sum();
-            state.add_log(&format!("Found {} syllables", total_syls));
+            state.add_log(&format!("Found {} {}s", total_syls, unit));
 
             // Store alignment data for the editor (clone before process borrows)
             let alignment_syllables = source_syllables.clone();
@@ -1114,7 +2891,13 @@ fn start_collage(app: &mut GlottisdaleApp) {
                 breaths: s.breaths,
                 breath_probability: s.breath_probability,
                 volume_normalize: s.volume_normalize,
+                volume_normalization: s.volume_normalization,
                 prosodic_dynamics: s.prosodic_dynamics,
+                limiter,
+                mastering,
+                intonation: s.intonation,
+                question_probability: s.question_probability,
+                voice_fx: VoiceFx::parse(&s.voice_fx).ok(),
                 speed: if s.speed.is_empty() { None } else { s.speed.parse().ok() },
                 stretch_config: StretchConfig {
                     random_stretch: if s.random_stretch.is_empty() { None } else { s.random_stretch.parse().ok() },
@@ -1129,16 +2912,51 @@ fn start_collage(app: &mut GlottisdaleApp) {
                 stutter: if s.stutter.is_empty() { None } else { s.stutter.parse().ok() },
                 stutter_count: s.stutter_count.clone(),
                 dispersal_gap: 1.0,
+                anchor_words: s
+                    .anchor_words
+                    .split(',')
+                    .map(|w| w.trim().to_string())
+                    .filter(|w| !w.is_empty())
+                    .collect(),
+                anchor_sentence_start: s.anchor_sentence_start,
+                pan_spread: s.pan_spread,
+                prefer_duration: if s.prefer_duration.is_empty() { None } else { Some(s.prefer_duration.clone()) },
+                prefer_energy: if s.prefer_energy.is_empty() { None } else { Some(s.prefer_energy.clone()) },
+                unit: unit.clone(),
+                word_model: s.word_model.clone(),
+                min_reuse_distance: s.min_reuse_distance,
+                phrase_min_pause_ms: CollageConfig::default().phrase_min_pause_ms,
+                bpm: s.bpm.trim().parse().ok().filter(|b: &f64| (20.0..=400.0).contains(b)),
+                beat_subdivision: s.beat_subdivision,
+                swing: s.swing,
+                source_weights: inputs
+                    .iter()
+                    .zip(&audio_paths)
+                    .filter_map(|(input, audio_path)| {
+                        let w = s.source_weights.get(input)?;
+                        Some((audio_path.to_string_lossy().to_string(), *w))
+                    })
+                    .collect(),
+                preview_s: DEFAULT_PREVIEW_S,
             };
 
-            let result = process(&source_audio, &source_syllables, &run_dir, &config)?;
-            state.add_output("Output", result.concatenated);
+            let mut stereo_sources = HashMap::new();
+            let result = if s.stereo {
+                state.add_log("Extracting stereo sources...");
+                stereo_sources = load_stereo_sources(&inputs, &[], &audio_paths, denoise)?;
+                process_channels(&stereo_sources, &source_syllables, &run_dir, &config, &state)?
+            } else {
+                process(&source_audio, &source_syllables, &run_dir, &config, &state)?
+            };
+            state.finish_output("Output", &run_dir, result.concatenated);
             state.add_log(&format!("Selected {} clips", result.clips.len()));
 
             state.store_alignment(AlignmentData {
                 syllables: alignment_syllables,
                 audio: alignment_audio,
+                stereo: stereo_sources,
                 pipeline_mode: EditorPipelineMode::Collage,
+                melody: None,
             });
 
             Ok(())
@@ -1155,115 +2973,232 @@ fn start_collage(app: &mut GlottisdaleApp) {
 }
 
 fn start_sing(app: &mut GlottisdaleApp) {
-    use glottisdale_core::audio::io::read_wav;
-    use glottisdale_core::language::align::get_aligner;
-    use glottisdale_core::names::create_run_dir;
-    use glottisdale_core::sing::midi_parser::parse_midi;
-    use glottisdale_core::sing::syllable_prep::{prepare_syllables, median_f0};
-    use glottisdale_core::sing::vocal_mapper::{plan_note_mapping, render_vocal_track};
-    use glottisdale_core::sing::mixer::mix_tracks;
+    use glottisdale_core::audio::io::{load_stereo_sources, read_wav, to_layout, Channels};
+    use glottisdale_core::names::{create_run_dir_with, NameTheme};
+    use anyhow::Context as _;
+    use glottisdale_core::sing::harmony::{pick_chords, plan_harmony};
+    use glottisdale_core::sing::lyrics::{apply_lyrics, match_lyrics};
+    use glottisdale_core::sing::midi_parser::{load_song, parse_midi};
+    use glottisdale_core::sing::syllable_prep::{prepare_syllables_channels, median_f0};
+    use glottisdale_core::sing::vocal_mapper::{
+        arrange_track, plan_note_mapping_with, render_vocal_track_with, ChorusConfig, LoopMode, NotePins,
+        ReleaseConfig, SingConfig, VibratoConfig,
+    };
+    use glottisdale_core::sing::mixer::{
+        bar_seconds, mix_harmony_channels, mix_tracks_channels, truncate_with_fade, write_stem_metadata,
+    };
+    use glottisdale_core::sing::soundfont::Soundfont;
+    use glottisdale_core::sing::synthesize::{DrumMap, Instruments};
+    use glottisdale_core::speak::matcher::MatchStrategy;
 
     let state = app.processing.clone();
     state.clear();
     state.set_status(ProcessingStatus::Running("Starting sing...".into()));
 
     let inputs = app.source_files.clone();
+    let aligner_overrides = source_aligner_overrides(&inputs, &app.source_aligners);
+    let denoise = app.denoise.then_some(app.denoise_level);
+    let vad = app.vad;
+    let min_quality = app.min_quality;
     let output_dir = PathBuf::from(&app.output_dir);
     let seed = parse_seed(&app.seed);
     let run_name = if app.run_name.is_empty() { None } else { Some(app.run_name.clone()) };
+    let name_theme = app.name_theme.clone();
     let whisper_model = app.whisper_model.clone();
+    let language = app.language;
+    let device = app.device.clone();
+    let mastering = app.mastering();
+    let limiter = app.limiter;
     let settings = app.sing.clone();
 
-    thread::spawn(move || {
+    note_crash_context(app, &settings);
+    spawn_pipeline("sing", &app.processing, move || {
         let result: anyhow::Result<()> = (|| {
-            let midi_dir = PathBuf::from(&settings.midi_dir);
-            let melody_path = midi_dir.join("melody.mid");
-            if !melody_path.exists() {
-                anyhow::bail!("MIDI melody not found: {}", melody_path.display());
+            let midi_path = PathBuf::from(&settings.midi_path);
+            if !midi_path.exists() {
+                anyhow::bail!("MIDI not found: {}", midi_path.display());
             }
+            let melody_track = match settings.melody_track.trim() {
+                "" => None,
+                n => Some(n.parse::<usize>().map_err(|_| anyhow::anyhow!("Invalid melody track: {}", n))?),
+            };
+            let optional_path = |s: &str| Some(s.trim()).filter(|s| !s.is_empty()).map(PathBuf::from);
+            let instruments = Instruments {
+                soundfont: optional_path(&settings.soundfont).as_deref().map(Soundfont::load).transpose()?,
+                drum_map: optional_path(&settings.drum_map).as_deref().map(DrumMap::load).transpose()?,
+            };
+            let pins = optional_path(&settings.pins).as_deref().map(NotePins::load).transpose()?.unwrap_or_default();
 
-            let run_dir = create_run_dir(&output_dir, seed, run_name.as_deref())?;
+            let theme = NameTheme::load(&name_theme)?;
+            let run_dir = create_run_dir_with(&output_dir, seed, run_name.as_deref(), &theme)?;
             let run_dir_name = run_dir.file_name().unwrap().to_string_lossy().to_string();
             state.add_log(&format!("Run: {}", run_dir_name));
 
             let work_dir = run_dir.join("work");
-            let audio_paths = prepare_audio(&inputs, &work_dir, &state)?;
+            let audio_paths = prepare_audio(&inputs, &work_dir, denoise, &state)?;
 
             state.add_log("Parsing MIDI...");
-            let track = parse_midi(&melody_path)?;
+            let song = load_song(&midi_path, melody_track)?;
+            let track = song.melody;
             state.add_log(&format!("Melody: {} notes, {:.0} BPM", track.notes.len(), track.tempo));
+            let loop_mode = LoopMode::parse(&settings.loop_mode)?;
+            let period = track.total_duration;
+            let melody = arrange_track(&track, period, settings.target_duration, loop_mode);
 
             state.set_status(ProcessingStatus::Running("Aligning...".into()));
-            let aligner = get_aligner("auto", &whisper_model, "en", "cpu")?;
-            let mut all_syllable_clips = Vec::new();
+            let aligners = input_aligners(&inputs, &aligner_overrides, "auto", &whisper_model, language, &device, vad)?;
+            let stereo_sources = if settings.stereo {
+                state.add_log("Extracting stereo sources...");
+                load_stereo_sources(&inputs, &[], &audio_paths, denoise)?
+            } else {
+                HashMap::new()
+            };
+            // One clip list per output channel, parallel to each other
+            let mut channel_clips = vec![Vec::new(); if settings.stereo { 2 } else { 1 }];
             let mut sample_rate = 16000u32;
             let mut source_syllables = HashMap::new();
             let mut source_audio_map = HashMap::new();
+            let mut source_order = Vec::new();
 
-            for audio_path in &audio_paths {
+            let alignments = align_sources(&aligners, &audio_paths, &state, min_quality)?;
+            for (audio_path, alignment) in audio_paths.iter().zip(alignments) {
                 let key = audio_path.to_string_lossy().to_string();
-                state.add_log(&format!("Aligning: {}", audio_path.file_name().unwrap().to_string_lossy()));
-                let alignment = aligner.process(audio_path, None)?;
                 let (samples, sr) = read_wav(audio_path)?;
                 sample_rate = sr;
-                let prepared = prepare_syllables(&alignment.syllables, &samples, sr, 12.0);
-                all_syllable_clips.extend(prepared);
+                let source = match stereo_sources.get(&key) {
+                    Some((channels, _)) => to_layout(channels, Channels::Stereo),
+                    None => vec![samples.clone()],
+                };
+                let prepared = prepare_syllables_channels(&alignment.syllables, &source, sr, 12.0);
+                for (clips, prepared) in channel_clips.iter_mut().zip(prepared) {
+                    clips.extend(prepared);
+                }
                 source_syllables.insert(key.clone(), alignment.syllables);
-                source_audio_map.insert(key, (samples, sr));
+                source_audio_map.insert(key.clone(), (samples, sr));
+                source_order.push(key);
             }
+            let all_syllable_clips = &channel_clips[0];
 
+            state.stage(Stage::Cutting, 1.0);
             state.add_log(&format!("Prepared {} syllable clips", all_syllable_clips.len()));
-            state.store_alignment(AlignmentData {
-                syllables: source_syllables,
-                audio: source_audio_map,
-                pipeline_mode: EditorPipelineMode::Sing,
-            });
             if all_syllable_clips.is_empty() {
                 anyhow::bail!("No syllables found in source audio");
             }
 
-            let med_f0 = median_f0(&all_syllable_clips).unwrap_or(220.0);
+            let med_f0 = median_f0(all_syllable_clips).unwrap_or(220.0);
             state.add_log(&format!("Median F0: {:.1} Hz", med_f0));
 
-            let chorus_prob = if settings.chorus { 0.3 } else { 0.0 };
-            let mappings = plan_note_mapping(
-                &track.notes,
-                all_syllable_clips.len(),
-                seed,
-                settings.drift_range,
-                chorus_prob,
-            );
+            let sing_config = SingConfig {
+                drift_range: settings.drift_range,
+                chorus_probability: if settings.chorus { 0.3 } else { 0.0 },
+                short_note_s: settings.short_note,
+                long_note_s: settings.long_note,
+                short_syllables: SingConfig::parse_counts(&settings.short_syllables).context("Short syllables")?,
+                medium_syllables: SingConfig::parse_counts(&settings.medium_syllables).context("Medium syllables")?,
+                long_syllables: SingConfig::parse_counts(&settings.long_syllables).context("Long syllables")?,
+            };
+            sing_config.validate()?;
+            let chorus = ChorusConfig {
+                voices: settings.chorus_voices,
+                detune_cents: ChorusConfig::parse_range(&settings.chorus_detune).context("Chorus detune")?,
+                delay_ms: ChorusConfig::parse_range(&settings.chorus_delay).context("Chorus delay")?,
+                seed: seed.unwrap_or(ChorusConfig::default().seed),
+                mix: settings.chorus_mix,
+                gain_db: settings.chorus_gain,
+            };
+            let vibrato = VibratoConfig {
+                depth_cents: if settings.vibrato { settings.vibrato_depth } else { 0.0 },
+                rate_hz: settings.vibrato_rate,
+                onset_ms: settings.vibrato_onset,
+                mix: settings.vibrato_mix,
+                gain_db: settings.vibrato_gain,
+            };
+            let release = ReleaseConfig { release_ms: settings.release, legato_gap_ms: settings.legato_gap };
+            let mut mappings = plan_note_mapping_with(&melody.notes, all_syllable_clips.len(), seed, &sing_config);
+            let mut lyric = Vec::new();
+            if !settings.lyrics.trim().is_empty() {
+                lyric = match_lyrics(&settings.lyrics, all_syllable_clips, language, MatchStrategy::Viterbi)?;
+                state.add_log(&format!("Lyrics: {} syllables over {} notes", lyric.len(), melody.notes.len()));
+                apply_lyrics(&mut mappings, &lyric);
+            }
+            pins.apply(&mut mappings, track.notes.len(), all_syllable_clips)?;
+            state.store_alignment(AlignmentData {
+                syllables: source_syllables,
+                audio: source_audio_map,
+                stereo: stereo_sources,
+                pipeline_mode: EditorPipelineMode::Sing,
+                melody: Some(SingMelody {
+                    source_order,
+                    mappings: mappings.clone(),
+                    median_f0: med_f0,
+                }),
+            });
 
             state.set_status(ProcessingStatus::Running("Rendering...".into()));
+            state.stage(Stage::Assembly, 0.0);
             state.add_log("Rendering vocal track...");
-            let vocal_samples = render_vocal_track(&mappings, &all_syllable_clips, med_f0, sample_rate);
-
-            if vocal_samples.is_empty() {
-                anyhow::bail!("Vocal rendering produced no output");
+            let render = |mappings: &[_], clips: &[_]| {
+                render_vocal_track_with(mappings, clips, med_f0, sample_rate, &chorus, &vibrato, &release)
+            };
+            let mut vocal_channels: Vec<Vec<f64>> = channel_clips.iter().map(|clips| render(&mappings, clips)).collect();
+
+            let backing_tracks: Vec<_> = song
+                .backing
+                .iter()
+                .map(|t| arrange_track(t, period, settings.target_duration, loop_mode))
+                .collect();
+
+            if settings.harmony {
+                let chords = match optional_path(&settings.chords) {
+                    Some(path) => Some(arrange_track(&parse_midi(&path)?, period, settings.target_duration, loop_mode)),
+                    None => pick_chords(&backing_tracks).map(|i| backing_tracks[i].clone()),
+                };
+                if chords.is_none() {
+                    state.add_log("WARNING: no chord track found; harmony sings fixed intervals below the melody");
+                }
+                let mut voices = plan_harmony(
+                    &melody.notes,
+                    chords.as_ref().map_or(&[][..], |t| t.notes.as_slice()),
+                    settings.harmony_voices as usize,
+                    all_syllable_clips.len(),
+                    seed,
+                    &sing_config,
+                    settings.harmony_jitter / 1000.0,
+                );
+                for voice in &mut voices {
+                    apply_lyrics(voice, &lyric);
+                }
+                state.add_log(&format!("Rendering {} harmony voices...", voices.len()));
+                let rendered: Vec<Vec<Vec<f64>>> = voices
+                    .iter()
+                    .map(|voice| channel_clips.iter().map(|clips| render(voice, clips)).collect())
+                    .collect();
+                vocal_channels = mix_harmony_channels(&vocal_channels, &rendered, -6.0);
             }
-
-            // Parse backing MIDI tracks
-            let mut backing_tracks = Vec::new();
-            if let Ok(entries) = std::fs::read_dir(&midi_dir) {
-                for entry in entries.flatten() {
-                    let path = entry.path();
-                    if path.extension().map(|e| e == "mid" || e == "midi").unwrap_or(false)
-                        && path != melody_path
-                    {
-                        if let Ok(t) = parse_midi(&path) {
-                            backing_tracks.push(t);
-                        }
-                    }
+            if loop_mode.cuts_melody(period, settings.target_duration) {
+                let fade = bar_seconds(track.tempo);
+                for channel in &mut vocal_channels {
+                    truncate_with_fade(channel, sample_rate, settings.target_duration, fade);
                 }
             }
 
+            let vocal_len = vocal_channels[0].len();
+            if vocal_len == 0 {
+                anyhow::bail!("Vocal rendering produced no output");
+            }
+
+            state.stage(Stage::Assembly, 0.7);
             state.add_log("Mixing tracks...");
-            let (full_mix, acappella) = mix_tracks(
-                &vocal_samples, sample_rate, &backing_tracks, &run_dir, 0.0, -12.0,
+            let (full_mix, acappella) = mix_tracks_channels(
+                &vocal_channels, sample_rate, &backing_tracks, &instruments, &run_dir, 0.0, -12.0, limiter, &mastering,
             )?;
+            state.stage(Stage::Assembly, 1.0);
 
-            state.add_output("Output", full_mix);
-            state.add_output("A cappella", acappella);
+            state.finish_output("Output", &run_dir, full_mix);
+            let vocal_duration = vocal_len as f64 / sample_rate as f64;
+            let stems = write_stem_metadata(&run_dir, &acappella, vocal_duration, track.tempo, &melody.notes)?;
+            state.finish_output("A cappella", &run_dir, acappella);
+            state.add_log(&format!("Stem timing: {}", stems.display()));
 
             Ok(())
         })();
@@ -1280,55 +3215,70 @@ fn start_sing(app: &mut GlottisdaleApp) {
 
 fn start_speak(app: &mut GlottisdaleApp) {
     use glottisdale_core::audio::io::{extract_audio, read_wav};
-    use glottisdale_core::language::align::get_aligner;
-    use glottisdale_core::names::create_run_dir;
-    use glottisdale_core::speak::syllable_bank::build_bank;
-    use glottisdale_core::speak::target_text::{text_to_syllables, word_boundaries_from_syllables};
-    use glottisdale_core::speak::matcher::{match_syllables, match_phonemes};
-    use glottisdale_core::speak::assembler::{plan_timing, assemble};
+    use glottisdale_core::names::{create_run_dir_with, NameTheme};
+    use glottisdale_core::speak::syllable_bank::{build_bank, score_bank};
+    use glottisdale_core::speak::target_text::{
+        markup_to_syllables, phoneme_prosody, text_to_syllables_in, word_boundaries_from_syllables,
+    };
+    use glottisdale_core::speak::matcher::{
+        alternates, match_phonemes_in, match_syllables_with, write_matches_report, MatchStrategy,
+        DEFAULT_ALTERNATES,
+    };
+    use glottisdale_core::speak::assembler::{plan_timing_with, assemble};
 
     let state = app.processing.clone();
     state.clear();
     state.set_status(ProcessingStatus::Running("Starting speak...".into()));
 
     let inputs = app.source_files.clone();
+    let aligner_overrides = source_aligner_overrides(&inputs, &app.source_aligners);
+    let denoise = app.denoise.then_some(app.denoise_level);
+    let vad = app.vad;
+    let min_quality = app.min_quality;
     let output_dir = PathBuf::from(&app.output_dir);
     let seed = parse_seed(&app.seed);
     let run_name = if app.run_name.is_empty() { None } else { Some(app.run_name.clone()) };
+    let name_theme = app.name_theme.clone();
     let whisper_model = app.whisper_model.clone();
+    let language = app.language;
+    let device = app.device.clone();
     let aligner_name = app.aligner.clone();
+    let mastering = app.mastering();
+    let limiter = app.limiter;
     let settings = app.speak.clone();
+    let normalize_volume = settings.normalize_volume.then_some(settings.volume_normalization);
 
-    thread::spawn(move || {
+    note_crash_context(app, &settings);
+    spawn_pipeline("speak", &app.processing, move || {
         let result: anyhow::Result<()> = (|| {
             if settings.target_text.is_empty() && settings.reference_path.is_empty() {
                 anyhow::bail!("Either target text or reference audio is required");
             }
 
-            let run_dir = create_run_dir(&output_dir, seed, run_name.as_deref())?;
+            let theme = NameTheme::load(&name_theme)?;
+            let run_dir = create_run_dir_with(&output_dir, seed, run_name.as_deref(), &theme)?;
             let run_dir_name = run_dir.file_name().unwrap().to_string_lossy().to_string();
             state.add_log(&format!("Run: {}", run_dir_name));
 
             let work_dir = run_dir.join("work");
-            let audio_paths = prepare_audio(&inputs, &work_dir, &state)?;
+            let audio_paths = prepare_audio(&inputs, &work_dir, denoise, &state)?;
 
             state.set_status(ProcessingStatus::Running("Building syllable bank...".into()));
             state.add_log("Building source syllable bank...");
-            let aligner = get_aligner(&aligner_name, &whisper_model, "en", "cpu")?;
+            let aligners = input_aligners(&inputs, &aligner_overrides, &aligner_name, &whisper_model, language, &device, vad)?;
             let mut all_bank_entries = Vec::new();
             let mut source_audio = std::collections::HashMap::new();
             let mut source_syllables = std::collections::HashMap::new();
 
-            for audio_path in &audio_paths {
+            let alignments = align_sources(&aligners, &audio_paths, &state, min_quality)?;
+            for (audio_path, alignment) in audio_paths.iter().zip(alignments) {
                 let key = audio_path.to_string_lossy().to_string();
-                state.add_log(&format!("Aligning: {}", audio_path.file_name().unwrap().to_string_lossy()));
-                let alignment = aligner.process(audio_path, None)?;
-                let entries = build_bank(&alignment.syllables, &key);
+                let (samples, sr) = read_wav(audio_path)?;
+                let mut entries = build_bank(&alignment.syllables, &key);
+                score_bank(&mut entries, &samples, sr);
                 state.add_log(&format!("  {} syllables", entries.len()));
                 all_bank_entries.extend(entries);
                 source_syllables.insert(key.clone(), alignment.syllables);
-
-                let (samples, sr) = read_wav(audio_path)?;
                 source_audio.insert(key, (samples, sr));
             }
 
@@ -1347,7 +3297,9 @@ fn start_speak(app: &mut GlottisdaleApp) {
                 state.add_log(&format!("Transcribing reference: {}", ref_path.display()));
                 let ref_wav = work_dir.join("reference_16k.wav");
                 extract_audio(&ref_path, &ref_wav)?;
-                let ref_alignment = aligner.process(&ref_wav, None)?;
+                let ref_aligner =
+                    input_aligners(std::slice::from_ref(&ref_wav), &[], &aligner_name, &whisper_model, language, &device, vad)?;
+                let ref_alignment = ref_aligner[0].process(&ref_wav, None)?;
                 target_text = Some(ref_alignment.text);
                 reference_timings = Some(
                     ref_alignment.syllables.iter().map(|s| (s.start, s.end)).collect(),
@@ -1358,31 +3310,53 @@ fn start_speak(app: &mut GlottisdaleApp) {
                 .ok_or_else(|| anyhow::anyhow!("No target text (use text or reference)"))?;
             state.add_log(&format!("Target text: {}", target_text));
 
-            let target_syls = text_to_syllables(&target_text);
+            // Typed text may carry markup; transcripts are plain
+            let target_syls = if reference_timings.is_some() {
+                text_to_syllables_in(&target_text, language)
+            } else {
+                markup_to_syllables(&target_text, language)?
+            };
             let word_bounds = word_boundaries_from_syllables(&target_syls);
             state.add_log(&format!("Target: {} syllables, {} words", target_syls.len(), word_bounds.len()));
 
             state.set_status(ProcessingStatus::Running("Matching...".into()));
+            state.stage(Stage::Matching, 0.0);
             state.add_log(&format!("Matching ({} mode)...", settings.match_unit));
 
+            let target_phoneme_lists: Vec<Vec<String>> =
+                target_syls.iter().map(|ts| ts.phonemes.clone()).collect();
+            let target_stresses: Vec<Option<u8>> =
+                target_syls.iter().map(|ts| ts.stress).collect();
             let matches = if settings.match_unit == "phoneme" {
                 let all_phonemes: Vec<String> = target_syls
                     .iter()
                     .flat_map(|ts| ts.phonemes.clone())
                     .collect();
-                match_phonemes(&all_phonemes, &all_bank_entries)
+                match_phonemes_in(&all_phonemes, &all_bank_entries, language)
             } else {
-                let target_phoneme_lists: Vec<Vec<String>> =
-                    target_syls.iter().map(|ts| ts.phonemes.clone()).collect();
-                let target_stresses: Vec<Option<u8>> =
-                    target_syls.iter().map(|ts| ts.stress).collect();
-                match_syllables(
+                let strategy = MatchStrategy::from_name(&settings.match_strategy, settings.beam_width)
+                    .unwrap_or(MatchStrategy::Viterbi);
+                match_syllables_with(
                     &target_phoneme_lists,
                     &all_bank_entries,
                     Some(&target_stresses),
                     None,
+                    strategy,
+                    language,
                 )
             };
+            let alts = (settings.match_unit != "phoneme").then(|| {
+                alternates(
+                    &target_phoneme_lists,
+                    &all_bank_entries,
+                    Some(&target_stresses),
+                    DEFAULT_ALTERNATES,
+                    language,
+                )
+            });
+            if let Some(alts) = &alts {
+                write_matches_report(&run_dir.join("matches.json"), &target_syls, &matches, alts)?;
+            }
 
             let avg_dur = if all_bank_entries.is_empty() {
                 0.25
@@ -1391,15 +3365,22 @@ fn start_speak(app: &mut GlottisdaleApp) {
                     / all_bank_entries.len() as f64
             };
 
-            let timing = plan_timing(
+            let prosody = if settings.match_unit == "phoneme" {
+                phoneme_prosody(&target_syls)
+            } else {
+                target_syls.iter().map(|ts| ts.prosody).collect()
+            };
+            let timing = plan_timing_with(
                 &matches,
                 &word_bounds,
                 avg_dur,
                 reference_timings.as_deref(),
                 settings.timing_strictness,
+                &prosody,
             );
 
             state.set_status(ProcessingStatus::Running("Assembling...".into()));
+            state.stage(Stage::Assembly, 0.0);
             state.add_log("Assembling output audio...");
             let output_path = assemble(
                 &matches,
@@ -1408,17 +3389,40 @@ fn start_speak(app: &mut GlottisdaleApp) {
                 &run_dir,
                 settings.crossfade_ms,
                 None,
-                settings.normalize_volume,
+                normalize_volume.as_ref(),
                 settings.pitch_correct,
+                limiter,
+                &mastering,
             )?;
+            state.stage(Stage::Assembly, 1.0);
 
-            state.add_output("Output", output_path);
+            state.finish_output("Output", &run_dir, output_path);
 
             state.store_alignment(AlignmentData {
                 syllables: source_syllables,
                 audio: source_audio,
+                stereo: HashMap::new(),
                 pipeline_mode: EditorPipelineMode::Speak,
+                melody: None,
             });
+            if let Some(alts) = alts {
+                state.store_speak_review(SpeakReview::new(
+                    target_syls,
+                    matches,
+                    alts,
+                    SpeakTiming {
+                        word_bounds,
+                        avg_dur,
+                        reference_timings,
+                        prosody,
+                        timing_strictness: settings.timing_strictness,
+                        crossfade_ms: settings.crossfade_ms,
+                        normalize_volume,
+                        pitch_correct: settings.pitch_correct,
+                    },
+                    run_dir,
+                ));
+            }
 
             Ok(())
         })();
@@ -1433,41 +3437,163 @@ fn start_speak(app: &mut GlottisdaleApp) {
     });
 }
 
+/// Assemble the last speak run again with the alternates picked in the
+/// review panel, overwriting its output.
+fn start_speak_reassemble(app: &mut GlottisdaleApp) {
+    use glottisdale_core::speak::assembler::{plan_timing_with, assemble};
+    use glottisdale_core::speak::matcher::write_matches_report;
+
+    let Some(review) = app.processing.speak_review.lock().unwrap().clone() else {
+        return;
+    };
+    let Some(alignment) = app.processing.get_alignment() else {
+        return;
+    };
+    let mastering = app.mastering();
+    let limiter = app.limiter;
+    let state = app.processing.clone();
+    state.set_status(ProcessingStatus::Running("Reassembling...".into()));
+
+    spawn_pipeline("speak", &app.processing, move || {
+        let result: anyhow::Result<()> = (|| {
+            let matches = review.chosen_matches();
+            let t = &review.timing;
+            let timing = plan_timing_with(
+                &matches,
+                &t.word_bounds,
+                t.avg_dur,
+                t.reference_timings.as_deref(),
+                t.timing_strictness,
+                &t.prosody,
+            );
+            state.add_log("Reassembling with the chosen alternates...");
+            let output_path = assemble(
+                &matches,
+                &timing,
+                &alignment.audio,
+                &review.run_dir,
+                t.crossfade_ms,
+                None,
+                t.normalize_volume.as_ref(),
+                t.pitch_correct,
+                limiter,
+                &mastering,
+            )?;
+            write_matches_report(&review.run_dir.join("matches.json"), &review.targets, &matches, &review.alternates)?;
+            state.add_log(&format!("Output: {}", output_path.display()));
+            Ok(())
+        })();
+
+        match result {
+            Ok(()) => state.set_status(ProcessingStatus::Done("Reassembled".into())),
+            Err(e) => {
+                state.add_log(&format!("ERROR: {:#}", e));
+                state.set_status(ProcessingStatus::Error(format!("{}", e)));
+            }
+        }
+    });
+}
+
 /// Run alignment only and auto-open the editor when done.
 fn start_alignment_only(app: &mut GlottisdaleApp) {
     use glottisdale_core::audio::io::read_wav;
-    use glottisdale_core::language::align::get_aligner;
+    use glottisdale_core::editor::bank_builder::{clips_from_source, shared_source};
+    use glottisdale_core::temp;
 
     let state = app.processing.clone();
     state.clear();
     state.set_status(ProcessingStatus::Running("Building syllable bank...".into()));
 
     let inputs = app.source_files.clone();
+    let aligner_overrides = source_aligner_overrides(&inputs, &app.source_aligners);
+    let denoise = app.denoise.then_some(app.denoise_level);
+    let vad = app.vad;
+    let min_quality = app.min_quality;
     let whisper_model = app.whisper_model.clone();
+    let language = app.language;
+    let device = app.device.clone();
     let aligner_name = app.aligner.clone();
+    let word_units = app.unit == "word";
     let pipeline_mode = app.mode.to_editor_mode();
 
-    thread::spawn(move || {
+    note_crash_context(app, &"alignment only");
+    spawn_pipeline("alignment", &app.processing, move || {
         let result: anyhow::Result<()> = (|| {
-            let work_dir = std::env::temp_dir().join("glottisdale-alignment");
-            let audio_paths = prepare_audio(&inputs, &work_dir, &state)?;
+            // A fresh directory per run; old runs' are pruned to a size cap
+            match temp::prune_temp_dirs(temp::DEFAULT_TEMP_CAP_BYTES) {
+                Ok(report) if !report.removed.is_empty() => state.add_log(&format!(
+                    "Removed {} old temp folder(s), {:.0} MB",
+                    report.removed.len(),
+                    report.freed_bytes as f64 / 1e6
+                )),
+                Ok(_) => {}
+                Err(e) => log::warn!("Temp cleanup failed: {:#}", e),
+            }
+            let work_dir = temp::create_temp_dir("alignment")?;
+            let audio_paths = prepare_audio(&inputs, &work_dir, denoise, &state)?;
 
             state.add_log("Aligning syllables...");
             state.set_status(ProcessingStatus::Running("Aligning...".into()));
-            let aligner = get_aligner(&aligner_name, &whisper_model, "en", "cpu")?;
+            let aligners = input_aligners(&inputs, &aligner_overrides, &aligner_name, &whisper_model, language, &device, vad)?;
+            state.request_stream_editor(pipeline_mode);
 
             let mut source_syllables = HashMap::new();
             let mut source_audio = HashMap::new();
 
-            for audio_path in &audio_paths {
+            let source_count = audio_paths.len() as f64;
+            for (i, audio_path) in audio_paths.iter().enumerate() {
                 let key = audio_path.to_string_lossy().to_string();
-                state.add_log(&format!(
-                    "Aligning: {}",
-                    audio_path.file_name().unwrap().to_string_lossy()
-                ));
-                let alignment = aligner.process(audio_path, None)?;
+                let file_name = audio_path.file_name().unwrap().to_string_lossy().to_string();
+                state.add_log(&format!("Aligning: {}", file_name));
+
+                // Stream partial syllables into the editor bank as segments finish
                 let (samples, sr) = read_wav(audio_path)?;
-                source_syllables.insert(key.clone(), alignment.syllables);
+                let progress_state = state.clone();
+                // Partial clips share one f32 copy of the source
+                let progress_source = shared_source(&samples);
+                let progress_path = audio_path.clone();
+                let mut alignment = aligners[i].process_with_progress(
+                    audio_path,
+                    None,
+                    Box::new(move |event| match event {
+                        AlignmentProgress::Fraction(f) => {
+                            progress_state.stage(Stage::Alignment, (i as f64 + f) / source_count);
+                            progress_state.set_status(ProcessingStatus::Running(format!(
+                                "Aligning {} ({:.0}%)",
+                                file_name,
+                                f * 100.0
+                            )));
+                        }
+                        AlignmentProgress::Partial(syllables) => {
+                            let syllables = if word_units {
+                                merge_into_words(&syllables)
+                            } else {
+                                syllables
+                            };
+                            progress_state.push_partial_clips(clips_from_source(
+                                &syllables,
+                                &progress_source,
+                                sr,
+                                &progress_path,
+                            ));
+                        }
+                    }),
+                )?;
+                let dropped = glottisdale_core::audio::analysis::retain_quality_syllables(
+                    &mut alignment.syllables,
+                    &samples,
+                    sr,
+                    min_quality,
+                );
+                if dropped > 0 {
+                    state.add_log(&format!("  Dropped {} low-quality syllables", dropped));
+                }
+                let syllables = if word_units {
+                    merge_into_words(&alignment.syllables)
+                } else {
+                    alignment.syllables
+                };
+                source_syllables.insert(key.clone(), syllables);
                 source_audio.insert(key, (samples, sr));
             }
 
@@ -1477,7 +3603,9 @@ fn start_alignment_only(app: &mut GlottisdaleApp) {
             state.store_alignment(AlignmentData {
                 syllables: source_syllables,
                 audio: source_audio,
+                stereo: HashMap::new(),
                 pipeline_mode,
+                melody: None,
             });
 
             state.set_auto_open_editor();
@@ -1496,7 +3624,7 @@ fn start_alignment_only(app: &mut GlottisdaleApp) {
 }
 
 /// Open a file or directory in the system's default handler.
-fn open_path(path: &Path) {
+pub(crate) fn open_path(path: &Path) {
     #[cfg(target_os = "macos")]
     {
         std::process::Command::new("open").arg(path).spawn().ok();
@@ -1515,3 +3643,200 @@ fn open_path(path: &Path) {
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wait_for_final_status(state: &ProcessingState) -> ProcessingStatus {
+        for _ in 0..200 {
+            let status = state.get_status();
+            if !matches!(status, ProcessingStatus::Running(_)) {
+                return status;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        state.get_status()
+    }
+
+    #[test]
+    fn test_speak_review_options_and_words() {
+        use glottisdale_core::speak::syllable_bank::SyllableEntry;
+        use glottisdale_core::speak::target_text::text_to_syllables_in;
+
+        let targets = text_to_syllables_in("hello cat", Language::English);
+        let candidate = |target: usize, index: usize, distance: i32| MatchResult {
+            target_phonemes: targets[target].phonemes.clone(),
+            entry: SyllableEntry {
+                phoneme_labels: vec!["AH0".into()],
+                start: index as f64 * 0.2,
+                end: index as f64 * 0.2 + 0.2,
+                word: format!("w{}", index),
+                stress: None,
+                source: "a.wav".into(),
+                index,
+                quality: None,
+            },
+            distance,
+            target_index: target,
+        };
+        let matches = vec![candidate(0, 4, 3), candidate(1, 5, 2), candidate(2, 6, 0)];
+        // The run's match also shows up among the alternates of syllable 0
+        let alternates = vec![
+            vec![candidate(0, 1, 1), candidate(0, 4, 3)],
+            vec![candidate(1, 2, 2)],
+            vec![candidate(2, 6, 0)],
+        ];
+        let timing = SpeakTiming {
+            word_bounds: vec![0, 2],
+            avg_dur: 0.2,
+            reference_timings: None,
+            prosody: vec![Prosody::default(); 3],
+            timing_strictness: 0.8,
+            crossfade_ms: 10.0,
+            normalize_volume: Some(RmsNormalization::default()),
+            pitch_correct: false,
+        };
+        let mut review = SpeakReview::new(targets, matches, alternates, timing, PathBuf::from("run"));
+
+        let indices = |i: usize| review.options(i).iter().map(|m| m.entry.index).collect::<Vec<_>>();
+        assert_eq!(indices(0), vec![4, 1]);
+        assert_eq!(indices(2), vec![6]);
+        assert_eq!(review.words(), vec![0..2, 2..3]);
+
+        review.chosen[0] = 1;
+        let picked: Vec<usize> = review.chosen_matches().iter().map(|m| m.entry.index).collect();
+        assert_eq!(picked, vec![1, 5, 6]);
+    }
+
+    #[test]
+    fn test_parse_seed_accepts_names() {
+        assert_eq!(parse_seed(""), None);
+        assert_eq!(parse_seed(" 42 "), Some(42));
+        assert_eq!(
+            parse_seed("breathy-bassoon"),
+            Some(glottisdale_core::names::seed_from_name("breathy-bassoon"))
+        );
+    }
+
+    #[test]
+    fn test_collect_media_files_scans_folders() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_drop_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("nested")).unwrap();
+        for name in ["b.wav", "notes.txt", "nested/a.MP3", "nested/cover.jpg"] {
+            std::fs::write(dir.join(name), b"").unwrap();
+        }
+        let loose = dir.join("b.wav");
+        let found = collect_media_files(&[dir.clone(), loose.clone(), dir.join("notes.txt")]);
+        assert_eq!(found, vec![loose.clone(), dir.join("nested/a.MP3"), loose]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_source_aligner_overrides_follow_inputs() {
+        let pick = |file: &str, aligner: &str| AlignerOverride {
+            file: PathBuf::from(file),
+            aligner: aligner.to_string(),
+            whisper_model: None,
+        };
+        let inputs = vec![PathBuf::from("/data/studio.wav"), PathBuf::from("/data/phone.wav")];
+        let mut chosen = HashMap::new();
+        // A pick for a source that has since been removed is left out
+        for o in [pick("/data/phone.wav", "default"), pick("/data/gone.wav", "bfa"), pick("/data/studio.wav", "auto")] {
+            chosen.insert(o.file.clone(), o);
+        }
+        let overrides = source_aligner_overrides(&inputs, &chosen);
+        assert_eq!(overrides, vec![pick("/data/studio.wav", "auto"), pick("/data/phone.wav", "default")]);
+
+        chosen.insert(PathBuf::from("/data/phone.wav"), pick("/data/phone.wav", "bfa"));
+        let overrides = source_aligner_overrides(&inputs, &chosen);
+        assert!(input_aligners(&inputs, &overrides[..1], "default", "base", Language::English, "cpu", false).is_ok());
+        // The phone source now asks for an unavailable backend
+        assert!(input_aligners(&inputs, &overrides, "default", "base", Language::English, "cpu", false).is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(4.4), "0:04");
+        assert_eq!(format_duration(125.0), "2:05");
+        assert_eq!(format_duration(3725.0), "1:02:05");
+    }
+
+    #[test]
+    fn test_log_keeps_recent_lines() {
+        let state = ProcessingState::new();
+        for i in 0..MAX_LOG_LINES + 10 {
+            state.add_log(&format!("line {}", i));
+        }
+        let logs = state.get_logs();
+        assert_eq!(logs.len(), MAX_LOG_LINES);
+        assert_eq!(logs[0].text, "line 10");
+        assert_eq!(logs.last().unwrap().text, format!("line {}", MAX_LOG_LINES + 9));
+    }
+
+    #[test]
+    fn test_log_filter_levels_and_search() {
+        let lines: Vec<LogLine> = ["ERROR: boom", "WARNING: odd", "Aligning: a.wav", "  12 syllables"]
+            .iter()
+            .map(|m| LogLine::new(m))
+            .collect();
+        let levels: Vec<log::Level> = lines.iter().map(|l| l.level).collect();
+        assert_eq!(levels, [log::Level::Error, log::Level::Warn, log::Level::Info, log::Level::Debug]);
+
+        let mut filter = LogFilter::default();
+        assert!(lines.iter().all(|l| filter.matches(l)));
+        filter.debug = false;
+        filter.info = false;
+        assert_eq!(lines.iter().filter(|l| filter.matches(l)).count(), 2);
+        filter.search = "BOOM".into();
+        assert_eq!(lines.iter().filter(|l| filter.matches(l)).count(), 1);
+    }
+
+    #[test]
+    fn test_watchdog_fails_panicked_run() {
+        let state = ProcessingState::new();
+        state.set_status(ProcessingStatus::Running("Aligning...".into()));
+        spawn_pipeline("test-panic", &state, || panic!("aligner exploded"));
+        match wait_for_final_status(&state) {
+            ProcessingStatus::Error(msg) => assert!(msg.contains("aligner exploded")),
+            other => panic!("expected an error, got {:?}", other),
+        }
+        assert!(state.get_logs().iter().any(|l| l.text.contains("test-panic thread crashed")));
+    }
+
+    #[test]
+    fn test_watchdog_catches_silent_exit_and_keeps_results() {
+        let state = ProcessingState::new();
+        state.set_status(ProcessingStatus::Running("Starting...".into()));
+        spawn_pipeline("test-silent", &state, || {});
+        assert!(matches!(wait_for_final_status(&state), ProcessingStatus::Error(_)));
+
+        let done = ProcessingState::new();
+        done.set_status(ProcessingStatus::Running("Starting...".into()));
+        let thread_state = done.clone();
+        spawn_pipeline("test-done", &done, move || {
+            thread_state.set_status(ProcessingStatus::Done("Completed successfully".into()))
+        });
+        assert!(matches!(wait_for_final_status(&done), ProcessingStatus::Done(_)));
+    }
+
+    #[test]
+    fn test_stop_and_preview_reset_for_next_run() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_gui_preview_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let preview = dir.join("preview.wav");
+        let state = ProcessingState::new();
+        state.set_preview(preview.clone());
+        // Not offered until the pipeline has written it
+        assert_eq!(state.get_preview(), None);
+        std::fs::write(&preview, b"RIFF").unwrap();
+        assert_eq!(state.get_preview(), Some(preview));
+
+        state.request_cancel();
+        assert!(state.cancelled());
+        state.clear();
+        assert!(!state.cancelled());
+        assert_eq!(state.get_preview(), None);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,234 @@
+//! Crash reports: a panic hook that writes the backtrace, recent log lines,
+//! and the active run's settings to the output dir so the GUI can tell the
+//! user what happened instead of a worker thread dying silently.
+
+use std::collections::VecDeque;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Log lines kept for crash reports.
+const RECENT_LOG_LINES: usize = 200;
+
+static RECENT_LOGS: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static CONTEXT: Mutex<Option<CrashContext>> = Mutex::new(None);
+static CRASHES: Mutex<Vec<CrashNotice>> = Mutex::new(Vec::new());
+
+/// Where reports go and what was running.
+#[derive(Debug, Clone)]
+struct CrashContext {
+    output_dir: PathBuf,
+    config: String,
+}
+
+/// A panic the GUI has not shown yet.
+#[derive(Debug, Clone)]
+pub struct CrashNotice {
+    pub thread: String,
+    pub message: String,
+    /// Written report, or `None` if it could not be saved.
+    pub report: Option<PathBuf>,
+}
+
+/// Logger that keeps recent lines for crash reports and forwards to env_logger.
+struct RecordingLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for RecordingLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.inner.matches(record) {
+            // try_lock: never block (or deadlock) inside a panicking log call
+            if let Ok(mut recent) = RECENT_LOGS.try_lock() {
+                if recent.len() == RECENT_LOG_LINES {
+                    recent.pop_front();
+                }
+                recent.push_back(format!("[{} {}] {}", record.level(), record.target(), record.args()));
+            }
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Set up env_logger, keeping recent lines for crash reports.
+pub fn init_logging() {
+    let inner = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_timestamp(None)
+        .build();
+    let level = inner.filter();
+    if log::set_boxed_logger(Box::new(RecordingLogger { inner })).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+/// Record where reports go and the settings of the run that is starting.
+pub fn set_context(output_dir: &Path, config: String) {
+    *CONTEXT.lock().unwrap_or_else(|e| e.into_inner()) = Some(CrashContext {
+        output_dir: output_dir.to_path_buf(),
+        config,
+    });
+}
+
+/// Take panics recorded since the last call.
+pub fn take_crashes() -> Vec<CrashNotice> {
+    std::mem::take(&mut *CRASHES.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Install the panic hook. The default hook still prints to stderr.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+
+        let thread = std::thread::current().name().unwrap_or("background").to_string();
+        let message = panic_message(info);
+        let location = info.location().map(|l| l.to_string()).unwrap_or_default();
+        let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+        let context = CONTEXT.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        let logs: Vec<String> = RECENT_LOGS
+            .try_lock()
+            .map(|l| l.iter().cloned().collect())
+            .unwrap_or_default();
+
+        let report = crash_report(
+            &thread,
+            &message,
+            &location,
+            &backtrace,
+            context.as_ref().map(|c| c.config.as_str()),
+            &logs,
+        );
+        let dir = context
+            .map(|c| c.output_dir)
+            .unwrap_or_else(std::env::temp_dir)
+            .join("crash-reports");
+        let path = match write_report(&dir, &report) {
+            Ok(path) => {
+                eprintln!("Crash report written to {}", path.display());
+                Some(path)
+            }
+            Err(e) => {
+                eprintln!("Failed to write crash report: {}", e);
+                None
+            }
+        };
+
+        if thread == "main" {
+            // The UI is gone; this is the only chance to tell the user
+            let detail = match &path {
+                Some(p) => format!("{}\n\nA crash report was saved to:\n{}", message, p.display()),
+                None => message.clone(),
+            };
+            rfd::MessageDialog::new()
+                .set_level(rfd::MessageLevel::Error)
+                .set_title("Glottisdale crashed")
+                .set_description(detail)
+                .show();
+        } else {
+            CRASHES.lock().unwrap_or_else(|e| e.into_inner()).push(CrashNotice {
+                thread,
+                message,
+                report: path,
+            });
+        }
+    }));
+}
+
+fn panic_message(info: &std::panic::PanicHookInfo) -> String {
+    payload_message(info.payload())
+}
+
+/// Text of a panic payload (from a hook or a failed thread join).
+pub fn payload_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Format a crash report.
+fn crash_report(
+    thread: &str,
+    message: &str,
+    location: &str,
+    backtrace: &str,
+    config: Option<&str>,
+    logs: &[String],
+) -> String {
+    let mut out = String::new();
+    out.push_str(&format!("Glottisdale {} crash report\n\n", env!("CARGO_PKG_VERSION")));
+    out.push_str(&format!("Thread: {}\nPanic: {}\n", thread, message));
+    if !location.is_empty() {
+        out.push_str(&format!("Location: {}\n", location));
+    }
+    out.push_str(&format!("\n== Backtrace\n{}\n", backtrace.trim_end()));
+    out.push_str(&format!("\n== Settings\n{}\n", config.unwrap_or("(no run started)")));
+    out.push_str(&format!("\n== Recent log ({} lines)\n", logs.len()));
+    for line in logs {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+fn write_report(dir: &Path, report: &str) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut n = 0;
+    loop {
+        let name = if n == 0 {
+            format!("crash-{}.txt", secs)
+        } else {
+            format!("crash-{}-{}.txt", secs, n)
+        };
+        let path = dir.join(name);
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(report.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crash_report_sections() {
+        let logs = vec!["[INFO glottisdale] Aligning".to_string()];
+        let report = crash_report("collage", "boom", "src/app.rs:1:1", "0: main", Some("seed: 42"), &logs);
+        assert!(report.contains("Thread: collage\nPanic: boom\nLocation: src/app.rs:1:1"));
+        assert!(report.contains("== Backtrace\n0: main"));
+        assert!(report.contains("== Settings\nseed: 42"));
+        assert!(report.ends_with("== Recent log (1 lines)\n[INFO glottisdale] Aligning\n"));
+        assert!(crash_report("t", "m", "", "", None, &[]).contains("(no run started)"));
+    }
+
+    #[test]
+    fn test_write_report_never_overwrites() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_crash_{}", std::process::id()));
+        let a = write_report(&dir, "one").unwrap();
+        let b = write_report(&dir, "two").unwrap();
+        assert_ne!(a, b);
+        assert_eq!(std::fs::read_to_string(&a).unwrap(), "one");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,197 @@
+//! Automation lanes below the clip track: gain and pitch envelopes drawn
+//! on the timeline's time axis, edited by their breakpoints.
+
+use eframe::egui;
+use glottisdale_core::editor::{Automation, AutomationParam, Envelope};
+
+use super::timeline::TimelineState;
+
+/// Height of one lane (pixels).
+const LANE_HEIGHT: f32 = 48.0;
+
+/// How close the pointer must be to grab a breakpoint (pixels).
+const GRAB_RADIUS_PX: f32 = 7.0;
+
+/// Lanes' visibility and the breakpoint being dragged.
+#[derive(Default)]
+pub struct AutomationState {
+    /// Whether the lanes are shown below the clip track.
+    pub visible: bool,
+    /// Breakpoint under the pointer's drag: its lane and index.
+    drag: Option<(AutomationParam, usize)>,
+}
+
+/// Vertical position of `value` in a lane for `param`.
+fn value_to_y(param: AutomationParam, value: f64, rect: egui::Rect) -> f32 {
+    let (min, max) = param.range();
+    let t = ((value - min) / (max - min)) as f32;
+    rect.bottom() - 3.0 - t * (rect.height() - 6.0)
+}
+
+/// Lane value at vertical position `y`, snapped to a tenth.
+pub fn y_to_value(param: AutomationParam, y: f32, rect: egui::Rect) -> f64 {
+    let (min, max) = param.range();
+    let t = ((rect.bottom() - 3.0 - y) / (rect.height() - 6.0)).clamp(0.0, 1.0) as f64;
+    ((min + t * (max - min)) * 10.0).round() / 10.0
+}
+
+/// Index of the breakpoint within grabbing distance of `pos`, if any.
+fn point_at(
+    param: AutomationParam,
+    lane: &Envelope,
+    pos: egui::Pos2,
+    rect: egui::Rect,
+    timeline: &TimelineState,
+) -> Option<usize> {
+    lane.points
+        .iter()
+        .map(|p| egui::pos2(timeline.time_to_px(p.time_s) + rect.left(), value_to_y(param, p.value, rect)))
+        .enumerate()
+        .map(|(i, p)| (i, p.distance(pos)))
+        .filter(|&(_, d)| d <= GRAB_RADIUS_PX)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+/// Show the gain and pitch lanes. Returns true once an edit is finished:
+/// a breakpoint added, removed, or dropped after a drag.
+pub fn show_automation_lanes(
+    ui: &mut egui::Ui,
+    automation: &mut Automation,
+    timeline: &TimelineState,
+    state: &mut AutomationState,
+) -> bool {
+    let mut edited = false;
+    for param in AutomationParam::ALL {
+        ui.push_id(param.name(), |ui| {
+            edited |= show_lane(ui, param, automation.lane_mut(param), timeline, state);
+        });
+    }
+    edited
+}
+
+fn show_lane(
+    ui: &mut egui::Ui,
+    param: AutomationParam,
+    lane: &mut Envelope,
+    timeline: &TimelineState,
+    state: &mut AutomationState,
+) -> bool {
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(ui.available_width(), LANE_HEIGHT), egui::Sense::click_and_drag());
+    let response = response.on_hover_text(format!(
+        "{} automation: double-click to add a point, drag to move it, right-click to remove it",
+        param.name()
+    ));
+    let mut edited = false;
+    let pointer_time = |pos: egui::Pos2| timeline.px_to_time(pos.x - rect.left()).max(0.0);
+
+    if response.drag_started() {
+        state.drag = response
+            .interact_pointer_pos()
+            .and_then(|pos| point_at(param, lane, pos, rect, timeline))
+            .map(|index| (param, index));
+    }
+    if let (Some((dragged, index)), Some(pos)) = (state.drag, response.interact_pointer_pos()) {
+        if dragged == param && response.dragged() {
+            let value = y_to_value(param, pos.y, rect);
+            state.drag = lane.move_point(param, index, pointer_time(pos), value).map(|i| (param, i));
+        }
+    }
+    if response.drag_stopped() && state.drag.is_some_and(|(dragged, _)| dragged == param) {
+        state.drag = None;
+        edited = true;
+    }
+    if response.double_clicked() {
+        if let Some(pos) = response.interact_pointer_pos() {
+            if point_at(param, lane, pos, rect, timeline).is_none() {
+                lane.insert(param, pointer_time(pos), y_to_value(param, pos.y, rect));
+                edited = true;
+            }
+        }
+    }
+    if response.secondary_clicked() {
+        if let Some(index) = response.interact_pointer_pos().and_then(|pos| point_at(param, lane, pos, rect, timeline)) {
+            lane.remove(index);
+            edited = true;
+        }
+    }
+
+    if ui.is_rect_visible(rect) {
+        paint_lane(ui, param, lane, rect, timeline, state.drag);
+        if let Some(pos) = response.hover_pos() {
+            ui.painter_at(rect).text(
+                rect.right_top() + egui::vec2(-4.0, 2.0),
+                egui::Align2::RIGHT_TOP,
+                format!("{:+.1} {} @ {:.2}s", y_to_value(param, pos.y, rect), param.unit(), pointer_time(pos)),
+                egui::FontId::proportional(10.0),
+                egui::Color32::from_gray(160),
+            );
+        }
+    }
+    edited
+}
+
+fn paint_lane(
+    ui: &egui::Ui,
+    param: AutomationParam,
+    lane: &Envelope,
+    rect: egui::Rect,
+    timeline: &TimelineState,
+    drag: Option<(AutomationParam, usize)>,
+) {
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(24));
+    painter.hline(rect.x_range(), value_to_y(param, 0.0, rect), egui::Stroke::new(1.0, egui::Color32::from_gray(60)));
+    painter.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        format!("{} ({})", param.name(), param.unit()),
+        egui::FontId::proportional(10.0),
+        egui::Color32::from_gray(160),
+    );
+
+    let color = match param {
+        AutomationParam::Gain => egui::Color32::from_rgb(120, 200, 120),
+        AutomationParam::Pitch => egui::Color32::from_rgb(200, 150, 240),
+    };
+    let to_pos = |time_s: f64, value: f64| {
+        egui::pos2(timeline.time_to_px(time_s) + rect.left(), value_to_y(param, value, rect))
+    };
+    // Held flat before the first point and after the last
+    if let (Some(first), Some(last)) = (lane.points.first(), lane.points.last()) {
+        let mut line = vec![egui::pos2(rect.left(), value_to_y(param, first.value, rect))];
+        line.extend(lane.points.iter().map(|p| to_pos(p.time_s, p.value)));
+        line.push(egui::pos2(rect.right(), value_to_y(param, last.value, rect)));
+        painter.add(egui::Shape::line(line, egui::Stroke::new(1.5, color)));
+    }
+    for (i, p) in lane.points.iter().enumerate() {
+        let grabbed = drag == Some((param, i));
+        painter.circle_filled(to_pos(p.time_s, p.value), if grabbed { 5.0 } else { 3.5 }, color);
+    }
+
+    let cursor_x = timeline.time_to_px(timeline.cursor_s) + rect.left();
+    if rect.x_range().contains(cursor_x) {
+        painter.vline(cursor_x, rect.y_range(), egui::Stroke::new(1.0, egui::Color32::RED.gamma_multiply(0.6)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lane_values_map_to_heights_and_back() {
+        let rect = egui::Rect::from_min_size(egui::pos2(0.0, 100.0), egui::vec2(400.0, LANE_HEIGHT));
+        for param in AutomationParam::ALL {
+            let (min, max) = param.range();
+            assert_eq!(value_to_y(param, max, rect), rect.top() + 3.0);
+            assert_eq!(value_to_y(param, min, rect), rect.bottom() - 3.0);
+            for value in [min, -6.5, 0.0, max] {
+                assert_eq!(y_to_value(param, value_to_y(param, value, rect), rect), value);
+            }
+        }
+        // Dragging past the lane clamps to its range
+        assert_eq!(y_to_value(AutomationParam::Pitch, 0.0, rect), 12.0);
+    }
+}
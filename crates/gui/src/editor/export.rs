@@ -0,0 +1,286 @@
+//! Export dialog: render the arrangement to disk on a worker thread, with
+//! progress, cancel, overwrite confirmation, and a success toast.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use eframe::egui;
+use glottisdale_core::audio::encode::OutputFormat;
+use glottisdale_core::editor::render::{export_arrangement, RenderSettings};
+use glottisdale_core::editor::Arrangement;
+use glottisdale_core::progress::ProgressReporter;
+
+use crate::app::open_path;
+
+/// How long the success toast stays up (seconds).
+const TOAST_S: f64 = 8.0;
+
+/// Progress shared between the export thread and the UI.
+#[derive(Default)]
+struct JobProgress {
+    fraction: Mutex<f64>,
+    cancel: AtomicBool,
+    /// Set once the thread finishes: Ok, or the error message.
+    result: Mutex<Option<Result<(), String>>>,
+}
+
+impl ProgressReporter for JobProgress {
+    fn report(&self, _stage: &str, fraction: f64) {
+        *self.fraction.lock().unwrap() = fraction;
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+/// An export running on a worker thread.
+struct ExportJob {
+    path: PathBuf,
+    progress: Arc<JobProgress>,
+}
+
+/// What a finished export left behind.
+#[derive(Debug, PartialEq)]
+pub enum ExportOutcome {
+    Done(PathBuf),
+    Cancelled,
+    Failed(String),
+}
+
+/// Export dialog state.
+#[derive(Default)]
+pub struct ExportState {
+    /// Chosen path that already exists, awaiting overwrite confirmation.
+    pub confirm_overwrite: Option<PathBuf>,
+    job: Option<ExportJob>,
+    /// Last successful export and when its toast appeared (egui time).
+    toast: Option<(PathBuf, f64)>,
+}
+
+impl ExportState {
+    /// Whether an export is rendering.
+    pub fn is_running(&self) -> bool {
+        self.job.is_some()
+    }
+
+    /// Overall progress of the running export (0–1).
+    pub fn fraction(&self) -> f64 {
+        self.job
+            .as_ref()
+            .map(|j| *j.progress.fraction.lock().unwrap())
+            .unwrap_or(0.0)
+    }
+
+    /// Start exporting to `path`, or ask first if the file exists.
+    ///
+    /// Returns true if the export started.
+    pub fn request(
+        &mut self,
+        path: PathBuf,
+        arrangement: &Arrangement,
+        settings: &RenderSettings,
+        format: OutputFormat,
+    ) -> bool {
+        if path.exists() {
+            self.confirm_overwrite = Some(path);
+            return false;
+        }
+        self.start(path, arrangement, settings, format);
+        true
+    }
+
+    /// Render and write on a worker thread, replacing any existing file.
+    pub fn start(
+        &mut self,
+        path: PathBuf,
+        arrangement: &Arrangement,
+        settings: &RenderSettings,
+        format: OutputFormat,
+    ) {
+        if self.is_running() {
+            return;
+        }
+        self.confirm_overwrite = None;
+        self.toast = None;
+        let progress = Arc::new(JobProgress::default());
+        let arrangement = arrangement.clone();
+        let settings = settings.clone();
+        let thread_path = path.clone();
+        let thread_progress = Arc::clone(&progress);
+        std::thread::spawn(move || {
+            let result = export_arrangement(&arrangement, &settings, &thread_path, format, thread_progress.as_ref())
+                .map_err(|e| format!("{:#}", e));
+            *thread_progress.result.lock().unwrap() = Some(result);
+        });
+        self.job = Some(ExportJob { path, progress });
+    }
+
+    /// Ask the running export to stop; nothing is written.
+    pub fn cancel(&self) {
+        if let Some(job) = &self.job {
+            job.progress.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Collect the running export's outcome once it finishes.
+    pub fn poll(&mut self) -> Option<ExportOutcome> {
+        let result = self.job.as_ref()?.progress.result.lock().unwrap().take()?;
+        let job = self.job.take()?;
+        Some(match result {
+            Ok(()) => ExportOutcome::Done(job.path),
+            Err(_) if job.progress.cancelled() => ExportOutcome::Cancelled,
+            Err(e) => ExportOutcome::Failed(e),
+        })
+    }
+}
+
+/// Show the overwrite prompt, the progress window, and the success toast.
+///
+/// Returns an error message if an export failed this frame.
+pub fn show_export_windows(
+    ctx: &egui::Context,
+    export: &mut ExportState,
+    arrangement: &Arrangement,
+    settings: &RenderSettings,
+    format: OutputFormat,
+) -> Option<String> {
+    let now = ctx.input(|i| i.time);
+    let mut error = None;
+
+    match export.poll() {
+        Some(ExportOutcome::Done(path)) => export.toast = Some((path, now)),
+        Some(ExportOutcome::Failed(e)) => {
+            log::error!("Export failed: {}", e);
+            error = Some(format!("Export failed: {}", e));
+        }
+        Some(ExportOutcome::Cancelled) => log::info!("Export cancelled"),
+        None => {}
+    }
+
+    if let Some(path) = export.confirm_overwrite.clone() {
+        let mut overwrite = false;
+        let mut keep = false;
+        egui::Window::new("File Exists")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!("{} already exists. Replace it?", display_name(&path)));
+                ui.horizontal(|ui| {
+                    if ui.button("Replace").clicked() {
+                        overwrite = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        keep = true;
+                    }
+                });
+            });
+        if overwrite {
+            export.start(path, arrangement, settings, format);
+        } else if keep {
+            export.confirm_overwrite = None;
+        }
+    }
+
+    if let Some(job) = &export.job {
+        let fraction = export.fraction() as f32;
+        egui::Window::new("Exporting")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(display_name(&job.path));
+                ui.add(egui::ProgressBar::new(fraction).show_percentage().desired_width(260.0));
+                if job.progress.cancelled() {
+                    ui.label("Cancelling...");
+                } else if ui.button("Cancel").clicked() {
+                    export.cancel();
+                }
+            });
+        ctx.request_repaint();
+    }
+
+    if let Some((path, shown_at)) = export.toast.clone() {
+        if now - shown_at > TOAST_S {
+            export.toast = None;
+        } else {
+            egui::Area::new(egui::Id::new("export_toast"))
+                .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+                .show(ctx, |ui| {
+                    egui::Frame::popup(ui.style()).show(ui, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("Exported {}", display_name(&path)));
+                            if ui.button("Open Folder").clicked() {
+                                if let Some(dir) = path.parent() {
+                                    open_path(dir);
+                                }
+                                export.toast = None;
+                            }
+                            if ui.small_button("x").clicked() {
+                                export.toast = None;
+                            }
+                        });
+                    });
+                });
+            ctx.request_repaint_after(std::time::Duration::from_secs_f64(TOAST_S));
+        }
+    }
+
+    error
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glottisdale_core::editor::{EditorPipelineMode, SyllableClip, TimelineClip};
+
+    fn arrangement() -> Arrangement {
+        let samples = glottisdale_core::editor::SourceBuffer::from(vec![0.3f32; 1600]);
+        let clip = SyllableClip::from_range(&samples, 16000, "src.wav".into(), 0.0, 0.1, "a");
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.timeline.push(TimelineClip::new(&clip));
+        arr.bank.push(clip);
+        arr.relayout(0.0);
+        arr
+    }
+
+    fn wait(export: &mut ExportState) -> ExportOutcome {
+        loop {
+            if let Some(outcome) = export.poll() {
+                return outcome;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_export_runs_to_completion() {
+        let path = std::env::temp_dir().join(format!("glottisdale_gui_export_{}.wav", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let mut export = ExportState::default();
+        assert!(export.request(path.clone(), &arrangement(), &RenderSettings::bypass(), OutputFormat::Wav));
+        assert!(export.is_running());
+        assert_eq!(wait(&mut export), ExportOutcome::Done(path.clone()));
+        assert!(!export.is_running());
+        assert!(path.exists());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_existing_file_needs_confirmation() {
+        let path = std::env::temp_dir().join(format!("glottisdale_gui_overwrite_{}.wav", std::process::id()));
+        std::fs::write(&path, b"keep").unwrap();
+        let mut export = ExportState::default();
+        assert!(!export.request(path.clone(), &arrangement(), &RenderSettings::bypass(), OutputFormat::Wav));
+        assert!(!export.is_running());
+        assert_eq!(export.confirm_overwrite.as_ref(), Some(&path));
+        assert_eq!(std::fs::read(&path).unwrap(), b"keep");
+        std::fs::remove_file(&path).ok();
+    }
+}
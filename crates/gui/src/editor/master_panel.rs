@@ -0,0 +1,113 @@
+//! Master panel: edit the effect chain run over the whole mix.
+
+use eframe::egui;
+use glottisdale_core::audio::effects::{ReverbIr, REVERB_IRS};
+use glottisdale_core::editor::master_chain::{EQ_HIGH_HZ, EQ_LOW_HZ, EQ_MID_HZ};
+use glottisdale_core::editor::MasterEffect;
+
+/// Slider range of the gain and EQ bands (dB).
+const GAIN_RANGE_DB: std::ops::RangeInclusive<f64> = -24.0..=24.0;
+
+/// Slider range of the limiter ceiling (dBFS).
+const CEILING_RANGE_DB: std::ops::RangeInclusive<f64> = -24.0..=0.0;
+
+/// Edit to the chain picked this frame, applied after drawing it.
+enum ChainEdit {
+    Add(MasterEffect),
+    Remove(usize),
+    /// Swap with the effect before it.
+    MoveUp(usize),
+}
+
+/// Show the master effect chain. Returns true if it changed.
+pub fn show_master_panel(ui: &mut egui::Ui, effects: &mut Vec<MasterEffect>) -> bool {
+    let mut changed = false;
+    let mut edit = None;
+    ui.heading("Master");
+    ui.label("Effects on the whole mix, in order. Heard in playback and exports.");
+    ui.separator();
+
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        if effects.is_empty() {
+            ui.weak("No master effects");
+        }
+        for (index, effect) in effects.iter_mut().enumerate() {
+            ui.push_id(index, |ui| {
+                ui.group(|ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong(effect.name());
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.small_button("x").on_hover_text("Remove").clicked() {
+                                edit = Some(ChainEdit::Remove(index));
+                            }
+                            if ui
+                                .add_enabled(index > 0, egui::Button::new("⏶").small())
+                                .on_hover_text("Move earlier in the chain")
+                                .clicked()
+                            {
+                                edit = Some(ChainEdit::MoveUp(index));
+                            }
+                        });
+                    });
+                    changed |= show_effect_settings(ui, effect);
+                });
+            });
+        }
+    });
+
+    ui.menu_button("Add Effect", |ui| {
+        for effect in MasterEffect::defaults() {
+            if ui.button(effect.name()).clicked() {
+                edit = Some(ChainEdit::Add(effect));
+                ui.close_menu();
+            }
+        }
+    });
+
+    match edit {
+        Some(ChainEdit::Add(effect)) => effects.push(effect),
+        Some(ChainEdit::Remove(index)) => {
+            effects.remove(index);
+        }
+        Some(ChainEdit::MoveUp(index)) => effects.swap(index - 1, index),
+        None => return changed,
+    }
+    true
+}
+
+fn db_slider<'a>(value: &'a mut f64, text: &str) -> egui::Slider<'a> {
+    egui::Slider::new(value, GAIN_RANGE_DB).text(text).suffix(" dB")
+}
+
+/// Sliders for one effect's settings. Returns true if any changed.
+fn show_effect_settings(ui: &mut egui::Ui, effect: &mut MasterEffect) -> bool {
+    match effect {
+        MasterEffect::Gain { gain_db } => ui.add(db_slider(gain_db, "gain")).changed(),
+        MasterEffect::Eq { low_db, mid_db, high_db } => {
+            let low = ui.add(db_slider(low_db, "low")).on_hover_text(format!("Shelf below {} Hz", EQ_LOW_HZ)).changed();
+            let mid = ui.add(db_slider(mid_db, "mid")).on_hover_text(format!("Peak around {} Hz", EQ_MID_HZ)).changed();
+            let high = ui.add(db_slider(high_db, "high")).on_hover_text(format!("Shelf above {} Hz", EQ_HIGH_HZ)).changed();
+            low || mid || high
+        }
+        MasterEffect::Limiter { ceiling_db } => ui
+            .add(egui::Slider::new(ceiling_db, CEILING_RANGE_DB).text("ceiling").suffix(" dBFS"))
+            .on_hover_text("Peaks are held at or below this level")
+            .changed(),
+        MasterEffect::Reverb { ir, send } => {
+            let mut changed = false;
+            egui::ComboBox::from_id_salt("master_reverb_ir")
+                .width(70.0)
+                .selected_text(ir.name())
+                .show_ui(ui, |ui| {
+                    for option in REVERB_IRS.iter().filter_map(|name| ReverbIr::parse(name).ok()) {
+                        changed |= ui.selectable_value(ir, option, option.name()).changed();
+                    }
+                });
+            changed
+                | ui.add(egui::Slider::new(send, 0.0..=1.0).text("send"))
+                    .on_hover_text("Reverb level added on top of the dry mix")
+                    .changed()
+        }
+    }
+}
+
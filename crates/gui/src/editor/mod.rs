@@ -1,5 +1,10 @@
 //! Interactive syllable editor GUI.
 
+pub mod automation_lane;
+pub mod export;
+pub mod master_panel;
+pub mod relink;
+pub mod source_view;
 pub mod timeline;
 pub mod waveform_painter;
 
@@ -7,14 +12,25 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use eframe::egui;
+use glottisdale_core::audio::analysis::estimate_f0;
+use glottisdale_core::audio::effects::Filter;
+use glottisdale_core::audio::encode::{OutputFormat, OUTPUT_FORMATS};
 use glottisdale_core::editor::{
-    Arrangement, ClipEffect, ClipId, TimelineClip,
-    effects_chain::compute_effective_duration,
+    Arrangement, bank_builder::{import_sample, BankQuery}, ClipEffect, ClipId, LayoutMode, MissingSources, SyllableClip,
+    TimelineClip, PROJECT_EXTENSION,
+    arrangement_source::ArrangementSource,
     playback_engine::PlaybackEngine,
-    render::{render_arrangement, RenderSettings},
+    preroll::PreRoll,
+    render::{render_arrangement_channels, RenderSettings},
+    timeline_image::{self, export_timeline_image},
 };
+use glottisdale_core::source::SourceId;
 
-use self::timeline::{TimelineAction, TimelineState};
+use self::automation_lane::AutomationState;
+use self::export::{show_export_windows, ExportState};
+use self::relink::{RelinkDialog, RelinkOutcome};
+use self::source_view::SourceViewState;
+use self::timeline::{ClipDrag, SnapGrid, SnapSettings, TimelineAction, TimelineState, SNAP_GRIDS};
 
 /// Action from the context menu to apply after rendering.
 enum ContextAction {
@@ -22,9 +38,68 @@ enum ContextAction {
     Stretch(ClipId, f64),
     Pitch(ClipId, f64),
     Reverse(ClipId),
+    Filter(ClipId, Filter),
+    /// Wet share of the clip's last effect, 0-1.
+    EffectWet(ClipId, f64),
+    /// Output gain of the clip's last effect, in dB.
+    EffectGain(ClipId, f64),
+    /// Save the clip's effect chain under a name.
+    SavePreset(ClipId, String),
+    /// Replace the clip's effects with a named preset.
+    ApplyPreset(ClipId, String),
+    Pan(ClipId, f64),
     Duplicate(ClipId),
+    /// Repeat the clip (or the selection holding it) this many times.
+    DuplicateTimes(ClipId, usize),
+    SplitAtCursor(ClipId),
     Delete(ClipId),
     ClearEffects(ClipId),
+    SwapSyllable(ClipId),
+}
+
+/// What to do once the user has dealt with unsaved changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingClose {
+    /// Close the editor.
+    Editor,
+    /// Quit the app.
+    App,
+    /// Pick another project to open in place of this one.
+    OpenProject,
+}
+
+/// Order of the bank panel's list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BankSort {
+    /// As stored: alignment order, then imports and hand-cut clips.
+    #[default]
+    Stored,
+    /// Alphabetical by word, then label.
+    Word,
+    /// Shortest first.
+    Duration,
+    /// By source file path, in stored order within each.
+    Source,
+    /// Lowest pitch first, unvoiced clips last.
+    Pitch,
+    /// Best quality first, unscored clips last.
+    Quality,
+}
+
+impl BankSort {
+    pub const ALL: [BankSort; 6] =
+        [BankSort::Stored, BankSort::Word, BankSort::Duration, BankSort::Source, BankSort::Pitch, BankSort::Quality];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BankSort::Stored => "Bank order",
+            BankSort::Word => "Word",
+            BankSort::Duration => "Duration",
+            BankSort::Source => "Source",
+            BankSort::Pitch => "F0",
+            BankSort::Quality => "Quality",
+        }
+    }
 }
 
 /// Full editor state.
@@ -33,9 +108,18 @@ pub struct EditorState {
     pub timeline: TimelineState,
     pub playback: PlaybackEngine,
     /// Map from source file path to color index.
-    pub source_indices: HashMap<PathBuf, usize>,
+    pub source_indices: HashMap<SourceId, usize>,
     /// Search filter for the bank panel.
     pub bank_filter: String,
+    /// Order of the bank list.
+    pub bank_sort: BankSort,
+    /// List the bank under a collapsible header per source file.
+    pub bank_group_by_source: bool,
+    /// Bank clips picked with Shift+click, in the order picked.
+    pub bank_selected: Vec<ClipId>,
+    /// Estimated F0 of bank clips (None when unvoiced), filled in the
+    /// first time the bank is sorted by F0.
+    bank_f0: HashMap<ClipId, Option<f64>>,
     /// Last audio/playback error to display.
     pub audio_error: Option<String>,
     /// Whether the keyboard shortcuts help popup is open.
@@ -44,41 +128,168 @@ pub struct EditorState {
     pub looping: bool,
     /// Track whether playback was active last frame (for loop detection).
     was_playing_last_frame: bool,
+    /// Whether alignment is still streaming partial clips into the bank.
+    pub bank_streaming: bool,
+    /// Audio format used by Export.
+    pub export_format: OutputFormat,
+    /// Whether the source view replaces the timeline.
+    pub show_source_view: bool,
+    /// Whether the master effects panel is open.
+    pub show_master_panel: bool,
+    /// Range selection state for the source view.
+    pub source_view: SourceViewState,
+    /// Sample chosen via Import Sample, awaiting a label: (path, label).
+    pub pending_import: Option<(PathBuf, String)>,
+    /// Pre-roll or count-in before playback starts at the cursor.
+    pub pre_roll: PreRoll,
+    /// Export dialog: overwrite prompt, progress, and success toast.
+    pub export: ExportState,
+    /// Name typed into the clip menu's Save Preset box.
+    pub preset_name: String,
+    /// `Arrangement::project_fingerprint` as of the last save or load.
+    saved_fingerprint: u64,
+    /// Close waiting on the unsaved-changes prompt.
+    pub close_prompt: Option<PendingClose>,
+    /// The user chose to quit despite unsaved changes.
+    pub quit_confirmed: bool,
+    /// Shown in its own OS window instead of the main window.
+    pub detached: bool,
+    /// Project being opened whose moved sources need locating.
+    pub relink: Option<RelinkDialog>,
+    /// Clips copied or cut from the timeline, in timeline order.
+    pub clipboard: Vec<TimelineClip>,
+    /// Gain and pitch automation lanes below the timeline.
+    pub automation: AutomationState,
 }
 
 impl EditorState {
     pub fn new(arrangement: Arrangement) -> Self {
-        // Build source index map
-        let mut source_indices = HashMap::new();
-        let mut next_idx = 0usize;
-        for clip in &arrangement.bank {
-            source_indices
-                .entry(clip.source_path.clone())
-                .or_insert_with(|| {
-                    let idx = next_idx;
-                    next_idx += 1;
-                    idx
-                });
-        }
-
-        Self {
+        let mut state = Self {
             arrangement,
             timeline: TimelineState::default(),
             playback: PlaybackEngine::new(),
-            source_indices,
+            source_indices: HashMap::new(),
             bank_filter: String::new(),
+            bank_sort: BankSort::default(),
+            bank_group_by_source: false,
+            bank_selected: Vec::new(),
+            bank_f0: HashMap::new(),
             audio_error: None,
             show_keyboard_help: false,
             looping: false,
             was_playing_last_frame: false,
+            bank_streaming: false,
+            export_format: OutputFormat::Wav,
+            show_source_view: false,
+            show_master_panel: false,
+            source_view: SourceViewState::default(),
+            pending_import: None,
+            pre_roll: PreRoll::Off,
+            export: ExportState::default(),
+            preset_name: String::new(),
+            saved_fingerprint: 0,
+            close_prompt: None,
+            quit_confirmed: false,
+            detached: false,
+            relink: None,
+            clipboard: Vec::new(),
+            automation: AutomationState::default(),
+        };
+        state.rebuild_source_indices();
+        state.mark_saved();
+        state
+    }
+
+    /// Whether the arrangement differs from the last saved or loaded one.
+    ///
+    /// A freshly generated arrangement counts as saved: it can be rebuilt
+    /// from its run.
+    pub fn has_unsaved_changes(&self) -> bool {
+        self.arrangement.project_fingerprint() != self.saved_fingerprint
+    }
+
+    /// Treat the current arrangement as saved.
+    pub fn mark_saved(&mut self) {
+        self.saved_fingerprint = self.arrangement.project_fingerprint();
+    }
+
+    /// Whether `then` can go ahead now. With unsaved changes, asks the
+    /// user first and returns false; `show_editor` carries it out once
+    /// they save or discard.
+    pub fn confirm_discard(&mut self, then: PendingClose) -> bool {
+        if self.has_unsaved_changes() {
+            self.close_prompt = Some(then);
+            false
+        } else {
+            true
+        }
+    }
+
+    /// Build the source index map from the bank, in bank order.
+    fn rebuild_source_indices(&mut self) {
+        self.source_indices.clear();
+        for clip in &self.arrangement.bank {
+            let next_idx = self.source_indices.len();
+            self.source_indices.entry(clip.source).or_insert(next_idx);
+        }
+    }
+
+    /// Append streamed clips to the bank while alignment is running.
+    pub fn append_bank_clips(&mut self, clips: Vec<SyllableClip>) {
+        for clip in clips {
+            let next_idx = self.source_indices.len();
+            self.source_indices.entry(clip.source).or_insert(next_idx);
+            self.arrangement.bank.push(clip);
+        }
+    }
+
+    /// Load a one-shot WAV into the bank as an unaligned clip.
+    pub fn import_sample(&mut self, path: &std::path::Path, label: &str) -> Result<(), String> {
+        let label = if label.trim().is_empty() { "sample" } else { label.trim() };
+        let clip = import_sample(path, label, self.arrangement.sample_rate)
+            .map_err(|e| format!("Import failed: {}", e))?;
+        self.append_bank_clips(vec![clip]);
+        Ok(())
+    }
+
+    /// Swap the streamed bank for the final aligned one.
+    ///
+    /// Timeline clips placed while streaming keep their provisional bank
+    /// entries so nothing on the timeline is orphaned.
+    pub fn finish_streaming(&mut self, final_arrangement: Arrangement) {
+        let referenced: Vec<SyllableClip> = self
+            .arrangement
+            .bank
+            .iter()
+            .filter(|c| {
+                self.arrangement
+                    .timeline
+                    .iter()
+                    .any(|tc| tc.source_clip_id == c.id)
+            })
+            .cloned()
+            .collect();
+
+        let mut bank = final_arrangement.bank;
+        bank.extend(referenced);
+        self.arrangement.bank = bank;
+        self.arrangement.room_tone_clips = final_arrangement.room_tone_clips;
+        self.arrangement.breath_clips = final_arrangement.breath_clips;
+        self.bank_streaming = false;
+        self.rebuild_source_indices();
+        // Nothing was arranged while streaming, so nothing is unsaved yet
+        if self.arrangement.timeline.is_empty() {
+            self.mark_saved();
         }
     }
 
     /// Shuffle clips randomly. If 2+ clips are selected, shuffles only those.
-    /// Otherwise shuffles the entire timeline.
+    /// Otherwise shuffles the entire timeline. In free layout the clips swap
+    /// positions too, so the set of occupied slots stays the same.
     pub fn shuffle(&mut self) {
         use rand::seq::SliceRandom;
 
+        let positions: Vec<f64> = self.arrangement.timeline.iter().map(|tc| tc.position_s).collect();
         let selected = self.timeline.selected.clone();
         let shuffle_all = selected.len() < 2;
 
@@ -110,7 +321,154 @@ impl EditorState {
             }
         }
 
+        if self.arrangement.layout_mode == LayoutMode::Free {
+            for (tc, position_s) in self.arrangement.timeline.iter_mut().zip(positions) {
+                tc.position_s = position_s;
+            }
+        }
+        self.arrangement.relayout(0.0);
+    }
+
+    /// Sing the melody note under a timeline clip with a random other
+    /// bank clip, and select the new clip.
+    pub fn swap_syllable(&mut self, clip_id: ClipId) {
+        use rand::seq::IteratorRandom;
+
+        let Some(note_index) = self.arrangement.note_for_clip(clip_id) else {
+            return;
+        };
+        let current: Vec<ClipId> = self
+            .arrangement
+            .timeline
+            .iter()
+            .filter(|tc| self.arrangement.melody[note_index].clip_ids.contains(&tc.id))
+            .map(|tc| tc.source_clip_id)
+            .collect();
+        let mut rng = rand::thread_rng();
+        let choice = self
+            .arrangement
+            .bank
+            .iter()
+            .filter(|c| !current.contains(&c.id))
+            .choose(&mut rng)
+            .map(|c| c.id);
+        if let Some(new_id) = choice.and_then(|id| self.arrangement.swap_note_syllable(note_index, id)) {
+            self.timeline.selected = vec![new_id];
+        }
+    }
+
+    /// Split a clip in two at the cursor: `clip_id`, or else the clip
+    /// under the cursor. A selected clip stays selected as both halves.
+    /// Returns false when there was nothing to split.
+    pub fn split_at_cursor(&mut self, clip_id: Option<ClipId>) -> bool {
+        let cursor_s = self.timeline.cursor_s;
+        let index = match clip_id {
+            Some(id) => self.arrangement.timeline.iter().position(|tc| tc.id == id),
+            None => timeline::clip_at_time(&self.arrangement, cursor_s).map(|(index, _)| index),
+        };
+        let Some(index) = index else {
+            return false;
+        };
+        let id = self.arrangement.timeline[index].id;
+        let Some(later_id) = self.arrangement.split_clip(index, cursor_s) else {
+            return false;
+        };
+        if self.timeline.selected.contains(&id) {
+            self.timeline.selected.push(later_id);
+        }
+        true
+    }
+
+    /// Copy the selected clips, in timeline order, to the clipboard.
+    /// Returns how many were copied; with none selected the clipboard is
+    /// left as it was.
+    pub fn copy_selected(&mut self) -> usize {
+        let selected = &self.timeline.selected;
+        let clips: Vec<TimelineClip> =
+            self.arrangement.timeline.iter().filter(|tc| selected.contains(&tc.id)).cloned().collect();
+        let count = clips.len();
+        if count > 0 {
+            self.clipboard = clips;
+        }
+        count
+    }
+
+    /// Move the selected clips to the clipboard.
+    pub fn cut_selected(&mut self) -> bool {
+        let cut = self.copy_selected() > 0;
+        if cut {
+            self.delete_selected();
+        }
+        cut
+    }
+
+    /// Paste the clipboard at the cursor as new clips, selected, and move
+    /// the cursor past them. Sequential layouts insert them before the
+    /// first clip whose middle is past the cursor; free layouts keep
+    /// their spacing, starting at the cursor.
+    pub fn paste_at_cursor(&mut self) -> bool {
+        if self.clipboard.is_empty() {
+            return false;
+        }
+        let cursor_s = self.timeline.cursor_s;
+        let index = self
+            .arrangement
+            .timeline
+            .iter()
+            .position(|tc| tc.position_s + tc.effective_duration_s / 2.0 > cursor_s)
+            .unwrap_or(self.arrangement.timeline.len());
+        let clips = self.clipboard.clone();
+        let offset = cursor_s - clips[0].position_s;
+        let pasted = self.insert_copies(&clips, index, offset);
+        self.timeline.cursor_s = self
+            .arrangement
+            .timeline
+            .iter()
+            .filter(|tc| pasted.contains(&tc.id))
+            .map(|tc| tc.position_s + tc.effective_duration_s)
+            .fold(cursor_s, f64::max);
+        self.timeline.selected = pasted;
+        true
+    }
+
+    /// Repeat a clip `times` times right after itself. When the clip is
+    /// part of the selection the whole selection repeats, as a block.
+    pub fn duplicate_times(&mut self, clip_id: ClipId, times: usize) {
+        let group: Vec<TimelineClip> = if self.timeline.selected.contains(&clip_id) {
+            let selected = &self.timeline.selected;
+            self.arrangement.timeline.iter().filter(|tc| selected.contains(&tc.id)).cloned().collect()
+        } else {
+            self.arrangement.timeline.iter().filter(|tc| tc.id == clip_id).cloned().collect()
+        };
+        let Some(last) = group.last() else {
+            return;
+        };
+        let index = self.arrangement.timeline.iter().position(|tc| tc.id == last.id).map_or(0, |i| i + 1);
+        let start = group[0].position_s;
+        let span = group.iter().map(|tc| tc.position_s + tc.effective_duration_s).fold(start, f64::max) - start;
+        let copies: Vec<TimelineClip> = (1..=times)
+            .flat_map(|k| {
+                group.iter().map(move |tc| TimelineClip { position_s: tc.position_s + span * k as f64, ..tc.clone() })
+            })
+            .collect();
+        self.insert_copies(&copies, index, 0.0);
+    }
+
+    /// Insert copies of `clips` with new IDs at timeline `index`, shifted
+    /// by `offset_s`, and re-layout. Returns the copies' IDs.
+    fn insert_copies(&mut self, clips: &[TimelineClip], index: usize, offset_s: f64) -> Vec<ClipId> {
+        let copies: Vec<TimelineClip> = clips
+            .iter()
+            .map(|tc| TimelineClip {
+                id: uuid::Uuid::new_v4(),
+                position_s: (tc.position_s + offset_s).max(0.0),
+                ..tc.clone()
+            })
+            .collect();
+        let ids = copies.iter().map(|tc| tc.id).collect();
+        self.arrangement.timeline.splice(index..index, copies);
         self.arrangement.relayout(0.0);
+        ids
     }
 
     /// Delete selected clips from the timeline.
@@ -135,8 +493,7 @@ impl EditorState {
                     .iter()
                     .find(|c| c.id == tc.source_clip_id)
                 {
-                    tc.effective_duration_s =
-                        compute_effective_duration(source.duration_s(), &tc.effects);
+                    tc.update_duration(source);
                 }
             }
         }
@@ -155,7 +512,7 @@ impl EditorState {
                     .iter()
                     .find(|c| c.id == tc.source_clip_id)
                 {
-                    tc.effective_duration_s = source.duration_s();
+                    tc.update_duration(source);
                 }
             }
         }
@@ -164,26 +521,77 @@ impl EditorState {
 
     /// Play the arrangement from the current cursor position.
     pub fn play_from_cursor(&self, settings: &RenderSettings) {
+        self.play_with_pre_roll(settings, self.pre_roll);
+    }
+
+    /// After an edit, cut later passes of a playing loop region from the
+    /// edited arrangement so changes are heard on the next pass.
+    fn refresh_loop(&self, settings: &RenderSettings) {
+        if self.playback.loop_region().is_none()
+            || !self.playback.state.is_playing()
+            || !ArrangementSource::can_stream(settings)
+        {
+            return;
+        }
+        match ArrangementSource::new(&self.arrangement, settings, 0.0) {
+            Ok(source) => self.playback.update_loop_source(source),
+            Err(e) => log::warn!("Loop refresh failed: {}", e),
+        }
+    }
+
+    /// Play from the cursor, starting early or counting in per `pre_roll`.
+    fn play_with_pre_roll(&self, settings: &RenderSettings, pre_roll: PreRoll) {
         if self.arrangement.timeline.is_empty() {
             log::warn!("Nothing to play — timeline is empty");
             return;
         }
-        match render_arrangement(&self.arrangement, settings) {
-            Ok(samples) => {
-                if samples.is_empty() {
+        // With a loop region, start inside it
+        let from = match self.timeline.loop_region {
+            Some(region) if !region.contains(self.timeline.cursor_s) => region.start_s,
+            _ => self.timeline.cursor_s,
+        };
+        let cursor = pre_roll.start_s(from);
+        let sr = self.arrangement.sample_rate;
+        let lead_in = pre_roll.lead_in(sr);
+        if ArrangementSource::can_stream(settings) {
+            if cursor >= self.arrangement.total_duration_s() {
+                log::warn!("Cursor past end of arrangement");
+                return;
+            }
+            // Render clips on the fly so playback starts immediately
+            match ArrangementSource::new(&self.arrangement, settings, cursor) {
+                Ok(source) => self.playback.play_source(source, cursor, lead_in),
+                Err(e) => {
+                    log::error!("Render failed: {}", e);
+                    self.playback.state.set_error(format!("Render: {}", e));
+                }
+            }
+            return;
+        }
+        // Global speed changes need the whole mix up front
+        let layout = self.arrangement.output_layout();
+        match render_arrangement_channels(&self.arrangement, settings, layout) {
+            Ok(channels) => {
+                let len = channels.first().map_or(0, |c| c.len());
+                if len == 0 {
                     log::warn!("Render produced no audio");
                     return;
                 }
-                let sr = self.arrangement.sample_rate;
-                let cursor = self.timeline.cursor_s;
                 let start_sample = (cursor * sr as f64).round() as usize;
-                let play_samples = if start_sample < samples.len() {
-                    samples[start_sample..].to_vec()
-                } else {
+                if start_sample >= len {
                     log::warn!("Cursor past end of arrangement");
                     return;
-                };
-                self.playback.play_samples(play_samples, sr, cursor);
+                }
+                let lead_s = lead_in.len() as f64 / sr as f64;
+                let play_channels = channels
+                    .iter()
+                    .map(|channel| {
+                        let mut samples = lead_in.clone();
+                        samples.extend_from_slice(&channel[start_sample..]);
+                        samples
+                    })
+                    .collect();
+                self.playback.play_channels(play_channels, sr, cursor - lead_s);
             }
             Err(e) => {
                 log::error!("Render failed: {}", e);
@@ -196,7 +604,31 @@ impl EditorState {
     pub fn play_clip(&self, clip_id: ClipId) {
         if let Some(clip) = self.arrangement.get_bank_clip(clip_id) {
             self.playback
-                .play_samples(clip.samples.clone(), clip.sample_rate, 0.0);
+                .play_channels(clip.samples.to_channels(), clip.sample_rate, 0.0);
+        }
+    }
+
+    /// Append bank clips to the end of the timeline, in the given order.
+    pub fn add_bank_clips_to_timeline(&mut self, ids: &[ClipId]) {
+        let clips: Vec<TimelineClip> =
+            ids.iter().filter_map(|&id| self.arrangement.get_bank_clip(id)).map(TimelineClip::new).collect();
+        if clips.is_empty() {
+            return;
+        }
+        for mut tc in clips {
+            // Free layout keeps positions, so append after the last clip
+            tc.position_s = self.arrangement.total_duration_s();
+            self.arrangement.timeline.push(tc);
+        }
+        self.arrangement.relayout(0.0);
+    }
+
+    /// Estimate F0 for bank clips not yet estimated, for sorting by pitch.
+    fn estimate_bank_f0(&mut self) {
+        for clip in &self.arrangement.bank {
+            self.bank_f0
+                .entry(clip.id)
+                .or_insert_with(|| estimate_f0(&clip.samples.to_vec::<f64>(), clip.sample_rate, 80, 600));
         }
     }
 }
@@ -216,6 +648,33 @@ fn apply_context_action(state: &mut EditorState, action: ContextAction) {
         ContextAction::Reverse(clip_id) => {
             apply_effect_to_clip(state, clip_id, ClipEffect::Reverse);
         }
+        ContextAction::Filter(clip_id, filter) => {
+            apply_effect_to_clip(state, clip_id, ClipEffect::Filter(filter));
+        }
+        ContextAction::EffectWet(clip_id, wet) => {
+            blend_last_effect(state, clip_id, |(_, gain_db)| (wet, gain_db));
+        }
+        ContextAction::EffectGain(clip_id, gain_db) => {
+            blend_last_effect(state, clip_id, |(wet, _)| (wet, gain_db));
+        }
+        ContextAction::SavePreset(clip_id, name) => {
+            if state.arrangement.save_effect_preset(&name, clip_id) {
+                state.preset_name.clear();
+            }
+        }
+        ContextAction::ApplyPreset(clip_id, name) => {
+            state.arrangement.apply_effect_preset(&name, clip_id);
+        }
+        ContextAction::Pan(clip_id, position) => {
+            if let Some(tc) = state
+                .arrangement
+                .timeline
+                .iter_mut()
+                .find(|tc| tc.id == clip_id)
+            {
+                tc.pan = position;
+            }
+        }
         ContextAction::Duplicate(clip_id) => {
             if let Some(tc_idx) = state
                 .arrangement
@@ -227,14 +686,22 @@ fn apply_context_action(state: &mut EditorState, action: ContextAction) {
                 let new_tc = TimelineClip {
                     id: uuid::Uuid::new_v4(),
                     source_clip_id: tc.source_clip_id,
-                    position_s: 0.0,
+                    // Right after the original; sequential layout recomputes it
+                    position_s: tc.position_s + tc.effective_duration_s,
                     effects: tc.effects.clone(),
                     effective_duration_s: tc.effective_duration_s,
+                    pan: tc.pan,
+                    trim_start_s: tc.trim_start_s,
+                    trim_end_s: tc.trim_end_s,
                 };
                 state.arrangement.timeline.insert(tc_idx + 1, new_tc);
                 state.arrangement.relayout(0.0);
             }
         }
+        ContextAction::DuplicateTimes(clip_id, times) => state.duplicate_times(clip_id, times),
+        ContextAction::SplitAtCursor(clip_id) => {
+            state.split_at_cursor(Some(clip_id));
+        }
         ContextAction::Delete(clip_id) => {
             state.arrangement.timeline.retain(|tc| tc.id != clip_id);
             state.timeline.selected.retain(|&id| id != clip_id);
@@ -250,12 +717,24 @@ fn apply_context_action(state: &mut EditorState, action: ContextAction) {
                         .iter()
                         .find(|c| c.id == tc.source_clip_id)
                     {
-                        tc.effective_duration_s = source.duration_s();
+                        tc.update_duration(source);
                     }
                 }
             }
             state.arrangement.relayout(0.0);
         }
+        ContextAction::SwapSyllable(clip_id) => state.swap_syllable(clip_id),
+    }
+}
+
+/// Change the wet share and output gain of a clip's most recent effect.
+fn blend_last_effect(state: &mut EditorState, clip_id: ClipId, f: impl FnOnce((f64, f64)) -> (f64, f64)) {
+    let Some(tc) = state.arrangement.timeline.iter_mut().find(|tc| tc.id == clip_id) else {
+        return;
+    };
+    if let Some(last) = tc.effects.pop() {
+        let (wet, gain_db) = f(last.wet_gain());
+        tc.effects.push(last.blended(wet, gain_db));
     }
 }
 
@@ -270,8 +749,7 @@ fn apply_effect_to_clip(state: &mut EditorState, clip_id: ClipId, effect: ClipEf
                 .iter()
                 .find(|c| c.id == tc.source_clip_id)
             {
-                tc.effective_duration_s =
-                    compute_effective_duration(source.duration_s(), &tc.effects);
+                tc.update_duration(source);
             }
             break;
         }
@@ -280,7 +758,27 @@ fn apply_effect_to_clip(state: &mut EditorState, clip_id: ClipId, effect: ClipEf
 }
 
 /// Render context menu items for a clip.
-fn show_clip_context_menu(ui: &mut egui::Ui, clip_id: ClipId, action: &mut Option<ContextAction>) {
+///
+/// Clips that sing a melody note also offer swapping the note's syllable.
+/// `presets` names the effect presets on offer; `preset_name` backs the
+/// Save Preset box. Splitting needs the cursor inside the clip.
+fn show_clip_context_menu(
+    ui: &mut egui::Ui,
+    clip_id: ClipId,
+    on_note: bool,
+    cursor_in_clip: bool,
+    presets: &[String],
+    preset_name: &mut String,
+    action: &mut Option<ContextAction>,
+) {
+    if on_note {
+        if ui.button("Swap Syllable").on_hover_text("Sing this note with another syllable from the bank").clicked() {
+            *action = Some(ContextAction::SwapSyllable(clip_id));
+            ui.close_menu();
+        }
+        ui.separator();
+    }
+
     ui.menu_button("Stutter", |ui| {
         for count in 2..=8 {
             if ui.button(format!("x{}", count)).clicked() {
@@ -318,6 +816,74 @@ fn show_clip_context_menu(ui: &mut egui::Ui, clip_id: ClipId, action: &mut Optio
         ui.close_menu();
     }
 
+    ui.menu_button("Filter", |ui| {
+        for filter in CLIP_FILTERS {
+            if ui.button(filter.label()).clicked() {
+                *action = Some(ContextAction::Filter(clip_id, *filter));
+                ui.close_menu();
+            }
+        }
+    })
+    .response
+    .on_hover_text("Telephone, radio, and megaphone voices are under Effect Presets");
+
+    ui.menu_button("Last Effect Mix", |ui| {
+        for &wet in &[0.25, 0.5, 0.75, 1.0] {
+            if ui.button(format!("{:.0}% wet", wet * 100.0)).clicked() {
+                *action = Some(ContextAction::EffectWet(clip_id, wet));
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+        for &gain_db in &[-6.0, -3.0, 0.0, 3.0, 6.0] {
+            if ui.button(format!("{:+.0} dB", gain_db)).clicked() {
+                *action = Some(ContextAction::EffectGain(clip_id, gain_db));
+                ui.close_menu();
+            }
+        }
+    })
+    .response
+    .on_hover_text("Blend the clip's most recent effect with its input and set its output gain");
+
+    ui.menu_button("Effect Presets", |ui| {
+        for name in presets {
+            if ui.button(name).clicked() {
+                *action = Some(ContextAction::ApplyPreset(clip_id, name.clone()));
+                ui.close_menu();
+            }
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(preset_name).hint_text("Preset name").desired_width(100.0));
+            let can_save = !preset_name.trim().is_empty();
+            if ui
+                .add_enabled(can_save, egui::Button::new("Save"))
+                .on_hover_text("Save this clip's effects as a preset in the project")
+                .clicked()
+            {
+                *action = Some(ContextAction::SavePreset(clip_id, preset_name.clone()));
+                ui.close_menu();
+            }
+        });
+    })
+    .response
+    .on_hover_text("Replace the clip's effects with a saved chain");
+
+    ui.menu_button("Pan", |ui| {
+        for &(label, position) in &[
+            ("Left", -1.0),
+            ("Left 50%", -0.5),
+            ("Center", 0.0),
+            ("Right 50%", 0.5),
+            ("Right", 1.0),
+        ] {
+            if ui.button(label).clicked() {
+                *action = Some(ContextAction::Pan(clip_id, position));
+                ui.close_menu();
+            }
+        }
+    });
+
     ui.separator();
 
     if ui.button("Duplicate").clicked() {
@@ -325,6 +891,27 @@ fn show_clip_context_menu(ui: &mut egui::Ui, clip_id: ClipId, action: &mut Optio
         ui.close_menu();
     }
 
+    ui.menu_button("Duplicate ×N", |ui| {
+        for times in [2, 3, 4, 8, 16] {
+            if ui.button(format!("×{}", times)).clicked() {
+                *action = Some(ContextAction::DuplicateTimes(clip_id, times));
+                ui.close_menu();
+            }
+        }
+    })
+    .response
+    .on_hover_text("Repeat the clip, or the selection it is in, right after itself");
+
+    if ui
+        .add_enabled(cursor_in_clip, egui::Button::new("Split at Cursor"))
+        .on_hover_text("Cut the clip in two where the cursor is (s)")
+        .on_disabled_hover_text("Move the cursor into this clip to split it")
+        .clicked()
+    {
+        *action = Some(ContextAction::SplitAtCursor(clip_id));
+        ui.close_menu();
+    }
+
     if ui.button("Delete").clicked() {
         *action = Some(ContextAction::Delete(clip_id));
         ui.close_menu();
@@ -336,6 +923,128 @@ fn show_clip_context_menu(ui: &mut egui::Ui, clip_id: ClipId, action: &mut Optio
     }
 }
 
+/// Filter stages offered in the clip context menu.
+const CLIP_FILTERS: &[Filter] = &[
+    Filter::HighPass { cutoff_hz: 300.0 },
+    Filter::HighPass { cutoff_hz: 1000.0 },
+    Filter::LowPass { cutoff_hz: 1000.0 },
+    Filter::LowPass { cutoff_hz: 3400.0 },
+    Filter::BandPass { center_hz: 1000.0, q: 1.0 },
+    Filter::BitCrush { bits: 8, downsample: 1 },
+    Filter::BitCrush { bits: 4, downsample: 4 },
+    Filter::Saturate { drive: 3.0 },
+];
+
+/// Pre-roll presets offered in the toolbar.
+const PRE_ROLL_PRESETS: &[(&str, PreRoll)] = &[
+    ("Off", PreRoll::Off),
+    ("1s pre-roll", PreRoll::Seconds(1.0)),
+    ("2s pre-roll", PreRoll::Seconds(2.0)),
+    ("Count-in 1 bar", PreRoll::CountIn { bars: 1, bpm: 120.0 }),
+    ("Count-in 2 bars", PreRoll::CountIn { bars: 2, bpm: 120.0 }),
+];
+
+/// Toolbar picker for pre-roll / count-in, with a BPM field when counting in.
+fn show_pre_roll_picker(ui: &mut egui::Ui, pre_roll: &mut PreRoll) {
+    let label = match *pre_roll {
+        PreRoll::Off => "Off".to_string(),
+        PreRoll::Seconds(s) => format!("{:.0}s pre-roll", s),
+        PreRoll::CountIn { bars, .. } => {
+            format!("Count-in {} bar{}", bars, if bars == 1 { "" } else { "s" })
+        }
+    };
+    ui.label("Pre-roll:");
+    egui::ComboBox::from_id_salt("pre_roll")
+        .width(110.0)
+        .selected_text(label)
+        .show_ui(ui, |ui| {
+            for &(name, preset) in PRE_ROLL_PRESETS {
+                // Keep the chosen tempo when switching bar counts
+                let preset = match (preset, *pre_roll) {
+                    (PreRoll::CountIn { bars, .. }, PreRoll::CountIn { bpm, .. }) => {
+                        PreRoll::CountIn { bars, bpm }
+                    }
+                    _ => preset,
+                };
+                ui.selectable_value(pre_roll, preset, name);
+            }
+        })
+        .response
+        .on_hover_text("Start playback early, or count in with clicks, to hear how clips enter");
+    if let PreRoll::CountIn { bpm, .. } = pre_roll {
+        ui.add(egui::DragValue::new(bpm).range(40.0..=240.0).suffix(" BPM"));
+    }
+}
+
+/// Toolbar toggle and grid picker for timeline snapping.
+///
+/// The beat grid follows the count-in tempo, so it is only offered while
+/// a count-in is selected.
+fn show_snap_controls(ui: &mut egui::Ui, snap: &mut SnapSettings, pre_roll: PreRoll) {
+    snap.bpm = match pre_roll {
+        PreRoll::CountIn { bpm, .. } => Some(bpm),
+        _ => None,
+    };
+    if snap.grid == SnapGrid::Beat && snap.bpm.is_none() {
+        snap.grid = SnapGrid::Off;
+    }
+    ui.toggle_value(&mut snap.enabled, "Snap")
+        .on_hover_text("Snap drags to the grid and to clip edges (hold Alt to bypass)");
+    ui.add_enabled_ui(snap.enabled, |ui| {
+        egui::ComboBox::from_id_salt("snap_grid")
+            .width(80.0)
+            .selected_text(snap.grid.label())
+            .show_ui(ui, |ui| {
+                for &grid in SNAP_GRIDS {
+                    if grid == SnapGrid::Beat && snap.bpm.is_none() {
+                        continue;
+                    }
+                    ui.selectable_value(&mut snap.grid, grid, grid.label());
+                }
+            });
+        ui.checkbox(&mut snap.clip_edges, "Edges")
+            .on_hover_text("Pull drags onto nearby clip edges");
+    });
+}
+
+/// Ask for a path and return it with the project extension applied.
+fn save_project_dialog() -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .set_file_name(format!("arrangement.{}", PROJECT_EXTENSION))
+        .add_filter("Glottisdale project", &[PROJECT_EXTENSION])
+        .save_file()
+        .map(|path| path.with_extension(PROJECT_EXTENSION))
+}
+
+/// Result of opening a project file.
+pub enum ProjectOpen {
+    Loaded(Box<Arrangement>),
+    /// Some sources moved; the dialog asks where they went.
+    Relink(RelinkDialog),
+    /// The load error message.
+    Failed(String),
+}
+
+/// Ask for a project file and load it. `None` if the dialog was cancelled.
+pub fn open_project_dialog() -> Option<ProjectOpen> {
+    let path = rfd::FileDialog::new()
+        .add_filter("Glottisdale project", &[PROJECT_EXTENSION])
+        .pick_file()?;
+    Some(match Arrangement::load_project(&path) {
+        Ok(arrangement) => ProjectOpen::Loaded(Box::new(arrangement)),
+        Err(e) => match e.downcast_ref::<MissingSources>() {
+            Some(missing) => {
+                log::warn!("{}", missing);
+                ProjectOpen::Relink(RelinkDialog::new(missing))
+            }
+            None => {
+                log::error!("Open failed: {:#}", e);
+                ProjectOpen::Failed(format!("Open failed: {}", e))
+            }
+        },
+    })
+}
+
 /// Main entry point: render the full editor UI.
 pub fn show_editor(
     ui: &mut egui::Ui,
@@ -345,6 +1054,7 @@ pub fn show_editor(
 ) -> bool {
     let mut close = false;
     let mut context_action: Option<ContextAction> = None;
+    let mut opened_project: Option<Arrangement> = None;
 
     // Update cursor from playback engine (only while playing, so user
     // clicks can set cursor position when playback is stopped)
@@ -358,10 +1068,17 @@ pub fn show_editor(
     if state.looping && state.was_playing_last_frame && !is_playing {
         state.timeline.cursor_s = 0.0;
         state.audio_error = None;
-        state.play_from_cursor(render_settings);
+        // No count-in between loop passes
+        state.play_with_pre_roll(render_settings, PreRoll::Off);
     }
     state.was_playing_last_frame = is_playing;
 
+    // A new loop region takes effect right away
+    if state.playback.set_loop(state.timeline.loop_region) && is_playing {
+        state.play_with_pre_roll(render_settings, PreRoll::Off);
+    }
+    let mut edited = false;
+
     // Check for playback errors
     if let Some(err) = state.playback.state.take_error() {
         state.audio_error = Some(err);
@@ -370,9 +1087,70 @@ pub fn show_editor(
 
     // Toolbar
     ui.horizontal(|ui| {
-        if ui.button("Close Editor").clicked() {
+        if ui.button("Close Editor").clicked() && state.confirm_discard(PendingClose::Editor) {
             close = true;
         }
+        let (dock_label, dock_hint) = if state.detached {
+            ("Dock", "Move the editor back into the main window")
+        } else {
+            ("Pop Out", "Open the editor in its own window, next to the pipeline settings")
+        };
+        if ui.button(dock_label).on_hover_text(dock_hint).clicked() {
+            state.detached = !state.detached;
+        }
+        ui.menu_button("File", |ui| {
+            if ui.button("Save Project...").clicked() {
+                ui.close_menu();
+                save_project(state);
+            }
+            if ui
+                .add_enabled(!state.arrangement.bank.is_empty(), egui::Button::new("Save Project with Audio..."))
+                .on_hover_text("Embed the source audio so the project opens without the original files")
+                .clicked()
+            {
+                ui.close_menu();
+                save_project_with_audio(state);
+            }
+            if ui.button("Open Project...").clicked() {
+                ui.close_menu();
+                if state.confirm_discard(PendingClose::OpenProject) {
+                    opened_project = pick_project(state);
+                }
+            }
+            ui.separator();
+            if ui.button("Import Sample...").clicked() {
+                ui.close_menu();
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("WAV audio", &["wav"])
+                    .pick_file()
+                {
+                    let label = path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    state.pending_import = Some((path, label));
+                }
+            }
+            ui.separator();
+            let has_clips = !state.arrangement.timeline.is_empty();
+            if ui
+                .add_enabled(has_clips, egui::Button::new("Export Timeline Image..."))
+                .clicked()
+            {
+                ui.close_menu();
+                if let Some(path) = rfd::FileDialog::new()
+                    .set_file_name("timeline.png")
+                    .add_filter("PNG image", &["png"])
+                    .add_filter("SVG image", &["svg"])
+                    .save_file()
+                {
+                    if let Err(e) = export_timeline_image(&state.arrangement, &path, timeline_image::DEFAULT_WIDTH) {
+                        log::error!("Timeline image export failed: {:#}", e);
+                        state.audio_error = Some(format!("Timeline image export failed: {}", e));
+                    }
+                }
+            }
+        });
         ui.separator();
 
         let has_selection = !state.timeline.selected.is_empty();
@@ -383,18 +1161,21 @@ pub fn show_editor(
             .clicked()
         {
             state.shuffle();
+            edited = true;
         }
         if ui
             .add_enabled(has_selection, egui::Button::new("Delete"))
             .clicked()
         {
             state.delete_selected();
+            edited = true;
         }
         if ui
             .add_enabled(has_selection, egui::Button::new("Clear FX"))
             .clicked()
         {
             state.clear_effects_selected();
+            edited = true;
         }
 
         ui.separator();
@@ -417,10 +1198,18 @@ pub fn show_editor(
         if ui.button(loop_label).clicked() {
             state.looping = !state.looping;
         }
+        if let Some(region) = state.timeline.loop_region {
+            ui.label(format!("⟲ {:.2}–{:.2}s", region.start_s, region.end_s))
+                .on_hover_text("Playback repeats this region. Drag on the ruler to change it.");
+            if ui.small_button("x").on_hover_text("Clear loop region").clicked() {
+                state.timeline.loop_region = None;
+            }
+        }
 
         if ui.button("Stop").clicked() {
             state.playback.stop();
         }
+        show_pre_roll_picker(ui, &mut state.pre_roll);
 
         ui.separator();
 
@@ -438,19 +1227,54 @@ pub fn show_editor(
 
         ui.separator();
 
+        show_snap_controls(ui, &mut state.timeline.snap, state.pre_roll);
+
+        let mut free_layout = state.arrangement.layout_mode == LayoutMode::Free;
+        if ui
+            .toggle_value(&mut free_layout, "Free")
+            .on_hover_text("Place clips anywhere on the timeline, with gaps and overlaps (overlaps crossfade)")
+            .changed()
+        {
+            let mode = if free_layout { LayoutMode::Free } else { LayoutMode::Sequential };
+            state.arrangement.set_layout_mode(mode);
+            edited = true;
+        }
+
+        ui.separator();
+
+        let master_label = match state.arrangement.master_effects.len() {
+            0 => "Master".to_string(),
+            n => format!("Master ({})", n),
+        };
+        ui.toggle_value(&mut state.show_master_panel, master_label)
+            .on_hover_text("Gain, EQ, limiter, and reverb on the whole mix");
+
+        ui.separator();
+
+        let automation_label = if state.arrangement.automation.is_empty() { "Automation" } else { "Automation •" };
+        ui.toggle_value(&mut state.automation.visible, automation_label)
+            .on_hover_text("Gain and pitch envelopes over the timeline, below the clips");
+
+        ui.separator();
+
+        ui.toggle_value(&mut state.show_source_view, "Source View")
+            .on_hover_text("Select a range of a source and add it to the bank");
+
+        ui.separator();
+
         // Export
-        if ui.button("Export WAV").clicked() {
-            if let Some(path) = rfd::FileDialog::new()
-                .set_file_name("arrangement.wav")
-                .add_filter("WAV audio", &["wav"])
-                .save_file()
-            {
-                if let Err(e) =
-                    glottisdale_core::editor::render::export_arrangement(&state.arrangement, render_settings, &path)
-                {
-                    log::error!("Export failed: {}", e);
+        egui::ComboBox::from_id_salt("export_format")
+            .width(60.0)
+            .selected_text(state.export_format.extension().to_uppercase())
+            .show_ui(ui, |ui| {
+                for name in OUTPUT_FORMATS {
+                    if let Ok(format) = OutputFormat::parse(name) {
+                        ui.selectable_value(&mut state.export_format, format, name.to_uppercase());
+                    }
                 }
-            }
+            });
+        if ui.add_enabled(!state.export.is_running(), egui::Button::new("Export")).clicked() {
+            request_export(state, render_settings);
         }
 
         if let Some(ref err) = state.audio_error {
@@ -471,6 +1295,32 @@ pub fn show_editor(
         });
     });
 
+    match show_unsaved_changes_window(ctx, state, render_settings) {
+        Some(PendingClose::Editor) => close = true,
+        Some(PendingClose::App) => {
+            state.quit_confirmed = true;
+            // The editor may be in its own window; quitting closes the main one
+            ctx.send_viewport_cmd_to(egui::ViewportId::ROOT, egui::ViewportCommand::Close);
+        }
+        Some(PendingClose::OpenProject) => opened_project = pick_project(state),
+        None => {}
+    }
+
+    if let Some(dialog) = &mut state.relink {
+        match dialog.show(ctx) {
+            RelinkOutcome::Loaded(arrangement) => opened_project = Some(*arrangement),
+            RelinkOutcome::Cancelled => state.relink = None,
+            RelinkOutcome::Pending => {}
+        }
+    }
+
+    if let Some(arrangement) = opened_project {
+        state.playback.stop();
+        let detached = state.detached;
+        *state = EditorState::new(arrangement);
+        state.detached = detached;
+    }
+
     ui.separator();
 
     // Main area: bank panel on left, timeline on right
@@ -482,41 +1332,96 @@ pub fn show_editor(
             show_bank_panel(ui, state);
         });
 
+    if state.show_master_panel {
+        egui::SidePanel::right("editor_master")
+            .min_width(200.0)
+            .default_width(240.0)
+            .resizable(true)
+            .show_inside(ui, |ui| {
+                if master_panel::show_master_panel(ui, &mut state.arrangement.master_effects) {
+                    edited = true;
+                }
+            });
+    }
+
     // Timeline in central panel
-    let mut reorder: Option<(usize, usize)> = None;
+    let mut clip_drag: Option<ClipDrag> = None;
     let mut timeline_actions: Vec<TimelineAction> = Vec::new();
     egui::CentralPanel::default().show_inside(ui, |ui| {
+        if state.show_source_view {
+            source_view::show_source_view(ui, state);
+            return;
+        }
+        timeline::show_minimap(ui, &state.arrangement, &mut state.timeline);
         egui::ScrollArea::vertical().show(ui, |ui| {
-            let (response, timeline_reorder, actions) = timeline::show_timeline(
+            let (response, timeline_drag, actions) = timeline::show_timeline(
                 ui,
                 &state.arrangement,
                 &mut state.timeline,
                 &state.source_indices,
             );
-            reorder = timeline_reorder;
+            clip_drag = timeline_drag;
             timeline_actions = actions;
+            if state.automation.visible {
+                edited |= automation_lane::show_automation_lanes(
+                    ui,
+                    &mut state.arrangement.automation,
+                    &state.timeline,
+                    &mut state.automation,
+                );
+            }
 
             // Context menu on right-click
             let menu_clip = state.timeline.context_menu_clip;
+            let on_note = menu_clip.is_some_and(|id| state.arrangement.note_for_clip(id).is_some());
+            let cursor_s = state.timeline.cursor_s;
+            let cursor_in_clip = menu_clip.is_some_and(|id| {
+                state.arrangement.timeline.iter().any(|tc| {
+                    tc.id == id && cursor_s > tc.position_s && cursor_s < tc.position_s + tc.effective_duration_s
+                })
+            });
+            let presets: Vec<String> =
+                state.arrangement.available_effect_presets().into_iter().map(|p| p.name).collect();
+            let preset_name = &mut state.preset_name;
             response.context_menu(|ui| {
                 if let Some(clip_id) = menu_clip {
-                    show_clip_context_menu(ui, clip_id, &mut context_action);
+                    show_clip_context_menu(
+                        ui,
+                        clip_id,
+                        on_note,
+                        cursor_in_clip,
+                        &presets,
+                        preset_name,
+                        &mut context_action,
+                    );
                 }
             });
         });
     });
 
-    // Apply reorder from drag-to-reorder
-    if let Some((from, to)) = reorder {
-        let clip = state.arrangement.timeline.remove(from);
-        let insert_at = if to > from { to - 1 } else { to };
-        state.arrangement.timeline.insert(insert_at, clip);
-        state.arrangement.relayout(0.0);
+    // Apply a finished clip drag
+    match clip_drag {
+        Some(ClipDrag::Reorder { from, to }) => {
+            let clip = state.arrangement.timeline.remove(from);
+            let insert_at = if to > from { to - 1 } else { to };
+            state.arrangement.timeline.insert(insert_at, clip);
+            state.arrangement.relayout(0.0);
+            edited = true;
+        }
+        Some(ClipDrag::Move { index, position_s }) => {
+            state.arrangement.move_clip(index, position_s);
+            edited = true;
+        }
+        Some(ClipDrag::Trim { index, trim_start_s, trim_end_s }) => {
+            edited = state.arrangement.trim_clip(index, trim_start_s, trim_end_s);
+        }
+        None => {}
     }
 
     // Apply context menu action
     if let Some(action) = context_action {
         apply_context_action(state, action);
+        edited = true;
     }
 
     // Handle keyboard actions from timeline
@@ -532,6 +1437,7 @@ pub fn show_editor(
             }
             TimelineAction::DeleteSelected => {
                 state.delete_selected();
+                edited = true;
             }
             TimelineAction::SelectAll => {
                 state.timeline.selected = state
@@ -543,104 +1449,500 @@ pub fn show_editor(
             }
             TimelineAction::ReverseSelected => {
                 state.apply_effect_to_selected(ClipEffect::Reverse);
+                edited = true;
+            }
+            TimelineAction::SplitAtCursor => {
+                edited |= state.split_at_cursor(None);
+            }
+            TimelineAction::Copy => {
+                state.copy_selected();
+            }
+            TimelineAction::Cut => {
+                edited |= state.cut_selected();
+            }
+            TimelineAction::Paste => {
+                edited |= state.paste_at_cursor();
+            }
+            TimelineAction::PreviewSelected => {
+                let bank_id = state.timeline.selected.last().and_then(|id| {
+                    state
+                        .arrangement
+                        .timeline
+                        .iter()
+                        .find(|tc| tc.id == *id)
+                        .map(|tc| tc.source_clip_id)
+                });
+                if let Some(bank_id) = bank_id {
+                    state.play_clip(bank_id);
+                }
             }
         }
     }
 
+    if edited {
+        state.refresh_loop(render_settings);
+    }
+
     // Keyboard shortcuts help popup
     if state.show_keyboard_help {
         show_keyboard_help_window(ctx, &mut state.show_keyboard_help);
     }
 
+    // Label prompt for an imported sample
+    if state.pending_import.is_some() {
+        show_import_sample_window(ctx, state);
+    }
+
+    if let Some(err) = show_export_windows(
+        ctx,
+        &mut state.export,
+        &state.arrangement,
+        render_settings,
+        state.export_format,
+    ) {
+        state.audio_error = Some(err);
+    }
+
     close
 }
 
+/// Ask for a path and save the project there. Returns whether it saved.
+fn save_project(state: &mut EditorState) -> bool {
+    let Some(path) = save_project_dialog() else {
+        return false;
+    };
+    match state.arrangement.save_project(&path) {
+        Ok(()) => {
+            state.mark_saved();
+            true
+        }
+        Err(e) => {
+            log::error!("Save failed: {}", e);
+            state.audio_error = Some(format!("Save failed: {}", e));
+            false
+        }
+    }
+}
+
+/// Ask for a path and save a copy of the project there with its audio
+/// embedded. The open project stays linked to its own sources.
+fn save_project_with_audio(state: &mut EditorState) {
+    let Some(path) = save_project_dialog() else {
+        return;
+    };
+    match state.arrangement.save_project_with_audio(&path) {
+        Ok(()) => log::info!("Saved {} with its audio", path.display()),
+        Err(e) => {
+            log::error!("Save failed: {:#}", e);
+            state.audio_error = Some(format!("Save failed: {}", e));
+        }
+    }
+}
+
+/// Ask for a project file to open, reporting load errors in the toolbar.
+fn pick_project(state: &mut EditorState) -> Option<Arrangement> {
+    match open_project_dialog()? {
+        ProjectOpen::Loaded(arrangement) => Some(*arrangement),
+        ProjectOpen::Relink(dialog) => {
+            state.relink = Some(dialog);
+            None
+        }
+        ProjectOpen::Failed(e) => {
+            state.audio_error = Some(e);
+            None
+        }
+    }
+}
+
+/// Ask for an audio path and start exporting the arrangement to it.
+fn request_export(state: &mut EditorState, render_settings: &RenderSettings) {
+    let ext = state.export_format.extension();
+    if let Some(path) = rfd::FileDialog::new()
+        .set_file_name(format!("arrangement.{}", ext))
+        .add_filter(format!("{} audio", ext.to_uppercase()), &[ext])
+        .save_file()
+    {
+        state
+            .export
+            .request(path, &state.arrangement, render_settings, state.export_format);
+    }
+}
+
+/// Offer to save or export before a close that would lose changes.
+///
+/// Returns the pending close once the project is saved or the user
+/// discards the changes.
+fn show_unsaved_changes_window(
+    ctx: &egui::Context,
+    state: &mut EditorState,
+    render_settings: &RenderSettings,
+) -> Option<PendingClose> {
+    let pending = state.close_prompt?;
+    let mut choice = None;
+    let mut cancel = false;
+    let mut export = false;
+    egui::Window::new("Unsaved Changes")
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label("This arrangement has changes that haven't been saved.");
+            ui.label("Save the project, or export the audio, before closing?");
+            ui.horizontal(|ui| {
+                if ui.button("Save Project...").clicked() && save_project(state) {
+                    choice = Some(pending);
+                }
+                if ui
+                    .add_enabled(!state.export.is_running(), egui::Button::new("Export Audio..."))
+                    .on_hover_text("Export now and stay in the editor until it finishes")
+                    .clicked()
+                {
+                    export = true;
+                }
+                if ui.button("Discard").clicked() {
+                    choice = Some(pending);
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+    if export {
+        request_export(state, render_settings);
+        cancel = true;
+    }
+    if choice.is_some() || cancel {
+        state.close_prompt = None;
+    }
+    choice
+}
+
+/// Prompt for a label and import the pending sample into the bank.
+fn show_import_sample_window(ctx: &egui::Context, state: &mut EditorState) {
+    let mut import = false;
+    let mut cancel = false;
+    if let Some((path, label)) = state.pending_import.as_mut() {
+        egui::Window::new("Import Sample")
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(path.file_name().unwrap_or_default().to_string_lossy());
+                ui.horizontal(|ui| {
+                    ui.label("Label:");
+                    let response = ui.text_edit_singleline(label);
+                    if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        import = true;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Import").clicked() {
+                        import = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+    }
+    if import {
+        if let Some((path, label)) = state.pending_import.take() {
+            match state.import_sample(&path, &label) {
+                Ok(()) => state.audio_error = None,
+                Err(e) => {
+                    log::error!("{}", e);
+                    state.audio_error = Some(e);
+                }
+            }
+        }
+    } else if cancel {
+        state.pending_import = None;
+    }
+}
+
 /// Show the syllable bank/palette panel.
+/// Color pickers for the bank's source files, in color order.
+fn show_source_colors(ui: &mut egui::Ui, state: &mut EditorState) {
+    let mut sources: Vec<(SourceId, usize)> = state.source_indices.iter().map(|(&s, &i)| (s, i)).collect();
+    if sources.len() < 2 {
+        return;
+    }
+    sources.sort_by_key(|(_, i)| *i);
+    egui::CollapsingHeader::new(format!("Source Colors ({})", sources.len()))
+        .id_salt("source_colors")
+        .show(ui, |ui| {
+            for (source, index) in sources {
+                ui.horizontal(|ui| {
+                    let (r, g, b) = state.arrangement.source_color(source, index);
+                    let mut rgb = [r, g, b];
+                    if egui::color_picker::color_edit_button_srgb(ui, &mut rgb).changed() {
+                        state.arrangement.set_source_color(source, Some((rgb[0], rgb[1], rgb[2])));
+                    }
+                    if state.arrangement.source_colors.contains_key(&source)
+                        && ui.small_button("↺").on_hover_text("Back to the default color").clicked()
+                    {
+                        state.arrangement.set_source_color(source, None);
+                    }
+                    ui.label(source.path().file_name().unwrap_or_default().to_string_lossy())
+                        .on_hover_text(source.to_string());
+                });
+            }
+        });
+}
+
 fn show_bank_panel(ui: &mut egui::Ui, state: &mut EditorState) {
     ui.heading("Syllable Bank");
+    if state.bank_streaming {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(format!("Aligning... {} clips so far", state.arrangement.bank.len()));
+        });
+    }
     ui.add(
         egui::TextEdit::singleline(&mut state.bank_filter)
-            .hint_text("Filter...")
+            .hint_text("Filter... (ph:AE+T stress:1 dur:>0.3)")
             .desired_width(ui.available_width()),
     );
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("bank_sort")
+            .selected_text(format!("Sort: {}", state.bank_sort.name()))
+            .show_ui(ui, |ui| {
+                for sort in BankSort::ALL {
+                    ui.selectable_value(&mut state.bank_sort, sort, sort.name());
+                }
+            })
+            .response
+            .on_hover_text("Quality lists the best-sounding syllables first; F0 the lowest-pitched");
+        ui.toggle_value(&mut state.bank_group_by_source, "Group by source")
+            .on_hover_text("List each source file's clips under its own header");
+    });
+    show_source_colors(ui, state);
+    if !state.bank_selected.is_empty() {
+        ui.horizontal(|ui| {
+            if ui.button(format!("Add {} selected to timeline", state.bank_selected.len())).clicked() {
+                let ids = std::mem::take(&mut state.bank_selected);
+                state.add_bank_clips_to_timeline(&ids);
+            }
+            if ui.small_button("Clear").on_hover_text("Clear the bank selection").clicked() {
+                state.bank_selected.clear();
+            }
+        });
+    }
     ui.separator();
 
-    let filter = state.bank_filter.to_lowercase();
+    if state.bank_sort == BankSort::Pitch {
+        state.estimate_bank_f0();
+    }
+    let query = match BankQuery::parse(&state.bank_filter) {
+        Ok(query) => query,
+        Err(e) => {
+            ui.colored_label(egui::Color32::from_rgb(220, 80, 70), e.to_string());
+            BankQuery::default()
+        }
+    };
+    let order: Vec<usize> = bank_order(&state.arrangement.bank, state.bank_sort, &state.bank_f0)
+        .into_iter()
+        .filter(|&i| query.matches(&state.arrangement.bank[i]))
+        .collect();
 
     // Collect actions to apply after iterating (avoids borrow conflicts)
     let mut clip_to_add: Option<ClipId> = None;
     let mut clip_to_play: Option<ClipId> = None;
+    let mut clip_to_select: Option<ClipId> = None;
 
+    let state_ref = &*state;
     egui::ScrollArea::vertical().show(ui, |ui| {
-        for clip in &state.arrangement.bank {
-            // Filter
-            if !filter.is_empty()
-                && !clip.label.to_lowercase().contains(&filter)
-                && !clip.syllable.word.to_lowercase().contains(&filter)
-            {
-                continue;
+        let mut show_rows = |ui: &mut egui::Ui, rows: &[usize]| {
+            for clip in rows.iter().map(|&i| &state_ref.arrangement.bank[i]) {
+                let selected = state_ref.bank_selected.contains(&clip.id);
+                match show_bank_row(ui, state_ref, clip, selected) {
+                    Some(BankClick::Add) => clip_to_add = Some(clip.id),
+                    Some(BankClick::Play) => clip_to_play = Some(clip.id),
+                    Some(BankClick::Select) => clip_to_select = Some(clip.id),
+                    None => {}
+                }
             }
+        };
+        if state_ref.bank_group_by_source {
+            for (source, rows) in group_by_source(&state_ref.arrangement.bank, &order, &state_ref.source_indices) {
+                let name = source.path().file_name().unwrap_or_default().to_string_lossy().into_owned();
+                egui::CollapsingHeader::new(format!("{} ({})", name, rows.len()))
+                    .id_salt(("bank_group", source.to_string()))
+                    .default_open(true)
+                    .show(ui, |ui| show_rows(ui, &rows))
+                    .header_response
+                    .on_hover_text(source.to_string());
+            }
+        } else {
+            show_rows(ui, &order);
+        }
+    });
 
-            ui.horizontal(|ui| {
-                // Play/preview button
-                if ui.small_button("▶").clicked() {
-                    clip_to_play = Some(clip.id);
-                }
+    // Apply deferred actions
+    if let Some(id) = clip_to_select {
+        toggle_bank_selection(&mut state.bank_selected, id);
+    }
+    if let Some(id) = clip_to_add {
+        state.bank_selected.clear();
+        state.add_bank_clips_to_timeline(&[id]);
+    }
+    if let Some(id) = clip_to_play {
+        state.play_clip(id);
+    }
+}
 
-                // Mini waveform (click to add to timeline)
-                let (rect, wf_resp) =
-                    ui.allocate_exact_size(egui::vec2(40.0, 24.0), egui::Sense::click());
-                if ui.is_rect_visible(rect) {
-                    let src_idx = state
-                        .source_indices
-                        .get(&clip.source_path)
-                        .copied()
-                        .unwrap_or(0);
-                    let color =
-                        timeline::SOURCE_COLORS[src_idx % timeline::SOURCE_COLORS.len()];
-                    waveform_painter::paint_waveform(
-                        ui.painter(),
+/// What a click on a bank row asked for.
+enum BankClick {
+    Add,
+    Play,
+    /// Shift+click: add to or remove from the bank selection.
+    Select,
+}
+
+/// One bank entry: preview button, waveform, label, and quality badge.
+fn show_bank_row(ui: &mut egui::Ui, state: &EditorState, clip: &SyllableClip, selected: bool) -> Option<BankClick> {
+    let mut click = None;
+    let fill = if selected { ui.visuals().selection.bg_fill.gamma_multiply(0.5) } else { egui::Color32::TRANSPARENT };
+    egui::Frame::new().fill(fill).corner_radius(2.0).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            // Play/preview button
+            let play_resp = ui.small_button("▶").on_hover_text("Preview");
+            play_resp.widget_info(|| {
+                egui::WidgetInfo::labeled(egui::WidgetType::Button, true, format!("Preview {}", clip.label))
+            });
+            if play_resp.clicked() {
+                click = Some(BankClick::Play);
+            }
+
+            // Mini waveform (click or Enter to add to timeline, Shift+click to select)
+            let (rect, wf_resp) =
+                ui.allocate_exact_size(egui::vec2(40.0, 24.0), egui::Sense::click());
+            wf_resp.widget_info(|| {
+                egui::WidgetInfo::labeled(
+                    egui::WidgetType::Button,
+                    true,
+                    format!("Add {} ({}, {:.2} seconds) to timeline", clip.label, clip.syllable.word, clip.duration_s()),
+                )
+            });
+            if wf_resp.gained_focus() {
+                wf_resp.scroll_to_me(None);
+            }
+            if ui.is_rect_visible(rect) {
+                let src_idx = state
+                    .source_indices
+                    .get(&clip.source)
+                    .copied()
+                    .unwrap_or(0);
+                waveform_painter::paint_waveform(
+                    ui.painter(),
+                    rect,
+                    &clip.waveform,
+                    timeline::source_color(&state.arrangement, clip.source, src_idx),
+                );
+                if wf_resp.has_focus() {
+                    ui.painter().rect_stroke(
                         rect,
-                        &clip.waveform,
-                        egui::Color32::from_rgb(color.0, color.1, color.2),
+                        2.0,
+                        ui.visuals().selection.stroke,
+                        egui::StrokeKind::Outside,
                     );
                 }
+            }
 
-                // Label (click to add to timeline)
-                let label_resp = ui.vertical(|ui| {
-                    ui.label(egui::RichText::new(&clip.label).small().monospace());
-                    ui.label(
-                        egui::RichText::new(format!(
-                            "{} ({:.2}s)",
-                            clip.syllable.word,
-                            clip.duration_s()
-                        ))
-                        .small()
-                        .weak(),
-                    );
-                }).response;
+            // Label (click to add to timeline)
+            let label_resp = ui.vertical(|ui| {
+                ui.label(egui::RichText::new(&clip.label).small().monospace());
+                ui.label(
+                    egui::RichText::new(match state.bank_f0.get(&clip.id) {
+                        Some(Some(f0)) if state.bank_sort == BankSort::Pitch => {
+                            format!("{} ({:.2}s, {:.0} Hz)", clip.syllable.word, clip.duration_s(), f0)
+                        }
+                        _ => format!("{} ({:.2}s)", clip.syllable.word, clip.duration_s()),
+                    })
+                    .small()
+                    .weak(),
+                );
+            }).response;
+
+            if let Some(quality) = clip.quality {
+                let score = quality.score();
+                ui.label(egui::RichText::new(format!("{:.0}", score * 100.0)).small().color(quality_color(score)))
+                    .on_hover_text(format!(
+                        "Quality {:.0}%\nLevel {:.0}%  Clipping {:.0}%\nPitch stability {:.0}%  Duration {:.0}%",
+                        score * 100.0,
+                        quality.level * 100.0,
+                        quality.clipping * 100.0,
+                        quality.pitch_stability * 100.0,
+                        quality.duration * 100.0
+                    ));
+            }
 
-                // Click on waveform or label = add to timeline
-                if wf_resp.clicked() || label_resp.clicked() {
-                    clip_to_add = Some(clip.id);
-                }
-            });
-        }
+            // Click on waveform or label = add to timeline
+            if wf_resp.clicked() || label_resp.clicked() {
+                click = Some(if ui.input(|i| i.modifiers.shift) { BankClick::Select } else { BankClick::Add });
+            }
+        });
     });
+    click
+}
 
-    // Apply deferred actions
-    if let Some(id) = clip_to_add {
-        let tc = state
-            .arrangement
-            .get_bank_clip(id)
-            .map(TimelineClip::new);
-        if let Some(tc) = tc {
-            state.arrangement.timeline.push(tc);
-            state.arrangement.relayout(0.0);
+/// Add or remove `id` from the bank selection.
+fn toggle_bank_selection(selected: &mut Vec<ClipId>, id: ClipId) {
+    match selected.iter().position(|&s| s == id) {
+        Some(i) => {
+            selected.remove(i);
         }
+        None => selected.push(id),
     }
-    if let Some(id) = clip_to_play {
-        state.play_clip(id);
+}
+
+/// `rows` (bank indices) split by source file, keeping their order
+/// within each. Sources come in load order.
+fn group_by_source(
+    bank: &[SyllableClip],
+    rows: &[usize],
+    source_indices: &HashMap<SourceId, usize>,
+) -> Vec<(SourceId, Vec<usize>)> {
+    let mut groups: Vec<(SourceId, Vec<usize>)> = Vec::new();
+    for &i in rows {
+        let source = bank[i].source;
+        match groups.iter_mut().find(|(s, _)| *s == source) {
+            Some((_, group)) => group.push(i),
+            None => groups.push((source, vec![i])),
+        }
+    }
+    groups.sort_by_key(|(source, _)| source_indices.get(source).copied().unwrap_or(usize::MAX));
+    groups
+}
+
+/// Bank indices in display order for `sort`. `f0` holds the clips'
+/// estimated F0 for sorting by pitch; clips missing from it sort last.
+fn bank_order(bank: &[SyllableClip], sort: BankSort, f0: &HashMap<ClipId, Option<f64>>) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..bank.len()).collect();
+    match sort {
+        BankSort::Stored => {}
+        BankSort::Word => order.sort_by_cached_key(|&i| (bank[i].syllable.word.to_lowercase(), bank[i].label.clone())),
+        BankSort::Duration => order.sort_by(|&a, &b| bank[a].duration_s().total_cmp(&bank[b].duration_s())),
+        BankSort::Source => order.sort_by_cached_key(|&i| bank[i].source.to_string()),
+        BankSort::Pitch => {
+            let hz = |i: usize| f0.get(&bank[i].id).copied().flatten().unwrap_or(f64::INFINITY);
+            order.sort_by(|&a, &b| hz(a).total_cmp(&hz(b)));
+        }
+        BankSort::Quality => {
+            let score = |i: usize| bank[i].quality.map_or(-1.0, |q| q.score());
+            order.sort_by(|&a, &b| score(b).total_cmp(&score(a)));
+        }
+    }
+    order
+}
+
+/// Badge color for a quality score: green, amber, or red.
+fn quality_color(score: f64) -> egui::Color32 {
+    if score >= 0.7 {
+        egui::Color32::from_rgb(90, 190, 90)
+    } else if score >= 0.4 {
+        egui::Color32::from_rgb(220, 170, 50)
+    } else {
+        egui::Color32::from_rgb(220, 80, 70)
     }
 }
 
@@ -655,15 +1957,26 @@ pub const KEYBOARD_SHORTCUTS: &[(&str, &str)] = &[
     ("0 / g", "Cursor to beginning"),
     ("$ / G", "Cursor to end"),
     ("Ctrl+A", "Select all clips"),
+    ("Ctrl+C / Ctrl+X / Ctrl+V", "Copy / cut selected clips, paste at the cursor"),
+    ("Tab / Shift+Tab", "Select next / previous clip (timeline focused)"),
+    ("Enter", "Preview selected clip"),
+    ("Esc", "Leave the timeline so Tab moves between controls"),
+    ("Tab / Up / Down (bank)", "Move between bank entries; Enter adds or previews"),
+    ("Shift+Click (bank)", "Select bank clips to add together"),
     ("r", "Reverse selected clips"),
+    ("s", "Split the clip under the cursor"),
     ("Delete / Backspace / x", "Delete selected clips"),
     ("Ctrl+Scroll", "Zoom in/out"),
     ("Scroll", "Pan timeline"),
     ("Click clip", "Select clip"),
     ("Shift+Click", "Toggle clip selection"),
-    ("Right-click clip", "Context menu (effects)"),
-    ("Drag clip", "Reorder clips"),
+    ("Right-click clip", "Context menu (effects, swap a sung note's syllable)"),
+    ("Click note (sing lane)", "Select the clips singing that note"),
+    ("Drag clip", "Reorder clips (move freely in Free layout)"),
     ("Drag cursor", "Scrub playback position"),
+    ("Drag on ruler", "Set loop region"),
+    ("Alt+Drag", "Drag without snapping"),
+    ("Double-click ruler", "Clear loop region"),
 ];
 
 /// Show the keyboard shortcuts help window.
@@ -720,6 +2033,93 @@ mod tests {
         assert!(!state.show_keyboard_help);
     }
 
+    #[test]
+    fn test_confirm_discard_prompts_only_with_unsaved_changes() {
+        let mut state = state_with_clips(3);
+        assert!(!state.has_unsaved_changes());
+        assert!(state.confirm_discard(PendingClose::Editor));
+        assert_eq!(state.close_prompt, None);
+
+        state.timeline.selected = vec![state.arrangement.timeline[0].id];
+        state.delete_selected();
+        assert!(state.has_unsaved_changes());
+        assert!(!state.confirm_discard(PendingClose::App));
+        assert_eq!(state.close_prompt, Some(PendingClose::App));
+
+        state.close_prompt = None;
+        state.mark_saved();
+        assert!(state.confirm_discard(PendingClose::OpenProject));
+        assert_eq!(state.close_prompt, None);
+    }
+
+    #[test]
+    fn test_bank_order_by_quality() {
+        use glottisdale_core::audio::analysis::SyllableQuality;
+
+        let mut state = state_with_clips(0);
+        let template = state.arrangement.bank[0].clone();
+        let scored = |level: f64| {
+            let mut clip = template.clone();
+            clip.quality = Some(SyllableQuality { level, clipping: 1.0, pitch_stability: 1.0, duration: 1.0 });
+            clip
+        };
+        state.arrangement.bank = vec![template.clone(), scored(0.2), scored(0.9), scored(0.5)];
+
+        let no_f0 = HashMap::new();
+        assert_eq!(bank_order(&state.arrangement.bank, BankSort::Stored, &no_f0), vec![0, 1, 2, 3]);
+        assert_eq!(bank_order(&state.arrangement.bank, BankSort::Quality, &no_f0), vec![2, 3, 1, 0]);
+    }
+
+    #[test]
+    fn test_bank_order_by_word_duration_source_and_f0() {
+        let state = state_with_clips(0);
+        let template = state.arrangement.bank[0].clone();
+        let clip = |word: &str, secs: usize, source: &str| {
+            let mut clip = template.clone();
+            clip.id = uuid::Uuid::new_v4();
+            clip.syllable.word = word.into();
+            clip.samples = vec![0.0; secs * 1600].into();
+            clip.source = source.into();
+            clip
+        };
+        let bank = vec![clip("cat", 3, "b.wav"), clip("Apple", 5, "a.wav"), clip("bee", 1, "b.wav")];
+
+        let no_f0 = HashMap::new();
+        assert_eq!(bank_order(&bank, BankSort::Word, &no_f0), vec![1, 2, 0]);
+        assert_eq!(bank_order(&bank, BankSort::Duration, &no_f0), vec![2, 0, 1]);
+        assert_eq!(bank_order(&bank, BankSort::Source, &no_f0), vec![1, 0, 2]);
+        // Unvoiced and unestimated clips go last
+        let f0 = HashMap::from([(bank[0].id, Some(220.0)), (bank[1].id, None), (bank[2].id, Some(110.0))]);
+        assert_eq!(bank_order(&bank, BankSort::Pitch, &f0), vec![2, 0, 1]);
+
+        let sources = HashMap::from([(bank[0].source, 0), (bank[1].source, 1)]);
+        let groups = group_by_source(&bank, &[2, 1, 0], &sources);
+        assert_eq!(groups, vec![(bank[0].source, vec![2, 0]), (bank[1].source, vec![1])]);
+    }
+
+    #[test]
+    fn test_add_selected_bank_clips_in_pick_order() {
+        let mut state = state_with_clips(0);
+        let mut second = state.arrangement.bank[0].clone();
+        second.id = uuid::Uuid::new_v4();
+        state.arrangement.bank.push(second);
+        let (a, b) = (state.arrangement.bank[0].id, state.arrangement.bank[1].id);
+
+        toggle_bank_selection(&mut state.bank_selected, b);
+        toggle_bank_selection(&mut state.bank_selected, a);
+        toggle_bank_selection(&mut state.bank_selected, uuid::Uuid::new_v4());
+        assert_eq!(state.bank_selected.len(), 3);
+        let stale = state.bank_selected[2];
+        toggle_bank_selection(&mut state.bank_selected, stale);
+        assert_eq!(state.bank_selected, vec![b, a]);
+
+        let ids = state.bank_selected.clone();
+        state.add_bank_clips_to_timeline(&ids);
+        let sources: Vec<ClipId> = state.arrangement.timeline.iter().map(|c| c.source_clip_id).collect();
+        assert_eq!(sources, vec![b, a]);
+        assert_eq!(state.arrangement.timeline[1].position_s, 0.5);
+    }
+
     #[test]
     fn test_editor_state_looping_default_off() {
         let arrangement = Arrangement::new(16000, glottisdale_core::editor::EditorPipelineMode::Collage);
@@ -732,7 +2132,6 @@ mod tests {
     fn state_with_clips(n: usize) -> EditorState {
         use glottisdale_core::editor::{EditorPipelineMode, SyllableClip};
         use glottisdale_core::types::Syllable;
-        use std::path::PathBuf;
 
         let mut arrangement = Arrangement::new(16000, EditorPipelineMode::Collage);
         // Add a bank clip to reference
@@ -744,9 +2143,9 @@ mod tests {
                 end: 0.5,
                 word_index: 0,
             },
-            vec![0.0; 8000],
+            vec![0.0; 8000].into(),
             16000,
-            PathBuf::from("test.wav"),
+            "test.wav".into(),
         );
         let bank_id = bank_clip.id;
         arrangement.bank.push(bank_clip);
@@ -758,6 +2157,9 @@ mod tests {
                 position_s: 0.0,
                 effects: vec![],
                 effective_duration_s: 0.5,
+                pan: 0.0,
+                trim_start_s: 0.0,
+                trim_end_s: 0.0,
             });
         }
         arrangement.relayout(0.0);
@@ -811,6 +2213,20 @@ mod tests {
         assert_eq!(original_ids, new_ids, "Shuffle should be a no-op with < 2 clips");
     }
 
+    #[test]
+    fn test_shuffle_in_free_layout_keeps_slots() {
+        let mut state = state_with_clips(5);
+        state.arrangement.set_layout_mode(LayoutMode::Free);
+        for (i, tc) in state.arrangement.timeline.iter_mut().enumerate() {
+            tc.position_s = i as f64 * 2.0;
+        }
+        for _ in 0..5 {
+            state.shuffle();
+            let positions: Vec<f64> = state.arrangement.timeline.iter().map(|tc| tc.position_s).collect();
+            assert_eq!(positions, vec![0.0, 2.0, 4.0, 6.0, 8.0]);
+        }
+    }
+
     #[test]
     fn test_shuffle_preserves_clip_count() {
         let mut state = state_with_clips(5);
@@ -837,4 +2253,195 @@ mod tests {
             assert_eq!(state.arrangement.timeline[4].id, last_id);
         }
     }
+
+    #[test]
+    fn test_pan_action_sets_clip_pan_and_duplicate_keeps_it() {
+        let mut state = state_with_clips(2);
+        let clip_id = state.arrangement.timeline[0].id;
+
+        apply_context_action(&mut state, ContextAction::Pan(clip_id, -0.5));
+        assert_eq!(state.arrangement.timeline[0].pan, -0.5);
+        assert_eq!(state.arrangement.timeline[1].pan, 0.0);
+
+        apply_context_action(&mut state, ContextAction::Duplicate(clip_id));
+        assert_eq!(state.arrangement.timeline[1].pan, -0.5);
+    }
+
+    #[test]
+    fn test_split_at_cursor() {
+        let mut state = state_with_clips(2);
+        let first = state.arrangement.timeline[0].id;
+        state.timeline.selected = vec![first];
+
+        // Cursor in the second clip; the key splits the clip under it
+        state.timeline.cursor_s = 0.7;
+        assert!(state.split_at_cursor(None));
+        let durations: Vec<f64> = state.arrangement.timeline.iter().map(|tc| tc.effective_duration_s).collect();
+        assert_eq!(durations.len(), 3);
+        assert!((durations[1] - 0.2).abs() < 1e-9 && (durations[2] - 0.3).abs() < 1e-9);
+        assert_eq!(state.timeline.selected, vec![first]);
+
+        // The menu names its clip; a selected clip stays selected as both halves
+        state.timeline.cursor_s = 0.25;
+        apply_context_action(&mut state, ContextAction::SplitAtCursor(first));
+        assert_eq!(state.arrangement.timeline.len(), 4);
+        assert_eq!(state.timeline.selected, vec![first, state.arrangement.timeline[1].id]);
+
+        // Nothing under the cursor
+        state.timeline.cursor_s = 5.0;
+        assert!(!state.split_at_cursor(None));
+    }
+
+    #[test]
+    fn test_copy_cut_paste_selection() {
+        let mut state = state_with_clips(3);
+        let ids: Vec<ClipId> = state.arrangement.timeline.iter().map(|tc| tc.id).collect();
+        state.arrangement.timeline[2].effects.push(ClipEffect::Reverse);
+
+        // Nothing selected leaves the clipboard alone
+        assert_eq!(state.copy_selected(), 0);
+        assert!(!state.paste_at_cursor());
+
+        // Copied in timeline order, whatever the selection order
+        state.timeline.selected = vec![ids[2], ids[0]];
+        assert_eq!(state.copy_selected(), 2);
+        state.timeline.cursor_s = 0.8;
+        assert!(state.paste_at_cursor());
+        let timeline = &state.arrangement.timeline;
+        assert_eq!(timeline.len(), 5);
+        assert_eq!((timeline[0].id, timeline[1].id, timeline[4].id), (ids[0], ids[1], ids[2]));
+        assert!(timeline[2].effects.is_empty());
+        assert_eq!(timeline[3].effects, vec![ClipEffect::Reverse]);
+        assert_eq!(state.timeline.selected, vec![timeline[2].id, timeline[3].id]);
+        assert!((state.timeline.cursor_s - 2.0).abs() < 1e-9);
+
+        state.timeline.selected = vec![ids[1]];
+        assert!(state.cut_selected());
+        assert_eq!(state.arrangement.timeline.len(), 4);
+        assert!(state.arrangement.timeline.iter().all(|tc| tc.id != ids[1]));
+        state.timeline.cursor_s = 0.0;
+        state.paste_at_cursor();
+        assert_eq!(state.arrangement.timeline[0].source_clip_id, state.clipboard[0].source_clip_id);
+        assert_eq!(state.arrangement.timeline.len(), 5);
+    }
+
+    #[test]
+    fn test_duplicate_times_repeats_clip_or_selection() {
+        let mut state = state_with_clips(3);
+        let ids: Vec<ClipId> = state.arrangement.timeline.iter().map(|tc| tc.id).collect();
+
+        // An unselected clip repeats on its own
+        apply_context_action(&mut state, ContextAction::DuplicateTimes(ids[0], 2));
+        assert_eq!(state.arrangement.timeline.len(), 5);
+        assert_eq!(state.arrangement.timeline[3].id, ids[1]);
+
+        // A selected clip repeats the selection as a block, after its last clip
+        state.timeline.selected = vec![ids[1], ids[2]];
+        apply_context_action(&mut state, ContextAction::DuplicateTimes(ids[1], 3));
+        let timeline = &state.arrangement.timeline;
+        assert_eq!(timeline.len(), 11);
+        assert_eq!(timeline[4].id, ids[2]);
+        assert!(timeline.iter().skip(5).all(|tc| !ids.contains(&tc.id)));
+
+        // Free layout keeps the block's rhythm: copies start one span apart
+        let mut state = state_with_clips(1);
+        state.arrangement.set_layout_mode(LayoutMode::Free);
+        let id = state.arrangement.timeline[0].id;
+        state.duplicate_times(id, 2);
+        let starts: Vec<f64> = state.arrangement.timeline.iter().map(|tc| tc.position_s).collect();
+        assert_eq!(starts, vec![0.0, 0.5, 1.0]);
+    }
+
+    #[test]
+    fn test_effect_mix_actions_blend_last_effect() {
+        let mut state = state_with_clips(1);
+        let clip_id = state.arrangement.timeline[0].id;
+
+        // Nothing to blend yet
+        apply_context_action(&mut state, ContextAction::EffectWet(clip_id, 0.5));
+        assert!(state.arrangement.timeline[0].effects.is_empty());
+
+        apply_context_action(&mut state, ContextAction::Stutter(clip_id, 2));
+        apply_context_action(&mut state, ContextAction::Reverse(clip_id));
+        apply_context_action(&mut state, ContextAction::EffectWet(clip_id, 0.5));
+        apply_context_action(&mut state, ContextAction::EffectGain(clip_id, -3.0));
+        let effects = &state.arrangement.timeline[0].effects;
+        assert_eq!(effects[0], ClipEffect::Stutter { count: 2 });
+        assert_eq!(effects[1].base(), &ClipEffect::Reverse);
+        assert_eq!(effects[1].wet_gain(), (0.5, -3.0));
+
+        apply_context_action(&mut state, ContextAction::EffectWet(clip_id, 1.0));
+        apply_context_action(&mut state, ContextAction::EffectGain(clip_id, 0.0));
+        assert_eq!(state.arrangement.timeline[0].effects[1], ClipEffect::Reverse);
+    }
+
+    #[test]
+    fn test_preset_actions_save_and_apply_chain() {
+        let mut state = state_with_clips(2);
+        let (first, second) = (state.arrangement.timeline[0].id, state.arrangement.timeline[1].id);
+        apply_context_action(&mut state, ContextAction::Stutter(first, 2));
+        apply_context_action(&mut state, ContextAction::Pitch(first, -3.0));
+
+        state.preset_name = "stammer".to_string();
+        let name = state.preset_name.clone();
+        apply_context_action(&mut state, ContextAction::SavePreset(first, name));
+        assert!(state.preset_name.is_empty());
+
+        apply_context_action(&mut state, ContextAction::ApplyPreset(second, "stammer".to_string()));
+        let timeline = &state.arrangement.timeline;
+        assert_eq!(timeline[1].effects, timeline[0].effects);
+        assert_eq!(timeline[1].effective_duration_s, timeline[0].effective_duration_s);
+    }
+
+    #[test]
+    fn test_swap_syllable_replaces_note_clips() {
+        use glottisdale_core::editor::MelodyNote;
+
+        let mut state = state_with_clips(2);
+        let mut other = state.arrangement.bank[0].clone();
+        other.id = uuid::Uuid::new_v4();
+        let other_id = other.id;
+        state.arrangement.bank.push(other);
+        let sung = state.arrangement.timeline[0].id;
+        state.arrangement.melody.push(MelodyNote {
+            pitch: 60,
+            start_s: 0.0,
+            end_s: 0.5,
+            semitones: 0.0,
+            clip_ids: vec![sung],
+        });
+
+        // Clips off the melody have nothing to swap
+        let unsung = state.arrangement.timeline[1].id;
+        state.swap_syllable(unsung);
+        assert_eq!(state.arrangement.timeline.len(), 2);
+
+        state.swap_syllable(sung);
+        assert_eq!(state.arrangement.timeline.len(), 2);
+        let new_id = state.arrangement.melody[0].clip_ids[0];
+        assert_eq!(state.timeline.selected, vec![new_id]);
+        let tc = state.arrangement.timeline.iter().find(|tc| tc.id == new_id).unwrap();
+        assert_eq!(tc.source_clip_id, other_id);
+    }
+
+    #[test]
+    fn test_finish_streaming_keeps_referenced_partial_clips() {
+        use glottisdale_core::editor::EditorPipelineMode;
+
+        // Streaming editor: bank holds one provisional clip placed on the timeline
+        let mut state = state_with_clips(1);
+        state.bank_streaming = true;
+        let partial_id = state.arrangement.bank[0].id;
+
+        let final_state = state_with_clips(0);
+        let final_id = final_state.arrangement.bank[0].id;
+        let mut final_arrangement = Arrangement::new(16000, EditorPipelineMode::Collage);
+        final_arrangement.bank = final_state.arrangement.bank;
+
+        state.finish_streaming(final_arrangement);
+        assert!(!state.bank_streaming);
+        let ids: Vec<_> = state.arrangement.bank.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![final_id, partial_id]);
+        assert_eq!(state.source_indices.len(), 1);
+    }
 }
@@ -0,0 +1,192 @@
+//! Relink dialog: point a project's moved or renamed sources at their new
+//! location instead of failing to open it.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use glottisdale_core::audio::io::MEDIA_EXTENSIONS;
+use glottisdale_core::editor::{Arrangement, MissingSources};
+use glottisdale_core::source::Relink;
+
+/// How deep Search Folder looks below the chosen folder.
+const SEARCH_DEPTH: usize = 4;
+
+/// What the dialog did this frame.
+pub enum RelinkOutcome {
+    Pending,
+    Cancelled,
+    /// The project opened with the picked locations.
+    Loaded(Box<Arrangement>),
+}
+
+/// A project waiting for its missing sources to be located.
+pub struct RelinkDialog {
+    project: PathBuf,
+    /// Each missing source and the replacement picked for it.
+    entries: Vec<(PathBuf, Option<PathBuf>)>,
+    error: Option<String>,
+}
+
+impl RelinkDialog {
+    pub fn new(missing: &MissingSources) -> Self {
+        Self {
+            project: missing.project.clone(),
+            entries: missing.missing.iter().map(|p| (p.clone(), None)).collect(),
+            error: None,
+        }
+    }
+
+    fn relinks(&self) -> Vec<Relink> {
+        self.entries
+            .iter()
+            .filter_map(|(from, to)| Some(Relink { from: from.clone(), to: to.clone()? }))
+            .collect()
+    }
+
+    /// Fill in unlocated sources from files of the same name in `folder`.
+    fn search_folder(&mut self, folder: &Path) {
+        let names: Vec<OsString> = self
+            .entries
+            .iter()
+            .filter(|(_, to)| to.is_none())
+            .filter_map(|(from, _)| from.file_name().map(|n| n.to_os_string()))
+            .collect();
+        let found = find_by_name(folder, &names, SEARCH_DEPTH);
+        for (from, to) in self.entries.iter_mut().filter(|(_, to)| to.is_none()) {
+            *to = from.file_name().and_then(|n| found.get(n)).cloned();
+        }
+    }
+
+    fn open(&mut self) -> Option<Arrangement> {
+        match Arrangement::load_project_relinked(&self.project, &self.relinks()) {
+            Ok(arrangement) => Some(arrangement),
+            Err(e) => {
+                match e.downcast_ref::<MissingSources>() {
+                    // A picked file that vanished since; ask again for those
+                    Some(missing) => *self = Self { error: Some(e.to_string()), ..Self::new(missing) },
+                    None => self.error = Some(format!("{:#}", e)),
+                }
+                None
+            }
+        }
+    }
+
+    /// Show the dialog window.
+    pub fn show(&mut self, ctx: &egui::Context) -> RelinkOutcome {
+        let mut outcome = RelinkOutcome::Pending;
+        let mut open = false;
+        egui::Window::new("Relink Sources")
+            .resizable(false)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "Some sources of {} were moved or renamed. Locate each one to open the project.",
+                    display_name(&self.project)
+                ));
+                egui::Grid::new("relink_sources").num_columns(3).striped(true).show(ui, |ui| {
+                    for (from, to) in &mut self.entries {
+                        ui.label(display_name(from)).on_hover_text(from.display().to_string());
+                        match to {
+                            Some(path) => ui.label(path.display().to_string()),
+                            None => ui.colored_label(ui.visuals().warn_fg_color, "not found"),
+                        };
+                        if ui.button("Locate...").clicked() {
+                            let mut dialog = rfd::FileDialog::new().add_filter("Audio/Video", MEDIA_EXTENSIONS);
+                            if let Some(dir) = to.as_deref().and_then(Path::parent) {
+                                dialog = dialog.set_directory(dir);
+                            }
+                            if let Some(path) = dialog.pick_file() {
+                                *to = Some(path);
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(err) = &self.error {
+                    ui.colored_label(ui.visuals().error_fg_color, err);
+                }
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Search Folder...")
+                        .on_hover_text("Find sources not yet located by file name")
+                        .clicked()
+                    {
+                        if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                            self.search_folder(&folder);
+                        }
+                    }
+                    let ready = self.entries.iter().all(|(_, to)| to.is_some());
+                    if ui.add_enabled(ready, egui::Button::new("Open")).clicked() {
+                        open = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        outcome = RelinkOutcome::Cancelled;
+                    }
+                });
+            });
+        if open {
+            if let Some(arrangement) = self.open() {
+                outcome = RelinkOutcome::Loaded(Box::new(arrangement));
+            }
+        }
+        outcome
+    }
+}
+
+/// Files under `dir` (up to `depth` folders down) whose names are in
+/// `names`. The shallowest match wins.
+fn find_by_name(dir: &Path, names: &[OsString], depth: usize) -> HashMap<OsString, PathBuf> {
+    let mut found = HashMap::new();
+    let mut level = vec![dir.to_path_buf()];
+    for _ in 0..=depth {
+        let mut next = Vec::new();
+        for dir in level {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|e| e.path()) {
+                if path.is_dir() {
+                    next.push(path);
+                } else if let Some(name) = path.file_name().filter(|n| names.iter().any(|m| m == n)) {
+                    found.entry(name.to_os_string()).or_insert(path);
+                }
+            }
+        }
+        level = next;
+    }
+    found
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_folder_fills_missing_by_name() {
+        let dir = std::env::temp_dir().join(format!("glottisdale_relink_search_{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("a").join("b")).unwrap();
+        std::fs::write(dir.join("a").join("b").join("voice.wav"), b"").unwrap();
+        std::fs::write(dir.join("a").join("voice.wav"), b"").unwrap();
+
+        let missing = MissingSources {
+            project: PathBuf::from("/projects/song.glott"),
+            missing: vec![PathBuf::from("/gone/voice.wav"), PathBuf::from("/gone/other.wav")],
+        };
+        let mut dialog = RelinkDialog::new(&missing);
+        dialog.search_folder(&dir);
+        assert_eq!(dialog.entries[0].1, Some(dir.join("a").join("voice.wav")));
+        assert_eq!(dialog.entries[1].1, None);
+        assert_eq!(
+            dialog.relinks(),
+            [Relink { from: PathBuf::from("/gone/voice.wav"), to: dir.join("a").join("voice.wav") }]
+        );
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
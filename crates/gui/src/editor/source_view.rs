@@ -0,0 +1,263 @@
+//! Source view: select a time range on a source waveform and add it to the
+//! bank as a manual clip, for non-speech sounds alignment never finds.
+
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use glottisdale_core::audio::io::read_wav_channels;
+use glottisdale_core::editor::bank_builder::shared_source_channels;
+use glottisdale_core::editor::{ClipAudio, SourceBuffer, SyllableClip, WaveformData};
+use glottisdale_core::source::SourceId;
+
+use super::{waveform_painter, EditorState};
+
+/// Shortest selection that can be added to the bank (seconds).
+const MIN_SELECTION_S: f64 = 0.01;
+
+/// Peak buckets across a loaded source waveform.
+const WAVEFORM_BUCKETS: usize = 2000;
+
+/// A source file loaded for range selection.
+struct LoadedSource {
+    path: PathBuf,
+    /// Shared with the manual clips cut from it; stereo sources keep
+    /// their channels.
+    samples: SourceBuffer,
+    sample_rate: u32,
+    waveform: WaveformData,
+}
+
+/// State for the source view mode.
+#[derive(Default)]
+pub struct SourceViewState {
+    loaded: Option<LoadedSource>,
+    /// Selection as (anchor, current) in seconds, in drag order.
+    pub selection: Option<(f64, f64)>,
+    /// Label for the next manual clip.
+    pub label: String,
+    /// Last load error to display.
+    pub error: Option<String>,
+}
+
+impl SourceViewState {
+    /// Path of the loaded source, if any.
+    pub fn source_path(&self) -> Option<&Path> {
+        self.loaded.as_ref().map(|l| l.path.as_path())
+    }
+
+    /// Duration of the loaded source in seconds.
+    pub fn duration_s(&self) -> f64 {
+        self.loaded
+            .as_ref()
+            .map(|l| l.samples.samples().len() as f64 / l.sample_rate as f64)
+            .unwrap_or(0.0)
+    }
+
+    /// Selection as an ordered `(start, end)` clamped to the source, or None
+    /// if nothing usable is selected.
+    pub fn selection_range(&self) -> Option<(f64, f64)> {
+        let (a, b) = self.selection?;
+        let duration = self.duration_s();
+        let start = a.min(b).clamp(0.0, duration);
+        let end = a.max(b).clamp(0.0, duration);
+        (end - start >= MIN_SELECTION_S).then_some((start, end))
+    }
+
+    /// Load a source WAV, replacing the current one.
+    fn load(&mut self, path: &Path) {
+        self.selection = None;
+        match read_wav_channels(path) {
+            Ok((channels, sample_rate)) => {
+                let samples = shared_source_channels(&channels);
+                let bucket = (samples.samples().len() / WAVEFORM_BUCKETS).max(1);
+                let waveform = WaveformData::from_samples(samples.samples(), bucket);
+                self.loaded = Some(LoadedSource {
+                    path: path.to_path_buf(),
+                    samples,
+                    sample_rate,
+                    waveform,
+                });
+                self.error = None;
+            }
+            Err(e) => {
+                self.loaded = None;
+                self.error = Some(format!("Failed to load {}: {}", path.display(), e));
+            }
+        }
+    }
+
+    /// Build a manual bank clip from the current selection.
+    fn selection_clip(&self) -> Option<SyllableClip> {
+        let loaded = self.loaded.as_ref()?;
+        let (start, end) = self.selection_range()?;
+        let label = if self.label.trim().is_empty() { "selection" } else { self.label.trim() };
+        Some(SyllableClip::from_range(
+            &loaded.samples,
+            loaded.sample_rate,
+            SourceId::intern(&loaded.path),
+            start,
+            end,
+            label,
+        ))
+    }
+}
+
+/// Show the source view in place of the timeline.
+pub fn show_source_view(ui: &mut egui::Ui, state: &mut EditorState) {
+    // Sources known to the bank, in color order
+    let mut sources: Vec<(SourceId, usize)> = state.source_indices.iter().map(|(&s, &i)| (s, i)).collect();
+    sources.sort_by_key(|(_, i)| *i);
+
+    let mut to_load: Option<PathBuf> = None;
+    ui.horizontal(|ui| {
+        ui.label("Source:");
+        let selected_name = state
+            .source_view
+            .source_path()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "(choose)".to_string());
+        egui::ComboBox::from_id_salt("source_view_source")
+            .selected_text(selected_name)
+            .show_ui(ui, |ui| {
+                for (source, _) in &sources {
+                    let path = source.path();
+                    let name = path.file_name().unwrap_or_default().to_string_lossy();
+                    let is_current = state.source_view.source_path() == Some(path);
+                    if ui.selectable_label(is_current, name).clicked() && !is_current {
+                        to_load = Some(path.to_path_buf());
+                    }
+                }
+            });
+    });
+    if let Some(path) = to_load {
+        state.source_view.load(&path);
+    }
+
+    if let Some(ref err) = state.source_view.error {
+        ui.colored_label(egui::Color32::RED, err);
+    }
+
+    let Some(loaded) = state.source_view.loaded.as_ref() else {
+        ui.label("Pick a source to select a range from its waveform.");
+        return;
+    };
+    let duration = state.source_view.duration_s();
+
+    // Waveform with drag-to-select
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), 160.0),
+        egui::Sense::click_and_drag(),
+    );
+    let px_to_time = |x: f32| ((x - rect.left()) / rect.width()) as f64 * duration;
+    let time_to_px = |t: f64| rect.left() + (t / duration.max(1e-9)) as f32 * rect.width();
+
+    if response.drag_started() {
+        if let Some(pos) = response.interact_pointer_pos() {
+            let t = px_to_time(pos.x);
+            state.source_view.selection = Some((t, t));
+        }
+    } else if response.dragged() {
+        if let (Some(pos), Some(sel)) = (response.interact_pointer_pos(), state.source_view.selection.as_mut()) {
+            sel.1 = px_to_time(pos.x);
+        }
+    } else if response.clicked() {
+        state.source_view.selection = None;
+    }
+
+    if ui.is_rect_visible(rect) {
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, egui::Color32::from_gray(30));
+        waveform_painter::paint_waveform(
+            &painter,
+            rect,
+            &loaded.waveform,
+            egui::Color32::from_rgb(120, 180, 220),
+        );
+        if let Some((start, end)) = state.source_view.selection_range() {
+            let sel_rect = egui::Rect::from_x_y_ranges(time_to_px(start)..=time_to_px(end), rect.y_range());
+            painter.rect_filled(sel_rect, 0.0, egui::Color32::from_rgba_unmultiplied(255, 220, 80, 50));
+            painter.rect_stroke(
+                sel_rect,
+                0.0,
+                egui::Stroke::new(1.0, egui::Color32::from_rgb(255, 220, 80)),
+                egui::StrokeKind::Inside,
+            );
+        }
+    }
+
+    // Selection actions
+    let range = state.source_view.selection_range();
+    ui.horizontal(|ui| {
+        match range {
+            Some((start, end)) => ui.label(format!("{:.2}s - {:.2}s ({:.2}s)", start, end, end - start)),
+            None => ui.label("Drag on the waveform to select a range"),
+        };
+        ui.separator();
+        ui.label("Label:");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.source_view.label)
+                .hint_text("selection")
+                .desired_width(120.0),
+        );
+        if ui.add_enabled(range.is_some(), egui::Button::new("▶ Play")).clicked() {
+            if let (Some((start, end)), Some(loaded)) = (range, state.source_view.loaded.as_ref()) {
+                let selection = ClipAudio::cut(&loaded.samples, loaded.sample_rate, start, end, 0.0, 0.0);
+                state
+                    .playback
+                    .play_channels(selection.to_channels(), loaded.sample_rate, 0.0);
+            }
+        }
+        if ui.add_enabled(range.is_some(), egui::Button::new("Add to Bank")).clicked() {
+            if let Some(clip) = state.source_view.selection_clip() {
+                state.arrangement.bank.push(clip);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loaded_state(duration_s: f64) -> SourceViewState {
+        let samples = SourceBuffer::from(vec![0.1f32; (duration_s * 16000.0) as usize]);
+        SourceViewState {
+            loaded: Some(LoadedSource {
+                path: PathBuf::from("source.wav"),
+                waveform: WaveformData::new(&samples.samples()[..]),
+                samples,
+                sample_rate: 16000,
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_selection_range_orders_and_clamps() {
+        let mut view = loaded_state(2.0);
+        assert_eq!(view.selection_range(), None);
+
+        view.selection = Some((1.5, 0.5));
+        assert_eq!(view.selection_range(), Some((0.5, 1.5)));
+
+        view.selection = Some((1.0, 5.0));
+        assert_eq!(view.selection_range(), Some((1.0, 2.0)));
+
+        view.selection = Some((1.0, 1.001));
+        assert_eq!(view.selection_range(), None);
+    }
+
+    #[test]
+    fn test_selection_clip_is_manual() {
+        let mut view = loaded_state(2.0);
+        view.selection = Some((0.5, 1.0));
+        let clip = view.selection_clip().unwrap();
+        assert!(clip.manual);
+        assert_eq!(clip.label, "selection");
+        assert_eq!(clip.samples.len(), 8000);
+
+        view.label = "whistle".into();
+        assert_eq!(view.selection_clip().unwrap().label, "whistle");
+    }
+}
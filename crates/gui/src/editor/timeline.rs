@@ -1,26 +1,164 @@
 //! Timeline widget — custom egui painting with zoom/pan and clip layout.
 
 use eframe::egui;
-use glottisdale_core::editor::{Arrangement, ClipId};
+use glottisdale_core::editor::playback_engine::LoopRegion;
+use glottisdale_core::editor::{Arrangement, ClipId, LayoutMode, MelodyNote, TimelineClip, MIN_TRIMMED_S};
+use glottisdale_core::sing::midi_parser::note_name;
+use glottisdale_core::source::SourceId;
 
 use super::waveform_painter::paint_clip_block;
 
-/// Colors for clips from different source files.
-pub const SOURCE_COLORS: &[(u8, u8, u8)] = &[
-    (70, 130, 180),  // steel blue
-    (180, 100, 60),  // terracotta
-    (80, 160, 80),   // green
-    (160, 80, 160),  // purple
-    (180, 160, 50),  // gold
-    (80, 160, 160),  // teal
-];
-
-/// Drag-to-reorder state.
+/// Clip drag state: a reorder in sequential layout, a move in free layout.
 pub struct DragState {
     pub clip_index: usize,
     pub clip_id: ClipId,
     /// Index to insert before (None = not yet determined).
     pub insert_before: Option<usize>,
+    /// Where the pointer grabbed the clip, relative to its start (seconds).
+    pub grab_offset_s: f64,
+    /// New clip start in free layout (None = not yet moved).
+    pub move_to_s: Option<f64>,
+}
+
+/// Which end of a clip, as drawn on the timeline, a trim drag moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TrimEdge {
+    Left,
+    Right,
+}
+
+/// Clip edge drag state: trimming the clip's in or out point.
+pub struct TrimDrag {
+    pub clip_index: usize,
+    pub edge: TrimEdge,
+    /// Time of the dragged edge when the drag started.
+    pub edge_s: f64,
+    /// Trims (start, end) the drag would set, once moved.
+    pub trims: Option<(f64, f64)>,
+    /// Where the clip would start and end with those trims (seconds).
+    pub span_s: (f64, f64),
+}
+
+/// Clip edit produced by a finished drag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClipDrag {
+    /// Move the clip at `from` to before index `to`.
+    Reorder { from: usize, to: usize },
+    /// Place the clip at `index` so it starts at `position_s`.
+    Move { index: usize, position_s: f64 },
+    /// Trim the clip at `index` to skip this much of its bank clip.
+    Trim { index: usize, trim_start_s: f64, trim_end_s: f64 },
+}
+
+/// Width of the grab zone at each end of a clip for trimming (pixels).
+const TRIM_HANDLE_PX: f32 = 6.0;
+
+/// Height of the time ruler, where dragging sets the loop region.
+const RULER_HEIGHT: f32 = 16.0;
+
+/// Height of the overview strip above the timeline.
+const MINIMAP_HEIGHT: f32 = 32.0;
+
+/// Height of the melody notes lane shown above sing arrangements.
+const NOTES_LANE_HEIGHT: f32 = 28.0;
+
+/// Distance (pixels) within which a clip edge pulls a dragged time onto it.
+const SNAP_RADIUS_PX: f64 = 8.0;
+
+/// Grid that dragged times snap to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapGrid {
+    Off,
+    /// A fixed step in seconds.
+    Seconds(f64),
+    /// One beat at the current BPM.
+    Beat,
+}
+
+impl SnapGrid {
+    pub fn label(&self) -> String {
+        match self {
+            SnapGrid::Off => "No grid".to_string(),
+            SnapGrid::Seconds(s) => format!("{:.0} ms", s * 1000.0),
+            SnapGrid::Beat => "Beat".to_string(),
+        }
+    }
+}
+
+/// Grids offered in the toolbar.
+pub const SNAP_GRIDS: &[SnapGrid] = &[
+    SnapGrid::Off,
+    SnapGrid::Seconds(0.05),
+    SnapGrid::Seconds(0.1),
+    SnapGrid::Seconds(0.25),
+    SnapGrid::Beat,
+];
+
+/// Snapping for reorder drags, cursor scrubbing, and loop markers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapSettings {
+    pub enabled: bool,
+    pub grid: SnapGrid,
+    /// Pull times onto nearby clip edges.
+    pub clip_edges: bool,
+    /// Tempo for the beat grid; `None` disables it.
+    pub bpm: Option<f64>,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            grid: SnapGrid::Seconds(0.1),
+            clip_edges: true,
+            bpm: None,
+        }
+    }
+}
+
+impl SnapSettings {
+    /// Grid step in seconds, if a usable grid is selected.
+    pub fn grid_step_s(&self) -> Option<f64> {
+        match self.grid {
+            SnapGrid::Off => None,
+            SnapGrid::Seconds(s) => Some(s),
+            SnapGrid::Beat => self.bpm.filter(|&bpm| bpm > 0.0).map(|bpm| 60.0 / bpm),
+        }
+        .filter(|&step| step > 0.0)
+    }
+
+    /// Snap `time_s` to the nearest clip edge within the magnet radius,
+    /// otherwise to the grid. Unchanged when snapping is off.
+    pub fn snap(&self, time_s: f64, edges: &[f64], pixels_per_second: f64) -> f64 {
+        if !self.enabled {
+            return time_s;
+        }
+        if self.clip_edges {
+            let radius_s = SNAP_RADIUS_PX / pixels_per_second;
+            let nearest = edges
+                .iter()
+                .copied()
+                .filter(|e| (e - time_s).abs() <= radius_s)
+                .min_by(|a, b| (a - time_s).abs().total_cmp(&(b - time_s).abs()));
+            if let Some(edge) = nearest {
+                return edge;
+            }
+        }
+        match self.grid_step_s() {
+            Some(step) => ((time_s / step).round() * step).max(0.0),
+            None => time_s,
+        }
+    }
+}
+
+/// Start and end times of every timeline clip except `skip`.
+fn clip_edges(arrangement: &Arrangement, skip: Option<ClipId>) -> Vec<f64> {
+    arrangement
+        .timeline
+        .iter()
+        .filter(|tc| Some(tc.id) != skip)
+        .flat_map(|tc| [tc.position_s, tc.position_s + tc.effective_duration_s])
+        .collect()
 }
 
 /// Keyboard action emitted by the timeline for the parent to handle.
@@ -34,6 +172,16 @@ pub enum TimelineAction {
     SelectAll,
     /// Reverse selected clips.
     ReverseSelected,
+    /// Play the selected clip on its own.
+    PreviewSelected,
+    /// Split the clip under the cursor in two.
+    SplitAtCursor,
+    /// Copy selected clips to the editor clipboard.
+    Copy,
+    /// Move selected clips to the editor clipboard.
+    Cut,
+    /// Paste the editor clipboard at the cursor.
+    Paste,
 }
 
 /// Visual and interaction state for the timeline.
@@ -52,8 +200,20 @@ pub struct TimelineState {
     pub context_menu_clip: Option<ClipId>,
     /// Active drag-to-reorder state.
     pub drag: Option<DragState>,
+    /// Active clip edge (trim) drag.
+    pub trim_drag: Option<TrimDrag>,
     /// Whether the cursor/scrubber is being dragged.
     pub dragging_cursor: bool,
+    /// Region playback repeats, set by dragging on the ruler.
+    pub loop_region: Option<LoopRegion>,
+    /// Where a ruler drag started while setting the loop region.
+    pub loop_drag_anchor: Option<f64>,
+    /// Snap-to-grid and snap-to-clip-edge settings.
+    pub snap: SnapSettings,
+    /// Active drag on the minimap.
+    pub minimap_drag: Option<MinimapDrag>,
+    /// Minimap RMS envelope and the key of the timeline it was built from.
+    minimap_envelope: Option<(u64, Vec<f32>)>,
 }
 
 impl Default for TimelineState {
@@ -66,7 +226,13 @@ impl Default for TimelineState {
             selected: Vec::new(),
             context_menu_clip: None,
             drag: None,
+            trim_drag: None,
             dragging_cursor: false,
+            loop_region: None,
+            loop_drag_anchor: None,
+            snap: SnapSettings::default(),
+            minimap_drag: None,
+            minimap_envelope: None,
         }
     }
 }
@@ -95,8 +261,8 @@ impl TimelineState {
                 if let Some(mouse_pos) = ui.input(|i| i.pointer.hover_pos()) {
                     let time_at_mouse = self.px_to_time(mouse_pos.x - response.rect.left());
                     let zoom_factor = 1.0 + scroll_y as f64 * 0.003;
-                    self.pixels_per_second =
-                        (self.pixels_per_second * zoom_factor).clamp(10.0, 5000.0);
+                    self.pixels_per_second = (self.pixels_per_second * zoom_factor)
+                        .clamp(MIN_PIXELS_PER_SECOND, MAX_PIXELS_PER_SECOND);
                     // Keep time_at_mouse at the same pixel position
                     let new_px = mouse_pos.x - response.rect.left();
                     self.scroll_offset_s =
@@ -118,15 +284,71 @@ impl TimelineState {
     }
 }
 
-/// Get a color for a source file index.
-fn source_color(index: usize) -> egui::Color32 {
-    let (r, g, b) = SOURCE_COLORS[index % SOURCE_COLORS.len()];
+/// Display color for clips from a source file (see
+/// [`Arrangement::source_color`]).
+pub fn source_color(arrangement: &Arrangement, source: SourceId, index: usize) -> egui::Color32 {
+    let (r, g, b) = arrangement.source_color(source, index);
     egui::Color32::from_rgb(r, g, b)
 }
 
-/// Find which clip index is at a given time, if any.
-fn clip_at_time(arrangement: &Arrangement, time_s: f64) -> Option<(usize, ClipId)> {
-    for (i, tc) in arrangement.timeline.iter().enumerate() {
+/// Timeline index of the clip after (or before) the current selection.
+///
+/// With nothing selected, steps onto the first (or last) clip. Stops at
+/// either end instead of wrapping.
+pub fn step_selection(arrangement: &Arrangement, selected: &[ClipId], forward: bool) -> Option<usize> {
+    let len = arrangement.timeline.len();
+    if len == 0 {
+        return None;
+    }
+    let current = arrangement
+        .timeline
+        .iter()
+        .rposition(|tc| selected.last() == Some(&tc.id));
+    Some(match (current, forward) {
+        (None, true) => 0,
+        (None, false) => len - 1,
+        (Some(i), true) => (i + 1).min(len - 1),
+        (Some(i), false) => i.saturating_sub(1),
+    })
+}
+
+/// Screen reader description of the timeline and its selection.
+fn accessible_label(arrangement: &Arrangement, state: &TimelineState) -> String {
+    let mut label = format!(
+        "Timeline, {} clips, cursor at {:.2} seconds",
+        arrangement.timeline.len(),
+        state.cursor_s
+    );
+    if let Some(tc) = state
+        .selected
+        .last()
+        .and_then(|id| arrangement.timeline.iter().find(|tc| tc.id == *id))
+    {
+        if let Some(clip) = arrangement.get_bank_clip(tc.source_clip_id) {
+            label.push_str(&format!(
+                ", selected {} at {:.2} seconds",
+                clip.label, tc.position_s
+            ));
+        }
+    }
+    label
+}
+
+/// Timeline index to insert the dragged clip before when dropped at
+/// `drop_time_s`: before the first other clip whose midpoint is later.
+fn insertion_index(arrangement: &Arrangement, dragged_index: usize, drop_time_s: f64) -> usize {
+    arrangement
+        .timeline
+        .iter()
+        .enumerate()
+        .find(|&(i, tc)| i != dragged_index && drop_time_s < tc.position_s + tc.effective_duration_s / 2.0)
+        .map_or(arrangement.timeline.len(), |(i, _)| i)
+}
+
+/// Find which clip index is at a given time, if any. Where clips overlap,
+/// the later one (painted on top) wins.
+pub fn clip_at_time(arrangement: &Arrangement, time_s: f64) -> Option<(usize, ClipId)> {
+    for (i, tc) in arrangement.timeline.iter().enumerate().rev() {
         let clip_end = tc.position_s + tc.effective_duration_s;
         if time_s >= tc.position_s && time_s <= clip_end {
             return Some((i, tc.id));
@@ -135,37 +357,129 @@ fn clip_at_time(arrangement: &Arrangement, time_s: f64) -> Option<(usize, ClipId
     None
 }
 
-/// Paint the timeline with all clips. Returns (response, optional reorder, keyboard actions).
+/// The clip edge at `time_s` that a drag would trim: within `tolerance_s`
+/// inside either end of a clip wide enough to have handles.
+fn trim_edge_at(arrangement: &Arrangement, time_s: f64, tolerance_s: f64) -> Option<(usize, TrimEdge)> {
+    arrangement.timeline.iter().enumerate().rev().find_map(|(i, tc)| {
+        let end = tc.position_s + tc.effective_duration_s;
+        if tc.effective_duration_s < tolerance_s * 3.0 || time_s < tc.position_s || time_s > end {
+            None
+        } else if time_s <= tc.position_s + tolerance_s {
+            Some((i, TrimEdge::Left))
+        } else if time_s >= end - tolerance_s {
+            Some((i, TrimEdge::Right))
+        } else {
+            None
+        }
+    })
+}
+
+/// Trims `(start, end)` of `tc` after moving its `edge` by `delta_s`
+/// timeline seconds, and where the clip would then start and end.
+///
+/// The move is scaled back through the clip's effects (a clip stretched
+/// 2x trims half as much source audio), and a reversed clip's left edge
+/// is its end. At least [`MIN_TRIMMED_S`] of the bank clip is kept.
+pub fn dragged_trims(tc: &TimelineClip, source_duration_s: f64, edge: TrimEdge, delta_s: f64) -> ((f64, f64), (f64, f64)) {
+    let trimmed = tc.trimmed_duration_s(source_duration_s);
+    let scale = if trimmed > 0.0 { tc.effective_duration_s / trimmed } else { 1.0 };
+    let room = (source_duration_s - MIN_TRIMMED_S).max(0.0);
+    let (start, end) = (tc.trim_start_s, tc.trim_end_s);
+    // Source seconds added to the trim at the dragged end
+    let delta = match edge {
+        TrimEdge::Left => delta_s / scale,
+        TrimEdge::Right => -delta_s / scale,
+    };
+    let trims = if (edge == TrimEdge::Left) != tc.is_reversed() {
+        ((start + delta).clamp(0.0, (room - end).max(0.0)), end)
+    } else {
+        (start, (end + delta).clamp(0.0, (room - start).max(0.0)))
+    };
+    let added = (trims.0 - start) + (trims.1 - end);
+    let clip_end = tc.position_s + tc.effective_duration_s;
+    let span = match edge {
+        TrimEdge::Left => (tc.position_s + added * scale, clip_end),
+        TrimEdge::Right => (tc.position_s, clip_end - added * scale),
+    };
+    (trims, span)
+}
+
+/// Find the melody note sounding at a given time, if any.
+fn note_at_time(arrangement: &Arrangement, time_s: f64) -> Option<&MelodyNote> {
+    arrangement
+        .melody
+        .iter()
+        .rev()
+        .find(|n| time_s >= n.start_s && time_s <= n.end_s)
+}
+
+/// Paint the timeline with all clips. Returns (response, finished clip drag, keyboard actions).
 pub fn show_timeline(
     ui: &mut egui::Ui,
     arrangement: &Arrangement,
     state: &mut TimelineState,
-    source_file_indices: &std::collections::HashMap<std::path::PathBuf, usize>,
-) -> (egui::Response, Option<(usize, usize)>, Vec<TimelineAction>) {
-    let desired_size = egui::vec2(ui.available_width(), state.track_height + 20.0);
+    source_file_indices: &std::collections::HashMap<SourceId, usize>,
+) -> (egui::Response, Option<ClipDrag>, Vec<TimelineAction>) {
+    let lane_height = if arrangement.melody.is_empty() { 0.0 } else { NOTES_LANE_HEIGHT };
+    let desired_size = egui::vec2(ui.available_width(), state.track_height + lane_height + 20.0);
     let (rect, response) = ui.allocate_exact_size(desired_size, egui::Sense::click_and_drag());
 
     if !ui.is_rect_visible(rect) {
         return (response, None, Vec::new());
     }
 
+    // Keep Tab and arrows inside the focused timeline; Escape leaves it
+    if response.clicked() || response.drag_started() || response.secondary_clicked() {
+        response.request_focus();
+    }
+    let focused = response.has_focus();
+    if focused {
+        ui.memory_mut(|m| {
+            m.set_focus_lock_filter(
+                response.id,
+                egui::EventFilter {
+                    tab: true,
+                    horizontal_arrows: true,
+                    vertical_arrows: true,
+                    escape: false,
+                },
+            )
+        });
+    }
+    response.widget_info(|| {
+        egui::WidgetInfo::labeled(egui::WidgetType::Other, true, accessible_label(arrangement, state))
+    });
+
     let painter = ui.painter_at(rect);
 
     // Background
     painter.rect_filled(rect, 0.0, egui::Color32::from_gray(30));
+    if focused {
+        painter.rect_stroke(
+            rect,
+            0.0,
+            ui.visuals().selection.stroke,
+            egui::StrokeKind::Inside,
+        );
+    }
 
-    // Track area
+    // Notes lane (sing arrangements) between the ruler and the track
+    let lane_rect = egui::Rect::from_min_size(
+        egui::pos2(rect.left(), rect.top() + RULER_HEIGHT),
+        egui::vec2(rect.width(), lane_height),
+    );
     let track_rect = egui::Rect::from_min_size(
-        egui::pos2(rect.left(), rect.top() + 16.0),
+        egui::pos2(rect.left(), lane_rect.bottom()),
         egui::vec2(rect.width(), state.track_height),
     );
 
     // Time ruler at top
-    paint_time_ruler(
-        &painter,
-        egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), 16.0)),
-        state,
-    );
+    let ruler_rect = egui::Rect::from_min_size(rect.min, egui::vec2(rect.width(), RULER_HEIGHT));
+    paint_loop_region(&painter, ruler_rect, rect, state);
+    paint_time_ruler(&painter, ruler_rect, state);
+    if !arrangement.melody.is_empty() {
+        paint_notes_lane(&painter, lane_rect, &arrangement.melody, state);
+    }
 
     // Paint clips
     let dragging_id = state.drag.as_ref().map(|d| d.clip_id);
@@ -186,22 +500,19 @@ pub fn show_timeline(
 
         if let Some(bank_clip) = arrangement.get_bank_clip(tc.source_clip_id) {
             let src_idx = source_file_indices
-                .get(&bank_clip.source_path)
+                .get(&bank_clip.source)
                 .copied()
                 .unwrap_or(0);
             let is_ghost = dragging_id == Some(tc.id);
             let alpha = if is_ghost { 0.15 } else { 0.3 };
-            let bg = source_color(src_idx).gamma_multiply(alpha);
-            let wf_color = if is_ghost {
-                source_color(src_idx).gamma_multiply(0.4)
-            } else {
-                source_color(src_idx)
-            };
+            let color = source_color(arrangement, bank_clip.source, src_idx);
+            let bg = color.gamma_multiply(alpha);
+            let wf_color = if is_ghost { color.gamma_multiply(0.4) } else { color };
 
             paint_clip_block(
                 &painter,
                 clip_rect,
-                &bank_clip.waveform,
+                &tc.trimmed_waveform(bank_clip),
                 &bank_clip.label,
                 bg,
                 wf_color,
@@ -229,6 +540,33 @@ pub fn show_timeline(
                 egui::Stroke::new(3.0, egui::Color32::from_rgb(100, 180, 255)),
             );
         }
+        // Outline where a free-layout move will land
+        if let (Some(move_to), Some(tc)) = (drag.move_to_s, arrangement.timeline.get(drag.clip_index)) {
+            let target = egui::Rect::from_min_size(
+                egui::pos2(state.time_to_px(move_to) + rect.left(), track_rect.top()),
+                egui::vec2((tc.effective_duration_s * state.pixels_per_second) as f32, state.track_height),
+            );
+            painter.rect_stroke(
+                target,
+                2.0,
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(100, 180, 255)),
+                egui::StrokeKind::Inside,
+            );
+        }
+    }
+
+    // Outline what a trim drag keeps of the clip
+    if let Some(span) = state.trim_drag.as_ref().filter(|t| t.trims.is_some()).map(|t| t.span_s) {
+        let target = egui::Rect::from_x_y_ranges(
+            state.time_to_px(span.0) + rect.left()..=state.time_to_px(span.1) + rect.left(),
+            track_rect.y_range(),
+        );
+        painter.rect_stroke(
+            target,
+            2.0,
+            egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 200, 80)),
+            egui::StrokeKind::Inside,
+        );
     }
 
     // Playback cursor with drag handle
@@ -264,17 +602,56 @@ pub fn show_timeline(
     state.handle_zoom(ui, &response);
     state.handle_pan(ui, &response);
 
-    let mut reorder: Option<(usize, usize)> = None;
+    let mut clip_drag: Option<ClipDrag> = None;
+    let free_layout = arrangement.layout_mode == LayoutMode::Free;
+
+    // Alt bypasses snapping for the current drag
+    let bypass_snap = ui.input(|i| i.modifiers.alt);
+    let snap = |state: &TimelineState, time_s: f64, skip: Option<ClipId>| {
+        if bypass_snap {
+            time_s
+        } else {
+            state.snap.snap(time_s, &clip_edges(arrangement, skip), state.pixels_per_second)
+        }
+    };
 
-    // Handle drag — cursor drag takes priority over clip reorder
+    // Clip edges can be dragged to trim
+    let trim_tolerance_s = TRIM_HANDLE_PX as f64 / state.pixels_per_second;
+    let hover_edge = response
+        .hover_pos()
+        .filter(|pos| track_rect.contains(*pos))
+        .and_then(|pos| trim_edge_at(arrangement, state.px_to_time(pos.x - rect.left()), trim_tolerance_s));
+    if hover_edge.is_some() || state.trim_drag.is_some() {
+        ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+    }
+
+    // Handle drag — cursor drag takes priority over trims and clip reorder
     let cursor_grab_px = 8.0; // pixels of tolerance for grabbing cursor
     if response.drag_started() {
         if let Some(origin) = ui.input(|i| i.pointer.press_origin()) {
             let click_px = origin.x - rect.left();
             let cursor_px = state.time_to_px(state.cursor_s);
-            if (click_px - cursor_px).abs() < cursor_grab_px {
+            if ruler_rect.contains(origin) && (click_px - cursor_px).abs() >= cursor_grab_px {
+                // Dragging on the ruler marks the loop region
+                state.loop_drag_anchor = Some(snap(state, state.px_to_time(click_px).max(0.0), None));
+            } else if (click_px - cursor_px).abs() < cursor_grab_px {
                 // Dragging the cursor/scrubber
                 state.dragging_cursor = true;
+            } else if let Some((idx, edge)) = track_rect
+                .contains(origin)
+                .then(|| trim_edge_at(arrangement, state.px_to_time(click_px), trim_tolerance_s))
+                .flatten()
+            {
+                let tc = &arrangement.timeline[idx];
+                let span_s = (tc.position_s, tc.position_s + tc.effective_duration_s);
+                state.trim_drag = Some(TrimDrag {
+                    clip_index: idx,
+                    edge,
+                    edge_s: if edge == TrimEdge::Left { span_s.0 } else { span_s.1 },
+                    trims: None,
+                    span_s,
+                });
+                state.selected = vec![tc.id];
             } else {
                 let click_time = state.px_to_time(click_px);
                 if let Some((idx, id)) = clip_at_time(arrangement, click_time) {
@@ -282,6 +659,8 @@ pub fn show_timeline(
                         clip_index: idx,
                         clip_id: id,
                         insert_before: None,
+                        grab_offset_s: click_time - arrangement.timeline[idx].position_s,
+                        move_to_s: None,
                     });
                     if !state.selected.contains(&id) {
                         state.selected = vec![id];
@@ -296,38 +675,74 @@ pub fn show_timeline(
     }
 
     if response.dragged() {
-        if state.dragging_cursor {
+        if let Some(anchor) = state.loop_drag_anchor {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let time = snap(state, state.px_to_time(pos.x - rect.left()).max(0.0), None);
+                state.loop_region = LoopRegion::new(anchor, time);
+            }
+        } else if state.dragging_cursor {
             if let Some(pos) = response.interact_pointer_pos() {
                 let px = pos.x - rect.left();
-                state.cursor_s = state.px_to_time(px).max(0.0);
+                state.cursor_s = snap(state, state.px_to_time(px).max(0.0), None);
             }
-        } else if let Some(ref mut drag) = state.drag {
+        } else if let Some((index, edge, edge_s)) = state.trim_drag.as_ref().map(|t| (t.clip_index, t.edge, t.edge_s)) {
+            let tc = &arrangement.timeline[index];
+            let source = arrangement.get_bank_clip(tc.source_clip_id);
+            if let (Some(pos), Some(source)) = (response.interact_pointer_pos(), source) {
+                let to = snap(state, state.px_to_time(pos.x - rect.left()).max(0.0), Some(tc.id));
+                let (trims, span_s) = dragged_trims(tc, source.duration_s(), edge, to - edge_s);
+                if let Some(trim) = state.trim_drag.as_mut() {
+                    trim.trims = Some(trims);
+                    trim.span_s = span_s;
+                }
+            }
+        } else if let Some((clip_id, clip_index, grab_offset)) =
+            state.drag.as_ref().map(|d| (d.clip_id, d.clip_index, d.grab_offset_s))
+        {
             if let Some(pos) = response.interact_pointer_pos() {
                 let px = pos.x - rect.left();
-                let drag_time = px as f64 / state.pixels_per_second + state.scroll_offset_s;
-                let mut insert = arrangement.timeline.len();
-                for (i, tc) in arrangement.timeline.iter().enumerate() {
-                    if i == drag.clip_index {
-                        continue;
+                if free_layout {
+                    // Snap the clip's start, not the grab point
+                    let start = (state.px_to_time(px) - grab_offset).max(0.0);
+                    let start = snap(state, start, Some(clip_id)).max(0.0);
+                    if let Some(drag) = state.drag.as_mut() {
+                        drag.move_to_s = Some(start);
                     }
-                    let mid = tc.position_s + tc.effective_duration_s / 2.0;
-                    if drag_time < mid {
-                        insert = i;
-                        break;
+                } else {
+                    let drag_time = snap(state, state.px_to_time(px), Some(clip_id));
+                    let insert = insertion_index(arrangement, clip_index, drag_time);
+                    if let Some(drag) = state.drag.as_mut() {
+                        drag.insert_before = Some(insert);
                     }
                 }
-                drag.insert_before = Some(insert);
             }
         }
     }
 
     if response.drag_stopped() {
-        if state.dragging_cursor {
+        if state.loop_drag_anchor.is_some() {
+            state.loop_drag_anchor = None;
+        } else if state.dragging_cursor {
             state.dragging_cursor = false;
+        } else if let Some(trim) = state.trim_drag.take() {
+            let changed = trim.trims.zip(arrangement.timeline.get(trim.clip_index)).is_some_and(
+                |((start, end), tc)| start != tc.trim_start_s || end != tc.trim_end_s,
+            );
+            if let (true, Some((trim_start_s, trim_end_s))) = (changed, trim.trims) {
+                clip_drag = Some(ClipDrag::Trim { index: trim.clip_index, trim_start_s, trim_end_s });
+            }
         } else if let Some(drag) = state.drag.take() {
-            if let Some(insert) = drag.insert_before {
+            if let Some(position_s) = drag.move_to_s {
+                let moved = arrangement
+                    .timeline
+                    .get(drag.clip_index)
+                    .is_some_and(|tc| tc.position_s != position_s);
+                if moved {
+                    clip_drag = Some(ClipDrag::Move { index: drag.clip_index, position_s });
+                }
+            } else if let Some(insert) = drag.insert_before {
                 if insert != drag.clip_index && insert != drag.clip_index + 1 {
-                    reorder = Some((drag.clip_index, insert));
+                    clip_drag = Some(ClipDrag::Reorder { from: drag.clip_index, to: insert });
                 }
             }
         }
@@ -348,12 +763,31 @@ pub fn show_timeline(
         }
     }
 
+    // Double-click on the ruler clears the loop region
+    if response.double_clicked()
+        && response.interact_pointer_pos().is_some_and(|pos| ruler_rect.contains(pos))
+    {
+        state.loop_region = None;
+    }
+
     // Handle click to select/set cursor (only if not dragging)
-    if response.clicked() && state.drag.is_none() && !state.dragging_cursor {
+    if response.clicked() && state.drag.is_none() && state.trim_drag.is_none() && !state.dragging_cursor {
         if let Some(pos) = response.interact_pointer_pos() {
             let click_time = state.px_to_time(pos.x - rect.left());
 
-            if let Some((_, clip_id)) = clip_at_time(arrangement, click_time) {
+            let lane_note = lane_rect
+                .contains(pos)
+                .then(|| note_at_time(arrangement, click_time))
+                .flatten();
+            if let Some(note) = lane_note {
+                // Clicking a note selects the clips that sing it
+                state.selected = note
+                    .clip_ids
+                    .iter()
+                    .copied()
+                    .filter(|id| arrangement.timeline.iter().any(|tc| tc.id == *id))
+                    .collect();
+            } else if let Some((_, clip_id)) = clip_at_time(arrangement, click_time) {
                 let shift = ui.input(|i| i.modifiers.shift || i.modifiers.command);
                 if shift {
                     if let Some(idx) = state.selected.iter().position(|&id| id == clip_id) {
@@ -371,9 +805,9 @@ pub fn show_timeline(
         }
     }
 
-    // Keyboard shortcuts (when timeline is hovered)
+    // Keyboard shortcuts (when timeline is hovered or focused)
     let mut actions = Vec::new();
-    if response.hovered() {
+    if response.hovered() || focused {
         // Step size: 1 pixel worth of time, or 0.05s minimum
         let step = (1.0 / state.pixels_per_second).max(0.05);
         let big_step = step * 10.0; // Shift+arrow for larger jumps
@@ -445,13 +879,269 @@ pub fn show_timeline(
             actions.push(TimelineAction::ReverseSelected);
         }
 
+        // s — split the clip under the cursor
+        if !shift && !cmd && ui.input(|i| i.key_pressed(egui::Key::S)) {
+            actions.push(TimelineAction::SplitAtCursor);
+        }
+
         // Ctrl+A — select all
         if cmd && ui.input(|i| i.key_pressed(egui::Key::A)) {
             actions.push(TimelineAction::SelectAll);
         }
+
+        // Ctrl+C / Ctrl+X / Ctrl+V — arrive as clipboard events, not keys.
+        // Copying also puts text on the system clipboard, since egui only
+        // reports Ctrl+V while that is non-empty.
+        let (copy, cut, paste) = ui.input(|i| {
+            i.events.iter().fold((false, false, false), |(c, x, v), e| match e {
+                egui::Event::Copy => (true, x, v),
+                egui::Event::Cut => (c, true, v),
+                egui::Event::Paste(_) => (c, x, true),
+                _ => (c, x, v),
+            })
+        });
+        if (copy || cut) && !state.selected.is_empty() {
+            ui.ctx().copy_text(format!("{} Glottisdale clip(s)", state.selected.len()));
+            actions.push(if cut { TimelineAction::Cut } else { TimelineAction::Copy });
+        }
+        if paste {
+            actions.push(TimelineAction::Paste);
+        }
+
+        // Tab / Shift+Tab — select next / previous clip and move the cursor to it
+        if ui.input(|i| i.key_pressed(egui::Key::Tab)) {
+            if let Some(idx) = step_selection(arrangement, &state.selected, !shift) {
+                let tc = &arrangement.timeline[idx];
+                state.selected = vec![tc.id];
+                state.cursor_s = tc.position_s;
+                // Scroll the clip into view
+                let visible_s = rect.width() as f64 / state.pixels_per_second;
+                let clip_end = tc.position_s + tc.effective_duration_s;
+                if tc.position_s < state.scroll_offset_s || clip_end > state.scroll_offset_s + visible_s {
+                    state.scroll_offset_s = (tc.position_s - visible_s * 0.1).max(0.0);
+                }
+            }
+        }
+
+        // Enter — preview the selected clip
+        if ui.input(|i| i.key_pressed(egui::Key::Enter)) && !state.selected.is_empty() {
+            actions.push(TimelineAction::PreviewSelected);
+        }
+    }
+
+    (response, clip_drag, actions)
+}
+
+/// Envelope buckets across the minimap.
+const MINIMAP_BUCKETS: usize = 400;
+
+/// How close to an edge of the minimap viewport a drag resizes it (pixels).
+const MINIMAP_EDGE_PX: f32 = 5.0;
+
+/// Zoom limits of the timeline (pixels per second).
+const MIN_PIXELS_PER_SECOND: f64 = 10.0;
+const MAX_PIXELS_PER_SECOND: f64 = 5000.0;
+
+/// What a drag on the minimap does to the timeline viewport.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MinimapDrag {
+    /// Slide the viewport; the pointer holds it `grab_s` after its left edge.
+    Move { grab_s: f64 },
+    /// Move the left edge, zooming with the right edge fixed.
+    Left,
+    /// Move the right edge, zooming with the left edge fixed.
+    Right,
+}
+
+/// Key of what the minimap envelope was computed from.
+fn envelope_key(arrangement: &Arrangement) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for tc in &arrangement.timeline {
+        tc.source_clip_id.hash(&mut hasher);
+        tc.position_s.to_bits().hash(&mut hasher);
+        tc.effective_duration_s.to_bits().hash(&mut hasher);
+        tc.is_reversed().hash(&mut hasher);
+        tc.trim_start_s.to_bits().hash(&mut hasher);
+        tc.trim_end_s.to_bits().hash(&mut hasher);
+        arrangement.get_bank_clip(tc.source_clip_id).map(|c| c.samples.len()).hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+/// RMS level of the arrangement in `buckets` equal slices of its length.
+///
+/// Built from each clip's source audio stretched over its place on the
+/// timeline. Effects other than reversal are left out: the envelope is for
+/// finding your way around, not for metering.
+pub fn arrangement_envelope(arrangement: &Arrangement, buckets: usize) -> Vec<f32> {
+    let total_s = arrangement.total_duration_s();
+    if total_s <= 0.0 || buckets == 0 {
+        return Vec::new();
+    }
+    let bucket_s = total_s / buckets as f64;
+    let mut energy = vec![0.0f64; buckets];
+    for tc in &arrangement.timeline {
+        let Some(clip) = arrangement.get_bank_clip(tc.source_clip_id) else {
+            continue;
+        };
+        let samples = tc.trimmed_audio(clip);
+        let n = samples.len();
+        if n == 0 {
+            continue;
+        }
+        // Each source sample covers this many seconds of the timeline
+        let step_s = tc.effective_duration_s / n as f64;
+        let reversed = tc.is_reversed();
+        for (i, s) in samples.iter().enumerate() {
+            let i = if reversed { n - 1 - i } else { i };
+            let bucket = (((tc.position_s + i as f64 * step_s) / bucket_s) as usize).min(buckets - 1);
+            energy[bucket] += (s as f64).powi(2) * step_s;
+        }
+    }
+    energy.into_iter().map(|e| (e / bucket_s).sqrt() as f32).collect()
+}
+
+/// Seconds of the timeline the minimap spans: the arrangement, or further
+/// if the viewport reaches past its end.
+fn minimap_span_s(arrangement: &Arrangement, state: &TimelineState, visible_s: f64) -> f64 {
+    arrangement
+        .total_duration_s()
+        .max(state.scroll_offset_s + visible_s)
+        .max(1e-3)
+}
+
+/// Apply a minimap drag with the pointer at `time_s`, for a timeline
+/// `width_px` wide.
+pub fn apply_minimap_drag(state: &mut TimelineState, drag: MinimapDrag, time_s: f64, width_px: f32) {
+    let width = width_px.max(1.0) as f64;
+    let visible_s = width / state.pixels_per_second;
+    let zoom_to = |span_s: f64| (width / span_s.max(1e-6)).clamp(MIN_PIXELS_PER_SECOND, MAX_PIXELS_PER_SECOND);
+    match drag {
+        MinimapDrag::Move { grab_s } => {
+            state.scroll_offset_s = (time_s - grab_s).max(0.0);
+        }
+        MinimapDrag::Left => {
+            let right_s = state.scroll_offset_s + visible_s;
+            state.pixels_per_second = zoom_to(right_s - time_s.max(0.0));
+            state.scroll_offset_s = (right_s - width / state.pixels_per_second).max(0.0);
+        }
+        MinimapDrag::Right => {
+            state.pixels_per_second = zoom_to(time_s - state.scroll_offset_s);
+        }
+    }
+}
+
+/// Overview strip of the whole arrangement with a draggable rectangle
+/// showing the part the timeline shows.
+///
+/// Dragging the rectangle scrolls the timeline, dragging its edges zooms,
+/// and clicking elsewhere centers the view there.
+pub fn show_minimap(ui: &mut egui::Ui, arrangement: &Arrangement, state: &mut TimelineState) {
+    let width = ui.available_width();
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(width, MINIMAP_HEIGHT), egui::Sense::click_and_drag());
+    if !ui.is_rect_visible(rect) {
+        return;
+    }
+
+    let key = envelope_key(arrangement);
+    if state.minimap_envelope.as_ref().is_none_or(|(k, _)| *k != key) {
+        state.minimap_envelope = Some((key, arrangement_envelope(arrangement, MINIMAP_BUCKETS)));
+    }
+
+    let visible_s = width as f64 / state.pixels_per_second;
+    let span_s = minimap_span_s(arrangement, state, visible_s);
+    let to_x = |t: f64| rect.left() + (t / span_s) as f32 * rect.width();
+    let to_time = |x: f32| ((x - rect.left()) / rect.width()) as f64 * span_s;
+    let viewport = egui::Rect::from_x_y_ranges(
+        to_x(state.scroll_offset_s)..=to_x(state.scroll_offset_s + visible_s),
+        rect.y_range(),
+    );
+
+    // Interaction
+    let pointer = response.interact_pointer_pos().or(response.hover_pos());
+    let edge_at = |x: f32| {
+        if (x - viewport.left()).abs() <= MINIMAP_EDGE_PX {
+            Some(MinimapDrag::Left)
+        } else if (x - viewport.right()).abs() <= MINIMAP_EDGE_PX {
+            Some(MinimapDrag::Right)
+        } else {
+            None
+        }
+    };
+    if let Some(pos) = response.hover_pos() {
+        let icon = match edge_at(pos.x) {
+            Some(_) => egui::CursorIcon::ResizeHorizontal,
+            None if viewport.contains(pos) => egui::CursorIcon::Grab,
+            None => egui::CursorIcon::PointingHand,
+        };
+        ui.ctx().set_cursor_icon(icon);
+    }
+    if response.drag_started() {
+        if let Some(origin) = ui.input(|i| i.pointer.press_origin()) {
+            let time_s = to_time(origin.x);
+            state.minimap_drag = Some(match edge_at(origin.x) {
+                Some(edge) => edge,
+                None if viewport.contains(origin) => MinimapDrag::Move { grab_s: time_s - state.scroll_offset_s },
+                // Jump there, then keep dragging from the center
+                None => MinimapDrag::Move { grab_s: visible_s / 2.0 },
+            });
+        }
+    }
+    if let (Some(drag), Some(pos)) = (state.minimap_drag, pointer) {
+        if response.dragged() {
+            apply_minimap_drag(state, drag, to_time(pos.x), width);
+        }
+    }
+    if response.drag_stopped() {
+        state.minimap_drag = None;
+    }
+    if response.clicked() {
+        if let Some(pos) = pointer {
+            apply_minimap_drag(state, MinimapDrag::Move { grab_s: visible_s / 2.0 }, to_time(pos.x), width);
+        }
+    }
+
+    // Envelope, scaled so the loudest slice fills the strip
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(22));
+    if let Some((_, envelope)) = &state.minimap_envelope {
+        let loudest = envelope.iter().copied().fold(0.0f32, f32::max);
+        if loudest > 0.0 {
+            let total_s = arrangement.total_duration_s();
+            let bucket_s = total_s / envelope.len() as f64;
+            let half = rect.height() / 2.0 - 2.0;
+            for (i, &level) in envelope.iter().enumerate() {
+                let h = level / loudest * half;
+                let x0 = to_x(i as f64 * bucket_s);
+                let x1 = to_x((i + 1) as f64 * bucket_s).max(x0 + 1.0);
+                painter.rect_filled(
+                    egui::Rect::from_x_y_ranges(x0..=x1, rect.center().y - h..=rect.center().y + h),
+                    0.0,
+                    egui::Color32::from_gray(140),
+                );
+            }
+        }
     }
 
-    (response, reorder, actions)
+    // Dim what the timeline doesn't show, outline what it does
+    let shade = egui::Color32::from_black_alpha(110);
+    painter.rect_filled(egui::Rect::from_x_y_ranges(rect.left()..=viewport.left(), rect.y_range()), 0.0, shade);
+    painter.rect_filled(egui::Rect::from_x_y_ranges(viewport.right()..=rect.right(), rect.y_range()), 0.0, shade);
+    painter.rect_stroke(
+        viewport.intersect(rect),
+        2.0,
+        egui::Stroke::new(1.5, egui::Color32::from_rgb(100, 180, 255)),
+        egui::StrokeKind::Inside,
+    );
+
+    let cursor_x = to_x(state.cursor_s);
+    painter.line_segment(
+        [egui::pos2(cursor_x, rect.top()), egui::pos2(cursor_x, rect.bottom())],
+        egui::Stroke::new(1.0, egui::Color32::RED),
+    );
 }
 
 #[cfg(test)]
@@ -471,6 +1161,70 @@ mod tests {
         assert_eq!(actions[2], TimelineAction::SelectAll);
     }
 
+    #[test]
+    fn test_step_selection_walks_clips_without_wrapping() {
+        use glottisdale_core::editor::{EditorPipelineMode, SyllableClip, TimelineClip};
+
+        let samples = glottisdale_core::editor::SourceBuffer::from(vec![0.1f32; 1600]);
+        let clip = SyllableClip::from_range(&samples, 16000, "src.wav".into(), 0.0, 0.1, "a");
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        for _ in 0..3 {
+            arr.timeline.push(TimelineClip::new(&clip));
+        }
+        arr.bank.push(clip);
+        let ids: Vec<ClipId> = arr.timeline.iter().map(|tc| tc.id).collect();
+
+        assert_eq!(step_selection(&arr, &[], true), Some(0));
+        assert_eq!(step_selection(&arr, &[], false), Some(2));
+        assert_eq!(step_selection(&arr, &[ids[0]], true), Some(1));
+        assert_eq!(step_selection(&arr, &[ids[2]], true), Some(2));
+        assert_eq!(step_selection(&arr, &[ids[0]], false), Some(0));
+        assert_eq!(step_selection(&Arrangement::new(16000, EditorPipelineMode::Collage), &[], true), None);
+    }
+
+    #[test]
+    fn test_snap_prefers_clip_edges_then_grid() {
+        let mut snap = SnapSettings::default();
+        // Off by default: times pass through
+        assert_eq!(snap.snap(0.123, &[0.12], 200.0), 0.123);
+        snap.enabled = true;
+        // 8 px at 200 px/s = 40 ms magnet radius
+        assert_eq!(snap.snap(0.47, &[0.5], 200.0), 0.5);
+        assert!((snap.snap(0.43, &[0.5], 200.0) - 0.4).abs() < 1e-9);
+        snap.clip_edges = false;
+        assert!((snap.snap(0.47, &[0.5], 200.0) - 0.5).abs() < 1e-9);
+        snap.grid = SnapGrid::Off;
+        assert_eq!(snap.snap(0.47, &[0.5], 200.0), 0.47);
+    }
+
+    #[test]
+    fn test_beat_grid_needs_bpm() {
+        let mut snap = SnapSettings { enabled: true, grid: SnapGrid::Beat, clip_edges: false, bpm: None };
+        assert_eq!(snap.grid_step_s(), None);
+        assert_eq!(snap.snap(0.7, &[], 200.0), 0.7);
+        snap.bpm = Some(120.0);
+        assert_eq!(snap.grid_step_s(), Some(0.5));
+        assert_eq!(snap.snap(0.7, &[], 200.0), 0.5);
+    }
+
+    #[test]
+    fn test_insertion_index_skips_dragged_clip() {
+        use glottisdale_core::editor::{EditorPipelineMode, SyllableClip, TimelineClip};
+
+        let samples = glottisdale_core::editor::SourceBuffer::from(vec![0.1f32; 1600]);
+        let clip = SyllableClip::from_range(&samples, 16000, "src.wav".into(), 0.0, 0.1, "a");
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        for _ in 0..3 {
+            arr.timeline.push(TimelineClip::new(&clip));
+        }
+        arr.bank.push(clip);
+        arr.relayout(0.0);
+        // Clip edges at 0.0, 0.1, 0.2, 0.3
+        assert_eq!(insertion_index(&arr, 0, 0.1), 1);
+        assert_eq!(insertion_index(&arr, 2, 0.0), 0);
+        assert_eq!(insertion_index(&arr, 0, 0.3), 3);
+    }
+
     #[test]
     fn test_timeline_state_defaults() {
         let state = TimelineState::default();
@@ -480,6 +1234,7 @@ mod tests {
         assert!(state.selected.is_empty());
         assert!(state.drag.is_none());
         assert!(!state.dragging_cursor);
+        assert!(state.loop_region.is_none());
     }
 
     #[test]
@@ -500,6 +1255,78 @@ mod tests {
         assert_eq!(state.time_to_px(3.0), 200.0);
     }
 
+    #[test]
+    fn test_envelope_follows_clip_placement() {
+        use glottisdale_core::editor::{EditorPipelineMode, SyllableClip};
+
+        let samples = glottisdale_core::editor::SourceBuffer::from(vec![0.5f32; 1600]);
+        let clip = SyllableClip::from_range(&samples, 16000, "src.wav".into(), 0.0, 0.1, "a");
+        let mut arr = Arrangement::new(16000, EditorPipelineMode::Collage);
+        arr.layout_mode = LayoutMode::Free;
+        let mut late = TimelineClip::new(&clip);
+        late.position_s = 0.3;
+        arr.timeline.push(TimelineClip::new(&clip));
+        arr.timeline.push(late);
+        arr.bank.push(clip);
+
+        // 0.4 s in four slices: clip, gap, gap, clip
+        let envelope = arrangement_envelope(&arr, 4);
+        assert_eq!(envelope.len(), 4);
+        // Just under 0.5: the cut clip has short fades
+        assert!(envelope[0] > 0.45 && envelope[0] <= 0.5, "{:?}", envelope);
+        assert_eq!(envelope[0], envelope[3]);
+        assert_eq!(&envelope[1..3], [0.0, 0.0]);
+        assert!(arrangement_envelope(&Arrangement::new(16000, EditorPipelineMode::Collage), 4).is_empty());
+    }
+
+    #[test]
+    fn test_dragged_trims() {
+        use glottisdale_core::editor::{ClipEffect, SyllableClip};
+
+        let samples = glottisdale_core::editor::SourceBuffer::from(vec![0.5f32; 16000]);
+        let clip = SyllableClip::from_range(&samples, 16000, "src.wav".into(), 0.0, 0.5, "a");
+        let mut tc = TimelineClip::new(&clip);
+        tc.position_s = 2.0;
+
+        // Dragging the left edge right trims the start and moves the clip's start
+        let (trims, span) = dragged_trims(&tc, 0.5, TrimEdge::Left, 0.1);
+        assert!((trims.0 - 0.1).abs() < 1e-9 && trims.1 == 0.0);
+        assert!((span.0 - 2.1).abs() < 1e-9 && (span.1 - 2.5).abs() < 1e-9);
+
+        // Stretched 2x, the same drag trims half as much; reversed, the
+        // left edge is the clip's end
+        tc.effects = vec![ClipEffect::TimeStretch { factor: 2.0 }, ClipEffect::Reverse];
+        tc.update_duration(&clip);
+        let (trims, _) = dragged_trims(&tc, 0.5, TrimEdge::Left, 0.1);
+        assert!(trims.0 == 0.0 && (trims.1 - 0.05).abs() < 1e-9);
+
+        // Dragging past the other edge keeps a sliver; outward stops at the clip's ends
+        let (trims, _) = dragged_trims(&tc, 0.5, TrimEdge::Right, -5.0);
+        assert!((0.5 - trims.0 - MIN_TRIMMED_S).abs() < 1e-9);
+        let (trims, _) = dragged_trims(&tc, 0.5, TrimEdge::Right, 5.0);
+        assert_eq!(trims, (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_minimap_drags_scroll_and_zoom() {
+        // 800 px at 200 px/s shows 4 s
+        let mut state = TimelineState::default();
+        apply_minimap_drag(&mut state, MinimapDrag::Move { grab_s: 1.0 }, 3.0, 800.0);
+        assert_eq!(state.scroll_offset_s, 2.0);
+        apply_minimap_drag(&mut state, MinimapDrag::Move { grab_s: 1.0 }, 0.5, 800.0);
+        assert_eq!(state.scroll_offset_s, 0.0);
+
+        // Widening to 8 s from the right edge halves the zoom
+        apply_minimap_drag(&mut state, MinimapDrag::Right, 8.0, 800.0);
+        assert_eq!(state.pixels_per_second, 100.0);
+        assert_eq!(state.scroll_offset_s, 0.0);
+
+        // Narrowing from the left keeps the right edge at 8 s
+        apply_minimap_drag(&mut state, MinimapDrag::Left, 6.0, 800.0);
+        assert_eq!(state.pixels_per_second, 400.0);
+        assert!((state.scroll_offset_s - 6.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_is_selected() {
         let mut state = TimelineState::default();
@@ -510,6 +1337,66 @@ mod tests {
     }
 }
 
+/// Shade the loop region on the ruler and track, with in/out lines.
+fn paint_loop_region(painter: &egui::Painter, ruler_rect: egui::Rect, rect: egui::Rect, state: &TimelineState) {
+    let Some(region) = state.loop_region else {
+        return;
+    };
+    let left = state.time_to_px(region.start_s) + rect.left();
+    let right = state.time_to_px(region.end_s) + rect.left();
+    if right < rect.left() || left > rect.right() {
+        return;
+    }
+    let color = egui::Color32::from_rgb(240, 190, 60);
+    let span = |r: egui::Rect| egui::Rect::from_x_y_ranges(left.max(r.left())..=right.min(r.right()), r.y_range());
+    painter.rect_filled(span(ruler_rect), 0.0, color.gamma_multiply(0.6));
+    painter.rect_filled(span(rect), 0.0, color.gamma_multiply(0.08));
+    for x in [left, right] {
+        if x >= rect.left() && x <= rect.right() {
+            painter.line_segment(
+                [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+                egui::Stroke::new(1.0, color),
+            );
+        }
+    }
+}
+
+/// Paint melody notes as bars whose height follows pitch, highlighting
+/// notes sung by a selected clip.
+fn paint_notes_lane(painter: &egui::Painter, rect: egui::Rect, melody: &[MelodyNote], state: &TimelineState) {
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(38));
+    let lowest = melody.iter().map(|n| n.pitch).min().unwrap_or(0);
+    let highest = melody.iter().map(|n| n.pitch).max().unwrap_or(0);
+    let span = (highest - lowest).max(1) as f32;
+    let bar_height = 6.0;
+    let font = egui::FontId::proportional(9.0);
+
+    for note in melody {
+        let left = state.time_to_px(note.start_s) + rect.left();
+        let right = state.time_to_px(note.end_s) + rect.left();
+        if right < rect.left() || left > rect.right() {
+            continue;
+        }
+        let rise = (note.pitch - lowest) as f32 / span * (rect.height() - bar_height - 4.0);
+        let top = rect.bottom() - 2.0 - bar_height - rise;
+        let bar = egui::Rect::from_min_max(egui::pos2(left, top), egui::pos2(right.max(left + 2.0), top + bar_height));
+        let selected = note.clip_ids.iter().any(|id| state.is_selected(*id));
+        let color = if selected {
+            egui::Color32::from_rgb(255, 200, 90)
+        } else {
+            egui::Color32::from_rgb(140, 170, 220)
+        };
+        painter.rect_filled(bar, 1.0, color);
+
+        let label = note_name(note.pitch);
+        let galley = painter.layout_no_wrap(label, font.clone(), color);
+        if galley.size().x + 2.0 < right - left {
+            let y = (top - galley.size().y).max(rect.top());
+            painter.galley(egui::pos2(left + 1.0, y), galley, color);
+        }
+    }
+}
+
 /// Paint time markers along the top of the timeline.
 fn paint_time_ruler(painter: &egui::Painter, rect: egui::Rect, state: &TimelineState) {
     let font = egui::FontId::proportional(9.0);
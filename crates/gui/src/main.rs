@@ -1,16 +1,17 @@
 //! Glottisdale GUI — egui-based interface for syllable-level audio processing.
 
 mod app;
+mod crash;
 mod editor;
+mod notify;
 
 use std::sync::Arc;
 
 use eframe::egui;
 
 fn main() -> eframe::Result<()> {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
-        .format_timestamp(None)
-        .init();
+    crash::init_logging();
+    crash::install_panic_hook();
 
     // Load and decode the app icon
     let icon_bytes = include_bytes!("../assets/icon.jpg");
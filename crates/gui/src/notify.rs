@@ -0,0 +1,134 @@
+//! Toasts (and optional OS notifications) for background runs that finish
+//! while the user is looking at something else.
+
+use std::path::PathBuf;
+
+use eframe::egui;
+
+/// How long a successful run's toast stays up (seconds). Failures stay
+/// until dismissed.
+const RUN_TOAST_S: f64 = 20.0;
+
+/// A finished run, waiting to be acknowledged.
+#[derive(Debug, Clone)]
+pub struct RunToast {
+    /// Pipeline that ran, e.g. "Collage".
+    pub pipeline: String,
+    pub message: String,
+    pub ok: bool,
+    /// Main output file, if the run wrote one.
+    pub output: Option<PathBuf>,
+    /// Whether the run left alignment data to open in the editor.
+    pub editable: bool,
+    /// egui time the toast appeared.
+    pub shown_at: f64,
+}
+
+/// Quick action picked from a run toast.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToastAction {
+    /// Play the output file.
+    Play(PathBuf),
+    /// Open the run directory.
+    Open(PathBuf),
+    /// Open the run's arrangement in the editor.
+    Edit,
+}
+
+/// Show the run toast, if any, in the bottom-right corner.
+///
+/// `can_edit` is false while an editor with unsaved changes is open.
+/// Clears the toast when it expires or an action is picked.
+pub fn show_run_toast(ctx: &egui::Context, toast: &mut Option<RunToast>, can_edit: bool) -> Option<ToastAction> {
+    let current = toast.as_ref()?;
+    let now = ctx.input(|i| i.time);
+    if current.ok && now - current.shown_at > RUN_TOAST_S {
+        *toast = None;
+        return None;
+    }
+
+    let mut action = None;
+    let mut dismiss = false;
+    egui::Area::new(egui::Id::new("run_toast"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -40.0))
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let (mark, color) = if current.ok {
+                        ("✔", egui::Color32::GREEN)
+                    } else {
+                        ("✖", egui::Color32::RED)
+                    };
+                    ui.colored_label(color, mark);
+                    ui.label(format!("{} run: {}", current.pipeline, current.message));
+                    if let Some(output) = &current.output {
+                        if ui.button("Play").clicked() {
+                            action = Some(ToastAction::Play(output.clone()));
+                        }
+                        if let Some(dir) = output.parent() {
+                            if ui.button("Open").on_hover_text("Open the run folder").clicked() {
+                                action = Some(ToastAction::Open(dir.to_path_buf()));
+                            }
+                        }
+                    }
+                    if current.editable
+                        && ui
+                            .add_enabled(can_edit, egui::Button::new("Edit"))
+                            .on_hover_text("Open the arrangement in the editor")
+                            .on_disabled_hover_text("Save or close the open arrangement first")
+                            .clicked()
+                    {
+                        action = Some(ToastAction::Edit);
+                    }
+                    if ui.small_button("x").clicked() {
+                        dismiss = true;
+                    }
+                });
+            });
+        });
+    if current.ok {
+        ctx.request_repaint_after(std::time::Duration::from_secs_f64(RUN_TOAST_S));
+    }
+    if action.is_some() || dismiss {
+        *toast = None;
+    }
+    action
+}
+
+/// Post a desktop notification, best effort.
+pub fn notify_os(title: &str, body: &str) {
+    #[cfg(target_os = "macos")]
+    {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(body),
+            applescript_string(title)
+        );
+        std::process::Command::new("osascript").args(["-e", &script]).spawn().ok();
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::process::Command::new("notify-send").args([title, body]).spawn().ok();
+    }
+    #[cfg(target_os = "windows")]
+    {
+        // No notification API without extra dependencies; the toast suffices
+        let _ = (title, body);
+    }
+}
+
+/// Quote `s` as an AppleScript string literal.
+#[cfg(any(target_os = "macos", test))]
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_applescript_string_escapes_quotes() {
+        assert_eq!(applescript_string(r#"say "hi" \ bye"#), r#""say \"hi\" \\ bye""#);
+    }
+}
@@ -0,0 +1,20 @@
+//! Decode arbitrary bytes as media the way `extract_audio` does.
+//!
+//! The first byte picks the format hint so every symphonia demuxer is
+//! reached, not only the ones the prober recognizes.
+
+#![no_main]
+
+use glottisdale_core::audio::io::{decode_audio_bytes, Channels};
+use libfuzzer_sys::fuzz_target;
+
+const HINTS: &[Option<&str>] = &[None, Some("wav"), Some("mp3"), Some("flac"), Some("mp4"), Some("m4a")];
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&selector, media)) = data.split_first() else {
+        return;
+    };
+    let hint = HINTS[selector as usize % HINTS.len()];
+    let layout = if selector & 0x80 != 0 { Channels::Stereo } else { Channels::Mono };
+    let _ = decode_audio_bytes(media.to_vec(), hint, layout);
+});
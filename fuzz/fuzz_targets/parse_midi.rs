@@ -0,0 +1,14 @@
+//! Parse arbitrary bytes as a MIDI file.
+
+#![no_main]
+
+use glottisdale_core::sing::midi_parser::parse_midi_bytes;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(track) = parse_midi_bytes(data) {
+        // Downstream code sorts and divides by these
+        assert!(track.total_duration.is_finite());
+        assert!(track.notes.iter().all(|n| n.start.is_finite() && n.end >= n.start));
+    }
+});